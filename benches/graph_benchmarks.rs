@@ -1,6 +1,7 @@
 use criterion::{criterion_group, criterion_main, Criterion};
 use serde_json::json;
-use std::sync::{Arc, RwLock};
+use std::sync::Arc;
+use tokio::sync::RwLock;
 
 use codegraph::config::Config;
 use codegraph::session::SessionManager;
@@ -137,7 +138,7 @@ fn bench_smart_context(c: &mut Criterion) {
                     codegraph_dir: temp_path.join(".codegraph"),
                     store_db_path: temp_path.join("cache/store.db"),
                     learning_db_path: temp_path.join(".codegraph/learning.db"),
-                    settings: codegraph::config::ConfigFile::default(),
+                    settings: codegraph::config::ConfigFile::default().into(),
                 };
                 config.ensure_dirs().unwrap();
 
@@ -146,7 +147,7 @@ fn bench_smart_context(c: &mut Criterion) {
                 let manager = SessionManager::new(store, graph);
 
                 manager
-                    .start_session("Test task", &["Item 1".to_string()])
+                    .start_session(None, "Test task", &["Item 1".to_string()])
                     .await
                     .unwrap();
 
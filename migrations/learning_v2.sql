@@ -0,0 +1,4 @@
+-- Learning Database V2: Scoped manual instructions
+-- Adds an optional JSON Scope to manual instructions so they can be limited to paths/tags
+
+ALTER TABLE instructions ADD COLUMN scope TEXT;  -- JSON Scope object (NULL = applies everywhere)
@@ -0,0 +1,8 @@
+-- Learning Database V3: Instruction priorities
+-- Pins patterns, failures, or manual instructions so they render first and bypass thresholds
+
+CREATE TABLE IF NOT EXISTS instruction_priorities (
+    id TEXT PRIMARY KEY,          -- Pattern, failure, or manual instruction ID
+    priority TEXT NOT NULL,       -- high, critical
+    updated_at INTEGER NOT NULL
+);
@@ -0,0 +1,4 @@
+-- Learning Database V4: OpenAPI metadata on inferred connections
+-- Connections whose route is in the project's OpenAPI spec carry its operationId, summary, and tags
+
+ALTER TABLE cross_language_edges ADD COLUMN metadata TEXT;  -- JSON SpecOperation (NULL = not in a spec)
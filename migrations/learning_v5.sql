@@ -0,0 +1,12 @@
+-- Learning Database V5: Reviewed cross-language connections
+-- Confirmed connections keep full confidence across rebuilds; rejected ones are never re-inferred
+
+CREATE TABLE IF NOT EXISTS cross_language_reviews (
+    client_file TEXT NOT NULL,
+    server_file TEXT NOT NULL,
+    api_path TEXT NOT NULL,
+    verdict TEXT NOT NULL,             -- confirmed, rejected
+    reviewed_at INTEGER NOT NULL,
+
+    PRIMARY KEY (client_file, server_file, api_path)
+);
@@ -0,0 +1,21 @@
+-- Learning Database V6: Embeddings for semantic recall
+-- Vectors for pattern and failure text, cached per model; a changed text hash means re-embed
+
+CREATE TABLE IF NOT EXISTS embeddings (
+    kind TEXT NOT NULL,                -- pattern, failure
+    id TEXT NOT NULL,                  -- Pattern or failure ID
+    model TEXT NOT NULL,               -- Embedder that produced the vector
+    text_hash TEXT NOT NULL,           -- xxh3 of the embedded text
+    vector BLOB NOT NULL,              -- Little-endian f32s
+    created_at INTEGER DEFAULT (strftime('%s', 'now')),
+
+    PRIMARY KEY (kind, id, model)
+);
+
+CREATE TRIGGER IF NOT EXISTS patterns_embeddings_delete AFTER DELETE ON patterns BEGIN
+    DELETE FROM embeddings WHERE kind = 'pattern' AND id = old.id;
+END;
+
+CREATE TRIGGER IF NOT EXISTS failures_embeddings_delete AFTER DELETE ON failures BEGIN
+    DELETE FROM embeddings WHERE kind = 'failure' AND id = old.id;
+END;
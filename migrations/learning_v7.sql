@@ -0,0 +1,14 @@
+-- Learning Database V7: Pattern merge lineage
+-- Near-duplicate patterns folded into another by consolidate_patterns, so merges stay traceable
+
+CREATE TABLE IF NOT EXISTS pattern_merges (
+    merged_id TEXT PRIMARY KEY,        -- ID of the pattern that was folded in (now deleted)
+    into_id TEXT NOT NULL,             -- Pattern it was merged into
+    intent TEXT NOT NULL,              -- Intent of the merged pattern
+    usage_count INTEGER NOT NULL,      -- Usage carried over to into_id
+    success_count INTEGER NOT NULL,    -- Successes carried over to into_id
+    similarity REAL NOT NULL,          -- Similarity to into_id when merged (0.0-1.0)
+    merged_at INTEGER NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_pattern_merges_into ON pattern_merges(into_id);
@@ -0,0 +1,11 @@
+-- Learning Database V8: Failure merge lineage
+-- Failures folded into another when merging learning databases, so merging again doesn't count them twice
+
+CREATE TABLE IF NOT EXISTS failure_merges (
+    merged_id TEXT PRIMARY KEY,        -- ID of the failure in the merged database
+    into_id TEXT NOT NULL,             -- Failure it was folded into
+    times_prevented INTEGER NOT NULL,  -- Prevention count carried over to into_id so far
+    merged_at INTEGER NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_failure_merges_into ON failure_merges(into_id);
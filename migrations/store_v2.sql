@@ -0,0 +1,36 @@
+-- Store Database V2: Full-text search over symbols
+-- symbol_text holds the searchable text of each indexed symbol; symbol_fts indexes it
+-- (external content) and is kept in sync by the triggers below
+
+CREATE TABLE IF NOT EXISTS symbol_text (
+    rowid INTEGER PRIMARY KEY,
+    id TEXT NOT NULL,                  -- Node ID of the symbol
+    file TEXT NOT NULL,                -- Relative file path
+    kind TEXT NOT NULL,                -- Symbol kind (function, class, ...)
+    line INTEGER NOT NULL,             -- First line of the symbol
+    name TEXT NOT NULL,
+    signature TEXT NOT NULL DEFAULT '',
+    docstring TEXT NOT NULL DEFAULT '',
+    body TEXT NOT NULL DEFAULT ''
+);
+
+CREATE INDEX IF NOT EXISTS idx_symbol_text_file ON symbol_text(file);
+
+CREATE VIRTUAL TABLE IF NOT EXISTS symbol_fts USING fts5(
+    name,
+    signature,
+    docstring,
+    body,
+    content = 'symbol_text',
+    content_rowid = 'rowid'
+);
+
+CREATE TRIGGER IF NOT EXISTS symbol_text_insert AFTER INSERT ON symbol_text BEGIN
+    INSERT INTO symbol_fts (rowid, name, signature, docstring, body)
+    VALUES (new.rowid, new.name, new.signature, new.docstring, new.body);
+END;
+
+CREATE TRIGGER IF NOT EXISTS symbol_text_delete AFTER DELETE ON symbol_text BEGIN
+    INSERT INTO symbol_fts (symbol_fts, rowid, name, signature, docstring, body)
+    VALUES ('delete', old.rowid, old.name, old.signature, old.docstring, old.body);
+END;
@@ -0,0 +1,354 @@
+//! Terminal subcommands (`codegraph index`, `codegraph search Foo`, ...).
+//!
+//! Each command opens the project like the MCP server does and runs the matching tool, so
+//! the output is the same an agent would see. `check` is meant for CI: it prints a JSON
+//! report and exits nonzero when any check fails.
+
+use anyhow::{bail, Result};
+use clap::{Parser, Subcommand, ValueEnum};
+use serde::Serialize;
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::net::IpAddr;
+use std::path::PathBuf;
+use std::process::ExitCode;
+
+use crate::code::checks::{self, DeadSymbol};
+use crate::code::indexer::IndexStats;
+use crate::mcp::protocol::Content;
+use crate::mcp::tools::{ToolContext, ToolRegistry};
+use crate::mcp::Server;
+use crate::store::db::{Edge, StoreStats};
+use crate::store::graph::{ExportFilter, ExportFormat};
+use crate::store::CodeGraph;
+
+#[derive(Debug, Parser)]
+#[command(name = "codegraph", version, about = "Code graph and project memory for coding agents")]
+pub struct Cli {
+    /// Project root (defaults to the current directory)
+    #[arg(long, global = true)]
+    pub root: Option<PathBuf>,
+
+    /// How the MCP server talks to clients
+    #[arg(long, value_enum, global = true, default_value_t = Transport::Stdio)]
+    pub transport: Transport,
+
+    /// Port to listen on with --transport http
+    #[arg(long, global = true, default_value_t = 8787)]
+    pub port: u16,
+
+    /// Address to listen on with --transport http; 0.0.0.0 accepts remote clients, which
+    /// requires --token
+    #[arg(long, global = true, default_value = "127.0.0.1")]
+    pub host: IpAddr,
+
+    /// Bearer token HTTP clients must send (`Authorization: Bearer <token>`)
+    #[arg(long, global = true, env = "CODEGRAPH_TOKEN", hide_env_values = true)]
+    pub token: Option<String>,
+
+    /// Without a command, runs the MCP server
+    #[command(subcommand)]
+    pub command: Option<Command>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum Transport {
+    /// Newline-delimited JSON-RPC on stdin/stdout
+    Stdio,
+    /// Streamable HTTP on /mcp
+    Http,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum Command {
+    /// Run the MCP server (on stdio unless --transport http)
+    Serve,
+    /// Index the project (incrementally, unless --full)
+    Index {
+        /// Rebuild the whole index
+        #[arg(long)]
+        full: bool,
+        /// Report time per phase and the slowest files
+        #[arg(long)]
+        profile: bool,
+    },
+    /// Find symbols by name
+    Search {
+        /// Symbol name (partial match)
+        query: String,
+        /// Only symbols of this kind (function, class, struct, ...)
+        #[arg(long)]
+        kind: Option<String>,
+        /// Only files whose path contains this
+        #[arg(long)]
+        file: Option<String>,
+        #[arg(long, default_value_t = 20)]
+        limit: usize,
+    },
+    /// Show index and learning statistics
+    Stats,
+    /// Write the code graph's nodes and edges to stdout or a file
+    Export {
+        /// json, dot (Graphviz), or graphml (Gephi, yEd)
+        #[arg(long, default_value = "json")]
+        format: ExportFormat,
+        /// Write here instead of stdout
+        #[arg(long, short)]
+        output: Option<PathBuf>,
+        /// Only symbols in files matching this glob (e.g. "src/api/**")
+        #[arg(long)]
+        path: Option<String>,
+        /// Only nodes of these kinds (comma-separated)
+        #[arg(long, value_delimiter = ',')]
+        kind: Vec<String>,
+        /// Only edges of these kinds (comma-separated, e.g. calls,imports)
+        #[arg(long, value_delimiter = ',')]
+        edge_type: Vec<String>,
+        /// Keep `unresolved::` stubs for references to symbols outside the project
+        #[arg(long)]
+        include_unresolved: bool,
+    },
+    /// Generate SKILL.md from patterns, failures, and conventions
+    Skill {
+        /// Print the skill instead of writing .codegraph/SKILL.md
+        #[arg(long)]
+        print: bool,
+        /// Min confidence for patterns
+        #[arg(long)]
+        confidence_threshold: Option<f64>,
+    },
+    /// Diagnose installation problems (config, databases, grammars, permissions, git); exits 1 on errors
+    Doctor,
+    /// Index, then check graph integrity, cycles, and dead code; prints JSON, exits 1 on violations
+    Check {
+        /// Rebuild the whole index first
+        #[arg(long)]
+        full: bool,
+        /// Also fail when .codegraph/SKILL.md is out of date
+        #[arg(long)]
+        skill: bool,
+        /// Don't fail on file dependency cycles
+        #[arg(long)]
+        no_cycles: bool,
+        /// Don't fail on unreferenced functions
+        #[arg(long)]
+        no_dead_code: bool,
+    },
+}
+
+/// Machine-readable result of `codegraph check`. Skipped checks are `null`.
+#[derive(Debug, Serialize)]
+struct CheckReport {
+    passed: bool,
+    violations: usize,
+    index: IndexStats,
+    stats: StoreStats,
+    integrity: Integrity,
+    cycles: Option<Vec<Vec<String>>>,
+    dead_code: Option<Vec<DeadSymbol>>,
+    skill: Option<SkillStatus>,
+}
+
+#[derive(Debug, Serialize)]
+struct Integrity {
+    dangling_edges: Vec<Edge>,
+    orphaned_nodes: Vec<String>,
+}
+
+#[derive(Debug, Serialize)]
+struct SkillStatus {
+    up_to_date: bool,
+    /// Generated files that are missing or differ from what would be generated now
+    stale_files: Vec<String>,
+}
+
+/// Run a subcommand against the project at `root`
+pub async fn run(command: Command, root: Option<PathBuf>) -> Result<ExitCode> {
+    let root = match root {
+        Some(root) => root,
+        None => std::env::current_dir()?,
+    };
+    // Runs without opening the project the usual way, since that may be what's broken
+    if let Command::Doctor = command {
+        let results = crate::doctor::diagnose(&root, None).await;
+        println!("{}", crate::doctor::format_report(&results));
+        let failed = results.iter().any(|r| r.status == crate::doctor::Status::Error);
+        return Ok(if failed { ExitCode::FAILURE } else { ExitCode::SUCCESS });
+    }
+    let registry = Server::init_dependencies(&root).await.map_err(anyhow::Error::msg)?;
+
+    match command {
+        // Handled by main and above
+        Command::Serve | Command::Doctor => {}
+        Command::Index { full, profile } => {
+            let tool = if profile { "index_profile" } else { "index_project" };
+            print_tool(&registry, tool, json!({ "full": full })).await?
+        }
+        Command::Search { query, kind, file, limit } => {
+            let mut args = json!({ "query": query, "limit": limit, "compact": false });
+            if let Some(kind) = kind {
+                args["kind"] = json!(kind);
+            }
+            if let Some(file) = file {
+                args["file_pattern"] = json!(file);
+            }
+            print_tool(&registry, "search_symbols", args).await?
+        }
+        Command::Stats => {
+            let ctx = registry.context();
+            let stats = ctx.store.stats().await?;
+            println!("Project: {}", ctx.config.project_root.display());
+            println!("Files indexed: {}", stats.files);
+            println!("Symbols: {}", stats.symbols);
+            println!("Edges: {}", stats.edges);
+            println!("Cross-language connections: {}", stats.connections);
+            println!("Patterns: {}", stats.patterns);
+            println!("Failures: {}", stats.failures);
+        }
+        Command::Export { format, output, path, kind, edge_type, include_unresolved } => {
+            let filter = ExportFilter {
+                path: path.as_deref().map(glob::Pattern::new).transpose()?,
+                kinds: kind,
+                edge_kinds: edge_type,
+                include_unresolved,
+            };
+            let ctx = registry.context();
+            let export = CodeGraph::read_ranked(&ctx.graph).await.export(&ctx.store, format, &filter).await?;
+            match output {
+                Some(path) => {
+                    std::fs::write(&path, export.text)?;
+                    eprintln!("Exported {} nodes and {} edges to {}", export.nodes, export.edges, path.display());
+                }
+                None => print!("{}", export.text),
+            }
+        }
+        Command::Skill { print, confidence_threshold } => {
+            let mut args = json!({ "write_file": !print });
+            if let Some(threshold) = confidence_threshold {
+                args["confidence_threshold"] = json!(threshold);
+            }
+            print_tool(&registry, "distill_project_skill", args).await?
+        }
+        Command::Check { full, skill, no_cycles, no_dead_code } => {
+            let report = check(registry.context(), full, skill, !no_cycles, !no_dead_code).await?;
+            println!("{}", serde_json::to_string_pretty(&report)?);
+            if !report.passed {
+                return Ok(ExitCode::FAILURE);
+            }
+        }
+    }
+    Ok(ExitCode::SUCCESS)
+}
+
+async fn check(ctx: &ToolContext, full: bool, skill: bool, cycles: bool, dead_code: bool) -> Result<CheckReport> {
+    let index = if full {
+        ctx.indexer.index_full().await?
+    } else {
+        ctx.indexer.index_incremental().await?
+    };
+
+    let integrity = Integrity {
+        dangling_edges: ctx.store.dangling_edges().await?,
+        orphaned_nodes: ctx.store.orphaned_nodes().await?,
+    };
+
+    let mut sources = HashMap::new();
+    if cycles || dead_code {
+        for file in ctx.store.list_indexed_files().await? {
+            // Unreadable files were already reported by the indexer
+            if let Ok(source) = std::fs::read_to_string(ctx.config.project_root.join(&file)) {
+                sources.insert(file, source);
+            }
+        }
+    }
+
+    let settings = ctx.config.settings();
+    let loaded = if full { Some(CodeGraph::load(&ctx.store, &settings.graph).await?) } else { None };
+    {
+        let mut graph = ctx.graph.write().await;
+        match loaded {
+            Some(loaded) => *graph = loaded,
+            None => graph.apply(&index.changes),
+        }
+    }
+    let (cycles, dead_code) = {
+        let graph = ctx.graph.read().await;
+        let cycles = match cycles {
+            true => Some(checks::find_cycles(&graph, &ctx.store, &sources).await?),
+            false => None,
+        };
+        let dead_code = match dead_code {
+            true => Some(checks::find_dead_code(&graph, &ctx.store, &sources).await?),
+            false => None,
+        };
+        (cycles, dead_code)
+    };
+
+    let skill = if skill { Some(skill_status(ctx).await?) } else { None };
+
+    let violations = integrity.dangling_edges.len()
+        + integrity.orphaned_nodes.len()
+        + cycles.as_ref().map_or(0, Vec::len)
+        + dead_code.as_ref().map_or(0, Vec::len)
+        + skill.as_ref().map_or(0, |s| s.stale_files.len());
+
+    Ok(CheckReport {
+        passed: violations == 0,
+        violations,
+        index,
+        stats: ctx.store.stats().await?,
+        integrity,
+        cycles,
+        dead_code,
+        skill,
+    })
+}
+
+/// Compare the skill files on disk with what `distill_project_skill` would write now
+async fn skill_status(ctx: &ToolContext) -> Result<SkillStatus> {
+    let settings = ctx.config.settings();
+    let result = crate::skill::distill::distill_project_skill(
+        &ctx.pattern_store,
+        &ctx.failure_store,
+        &ctx.manual_instruction_store,
+        &ctx.store,
+        &ctx.graph,
+        settings.learning.distill_confidence_threshold,
+        &crate::skill::distill::SkillScope::default(),
+    )
+    .await?;
+    let files =
+        crate::skill::render::render_skill_files(&result, &ctx.config.codegraph_dir, &settings.skill.split_files)?;
+
+    let stale_files: Vec<String> = files
+        .into_iter()
+        .filter(|file| {
+            std::fs::read_to_string(ctx.config.codegraph_dir.join(&file.name)).ok().as_deref()
+                != Some(file.content.as_str())
+        })
+        .map(|file| file.name)
+        .collect();
+
+    Ok(SkillStatus {
+        up_to_date: stale_files.is_empty(),
+        stale_files,
+    })
+}
+
+/// Run a tool and print its output, failing when the tool reports an error
+async fn print_tool(registry: &ToolRegistry, name: &str, args: Value) -> Result<()> {
+    let result = registry.execute(name, args).await?;
+    let text: Vec<&str> = result
+        .content
+        .iter()
+        .map(|content| match content {
+            Content::Text { text } => text.as_str(),
+        })
+        .collect();
+    if result.is_error == Some(true) {
+        bail!("{}", text.join("\n"));
+    }
+    println!("{}", text.join("\n"));
+    Ok(())
+}
+
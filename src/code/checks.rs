@@ -0,0 +1,324 @@
+//! Graph health checks for CI: file dependency cycles and unreferenced functions.
+
+use anyhow::Result;
+use once_cell::sync::Lazy;
+use regex::Regex;
+use serde::Serialize;
+use std::collections::{HashMap, HashSet};
+
+use crate::store::db::Edge;
+use crate::store::graph::{Cycle, NodeData};
+use crate::store::{CodeGraph, Granularity, Store};
+
+static IDENTIFIER: Lazy<Regex> = Lazy::new(|| Regex::new(r"[A-Za-z_][A-Za-z0-9_]*").unwrap());
+
+/// Edge kinds produced by the parser; inferred edges (calls_api, topics, ...) don't count as dependencies
+pub const DEPENDENCY_EDGES: &[&str] = &["calls", "imports", "inherits", "implements", "uses_type"];
+
+/// A function or method nothing in the project refers to
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct DeadSymbol {
+    pub id: String,
+    pub name: String,
+    pub file: String,
+    pub line: u64,
+}
+
+fn str_field<'a>(data: &'a serde_json::Value, key: &str) -> Option<&'a str> {
+    data.get(key).and_then(|v| v.as_str())
+}
+
+/// Cycles between files or modules along `edge_kinds`, leaving out edges that were likely
+/// linked to the wrong definition. `source_line` returns a 1-based line of a project file and
+/// is used to check call sites.
+pub async fn dependency_cycles(
+    graph: &CodeGraph,
+    store: &Store,
+    edge_kinds: &[&str],
+    granularity: Granularity,
+    mut source_line: impl FnMut(&str, u64) -> Option<String>,
+) -> Result<Vec<Cycle>> {
+    // Cross-file references are linked by name, so only trust names defined once
+    let mut definitions: HashMap<&str, usize> = HashMap::new();
+    for node in graph.graph.node_weights() {
+        if node.data.get("file").is_some() {
+            if let Some(name) = str_field(&node.data, "name") {
+                *definitions.entry(name).or_default() += 1;
+            }
+        }
+    }
+
+    // A slim graph leaves edge data in the store. Leaving edges out can't create a cycle, so
+    // only edges on a cycle of the unfiltered graph need theirs.
+    let mut stored: HashMap<String, Vec<Edge>> = HashMap::new();
+    if graph.is_slim() {
+        let sources: HashSet<String> = graph
+            .cycles(edge_kinds, granularity, |_, _, _| true)
+            .into_iter()
+            .flat_map(|cycle| cycle.edges)
+            .map(|edge| edge.source)
+            .collect();
+        for source in sources {
+            let edges = store.get_edges_from(&source).await?;
+            stored.insert(source, edges);
+        }
+    }
+
+    Ok(graph.cycles(edge_kinds, granularity, |source, target, edge| {
+        let data = edge.data.as_ref().or_else(|| {
+            let stored = stored.get(source)?.iter().find(|e| e.target == target && e.kind == edge.kind)?;
+            stored.data.as_ref()
+        });
+        let Some(data) = data else {
+            return true;
+        };
+        let Some(name) = str_field(data, "target_name") else {
+            return true;
+        };
+        if definitions.get(name).copied().unwrap_or(0) > 1 {
+            return false;
+        }
+        // `conn.execute()` gets linked to the project's only `execute`, usually wrongly
+        !(edge.kind == "calls" && is_method_call(&mut source_line, data, name))
+    }))
+}
+
+/// Groups of files that depend on each other in a cycle, each sorted, largest group first.
+/// `sources` maps file paths to their contents and is used to check call sites.
+pub async fn find_cycles(
+    graph: &CodeGraph,
+    store: &Store,
+    sources: &HashMap<String, String>,
+) -> Result<Vec<Vec<String>>> {
+    let source_line = |file: &str, line: u64| {
+        let source = sources.get(file)?;
+        source.lines().nth((line as usize).saturating_sub(1)).map(String::from)
+    };
+    let cycles = dependency_cycles(graph, store, DEPENDENCY_EDGES, Granularity::File, source_line).await?;
+    Ok(cycles.into_iter().map(|cycle| cycle.members).collect())
+}
+
+/// Whether the call recorded in edge `data` is written as `receiver.name(...)`
+fn is_method_call(
+    source_line: &mut impl FnMut(&str, u64) -> Option<String>,
+    data: &serde_json::Value,
+    name: &str,
+) -> bool {
+    let Some(line) = str_field(data, "file")
+        .zip(data.get("line").and_then(|v| v.as_u64()))
+        .and_then(|(file, line)| source_line(file, line))
+    else {
+        return false;
+    };
+    let mut calls = IDENTIFIER
+        .find_iter(&line)
+        .filter(|token| token.as_str() == name)
+        .peekable();
+    calls.peek().is_some() && calls.all(|token| line[..token.start()].ends_with('.'))
+}
+
+/// Symbols with no incoming edge of `edge_kinds` (any kind if `None`), leaving out entry points,
+/// tests, and annotated symbols the runtime or a framework calls. Both `codegraph check` and
+/// find_unreferenced_symbols start from this list.
+pub async fn unreferenced_symbols<'a>(
+    graph: &'a CodeGraph,
+    store: &Store,
+    edge_kinds: Option<&[&str]>,
+) -> Result<Vec<&'a NodeData>> {
+    let mut symbols = Vec::new();
+    for node in graph.unreferenced(edge_kinds) {
+        let name = str_field(&node.data, "name").unwrap_or("");
+        let file = str_field(&node.data, "file").unwrap_or("");
+        if node.kind == "impl" || is_entry_point(name, file) {
+            continue;
+        }
+        // Signatures of a slim graph are read from the store, for these candidates only
+        let data = graph.node_data(store, node).await?;
+        if !is_annotated(str_field(&data, "signature").unwrap_or("")) {
+            symbols.push(node);
+        }
+    }
+    Ok(symbols)
+}
+
+/// Whether the parser marked a symbol as visible outside its module; symbols indexed without
+/// the flag count as public
+pub fn is_public(node: &NodeData) -> bool {
+    node.data.get("public").and_then(|v| v.as_bool()).unwrap_or(true)
+}
+
+/// Private functions and methods from `unreferenced_symbols` whose name appears nowhere in
+/// `sources` but their own definition. The text check covers calls the parser can't link
+/// (`Self::new()`, trait methods, callbacks passed by name). Public symbols may be used from
+/// outside the project.
+pub async fn find_dead_code(
+    graph: &CodeGraph,
+    store: &Store,
+    sources: &HashMap<String, String>,
+) -> Result<Vec<DeadSymbol>> {
+    let mut identifiers: HashMap<&str, usize> = HashMap::new();
+    for source in sources.values() {
+        for token in IDENTIFIER.find_iter(source) {
+            *identifiers.entry(token.as_str()).or_default() += 1;
+        }
+    }
+
+    // Already sorted by file and line
+    let symbols = unreferenced_symbols(graph, store, None).await?;
+    Ok(symbols
+        .into_iter()
+        .filter(|node| matches!(node.kind.as_str(), "function" | "method") && !is_public(node))
+        .filter_map(|node| {
+            let name = str_field(&node.data, "name")?;
+            let file = str_field(&node.data, "file")?;
+            if identifiers.get(name).copied().unwrap_or(0) > 1 {
+                return None;
+            }
+            Some(DeadSymbol {
+                id: node.id.clone(),
+                name: name.to_string(),
+                file: file.to_string(),
+                line: node.data.get("line_start").and_then(|v| v.as_u64()).unwrap_or(0),
+            })
+        })
+        .collect())
+}
+
+/// main, tests, benchmarks, and Python dunder methods are called by the runtime
+fn is_entry_point(name: &str, file: &str) -> bool {
+    let file = file.to_lowercase();
+    name == "main"
+        || name.starts_with("test")
+        || (name.starts_with("__") && name.ends_with("__"))
+        || file.split('/').any(|part| matches!(part, "test" | "tests" | "__tests__" | "benches"))
+        || [".test.", ".spec.", "_test.", "test_"].iter().any(|marker| file.contains(marker))
+}
+
+/// Decorated or attributed symbols are registered with a framework (routes, tests, overrides)
+fn is_annotated(signature: &str) -> bool {
+    signature.starts_with('@') || signature.starts_with("#[")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    async fn setup_store() -> (Store, tempfile::TempDir) {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let config = crate::config::Config {
+            project_root: temp_dir.path().to_path_buf(),
+            cache_dir: temp_dir.path().join("cache"),
+            codegraph_dir: temp_dir.path().join(".codegraph"),
+            store_db_path: temp_dir.path().join("cache/store.db"),
+            learning_db_path: temp_dir.path().join(".codegraph/learning.db"),
+            settings: crate::config::ConfigFile::default().into(),
+        };
+        (Store::open(&config).await.unwrap(), temp_dir)
+    }
+
+    fn add_function(graph: &mut CodeGraph, file: &str, name: &str, signature: &str, line: u64) {
+        let public = signature.starts_with("pub ");
+        graph.add_node(
+            format!("{}::{}", file, name),
+            "function".to_string(),
+            json!({
+                "name": name,
+                "file": file,
+                "signature": signature,
+                "line_start": line,
+                "line_end": line + 2,
+                "public": public,
+            }),
+        );
+    }
+
+    #[tokio::test]
+    async fn test_find_cycles() {
+        let (store, _temp) = setup_store().await;
+        let mut graph = CodeGraph::new();
+        add_function(&mut graph, "src/a.rs", "a", "fn a()", 1);
+        add_function(&mut graph, "src/b.rs", "b", "fn b()", 1);
+        add_function(&mut graph, "src/c.rs", "c", "fn c()", 1);
+        graph.add_edge("src/a.rs::a", "src/b.rs::b", "calls".to_string(), None);
+        graph.add_edge("src/b.rs::b", "src/a.rs::a", "uses_type".to_string(), None);
+        graph.add_edge("src/b.rs::b", "src/c.rs::c", "calls".to_string(), None);
+        // Inferred edges aren't code dependencies
+        graph.add_edge("src/c.rs::c", "src/b.rs::b", "calls_api".to_string(), None);
+        // `new` is defined in two files, so the link to b.rs may be wrong
+        add_function(&mut graph, "src/b.rs", "new", "fn new()", 5);
+        add_function(&mut graph, "src/d.rs", "new", "fn new()", 1);
+        let data = Some(json!({"target_name": "new"}));
+        graph.add_edge("src/c.rs::c", "src/b.rs::new", "calls".to_string(), data);
+        // A method call on some other type that happened to resolve to b.rs
+        add_function(&mut graph, "src/b.rs", "push", "fn push(&mut self)", 10);
+        let data = Some(json!({"target_name": "push", "file": "src/c.rs", "line": 2}));
+        graph.add_edge("src/c.rs::c", "src/b.rs::push", "calls".to_string(), data);
+        let sources = HashMap::from([("src/c.rs".to_string(), "fn c() {\n    items.push(1);\n}\n".to_string())]);
+
+        let cycles = find_cycles(&graph, &store, &sources).await.unwrap();
+        assert_eq!(cycles, vec![vec!["src/a.rs".to_string(), "src/b.rs".to_string()]]);
+    }
+
+    #[tokio::test]
+    async fn test_find_dead_code() {
+        let (store, _temp) = setup_store().await;
+        let mut graph = CodeGraph::new();
+        add_function(&mut graph, "src/lib.rs", "main", "fn main()", 1);
+        add_function(&mut graph, "src/lib.rs", "used", "fn used()", 5);
+        add_function(&mut graph, "src/lib.rs", "build", "fn build()", 10);
+        add_function(&mut graph, "src/lib.rs", "api", "pub fn api()", 15);
+        add_function(&mut graph, "src/lib.rs", "forgotten", "fn forgotten()", 30);
+        add_function(&mut graph, "tests/it.rs", "helper", "fn helper()", 1);
+        // Registered with a framework rather than called
+        add_function(&mut graph, "src/Hooks.java", "onStart", "@Override void onStart() {", 3);
+        graph.add_edge("src/lib.rs::main", "src/lib.rs::used", "calls".to_string(), None);
+
+        // `Self::build()` isn't linked in the graph, but the name is used
+        let source = concat!(
+            "fn main() { used(); }\nfn used() { Self::build() }\nfn build() {}\n",
+            "pub fn api() {}\nfn forgotten() {}\n",
+        );
+        let sources = HashMap::from([("src/lib.rs".to_string(), source.to_string())]);
+
+        let dead = find_dead_code(&graph, &store, &sources).await.unwrap();
+        let names: Vec<&str> = dead.iter().map(|d| d.name.as_str()).collect();
+        assert_eq!(names, vec!["forgotten"]);
+        assert_eq!(dead[0].line, 30);
+    }
+
+    #[tokio::test]
+    async fn test_checks_read_slim_data_from_store() {
+        let (store, _temp) = setup_store().await;
+        for (file, name, signature, public) in [
+            ("src/a.rs", "a", "fn a()", true),
+            ("src/b.rs", "b", "fn b()", true),
+            ("src/b.rs", "push", "fn push(&mut self)", true),
+            ("src/c.rs", "c", "fn c()", true),
+            ("src/lib.rs", "forgotten", "fn forgotten()", false),
+            ("src/Hooks.java", "onStart", "@Override void onStart() {", false),
+        ] {
+            let data = json!({"name": name, "file": file, "signature": signature, "line_start": 1, "public": public});
+            store.upsert_node(&format!("{}::{}", file, name), "code", "function", &data).await.unwrap();
+        }
+        store.upsert_edge("src/a.rs::a", "src/b.rs::b", "calls", "code", None).await.unwrap();
+        store.upsert_edge("src/b.rs::b", "src/a.rs::a", "uses_type", "code", None).await.unwrap();
+        store.upsert_edge("src/b.rs::b", "src/c.rs::c", "calls", "code", None).await.unwrap();
+        let data = json!({"target_name": "push", "file": "src/c.rs", "line": 2});
+        store.upsert_edge("src/c.rs::c", "src/b.rs::push", "calls", "code", Some(&data)).await.unwrap();
+        let settings = crate::config::GraphConfig {
+            mode: crate::config::GraphMode::Slim,
+            ..Default::default()
+        };
+        let graph = CodeGraph::load(&store, &settings).await.unwrap();
+        assert!(graph.is_slim());
+
+        // The method call and the annotation are only known from the store
+        let sources = HashMap::from([("src/c.rs".to_string(), "fn c() {\n    items.push(1);\n}\n".to_string())]);
+        let cycles = find_cycles(&graph, &store, &sources).await.unwrap();
+        assert_eq!(cycles, vec![vec!["src/a.rs".to_string(), "src/b.rs".to_string()]]);
+        let dead = find_dead_code(&graph, &store, &sources).await.unwrap();
+        let names: Vec<&str> = dead.iter().map(|d| d.name.as_str()).collect();
+        assert_eq!(names, vec!["forgotten"]);
+    }
+}
@@ -1,9 +1,22 @@
 use anyhow::Result;
+use once_cell::sync::Lazy;
 use regex::Regex;
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use serde_json::json;
+use std::collections::hash_map::Entry;
+use std::collections::{HashMap, HashSet};
+use std::path::Path;
 use std::sync::Arc;
+use tracing::warn;
 
+use super::env_vars::{self, EnvReference, ENV_READ_GLOB};
+use super::messaging::{self, Broker, Role, MESSAGING_GLOB};
+use super::openapi::{self, ApiSpec, SpecOperation};
+use super::payload::{self, PayloadTypes, TypeIndex, PAYLOAD_TYPE_GLOB};
+use super::realtime::{self, EventRole, Transport, REALTIME_GLOB};
+use super::sql_tables::{self, SQL_SOURCE_GLOB};
+use crate::config::Config;
+use crate::store::db::Node;
 use crate::store::Store;
 
 #[derive(Debug, Clone)]
@@ -11,8 +24,116 @@ pub struct CrossLanguageRule {
     pub rule_type: String,
     pub client_glob: String,
     pub server_glob: String,
+    /// Captures the called path as `path`
     pub client_pattern: Regex,
+    /// Captures the route as `path`, and its HTTP method as `method` when the route names one
     pub server_pattern: Regex,
+    /// Controller-level prefix (captured as `path`) joined onto the routes that follow it
+    pub prefix_pattern: Option<Regex>,
+}
+
+/// fetch/axios calls in frontend code, shared by all REST rules. Paths may be template
+/// literals or full URLs; fetch's method comes from its options object when given inline.
+/// axios calls may name their response type (`axios.get<User>(...)`).
+const REST_CLIENT_GLOB: &str = "**/*.{js,ts,jsx,tsx}";
+const REST_CLIENT_PATTERN: &str = concat!(
+    r#"(?:fetch|axios\.(?P<method>get|post|put|delete|patch)(?:\s*<[^()]*?>)?)\s*\(\s*['"`](?P<path>[/\w\-{}:$.]+)[^'"`]*['"`]"#,
+    r#"(?:\s*,\s*\{[^}]*?\bmethod\s*:\s*['"](?P<fetch_method>\w+)['"])?"#,
+);
+
+/// Paths and the client calls or server routes for them
+type Endpoints = HashMap<String, Vec<Endpoint>>;
+
+/// A client call or server route for an API path
+#[derive(Debug, Clone)]
+struct Endpoint {
+    file: String,
+    /// Uppercase HTTP method, when known
+    method: Option<String>,
+    /// Line of the call or route definition (1-based)
+    line: usize,
+    /// Handler the route definition names (`post(create_user)`)
+    handler: Option<String>,
+}
+
+/// Files producing to or consuming from a topic, with the line of their first usage
+type TopicSites = HashMap<Role, Vec<(String, usize)>>;
+
+/// Added to a connection's confidence when client and route name the same HTTP method
+const METHOD_AGREEMENT_BONUS: f32 = 0.1;
+
+/// Confidence of a producer/consumer connection through a shared topic name
+const TOPIC_CONFIDENCE: f32 = 0.8;
+
+/// Confidence of an emitter/listener connection through a shared socket.io event name, which
+/// is less distinctive than a topic name
+const SOCKET_EVENT_CONFIDENCE: f32 = 0.7;
+
+impl CrossLanguageRule {
+    /// A rule matching frontend REST calls to routes found by `server_pattern`
+    fn rest(rule_type: &str, server_glob: &str, server_pattern: &str) -> Self {
+        Self {
+            rule_type: rule_type.to_string(),
+            client_glob: REST_CLIENT_GLOB.to_string(),
+            server_glob: server_glob.to_string(),
+            client_pattern: Regex::new(REST_CLIENT_PATTERN).unwrap(),
+            server_pattern: Regex::new(server_pattern).unwrap(),
+            prefix_pattern: None,
+        }
+    }
+
+    fn with_prefix(mut self, prefix_pattern: &str) -> Self {
+        self.prefix_pattern = Some(Regex::new(prefix_pattern).unwrap());
+        self
+    }
+
+    /// Routes defined in a server file
+    fn server_routes(&self, file: &str, content: &str) -> Vec<ServerRoute> {
+        let prefixes: Vec<(usize, &str)> = self
+            .prefix_pattern
+            .iter()
+            .flat_map(|pattern| pattern.captures_iter(content))
+            .filter_map(|cap| Some((cap.get(0)?.start(), cap.name("path")?.as_str())))
+            .collect();
+
+        let mut routes = Vec::new();
+        for cap in self.server_pattern.captures_iter(content) {
+            let start = cap.get(0).map_or(0, |m| m.start());
+            // The controller annotation itself, when it doubles as a route annotation
+            if prefixes.iter().any(|(s, _)| *s == start) {
+                continue;
+            }
+            let prefix = prefixes.iter().rev().find(|(s, _)| *s < start).map_or("", |(_, p)| *p);
+            let path = cap.name("path").map_or("", |m| m.as_str());
+            let path = path.trim_start_matches('^').trim_start_matches('/');
+            let normalized = normalize_path(&format!("{}/{}", prefix.trim_end_matches('/'), path));
+            if normalized.is_empty() {
+                continue;
+            }
+            routes.push(ServerRoute {
+                path: normalized,
+                method: cap.name("method").map(|m| m.as_str().to_uppercase()),
+                handler: cap.name("handler").map(|m| m.as_str().to_string()),
+                file: file.to_string(),
+                line: content[..start].matches('\n').count() + 1,
+            });
+        }
+        routes
+    }
+}
+
+/// A route defined in server code
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ServerRoute {
+    /// Normalized path, e.g. `api/users/{id}`
+    pub path: String,
+    /// Uppercase HTTP method, when the route names one
+    pub method: Option<String>,
+    /// Handler name when the route definition passes it by name (`get(list_users)`)
+    pub handler: Option<String>,
+    pub file: String,
+    /// Line of the route definition (1-based)
+    pub line: usize,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -22,6 +143,60 @@ pub struct ApiConnection {
     pub api_path: String,
     pub method: Option<String>,
     pub confidence: f32,
+    /// The matching operation in the project's OpenAPI spec
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub operation: Option<SpecOperation>,
+    /// Request/response fields the client and handler types disagree on
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub warnings: Vec<String>,
+    /// Confirmed by a reviewer, locking confidence at 1.0
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub confirmed: bool,
+}
+
+/// A reviewer's verdict on an inferred connection
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Verdict {
+    Confirmed,
+    Rejected,
+}
+
+impl Verdict {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Confirmed => "confirmed",
+            Self::Rejected => "rejected",
+        }
+    }
+
+    fn parse(s: &str) -> Option<Self> {
+        match s {
+            "confirmed" => Some(Self::Confirmed),
+            "rejected" => Some(Self::Rejected),
+            _ => None,
+        }
+    }
+}
+
+/// What reviewing a connection did
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReviewOutcome {
+    /// The stored connection was confirmed or removed
+    Updated,
+    /// A rejection was lifted; the next inference restores the connection
+    Pending,
+    /// No such connection (or rejection to lift)
+    NotFound,
+}
+
+/// Extra details of a connection, stored as JSON in `cross_language_edges.metadata`
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+struct ConnectionMetadata {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    operation: Option<SpecOperation>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    warnings: Vec<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -29,45 +204,127 @@ pub struct InferenceStats {
     pub client_calls_found: usize,
     pub server_routes_found: usize,
     pub connections_made: usize,
+    /// Message-queue topics with at least one producer or consumer
+    #[serde(default)]
+    pub topics_found: usize,
+    /// Environment variables read or defined anywhere in the project
+    #[serde(default)]
+    pub env_vars_found: usize,
+    /// Database tables used by SQL strings, ORM models, or migrations
+    #[serde(default)]
+    pub tables_found: usize,
+    /// WebSocket/socket.io/SSE endpoints served and socket.io events emitted or listened for
+    #[serde(default)]
+    pub channels_found: usize,
     pub duration_ms: u64,
+    /// Reconciliation against the OpenAPI spec, when the project has one
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub openapi: Option<OpenApiReport>,
+    /// REST connections whose client and handler payload types disagree
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub payload_mismatches: Vec<ApiConnection>,
+}
+
+/// Where an environment variable is defined and read, as (file, line) pairs
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EnvVarUsage {
+    pub name: String,
+    pub definitions: Vec<(String, usize)>,
+    pub reads: Vec<(String, usize)>,
+}
+
+/// Code that defines, maps, reads, or writes a database table
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TableUsage {
+    pub name: String,
+    pub definitions: Vec<TableSite>,
+    pub models: Vec<TableSite>,
+    pub reads: Vec<TableSite>,
+    pub writes: Vec<TableSite>,
+    /// Symbols referencing one of the table's ORM models
+    pub model_references: Vec<String>,
+}
+
+/// The symbol (or file node) touching a table, and where
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct TableSite {
+    pub file: String,
+    pub line: usize,
+    pub symbol: String,
+}
+
+/// Differences between detected REST routes/calls and the OpenAPI spec
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OpenApiReport {
+    pub spec_file: String,
+    pub operations: usize,
+    /// Server routes the spec doesn't document, as `METHOD path (file)`
+    pub undocumented_routes: Vec<String>,
+    /// Spec operations no client code was found calling, as `METHOD path`
+    pub uncalled_operations: Vec<String>,
 }
 
 pub struct CrossLanguageInferrer {
     store: Arc<Store>,
+    config: Arc<Config>,
     rules: Vec<CrossLanguageRule>,
 }
 
 impl CrossLanguageInferrer {
-    pub fn new(store: Arc<Store>) -> Self {
+    pub fn new(store: Arc<Store>, config: Arc<Config>) -> Self {
         let rules = Self::default_rules();
-        Self { store, rules }
+        Self { store, config, rules }
     }
 
     /// Default inference rules for common patterns
     fn default_rules() -> Vec<CrossLanguageRule> {
         vec![
-            // REST API: fetch/axios calls
-            CrossLanguageRule {
-                rule_type: "rest_fetch".to_string(),
-                client_glob: "**/*.{js,ts,jsx,tsx}".to_string(),
-                server_glob: "**/*.{py,rs,js,ts,go}".to_string(),
-                client_pattern: Regex::new(
-                    r#"(?:fetch|axios\.(?:get|post|put|delete|patch))\s*\(\s*['"`]([/\w\-{}:]+)['"`]"#,
-                )
-                .unwrap(),
-                server_pattern: Regex::new(
-                    r#"(?:@app\.route|@router\.|router\.(?:get|post|put|delete|patch)|app\.(?:get|post|put|delete|patch))\s*\(\s*['"`]([/\w\-{}:]+)['"`]"#,
-                )
-                .unwrap(),
-            },
-            // GraphQL
-            CrossLanguageRule {
-                rule_type: "graphql".to_string(),
-                client_glob: "**/*.{js,ts,jsx,tsx,gql,graphql}".to_string(),
-                server_glob: "**/*.{py,rs,js,ts,go}".to_string(),
-                client_pattern: Regex::new(r#"(?:query|mutation)\s+(\w+)"#).unwrap(),
-                server_pattern: Regex::new(r#"def\s+(?:resolve_)?(\w+)"#).unwrap(),
-            },
+            // Express-style routers
+            CrossLanguageRule::rest(
+                "rest_fetch",
+                "**/*.{js,ts,go}",
+                r#"\b(?:router|app)\.(?P<method>get|post|put|delete|patch)\s*\(\s*['"`](?P<path>[/\w\-{}:]+)['"`](?:\s*,\s*(?P<handler>[\w.]+)\s*\))?"#,
+            ),
+            // FastAPI `@app.get("/x")` / `@router.post`, Flask `@app.route("/x")`
+            CrossLanguageRule::rest(
+                "rest_python",
+                "**/*.py",
+                r#"@\w+\.(?:(?P<method>get|post|put|delete|patch)|route)\s*\(\s*['"](?P<path>[/\w\-{}:<>.]+)['"]"#,
+            ),
+            // Django `urlpatterns = [path("users/<int:pk>/", ...)]`
+            CrossLanguageRule::rest(
+                "rest_django",
+                "**/urls.py",
+                r#"\b(?:re_)?path\(\s*r?['"](?P<path>[/\w\-{}:<>^$]+)['"]"#,
+            ),
+            // Actix and Rocket attributes: `#[get("/users/{id}")]`, `#[post("/users/<id>")]`
+            CrossLanguageRule::rest(
+                "rest_rust_attr",
+                "**/*.rs",
+                r#"#\[(?:(?P<method>get|post|put|delete|patch)|route)\(\s*"(?P<path>[/\w\-{}:<>.]+)""#,
+            ),
+            // Axum `.route("/users/:id", get(handler))`, Actix `.route("/x", web::get())` and `web::resource("/x")`
+            CrossLanguageRule::rest(
+                "rest_rust_router",
+                "**/*.rs",
+                r#"(?:\.route|web::resource)\(\s*"(?P<path>[/\w\-{}:<>.]+)"(?:\s*,\s*(?:web::)?(?P<method>get|post|put|delete|patch)\((?P<handler>[\w:]+)?)?"#,
+            ),
+            // Spring `@GetMapping("/{id}")` under a class-level `@RequestMapping("/api/users")`
+            CrossLanguageRule::rest(
+                "rest_spring",
+                "**/*.{java,kt}",
+                r#"@(?:(?P<method>Get|Post|Put|Delete|Patch)Mapping|RequestMapping)\b(?:\(\s*(?:(?:value|path)\s*=\s*)?[\[{]?\s*"(?P<path>[/\w\-{}:.]*)")?"#,
+            )
+            .with_prefix(
+                r#"@RequestMapping\(\s*(?:(?:value|path)\s*=\s*)?[\[{]?\s*"(?P<path>[/\w\-{}:.]*)"[^)]*\)\s*(?:@\w+(?:\([^)]*\))?\s*)*(?:(?:public|internal|open|final|abstract)\s+)*class\b"#,
+            ),
+            // NestJS `@Get(':id')` under `@Controller('users')`
+            CrossLanguageRule::rest(
+                "rest_nest",
+                "**/*.ts",
+                r#"@(?P<method>Get|Post|Put|Delete|Patch)\(\s*(?:['"`](?P<path>[/\w\-{}:.]*)['"`])?\s*\)"#,
+            )
+            .with_prefix(r#"@Controller\(\s*['"`](?P<path>[/\w\-{}:.]*)['"`]"#),
         ]
     }
 
@@ -84,108 +341,854 @@ impl CrossLanguageInferrer {
             client_calls_found: 0,
             server_routes_found: 0,
             connections_made: 0,
+            topics_found: 0,
+            env_vars_found: 0,
+            tables_found: 0,
+            channels_found: 0,
             duration_ms: 0,
+            openapi: None,
+            payload_mismatches: Vec::new(),
         };
 
         // Get all files from the database
         let files = self.store.list_indexed_files().await?;
 
+        // A broken spec shouldn't stop inference
+        let spec = openapi::load_spec(&self.config.project_root).unwrap_or_else(|e| {
+            warn!("Skipping OpenAPI spec: {:#}", e);
+            None
+        });
+
+        // Rules sharing a client side (all the REST rules) scan client files once, and their
+        // routes are matched together so each call goes to the best route across frameworks
+        let mut endpoints_by_client: HashMap<(&str, &str), (Endpoints, Endpoints)> = HashMap::new();
+
         for rule in &self.rules {
             // Find client calls
-            let mut client_calls: HashMap<String, Vec<String>> = HashMap::new();
-            for file in &files {
-                if matches_glob(file, &rule.client_glob) {
-                    if let Ok(content) = std::fs::read_to_string(file) {
-                        for cap in rule.client_pattern.captures_iter(&content) {
-                            if let Some(path) = cap.get(1) {
-                                let normalized = normalize_path(path.as_str());
-                                client_calls
-                                    .entry(normalized)
-                                    .or_default()
-                                    .push(file.clone());
+            let client_key = (rule.client_glob.as_str(), rule.client_pattern.as_str());
+            let (_, server_routes) = match endpoints_by_client.entry(client_key) {
+                Entry::Occupied(entry) => entry.into_mut(),
+                Entry::Vacant(entry) => {
+                    let mut client_calls = Endpoints::new();
+                    for file in &files {
+                        if matches_glob(file, &rule.client_glob) {
+                            if let Ok(content) = self.read_file(file) {
+                                for cap in rule.client_pattern.captures_iter(&content) {
+                                    if let Some(path) = cap.name("path") {
+                                        let normalized = normalize_path(path.as_str());
+                                        let start = cap.get(0).map_or(0, |m| m.start());
+                                        client_calls.entry(normalized).or_default().push(Endpoint {
+                                            file: file.clone(),
+                                            method: client_method(&cap),
+                                            line: content[..start].matches('\n').count() + 1,
+                                            handler: None,
+                                        });
+                                    }
+                                }
                             }
                         }
                     }
+                    stats.client_calls_found += client_calls.len();
+                    entry.insert((client_calls, Endpoints::new()))
                 }
-            }
-
-            stats.client_calls_found += client_calls.len();
+            };
 
             // Find server routes
-            let mut server_routes: HashMap<String, Vec<String>> = HashMap::new();
+            let mut routes_found = 0;
             for file in &files {
                 if matches_glob(file, &rule.server_glob) {
-                    if let Ok(content) = std::fs::read_to_string(file) {
-                        for cap in rule.server_pattern.captures_iter(&content) {
-                            if let Some(path) = cap.get(1) {
-                                let normalized = normalize_path(path.as_str());
-                                server_routes
-                                    .entry(normalized)
-                                    .or_default()
-                                    .push(file.clone());
-                            }
+                    if let Ok(content) = self.read_file(file) {
+                        for route in rule.server_routes(file, &content) {
+                            let handlers = server_routes.entry(route.path).or_default();
+                            routes_found += usize::from(handlers.is_empty());
+                            handlers.push(Endpoint {
+                                file: route.file,
+                                method: route.method,
+                                line: route.line,
+                                handler: route.handler,
+                            });
                         }
                     }
                 }
             }
 
-            stats.server_routes_found += server_routes.len();
-
-            // Match client calls to server routes
-            for (api_path, client_files) in &client_calls {
-                if let Some(server_files) = server_routes.get(api_path) {
-                    for client_file in client_files {
-                        for server_file in server_files {
-                            self.record_connection(
-                                client_file,
-                                server_file,
-                                api_path,
-                                None,
-                                0.8, // Base confidence
-                            )
-                            .await?;
-                            stats.connections_made += 1;
+            stats.server_routes_found += routes_found;
+        }
+
+        // Match client calls to server routes
+        let mut types: Option<TypeIndex> = None;
+        for (client_calls, server_routes) in endpoints_by_client.values() {
+            for (client_path, callers) in client_calls {
+                // Only the best-matching routes, so `/users/me` doesn't also go to `/users/{id}`
+                let candidates: Vec<(&String, f32)> = server_routes
+                    .keys()
+                    .filter_map(|server_path| Some((server_path, path_match_score(client_path, server_path)?)))
+                    .collect();
+                let best = candidates.iter().map(|(_, score)| *score).fold(0.0, f32::max);
+
+                // One connection per client file, server file, and route, with the payload
+                // warnings of all its calls
+                let mut connections: Vec<ApiConnection> = Vec::new();
+                let mut seen: HashMap<(&str, &str, &str), usize> = HashMap::new();
+                for (server_path, path_score) in candidates.iter().filter(|(_, score)| *score == best) {
+                    for caller in callers {
+                        for handler in &server_routes[*server_path] {
+                            let method_score = match (&caller.method, &handler.method) {
+                                (Some(c), Some(s)) if c != s => continue,
+                                (Some(_), Some(_)) => METHOD_AGREEMENT_BONUS,
+                                _ => 0.0,
+                            };
+                            let types = types.get_or_insert_with(|| self.payload_types(&files));
+                            let warnings = self.payload_warnings(types, caller, handler);
+
+                            let key = (caller.file.as_str(), handler.file.as_str(), server_path.as_str());
+                            if let Some(&index) = seen.get(&key) {
+                                let connection = &mut connections[index];
+                                for warning in warnings {
+                                    if !connection.warnings.contains(&warning) {
+                                        connection.warnings.push(warning);
+                                    }
+                                }
+                                continue;
+                            }
+                            let method = handler.method.as_deref().or(caller.method.as_deref());
+                            let operation = spec.as_ref().and_then(|spec| spec_operation(spec, server_path, method));
+                            seen.insert(key, connections.len());
+                            connections.push(ApiConnection {
+                                client_file: caller.file.clone(),
+                                server_file: handler.file.clone(),
+                                api_path: server_path.to_string(),
+                                method: method.map(str::to_string),
+                                confidence: path_score + method_score,
+                                operation: operation.cloned(),
+                                warnings,
+                                confirmed: false,
+                            });
                         }
                     }
                 }
+
+                for connection in connections {
+                    let metadata = ConnectionMetadata {
+                        operation: connection.operation.clone(),
+                        warnings: connection.warnings.clone(),
+                    };
+                    let recorded = self
+                        .record_connection(
+                            &connection.client_file,
+                            &connection.server_file,
+                            &connection.api_path,
+                            connection.method.as_deref(),
+                            connection.confidence,
+                            Some(&metadata),
+                        )
+                        .await?;
+                    if !recorded {
+                        continue;
+                    }
+                    stats.connections_made += 1;
+                    if !connection.warnings.is_empty() {
+                        stats.payload_mismatches.push(connection);
+                    }
+                }
             }
         }
 
+        if let Some(spec) = &spec {
+            stats.openapi = Some(reconcile_spec(spec, endpoints_by_client.values()));
+        }
+
+        self.infer_graphql(&files, &mut stats).await?;
+        self.infer_grpc(&files, &mut stats).await?;
+        self.infer_topics(&files, &mut stats).await?;
+        self.infer_channels(&files, &mut stats).await?;
+        self.infer_env_vars(&files, &mut stats).await?;
+        self.infer_tables(&files, &mut stats).await?;
+
         stats.duration_ms = start.elapsed().as_millis() as u64;
 
         Ok(stats)
     }
 
+    /// Type definitions in indexed files, for comparing API payloads
+    fn payload_types(&self, files: &[String]) -> TypeIndex {
+        let mut types = TypeIndex::default();
+        for file in files.iter().filter(|f| matches_glob(f, PAYLOAD_TYPE_GLOB)) {
+            if let Ok(content) = self.read_file(file) {
+                types.insert(file, &content);
+            }
+        }
+        types
+    }
+
+    /// Field mismatches between a client call's payload types and its route handler's
+    fn payload_warnings(&self, types: &TypeIndex, caller: &Endpoint, handler: &Endpoint) -> Vec<String> {
+        let Ok(client) = self.read_file(&caller.file) else { return Vec::new() };
+        let client_types = payload::client_payload_types(&client, caller.line);
+        if client_types == PayloadTypes::default() {
+            return Vec::new();
+        }
+        let Ok(server) = self.read_file(&handler.file) else { return Vec::new() };
+        let server_types =
+            payload::handler_payload_types(&handler.file, &server, handler.line, handler.handler.as_deref());
+        payload::payload_mismatches(types, &caller.file, &client_types, &handler.file, &server_types)
+    }
+
+    /// All REST routes defined in indexed server files, sorted by file and line
+    pub async fn detect_routes(&self) -> Result<Vec<ServerRoute>> {
+        let mut files = self.store.list_indexed_files().await?;
+        files.sort();
+        Ok(files.iter().flat_map(|file| self.file_routes(file)).collect())
+    }
+
+    /// REST routes defined in one file, sorted by line
+    pub fn file_routes(&self, file: &str) -> Vec<ServerRoute> {
+        let Ok(content) = self.read_file(file) else { return Vec::new() };
+        let mut routes: Vec<ServerRoute> = self
+            .rules
+            .iter()
+            .filter(|rule| matches_glob(file, &rule.server_glob))
+            .flat_map(|rule| rule.server_routes(file, &content))
+            .collect();
+        routes.sort_by_key(|route| route.line);
+        // Rules for the same language can find the same definition
+        routes.dedup_by(|a, b| a.line == b.line && a.path == b.path);
+        routes
+    }
+
+    /// Connect GraphQL operations in client code (`gql` templates, .graphql files) to the
+    /// resolvers for their root fields. Edges are recorded per root field, e.g. `Query.user`.
+    async fn infer_graphql(&self, files: &[String], stats: &mut InferenceStats) -> Result<()> {
+        // .graphql documents have no grammar, so they are never in the index
+        let documents = self.find_unindexed_files(&["graphql", "gql"]);
+
+        let mut operations: HashMap<(OperationType, String), Vec<(String, String)>> = HashMap::new();
+        for file in files.iter().filter(|f| matches_glob(f, GRAPHQL_CLIENT_GLOB)).chain(&documents) {
+            let Ok(content) = self.read_file(file) else { continue };
+            for document in graphql_documents(file, &content) {
+                for (op, field) in graphql_operations(document) {
+                    operations
+                        .entry((op, resolver_key(&field)))
+                        .or_default()
+                        .push((file.clone(), field));
+                }
+            }
+        }
+        stats.client_calls_found += operations.len();
+
+        let mut resolvers: HashMap<ResolverKey, Vec<(String, f32)>> = HashMap::new();
+        for file in files.iter().filter(|f| matches_glob(f, GRAPHQL_SERVER_GLOB)) {
+            let Ok(content) = self.read_file(file) else { continue };
+            for resolver in graphql_resolvers(file, &content) {
+                resolvers
+                    .entry((resolver.op, resolver_key(&resolver.field)))
+                    .or_default()
+                    .push((file.clone(), resolver.confidence));
+            }
+        }
+        stats.server_routes_found += resolvers.len();
+
+        for ((op, key), clients) in &operations {
+            let mut keys = vec![(Some(*op), key.clone())];
+            // Resolvers that don't say which root type they belong to are assumed to be queries
+            if *op == OperationType::Query {
+                keys.push((None, key.clone()));
+            }
+            let servers = keys.iter().filter_map(|k| resolvers.get(k)).flatten();
+
+            let mut seen = HashSet::new();
+            for (server_file, confidence) in servers {
+                for (client_file, field) in clients {
+                    if client_file == server_file || !seen.insert((client_file, server_file)) {
+                        continue;
+                    }
+                    let api_path = format!("{}.{}", op.root_type(), field);
+                    let method = Some(op.keyword());
+                    if self.record_connection(client_file, server_file, &api_path, method, *confidence, None).await? {
+                        stats.connections_made += 1;
+                    }
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Files without a grammar (so never in the index) by extension, relative to the project root
+    fn find_unindexed_files(&self, extensions: &[&str]) -> Vec<String> {
+        self.find_project_files(false, |path| {
+            Path::new(path)
+                .extension()
+                .and_then(|e| e.to_str())
+                .is_some_and(|e| extensions.contains(&e))
+        })
+    }
+
+    /// Project files (relative paths) accepted by `filter`, honoring ignore files.
+    /// Hidden files and directories other than `.git` are included on request.
+    fn find_project_files(&self, hidden: bool, filter: impl Fn(&str) -> bool) -> Vec<String> {
+        let root = &self.config.project_root;
+        ignore::WalkBuilder::new(root)
+            .require_git(false)
+            .hidden(!hidden)
+            .add_custom_ignore_filename(".codegraphignore")
+            .filter_entry(|entry| entry.file_name() != ".git")
+            .build()
+            .flatten()
+            .filter(|entry| entry.file_type().is_some_and(|t| t.is_file()))
+            .filter_map(|entry| {
+                let rel = entry.path().strip_prefix(root).ok()?;
+                Some(rel.to_string_lossy().to_string())
+            })
+            .filter(|path| filter(path))
+            .collect()
+    }
+
+    /// Connect generated gRPC client calls to the service implementations of the same rpc,
+    /// using the services declared in .proto files. Besides the API connection, each match
+    /// adds a `calls_api` edge to the code graph from the client file to the implementing
+    /// function (or its file, when the function isn't indexed).
+    async fn infer_grpc(&self, files: &[String], stats: &mut InferenceStats) -> Result<()> {
+        let services: Vec<ProtoService> = self
+            .find_unindexed_files(&["proto"])
+            .iter()
+            .filter_map(|file| self.read_file(file).ok())
+            .flat_map(|content| parse_proto(&content))
+            .collect();
+        if services.is_empty() {
+            return Ok(());
+        }
+
+        // Keyed by (service index, rpc)
+        let mut calls: HashMap<(usize, &str), Vec<String>> = HashMap::new();
+        let mut implementations: HashMap<(usize, &str), Vec<RpcImplementation>> = HashMap::new();
+        for file in files.iter().filter(|f| matches_glob(f, GRPC_SOURCE_GLOB) && !is_generated_grpc(f)) {
+            let Ok(content) = self.read_file(file) else { continue };
+            for (index, service) in services.iter().enumerate() {
+                for rpc in grpc_client_calls(&content, service) {
+                    calls.entry((index, rpc)).or_default().push(file.clone());
+                }
+                for (rpc, function) in grpc_implementations(&content, service) {
+                    // An implementation without a matching method may still inherit or register it
+                    let confidence = if function.is_some() { 0.9 } else { 0.6 };
+                    implementations.entry((index, rpc)).or_default().push(RpcImplementation {
+                        file: file.clone(),
+                        function,
+                        confidence,
+                    });
+                }
+            }
+        }
+        stats.client_calls_found += calls.len();
+        stats.server_routes_found += implementations
+            .values()
+            .flatten()
+            .filter(|i| i.function.is_some())
+            .count();
+
+        for (&(index, rpc), clients) in &calls {
+            let Some(servers) = implementations.get(&(index, rpc)) else { continue };
+            let service = &services[index];
+            let api_path = service.rpc_path(rpc);
+            for client_file in clients {
+                for server in servers {
+                    if *client_file == server.file {
+                        continue;
+                    }
+                    let confidence = server.confidence;
+                    if self
+                        .record_connection(client_file, &server.file, &api_path, Some("grpc"), confidence, None)
+                        .await?
+                    {
+                        self.add_calls_api_edge(client_file, server, &api_path).await?;
+                        stats.connections_made += 1;
+                    }
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Connect message-queue producers to consumers through topic nodes. Each topic gets a
+    /// `topic::{broker}:{name}` node with `publishes_to` edges from producer files and
+    /// `subscribes_to` edges from consumer files, and every producer/consumer pair is
+    /// recorded as an API connection on `topic:{name}`.
+    async fn infer_topics(&self, files: &[String], stats: &mut InferenceStats) -> Result<()> {
+        let mut topics: HashMap<(Broker, String), TopicSites> = HashMap::new();
+        for file in files.iter().filter(|f| matches_glob(f, MESSAGING_GLOB)) {
+            let Ok(content) = self.read_file(file) else { continue };
+            for usage in messaging::find_topic_usages(&content) {
+                let sites = topics.entry((usage.broker, usage.topic)).or_default().entry(usage.role).or_default();
+                if !sites.iter().any(|(f, _)| f == file) {
+                    sites.push((file.clone(), usage.line));
+                }
+            }
+        }
+        stats.topics_found += topics.len();
+
+        for ((broker, topic), roles) in &topics {
+            let topic_id = format!("topic::{}:{}", broker.name(), topic);
+            self.store
+                .upsert_node(
+                    &topic_id,
+                    "code",
+                    "topic",
+                    &json!({"name": topic, "kind": "topic", "broker": broker.name()}),
+                )
+                .await?;
+
+            let producers = roles.get(&Role::Producer).map(Vec::as_slice).unwrap_or_default();
+            let consumers = roles.get(&Role::Consumer).map(Vec::as_slice).unwrap_or_default();
+            for (kind, sites) in [("publishes_to", producers), ("subscribes_to", consumers)] {
+                for (file, line) in sites {
+                    let source = self.file_node(file).await?;
+                    let data = json!({"broker": broker.name(), "line": line, "inferred": true});
+                    self.store.upsert_edge(&source, &topic_id, kind, "code", Some(&data)).await?;
+                }
+            }
+
+            let api_path = format!("topic:{}", topic);
+            for (producer, _) in producers {
+                for (consumer, _) in consumers.iter().filter(|(c, _)| c != producer) {
+                    let method = Some(broker.name());
+                    if self.record_connection(producer, consumer, &api_path, method, TOPIC_CONFIDENCE, None).await? {
+                        stats.connections_made += 1;
+                    }
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Connect WebSocket, socket.io, and server-sent event clients to the endpoints serving
+    /// them, matched by path like REST calls, and socket.io emitters to listeners. Each event
+    /// gets a `channel::{name}` node with `emits_to` and `listens_to` edges from files, and
+    /// every emitter/listener pair is recorded as an API connection on `event:{name}`.
+    async fn infer_channels(&self, files: &[String], stats: &mut InferenceStats) -> Result<()> {
+        let mut servers: HashMap<(Transport, String), Vec<String>> = HashMap::new();
+        let mut clients: Vec<(Transport, String, String)> = Vec::new();
+        let mut events: HashMap<String, HashMap<EventRole, Vec<(String, usize)>>> = HashMap::new();
+        for file in files.iter().filter(|f| matches_glob(f, REALTIME_GLOB)) {
+            let Ok(content) = self.read_file(file) else { continue };
+            let mut served = Vec::new();
+            for endpoint in realtime::find_channel_servers(&content) {
+                served.push((endpoint.transport, normalize_path(&endpoint.path)));
+            }
+            // Event streams are served by ordinary GET routes
+            if realtime::streams_events(&content) {
+                for route in self.file_routes(file) {
+                    if route.method.as_deref().is_none_or(|m| m == "GET") {
+                        served.push((Transport::Sse, route.path));
+                    }
+                }
+            }
+            for key in served {
+                let files = servers.entry(key).or_default();
+                if !files.contains(file) {
+                    files.push(file.clone());
+                }
+            }
+            for endpoint in realtime::find_channel_clients(&content) {
+                clients.push((endpoint.transport, normalize_path(&endpoint.path), file.clone()));
+            }
+            for event in realtime::find_socket_events(&content) {
+                let sites = events.entry(event.event).or_default().entry(event.role).or_default();
+                if !sites.iter().any(|(f, _)| f == file) {
+                    sites.push((file.clone(), event.line));
+                }
+            }
+        }
+        stats.channels_found += servers.len() + events.len();
+
+        let mut seen = HashSet::new();
+        for (transport, client_path, client_file) in &clients {
+            // Only the best-matching endpoints, as for REST calls
+            let candidates: Vec<(&String, &Vec<String>, f32)> = servers
+                .iter()
+                .filter(|((t, _), _)| t == transport)
+                .filter_map(|((_, path), files)| Some((path, files, path_match_score(client_path, path)?)))
+                .collect();
+            let best = candidates.iter().map(|(_, _, score)| *score).fold(0.0, f32::max);
+            for (server_path, server_files, score) in candidates.iter().filter(|(_, _, score)| *score == best) {
+                for server_file in server_files.iter().filter(|f| *f != client_file) {
+                    if !seen.insert((client_file, server_file, *server_path)) {
+                        continue;
+                    }
+                    let method = Some(transport.name());
+                    if self.record_connection(client_file, server_file, server_path, method, *score, None).await? {
+                        stats.connections_made += 1;
+                    }
+                }
+            }
+        }
+
+        for (event, roles) in &events {
+            let channel_id = format!("channel::{}", event);
+            self.store
+                .upsert_node(&channel_id, "code", "channel", &json!({"name": event, "kind": "channel"}))
+                .await?;
+
+            let emitters = roles.get(&EventRole::Emit).map(Vec::as_slice).unwrap_or_default();
+            let listeners = roles.get(&EventRole::Listen).map(Vec::as_slice).unwrap_or_default();
+            for (kind, sites) in [("emits_to", emitters), ("listens_to", listeners)] {
+                for (file, line) in sites {
+                    let source = self.file_node(file).await?;
+                    let data = json!({"line": line, "inferred": true});
+                    self.store.upsert_edge(&source, &channel_id, kind, "code", Some(&data)).await?;
+                }
+            }
+
+            let api_path = format!("event:{}", event);
+            let method = Some(Transport::SocketIo.name());
+            for (emitter, _) in emitters {
+                for (listener, _) in listeners.iter().filter(|(l, _)| l != emitter) {
+                    let confidence = SOCKET_EVENT_CONFIDENCE;
+                    if self.record_connection(emitter, listener, &api_path, method, confidence, None).await? {
+                        stats.connections_made += 1;
+                    }
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Link environment variable reads in source files to the dotenv, compose, Dockerfile,
+    /// and CI files defining them, through `env::{NAME}` nodes with `reads_env` and
+    /// `defines_env` edges from each file.
+    async fn infer_env_vars(&self, files: &[String], stats: &mut InferenceStats) -> Result<()> {
+        let mut references: Vec<(&str, String, EnvReference)> = Vec::new();
+        for file in files.iter().filter(|f| matches_glob(f, ENV_READ_GLOB)) {
+            let Ok(content) = self.read_file(file) else { continue };
+            for read in env_vars::find_env_reads(&content) {
+                references.push(("reads_env", file.clone(), read));
+            }
+        }
+        for file in self.find_project_files(true, env_vars::is_env_definition_file) {
+            let Ok(content) = self.read_file(&file) else { continue };
+            for definition in env_vars::find_env_definitions(&file, &content) {
+                references.push(("defines_env", file.clone(), definition));
+            }
+        }
+
+        let mut names = HashSet::new();
+        let mut linked = HashSet::new();
+        for (kind, file, reference) in &references {
+            // One edge per file and variable, at its first line
+            if !linked.insert((*kind, file, &reference.name)) {
+                continue;
+            }
+            let env_id = format!("env::{}", reference.name);
+            if names.insert(reference.name.as_str()) {
+                self.store
+                    .upsert_node(
+                        &env_id,
+                        "code",
+                        "env_var",
+                        &json!({"name": reference.name, "kind": "env_var"}),
+                    )
+                    .await?;
+            }
+            let source = self.file_node(file).await?;
+            let data = json!({"line": reference.line, "inferred": true});
+            self.store.upsert_edge(&source, &env_id, kind, "code", Some(&data)).await?;
+        }
+        stats.env_vars_found += names.len();
+
+        Ok(())
+    }
+
+    /// Definitions and reads of environment variables, from the last inference run. With a
+    /// name, only that variable (if it was seen); otherwise every variable, sorted by name.
+    pub async fn env_var_usage(&self, name: Option<&str>) -> Result<Vec<EnvVarUsage>> {
+        let names: Vec<String> = match name {
+            Some(name) => vec![name.to_string()],
+            None => {
+                let mut names: Vec<String> = self
+                    .store
+                    .query_nodes("code", "env_var")
+                    .await?
+                    .into_iter()
+                    .filter_map(|node| node.id.strip_prefix("env::").map(str::to_string))
+                    .collect();
+                names.sort();
+                names
+            }
+        };
+
+        let mut usages = Vec::new();
+        for name in names {
+            let mut usage = EnvVarUsage {
+                name: name.clone(),
+                definitions: Vec::new(),
+                reads: Vec::new(),
+            };
+            for edge in self.store.get_edges_to(&format!("env::{}", name)).await? {
+                let file = edge.source.strip_prefix("file::").unwrap_or(&edge.source).to_string();
+                let line = edge.data.as_ref().and_then(|d| d.get("line")).and_then(|l| l.as_u64()).unwrap_or(0);
+                match edge.kind.as_str() {
+                    "defines_env" => usage.definitions.push((file, line as usize)),
+                    "reads_env" => usage.reads.push((file, line as usize)),
+                    _ => {}
+                }
+            }
+            if !usage.definitions.is_empty() || !usage.reads.is_empty() {
+                usage.definitions.sort();
+                usage.reads.sort();
+                usages.push(usage);
+            }
+        }
+        Ok(usages)
+    }
+
+    /// Connect code to the database tables it uses through `table::{name}` nodes: the
+    /// innermost symbol around SQL in a string literal gets a `reads_table`, `writes_table`,
+    /// or `defines_table` edge, ORM model classes a `maps_to_table` edge, and .sql files
+    /// `defines_table`/`reads_table`/`writes_table` edges from their file node.
+    async fn infer_tables(&self, files: &[String], stats: &mut InferenceStats) -> Result<()> {
+        // (source node, table, edge kind, file, line)
+        let mut links: Vec<(String, String, &str, String, usize)> = Vec::new();
+        for file in files.iter().filter(|f| matches_glob(f, SQL_SOURCE_GLOB)) {
+            let Ok(content) = self.read_file(file) else { continue };
+            let references = sql_tables::find_sql_references(&content);
+            let models = sql_tables::find_orm_models(file, &content);
+            if references.is_empty() && models.is_empty() {
+                continue;
+            }
+
+            let symbols = self.store.get_file_nodes(file).await?;
+            for reference in references {
+                let source = match enclosing_symbol(&symbols, reference.line) {
+                    Some(id) => id.to_string(),
+                    None => self.file_node(file).await?,
+                };
+                links.push((source, reference.table, reference.access.edge_kind(), file.clone(), reference.line));
+            }
+            for model in models {
+                let model_id = format!("{}::{}", file, model.model);
+                let source = if symbols.iter().any(|s| s.id == model_id) {
+                    model_id
+                } else {
+                    self.file_node(file).await?
+                };
+                links.push((source, model.table, "maps_to_table", file.clone(), model.line));
+            }
+        }
+        for file in self.find_unindexed_files(&["sql"]) {
+            let Ok(content) = self.read_file(&file) else { continue };
+            for reference in sql_tables::find_sql_file_references(&content) {
+                let source = self.file_node(&file).await?;
+                links.push((source, reference.table, reference.access.edge_kind(), file.clone(), reference.line));
+            }
+        }
+
+        let mut tables = HashSet::new();
+        let mut linked = HashSet::new();
+        for (source, table, kind, file, line) in &links {
+            // One edge per symbol, table, and access, at its first line
+            if !linked.insert((source, table, kind)) {
+                continue;
+            }
+            let table_id = format!("table::{}", table);
+            if tables.insert(table) {
+                self.store
+                    .upsert_node(&table_id, "code", "table", &json!({"name": table, "kind": "table"}))
+                    .await?;
+            }
+            let data = json!({"file": file, "line": line, "inferred": true});
+            self.store.upsert_edge(source, &table_id, kind, "code", Some(&data)).await?;
+        }
+        stats.tables_found += tables.len();
+
+        Ok(())
+    }
+
+    /// Code touching database tables, from the last inference run. With a name, only that
+    /// table (if it was seen); otherwise every table, sorted by name.
+    pub async fn table_usage(&self, name: Option<&str>) -> Result<Vec<TableUsage>> {
+        let names: Vec<String> = match name {
+            Some(name) => vec![name.to_lowercase()],
+            None => {
+                let mut names: Vec<String> = self
+                    .store
+                    .query_nodes("code", "table")
+                    .await?
+                    .into_iter()
+                    .filter_map(|node| node.id.strip_prefix("table::").map(str::to_string))
+                    .collect();
+                names.sort();
+                names
+            }
+        };
+
+        let mut usages = Vec::new();
+        for name in names {
+            let mut usage = TableUsage {
+                name: name.clone(),
+                definitions: Vec::new(),
+                models: Vec::new(),
+                reads: Vec::new(),
+                writes: Vec::new(),
+                model_references: Vec::new(),
+            };
+            for edge in self.store.get_edges_to(&format!("table::{}", name)).await? {
+                let data = edge.data.as_ref();
+                let site = TableSite {
+                    file: data.and_then(|d| d.get("file")).and_then(|f| f.as_str()).unwrap_or_default().to_string(),
+                    line: data.and_then(|d| d.get("line")).and_then(|l| l.as_u64()).unwrap_or(0) as usize,
+                    symbol: edge.source.clone(),
+                };
+                match edge.kind.as_str() {
+                    "defines_table" => usage.definitions.push(site),
+                    "maps_to_table" => usage.models.push(site),
+                    "reads_table" => usage.reads.push(site),
+                    "writes_table" => usage.writes.push(site),
+                    _ => {}
+                }
+            }
+            for model in &usage.models {
+                for edge in self.store.get_edges_to(&model.symbol).await? {
+                    if !usage.model_references.contains(&edge.source) {
+                        usage.model_references.push(edge.source);
+                    }
+                }
+            }
+            let sites = [&mut usage.definitions, &mut usage.models, &mut usage.reads, &mut usage.writes];
+            if sites.iter().all(|s| s.is_empty()) {
+                continue;
+            }
+            for sites in sites {
+                sites.sort();
+            }
+            usage.model_references.sort();
+            usages.push(usage);
+        }
+        Ok(usages)
+    }
+
+    /// Graph edge from a client file to the function (or file) serving an API call
+    async fn add_calls_api_edge(&self, client_file: &str, server: &RpcImplementation, api_path: &str) -> Result<()> {
+        let source = self.file_node(client_file).await?;
+        let target = match server.function.as_ref().map(|f| format!("{}::{}", server.file, f)) {
+            Some(id) if self.store.get_node(&id).await?.is_some() => id,
+            _ => self.file_node(&server.file).await?,
+        };
+        let data = json!({
+            "api": api_path,
+            "confidence": server.confidence,
+            "inferred": true,
+        });
+        self.store
+            .upsert_edge(&source, &target, "calls_api", "code", Some(&data))
+            .await
+    }
+
+    /// File-level node ID, created like the indexer does for file-level references
+    async fn file_node(&self, file: &str) -> Result<String> {
+        let id = format!("file::{}", file);
+        self.store
+            .upsert_node(&id, "code", "file", &json!({"path": file, "name": file}))
+            .await?;
+        Ok(id)
+    }
+
+    /// Read a file by its index path (relative to the project root)
+    fn read_file(&self, file: &str) -> std::io::Result<String> {
+        std::fs::read_to_string(self.config.project_root.join(file))
+    }
+
     /// Get API connections for a given path
     pub async fn get_api_connections(&self, path: &str) -> Result<Vec<ApiConnection>> {
+        self.query_connections(
+            "WHERE client_file = ?1 OR server_file = ?1 OR api_path LIKE ?2",
+            libsql::params![path, format!("%{}%", path)],
+        )
+        .await
+    }
+
+    /// Connections to an API endpoint, e.g. the callers of `POST /api/orders`. The path is matched
+    /// like a client call, so `/api/orders/42` finds the `api/orders/{id}` route.
+    pub async fn get_endpoint_connections(&self, path: &str, method: Option<&str>) -> Result<Vec<ApiConnection>> {
+        let path = normalize_path(path);
+        let connections = self.query_connections("", ()).await?;
+        Ok(connections
+            .into_iter()
+            .filter(|c| c.method.as_deref().is_none_or(|m| method_agrees(method, m)))
+            .filter(|c| {
+                path_match_score(&path, &c.api_path).is_some() || path_match_score(&c.api_path, &path).is_some()
+            })
+            .collect())
+    }
+
+    /// Connections to a server route, e.g. one found for a handler symbol
+    pub async fn get_route_connections(&self, route: &ServerRoute) -> Result<Vec<ApiConnection>> {
+        let connections = self
+            .query_connections(
+                "WHERE server_file = ?1 AND api_path = ?2",
+                libsql::params![route.file.as_str(), route.path.as_str()],
+            )
+            .await?;
+        // Connections are stored per path, so a GET and a POST route share one
+        Ok(connections
+            .into_iter()
+            .filter(|c| route.method.is_none() || c.method.is_none() || c.method == route.method)
+            .collect())
+    }
+
+    /// Stored connections matching a `WHERE` clause (or all of them), best first
+    async fn query_connections(
+        &self,
+        condition: &str,
+        params: impl libsql::params::IntoParams,
+    ) -> Result<Vec<ApiConnection>> {
         let mut rows = self
             .store
-            .code_db
+            .learning_db
             .query(
-                "SELECT client_file, server_file, api_path, method, confidence
-                 FROM cross_language_edges
-                 WHERE client_file = ?1 OR server_file = ?1 OR api_path LIKE ?2
-                 ORDER BY confidence DESC",
-                libsql::params![path, format!("%{}%", path)],
+                &format!(
+                    "SELECT client_file, server_file, api_path, method, confidence, metadata,
+                         EXISTS (
+                             SELECT 1 FROM cross_language_reviews r
+                             WHERE r.client_file = e.client_file AND r.server_file = e.server_file
+                               AND r.api_path = e.api_path AND r.verdict = 'confirmed'
+                         )
+                     FROM cross_language_edges e {}
+                     ORDER BY confidence DESC",
+                    condition
+                ),
+                params,
             )
             .await?;
 
         let mut connections = Vec::new();
 
         while let Some(row) = rows.next().await? {
-            let method: Option<String> = row.get(3).ok();
+            // Connections without a method are stored as ""
+            let method: Option<String> = row.get::<String>(3).ok().filter(|m| !m.is_empty());
+            let metadata: ConnectionMetadata = row
+                .get::<String>(5)
+                .ok()
+                .and_then(|json| serde_json::from_str(&json).ok())
+                .unwrap_or_default();
             connections.push(ApiConnection {
                 client_file: row.get(0)?,
                 server_file: row.get(1)?,
                 api_path: row.get(2)?,
                 method,
                 confidence: row.get::<f64>(4)? as f32,
+                operation: metadata.operation,
+                warnings: metadata.warnings,
+                confirmed: row.get::<i64>(6)? != 0,
             });
         }
 
         Ok(connections)
     }
 
+    /// Record an inferred connection, unless it was rejected. Confirmed connections are
+    /// recorded at full confidence. Returns whether the connection was recorded.
     async fn record_connection(
         &self,
         client_file: &str,
@@ -193,52 +1196,692 @@ impl CrossLanguageInferrer {
         api_path: &str,
         method: Option<&str>,
         confidence: f32,
-    ) -> Result<()> {
+        metadata: Option<&ConnectionMetadata>,
+    ) -> Result<bool> {
+        let confidence = match self.review(client_file, server_file, api_path).await? {
+            Some(Verdict::Rejected) => return Ok(false),
+            Some(Verdict::Confirmed) => 1.0,
+            None => confidence,
+        };
         let now = chrono::Utc::now().timestamp();
+        let metadata = metadata
+            .filter(|m| m.operation.is_some() || !m.warnings.is_empty())
+            .map(serde_json::to_string)
+            .transpose()?;
 
         self.store
-            .code_db
+            .learning_db
             .execute(
-                "INSERT INTO cross_language_edges (client_file, server_file, api_path, method, confidence, created_at)
-                 VALUES (?1, ?2, ?3, ?4, ?5, ?6)
+                "INSERT INTO cross_language_edges
+                     (client_file, server_file, api_path, method, confidence, created_at, metadata)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)
                  ON CONFLICT(client_file, server_file, api_path) DO UPDATE SET
                      method = ?4,
-                     confidence = ?5",
+                     confidence = ?5,
+                     metadata = ?7",
                 libsql::params![
                     client_file,
                     server_file,
                     api_path,
                     method.unwrap_or(""),
                     confidence as f64,
-                    now
+                    now,
+                    metadata
                 ],
             )
             .await?;
 
-        Ok(())
+        Ok(true)
     }
 
-    async fn clear_cross_language_edges(&self) -> Result<()> {
-        self.store
-            .code_db
-            .execute("DELETE FROM cross_language_edges", ())
+    /// The verdict on a connection, if it has been reviewed
+    async fn review(&self, client_file: &str, server_file: &str, api_path: &str) -> Result<Option<Verdict>> {
+        let mut rows = self
+            .store
+            .learning_db
+            .query(
+                "SELECT verdict FROM cross_language_reviews
+                 WHERE client_file = ?1 AND server_file = ?2 AND api_path = ?3",
+                libsql::params![client_file, server_file, api_path],
+            )
             .await?;
-        Ok(())
+        match rows.next().await? {
+            Some(row) => Ok(Verdict::parse(&row.get::<String>(0)?)),
+            None => Ok(None),
+        }
     }
-}
-
-/// Normalize an API path for matching
-fn normalize_path(path: &str) -> String {
-    // Replace param placeholders first (before lowercasing)
-    path.trim_start_matches('/')
-        .replace("${", "{")
-        .replace(":id", "{id}")
-        .replace(":userId", "{userId}")
-        .to_lowercase()
-        // Add more normalizations as needed
-}
 
-/// Simple glob matching
+    /// Confirm or reject an inferred connection. Confirmed connections are locked at full
+    /// confidence and kept by forced rebuilds; rejected ones are removed and never
+    /// re-inferred. Confirming a previously rejected connection lifts the rejection, so the
+    /// next inference restores it.
+    pub async fn review_connection(
+        &self,
+        client_file: &str,
+        server_file: &str,
+        api_path: &str,
+        verdict: Verdict,
+    ) -> Result<ReviewOutcome> {
+        let key = || libsql::params![client_file, server_file, api_path];
+        let exists = self
+            .store
+            .learning_db
+            .query(
+                "SELECT 1 FROM cross_language_edges
+                 WHERE client_file = ?1 AND server_file = ?2 AND api_path = ?3",
+                key(),
+            )
+            .await?
+            .next()
+            .await?
+            .is_some();
+        // Only a rejection can be reviewed without a stored connection, by confirming it
+        let lifts_rejection = verdict == Verdict::Confirmed
+            && self.review(client_file, server_file, api_path).await? == Some(Verdict::Rejected);
+        if !exists && !lifts_rejection {
+            return Ok(ReviewOutcome::NotFound);
+        }
+
+        let now = chrono::Utc::now().timestamp();
+        self.store
+            .learning_db
+            .execute(
+                "INSERT INTO cross_language_reviews (client_file, server_file, api_path, verdict, reviewed_at)
+                 VALUES (?1, ?2, ?3, ?4, ?5)
+                 ON CONFLICT(client_file, server_file, api_path) DO UPDATE SET verdict = ?4, reviewed_at = ?5",
+                libsql::params![client_file, server_file, api_path, verdict.as_str(), now],
+            )
+            .await?;
+
+        match verdict {
+            Verdict::Confirmed => {
+                self.store
+                    .learning_db
+                    .execute(
+                        "UPDATE cross_language_edges SET confidence = 1.0
+                         WHERE client_file = ?1 AND server_file = ?2 AND api_path = ?3",
+                        key(),
+                    )
+                    .await?;
+            }
+            Verdict::Rejected => {
+                self.store
+                    .learning_db
+                    .execute(
+                        "DELETE FROM cross_language_edges
+                         WHERE client_file = ?1 AND server_file = ?2 AND api_path = ?3",
+                        key(),
+                    )
+                    .await?;
+                // gRPC connections also put a calls_api edge in the code graph
+                self.store
+                    .delete_api_edges(&format!("file::{}", client_file), api_path)
+                    .await?;
+            }
+        }
+
+        Ok(if exists { ReviewOutcome::Updated } else { ReviewOutcome::Pending })
+    }
+
+    async fn clear_cross_language_edges(&self) -> Result<()> {
+        // Confirmed connections survive rebuilds
+        self.store
+            .learning_db
+            .execute(
+                "DELETE FROM cross_language_edges WHERE NOT EXISTS (
+                     SELECT 1 FROM cross_language_reviews r
+                     WHERE r.client_file = cross_language_edges.client_file
+                       AND r.server_file = cross_language_edges.server_file
+                       AND r.api_path = cross_language_edges.api_path
+                       AND r.verdict = 'confirmed'
+                 )",
+                (),
+            )
+            .await?;
+        self.store.delete_edges_by_kind("calls_api").await?;
+        self.store.delete_edges_by_node_prefix("topic::").await?;
+        self.store.delete_nodes_by_prefix("topic::").await?;
+        self.store.delete_edges_by_node_prefix("channel::").await?;
+        self.store.delete_nodes_by_prefix("channel::").await?;
+        self.store.delete_edges_by_node_prefix("env::").await?;
+        self.store.delete_nodes_by_prefix("env::").await?;
+        self.store.delete_edges_by_node_prefix("table::").await?;
+        self.store.delete_nodes_by_prefix("table::").await?;
+        Ok(())
+    }
+}
+
+const GRAPHQL_CLIENT_GLOB: &str = "**/*.{js,ts,jsx,tsx}";
+const GRAPHQL_SERVER_GLOB: &str = "**/*.{py,js,ts,go}";
+
+/// `gql`/`graphql` tagged templates, including `graphql(`...`)` calls
+static GQL_TEMPLATE: Lazy<Regex> = Lazy::new(|| Regex::new(r"\b(?:gql|graphql)\s*(?:\(\s*)?`([^`]*)`").unwrap());
+
+/// Operation header up to the opening brace of its selection set
+static GQL_OPERATION: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"\b(query|mutation|subscription)\b(?:\s+\w+)?\s*(?:\([^)]*\))?\s*(?:@\w+(?:\([^)]*\))?\s*)*\{").unwrap()
+});
+
+/// graphene: `def resolve_user(self, info)`
+static PY_GRAPHENE: Lazy<Regex> = Lazy::new(|| Regex::new(r"\bdef\s+resolve_(\w+)\s*\(").unwrap());
+
+/// ariadne: `@query.field("user")`
+static PY_ARIADNE: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r#"@(query|mutation|subscription)\.field\(\s*["'](\w+)["']"#).unwrap());
+
+/// strawberry: `@strawberry.field` / `@strawberry.mutation` above a def
+static PY_STRAWBERRY: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"@strawberry\.(field|mutation|subscription)\b[^\n]*\n\s*(?:async\s+)?def\s+(\w+)").unwrap()
+});
+
+/// gqlgen: `func (r *queryResolver) User(ctx context.Context, ...)`
+static GO_GQLGEN: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"func\s+\(\s*\w+\s+\*(query|mutation|subscription)Resolver\)\s+(\w+)\s*\(").unwrap()
+});
+
+/// Apollo-style resolver maps: `Query: { user: ..., users() {...} }`
+static JS_RESOLVER_MAP: Lazy<Regex> = Lazy::new(|| Regex::new(r"\b(Query|Mutation|Subscription)\s*:\s*\{").unwrap());
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+enum OperationType {
+    Query,
+    Mutation,
+    Subscription,
+}
+
+impl OperationType {
+    fn from_name(name: &str) -> Option<Self> {
+        match name.to_lowercase().as_str() {
+            "query" | "field" => Some(Self::Query),
+            "mutation" => Some(Self::Mutation),
+            "subscription" => Some(Self::Subscription),
+            _ => None,
+        }
+    }
+
+    fn keyword(&self) -> &'static str {
+        match self {
+            Self::Query => "query",
+            Self::Mutation => "mutation",
+            Self::Subscription => "subscription",
+        }
+    }
+
+    fn root_type(&self) -> &'static str {
+        match self {
+            Self::Query => "Query",
+            Self::Mutation => "Mutation",
+            Self::Subscription => "Subscription",
+        }
+    }
+}
+
+/// Root type (if known) and normalized field name of a resolver
+type ResolverKey = (Option<OperationType>, String);
+
+/// A backend function that resolves a root field
+#[derive(Debug, Clone, PartialEq)]
+struct ResolverDef {
+    /// `None` when the code doesn't say which root type the field is on
+    op: Option<OperationType>,
+    field: String,
+    confidence: f32,
+}
+
+/// GraphQL documents in a file: the whole file for .graphql/.gql, tagged templates otherwise
+fn graphql_documents<'a>(path: &str, content: &'a str) -> Vec<&'a str> {
+    if path.ends_with(".graphql") || path.ends_with(".gql") {
+        return vec![content];
+    }
+    GQL_TEMPLATE
+        .captures_iter(content)
+        .filter_map(|cap| cap.get(1).map(|m| m.as_str()))
+        .collect()
+}
+
+/// Root fields selected by each operation in a document, e.g. `(Query, "user")`
+fn graphql_operations(document: &str) -> Vec<(OperationType, String)> {
+    let mut operations = Vec::new();
+    // Shorthand query: `{ user { id } }`
+    if document.trim_start().starts_with('{') {
+        let body = &document[document.find('{').map_or(0, |i| i + 1)..];
+        operations.extend(selection_fields(body).into_iter().map(|f| (OperationType::Query, f)));
+    }
+    for cap in GQL_OPERATION.captures_iter(document) {
+        let Some(op) = OperationType::from_name(&cap[1]) else { continue };
+        let body = &document[cap.get(0).map_or(0, |m| m.end())..];
+        operations.extend(selection_fields(body).into_iter().map(|f| (op, f)));
+    }
+    operations
+}
+
+/// Root resolvers defined in a backend file
+fn graphql_resolvers(path: &str, content: &str) -> Vec<ResolverDef> {
+    let resolver = |op: Option<OperationType>, field: &str, confidence: f32| ResolverDef {
+        op,
+        field: field.to_string(),
+        confidence,
+    };
+    let mut resolvers = Vec::new();
+
+    if path.ends_with(".py") {
+        for cap in PY_GRAPHENE.captures_iter(content) {
+            resolvers.push(resolver(None, &cap[1], 0.75));
+        }
+        for cap in PY_ARIADNE.captures_iter(content) {
+            resolvers.push(resolver(OperationType::from_name(&cap[1]), &cap[2], 0.85));
+        }
+        // strawberry.field is also used on non-root types, so it's weaker evidence
+        for cap in PY_STRAWBERRY.captures_iter(content) {
+            resolvers.push(resolver(OperationType::from_name(&cap[1]), &cap[2], 0.7));
+        }
+    } else if path.ends_with(".go") {
+        for cap in GO_GQLGEN.captures_iter(content) {
+            resolvers.push(resolver(OperationType::from_name(&cap[1]), &cap[2], 0.85));
+        }
+    } else {
+        for cap in JS_RESOLVER_MAP.captures_iter(content) {
+            let op = OperationType::from_name(&cap[1]);
+            let body = &content[cap.get(0).map_or(0, |m| m.end())..];
+            for key in object_keys(body) {
+                resolvers.push(resolver(op, &key, 0.85));
+            }
+        }
+    }
+    resolvers
+}
+
+/// Matching key for field names across naming conventions: `createUser`,
+/// `create_user`, and gqlgen's `CreateUser` all become `createuser`
+fn resolver_key(name: &str) -> String {
+    name.chars().filter(|c| *c != '_').flat_map(|c| c.to_lowercase()).collect()
+}
+
+#[derive(Debug, PartialEq)]
+enum Token<'a> {
+    Ident(&'a str),
+    Str(&'a str),
+    Spread,
+    Punct(char),
+}
+
+/// Minimal tokenizer shared by the GraphQL and JavaScript scanners. Comments are
+/// `#` in GraphQL and `//`, `/* */` in JavaScript; string contents are kept whole.
+fn tokenize(source: &str, graphql: bool) -> Vec<Token<'_>> {
+    let bytes = source.as_bytes();
+    let mut tokens = Vec::new();
+    let mut i = 0;
+    while i < bytes.len() {
+        let c = bytes[i];
+        let rest = &source[i..];
+        if c.is_ascii_whitespace() || c == b',' && graphql {
+            i += 1;
+        } else if graphql && c == b'#' || !graphql && rest.starts_with("//") {
+            i += rest.find('\n').unwrap_or(rest.len());
+        } else if !graphql && rest.starts_with("/*") {
+            i += rest.find("*/").map_or(rest.len(), |end| end + 2);
+        } else if matches!(c, b'"' | b'\'' | b'`') {
+            let mut end = i + 1;
+            while end < bytes.len() && bytes[end] != c {
+                end += if bytes[end] == b'\\' { 2 } else { 1 };
+            }
+            tokens.push(Token::Str(&source[i + 1..end.min(bytes.len())]));
+            i = end + 1;
+        } else if c.is_ascii_alphabetic() || c == b'_' || c == b'$' {
+            let len = rest
+                .find(|ch: char| !(ch.is_ascii_alphanumeric() || ch == '_' || ch == '$'))
+                .unwrap_or(rest.len());
+            tokens.push(Token::Ident(&rest[..len]));
+            i += len;
+        } else if rest.starts_with("...") {
+            tokens.push(Token::Spread);
+            i += 3;
+        } else {
+            let ch = rest.chars().next().unwrap_or(' ');
+            tokens.push(Token::Punct(ch));
+            i += ch.len_utf8();
+        }
+    }
+    tokens
+}
+
+/// Field names at the top level of a selection set; `body` starts just inside its `{`
+fn selection_fields(body: &str) -> Vec<String> {
+    let tokens = tokenize(body, true);
+    let mut fields = Vec::new();
+    let mut depth = 0usize;
+    let mut i = 0;
+    while i < tokens.len() {
+        match &tokens[i] {
+            Token::Punct('{') | Token::Punct('(') | Token::Punct('[') => depth += 1,
+            Token::Punct('}') | Token::Punct(')') | Token::Punct(']') => {
+                if depth == 0 {
+                    break;
+                }
+                depth -= 1;
+            }
+            // Fragment spreads and directives name something other than a field
+            Token::Spread | Token::Punct('@') if depth == 0 => i += 1,
+            Token::Ident(name) if depth == 0 => {
+                // `alias: field`
+                if tokens.get(i + 1) == Some(&Token::Punct(':')) {
+                    if let Some(Token::Ident(field)) = tokens.get(i + 2) {
+                        fields.push(field.to_string());
+                    }
+                    i += 2;
+                } else {
+                    fields.push(name.to_string());
+                }
+            }
+            _ => {}
+        }
+        i += 1;
+    }
+    fields
+}
+
+/// Property names of a JavaScript object literal; `body` starts just inside its `{`
+fn object_keys(body: &str) -> Vec<String> {
+    let tokens = tokenize(body, false);
+    let mut keys = Vec::new();
+    let mut depth = 0usize;
+    let mut expect_key = true;
+    for (i, token) in tokens.iter().enumerate() {
+        match token {
+            Token::Punct('{') | Token::Punct('(') | Token::Punct('[') => depth += 1,
+            Token::Punct('}') | Token::Punct(')') | Token::Punct(']') => {
+                if depth == 0 {
+                    break;
+                }
+                depth -= 1;
+            }
+            Token::Punct(',') if depth == 0 => expect_key = true,
+            Token::Ident("async" | "get" | "set" | "static") if depth == 0 && expect_key => {}
+            Token::Ident(name) | Token::Str(name) if depth == 0 && expect_key => {
+                // `user: ...` or method shorthand `user(...) {...}`
+                if matches!(tokens.get(i + 1), Some(Token::Punct(':' | '('))) {
+                    keys.push(name.to_string());
+                }
+                expect_key = false;
+            }
+            _ => {}
+        }
+    }
+    keys
+}
+
+const GRPC_SOURCE_GLOB: &str = "**/*.{py,go,rs,java,kt,cs,js,ts,jsx,tsx}";
+
+static PROTO_COMMENT: Lazy<Regex> = Lazy::new(|| Regex::new(r"(?s)//[^\n]*|/\*.*?\*/").unwrap());
+static PROTO_PACKAGE: Lazy<Regex> = Lazy::new(|| Regex::new(r"\bpackage\s+([\w.]+)\s*;").unwrap());
+static PROTO_SERVICE: Lazy<Regex> = Lazy::new(|| Regex::new(r"\bservice\s+(\w+)\s*\{").unwrap());
+static PROTO_RPC: Lazy<Regex> = Lazy::new(|| Regex::new(r"\brpc\s+(\w+)\s*\(").unwrap());
+
+/// Method calls: `stub.GetUser(`, `client.getUser(`, `client.get_user(`
+static METHOD_CALL: Lazy<Regex> = Lazy::new(|| Regex::new(r"\.\s*(\w+)\s*\(").unwrap());
+
+/// Function and method definitions: Python/Rust/Go keywords, Java/TS/JS class methods,
+/// and JS object properties holding functions (grpc-js `addService` implementations)
+static METHOD_DEF: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(concat!(
+        r"\b(?:def|fn|func\s*\([^)]*\))\s+(\w+)\s*[(<]",
+        r"|(?m)^[ \t]*(?:[\w<>\[\],.?]+[ \t]+)*(\w+)\s*\([^;{)]*\)[^;{=]*\{",
+        r"|\b(\w+)\s*:\s*(?:async\b|function\b|\()",
+    ))
+    .unwrap()
+});
+
+/// A service declared in a .proto file
+#[derive(Debug, Clone, PartialEq)]
+struct ProtoService {
+    package: Option<String>,
+    name: String,
+    rpcs: Vec<String>,
+}
+
+impl ProtoService {
+    /// gRPC wire path, e.g. `/users.v1.UserService/GetUser`
+    fn rpc_path(&self, rpc: &str) -> String {
+        match &self.package {
+            Some(package) => format!("/{}.{}/{}", package, self.name, rpc),
+            None => format!("/{}/{}", self.name, rpc),
+        }
+    }
+}
+
+/// Services and their rpcs in a .proto file
+fn parse_proto(content: &str) -> Vec<ProtoService> {
+    let content = PROTO_COMMENT.replace_all(content, "");
+    let package = PROTO_PACKAGE.captures(&content).map(|cap| cap[1].to_string());
+
+    let mut services: Vec<ProtoService> = Vec::new();
+    let starts: Vec<(usize, String)> = PROTO_SERVICE
+        .captures_iter(&content)
+        .map(|cap| (cap.get(0).map_or(0, |m| m.start()), cap[1].to_string()))
+        .collect();
+    for (i, (start, name)) in starts.iter().enumerate() {
+        let end = starts.get(i + 1).map_or(content.len(), |(next, _)| *next);
+        services.push(ProtoService {
+            package: package.clone(),
+            name: name.clone(),
+            rpcs: PROTO_RPC
+                .captures_iter(&content[*start..end])
+                .map(|cap| cap[1].to_string())
+                .collect(),
+        });
+    }
+    services
+}
+
+/// Generated protobuf/gRPC code declares both stubs and servicers, so it would match everything
+fn is_generated_grpc(path: &str) -> bool {
+    let name = path.rsplit('/').next().unwrap_or(path);
+    ["_pb2.py", "_pb2_grpc.py", ".pb.go", "_pb.js", "_pb.ts", "_pb.d.ts", "Grpc.java", "Grpc.cs"]
+        .iter()
+        .any(|suffix| name.ends_with(suffix))
+}
+
+/// A file implementing an rpc
+#[derive(Debug, Clone)]
+struct RpcImplementation {
+    file: String,
+    /// The function defining the rpc, if one was found
+    function: Option<String>,
+    confidence: f32,
+}
+
+/// Rpcs of `service` called in a file that creates one of its generated clients
+fn grpc_client_calls<'a>(content: &str, service: &'a ProtoService) -> Vec<&'a str> {
+    let name = &service.name;
+    // Python `UserServiceStub`, Go `NewUserServiceClient`, JS/Rust/C# `UserServiceClient`,
+    // Java `UserServiceGrpc.newBlockingStub`
+    let is_client = content.contains(&format!("{}Stub", name))
+        || content.contains(&format!("{}Client", name))
+        || content.contains(&format!("{}Grpc.new", name));
+    if !is_client {
+        return Vec::new();
+    }
+
+    let called: HashSet<String> = METHOD_CALL
+        .captures_iter(content)
+        .map(|cap| resolver_key(&cap[1]))
+        .collect();
+    service
+        .rpcs
+        .iter()
+        .filter(|rpc| called.contains(&resolver_key(rpc)))
+        .map(|rpc| rpc.as_str())
+        .collect()
+}
+
+/// Rpcs of `service` in a file that implements it, with the function defining each (if found)
+fn grpc_implementations<'a>(content: &str, service: &'a ProtoService) -> Vec<(&'a str, Option<String>)> {
+    let name = &service.name;
+    // Python `UserServiceServicer`, Go `UnimplementedUserServiceServer`/`RegisterUserServiceServer`,
+    // Java `UserServiceImplBase`, Rust tonic `impl user_service_server::UserService for`,
+    // grpc-js `addService(proto.UserService.service, ...)`
+    let implemented = [
+        format!("{}Servicer", name),
+        format!("Unimplemented{}Server", name),
+        format!("Register{}Server", name),
+        format!("{}ImplBase", name),
+        format!("{}.service", name),
+    ]
+    .iter()
+    .any(|marker| content.contains(marker.as_str()))
+        || Regex::new(&format!(r"\bimpl\s+(?:\w+::)*{}\s+for\b", name)).is_ok_and(|re| re.is_match(content));
+    if !implemented {
+        return Vec::new();
+    }
+
+    let defined: HashMap<String, String> = METHOD_DEF
+        .captures_iter(content)
+        .filter_map(|cap| cap.iter().skip(1).flatten().next().map(|m| m.as_str().to_string()))
+        .map(|function| (resolver_key(&function), function))
+        .collect();
+    service
+        .rpcs
+        .iter()
+        .map(|rpc| (rpc.as_str(), defined.get(&resolver_key(rpc)).cloned()))
+        .collect()
+}
+
+/// ID of the innermost symbol whose lines span `line`
+fn enclosing_symbol(symbols: &[Node], line: usize) -> Option<&str> {
+    let span = |node: &Node, key: &str| node.data.get(key).and_then(|v| v.as_u64()).map(|l| l as usize);
+    symbols
+        .iter()
+        .filter_map(|node| Some((node, span(node, "line_start")?, span(node, "line_end")?)))
+        .filter(|(_, start, end)| (*start..=*end).contains(&line))
+        .min_by_key(|(_, start, end)| end - start)
+        .map(|(node, _, _)| node.id.as_str())
+}
+
+/// Normalize an API path for matching
+fn normalize_path(path: &str) -> String {
+    // Flask/Django/Rocket converters: `<int:user_id>` and `<user_id>` become `{user_id}`
+    static ANGLE_PARAM: Lazy<Regex> = Lazy::new(|| Regex::new(r"<(?:\w+:)?(\w+)>").unwrap());
+    // Express/Axum segments: `:user_id` becomes `{user_id}`
+    static COLON_PARAM: Lazy<Regex> = Lazy::new(|| Regex::new(r"/:(\w+)").unwrap());
+
+    // Replace param placeholders first (before lowercasing)
+    let path = path.trim_start_matches('^').trim_end_matches('$');
+    let path = ANGLE_PARAM.replace_all(path, "{$1}");
+    COLON_PARAM
+        .replace_all(&path, "/{$1}")
+        .trim_start_matches('/')
+        .trim_end_matches('/')
+        .replace("${", "{")
+        .to_lowercase()
+        // Add more normalizations as needed
+}
+
+/// Whether a detected method is compatible with a spec operation's (unknown matches anything)
+fn method_agrees(method: Option<&str>, spec_method: &str) -> bool {
+    method.is_none_or(|m| m.eq_ignore_ascii_case(spec_method))
+}
+
+/// The spec operation documenting a server route. Spec paths include the API's base path,
+/// which routes mounted under a router prefix may not show.
+fn spec_operation<'a>(spec: &'a ApiSpec, route: &str, method: Option<&str>) -> Option<&'a SpecOperation> {
+    spec.operations
+        .iter()
+        .filter(|op| method_agrees(method, &op.method))
+        .filter_map(|op| Some((op, path_match_score(&normalize_path(&op.path), route)?)))
+        .max_by(|a, b| a.1.total_cmp(&b.1))
+        .map(|(op, _)| op)
+}
+
+/// Compare detected REST endpoints (client calls, server routes) with the spec
+fn reconcile_spec<'a>(spec: &ApiSpec, endpoints: impl Iterator<Item = &'a (Endpoints, Endpoints)>) -> OpenApiReport {
+    let mut undocumented = Vec::new();
+    let mut called: HashSet<usize> = HashSet::new();
+    for (client_calls, server_routes) in endpoints {
+        for (route, handlers) in server_routes {
+            for handler in handlers {
+                let method = handler.method.as_deref();
+                if spec_operation(spec, route, method).is_none() {
+                    undocumented.push(format!("{} /{} ({})", method.unwrap_or("ANY"), route, handler.file));
+                }
+            }
+        }
+        for (path, callers) in client_calls {
+            for (index, op) in spec.operations.iter().enumerate() {
+                let spec_path = normalize_path(&op.path);
+                // Clients may call through a base URL that already includes the spec's base path
+                let matches = path_match_score(path, &spec_path).or_else(|| path_match_score(&spec_path, path));
+                if matches.is_some() && callers.iter().any(|c| method_agrees(c.method.as_deref(), &op.method)) {
+                    called.insert(index);
+                }
+            }
+        }
+    }
+
+    let mut uncalled: Vec<String> = spec
+        .operations
+        .iter()
+        .enumerate()
+        .filter(|(index, _)| !called.contains(index))
+        .map(|(_, op)| format!("{} {}", op.method, op.path))
+        .collect();
+    undocumented.sort();
+    undocumented.dedup();
+    uncalled.sort();
+
+    OpenApiReport {
+        spec_file: spec.file.clone(),
+        operations: spec.operations.len(),
+        undocumented_routes: undocumented,
+        uncalled_operations: uncalled,
+    }
+}
+
+/// HTTP method of a client call; fetch defaults to GET
+fn client_method(cap: &regex::Captures) -> Option<String> {
+    let method = cap.name("method").or(cap.name("fetch_method"));
+    Some(method.map_or("GET".to_string(), |m| m.as_str().to_uppercase()))
+}
+
+/// Path segments for matching, with parameters as `None` and any scheme, host, or
+/// leading base-URL variable (`${API_URL}/users`) removed
+fn route_segments(path: &str) -> Vec<Option<&str>> {
+    let path = match path.find("://") {
+        Some(scheme) => path[scheme + 3..].find('/').map_or("", |host| &path[scheme + 3 + host..]),
+        None => path,
+    };
+    path.split('/')
+        .filter(|segment| !segment.is_empty())
+        .map(|segment| Some(segment).filter(|s| !(s.starts_with('{') && s.ends_with('}'))))
+        .skip_while(|segment| segment.is_none())
+        .collect()
+}
+
+/// How well a client path matches a server route, or `None` if it can't be the same endpoint.
+/// Parameters match any segment, but a parameter facing a literal costs confidence; a route
+/// matching only the end of the client path (mounted under an unseen prefix) scores lowest.
+fn path_match_score(client_path: &str, server_path: &str) -> Option<f32> {
+    let client = route_segments(client_path);
+    let server = route_segments(server_path);
+    if server.is_empty() || client.len() < server.len() {
+        return None;
+    }
+
+    let offset = client.len() - server.len();
+    let mut score = if offset == 0 { 0.8 } else { 0.5 };
+    for (c, s) in client[offset..].iter().zip(&server) {
+        match (c, s) {
+            (Some(c), Some(s)) if c != s => return None,
+            (Some(_), Some(_)) | (None, None) => {}
+            _ => score -= 0.15,
+        }
+    }
+
+    // A bare `/{id}` suffix says nothing about which resource is meant
+    let literal = server.iter().any(|s| s.is_some());
+    (literal && score >= 0.4).then_some(score)
+}
+
+/// Simple glob matching
 fn matches_glob(path: &str, pattern: &str) -> bool {
     // Handle common patterns
     if pattern.contains("**/*.") {
@@ -291,7 +1934,7 @@ mod tests {
 
         let mut paths = Vec::new();
         for cap in rule.client_pattern.captures_iter(content) {
-            if let Some(path) = cap.get(1) {
+            if let Some(path) = cap.name("path") {
                 paths.push(path.as_str());
             }
         }
@@ -300,5 +1943,834 @@ mod tests {
         assert!(paths.contains(&"/api/users"));
         assert!(paths.contains(&"/api/posts"));
         assert!(paths.contains(&"/api/comments"));
+
+        let content = r#"
+            fetch(`${API_URL}/api/users/${user.id}?full=1`)
+            fetch("/api/users", { headers, method: "post" })
+        "#;
+        let calls: Vec<_> = rule
+            .client_pattern
+            .captures_iter(content)
+            .map(|cap| (normalize_path(&cap["path"]), client_method(&cap)))
+            .collect();
+        assert_eq!(
+            calls,
+            vec![
+                ("{api_url}/api/users/{user.id}".to_string(), Some("GET".to_string())),
+                ("api/users".to_string(), Some("POST".to_string())),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_path_match_score() {
+        // Parameter spellings don't matter
+        assert_eq!(path_match_score("users/{id}", "users/{user_id}"), Some(0.8));
+        assert_eq!(path_match_score("{api_url}/users/{user.id}", "users/{id}"), Some(0.8));
+        assert_eq!(path_match_score("http://localhost:8000/users", "users"), Some(0.8));
+
+        // A parameter facing a literal is weaker, and exact literals win
+        let me = path_match_score("users/me", "users/me").unwrap();
+        let param = path_match_score("users/me", "users/{id}").unwrap();
+        assert!(me > param);
+
+        // Routes mounted under a prefix the server code doesn't show
+        assert_eq!(path_match_score("api/v1/users", "users"), Some(0.5));
+        assert_eq!(path_match_score("users", "api/users"), None);
+        assert_eq!(path_match_score("users/{id}", "posts/{id}"), None);
+        assert_eq!(path_match_score("users/5", "{id}"), None);
+    }
+
+    async fn setup() -> (tempfile::TempDir, Arc<Store>, Arc<Config>) {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let root = temp_dir.path();
+        let config = Arc::new(Config {
+            project_root: root.to_path_buf(),
+            cache_dir: root.join("cache"),
+            codegraph_dir: root.join(".codegraph"),
+            store_db_path: root.join("cache/store.db"),
+            learning_db_path: root.join(".codegraph/learning.db"),
+            settings: crate::config::ConfigFile::default().into(),
+        });
+        let store = Arc::new(Store::open(&config).await.unwrap());
+        (temp_dir, store, config)
+    }
+
+    /// Write files and register them as indexed
+    async fn add_files(root: &std::path::Path, store: &Store, files: &[(&str, &str)]) {
+        for (path, content) in files {
+            let path_buf = root.join(path);
+            std::fs::create_dir_all(path_buf.parent().unwrap()).unwrap();
+            std::fs::write(path_buf, content).unwrap();
+            store.upsert_file_meta(path, 0, "hash").await.unwrap();
+        }
+    }
+
+    #[tokio::test]
+    async fn test_infer_rest_connections() {
+        let (temp_dir, store, config) = setup().await;
+        add_files(
+            temp_dir.path(),
+            &store,
+            &[
+                ("web/api.ts", "fetch('/api/users/:id');\naxios.post('/api/users');\n"),
+                ("backend/main.py", "@app.get('/api/users/{id}')\ndef user(id): ...\n"),
+                ("backend/urls.py", "urlpatterns = [path('api/users/', views.users)]\n"),
+            ],
+        )
+        .await;
+
+        let inferrer = CrossLanguageInferrer::new(store, config);
+        let stats = inferrer.infer(true).await.unwrap();
+        // Client calls are counted once even though several rules share them
+        assert_eq!(stats.client_calls_found, 2);
+        assert_eq!(stats.connections_made, 2);
+
+        let connections = inferrer.get_api_connections("backend/main.py").await.unwrap();
+        assert_eq!(connections.len(), 1);
+        assert_eq!(connections[0].api_path, "api/users/{id}");
+        assert_eq!(connections[0].method.as_deref(), Some("GET"));
+
+        // Django routes don't name a method, so the client's is kept
+        let connections = inferrer.get_api_connections("backend/urls.py").await.unwrap();
+        assert_eq!(connections[0].client_file, "web/api.ts");
+        assert_eq!(connections[0].method.as_deref(), Some("POST"));
+    }
+
+    #[tokio::test]
+    async fn test_infer_rest_requires_method_agreement() {
+        let (temp_dir, store, config) = setup().await;
+        add_files(
+            temp_dir.path(),
+            &store,
+            &[
+                ("web/api.ts", "axios.delete(`/api/users/${id}`);\nfetch('/api/users/me');\n"),
+                (
+                    "server/app.js",
+                    "router.get('/api/users/:id', show);\nrouter.get('/api/users/me', me);\n",
+                ),
+            ],
+        )
+        .await;
+
+        let inferrer = CrossLanguageInferrer::new(store, config);
+        let stats = inferrer.infer(true).await.unwrap();
+        // DELETE has no route, and `/api/users/me` only goes to its exact route
+        assert_eq!(stats.connections_made, 1);
+        let connections = inferrer.get_api_connections("web/api.ts").await.unwrap();
+        assert_eq!(connections[0].api_path, "api/users/me");
+        assert!((connections[0].confidence - 0.9).abs() < 1e-6);
+    }
+
+    #[tokio::test]
+    async fn test_reviewed_connections_survive_rebuilds() {
+        let (temp_dir, store, config) = setup().await;
+        add_files(
+            temp_dir.path(),
+            &store,
+            &[
+                ("web/api.ts", "fetch('/api/users');\nfetch('/api/health');\n"),
+                (
+                    "backend/main.py",
+                    "@app.get('/api/users')\ndef users(): ...\n@app.get('/health')\ndef health(): ...\n",
+                ),
+            ],
+        )
+        .await;
+
+        let inferrer = CrossLanguageInferrer::new(store, config.clone());
+        assert_eq!(inferrer.infer(true).await.unwrap().connections_made, 2);
+
+        let confirm = inferrer
+            .review_connection("web/api.ts", "backend/main.py", "api/users", Verdict::Confirmed)
+            .await
+            .unwrap();
+        assert_eq!(confirm, ReviewOutcome::Updated);
+        let reject = inferrer
+            .review_connection("web/api.ts", "backend/main.py", "health", Verdict::Rejected)
+            .await
+            .unwrap();
+        assert_eq!(reject, ReviewOutcome::Updated);
+        let missing = inferrer
+            .review_connection("web/api.ts", "backend/main.py", "api/orders", Verdict::Rejected)
+            .await
+            .unwrap();
+        assert_eq!(missing, ReviewOutcome::NotFound);
+
+        // The rejected match isn't resurrected, and the confirmed one keeps full confidence
+        let stats = inferrer.infer(true).await.unwrap();
+        assert_eq!(stats.connections_made, 1);
+        let connections = inferrer.get_api_connections("web/api.ts").await.unwrap();
+        assert_eq!(connections.len(), 1);
+        assert_eq!(connections[0].api_path, "api/users");
+        assert!(connections[0].confirmed);
+        assert_eq!(connections[0].confidence, 1.0);
+
+        // Confirmed connections are kept even when the code no longer shows them
+        std::fs::write(temp_dir.path().join("web/api.ts"), "").unwrap();
+        inferrer.infer(true).await.unwrap();
+        assert_eq!(inferrer.get_api_connections("web/api.ts").await.unwrap().len(), 1);
+
+        // Confirming a rejected connection lifts the rejection
+        let restore = inferrer
+            .review_connection("web/api.ts", "backend/main.py", "health", Verdict::Confirmed)
+            .await
+            .unwrap();
+        assert_eq!(restore, ReviewOutcome::Pending);
+        std::fs::write(temp_dir.path().join("web/api.ts"), "fetch('/api/health');\n").unwrap();
+        inferrer.infer(true).await.unwrap();
+        let connections = inferrer.get_api_connections("health").await.unwrap();
+        assert_eq!(connections.len(), 1);
+        assert!(connections[0].confirmed);
+    }
+
+    #[tokio::test]
+    async fn test_endpoint_and_route_queries() {
+        let (temp_dir, store, config) = setup().await;
+        add_files(
+            temp_dir.path(),
+            &store,
+            &[
+                ("web/orders.ts", "axios.post('/api/orders');\nfetch(`/api/orders/${id}`);\n"),
+                ("web/admin.ts", "axios.delete(`/api/orders/${id}`);\n"),
+                (
+                    "backend/orders.py",
+                    concat!(
+                        "@app.post('/api/orders')\ndef create_order(order): ...\n\n",
+                        "@app.get('/api/orders/{id}')\ndef get_order(id): ...\n\n",
+                        "@app.delete('/api/orders/{id}')\ndef delete_order(id): ...\n",
+                    ),
+                ),
+            ],
+        )
+        .await;
+
+        let inferrer = CrossLanguageInferrer::new(store, config);
+        inferrer.infer(true).await.unwrap();
+
+        let callers = inferrer.get_endpoint_connections("/api/orders", Some("POST")).await.unwrap();
+        assert_eq!(callers.len(), 1);
+        assert_eq!(callers[0].client_file, "web/orders.ts");
+        assert!(inferrer.get_endpoint_connections("/api/orders", Some("GET")).await.unwrap().is_empty());
+
+        // Concrete paths find their parameterized route
+        let callers = inferrer.get_endpoint_connections("/api/orders/42", None).await.unwrap();
+        let mut clients: Vec<_> = callers.iter().map(|c| c.client_file.as_str()).collect();
+        clients.sort();
+        assert_eq!(clients, vec!["web/admin.ts", "web/orders.ts"]);
+
+        let routes = inferrer.file_routes("backend/orders.py");
+        assert_eq!(routes.len(), 3);
+        let callers = inferrer.get_route_connections(&routes[2]).await.unwrap();
+        assert_eq!(callers.len(), 1);
+        assert_eq!(callers[0].client_file, "web/admin.ts");
+    }
+
+    #[tokio::test]
+    async fn test_payload_mismatch_warnings() {
+        let (temp_dir, store, config) = setup().await;
+        add_files(
+            temp_dir.path(),
+            &store,
+            &[
+                (
+                    "web/types.ts",
+                    "export interface NewUser { name: string }\nexport interface User { id: number; userName: string }\n",
+                ),
+                (
+                    "web/api.ts",
+                    concat!(
+                        "export const createUser = (body: NewUser) => axios.post<User>('/api/users', body);\n",
+                        "export const health = () => fetch('/api/health');\n",
+                    ),
+                ),
+                (
+                    "backend/main.py",
+                    concat!(
+                        "class UserIn(BaseModel):\n    name: str\n\n",
+                        "class UserOut(BaseModel):\n    id: int\n    user_name: str\n\n",
+                        "@app.post('/api/users', response_model=UserOut)\ndef create(user: UserIn): ...\n\n",
+                        "@app.get('/api/health')\ndef health(): ...\n",
+                    ),
+                ),
+            ],
+        )
+        .await;
+
+        let inferrer = CrossLanguageInferrer::new(store, config);
+        let stats = inferrer.infer(true).await.unwrap();
+        assert_eq!(stats.connections_made, 2);
+        assert_eq!(stats.payload_mismatches.len(), 1);
+        assert_eq!(stats.payload_mismatches[0].api_path, "api/users");
+
+        let connections = inferrer.get_api_connections("web/api.ts").await.unwrap();
+        let create = connections.iter().find(|c| c.api_path == "api/users").unwrap();
+        assert_eq!(
+            create.warnings,
+            vec!["response field `userName` (User): handler returns `user_name` (UserOut)"]
+        );
+        let health = connections.iter().find(|c| c.api_path == "api/health").unwrap();
+        assert!(health.warnings.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_openapi_reconciliation() {
+        let (temp_dir, store, config) = setup().await;
+        let spec = r#"
+openapi: 3.0.0
+servers:
+  - url: /api
+paths:
+  /users/{id}:
+    get:
+      operationId: getUser
+      summary: Fetch one user
+      tags: [users]
+  /users:
+    post:
+      operationId: createUser
+  /health:
+    get:
+      operationId: health
+"#;
+        add_files(
+            temp_dir.path(),
+            &store,
+            &[
+                ("web/api.ts", "fetch(`/api/users/${id}`);\naxios.post('/api/users');\n"),
+                (
+                    "server/app.js",
+                    "router.get('/api/users/:id', show);\nrouter.delete('/api/users/:id', destroy);\n",
+                ),
+            ],
+        )
+        .await;
+        std::fs::write(temp_dir.path().join("openapi.yaml"), spec).unwrap();
+
+        let inferrer = CrossLanguageInferrer::new(store, config);
+        let stats = inferrer.infer(true).await.unwrap();
+        let report = stats.openapi.unwrap();
+        assert_eq!(report.spec_file, "openapi.yaml");
+        assert_eq!(report.operations, 3);
+        assert_eq!(report.undocumented_routes, vec!["DELETE /api/users/{id} (server/app.js)"]);
+        assert_eq!(report.uncalled_operations, vec!["GET /api/health"]);
+
+        let connections = inferrer.get_api_connections("web/api.ts").await.unwrap();
+        assert_eq!(connections.len(), 1);
+        let operation = connections[0].operation.as_ref().unwrap();
+        assert_eq!(operation.operation_id.as_deref(), Some("getUser"));
+        assert_eq!(operation.summary.as_deref(), Some("Fetch one user"));
+    }
+
+    /// (method, normalized path) for each route the named rule finds
+    fn server_routes(rule_type: &str, content: &str) -> Vec<(Option<String>, String)> {
+        let rules = CrossLanguageInferrer::default_rules();
+        let rule = rules.iter().find(|r| r.rule_type == rule_type).unwrap();
+        rule.server_routes("routes", content)
+            .into_iter()
+            .map(|route| (route.method, route.path))
+            .collect()
+    }
+
+    #[test]
+    fn test_python_routes() {
+        let fastapi = r#"
+@app.get("/api/users/{user_id}")
+async def get_user(user_id: int): ...
+
+@router.post('/api/users')
+def create_user(): ...
+"#;
+        assert_eq!(
+            server_routes("rest_python", fastapi),
+            vec![
+                (Some("GET".to_string()), "api/users/{user_id}".to_string()),
+                (Some("POST".to_string()), "api/users".to_string()),
+            ]
+        );
+
+        let flask = "@bp.route('/api/posts/<int:post_id>', methods=['GET'])\ndef post(post_id): ...\n";
+        assert_eq!(server_routes("rest_python", flask), vec![(None, "api/posts/{post_id}".to_string())]);
+
+        let django = r#"
+urlpatterns = [
+    path("api/users/<int:pk>/", views.user_detail),
+    re_path(r"^api/health/$", views.health),
+]
+"#;
+        let routes: Vec<String> = server_routes("rest_django", django).into_iter().map(|(_, p)| p).collect();
+        assert_eq!(routes, vec!["api/users/{pk}", "api/health"]);
+    }
+
+    #[test]
+    fn test_rust_routes() {
+        let actix = r#"
+#[get("/api/users/{id}")]
+async fn user(path: web::Path<u32>) -> impl Responder {}
+
+#[post("/api/users")]
+async fn create_user() -> impl Responder {}
+"#;
+        assert_eq!(
+            server_routes("rest_rust_attr", actix),
+            vec![
+                (Some("GET".to_string()), "api/users/{id}".to_string()),
+                (Some("POST".to_string()), "api/users".to_string()),
+            ]
+        );
+
+        let rocket = "#[delete(\"/api/posts/<id>\")]\nfn delete_post(id: u32) {}\n";
+        assert_eq!(
+            server_routes("rest_rust_attr", rocket),
+            vec![(Some("DELETE".to_string()), "api/posts/{id}".to_string())]
+        );
+
+        let axum = r#"
+let app = Router::new()
+    .route("/api/users/:id", get(get_user).put(update_user))
+    .route("/api/health", any(health));
+App::new().service(web::resource("/api/items").route(web::get().to(items)));
+"#;
+        assert_eq!(
+            server_routes("rest_rust_router", axum),
+            vec![
+                (Some("GET".to_string()), "api/users/{id}".to_string()),
+                (None, "api/health".to_string()),
+                (None, "api/items".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_spring_and_nest_routes() {
+        let spring = r#"
+@RestController
+@RequestMapping("/api/users")
+public class UserController {
+    @GetMapping
+    public List<User> list() {}
+
+    @GetMapping("/{id}")
+    public User get(@PathVariable Long id) {}
+
+    @RequestMapping(value = "/search", method = RequestMethod.POST)
+    public List<User> search() {}
+}
+"#;
+        assert_eq!(
+            server_routes("rest_spring", spring),
+            vec![
+                (Some("GET".to_string()), "api/users".to_string()),
+                (Some("GET".to_string()), "api/users/{id}".to_string()),
+                (None, "api/users/search".to_string()),
+            ]
+        );
+
+        let kotlin = r#"
+@RestController
+class Health {
+    @PostMapping(path = ["/api/ping"])
+    fun ping() {}
+}
+"#;
+        assert_eq!(
+            server_routes("rest_spring", kotlin),
+            vec![(Some("POST".to_string()), "api/ping".to_string())]
+        );
+
+        let nest = r#"
+@Controller('api/posts')
+export class PostsController {
+  @Get()
+  findAll() {}
+
+  @Patch(':id')
+  update(@Param('id') id: string, @Body() dto: UpdatePostDto) {}
+}
+"#;
+        assert_eq!(
+            server_routes("rest_nest", nest),
+            vec![
+                (Some("GET".to_string()), "api/posts".to_string()),
+                (Some("PATCH".to_string()), "api/posts/{id}".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_graphql_operations() {
+        let content = r#"
+            const GET_USER = gql`
+                # the current user
+                query GetUser($id: ID!) @cached {
+                    me: currentUser(id: $id) { id posts(first: 5) { title } }
+                    ...Flags
+                    settings
+                }
+            `;
+            const ADD = graphql(`mutation { createPost(input: { title: "x" }) { id } }`);
+        "#;
+        let documents = graphql_documents("web/api.ts", content);
+        assert_eq!(documents.len(), 2);
+
+        let operations: Vec<_> = documents.into_iter().flat_map(graphql_operations).collect();
+        assert_eq!(
+            operations,
+            vec![
+                (OperationType::Query, "currentUser".to_string()),
+                (OperationType::Query, "settings".to_string()),
+                (OperationType::Mutation, "createPost".to_string()),
+            ]
+        );
+
+        let shorthand = graphql_operations("{ users { id } }");
+        assert_eq!(shorthand, vec![(OperationType::Query, "users".to_string())]);
+    }
+
+    #[test]
+    fn test_graphql_resolvers() {
+        let python = r#"
+class Query(graphene.ObjectType):
+    def resolve_current_user(self, info, id):
+        pass
+
+@mutation.field("createPost")
+def create_post(_, info, input):
+    pass
+"#;
+        let fields: Vec<_> = graphql_resolvers("server/schema.py", python)
+            .into_iter()
+            .map(|r| (r.op, r.field))
+            .collect();
+        assert_eq!(
+            fields,
+            vec![
+                (None, "current_user".to_string()),
+                (Some(OperationType::Mutation), "createPost".to_string()),
+            ]
+        );
+
+        let js = r#"
+const resolvers = {
+  Query: {
+    // comment: not a key
+    currentUser: (_, { id }) => users.get(id),
+    async settings(parent, args) { return { theme: "dark" }; },
+    'feed': feedResolver,
+  },
+  Mutation: { createPost },
+};
+"#;
+        let fields: Vec<_> = graphql_resolvers("server/resolvers.js", js)
+            .into_iter()
+            .map(|r| (r.op, r.field))
+            .collect();
+        assert_eq!(
+            fields,
+            vec![
+                (Some(OperationType::Query), "currentUser".to_string()),
+                (Some(OperationType::Query), "settings".to_string()),
+                (Some(OperationType::Query), "feed".to_string()),
+            ]
+        );
+
+        let go = "func (r *mutationResolver) CreatePost(ctx context.Context, input NewPost) (*Post, error) {";
+        let resolvers = graphql_resolvers("graph/schema.resolvers.go", go);
+        assert_eq!(resolvers[0].op, Some(OperationType::Mutation));
+        assert_eq!(resolver_key(&resolvers[0].field), resolver_key("createPost"));
+        assert_eq!(resolver_key("current_user"), resolver_key("currentUser"));
+    }
+
+    #[tokio::test]
+    async fn test_infer_graphql_connections() {
+        let (temp_dir, store, config) = setup().await;
+        let root = temp_dir.path();
+
+        std::fs::create_dir_all(root.join("web/queries")).unwrap();
+        std::fs::create_dir_all(root.join("server")).unwrap();
+        std::fs::write(root.join("web/app.ts"), "const Q = gql`query { currentUser { id } }`;\n").unwrap();
+        std::fs::write(root.join("web/queries/post.graphql"), "mutation Add { createPost { id } }\n").unwrap();
+        std::fs::write(
+            root.join("server/schema.py"),
+            "def resolve_current_user(root, info):\n    pass\n\ndef resolve_create_post(root, info):\n    pass\n",
+        )
+        .unwrap();
+        for file in ["web/app.ts", "server/schema.py"] {
+            store.upsert_file_meta(file, 0, "hash").await.unwrap();
+        }
+
+        let inferrer = CrossLanguageInferrer::new(store, config);
+        let stats = inferrer.infer(true).await.unwrap();
+        assert_eq!(stats.connections_made, 1);
+
+        let connections = inferrer.get_api_connections("Query.currentUser").await.unwrap();
+        assert_eq!(connections.len(), 1);
+        assert_eq!(connections[0].client_file, "web/app.ts");
+        assert_eq!(connections[0].server_file, "server/schema.py");
+        assert_eq!(connections[0].method.as_deref(), Some("query"));
+
+        // Untyped graphene resolvers only answer queries
+        assert!(inferrer.get_api_connections("Mutation.createPost").await.unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_parse_proto() {
+        let proto = r#"
+syntax = "proto3";
+package users.v1;
+
+// rpc Commented(X) returns (Y);
+service UserService {
+  rpc GetUser (GetUserRequest) returns (User);
+  rpc ListUsers (ListUsersRequest) returns (stream User) {}
+}
+
+service Health { rpc Check(HealthCheckRequest) returns (HealthCheckResponse); }
+"#;
+        let services = parse_proto(proto);
+        assert_eq!(services.len(), 2);
+        assert_eq!(services[0].name, "UserService");
+        assert_eq!(services[0].rpcs, vec!["GetUser", "ListUsers"]);
+        assert_eq!(services[1].rpcs, vec!["Check"]);
+        assert_eq!(services[0].rpc_path("GetUser"), "/users.v1.UserService/GetUser");
+    }
+
+    #[test]
+    fn test_grpc_clients_and_implementations() {
+        let service = ProtoService {
+            package: None,
+            name: "UserService".to_string(),
+            rpcs: vec!["GetUser".to_string(), "ListUsers".to_string()],
+        };
+
+        let python = "stub = users_pb2_grpc.UserServiceStub(channel)\nuser = stub.GetUser(request)\n";
+        assert_eq!(grpc_client_calls(python, &service), vec!["GetUser"]);
+        let ts = "const client = new UserServiceClient(addr);\nawait client.listUsers(req);\n";
+        assert_eq!(grpc_client_calls(ts, &service), vec!["ListUsers"]);
+        assert!(grpc_client_calls("db.GetUser(id)", &service).is_empty());
+
+        let go = r#"
+type server struct { pb.UnimplementedUserServiceServer }
+
+func (s *server) GetUser(ctx context.Context, req *pb.GetUserRequest) (*pb.User, error) {
+"#;
+        assert_eq!(
+            grpc_implementations(go, &service),
+            vec![("GetUser", Some("GetUser".to_string())), ("ListUsers", None)]
+        );
+
+        let rust = r#"
+#[tonic::async_trait]
+impl user_service_server::UserService for Users {
+    async fn get_user(&self, request: Request<GetUserRequest>) -> Result<Response<User>, Status> {
+"#;
+        assert_eq!(grpc_implementations(rust, &service)[0], ("GetUser", Some("get_user".to_string())));
+
+        let java = r#"
+public class UserServiceImpl extends UserServiceGrpc.UserServiceImplBase {
+    @Override
+    public void listUsers(ListUsersRequest request, StreamObserver<User> responseObserver) {
+"#;
+        assert_eq!(grpc_implementations(java, &service)[1], ("ListUsers", Some("listUsers".to_string())));
+        assert!(grpc_implementations("def GetUser(self): pass", &service).is_empty());
+
+        assert!(is_generated_grpc("gen/users_pb2_grpc.py"));
+        assert!(is_generated_grpc("gen/users_grpc.pb.go"));
+        assert!(!is_generated_grpc("server/users.go"));
+    }
+
+    #[tokio::test]
+    async fn test_infer_grpc_calls_api_edges() {
+        let (temp_dir, store, config) = setup().await;
+        let root = temp_dir.path();
+
+        std::fs::create_dir_all(root.join("proto")).unwrap();
+        std::fs::create_dir_all(root.join("client")).unwrap();
+        std::fs::create_dir_all(root.join("server")).unwrap();
+        std::fs::write(
+            root.join("proto/users.proto"),
+            "package users;\nservice UserService {\n  rpc GetUser (GetUserRequest) returns (User);\n}\n",
+        )
+        .unwrap();
+        std::fs::write(
+            root.join("client/app.py"),
+            "stub = users_pb2_grpc.UserServiceStub(channel)\nstub.GetUser(req)\n",
+        )
+        .unwrap();
+        std::fs::write(
+            root.join("server/main.go"),
+            "pb.RegisterUserServiceServer(s, &server{})\n\nfunc (s *server) GetUser(ctx context.Context) {}\n",
+        )
+        .unwrap();
+        for file in ["client/app.py", "server/main.go"] {
+            store.upsert_file_meta(file, 0, "hash").await.unwrap();
+        }
+        store
+            .upsert_node("server/main.go::GetUser", "code", "function", &json!({"name": "GetUser"}))
+            .await
+            .unwrap();
+
+        let inferrer = CrossLanguageInferrer::new(store.clone(), config);
+        let stats = inferrer.infer(true).await.unwrap();
+        assert_eq!(stats.connections_made, 1);
+
+        let connections = inferrer.get_api_connections("client/app.py").await.unwrap();
+        assert_eq!(connections.len(), 1);
+        assert_eq!(connections[0].api_path, "/users.UserService/GetUser");
+        assert_eq!(connections[0].method.as_deref(), Some("grpc"));
+
+        let edges = store.get_edges_from("file::client/app.py").await.unwrap();
+        assert_eq!(edges.len(), 1);
+        assert_eq!(edges[0].kind, "calls_api");
+        assert_eq!(edges[0].target, "server/main.go::GetUser");
+
+        // A forced rebuild starts from scratch
+        inferrer.infer(true).await.unwrap();
+        assert_eq!(store.get_edges_from("file::client/app.py").await.unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_infer_topic_edges() {
+        let (temp_dir, store, config) = setup().await;
+        add_files(
+            temp_dir.path(),
+            &store,
+            &[
+                ("orders/publish.ts", "await producer.send({ topic: 'orders', messages });\n"),
+                ("billing/worker.py", "consumer = KafkaConsumer('orders', group_id='billing')\n"),
+                ("chat/server.py", "redis.publish('orders', msg)\n"),
+            ],
+        )
+        .await;
+
+        let inferrer = CrossLanguageInferrer::new(store.clone(), config);
+        let stats = inferrer.infer(true).await.unwrap();
+        assert_eq!(stats.topics_found, 2);
+        assert_eq!(stats.connections_made, 1);
+
+        let connections = inferrer.get_api_connections("billing/worker.py").await.unwrap();
+        assert_eq!(connections.len(), 1);
+        assert_eq!(connections[0].client_file, "orders/publish.ts");
+        assert_eq!(connections[0].api_path, "topic:orders");
+        assert_eq!(connections[0].method.as_deref(), Some("kafka"));
+
+        let topic = store.get_node("topic::kafka:orders").await.unwrap().unwrap();
+        assert_eq!(topic.kind, "topic");
+        let published = store.get_edges_from("file::orders/publish.ts").await.unwrap();
+        assert_eq!(published.len(), 1);
+        assert_eq!((published[0].kind.as_str(), published[0].target.as_str()), ("publishes_to", "topic::kafka:orders"));
+        let subscribed = store.get_edges_from("file::billing/worker.py").await.unwrap();
+        assert_eq!(subscribed[0].kind, "subscribes_to");
+
+        // Redis has its own `orders` channel with no subscribers
+        assert!(store.get_node("topic::redis:orders").await.unwrap().is_some());
+        assert!(inferrer.get_api_connections("chat/server.py").await.unwrap().is_empty());
+
+        // A forced rebuild starts from scratch
+        inferrer.infer(true).await.unwrap();
+        assert_eq!(store.get_edges_from("file::orders/publish.ts").await.unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_infer_channel_connections() {
+        let (temp_dir, store, config) = setup().await;
+        add_files(
+            temp_dir.path(),
+            &store,
+            &[
+                (
+                    "web/live.ts",
+                    concat!(
+                        "const ws = new WebSocket(`wss://${location.host}/ws/prices`);\n",
+                        "const socket = io('/chat');\n",
+                        "socket.emit('chat:send', text);\n",
+                        "socket.on('chat:message', render);\n",
+                        "const feed = new EventSource('/api/feed');\n",
+                    ),
+                ),
+                (
+                    "server/realtime.js",
+                    concat!(
+                        "const wss = new WebSocketServer({ server, path: '/ws/prices' });\n",
+                        "const chat = io.of('/chat');\n",
+                        "chat.on('connection', (socket) => {\n",
+                        "  socket.on('chat:send', (text) => socket.broadcast.emit('chat:message', text));\n",
+                        "});\n",
+                    ),
+                ),
+                (
+                    "api/feed.py",
+                    "@app.get('/api/feed')\nasync def feed():\n    return EventSourceResponse(updates())\n",
+                ),
+            ],
+        )
+        .await;
+
+        let inferrer = CrossLanguageInferrer::new(store.clone(), config);
+        let stats = inferrer.infer(true).await.unwrap();
+        // ws, socket.io, and SSE endpoints plus two events
+        assert_eq!(stats.channels_found, 5);
+
+        let mut connections: Vec<_> = inferrer
+            .get_api_connections("web/live.ts")
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|c| (c.client_file, c.server_file, c.api_path, c.method.unwrap_or_default()))
+            .collect();
+        connections.sort();
+        let connection = |client: &str, server: &str, path: &str, method: &str| {
+            (client.to_string(), server.to_string(), path.to_string(), method.to_string())
+        };
+        assert_eq!(
+            connections,
+            vec![
+                connection("server/realtime.js", "web/live.ts", "event:chat:message", "socket.io"),
+                connection("web/live.ts", "api/feed.py", "api/feed", "sse"),
+                connection("web/live.ts", "server/realtime.js", "chat", "socket.io"),
+                connection("web/live.ts", "server/realtime.js", "event:chat:send", "socket.io"),
+                connection("web/live.ts", "server/realtime.js", "ws/prices", "ws"),
+            ]
+        );
+
+        assert_eq!(store.get_node("channel::chat:send").await.unwrap().unwrap().kind, "channel");
+        let edges = store.get_edges_to("channel::chat:message").await.unwrap();
+        let mut edges: Vec<_> = edges.iter().map(|e| (e.source.as_str(), e.kind.as_str())).collect();
+        edges.sort();
+        assert_eq!(edges, vec![("file::server/realtime.js", "emits_to"), ("file::web/live.ts", "listens_to")]);
+    }
+
+    #[tokio::test]
+    async fn test_infer_table_edges() {
+        let (temp_dir, store, config) = setup().await;
+        add_files(
+            temp_dir.path(),
+            &store,
+            &[(
+                "app/models.py",
+                concat!(
+                    "class User(Base):\n    __tablename__ = 'users'\n\n",
+                    "def count(db):\n    db.execute('SELECT count(*) FROM users')\n",
+                ),
+            )],
+        )
+        .await;
+        let user = json!({"name": "User", "file": "app/models.py", "line_start": 1, "line_end": 2});
+        store.upsert_node("app/models.py::User", "code", "class", &user).await.unwrap();
+        let count = json!({"name": "count", "file": "app/models.py", "line_start": 4, "line_end": 5});
+        store.upsert_node("app/models.py::count", "code", "function", &count).await.unwrap();
+        store.upsert_node("api/views.py::list_users", "code", "function", &json!({})).await.unwrap();
+        store.upsert_edge("api/views.py::list_users", "app/models.py::User", "calls", "code", None).await.unwrap();
+
+        let inferrer = CrossLanguageInferrer::new(store.clone(), config);
+        let stats = inferrer.infer(true).await.unwrap();
+        assert_eq!(stats.tables_found, 1);
+
+        let usage = inferrer.table_usage(Some("users")).await.unwrap();
+        assert_eq!(usage.len(), 1);
+        assert_eq!(usage[0].models[0].symbol, "app/models.py::User");
+        assert_eq!(usage[0].reads[0].symbol, "app/models.py::count");
+        assert_eq!(usage[0].reads[0].line, 5);
+        assert_eq!(usage[0].model_references, vec!["api/views.py::list_users"]);
     }
 }
@@ -0,0 +1,324 @@
+//! Environment variable reads and definitions.
+//!
+//! Reads are found in source code (`process.env.X`, `os.environ["X"]`,
+//! `std::env::var("X")`, ...), definitions in the files that set variables for
+//! the code: dotenv files, docker-compose, Dockerfiles, and CI pipelines. Only
+//! variable names and line numbers are extracted — values are never read out,
+//! since these files often hold secrets.
+
+use once_cell::sync::Lazy;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+
+/// Source files scanned for environment reads
+pub const ENV_READ_GLOB: &str = "**/*.{js,ts,jsx,tsx,mjs,cjs,vue,svelte,py,rs,go,java,kt,rb,cs,php}";
+
+/// A variable name at a line (1-based)
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EnvReference {
+    pub name: String,
+    pub line: usize,
+}
+
+/// Read patterns, each capturing the variable name as group 1
+static READ_PATTERNS: Lazy<Vec<Regex>> = Lazy::new(|| {
+    [
+        // JS/TS
+        r"\bprocess\.env\.([A-Za-z_]\w*)",
+        r#"\bprocess\.env\[\s*['"`]([A-Za-z_]\w*)['"`]\s*\]"#,
+        r"\bimport\.meta\.env\.([A-Za-z_]\w*)",
+        // Python
+        r#"\b(?:os\.)?environ\[\s*['"]([A-Za-z_]\w*)['"]\s*\]"#,
+        r#"\b(?:os\.)?environ\.(?:get|setdefault|pop)\(\s*['"]([A-Za-z_]\w*)['"]"#,
+        r#"\bos\.getenv\(\s*['"]([A-Za-z_]\w*)['"]"#,
+        // Rust
+        r#"\benv::var(?:_os)?\(\s*"([A-Za-z_]\w*)""#,
+        r#"\b(?:option_)?env!\(\s*"([A-Za-z_]\w*)""#,
+        // Go
+        r#"\bos\.(?:Getenv|LookupEnv)\(\s*"([A-Za-z_]\w*)""#,
+        // Java/Kotlin, C#
+        r#"\bSystem\.getenv\(\s*"([A-Za-z_]\w*)""#,
+        r#"\bEnvironment\.GetEnvironmentVariable\(\s*"([A-Za-z_]\w*)""#,
+        // Ruby, PHP
+        r#"\bENV(?:\[\s*|\.fetch\(\s*)['"]([A-Za-z_]\w*)['"]"#,
+        r#"\bgetenv\(\s*['"]([A-Za-z_]\w*)['"]"#,
+    ]
+    .iter()
+    .map(|p| Regex::new(p).unwrap())
+    .collect()
+});
+
+/// `const { API_URL, PORT = 3000 } = process.env`
+static JS_DESTRUCTURE: Lazy<Regex> = Lazy::new(|| Regex::new(r"\{([^{}]*)\}\s*=\s*process\.env\b").unwrap());
+
+/// `KEY=value` or `export KEY=value`
+static DOTENV_LINE: Lazy<Regex> = Lazy::new(|| Regex::new(r"^\s*(?:export\s+)?([A-Za-z_]\w*)\s*=").unwrap());
+
+/// `ENV KEY=value ...`, `ENV KEY value`, `ARG KEY`
+static DOCKERFILE_LINE: Lazy<Regex> = Lazy::new(|| Regex::new(r"(?i)^\s*(ENV|ARG)\s+(.*)$").unwrap());
+
+/// Start of a YAML block of variables: compose `environment:`, Actions `env:`, GitLab `variables:`
+static YAML_ENV_BLOCK: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"^(\s*)(?:-\s*)?(?:environment|env|variables)\s*:\s*(?:#.*)?$").unwrap());
+
+/// Entry in a YAML variable block: `- KEY=value`, `- KEY`, or `KEY: value`
+static YAML_ENV_ENTRY: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r#"^\s*(?:-\s*['"]?([A-Za-z_]\w*)\s*(?:=|['"]?\s*$)|['"]?([A-Za-z_]\w*)['"]?\s*:)"#).unwrap());
+
+/// All environment variable reads in a source file, in source order
+pub fn find_env_reads(content: &str) -> Vec<EnvReference> {
+    let mut reads: Vec<(usize, String)> = Vec::new();
+    for pattern in READ_PATTERNS.iter() {
+        for cap in pattern.captures_iter(content) {
+            reads.push((cap.get(0).unwrap().start(), cap[1].to_string()));
+        }
+    }
+    for cap in JS_DESTRUCTURE.captures_iter(content) {
+        let body = cap.get(1).unwrap();
+        for key in body.as_str().split(',') {
+            // `API_URL`, `PORT = 3000`, `API_URL: apiUrl`
+            let name = key.split([':', '=']).next().unwrap_or_default().trim();
+            if is_env_name(name) {
+                reads.push((body.start(), name.to_string()));
+            }
+        }
+    }
+
+    reads.sort_by_key(|(start, _)| *start);
+    let mut references: Vec<EnvReference> = reads
+        .into_iter()
+        .map(|(start, name)| EnvReference {
+            name,
+            line: line_at(content, start),
+        })
+        .collect();
+    // Patterns can overlap, e.g. PHP `getenv(` inside Python `os.getenv(`
+    references.dedup();
+    references
+}
+
+/// Files that define environment variables, by project-relative path
+pub fn is_env_definition_file(path: &str) -> bool {
+    let path = path.replace('\\', "/");
+    let name = path.rsplit('/').next().unwrap_or_default();
+    let yaml = name.ends_with(".yml") || name.ends_with(".yaml");
+
+    name == ".env"
+        || name.starts_with(".env.")
+        || name.ends_with(".env")
+        || name.starts_with("Dockerfile")
+        || name.ends_with(".dockerfile")
+        || (yaml && (name.starts_with("docker-compose") || name.starts_with("compose.")))
+        || (yaml && (path.starts_with(".github/workflows/") || path.contains("/.github/workflows/")))
+        || matches!(
+            name,
+            ".gitlab-ci.yml" | "azure-pipelines.yml" | "bitbucket-pipelines.yml" | ".travis.yml"
+        )
+        || path.ends_with(".circleci/config.yml")
+}
+
+/// Variables defined in a definition file (see [`is_env_definition_file`])
+pub fn find_env_definitions(path: &str, content: &str) -> Vec<EnvReference> {
+    let name = path.rsplit(['/', '\\']).next().unwrap_or_default();
+    let mut definitions = if name.starts_with("Dockerfile") || name.ends_with(".dockerfile") {
+        dockerfile_definitions(content)
+    } else if name.ends_with(".yml") || name.ends_with(".yaml") {
+        yaml_definitions(content)
+    } else {
+        dotenv_definitions(content)
+    };
+    definitions.dedup();
+    definitions
+}
+
+fn dotenv_definitions(content: &str) -> Vec<EnvReference> {
+    content
+        .lines()
+        .enumerate()
+        .filter_map(|(i, line)| {
+            let cap = DOTENV_LINE.captures(line)?;
+            Some(EnvReference {
+                name: cap[1].to_string(),
+                line: i + 1,
+            })
+        })
+        .collect()
+}
+
+fn dockerfile_definitions(content: &str) -> Vec<EnvReference> {
+    let mut definitions = Vec::new();
+    for (i, line) in content.lines().enumerate() {
+        let Some(cap) = DOCKERFILE_LINE.captures(line) else { continue };
+        let args = &cap[2];
+        let names: Vec<&str> = if cap[1].eq_ignore_ascii_case("env") && args.contains('=') {
+            // `ENV A=1 B="two words"`: every token before an `=` that starts a word
+            args.split_whitespace()
+                .filter_map(|token| token.split_once('=').map(|(key, _)| key))
+                .collect()
+        } else {
+            args.split(['=', ' ', '\t']).take(1).collect()
+        };
+        definitions.extend(names.into_iter().filter(|n| is_env_name(n)).map(|name| EnvReference {
+            name: name.to_string(),
+            line: i + 1,
+        }));
+    }
+    definitions
+}
+
+/// Entries directly under `environment:`/`env:`/`variables:` keys
+fn yaml_definitions(content: &str) -> Vec<EnvReference> {
+    let mut definitions = Vec::new();
+    // Indent of the block's key, and of its entries once the first one is seen
+    let mut block: Option<(usize, Option<usize>)> = None;
+
+    for (i, line) in content.lines().enumerate() {
+        let trimmed = line.trim_start();
+        if trimmed.is_empty() || trimmed.starts_with('#') {
+            continue;
+        }
+        let indent = line.len() - trimmed.len();
+
+        if let Some((key_indent, entry_indent)) = block.as_mut() {
+            // Compose allows the list under `environment:` at the key's own indent
+            let in_block = indent > *key_indent || (indent == *key_indent && trimmed.starts_with("- "));
+            if in_block && entry_indent.is_none_or(|e| e == indent) {
+                *entry_indent = Some(indent);
+                if let Some(cap) = YAML_ENV_ENTRY.captures(line) {
+                    if let Some(name) = cap.get(1).or(cap.get(2)) {
+                        definitions.push(EnvReference {
+                            name: name.as_str().to_string(),
+                            line: i + 1,
+                        });
+                    }
+                }
+                continue;
+            }
+            if in_block {
+                // Nested value of an entry
+                continue;
+            }
+            block = None;
+        }
+
+        if let Some(cap) = YAML_ENV_BLOCK.captures(line) {
+            // A `- env:` list item's keys sit past the dash
+            let key_indent = if trimmed.starts_with('-') { indent + 1 } else { cap[1].len() };
+            block = Some((key_indent, None));
+        }
+    }
+    definitions
+}
+
+fn is_env_name(name: &str) -> bool {
+    let mut chars = name.chars();
+    chars.next().is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+fn line_at(content: &str, offset: usize) -> usize {
+    content[..offset].matches('\n').count() + 1
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn names(references: &[EnvReference]) -> Vec<&str> {
+        references.iter().map(|r| r.name.as_str()).collect()
+    }
+
+    #[test]
+    fn test_find_env_reads() {
+        let ts = r#"
+const url = process.env.API_URL;
+const key = process.env["API_KEY"];
+const { PORT = 3000, NODE_ENV: mode } = process.env;
+const base = import.meta.env.VITE_BASE;
+"#;
+        let reads = find_env_reads(ts);
+        assert_eq!(names(&reads), vec!["API_URL", "API_KEY", "PORT", "NODE_ENV", "VITE_BASE"]);
+        assert_eq!(reads[0].line, 2);
+
+        let python = "db = os.environ['DATABASE_URL']\ndebug = os.getenv(\"DEBUG\", \"0\")\nos.environ.get('PORT')\n";
+        assert_eq!(names(&find_env_reads(python)), vec!["DATABASE_URL", "DEBUG", "PORT"]);
+
+        let rust = "let url = std::env::var(\"DATABASE_URL\")?;\nconst V: &str = env!(\"CARGO_PKG_VERSION\");\n";
+        assert_eq!(names(&find_env_reads(rust)), vec!["DATABASE_URL", "CARGO_PKG_VERSION"]);
+
+        let other = "os.Getenv(\"REDIS_ADDR\")\nSystem.getenv(\"JAVA_OPTS\")\nENV.fetch('RAILS_ENV')\n";
+        assert_eq!(names(&find_env_reads(other)), vec!["REDIS_ADDR", "JAVA_OPTS", "RAILS_ENV"]);
+
+        // Dynamic names aren't references
+        assert!(find_env_reads("process.env[name]; os.getenv(key)").is_empty());
+    }
+
+    #[test]
+    fn test_is_env_definition_file() {
+        for path in [
+            ".env",
+            "api/.env.example",
+            "config/prod.env",
+            "docker-compose.yml",
+            "deploy/compose.yaml",
+            "Dockerfile",
+            "services/api/Dockerfile.dev",
+            ".github/workflows/ci.yml",
+            ".gitlab-ci.yml",
+            ".circleci/config.yml",
+        ] {
+            assert!(is_env_definition_file(path), "{}", path);
+        }
+        assert!(!is_env_definition_file("src/env.ts"));
+        assert!(!is_env_definition_file("config/settings.yml"));
+    }
+
+    #[test]
+    fn test_find_env_definitions() {
+        let dotenv = "# database\nDATABASE_URL=postgres://localhost\nexport API_KEY=secret\n\nnot a definition\n";
+        let definitions = find_env_definitions(".env", dotenv);
+        assert_eq!(names(&definitions), vec!["DATABASE_URL", "API_KEY"]);
+        assert_eq!(definitions[1].line, 3);
+
+        let dockerfile = "FROM node:20\nARG NPM_TOKEN\nENV NODE_ENV=production PORT=8080\nENV LEGACY value\n";
+        assert_eq!(
+            names(&find_env_definitions("Dockerfile", dockerfile)),
+            vec!["NPM_TOKEN", "NODE_ENV", "PORT", "LEGACY"]
+        );
+
+        let compose = r#"
+services:
+  api:
+    image: api
+    environment:
+      - DATABASE_URL=postgres://db
+      - DEBUG
+    ports:
+      - "8080:8080"
+  worker:
+    environment:
+      REDIS_URL: redis://redis
+      QUEUE_NAME: jobs
+"#;
+        assert_eq!(
+            names(&find_env_definitions("docker-compose.yml", compose)),
+            vec!["DATABASE_URL", "DEBUG", "REDIS_URL", "QUEUE_NAME"]
+        );
+
+        let workflow = r#"
+env:
+  CI: true
+jobs:
+  test:
+    steps:
+      - run: npm test
+        env:
+          API_URL: http://localhost
+          TOKEN: ${{ secrets.TOKEN }}
+      - name: build
+"#;
+        let definitions = find_env_definitions(".github/workflows/ci.yml", workflow);
+        assert_eq!(names(&definitions), vec!["CI", "API_URL", "TOKEN"]);
+        assert_eq!(definitions[1].line, 9);
+    }
+}
@@ -3,20 +3,27 @@ use serde::{Deserialize, Serialize};
 use serde_json::json;
 use std::collections::HashSet;
 use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
-use std::time::{SystemTime, UNIX_EPOCH};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+use tokio::task::JoinSet;
 use tracing::{debug, info, warn};
 use walkdir::WalkDir;
 use xxhash_rust::xxh3::xxh3_64;
 
 use std::collections::HashMap;
 
-use super::languages::detect_language;
-use super::parser::{parse_file, ReferenceKind, SymbolKind};
+use ignore::gitignore::{Gitignore, GitignoreBuilder};
+
+use super::languages::{detect_language_with, LanguageConfig, LANGUAGE_REGISTRY};
+use super::parser::{parse_file, ParseResult, ReferenceKind, SymbolKind};
+use super::watcher::FileWatcher;
 use crate::config::Config;
-use crate::store::Store;
+use crate::store::graph::GraphChange;
+use crate::store::db::SymbolText;
+use crate::store::{CodeGraph, Store};
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct IndexStats {
     pub files_scanned: usize,
     pub files_indexed: usize,
@@ -28,33 +35,166 @@ pub struct IndexStats {
     pub resolved: usize,
     pub unresolved_after: usize,
     pub duration_ms: u64,
+    /// The run was cancelled: files written so far are kept, and removals and cross-file
+    /// resolution were skipped. The next incremental index picks up the rest.
+    pub cancelled: bool,
+    /// Changes made to the stored graph, in order, for patching an already loaded `CodeGraph`
+    #[serde(skip)]
+    pub changes: Vec<GraphChange>,
+    #[serde(skip)]
+    pub profile: IndexProfile,
+}
+
+/// Files listed in `IndexProfile::slowest_files`
+const SLOWEST_FILES: usize = 10;
+
+/// Files written per transaction while indexing
+const WRITE_BATCH: usize = 200;
+
+/// Least time between two progress callbacks
+const PROGRESS_INTERVAL: Duration = Duration::from_millis(250);
+
+/// How far an index run has got, as passed to the progress callback of `Indexer::index_with`
+#[derive(Debug, Clone, Default)]
+pub struct IndexProgress {
+    pub files_scanned: usize,
+    /// Files found to need indexing; `None` while the tree is still being walked
+    pub files_to_index: Option<usize>,
+    pub files_indexed: usize,
+    /// Time left to index the remaining files, at the rate so far
+    pub eta: Option<Duration>,
+}
+
+impl IndexProgress {
+    pub fn message(&self) -> String {
+        match (self.files_to_index, self.eta) {
+            (None, _) => format!("Scanned {} files", self.files_scanned),
+            (Some(total), Some(eta)) => {
+                format!("Indexed {}/{} files, ~{}s left", self.files_indexed, total, eta.as_secs().max(1))
+            }
+            (Some(total), None) => format!("Indexed {}/{} files", self.files_indexed, total),
+        }
+    }
+}
+
+/// Calls a progress callback at most once per `PROGRESS_INTERVAL`
+struct ProgressThrottle<'a> {
+    on_progress: &'a (dyn Fn(&IndexProgress) + Send + Sync),
+    last: Option<Instant>,
+}
+
+impl ProgressThrottle<'_> {
+    fn silent() -> Self {
+        ProgressThrottle { on_progress: &|_| {}, last: None }
+    }
+
+    fn report(&mut self, progress: impl FnOnce() -> IndexProgress) {
+        if self.last.is_some_and(|last| last.elapsed() < PROGRESS_INTERVAL) {
+            return;
+        }
+        self.last = Some(Instant::now());
+        (self.on_progress)(&progress());
+    }
+}
+
+/// Where an index run spent its time
+#[derive(Debug, Clone, Default)]
+pub struct IndexProfile {
+    /// Walking the tree and filtering paths (excludes, ignore file, include globs)
+    pub walk: Duration,
+    /// Reading and hashing files, including change detection on incremental runs. Files are
+    /// read on parallel workers, so this and `parse` are summed across threads and can exceed
+    /// the wall-clock `duration_ms`.
+    pub hash: Duration,
+    /// Tree-sitter parsing by language name
+    pub parse: HashMap<String, Duration>,
+    /// Replacing each file's nodes and edges, and removing deleted files
+    pub db_writes: Duration,
+    /// Linking `unresolved::` stubs to their definitions
+    pub resolution: Duration,
+    /// Files that took longest to index (read, parse, and write), slowest first
+    pub slowest_files: Vec<(String, Duration)>,
+}
+
+impl IndexProfile {
+    fn record_file(&mut self, path: &str, elapsed: Duration) {
+        if self.slowest_files.len() == SLOWEST_FILES
+            && self.slowest_files.last().is_some_and(|(_, slowest)| *slowest >= elapsed)
+        {
+            return;
+        }
+        let at = self.slowest_files.partition_point(|(_, other)| *other >= elapsed);
+        self.slowest_files.insert(at, (path.to_string(), elapsed));
+        self.slowest_files.truncate(SLOWEST_FILES);
+    }
 }
 
 pub struct Indexer {
     store: Arc<Store>,
     config: Arc<Config>,
+    /// Held for each run, so the file watcher and explicit index calls don't interleave writes
+    running: tokio::sync::Mutex<()>,
 }
 
 impl Indexer {
     pub fn new(store: Arc<Store>, config: Arc<Config>) -> Self {
-        Self { store, config }
+        Self {
+            store,
+            config,
+            running: tokio::sync::Mutex::new(()),
+        }
+    }
+
+    pub fn project_root(&self) -> &Path {
+        &self.config.project_root
+    }
+
+    /// Re-index files in the background as they change, until the returned watcher is
+    /// dropped. Each batch patches `graph`, then `on_update` runs with its stats.
+    pub fn watch<F, Fut>(
+        self: &Arc<Self>,
+        graph: Arc<tokio::sync::RwLock<CodeGraph>>,
+        on_update: F,
+    ) -> Result<FileWatcher>
+    where
+        F: Fn(IndexStats) -> Fut + Send + 'static,
+        Fut: std::future::Future<Output = ()> + Send + 'static,
+    {
+        let debounce = Duration::from_millis(self.config.settings().indexing.watch_debounce_ms);
+        FileWatcher::start(self.clone(), graph, debounce, on_update)
     }
 
     /// Full project index
     pub async fn index_full(&self) -> Result<IndexStats> {
-        info!("Starting full project index");
-        self.index_internal(true).await
+        self.index_with(true, &|_| {}, &AtomicBool::new(false)).await
     }
 
     /// Incremental index: only changed files
     pub async fn index_incremental(&self) -> Result<IndexStats> {
-        info!("Starting incremental index");
-        self.index_internal(false).await
+        self.index_with(false, &|_| {}, &AtomicBool::new(false)).await
+    }
+
+    /// Full or incremental index that reports its progress as it goes and stops early once
+    /// `cancelled` is set (see `IndexStats::cancelled`)
+    pub async fn index_with(
+        &self,
+        full: bool,
+        on_progress: &(dyn Fn(&IndexProgress) + Send + Sync),
+        cancelled: &AtomicBool,
+    ) -> Result<IndexStats> {
+        if full {
+            info!("Starting full project index");
+        } else {
+            info!("Starting incremental index");
+        }
+        let mut progress = ProgressThrottle { on_progress, last: None };
+        self.index_internal(full, &mut progress, cancelled).await
     }
 
     /// Index specific paths
     pub async fn index_paths(&self, paths: &[PathBuf]) -> Result<IndexStats> {
         info!("Indexing {} specific paths", paths.len());
+        let _running = self.running.lock().await;
         let start = SystemTime::now();
 
         let mut stats = IndexStats {
@@ -68,22 +208,122 @@ impl Indexer {
             resolved: 0,
             unresolved_after: 0,
             duration_ms: 0,
+            cancelled: false,
+            changes: Vec::new(),
+            profile: IndexProfile::default(),
         };
 
+        let project_root = &self.config.project_root;
+        let mut to_index = Vec::new();
         for path in paths {
             if path.is_file() {
                 stats.files_scanned += 1;
-                if let Err(e) = self.index_file(path, &mut stats).await {
-                    warn!("Failed to index {:?}: {}", path, e);
-                }
+                let rel_path = path.strip_prefix(project_root).unwrap_or(path).to_string_lossy().to_string();
+                to_index.push((path.clone(), rel_path));
             }
         }
+        self.index_files(to_index, &mut stats, &mut ProgressThrottle::silent(), &AtomicBool::new(false))
+            .await?;
 
         stats.duration_ms = start.elapsed()?.as_millis() as u64;
         Ok(stats)
     }
 
-    async fn index_internal(&self, force_full: bool) -> Result<IndexStats> {
+    /// Re-index paths reported by the file watcher. Paths the full walk would skip (excluded,
+    /// ignored, not included, or an unsupported language) are skipped here too. A path that
+    /// no longer exists removes its file, or every indexed file under a deleted directory.
+    pub async fn index_changed(&self, paths: &[PathBuf]) -> Result<IndexStats> {
+        let _running = self.running.lock().await;
+        let start = Instant::now();
+        let mut stats = IndexStats::default();
+
+        let settings = self.config.settings();
+        let include = IncludeFilter::new(&settings.indexing.include)?;
+        let project_root = &self.config.project_root;
+        let ignore = load_ignore_file(project_root);
+        let mut indexed_files: Option<Vec<String>> = None;
+        let mut to_index = Vec::new();
+
+        for path in paths {
+            let Ok(rel) = path.strip_prefix(project_root) else {
+                continue;
+            };
+            if is_excluded(rel, &settings.indexing.exclude) {
+                continue;
+            }
+
+            if path.is_file() {
+                if ignore.matched_path_or_any_parents(rel, false).is_ignore()
+                    || !include.includes(rel)
+                    || detect_language_with(path.to_str().unwrap_or(""), &settings.indexing.languages).is_none()
+                {
+                    continue;
+                }
+                stats.files_scanned += 1;
+                let rel_path = rel.to_string_lossy().to_string();
+
+                let work_start = Instant::now();
+                let needs_reindex = self.needs_reindex(path, &rel_path).await?;
+                stats.profile.hash += work_start.elapsed();
+                if needs_reindex {
+                    to_index.push((path.clone(), rel_path));
+                } else {
+                    stats.files_skipped += 1;
+                }
+            } else if !path.exists() {
+                let indexed = match &mut indexed_files {
+                    Some(files) => files,
+                    None => indexed_files.insert(self.store.list_indexed_files().await?),
+                };
+                let rel_path = rel.to_string_lossy();
+                let dir_prefix = format!("{}/", rel_path);
+                let (removed, kept) = std::mem::take(indexed)
+                    .into_iter()
+                    .partition(|file| *file == rel_path || file.starts_with(&dir_prefix));
+                *indexed = kept;
+
+                let removal_start = Instant::now();
+                for file in removed {
+                    self.remove_file(&file, &mut stats).await?;
+                }
+                stats.profile.db_writes += removal_start.elapsed();
+            }
+        }
+
+        self.index_files(to_index, &mut stats, &mut ProgressThrottle::silent(), &AtomicBool::new(false))
+            .await?;
+
+        if stats.files_indexed + stats.files_removed > 0 {
+            let resolution_start = Instant::now();
+            let tx = self.store.transaction().await?;
+            self.resolve_cross_file_references(&mut stats).await?;
+            tx.commit().await?;
+            stats.profile.resolution = resolution_start.elapsed();
+        }
+        stats.duration_ms = start.elapsed().as_millis() as u64;
+        Ok(stats)
+    }
+
+    /// Whether the watcher should watch a directory: the full walk would descend into it
+    pub(crate) fn watches_dir(&self, dir: &Path) -> bool {
+        let settings = self.config.settings();
+        let project_root = &self.config.project_root;
+        let Ok(rel) = dir.strip_prefix(project_root) else {
+            return false;
+        };
+        let include = IncludeFilter::new(&settings.indexing.include).map(|include| include.may_contain(rel));
+        !is_excluded(rel, &settings.indexing.exclude)
+            && !load_ignore_file(project_root).matched_path_or_any_parents(rel, true).is_ignore()
+            && include.unwrap_or(true)
+    }
+
+    async fn index_internal(
+        &self,
+        force_full: bool,
+        progress: &mut ProgressThrottle<'_>,
+        cancelled: &AtomicBool,
+    ) -> Result<IndexStats> {
+        let _running = self.running.lock().await;
         let start = SystemTime::now();
 
         let mut stats = IndexStats {
@@ -97,6 +337,9 @@ impl Indexer {
             resolved: 0,
             unresolved_after: 0,
             duration_ms: 0,
+            cancelled: false,
+            changes: Vec::new(),
+            profile: IndexProfile::default(),
         };
 
         // Get list of previously indexed files
@@ -108,13 +351,35 @@ impl Indexer {
             .collect();
 
         let mut found_files = HashSet::new();
+        let mut to_index = Vec::new();
+        // One snapshot for the whole run, so a reload mid-walk can't mix settings
+        let settings = self.config.settings();
+        let include = IncludeFilter::new(&settings.indexing.include)?;
+        let project_root = &self.config.project_root;
+        let ignore = load_ignore_file(project_root);
+        let languages = &settings.indexing.languages;
+        for name in languages.keys().filter(|name| !LANGUAGE_REGISTRY.contains_key(*name)) {
+            warn!("Unknown language '{}' in [indexing.languages]", name);
+        }
 
-        // Walk project directory
-        for entry in WalkDir::new(&self.config.project_root)
+        // Walk project directory. Time not spent on files counts as walking.
+        let walk_start = Instant::now();
+        let mut file_work = Duration::ZERO;
+        for entry in WalkDir::new(project_root)
             .follow_links(false)
             .into_iter()
-            .filter_entry(|e| !is_excluded(e.path(), &self.config.settings.indexing.exclude))
+            .filter_entry(|e| {
+                let rel = e.path().strip_prefix(project_root).unwrap_or(e.path());
+                let is_dir = e.file_type().is_dir();
+                !is_excluded(e.path(), &settings.indexing.exclude)
+                    && !ignore.matched(rel, is_dir).is_ignore()
+                    && (!is_dir || include.may_contain(rel))
+            })
         {
+            if cancelled.load(Ordering::Relaxed) {
+                stats.cancelled = true;
+                break;
+            }
             let entry = match entry {
                 Ok(e) => e,
                 Err(e) => {
@@ -130,49 +395,72 @@ impl Indexer {
             let path = entry.path();
 
             // Check if this is a supported language
-            if detect_language(path.to_str().unwrap_or("")).is_none() {
+            if detect_language_with(path.to_str().unwrap_or(""), languages).is_none() {
                 continue;
             }
 
             stats.files_scanned += 1;
+            progress.report(|| IndexProgress {
+                files_scanned: stats.files_scanned,
+                ..Default::default()
+            });
 
             // Get relative path
-            let rel_path = path
-                .strip_prefix(&self.config.project_root)
-                .unwrap_or(path)
-                .to_string_lossy()
-                .to_string();
+            let rel = path.strip_prefix(project_root).unwrap_or(path);
+            if !include.includes(rel) {
+                continue;
+            }
+            let rel_path = rel.to_string_lossy().to_string();
 
             found_files.insert(rel_path.clone());
 
             // Check if we need to reindex
+            let work_start = Instant::now();
             let needs_reindex = if force_full {
                 true
             } else {
                 self.needs_reindex(path, &rel_path).await?
             };
+            stats.profile.hash += work_start.elapsed();
 
             if needs_reindex {
-                if let Err(e) = self.index_file(path, &mut stats).await {
-                    warn!("Failed to index {:?}: {}", path, e);
-                }
+                to_index.push((path.to_path_buf(), rel_path));
             } else {
                 stats.files_skipped += 1;
             }
+            file_work += work_start.elapsed();
+        }
+        stats.profile.walk = walk_start.elapsed().saturating_sub(file_work);
+
+        if !stats.cancelled {
+            self.index_files(to_index, &mut stats, progress, cancelled).await?;
+        }
+        if stats.cancelled {
+            stats.duration_ms = start.elapsed()?.as_millis() as u64;
+            info!(
+                "Indexing cancelled after {} files scanned, {} indexed in {}ms",
+                stats.files_scanned, stats.files_indexed, stats.duration_ms
+            );
+            return Ok(stats);
         }
 
         // Remove files that no longer exist
+        let removal_start = Instant::now();
+        let tx = self.store.transaction().await?;
         for indexed_path in indexed_files {
             if !found_files.contains(&indexed_path) {
-                debug!("Removing deleted file: {}", indexed_path);
-                self.remove_file_nodes(&indexed_path).await?;
-                self.store.remove_file_meta(&indexed_path).await?;
-                stats.files_removed += 1;
+                self.remove_file(&indexed_path, &mut stats).await?;
             }
         }
+        tx.commit().await?;
+        stats.profile.db_writes += removal_start.elapsed();
 
         // Resolve cross-file references
+        let resolution_start = Instant::now();
+        let tx = self.store.transaction().await?;
         self.resolve_cross_file_references(&mut stats).await?;
+        tx.commit().await?;
+        stats.profile.resolution = resolution_start.elapsed();
 
         stats.duration_ms = start.elapsed()?.as_millis() as u64;
 
@@ -219,41 +507,115 @@ impl Indexer {
         Ok(true)
     }
 
-    async fn index_file(&self, path: &Path, stats: &mut IndexStats) -> Result<()> {
-        debug!("Indexing file: {:?}", path);
+    /// Read and parse files on blocking threads, a few per core in flight, and write each one
+    /// as it finishes. Writes are committed every `WRITE_BATCH` files instead of one statement
+    /// at a time. A file that fails to read or parse is logged and keeps its old nodes.
+    /// Cancelling stops at the next file, keeping the ones already written.
+    async fn index_files(
+        &self,
+        files: Vec<(PathBuf, String)>,
+        stats: &mut IndexStats,
+        progress: &mut ProgressThrottle<'_>,
+        cancelled: &AtomicBool,
+    ) -> Result<()> {
+        let settings = self.config.settings();
+        let total = files.len();
+        let start = Instant::now();
+        let mut done = 0;
+        let in_flight = 2 * std::thread::available_parallelism().map_or(4, |n| n.get());
+        let mut pending = files.into_iter();
+        let mut parsing = JoinSet::new();
+        let mut batch = None;
+        let mut batched = 0;
+
+        loop {
+            if cancelled.load(Ordering::Relaxed) {
+                stats.cancelled = true;
+                break;
+            }
+            while parsing.len() < in_flight {
+                let Some((path, rel_path)) = pending.next() else {
+                    break;
+                };
+                let Some(lang_config) = detect_language_with(path.to_str().unwrap_or(""), &settings.indexing.languages)
+                else {
+                    continue;
+                };
+                parsing.spawn_blocking(move || read_and_parse(&path, rel_path, lang_config).map_err(|e| (path, e)));
+            }
 
-        let rel_path = path
-            .strip_prefix(&self.config.project_root)
-            .unwrap_or(path)
-            .to_string_lossy()
-            .to_string();
+            let Some(joined) = parsing.join_next().await else {
+                break;
+            };
+            done += 1;
+            progress.report(|| IndexProgress {
+                files_scanned: stats.files_scanned,
+                files_to_index: Some(total),
+                files_indexed: done,
+                eta: Some(start.elapsed().mul_f64((total - done) as f64 / done as f64)),
+            });
+            let parsed = match joined {
+                Ok(Ok(parsed)) => parsed,
+                Ok(Err((path, e))) => {
+                    warn!("Failed to index {:?}: {:#}", path, e);
+                    continue;
+                }
+                Err(e) => {
+                    warn!("Parser task failed: {}", e);
+                    continue;
+                }
+            };
 
-        // Detect language
-        let lang_config = detect_language(path.to_str().unwrap_or(""))
-            .context("Unsupported file type")?;
+            if batch.is_none() {
+                batch = Some(self.store.transaction().await?);
+            }
+            let rel_path = parsed.rel_path.clone();
+            if let Err(e) = self.write_file(parsed, stats).await {
+                warn!("Failed to index {}: {}", rel_path, e);
+            }
+            batched += 1;
+            if batched == WRITE_BATCH {
+                if let Some(tx) = batch.take() {
+                    tx.commit().await?;
+                }
+                batched = 0;
+            }
+        }
 
-        // Read file
-        let content = std::fs::read(path).context("Failed to read file")?;
+        if let Some(tx) = batch {
+            tx.commit().await?;
+        }
+        Ok(())
+    }
 
-        // Compute hash
-        let hash = format!("{:016x}", xxh3_64(&content));
-        let mtime = std::fs::metadata(path)?
-            .modified()?
-            .duration_since(UNIX_EPOCH)?
-            .as_secs() as i64;
+    /// Replace a file's nodes and edges with freshly parsed ones
+    async fn write_file(&self, parsed: ParsedFile, stats: &mut IndexStats) -> Result<()> {
+        let start = Instant::now();
+        let ParsedFile {
+            rel_path,
+            language,
+            content,
+            hash,
+            mtime,
+            result: parse_result,
+            read_time,
+            parse_time,
+        } = parsed;
+        stats.profile.hash += read_time;
+        *stats.profile.parse.entry(language.to_string()).or_default() += parse_time;
 
         // Remove old nodes for this file
         self.remove_file_nodes(&rel_path).await?;
-
-        // Parse file
-        let parse_result = parse_file(path, &content, lang_config)?;
+        stats.changes.push(GraphChange::RemoveFile(rel_path.clone()));
 
         stats.symbols_found += parse_result.symbols.len();
         stats.edges_found += parse_result.references.len();
 
-        // Insert symbols as nodes
+        // Insert symbols as nodes, with their text for full-text search
+        let text = String::from_utf8_lossy(&content);
+        let lines: Vec<&str> = text.lines().collect();
         for symbol in &parse_result.symbols {
-            let node_id = format!("{}::{}", rel_path, symbol.name);
+            let node_id = format!("{}::{}", rel_path, symbol.qualified_name());
 
             let data = json!({
                 "name": symbol.name,
@@ -263,28 +625,54 @@ impl Indexer {
                 "line_end": symbol.line_end,
                 "signature": symbol.signature,
                 "docstring": symbol.docstring,
+                "public": symbol.public,
             });
 
             self.store
                 .upsert_node(&node_id, "code", &symbol_kind_to_str(&symbol.kind), &data)
                 .await?;
+            let first = (symbol.line_start as usize).saturating_sub(1);
+            let last = (symbol.line_end as usize).min(lines.len());
+            let body = lines.get(first..last).map(|lines| lines.join("\n")).unwrap_or_default();
+            self.store
+                .insert_symbol_text(&SymbolText {
+                    id: &node_id,
+                    file: &rel_path,
+                    kind: symbol_kind_to_str(&symbol.kind),
+                    line: symbol.line_start,
+                    name: &symbol.name,
+                    signature: &symbol.signature,
+                    docstring: symbol.docstring.as_deref().unwrap_or(""),
+                    body: &body,
+                })
+                .await?;
+            stats.changes.push(GraphChange::UpsertNode {
+                id: node_id,
+                kind: symbol_kind_to_str(&symbol.kind).to_string(),
+                data,
+            });
         }
 
-        // Build a lookup of symbol names to node IDs for this file
-        let local_symbols: HashMap<String, String> = parse_result
-            .symbols
-            .iter()
-            .map(|s| (s.name.clone(), format!("{}::{}", rel_path, s.name)))
-            .collect();
+        // Build a lookup of symbol names to node IDs for this file. A name shared by
+        // symbols in different scopes is ambiguous and left for resolution.
+        let mut local_symbols: HashMap<String, Option<String>> = HashMap::new();
+        for symbol in &parse_result.symbols {
+            let id = format!("{}::{}", rel_path, symbol.qualified_name());
+            local_symbols
+                .entry(symbol.name.clone())
+                .and_modify(|existing| {
+                    if existing.as_ref() != Some(&id) {
+                        *existing = None;
+                    }
+                })
+                .or_insert(Some(id));
+        }
 
         // Insert references as edges
         for reference in &parse_result.references {
             // Determine the source: the enclosing symbol, or a file-level node
             let source_id = match &reference.from_symbol {
-                Some(name) => local_symbols
-                    .get(name)
-                    .cloned()
-                    .unwrap_or_else(|| format!("{}::{}", rel_path, name)),
+                Some(name) => format!("{}::{}", rel_path, name),
                 None => format!("file::{}", rel_path),
             };
 
@@ -293,6 +681,7 @@ impl Indexer {
             let target_id = local_symbols
                 .get(&reference.to_name)
                 .cloned()
+                .flatten()
                 .unwrap_or_else(|| format!("unresolved::{}", reference.to_name));
 
             let data = json!({
@@ -303,26 +692,28 @@ impl Indexer {
 
             // Ensure the source node exists (create file-level node if needed)
             if source_id.starts_with("file::") {
+                let file_data = json!({"path": rel_path, "name": rel_path});
                 self.store
-                    .upsert_node(
-                        &source_id,
-                        "code",
-                        "file",
-                        &json!({"path": rel_path, "name": rel_path}),
-                    )
+                    .upsert_node(&source_id, "code", "file", &file_data)
                     .await?;
+                stats.changes.push(GraphChange::UpsertNode {
+                    id: source_id.clone(),
+                    kind: "file".to_string(),
+                    data: file_data,
+                });
             }
 
             // Ensure the target node exists (create unresolved stub if needed)
             if target_id.starts_with("unresolved::") {
+                let stub_data = json!({"name": reference.to_name});
                 self.store
-                    .upsert_node(
-                        &target_id,
-                        "code",
-                        "unresolved",
-                        &json!({"name": reference.to_name}),
-                    )
+                    .upsert_node(&target_id, "code", "unresolved", &stub_data)
                     .await?;
+                stats.changes.push(GraphChange::UpsertNode {
+                    id: target_id.clone(),
+                    kind: "unresolved".to_string(),
+                    data: stub_data,
+                });
             }
 
             self.store
@@ -334,12 +725,21 @@ impl Indexer {
                     Some(&data),
                 )
                 .await?;
+            stats.changes.push(GraphChange::UpsertEdge {
+                source: source_id,
+                target: target_id,
+                kind: reference_kind_to_str(&reference.kind).to_string(),
+                data: Some(data),
+            });
         }
 
         // Update file metadata
         self.store.upsert_file_meta(&rel_path, mtime, &hash).await?;
 
         stats.files_indexed += 1;
+        let write_time = start.elapsed();
+        stats.profile.db_writes += write_time;
+        stats.profile.record_file(&rel_path, read_time + parse_time + write_time);
 
         Ok(())
     }
@@ -370,6 +770,10 @@ impl Indexer {
 
                 // Delete the stub node (CASCADE will clean up any remaining edges)
                 self.store.delete_node(stub_id).await?;
+                stats.changes.push(GraphChange::Resolve {
+                    stub: stub_id.clone(),
+                    target: real_id.clone(),
+                });
                 resolved_count += 1;
             }
             // Multiple matches = ambiguous, skip
@@ -387,6 +791,16 @@ impl Indexer {
         Ok(())
     }
 
+    /// Drop a deleted file's nodes, edges, and metadata
+    async fn remove_file(&self, rel_path: &str, stats: &mut IndexStats) -> Result<()> {
+        debug!("Removing deleted file: {}", rel_path);
+        self.remove_file_nodes(rel_path).await?;
+        stats.changes.push(GraphChange::RemoveFile(rel_path.to_string()));
+        self.store.remove_file_meta(rel_path).await?;
+        stats.files_removed += 1;
+        Ok(())
+    }
+
     async fn remove_file_nodes(&self, rel_path: &str) -> Result<()> {
         let prefix = format!("{}::", rel_path);
         // Delete edges first (they reference nodes), then nodes
@@ -396,10 +810,48 @@ impl Indexer {
         let file_node_id = format!("file::{}", rel_path);
         self.store.delete_edges_for(&file_node_id).await?;
         self.store.delete_node(&file_node_id).await?;
+        self.store.delete_symbol_text(rel_path).await?;
         Ok(())
     }
 }
 
+/// A file read and parsed on a worker thread, ready to be written
+struct ParsedFile {
+    rel_path: String,
+    language: &'static str,
+    content: Vec<u8>,
+    hash: String,
+    mtime: i64,
+    result: ParseResult,
+    read_time: Duration,
+    parse_time: Duration,
+}
+
+fn read_and_parse(path: &Path, rel_path: String, lang_config: &'static LanguageConfig) -> Result<ParsedFile> {
+    debug!("Indexing file: {:?}", path);
+    let read_start = Instant::now();
+    let content = std::fs::read(path).context("Failed to read file")?;
+    let hash = format!("{:016x}", xxh3_64(&content));
+    let mtime = std::fs::metadata(path)?
+        .modified()?
+        .duration_since(UNIX_EPOCH)?
+        .as_secs() as i64;
+    let read_time = read_start.elapsed();
+
+    let parse_start = Instant::now();
+    let result = parse_file(path, &content, lang_config)?;
+    Ok(ParsedFile {
+        rel_path,
+        language: lang_config.name,
+        content,
+        hash,
+        mtime,
+        result,
+        read_time,
+        parse_time: parse_start.elapsed(),
+    })
+}
+
 fn is_excluded(path: &Path, exclude_list: &[String]) -> bool {
     for component in path.components() {
         if let std::path::Component::Normal(name) = component {
@@ -414,7 +866,67 @@ fn is_excluded(path: &Path, exclude_list: &[String]) -> bool {
     false
 }
 
-fn symbol_kind_to_str(kind: &SymbolKind) -> &'static str {
+/// Gitignore-style exclusions kept next to the code, in addition to `[indexing] exclude`
+const IGNORE_FILE: &str = ".codegraphignore";
+
+/// Read `.codegraphignore` from the project root. Lines that fail to parse are
+/// skipped with a warning; a missing file ignores nothing.
+fn load_ignore_file(project_root: &Path) -> Gitignore {
+    let path = project_root.join(IGNORE_FILE);
+    if !path.is_file() {
+        return Gitignore::empty();
+    }
+
+    let mut builder = GitignoreBuilder::new(project_root);
+    if let Some(e) = builder.add(&path) {
+        warn!("Problem reading {}: {}", IGNORE_FILE, e);
+    }
+    builder.build().unwrap_or_else(|e| {
+        warn!("Ignoring {}: {}", IGNORE_FILE, e);
+        Gitignore::empty()
+    })
+}
+
+/// Compiled `[indexing] include` globs; with none, every file is included
+struct IncludeFilter {
+    patterns: Vec<glob::Pattern>,
+    /// Literal leading directories of each glob, used to skip unrelated subtrees
+    prefixes: Vec<PathBuf>,
+}
+
+impl IncludeFilter {
+    fn new(globs: &[String]) -> Result<Self> {
+        let patterns = globs
+            .iter()
+            .map(|g| glob::Pattern::new(g).with_context(|| format!("Invalid [indexing] include glob '{}'", g)))
+            .collect::<Result<Vec<_>>>()?;
+        let prefixes = globs
+            .iter()
+            .map(|g| {
+                g.split('/')
+                    .take_while(|part| !part.contains(['*', '?', '[']))
+                    .collect::<PathBuf>()
+            })
+            .collect();
+        Ok(Self { patterns, prefixes })
+    }
+
+    /// Whether a directory (relative to the project root) could contain included files
+    fn may_contain(&self, dir: &Path) -> bool {
+        self.patterns.is_empty() || self.prefixes.iter().any(|p| p.starts_with(dir) || dir.starts_with(p))
+    }
+
+    /// Whether a file (relative to the project root) is included
+    fn includes(&self, path: &Path) -> bool {
+        let options = glob::MatchOptions {
+            require_literal_separator: true,
+            ..Default::default()
+        };
+        self.patterns.is_empty() || self.patterns.iter().any(|p| p.matches_path_with(path, options))
+    }
+}
+
+pub(crate) fn symbol_kind_to_str(kind: &SymbolKind) -> &'static str {
     match kind {
         SymbolKind::Function => "function",
         SymbolKind::Method => "method",
@@ -459,6 +971,43 @@ mod tests {
         assert!(!is_excluded(Path::new("src/rebuild/mod.rs"), &defaults));
     }
 
+    #[test]
+    fn test_include_filter() {
+        let filter = IncludeFilter::new(&["services/auth/**".to_string(), "libs/*.rs".to_string()]).unwrap();
+        assert!(filter.includes(Path::new("services/auth/src/main.rs")));
+        assert!(filter.includes(Path::new("libs/util.rs")));
+        assert!(!filter.includes(Path::new("libs/nested/util.rs")));
+        assert!(!filter.includes(Path::new("services/billing/main.rs")));
+
+        assert!(filter.may_contain(Path::new("")));
+        assert!(filter.may_contain(Path::new("services")));
+        assert!(filter.may_contain(Path::new("services/auth/src")));
+        assert!(!filter.may_contain(Path::new("services/billing")));
+        assert!(!filter.may_contain(Path::new("docs")));
+
+        let everything = IncludeFilter::new(&[]).unwrap();
+        assert!(everything.includes(Path::new("any/file.rs")));
+        assert!(everything.may_contain(Path::new("any")));
+        assert!(IncludeFilter::new(&["src/[".to_string()]).is_err());
+    }
+
+    #[test]
+    fn test_slowest_files() {
+        let mut profile = IndexProfile::default();
+        for ms in [5, 30, 1, 20] {
+            profile.record_file(&format!("{}.rs", ms), Duration::from_millis(ms));
+        }
+        let order: Vec<&str> = profile.slowest_files.iter().map(|(path, _)| path.as_str()).collect();
+        assert_eq!(order, vec!["30.rs", "20.rs", "5.rs", "1.rs"]);
+
+        for ms in 100..100 + SLOWEST_FILES as u64 {
+            profile.record_file(&format!("{}.rs", ms), Duration::from_millis(ms));
+        }
+        assert_eq!(profile.slowest_files.len(), SLOWEST_FILES);
+        assert_eq!(profile.slowest_files[0].0, format!("{}.rs", 99 + SLOWEST_FILES));
+        assert!(profile.slowest_files.iter().all(|(_, elapsed)| elapsed.as_millis() >= 100));
+    }
+
     #[test]
     fn test_symbol_kind_conversion() {
         assert_eq!(symbol_kind_to_str(&SymbolKind::Function), "function");
@@ -2,12 +2,17 @@ use once_cell::sync::Lazy;
 use std::collections::HashMap;
 use tree_sitter::Language;
 
+use crate::config::LanguageSettings;
+
 #[derive(Debug, Clone)]
 pub struct LanguageConfig {
     pub name: &'static str,
     pub extensions: &'static [&'static str],
     pub tree_sitter_language: Language,
     pub queries: LanguageQueries,
+    /// Node kinds (namespaces, classes, ...) whose names qualify the symbols nested in them,
+    /// giving IDs like `path::Namespace.Class.Method`. Empty means IDs use the bare name.
+    pub scopes: &'static [&'static str],
 }
 
 #[derive(Debug, Clone)]
@@ -31,6 +36,7 @@ pub static LANGUAGE_REGISTRY: Lazy<HashMap<String, LanguageConfig>> = Lazy::new(
                 symbols: include_str!("queries/rust-symbols.scm"),
                 references: include_str!("queries/rust-references.scm"),
             },
+            scopes: &[],
         },
     );
 
@@ -45,6 +51,7 @@ pub static LANGUAGE_REGISTRY: Lazy<HashMap<String, LanguageConfig>> = Lazy::new(
                 symbols: include_str!("queries/typescript-symbols.scm"),
                 references: include_str!("queries/typescript-references.scm"),
             },
+            scopes: &[],
         },
     );
 
@@ -59,6 +66,7 @@ pub static LANGUAGE_REGISTRY: Lazy<HashMap<String, LanguageConfig>> = Lazy::new(
                 symbols: include_str!("queries/javascript-symbols.scm"),
                 references: include_str!("queries/javascript-references.scm"),
             },
+            scopes: &[],
         },
     );
 
@@ -73,6 +81,7 @@ pub static LANGUAGE_REGISTRY: Lazy<HashMap<String, LanguageConfig>> = Lazy::new(
                 symbols: include_str!("queries/python-symbols.scm"),
                 references: include_str!("queries/python-references.scm"),
             },
+            scopes: &[],
         },
     );
 
@@ -87,6 +96,75 @@ pub static LANGUAGE_REGISTRY: Lazy<HashMap<String, LanguageConfig>> = Lazy::new(
                 symbols: include_str!("queries/go-symbols.scm"),
                 references: include_str!("queries/go-references.scm"),
             },
+            scopes: &[],
+        },
+    );
+
+    // Java
+    registry.insert(
+        "java".to_string(),
+        LanguageConfig {
+            name: "java",
+            extensions: &["java"],
+            tree_sitter_language: tree_sitter_java::LANGUAGE.into(),
+            queries: LanguageQueries {
+                symbols: include_str!("queries/java-symbols.scm"),
+                references: include_str!("queries/java-references.scm"),
+            },
+            scopes: &[],
+        },
+    );
+
+    // Ruby
+    registry.insert(
+        "ruby".to_string(),
+        LanguageConfig {
+            name: "ruby",
+            extensions: &["rb", "rake"],
+            tree_sitter_language: tree_sitter_ruby::LANGUAGE.into(),
+            queries: LanguageQueries {
+                symbols: include_str!("queries/ruby-symbols.scm"),
+                references: include_str!("queries/ruby-references.scm"),
+            },
+            scopes: &[],
+        },
+    );
+
+    // PHP (the grammar handles inline HTML around <?php tags)
+    registry.insert(
+        "php".to_string(),
+        LanguageConfig {
+            name: "php",
+            extensions: &["php"],
+            tree_sitter_language: tree_sitter_php::LANGUAGE_PHP.into(),
+            queries: LanguageQueries {
+                symbols: include_str!("queries/php-symbols.scm"),
+                references: include_str!("queries/php-references.scm"),
+            },
+            scopes: &[],
+        },
+    );
+
+    // C#: common method names (Execute, Handle) repeat across classes, so IDs are qualified
+    registry.insert(
+        "csharp".to_string(),
+        LanguageConfig {
+            name: "csharp",
+            extensions: &["cs"],
+            tree_sitter_language: tree_sitter_c_sharp::LANGUAGE.into(),
+            queries: LanguageQueries {
+                symbols: include_str!("queries/csharp-symbols.scm"),
+                references: include_str!("queries/csharp-references.scm"),
+            },
+            scopes: &[
+                "namespace_declaration",
+                "file_scoped_namespace_declaration",
+                "class_declaration",
+                "record_declaration",
+                "struct_declaration",
+                "interface_declaration",
+                "enum_declaration",
+            ],
         },
     );
 
@@ -95,13 +173,37 @@ pub static LANGUAGE_REGISTRY: Lazy<HashMap<String, LanguageConfig>> = Lazy::new(
 
 /// Detect language from file extension
 pub fn detect_language(path: &str) -> Option<&'static LanguageConfig> {
+    detect_language_with(path, &HashMap::new())
+}
+
+/// Detect language from file extension, applying `[indexing.languages]` settings:
+/// extra extensions map to their language, and disabled languages are never detected
+pub fn detect_language_with(
+    path: &str,
+    settings: &HashMap<String, LanguageSettings>,
+) -> Option<&'static LanguageConfig> {
     let extension = std::path::Path::new(path)
         .extension()?
         .to_str()?;
 
-    LANGUAGE_REGISTRY
-        .values()
-        .find(|config| config.extensions.contains(&extension))
+    // Configured extensions take precedence over the built-in ones
+    let configured = settings
+        .iter()
+        .filter(|(_, s)| s.extensions.iter().any(|e| e.trim_start_matches('.') == extension))
+        .map(|(name, _)| name)
+        .min()
+        .and_then(|name| LANGUAGE_REGISTRY.get(name));
+
+    let config = configured.or_else(|| {
+        LANGUAGE_REGISTRY
+            .values()
+            .find(|config| config.extensions.contains(&extension))
+    })?;
+
+    match settings.get(config.name) {
+        Some(s) if !s.enabled => None,
+        _ => Some(config),
+    }
 }
 
 #[cfg(test)]
@@ -115,16 +217,57 @@ mod tests {
         assert_eq!(detect_language("test.js").unwrap().name, "javascript");
         assert_eq!(detect_language("test.py").unwrap().name, "python");
         assert_eq!(detect_language("test.go").unwrap().name, "go");
+        assert_eq!(detect_language("Test.java").unwrap().name, "java");
+        assert_eq!(detect_language("user.rb").unwrap().name, "ruby");
+        assert_eq!(detect_language("lib/tasks/db.rake").unwrap().name, "ruby");
+        assert_eq!(detect_language("index.php").unwrap().name, "php");
+        assert_eq!(detect_language("Program.cs").unwrap().name, "csharp");
         assert!(detect_language("test.txt").is_none());
     }
 
+    #[test]
+    fn test_detect_language_with_settings() {
+        let mut settings = HashMap::new();
+        settings.insert(
+            "python".to_string(),
+            LanguageSettings {
+                extensions: vec!["pyi".to_string()],
+                ..Default::default()
+            },
+        );
+        settings.insert(
+            "go".to_string(),
+            LanguageSettings {
+                enabled: false,
+                ..Default::default()
+            },
+        );
+        settings.insert(
+            "javascript".to_string(),
+            LanguageSettings {
+                extensions: vec![".cjs".to_string()],
+                ..Default::default()
+            },
+        );
+
+        assert_eq!(detect_language_with("stubs/os.pyi", &settings).unwrap().name, "python");
+        assert_eq!(detect_language_with("config.cjs", &settings).unwrap().name, "javascript");
+        assert!(detect_language_with("main.go", &settings).is_none());
+        assert_eq!(detect_language_with("main.rs", &settings).unwrap().name, "rust");
+        assert!(detect_language("stubs/os.pyi").is_none());
+    }
+
     #[test]
     fn test_language_registry() {
-        assert_eq!(LANGUAGE_REGISTRY.len(), 5);
+        assert_eq!(LANGUAGE_REGISTRY.len(), 9);
         assert!(LANGUAGE_REGISTRY.contains_key("rust"));
         assert!(LANGUAGE_REGISTRY.contains_key("typescript"));
         assert!(LANGUAGE_REGISTRY.contains_key("javascript"));
         assert!(LANGUAGE_REGISTRY.contains_key("python"));
         assert!(LANGUAGE_REGISTRY.contains_key("go"));
+        assert!(LANGUAGE_REGISTRY.contains_key("java"));
+        assert!(LANGUAGE_REGISTRY.contains_key("ruby"));
+        assert!(LANGUAGE_REGISTRY.contains_key("php"));
+        assert!(LANGUAGE_REGISTRY.contains_key("csharp"));
     }
 }
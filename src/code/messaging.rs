@@ -0,0 +1,216 @@
+//! Message-queue publish/subscribe detection.
+//!
+//! Finds where code produces to or consumes from a named topic or queue on
+//! Kafka, RabbitMQ, SQS, or Redis pub/sub, so cross-language inference can
+//! connect producers to consumers through the topic they share. Only literal
+//! topic names are recognized; names built at runtime are skipped.
+
+use once_cell::sync::Lazy;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+
+/// Source files scanned for queue clients
+pub const MESSAGING_GLOB: &str = "**/*.{py,js,ts,jsx,tsx,go,java,kt,rb}";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Broker {
+    Kafka,
+    RabbitMq,
+    Sqs,
+    Redis,
+}
+
+impl Broker {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Kafka => "kafka",
+            Self::RabbitMq => "rabbitmq",
+            Self::Sqs => "sqs",
+            Self::Redis => "redis",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Role {
+    Producer,
+    Consumer,
+}
+
+/// One publish or subscribe site
+#[derive(Debug, Clone, PartialEq)]
+pub struct TopicUsage {
+    pub broker: Broker,
+    pub role: Role,
+    pub topic: String,
+    /// Line of the call (1-based)
+    pub line: usize,
+}
+
+/// (broker, role, pattern capturing the name as `topic`)
+static PATTERNS: Lazy<Vec<(Broker, Role, Regex)>> = Lazy::new(|| {
+    use Broker::*;
+    use Role::*;
+    // A quoted topic name
+    let q = r#"['"`](?P<topic>[\w.\-:/]+)['"`]"#;
+    let patterns: Vec<(Broker, Role, String)> = vec![
+        // kafkajs `producer.send({ topic: 'orders' })`, `consumer.subscribe({ topics: ['orders'] })`
+        (Kafka, Producer, format!(r"\.send\(\s*\{{\s*topic\s*:\s*{}", q)),
+        (Kafka, Consumer, format!(r"\.subscribe\(\s*\{{\s*topics?\s*:\s*\[?\s*{}", q)),
+        // kafka-python / confluent-kafka
+        (Kafka, Producer, format!(r"\bproducer\.(?:send|produce)\(\s*(?:topic\s*=\s*)?{}", q)),
+        (Kafka, Consumer, format!(r"\bKafkaConsumer\(\s*{}", q)),
+        (Kafka, Consumer, format!(r"\bconsumer\.subscribe\(\s*\[\s*{}", q)),
+        // Spring Kafka and the Java client
+        (Kafka, Producer, format!(r"\bkafkaTemplate\.send\(\s*{}", q)),
+        (Kafka, Producer, format!(r"\bnew\s+ProducerRecord\s*(?:<[^>]*>)?\(\s*{}", q)),
+        (Kafka, Consumer, format!(r"@KafkaListener\([^)]*?topics\s*=\s*\{{?\s*{}", q)),
+        // Go: sarama and segmentio/kafka-go
+        (Kafka, Producer, format!(r"\bProducerMessage\s*\{{[^}}]*?Topic\s*:\s*{}", q)),
+        (Kafka, Producer, format!(r"\bkafka\.Writer(?:Config)?\s*\{{[^}}]*?Topic\s*:\s*{}", q)),
+        (Kafka, Consumer, format!(r"\bkafka\.ReaderConfig\s*\{{[^}}]*?Topic\s*:\s*{}", q)),
+        (Kafka, Consumer, format!(r"\.ConsumePartition\(\s*{}", q)),
+        // pika, amqplib, Spring AMQP
+        (RabbitMq, Producer, format!(r"\.basic_publish\([^)]*?routing_key\s*=\s*{}", q)),
+        (RabbitMq, Consumer, format!(r"\.basic_consume\([^)]*?queue\s*=\s*{}", q)),
+        (RabbitMq, Producer, format!(r"\.sendToQueue\(\s*{}", q)),
+        (RabbitMq, Consumer, format!(r"\bchannel\.consume\(\s*{}", q)),
+        // `convertAndSend(exchange, routingKey, ...)` or `convertAndSend(queue, ...)`
+        (RabbitMq, Producer, format!(r#"\brabbitTemplate\.convertAndSend\(\s*(?:['"][^'"]*['"]\s*,\s*)?{}"#, q)),
+        (RabbitMq, Consumer, format!(r"@RabbitListener\([^)]*?queues\s*=\s*\{{?\s*{}", q)),
+        // SQS by queue URL (the queue name is its last segment)
+        (Sqs, Producer, format!(r"\bsend_message\([^)]*?QueueUrl\s*=\s*{}", q)),
+        (Sqs, Consumer, format!(r"\breceive_message\([^)]*?QueueUrl\s*=\s*{}", q)),
+        (Sqs, Producer, format!(r"\bSendMessage(?:Batch)?Command\(\s*\{{[^}}]*?QueueUrl\s*:\s*{}", q)),
+        (Sqs, Consumer, format!(r"\bReceiveMessageCommand\(\s*\{{[^}}]*?QueueUrl\s*:\s*{}", q)),
+        (Sqs, Consumer, format!(r"@SqsListener\(\s*(?:value\s*=\s*)?\{{?\s*{}", q)),
+        // Redis pub/sub: redis-py, node-redis/ioredis, go-redis
+        (Redis, Producer, format!(r"\b(?:redis|client|r|rdb|publisher|pub)\.[Pp]ublish\(\s*(?:ctx\s*,\s*)?{}", q)),
+        (Redis, Consumer, format!(r"\b(?:pubsub|subscriber|sub|p|rdb|client)\.[Ss]ubscribe\(\s*(?:ctx\s*,\s*)?{}", q)),
+    ];
+    patterns
+        .into_iter()
+        .map(|(broker, role, pattern)| (broker, role, Regex::new(&pattern).unwrap()))
+        .collect()
+});
+
+/// All publish/subscribe sites with a literal topic in a file, in source order
+pub fn find_topic_usages(content: &str) -> Vec<TopicUsage> {
+    let mut usages: Vec<(usize, TopicUsage)> = Vec::new();
+    for (broker, role, pattern) in PATTERNS.iter() {
+        for cap in pattern.captures_iter(content) {
+            let (Some(whole), Some(topic)) = (cap.get(0), cap.name("topic")) else { continue };
+            let topic = match broker {
+                Broker::Sqs => topic.as_str().rsplit('/').next().unwrap_or_default(),
+                _ => topic.as_str(),
+            };
+            if topic.is_empty() {
+                continue;
+            }
+            usages.push((
+                whole.start(),
+                TopicUsage {
+                    broker: *broker,
+                    role: *role,
+                    topic: topic.to_string(),
+                    line: content[..whole.start()].matches('\n').count() + 1,
+                },
+            ));
+        }
+    }
+    usages.sort_by_key(|(start, _)| *start);
+    // Overlapping patterns (e.g. kafkajs and kafka-python `send`) can find the same call
+    usages.dedup_by(|a, b| a.1 == b.1);
+    usages.into_iter().map(|(_, usage)| usage).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn usages(content: &str) -> Vec<(Broker, Role, String)> {
+        find_topic_usages(content)
+            .into_iter()
+            .map(|u| (u.broker, u.role, u.topic))
+            .collect()
+    }
+
+    #[test]
+    fn test_kafka_usages() {
+        let js = r#"
+await producer.send({ topic: 'orders.created', messages: [{ value }] });
+await consumer.subscribe({ topics: ["orders.created"], fromBeginning: true });
+"#;
+        assert_eq!(
+            usages(js),
+            vec![
+                (Broker::Kafka, Role::Producer, "orders.created".to_string()),
+                (Broker::Kafka, Role::Consumer, "orders.created".to_string()),
+            ]
+        );
+
+        let python = "producer.send('payments', value=b'x')\nconsumer = KafkaConsumer('payments', group_id='g')\n";
+        assert_eq!(
+            usages(python),
+            vec![
+                (Broker::Kafka, Role::Producer, "payments".to_string()),
+                (Broker::Kafka, Role::Consumer, "payments".to_string()),
+            ]
+        );
+
+        let java = r#"
+kafkaTemplate.send("audit", event);
+@KafkaListener(topics = "audit", groupId = "auditors")
+"#;
+        assert_eq!(usages(java).len(), 2);
+
+        let go = r#"msg := &sarama.ProducerMessage{Topic: "clicks", Value: v}
+r := kafka.NewReader(kafka.ReaderConfig{Brokers: brokers, Topic: "clicks"})"#;
+        assert_eq!(
+            usages(go),
+            vec![
+                (Broker::Kafka, Role::Producer, "clicks".to_string()),
+                (Broker::Kafka, Role::Consumer, "clicks".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_other_broker_usages() {
+        let rabbit = r#"
+channel.basic_publish(exchange='', routing_key='emails', body=msg)
+channel.basic_consume(queue='emails', on_message_callback=cb)
+ch.sendToQueue("jobs", Buffer.from(job));
+"#;
+        assert_eq!(
+            usages(rabbit),
+            vec![
+                (Broker::RabbitMq, Role::Producer, "emails".to_string()),
+                (Broker::RabbitMq, Role::Consumer, "emails".to_string()),
+                (Broker::RabbitMq, Role::Producer, "jobs".to_string()),
+            ]
+        );
+
+        let sqs = r#"
+sqs.send_message(QueueUrl="https://sqs.us-east-1.amazonaws.com/123/thumbnails", MessageBody=b)
+await client.send(new ReceiveMessageCommand({ QueueUrl: "https://sqs.us-east-1.amazonaws.com/123/thumbnails" }));
+"#;
+        assert_eq!(
+            usages(sqs),
+            vec![
+                (Broker::Sqs, Role::Producer, "thumbnails".to_string()),
+                (Broker::Sqs, Role::Consumer, "thumbnails".to_string()),
+            ]
+        );
+
+        let redis = "redis.publish('chat', msg)\npubsub.subscribe('chat')\nrdb.Publish(ctx, \"chat\", m)\n";
+        let found = find_topic_usages(redis);
+        assert_eq!(found.len(), 3);
+        assert!(found.iter().all(|u| u.broker == Broker::Redis && u.topic == "chat"));
+        assert_eq!(found[2].line, 3);
+
+        // Names built at runtime are skipped
+        assert!(usages("producer.send(topicFor(user), value)").is_empty());
+    }
+}
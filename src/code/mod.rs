@@ -1,7 +1,15 @@
+pub mod checks;
 pub mod cross_language;
+pub mod env_vars;
 pub mod indexer;
 pub mod languages;
+pub mod messaging;
+pub mod openapi;
 pub mod parser;
+pub mod payload;
+pub mod realtime;
+pub mod sql_tables;
+pub mod watcher;
 
 pub use cross_language::CrossLanguageInferrer;
 pub use indexer::Indexer;
@@ -0,0 +1,509 @@
+//! OpenAPI spec loading and drafting.
+//!
+//! Reads the operations (path, method, and descriptive metadata) from an
+//! `openapi.yaml`/`swagger.json`-style spec in the project, so inferred REST
+//! connections can be checked against it. JSON specs are parsed fully; YAML
+//! specs are read line by line for just the parts needed here, which covers
+//! the block style every generator and hand-written spec uses.
+//!
+//! Going the other way, `draft_spec` turns detected server routes into a
+//! starting-point spec for projects that don't have one.
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Map, Value};
+use std::collections::HashSet;
+use std::path::Path;
+
+use super::cross_language::ServerRoute;
+use crate::store::graph::NodeData;
+use crate::store::CodeGraph;
+
+/// Spec file names, in order of preference
+const SPEC_FILES: &[&str] = &[
+    "openapi.yaml",
+    "openapi.yml",
+    "openapi.json",
+    "swagger.yaml",
+    "swagger.yml",
+    "swagger.json",
+];
+
+/// How deep below the project root to look for a spec (e.g. `docs/api/openapi.yaml`)
+const MAX_SPEC_DEPTH: usize = 3;
+
+const METHODS: &[&str] = &["get", "put", "post", "delete", "patch", "head", "options"];
+
+/// Lines below a route definition to look for the function it decorates or annotates
+const HANDLER_LOOKAHEAD: usize = 3;
+
+/// An OpenAPI document's operations
+#[derive(Debug, Clone)]
+pub struct ApiSpec {
+    /// Spec path relative to the project root
+    pub file: String,
+    pub operations: Vec<SpecOperation>,
+}
+
+/// One path + method in the spec
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct SpecOperation {
+    /// Full path, including the spec's base path
+    pub path: String,
+    /// Uppercase HTTP method
+    pub method: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub operation_id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub summary: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+}
+
+/// Load the project's OpenAPI spec, if it has one
+pub fn load_spec(project_root: &Path) -> Result<Option<ApiSpec>> {
+    let Some(file) = find_spec(project_root) else {
+        return Ok(None);
+    };
+    let path = project_root.join(&file);
+    let content = std::fs::read_to_string(&path).with_context(|| format!("Failed to read {}", file))?;
+    let operations = if file.ends_with(".json") {
+        let value: Value = serde_json::from_str(&content).with_context(|| format!("Invalid JSON in {}", file))?;
+        parse_json(&value)
+    } else {
+        parse_yaml(&content)
+    };
+    Ok(Some(ApiSpec { file, operations }))
+}
+
+/// Relative path of the spec: the project root first, then the shallowest match below it
+fn find_spec(project_root: &Path) -> Option<String> {
+    if let Some(name) = SPEC_FILES.iter().find(|name| project_root.join(name).is_file()) {
+        return Some(name.to_string());
+    }
+    let mut found: Vec<(usize, usize, String)> = ignore::WalkBuilder::new(project_root)
+        .require_git(false)
+        .max_depth(Some(MAX_SPEC_DEPTH))
+        .build()
+        .flatten()
+        .filter(|entry| entry.file_type().is_some_and(|t| t.is_file()))
+        .filter_map(|entry| {
+            let name = entry.file_name().to_str()?;
+            let preference = SPEC_FILES.iter().position(|f| *f == name)?;
+            let rel = entry.path().strip_prefix(project_root).ok()?;
+            Some((entry.depth(), preference, rel.to_string_lossy().to_string()))
+        })
+        .collect();
+    found.sort();
+    found.into_iter().next().map(|(_, _, rel)| rel)
+}
+
+fn parse_json(spec: &Value) -> Vec<SpecOperation> {
+    // OpenAPI 3 `servers[0].url`, Swagger 2 `basePath`
+    let base = spec
+        .pointer("/servers/0/url")
+        .or_else(|| spec.get("basePath"))
+        .and_then(|v| v.as_str())
+        .map(base_path)
+        .unwrap_or_default();
+
+    let mut operations = Vec::new();
+    let Some(paths) = spec.get("paths").and_then(|p| p.as_object()) else {
+        return operations;
+    };
+    for (path, item) in paths {
+        for method in METHODS {
+            let Some(operation) = item.get(*method) else { continue };
+            let text = |key: &str| operation.get(key).and_then(|v| v.as_str()).map(String::from);
+            operations.push(SpecOperation {
+                path: join_path(&base, path),
+                method: method.to_uppercase(),
+                operation_id: text("operationId"),
+                summary: text("summary"),
+                tags: operation
+                    .get("tags")
+                    .and_then(|t| t.as_array())
+                    .map(|tags| tags.iter().filter_map(|t| t.as_str().map(String::from)).collect())
+                    .unwrap_or_default(),
+            });
+        }
+    }
+    operations
+}
+
+/// The indexed symbol handling a route: the handler it names, or else the definition right
+/// below it (decorated functions, annotated methods)
+pub fn handler_symbol<'a>(graph: &'a CodeGraph, route: &ServerRoute) -> Option<&'a NodeData> {
+    let symbols = graph.file_symbols(&route.file);
+    let line_start = |node: &NodeData| node.data.get("line_start").and_then(|v| v.as_u64()).unwrap_or(0) as usize;
+
+    if let Some(handler) = &route.handler {
+        // `controllers.show` or `handlers::show` names `show`
+        let name = handler.rsplit(['.', ':']).next().unwrap_or(handler);
+        return symbols
+            .into_iter()
+            .find(|node| node.data.get("name").and_then(|v| v.as_str()) == Some(name));
+    }
+    symbols
+        .into_iter()
+        .filter(|node| (route.line..=route.line + HANDLER_LOOKAHEAD).contains(&line_start(node)))
+        .min_by_key(|node| line_start(node))
+}
+
+/// A draft OpenAPI 3 document for detected routes, each paired with its handler symbol
+pub fn draft_spec(title: &str, routes: &[(ServerRoute, Option<&NodeData>)]) -> Value {
+    let mut paths = Map::new();
+    let mut operation_ids = HashSet::new();
+
+    for (route, handler) in routes {
+        let path = format!("/{}", route.path);
+        let item = paths
+            .entry(path.clone())
+            .or_insert_with(|| json!({}))
+            .as_object_mut()
+            .expect("path items are objects");
+        // Routes without a method (Flask `route`, Django `path`) are listed as GET
+        let method = route.method.as_deref().unwrap_or("GET").to_lowercase();
+        if item.contains_key(&method) {
+            continue;
+        }
+
+        let name = handler
+            .and_then(|h| h.data.get("name").and_then(|v| v.as_str()))
+            .or(route.handler.as_deref().and_then(|h| h.rsplit(['.', ':']).next()));
+        let base_id = match name {
+            Some(name) => name.to_string(),
+            None => {
+                let segments: Vec<&str> = route.path.split('/').map(|s| s.trim_matches(['{', '}'])).collect();
+                format!("{}_{}", method, segments.join("_").replace(['-', '.'], "_"))
+            }
+        };
+        let mut operation_id = base_id.clone();
+        let mut suffix = 2;
+        while !operation_ids.insert(operation_id.clone()) {
+            operation_id = format!("{}_{}", base_id, suffix);
+            suffix += 1;
+        }
+
+        let mut operation = json!({
+            "operationId": operation_id,
+            "responses": { "200": { "description": "OK" } },
+            "x-codegraph-source": format!("{}:{}", route.file, route.line),
+        });
+        let summary = handler
+            .and_then(|h| h.data.get("docstring").and_then(|v| v.as_str()))
+            .and_then(|doc| doc.lines().map(str::trim).find(|l| !l.is_empty()));
+        if let Some(summary) = summary {
+            operation["summary"] = json!(summary);
+        }
+        if let Some(handler) = handler {
+            operation["x-codegraph-handler"] = json!(handler.id);
+        }
+        if route.method.is_none() {
+            operation["description"] = json!("HTTP method not detected; listed as GET.");
+        }
+        let parameters: Vec<Value> = route
+            .path
+            .split('/')
+            .filter_map(|segment| segment.strip_prefix('{')?.strip_suffix('}'))
+            .map(|name| json!({ "name": name, "in": "path", "required": true, "schema": { "type": "string" } }))
+            .collect();
+        if !parameters.is_empty() {
+            operation["parameters"] = json!(parameters);
+        }
+        item.insert(method, operation);
+    }
+
+    json!({
+        "openapi": "3.0.3",
+        "info": {
+            "title": title,
+            "version": "0.0.0",
+            "description": "Draft generated from detected server routes. Review before publishing.",
+        },
+        "paths": paths,
+    })
+}
+
+/// Read operations from a block-style YAML spec by indentation
+fn parse_yaml(content: &str) -> Vec<SpecOperation> {
+    let mut base = String::new();
+    let mut operations: Vec<SpecOperation> = Vec::new();
+
+    // Indentation of the current path key, operation key, and operation fields
+    let mut in_paths = false;
+    let mut in_servers = false;
+    let mut path: Option<(usize, String)> = None;
+    let mut operation_indent: Option<usize> = None;
+    let mut field_indent: Option<usize> = None;
+    let mut in_tags = false;
+
+    for line in content.lines() {
+        let trimmed = line.trim();
+        if trimmed.is_empty() || trimmed.starts_with('#') {
+            continue;
+        }
+        let indent = line.len() - line.trim_start().len();
+
+        if indent == 0 {
+            in_paths = trimmed == "paths:";
+            in_servers = trimmed == "servers:";
+            path = None;
+            operation_indent = None;
+            if let Some(value) = trimmed.strip_prefix("basePath:") {
+                base = base_path(unquote(value));
+            }
+            continue;
+        }
+
+        if in_servers {
+            // First server only
+            if let Some(url) = trimmed.strip_prefix("- url:").or_else(|| trimmed.strip_prefix("url:")) {
+                if base.is_empty() {
+                    base = base_path(unquote(url));
+                }
+            }
+            continue;
+        }
+        if !in_paths {
+            continue;
+        }
+
+        let Some((key, value)) = split_key(trimmed) else {
+            // A `- tag` list item under `tags:`
+            if in_tags && field_indent.is_some_and(|f| indent >= f) {
+                if let (Some(tag), Some(op)) = (trimmed.strip_prefix("- "), operations.last_mut()) {
+                    op.tags.push(unquote(tag).to_string());
+                }
+            }
+            continue;
+        };
+        in_tags = false;
+
+        match path {
+            // Path keys are the first level under `paths:`
+            Some((path_indent, _)) if indent <= path_indent => {
+                path = key.starts_with('/').then(|| (indent, key.to_string()));
+                operation_indent = None;
+            }
+            None => {
+                path = key.starts_with('/').then(|| (indent, key.to_string()));
+                operation_indent = None;
+            }
+            Some((_, ref current)) => {
+                if operation_indent.is_none_or(|o| indent <= o) {
+                    // Operations are the first level under a path
+                    if operation_indent.is_none_or(|o| indent == o) && METHODS.contains(&key) {
+                        operation_indent = Some(indent);
+                        field_indent = None;
+                        operations.push(SpecOperation {
+                            path: join_path(&base, current),
+                            method: key.to_uppercase(),
+                            ..Default::default()
+                        });
+                    } else {
+                        // `parameters:`, `summary:` etc. on the path item itself
+                        operation_indent = Some(indent);
+                        field_indent = Some(usize::MAX);
+                    }
+                    continue;
+                }
+                // Fields are the first level under an operation
+                if *field_indent.get_or_insert(indent) != indent {
+                    continue;
+                }
+                let Some(op) = operations.last_mut() else { continue };
+                match key {
+                    "operationId" => op.operation_id = Some(unquote(value).to_string()),
+                    "summary" => op.summary = Some(unquote(value).to_string()),
+                    "tags" => {
+                        // Flow style `[a, b]`, or a block list on the following lines
+                        let flow = value.trim().trim_start_matches('[').trim_end_matches(']');
+                        op.tags.extend(flow.split(',').map(|t| unquote(t).to_string()).filter(|t| !t.is_empty()));
+                        in_tags = value.trim().is_empty();
+                    }
+                    _ => {}
+                }
+            }
+        }
+    }
+    operations
+}
+
+/// `key: value` (value may be empty), with quotes removed from the key
+fn split_key(line: &str) -> Option<(&str, &str)> {
+    if line.starts_with('-') {
+        return None;
+    }
+    // Quoted keys may contain colons
+    let (key, rest) = if let Some(quote) = line.chars().next().filter(|c| *c == '"' || *c == '\'') {
+        let end = line[1..].find(quote)? + 1;
+        (&line[1..end], line[end + 1..].trim_start())
+    } else {
+        let colon = line.find(": ").or_else(|| line.strip_suffix(':').map(|l| l.len()))?;
+        (&line[..colon], &line[colon..])
+    };
+    let value = rest.strip_prefix(':')?;
+    Some((key, value.trim()))
+}
+
+fn unquote(value: &str) -> &str {
+    let value = value.trim();
+    value
+        .strip_prefix('"')
+        .and_then(|v| v.strip_suffix('"'))
+        .or_else(|| value.strip_prefix('\'').and_then(|v| v.strip_suffix('\'')))
+        .unwrap_or(value)
+}
+
+/// Path part of a server URL (`https://api.example.com/v1` → `/v1`)
+fn base_path(url: &str) -> String {
+    let path = match url.find("://") {
+        Some(scheme) => url[scheme + 3..].find('/').map_or("", |host| &url[scheme + 3 + host..]),
+        None => url,
+    };
+    path.trim_end_matches('/').to_string()
+}
+
+fn join_path(base: &str, path: &str) -> String {
+    format!("{}/{}", base.trim_end_matches('/'), path.trim_start_matches('/'))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const YAML: &str = r#"
+openapi: 3.0.0
+info:
+  title: Users
+  version: "1.0"
+servers:
+  - url: https://api.example.com/v1
+paths:
+  /users:
+    get:
+      operationId: listUsers
+      summary: List users
+      tags: [users]
+      responses:
+        "200":
+          description: OK
+    post:
+      operationId: createUser
+      tags:
+        - users
+        - admin
+  "/users/{id}":
+    parameters:
+      - name: id
+        in: path
+    get:
+      summary: "Get a user"
+components:
+  schemas:
+    User:
+      type: object
+"#;
+
+    #[test]
+    fn test_parse_yaml() {
+        let operations = parse_yaml(YAML);
+        assert_eq!(
+            operations,
+            vec![
+                SpecOperation {
+                    path: "/v1/users".to_string(),
+                    method: "GET".to_string(),
+                    operation_id: Some("listUsers".to_string()),
+                    summary: Some("List users".to_string()),
+                    tags: vec!["users".to_string()],
+                },
+                SpecOperation {
+                    path: "/v1/users".to_string(),
+                    method: "POST".to_string(),
+                    operation_id: Some("createUser".to_string()),
+                    summary: None,
+                    tags: vec!["users".to_string(), "admin".to_string()],
+                },
+                SpecOperation {
+                    path: "/v1/users/{id}".to_string(),
+                    method: "GET".to_string(),
+                    operation_id: None,
+                    summary: Some("Get a user".to_string()),
+                    tags: vec![],
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_draft_spec() {
+        let mut graph = CodeGraph::new();
+        graph.add_node(
+            "api/users.py::get_user".to_string(),
+            "function".to_string(),
+            json!({"name": "get_user", "file": "api/users.py", "line_start": 5, "docstring": "Fetch a user.\n\nMore."}),
+        );
+        let route = |path: &str, method: Option<&str>, line: usize| ServerRoute {
+            path: path.to_string(),
+            method: method.map(String::from),
+            handler: None,
+            file: "api/users.py".to_string(),
+            line,
+        };
+        let routes = [
+            route("api/users/{id}", Some("GET"), 4),
+            route("api/users", Some("POST"), 20),
+            route("api/users", None, 30),
+        ];
+        let with_handlers: Vec<_> = routes.iter().map(|r| (r.clone(), handler_symbol(&graph, r))).collect();
+        assert_eq!(with_handlers[0].1.unwrap().id, "api/users.py::get_user");
+        assert!(with_handlers[1].1.is_none());
+
+        let spec = draft_spec("users", &with_handlers);
+        let get = &spec["paths"]["/api/users/{id}"]["get"];
+        assert_eq!(get["operationId"], "get_user");
+        assert_eq!(get["summary"], "Fetch a user.");
+        assert_eq!(get["x-codegraph-handler"], "api/users.py::get_user");
+        assert_eq!(get["parameters"][0]["name"], "id");
+
+        let users = &spec["paths"]["/api/users"];
+        assert_eq!(users["post"]["operationId"], "post_api_users");
+        assert_eq!(users["post"]["x-codegraph-source"], "api/users.py:20");
+        assert_eq!(users["get"]["description"], "HTTP method not detected; listed as GET.");
+
+        // The draft reads back as a spec
+        assert_eq!(parse_json(&spec).len(), 3);
+    }
+
+    #[test]
+    fn test_parse_json_and_find_spec() {
+        let dir = tempfile::TempDir::new().unwrap();
+        std::fs::create_dir_all(dir.path().join("docs")).unwrap();
+        let spec = r#"{
+            "swagger": "2.0",
+            "basePath": "/api",
+            "paths": {
+                "/posts/{id}": {
+                    "delete": { "operationId": "deletePost", "tags": ["posts"] }
+                }
+            }
+        }"#;
+        std::fs::write(dir.path().join("docs/swagger.json"), spec).unwrap();
+
+        let spec = load_spec(dir.path()).unwrap().unwrap();
+        assert_eq!(spec.file, "docs/swagger.json");
+        assert_eq!(spec.operations.len(), 1);
+        assert_eq!(spec.operations[0].path, "/api/posts/{id}");
+        assert_eq!(spec.operations[0].method, "DELETE");
+        assert_eq!(spec.operations[0].operation_id.as_deref(), Some("deletePost"));
+
+        // A spec at the root wins
+        std::fs::write(dir.path().join("openapi.yaml"), YAML).unwrap();
+        assert_eq!(load_spec(dir.path()).unwrap().unwrap().file, "openapi.yaml");
+
+        let empty = tempfile::TempDir::new().unwrap();
+        assert!(load_spec(empty.path()).unwrap().is_none());
+    }
+}
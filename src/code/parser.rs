@@ -39,7 +39,22 @@ pub struct ExtractedSymbol {
     pub line_end: u32,
     pub signature: String,
     pub docstring: Option<String>,
+    /// Enclosing scopes joined with `.` (e.g. `App.Services.UserService`), for languages
+    /// whose symbol IDs are qualified
     pub parent: Option<String>,
+    /// Whether code outside the defining module can use it (`pub`, `export`, `public`, a
+    /// capitalized Go name, no leading `_` in Python)
+    pub public: bool,
+}
+
+impl ExtractedSymbol {
+    /// The name used in the symbol's node ID: `parent.name`, or just `name` when unqualified
+    pub fn qualified_name(&self) -> String {
+        match &self.parent {
+            Some(parent) => format!("{}.{}", parent, self.name),
+            None => self.name.clone(),
+        }
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -184,6 +199,8 @@ fn extract_symbols(
                 .trim()
                 .to_string();
 
+            let scopes = enclosing_scopes(node, source, config.scopes);
+            let public = is_public(node, &name, source, config.name);
             symbols.push(ExtractedSymbol {
                 name,
                 kind,
@@ -191,7 +208,8 @@ fn extract_symbols(
                 line_end: end_pos.row as u32 + 1,
                 signature,
                 docstring: None,
-                parent: None,
+                parent: (!scopes.is_empty()).then(|| scopes.join(".")),
+                public,
             });
         }
     }
@@ -199,6 +217,81 @@ fn extract_symbols(
     Ok(symbols)
 }
 
+/// Whether a symbol is visible outside its module, by each language's convention
+fn is_public(node: tree_sitter::Node, name: &str, source: &[u8], language: &str) -> bool {
+    let text = |node: tree_sitter::Node| node.utf8_text(source).unwrap_or("");
+    let has_child = |kind: &str, matches: &dyn Fn(&str) -> bool| {
+        let mut cursor = node.walk();
+        let found = node.named_children(&mut cursor).any(|child| child.kind() == kind && matches(text(child)));
+        found
+    };
+    let inside = |kinds: &[&str]| {
+        let mut current = node.parent();
+        while let Some(parent) = current {
+            if kinds.contains(&parent.kind()) {
+                return Some(parent);
+            }
+            current = parent.parent();
+        }
+        None
+    };
+
+    match language {
+        // Trait methods are as visible as the trait, so only inherent items need `pub`
+        "rust" => {
+            has_child("visibility_modifier", &|_| true)
+                || inside(&["impl_item", "trait_item"])
+                    .is_some_and(|item| item.kind() == "trait_item" || item.child_by_field_name("trait").is_some())
+        }
+        "python" => !name.starts_with('_') || (name.starts_with("__") && name.ends_with("__")),
+        "go" => name.starts_with(|c: char| c.is_uppercase()),
+        // Exported, or a non-private member of an exported class; locals in function bodies never are
+        "typescript" | "javascript" => {
+            if has_child("accessibility_modifier", &|m| m != "public") || name.starts_with('#') {
+                return false;
+            }
+            inside(&["export_statement", "statement_block"]).is_some_and(|n| n.kind() == "export_statement")
+        }
+        "java" | "csharp" => {
+            has_child("modifiers", &|m| m.split_whitespace().any(|m| m == "public"))
+                || has_child("modifier", &|m| m == "public")
+                || inside(&["interface_declaration"]).is_some()
+        }
+        "php" => !has_child("visibility_modifier", &|m| m != "public"),
+        _ => true,
+    }
+}
+
+/// Names of the scopes enclosing a node, outermost first
+fn enclosing_scopes(node: tree_sitter::Node, source: &[u8], scopes: &[&str]) -> Vec<String> {
+    let name_of = |scope: tree_sitter::Node| {
+        scope
+            .child_by_field_name("name")
+            .and_then(|name| name.utf8_text(source).ok())
+            .map(String::from)
+    };
+
+    let mut names = Vec::new();
+    let mut current = node;
+    while let Some(parent) = current.parent() {
+        // A file-scoped scope (C#'s `namespace App;`) covers the declarations after it
+        let mut sibling = current.prev_named_sibling();
+        while let Some(scope) = sibling {
+            if scopes.contains(&scope.kind()) && scope.kind().starts_with("file_scoped_") {
+                names.extend(name_of(scope));
+                break;
+            }
+            sibling = scope.prev_named_sibling();
+        }
+        if scopes.contains(&parent.kind()) {
+            names.extend(name_of(parent));
+        }
+        current = parent;
+    }
+    names.reverse();
+    names
+}
+
 fn extract_references(
     source: &[u8],
     root_node: &tree_sitter::Node,
@@ -254,7 +347,13 @@ fn resolve_from_symbols(symbols: &[ExtractedSymbol], references: &mut [Extracted
         .iter()
         .filter(|s| matches!(
             s.kind,
-            SymbolKind::Function | SymbolKind::Method | SymbolKind::Class | SymbolKind::Struct | SymbolKind::Impl
+            SymbolKind::Function
+                | SymbolKind::Method
+                | SymbolKind::Class
+                | SymbolKind::Struct
+                | SymbolKind::Impl
+                | SymbolKind::Interface
+                | SymbolKind::Enum
         ))
         .collect();
     sorted_symbols.sort_by_key(|s| s.line_end - s.line_start);
@@ -263,7 +362,7 @@ fn resolve_from_symbols(symbols: &[ExtractedSymbol], references: &mut [Extracted
         // Find the smallest symbol that contains this reference line
         for symbol in &sorted_symbols {
             if reference.line >= symbol.line_start && reference.line <= symbol.line_end {
-                reference.from_symbol = Some(symbol.name.clone());
+                reference.from_symbol = Some(symbol.qualified_name());
                 break;
             }
         }
@@ -300,6 +399,19 @@ mod tests {
         assert_eq!(class_sym.unwrap().kind, SymbolKind::Class);
     }
 
+    #[test]
+    fn test_parse_javascript_class_extends() {
+        let source = b"class Foo extends Bar {\n  baz() {}\n}";
+        let config = LANGUAGE_REGISTRY.get("javascript").unwrap();
+
+        let result = parse_file(Path::new("test.js"), source, config).unwrap();
+
+        assert!(result.symbols.iter().any(|s| s.name == "Foo"));
+        let inherits = result.references.iter().find(|r| r.kind == ReferenceKind::Inherits);
+        assert!(inherits.is_some(), "Should find Foo extends Bar");
+        assert_eq!(inherits.unwrap().to_name, "Bar");
+    }
+
     #[test]
     fn test_parse_go_function_and_imports() {
         let source = b"package main\n\nimport \"fmt\"\n\nfunc Hello() {\n\tfmt.Println(\"hi\")\n}\n\ntype Server struct {\n\tPort int\n}\n\nfunc (s *Server) Start() {\n\tfmt.Println(\"starting\")\n}";
@@ -328,6 +440,223 @@ mod tests {
         assert!(!calls.is_empty(), "Should find Go method calls");
     }
 
+    #[test]
+    fn test_parse_java_class_hierarchy() {
+        let source = b"package com.example;\n\nimport java.util.List;\n\npublic interface Repository<T> extends AutoCloseable {\n    List<T> findAll();\n}\n\npublic class UserRepository extends BaseRepository<User> implements Repository<User>, Serializable {\n    public List<User> findAll() {\n        return loadUsers(new UserQuery());\n    }\n}\n\nenum Role implements Named {\n    ADMIN, USER\n}";
+        let config = LANGUAGE_REGISTRY.get("java").unwrap();
+
+        let result = parse_file(Path::new("UserRepository.java"), source, config).unwrap();
+
+        // Symbols: Repository (interface), UserRepository (class), Role (enum), findAll (methods)
+        let interface = result.symbols.iter().find(|s| s.name == "Repository");
+        assert_eq!(interface.unwrap().kind, SymbolKind::Interface);
+        let class = result.symbols.iter().find(|s| s.name == "UserRepository");
+        assert_eq!(class.unwrap().kind, SymbolKind::Class);
+        let role = result.symbols.iter().find(|s| s.name == "Role");
+        assert_eq!(role.unwrap().kind, SymbolKind::Enum);
+        let methods = result.symbols.iter().filter(|s| s.name == "findAll").count();
+        assert_eq!(methods, 2);
+        assert!(result.symbols.iter().filter(|s| s.name == "findAll").all(|s| s.kind == SymbolKind::Method));
+
+        let refs = |kind: ReferenceKind| -> Vec<(&str, Option<&str>)> {
+            result
+                .references
+                .iter()
+                .filter(|r| r.kind == kind)
+                .map(|r| (r.to_name.as_str(), r.from_symbol.as_deref()))
+                .collect()
+        };
+        assert_eq!(refs(ReferenceKind::Import), vec![("java.util.List", None)]);
+        assert_eq!(
+            refs(ReferenceKind::Inherits),
+            vec![("AutoCloseable", Some("Repository")), ("BaseRepository", Some("UserRepository"))]
+        );
+        assert_eq!(
+            refs(ReferenceKind::Implements),
+            vec![
+                ("Repository", Some("UserRepository")),
+                ("Serializable", Some("UserRepository")),
+                ("Named", Some("Role"))
+            ]
+        );
+        assert_eq!(
+            refs(ReferenceKind::Call),
+            vec![("loadUsers", Some("findAll")), ("UserQuery", Some("findAll"))]
+        );
+    }
+
+    #[test]
+    fn test_parse_ruby_classes_and_mixins() {
+        let source = b"require 'json'\nrequire_relative 'models/user'\n\nmodule Admin\n  class UsersController < ApplicationController\n    include Auditable\n\n    def index\n      @users = User.where(active: true)\n    end\n\n    def self.helper\n      format_name(\"x\")\n    end\n  end\nend\n\nclass Post < ActiveRecord::Base\nend";
+        let config = LANGUAGE_REGISTRY.get("ruby").unwrap();
+
+        let result = parse_file(Path::new("users_controller.rb"), source, config).unwrap();
+
+        let kind_of = |name: &str| result.symbols.iter().find(|s| s.name == name).map(|s| s.kind.clone());
+        assert_eq!(kind_of("Admin"), Some(SymbolKind::Module));
+        assert_eq!(kind_of("UsersController"), Some(SymbolKind::Class));
+        assert_eq!(kind_of("Post"), Some(SymbolKind::Class));
+        assert_eq!(kind_of("index"), Some(SymbolKind::Method));
+        assert_eq!(kind_of("helper"), Some(SymbolKind::Method));
+
+        let refs = |kind: ReferenceKind| -> Vec<(&str, Option<&str>)> {
+            result
+                .references
+                .iter()
+                .filter(|r| r.kind == kind)
+                .map(|r| (r.to_name.as_str(), r.from_symbol.as_deref()))
+                .collect()
+        };
+        assert_eq!(refs(ReferenceKind::Import), vec![("json", None), ("models/user", None)]);
+        assert_eq!(
+            refs(ReferenceKind::Inherits),
+            vec![("ApplicationController", Some("UsersController")), ("Base", Some("Post"))]
+        );
+        assert_eq!(refs(ReferenceKind::Implements), vec![("Auditable", Some("UsersController"))]);
+        assert_eq!(refs(ReferenceKind::Call), vec![("where", Some("index")), ("format_name", Some("helper"))]);
+    }
+
+    #[test]
+    fn test_parse_php_classes_and_imports() {
+        let source = b"<?php\n\nnamespace App\\Http\\Controllers;\n\nuse App\\Models\\User;\nrequire_once 'helpers.php';\n\ninterface Exportable extends Arrayable\n{\n    public function export(): array;\n}\n\ntrait Auditable\n{\n}\n\nclass UserController extends Controller implements Exportable\n{\n    use Auditable;\n\n    public function index()\n    {\n        return User::where('active', true)->get();\n    }\n}\n\nfunction helper() { return new UserExport(); }";
+        let config = LANGUAGE_REGISTRY.get("php").unwrap();
+
+        let result = parse_file(Path::new("UserController.php"), source, config).unwrap();
+
+        let kind_of = |name: &str| result.symbols.iter().find(|s| s.name == name).map(|s| s.kind.clone());
+        assert_eq!(kind_of("App\\Http\\Controllers"), Some(SymbolKind::Module));
+        assert_eq!(kind_of("Exportable"), Some(SymbolKind::Interface));
+        assert_eq!(kind_of("Auditable"), Some(SymbolKind::Trait));
+        assert_eq!(kind_of("UserController"), Some(SymbolKind::Class));
+        assert_eq!(kind_of("index"), Some(SymbolKind::Method));
+        assert_eq!(kind_of("helper"), Some(SymbolKind::Function));
+
+        let refs = |kind: ReferenceKind| -> Vec<(&str, Option<&str>)> {
+            result
+                .references
+                .iter()
+                .filter(|r| r.kind == kind)
+                .map(|r| (r.to_name.as_str(), r.from_symbol.as_deref()))
+                .collect()
+        };
+        assert_eq!(refs(ReferenceKind::Import), vec![("App\\Models\\User", None), ("helpers.php", None)]);
+        assert_eq!(
+            refs(ReferenceKind::Inherits),
+            vec![("Arrayable", Some("Exportable")), ("Controller", Some("UserController"))]
+        );
+        assert_eq!(
+            refs(ReferenceKind::Implements),
+            vec![("Exportable", Some("UserController")), ("Auditable", Some("UserController"))]
+        );
+        assert_eq!(
+            refs(ReferenceKind::Call),
+            vec![("where", Some("index")), ("get", Some("index")), ("UserExport", Some("helper"))]
+        );
+    }
+
+    #[test]
+    fn test_parse_csharp_qualified_names() {
+        let source = b"using System;\nusing App.Models;\n\nnamespace App.Services\n{\n    public interface ICommand { void Execute(); }\n\n    public class CreateUser : BaseCommand, ICommand\n    {\n        public void Execute() { Log(\"x\"); new User(); repo.Save(user); }\n    }\n\n    namespace Inner { struct Point { } }\n}";
+        let config = LANGUAGE_REGISTRY.get("csharp").unwrap();
+
+        let result = parse_file(Path::new("CreateUser.cs"), source, config).unwrap();
+
+        let symbols: Vec<(String, SymbolKind)> =
+            result.symbols.iter().map(|s| (s.qualified_name(), s.kind.clone())).collect();
+        assert!(symbols.contains(&("App.Services".to_string(), SymbolKind::Module)));
+        assert!(symbols.contains(&("App.Services.ICommand".to_string(), SymbolKind::Interface)));
+        assert!(symbols.contains(&("App.Services.ICommand.Execute".to_string(), SymbolKind::Method)));
+        assert!(symbols.contains(&("App.Services.CreateUser".to_string(), SymbolKind::Class)));
+        assert!(symbols.contains(&("App.Services.CreateUser.Execute".to_string(), SymbolKind::Method)));
+        assert!(symbols.contains(&("App.Services.Inner.Point".to_string(), SymbolKind::Struct)));
+
+        let refs = |kind: ReferenceKind| -> Vec<(&str, Option<&str>)> {
+            result
+                .references
+                .iter()
+                .filter(|r| r.kind == kind)
+                .map(|r| (r.to_name.as_str(), r.from_symbol.as_deref()))
+                .collect()
+        };
+        assert_eq!(refs(ReferenceKind::Import), vec![("System", None), ("App.Models", None)]);
+        assert_eq!(refs(ReferenceKind::Inherits), vec![("BaseCommand", Some("App.Services.CreateUser"))]);
+        assert_eq!(refs(ReferenceKind::Implements), vec![("ICommand", Some("App.Services.CreateUser"))]);
+        let from = Some("App.Services.CreateUser.Execute");
+        assert_eq!(refs(ReferenceKind::Call), vec![("Log", from), ("User", from), ("Save", from)]);
+    }
+
+    #[test]
+    fn test_parse_csharp_file_scoped_namespace() {
+        let source = b"namespace App.Handlers;\n\npublic record Deleted(int Id) : IEvent;\n\npublic class DeleteUser : ICommand<User>\n{\n    public void Execute() => Handle();\n}";
+        let config = LANGUAGE_REGISTRY.get("csharp").unwrap();
+
+        let result = parse_file(Path::new("DeleteUser.cs"), source, config).unwrap();
+
+        let names: Vec<String> = result.symbols.iter().map(|s| s.qualified_name()).collect();
+        assert!(names.contains(&"App.Handlers.Deleted".to_string()));
+        assert!(names.contains(&"App.Handlers.DeleteUser.Execute".to_string()));
+        let implements: Vec<&str> = result
+            .references
+            .iter()
+            .filter(|r| r.kind == ReferenceKind::Implements)
+            .map(|r| r.to_name.as_str())
+            .collect();
+        assert_eq!(implements, vec!["IEvent", "ICommand"]);
+    }
+
+    #[test]
+    fn test_symbol_visibility() {
+        let cases: &[(&str, &str, &[(&str, bool)])] = &[
+            (
+                "rust",
+                "pub fn api() {}\nfn helper() {}\nstruct Inner;\nimpl Inner { pub fn new() {} fn step() {} }\nimpl Drop for Inner { fn drop(&mut self) {} }",
+                &[("api", true), ("helper", false), ("Inner", false), ("new", true), ("step", false), ("drop", true)],
+            ),
+            (
+                "python",
+                "def api():\n    pass\n\ndef _helper():\n    pass\n\nclass User:\n    def __init__(self):\n        pass\n",
+                &[("api", true), ("_helper", false), ("User", true), ("__init__", true)],
+            ),
+            ("go", "package main\n\nfunc Serve() {}\n\nfunc helper() {}\n", &[("Serve", true), ("helper", false)]),
+            (
+                "typescript",
+                "export class Api {\n  get() { const local = 1; }\n  private cache() {}\n}\nfunction helper() {}\nexport const VERSION = 1;",
+                &[
+                    ("Api", true),
+                    ("get", true),
+                    ("local", false),
+                    ("cache", false),
+                    ("helper", false),
+                    ("VERSION", true),
+                ],
+            ),
+            (
+                "java",
+                "public class Api {\n  public void get() {}\n  private void cache() {}\n}\ninterface Store { void save(); }",
+                &[("Api", true), ("get", true), ("cache", false), ("Store", false), ("save", true)],
+            ),
+            (
+                "csharp",
+                "public class Api\n{\n    public void Get() {}\n    void Cache() {}\n}",
+                &[("Api", true), ("Get", true), ("Cache", false)],
+            ),
+            (
+                "php",
+                "<?php\nclass Api\n{\n    public function get() {}\n    private function cache() {}\n    function legacy() {}\n}",
+                &[("Api", true), ("get", true), ("cache", false), ("legacy", true)],
+            ),
+        ];
+
+        for (language, source, expected) in cases {
+            let config = LANGUAGE_REGISTRY.get(*language).unwrap();
+            let result = parse_file(Path::new("source"), source.as_bytes(), config).unwrap();
+            for (name, public) in *expected {
+                let symbol = result.symbols.iter().find(|s| s.name == *name);
+                assert_eq!(symbol.map(|s| s.public), Some(*public), "{} {}", language, name);
+            }
+        }
+    }
+
     #[test]
     fn test_parse_python_function() {
         let source = b"def test_func():\n    pass";
@@ -0,0 +1,868 @@
+//! Request/response payload shapes for matched API connections.
+//!
+//! Finds the types a client call sends and expects (`axios.get<User>(...)`,
+//! `JSON.stringify(body)` with `body: NewUser`) and the types a handler accepts
+//! and returns (FastAPI models, Axum/Actix `Json<T>`, NestJS `@Body()`, Spring
+//! `@RequestBody`), resolves both to their field lists, and reports fields one
+//! side relies on that the other doesn't provide. Field names are compared in
+//! their serialized form, so serde `rename_all` and Pydantic aliases apply.
+
+use once_cell::sync::Lazy;
+use regex::Regex;
+use std::collections::HashMap;
+
+/// Files whose type definitions can describe payloads
+pub const PAYLOAD_TYPE_GLOB: &str = "**/*.{ts,tsx,py,rs,java}";
+
+/// Lines after a client call searched for its response type (`as User`, `res.json()`)
+const CLIENT_LOOKAHEAD: usize = 2;
+
+/// Lines from a route definition searched for the handler signature
+const HANDLER_LOOKAHEAD: usize = 12;
+
+/// A payload type as named in code, e.g. `User` or `User[]`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TypeRef {
+    pub name: String,
+    pub list: bool,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PayloadTypes {
+    pub request: Option<TypeRef>,
+    pub response: Option<TypeRef>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Field {
+    /// Name as serialized on the wire
+    pub name: String,
+    pub optional: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TypeDef {
+    pub name: String,
+    pub fields: Vec<Field>,
+    /// Has members whose fields can't be listed (serde `flatten`, spreads, index
+    /// signatures), so missing fields can't be reported
+    pub open: bool,
+}
+
+impl TypeDef {
+    fn field(&self, name: &str) -> Option<&Field> {
+        self.fields.iter().find(|f| f.name == name)
+    }
+
+    /// A field differing only in case or separators (`userName` vs `user_name`)
+    fn similar_field(&self, name: &str) -> Option<&Field> {
+        let key = loose_name(name);
+        self.fields.iter().find(|f| loose_name(&f.name) == key)
+    }
+}
+
+/// Type definitions by name across the project
+#[derive(Debug, Default)]
+pub struct TypeIndex {
+    types: HashMap<String, Vec<(String, TypeDef)>>,
+}
+
+impl TypeIndex {
+    pub fn insert(&mut self, file: &str, content: &str) {
+        for def in type_definitions(file, content) {
+            self.types.entry(def.name.clone()).or_default().push((file.to_string(), def));
+        }
+    }
+
+    /// The definition of `name`, preferring `file` itself, then files in the same language
+    pub fn resolve(&self, name: &str, file: &str) -> Option<&TypeDef> {
+        let candidates = self.types.get(name)?;
+        let ext = extension(file);
+        candidates
+            .iter()
+            .find(|(f, _)| f == file)
+            .or_else(|| candidates.iter().find(|(f, _)| language(extension(f)) == language(ext)))
+            .map(|(_, def)| def)
+    }
+
+    fn resolve_ref<'a>(&'a self, type_ref: &'a Option<TypeRef>, file: &str) -> Option<(&'a TypeRef, &'a TypeDef)> {
+        let type_ref = type_ref.as_ref()?;
+        Some((type_ref, self.resolve(&type_ref.name, file)?))
+    }
+}
+
+/// Field mismatches between what a client call sends/expects and what its handler
+/// accepts/returns. Types that can't be found or resolved are skipped.
+pub fn payload_mismatches(
+    index: &TypeIndex,
+    client_file: &str,
+    client: &PayloadTypes,
+    server_file: &str,
+    server: &PayloadTypes,
+) -> Vec<String> {
+    let mut warnings = Vec::new();
+    if let (Some(sent), Some(accepted)) =
+        (index.resolve_ref(&client.request, client_file), index.resolve_ref(&server.request, server_file))
+    {
+        warnings.extend(compare_request(sent, accepted));
+    }
+    if let (Some(expected), Some(returned)) =
+        (index.resolve_ref(&client.response, client_file), index.resolve_ref(&server.response, server_file))
+    {
+        warnings.extend(compare_response(expected, returned));
+    }
+    warnings
+}
+
+/// Fields the handler requires that the client type lacks, and client fields the handler ignores
+fn compare_request(
+    (sent_ref, sent): (&TypeRef, &TypeDef),
+    (accepted_ref, accepted): (&TypeRef, &TypeDef),
+) -> Vec<String> {
+    let mut warnings = Vec::new();
+    if sent_ref.list != accepted_ref.list {
+        warnings.push(format!(
+            "request: client sends {} but handler accepts {}",
+            display(sent_ref),
+            display(accepted_ref)
+        ));
+        return warnings;
+    }
+    if !sent.open {
+        for field in accepted.fields.iter().filter(|f| !f.optional && sent.field(&f.name).is_none()) {
+            match sent.similar_field(&field.name) {
+                Some(similar) => warnings.push(format!(
+                    "request field `{}` ({}): client sends `{}` ({})",
+                    field.name, accepted.name, similar.name, sent.name
+                )),
+                None => warnings.push(format!(
+                    "request field `{}` required by {} is missing from client type {}",
+                    field.name, accepted.name, sent.name
+                )),
+            }
+        }
+    }
+    if !accepted.open {
+        for field in sent.fields.iter().filter(|f| accepted.field(&f.name).is_none()) {
+            // Already reported as a naming mismatch above
+            if accepted.similar_field(&field.name).is_some_and(|a| !a.optional) {
+                continue;
+            }
+            warnings.push(format!(
+                "request field `{}` sent by client type {} is not accepted by {}",
+                field.name, sent.name, accepted.name
+            ));
+        }
+    }
+    warnings
+}
+
+/// Fields the client relies on that the handler's response type doesn't guarantee
+fn compare_response(
+    (expected_ref, expected): (&TypeRef, &TypeDef),
+    (returned_ref, returned): (&TypeRef, &TypeDef),
+) -> Vec<String> {
+    let mut warnings = Vec::new();
+    if expected_ref.list != returned_ref.list {
+        warnings.push(format!(
+            "response: client expects {} but handler returns {}",
+            display(expected_ref),
+            display(returned_ref)
+        ));
+        return warnings;
+    }
+    if returned.open {
+        return warnings;
+    }
+    for field in expected.fields.iter().filter(|f| !f.optional) {
+        match (returned.field(&field.name), returned.similar_field(&field.name)) {
+            (Some(r), _) if r.optional => warnings.push(format!(
+                "response field `{}` is optional in {} but required by client type {}",
+                field.name, returned.name, expected.name
+            )),
+            (Some(_), _) => {}
+            (None, Some(similar)) => warnings.push(format!(
+                "response field `{}` ({}): handler returns `{}` ({})",
+                field.name, expected.name, similar.name, returned.name
+            )),
+            (None, None) => warnings.push(format!(
+                "response field `{}` expected by client type {} is missing from {}",
+                field.name, expected.name, returned.name
+            )),
+        }
+    }
+    warnings
+}
+
+fn display(type_ref: &TypeRef) -> String {
+    if type_ref.list {
+        format!("{}[]", type_ref.name)
+    } else {
+        type_ref.name.clone()
+    }
+}
+
+fn loose_name(name: &str) -> String {
+    name.chars().filter(|c| *c != '_' && *c != '-').flat_map(char::to_lowercase).collect()
+}
+
+fn extension(path: &str) -> &str {
+    path.rsplit_once('.').map_or("", |(_, ext)| ext)
+}
+
+fn language(ext: &str) -> &str {
+    match ext {
+        "ts" | "tsx" => "ts",
+        other => other,
+    }
+}
+
+// ===== Call sites and handlers =====
+
+/// `axios.get<User[]>(`, `http.post<User>(`
+static CLIENT_GENERIC: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"\.(?:get|post|put|patch|delete|request)\s*<\s*(?:Array<\s*)?(\w+)\s*(\[\])?").unwrap()
+});
+
+/// `const users: User[] = await ...`
+static CLIENT_TYPED_RESULT: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"\b(?:const|let|var)\s+\w+\s*:\s*(?:Array<\s*)?(\w+)\s*(\[\])?\s*>?\s*=\s*await\b").unwrap());
+
+/// `(await res.json()) as User`, `.then(r => r.data as User[])`
+static CLIENT_CAST: Lazy<Regex> = Lazy::new(|| Regex::new(r"\bas\s+(?:Array<\s*)?([A-Z]\w*)\s*(\[\])?").unwrap());
+
+/// The body argument: `JSON.stringify(payload)` or `axios.post(url, payload)`
+static CLIENT_BODY: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"JSON\.stringify\(\s*(\w+)\s*\)|\.(?:post|put|patch)\s*(?:<[^>]*>)?\(\s*[^,()]+,\s*(\w+)\s*[,)]").unwrap()
+});
+
+/// Types a client call at `line` (1-based) sends and expects
+pub fn client_payload_types(content: &str, line: usize) -> PayloadTypes {
+    let lines: Vec<&str> = content.lines().collect();
+    let start = line.saturating_sub(1).min(lines.len());
+    let call = lines[start..(start + 1 + CLIENT_LOOKAHEAD).min(lines.len())].join("\n");
+    let first_line = lines.get(start).copied().unwrap_or_default();
+
+    let response = CLIENT_GENERIC
+        .captures(first_line)
+        .or_else(|| CLIENT_TYPED_RESULT.captures(first_line))
+        .or_else(|| CLIENT_CAST.captures(&call))
+        .map(|cap| type_ref(&cap[1], cap.get(2).is_some()));
+
+    let request = CLIENT_BODY.captures(&call).and_then(|cap| {
+        let ident = cap.get(1).or(cap.get(2))?.as_str();
+        // The variable's declared type: `const payload: NewUser = ...` or a parameter
+        let declaration = Regex::new(&format!(
+            r"\b{}\s*:\s*(?:Array<\s*)?([A-Z]\w*)\s*(\[\])?",
+            regex::escape(ident)
+        ))
+        .ok()?;
+        let cap = declaration.captures(content)?;
+        Some(type_ref(&cap[1], cap.get(2).is_some()))
+    });
+
+    PayloadTypes { request, response }
+}
+
+/// FastAPI: `user: UserIn` parameters and `-> UserOut` / `response_model=UserOut`
+static PY_PARAM: Lazy<Regex> = Lazy::new(|| Regex::new(r"[(,]\s*\w+\s*:\s*(?:list\[|List\[)?([A-Z]\w*)(\])?").unwrap());
+static PY_RESPONSE: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"(?:response_model\s*=|->)\s*(?:list\[|List\[)?([A-Z]\w*)(\])?").unwrap());
+
+/// Axum/Actix: `Json(body): Json<NewUser>` / `web::Json<NewUser>` and `-> Json<User>`
+static RS_REQUEST: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r":\s*(?:web::)?Json<\s*(Vec<\s*)?(\w+)").unwrap());
+static RS_RESPONSE: Lazy<Regex> = Lazy::new(|| Regex::new(r"->\s*[^{]*?Json<\s*(Vec<\s*)?(\w+)").unwrap());
+
+/// NestJS: `@Body() dto: CreateUserDto` and `): Promise<User> {`
+static TS_REQUEST: Lazy<Regex> = Lazy::new(|| Regex::new(r"@Body\(\)\s*\w+\s*:\s*(\w+)\s*(\[\])?").unwrap());
+static TS_RESPONSE: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"\)\s*:\s*(?:Promise<\s*)?(?:Array<\s*)?(\w+)\s*(\[\])?\s*>?\s*>?\s*\{").unwrap());
+
+/// Spring: `@RequestBody NewUser body` and `public ResponseEntity<User> create(`
+static JAVA_REQUEST: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"@RequestBody\s+(?:@\w+\s+)*(List<\s*)?(\w+)").unwrap());
+static JAVA_RESPONSE: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"\bpublic\s+(?:ResponseEntity<\s*)?(List<\s*)?(\w+)\s*>?\s*>?\s+\w+\s*\(").unwrap()
+});
+
+/// Types the handler of a route at `line` (1-based) accepts and returns. Routes that name
+/// their handler (`post(create_user)`) are looked up by the handler's definition.
+pub fn handler_payload_types(path: &str, content: &str, line: usize, handler: Option<&str>) -> PayloadTypes {
+    let lines: Vec<&str> = content.lines().collect();
+    let mut start = line.saturating_sub(1);
+    if let Some(handler) = handler {
+        let name = handler.rsplit(['.', ':']).next().unwrap_or(handler);
+        let definition = Regex::new(&format!(r"\b(?:fn|def|function)\s+{}\b", regex::escape(name))).unwrap();
+        if let Some(found) = lines.iter().position(|l| definition.is_match(l)) {
+            start = found;
+        }
+    }
+    let signature = handler_signature(&lines, start);
+
+    let (request, response) = match extension(path) {
+        "py" => (
+            PY_PARAM.captures(&signature).map(|c| type_ref(&c[1], c.get(2).is_some())),
+            PY_RESPONSE.captures(&signature).map(|c| type_ref(&c[1], c.get(2).is_some())),
+        ),
+        "rs" => (
+            RS_REQUEST.captures(&signature).map(|c| type_ref(&c[2], c.get(1).is_some())),
+            RS_RESPONSE.captures(&signature).map(|c| type_ref(&c[2], c.get(1).is_some())),
+        ),
+        "ts" => (
+            TS_REQUEST.captures(&signature).map(|c| type_ref(&c[1], c.get(2).is_some())),
+            TS_RESPONSE.captures(&signature).map(|c| type_ref(&c[1], c.get(2).is_some())),
+        ),
+        "java" => (
+            JAVA_REQUEST.captures(&signature).map(|c| type_ref(&c[2], c.get(1).is_some())),
+            JAVA_RESPONSE.captures(&signature).map(|c| type_ref(&c[2], c.get(1).is_some())),
+        ),
+        _ => (None, None),
+    };
+    PayloadTypes { request, response }
+}
+
+/// From the route line through the end of the function signature: the first line ending
+/// in `{` or `:` that isn't a decorator or attribute
+fn handler_signature(lines: &[&str], start: usize) -> String {
+    let mut signature = Vec::new();
+    for line in lines.iter().skip(start).take(HANDLER_LOOKAHEAD) {
+        signature.push(*line);
+        let trimmed = line.trim();
+        let decorator = trimmed.starts_with('@') || trimmed.starts_with("#[");
+        if !decorator && (trimmed.ends_with('{') || trimmed.ends_with(':')) {
+            break;
+        }
+    }
+    signature.join("\n")
+}
+
+fn type_ref(name: &str, list: bool) -> TypeRef {
+    TypeRef {
+        name: name.to_string(),
+        list,
+    }
+}
+
+// ===== Type definitions =====
+
+/// Payload-like type definitions (interfaces, models, structs, classes) in a file
+pub fn type_definitions(path: &str, content: &str) -> Vec<TypeDef> {
+    match extension(path) {
+        "ts" | "tsx" => ts_types(content),
+        "py" => python_types(content),
+        "rs" => rust_types(content),
+        "java" => java_types(content),
+        _ => Vec::new(),
+    }
+}
+
+static TS_TYPE_DECL: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"\b(?:interface|class)\s+(\w+)(?:<[^>{]*>)?[^{;=]*\{|\btype\s+(\w+)(?:<[^>]*>)?\s*=\s*\{").unwrap()
+});
+static TS_EXTENDS: Lazy<Regex> = Lazy::new(|| Regex::new(r"\bextends\s+([\w\s,]+?)\s*(?:implements\b|\{)").unwrap());
+static TS_FIELD: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(
+        r#"^\s*(?:@\w+(?:\([^)]*\))?\s*)*(?:(?:public|private|protected|readonly|declare)\s+)*['"]?(\w+)['"]?\s*(\?)?!?\s*:\s*([^;,]+)"#,
+    )
+    .unwrap()
+});
+
+fn ts_types(content: &str) -> Vec<TypeDef> {
+    let mut types = Vec::new();
+    for cap in TS_TYPE_DECL.captures_iter(content) {
+        let name = cap.get(1).or(cap.get(2)).unwrap().as_str();
+        let header = cap.get(0).unwrap();
+        let parents = TS_EXTENDS
+            .captures(header.as_str())
+            .map(|c| c[1].split(',').map(|p| p.trim().to_string()).collect())
+            .unwrap_or_default();
+        let mut def = TypeDef {
+            name: name.to_string(),
+            fields: Vec::new(),
+            open: false,
+        };
+        for member in top_level_members(enclosed(content, header.end(), '{', '}'), &[';', ',', '\n']) {
+            let member = member.trim();
+            if member.starts_with("...") || member.starts_with('[') {
+                def.open = true;
+                continue;
+            }
+            if let Some(field) = TS_FIELD.captures(member) {
+                let optional = field.get(2).is_some() || field[3].contains("undefined");
+                def.fields.push(Field {
+                    name: field[1].to_string(),
+                    optional,
+                });
+            }
+        }
+        types.push((def, parents));
+    }
+    inherit(types)
+}
+
+static PY_CLASS: Lazy<Regex> = Lazy::new(|| Regex::new(r"(?m)^([ \t]*)class\s+(\w+)\s*(?:\(([^)]*)\))?\s*:").unwrap());
+static PY_FIELD: Lazy<Regex> = Lazy::new(|| Regex::new(r"^(\w+)\s*:\s*([^=#]+?)\s*(?:=\s*(.+))?$").unwrap());
+/// Bases marking a Python class as a payload model
+const PY_MODEL_BASES: &[&str] = &["BaseModel", "Schema", "TypedDict", "SQLModel"];
+static PY_ALIAS: Lazy<Regex> = Lazy::new(|| Regex::new(r#"\balias\s*=\s*['"]([^'"]+)['"]"#).unwrap());
+
+fn python_types(content: &str) -> Vec<TypeDef> {
+    let lines: Vec<&str> = content.lines().collect();
+    let mut types = Vec::new();
+    for cap in PY_CLASS.captures_iter(content) {
+        let bases: Vec<&str> = cap.get(3).map_or("", |m| m.as_str()).split(',').map(str::trim).collect();
+        let class_line = content[..cap.get(0).unwrap().start()].matches('\n').count();
+        let dataclass = class_line > 0 && lines[class_line - 1].trim_start().starts_with("@dataclass");
+        let model = dataclass || bases.iter().any(|b| PY_MODEL_BASES.iter().any(|m| b.ends_with(m)));
+        // Subclasses of models in the same file are models too
+        let parents: Vec<String> = bases
+            .iter()
+            .filter(|b| !b.is_empty() && !PY_MODEL_BASES.iter().any(|m| b.ends_with(m)))
+            .map(|b| b.to_string())
+            .collect();
+        let subclass = parents.iter().any(|p| types.iter().any(|(t, _): &(TypeDef, _)| t.name == *p));
+        if !model && !subclass {
+            continue;
+        }
+
+        let class_indent = cap[1].len();
+        let mut body_indent = None;
+        let mut def = TypeDef {
+            name: cap[2].to_string(),
+            fields: Vec::new(),
+            open: false,
+        };
+        let mut camel = false;
+        for line in &lines[class_line + 1..] {
+            let trimmed = line.trim_start();
+            if trimmed.is_empty() || trimmed.starts_with('#') {
+                continue;
+            }
+            let indent = line.len() - trimmed.len();
+            if indent <= class_indent {
+                break;
+            }
+            // Fields sit at the body's own indent; deeper lines are nested classes or methods
+            if *body_indent.get_or_insert(indent) != indent {
+                continue;
+            }
+            if trimmed.contains("alias_generator") && trimmed.contains("camel") {
+                camel = true;
+            }
+            let Some(field) = PY_FIELD.captures(trimmed) else { continue };
+            let name = &field[1];
+            if name == "model_config" || name.starts_with('_') || field[2].starts_with("ClassVar") {
+                continue;
+            }
+            let default = field.get(3).map_or("", |m| m.as_str());
+            let optional = field[2].contains("Optional[") || field[2].contains("None") || py_has_default(default);
+            let alias = PY_ALIAS.captures(default).map(|a| a[1].to_string());
+            def.fields.push(Field {
+                name: alias.unwrap_or_else(|| name.to_string()),
+                optional,
+            });
+        }
+        if camel {
+            for field in &mut def.fields {
+                field.name = rename("camelCase", &field.name);
+            }
+        }
+        types.push((def, parents));
+    }
+    inherit(types)
+}
+
+/// Whether a Pydantic/dataclass field default makes the field optional: any value other
+/// than `...` or a `Field(...)` without a default
+fn py_has_default(default: &str) -> bool {
+    let Some(args) = default.strip_prefix("Field(") else {
+        return !default.is_empty() && default != "...";
+    };
+    let first = args.split([',', ')']).next().unwrap_or_default().trim();
+    args.contains("default") || (!first.is_empty() && first != "..." && !first.contains('='))
+}
+
+static RS_STRUCT: Lazy<Regex> = Lazy::new(|| Regex::new(r"\bstruct\s+(\w+)(?:<[^>{]*>)?\s*\{").unwrap());
+static RS_FIELD: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"^\s*(?:pub(?:\([^)]*\))?\s+)?(?:r#)?(\w+)\s*:\s*(.+?),?\s*$").unwrap());
+static SERDE_RENAME_ALL: Lazy<Regex> = Lazy::new(|| Regex::new(r#"rename_all\s*=\s*"([^"]+)""#).unwrap());
+static SERDE_RENAME: Lazy<Regex> = Lazy::new(|| Regex::new(r#"\brename\s*=\s*"([^"]+)""#).unwrap());
+
+fn rust_types(content: &str) -> Vec<TypeDef> {
+    let mut types = Vec::new();
+    for cap in RS_STRUCT.captures_iter(content) {
+        let start = cap.get(0).unwrap().start();
+        let line_start = content[..start].rfind('\n').map_or(0, |i| i + 1);
+        // Attributes directly above the struct
+        let attributes: String = content[..line_start]
+            .lines()
+            .rev()
+            .take_while(|l| l.trim_start().starts_with("#[") || l.trim_start().starts_with("///"))
+            .collect();
+        let rename_all = SERDE_RENAME_ALL.captures(&attributes).map(|c| c[1].to_string());
+
+        let mut def = TypeDef {
+            name: cap[1].to_string(),
+            fields: Vec::new(),
+            open: false,
+        };
+        let mut field_attributes = String::new();
+        for line in top_level_members(enclosed(content, cap.get(0).unwrap().end(), '{', '}'), &['\n']) {
+            let trimmed = line.trim();
+            if trimmed.starts_with("#[") {
+                field_attributes.push_str(trimmed);
+                continue;
+            }
+            let Some(field) = RS_FIELD.captures(line) else { continue };
+            let serde = std::mem::take(&mut field_attributes);
+            if serde.contains("skip)") || serde.contains("skip,") || serde.contains("skip_serializing)") {
+                continue;
+            }
+            if serde.contains("flatten") {
+                def.open = true;
+                continue;
+            }
+            let name = match (SERDE_RENAME.captures(&serde), &rename_all) {
+                (Some(renamed), _) => renamed[1].to_string(),
+                (None, Some(style)) => rename(style, &field[1]),
+                (None, None) => field[1].to_string(),
+            };
+            let optional =
+                field[2].starts_with("Option<") || serde.contains("default") || serde.contains("skip_serializing_if");
+            def.fields.push(Field { name, optional });
+        }
+        types.push(def);
+    }
+    types
+}
+
+static JAVA_CLASS: Lazy<Regex> = Lazy::new(|| Regex::new(r"\bclass\s+(\w+)[^{;]*\{").unwrap());
+static JAVA_EXTENDS: Lazy<Regex> = Lazy::new(|| Regex::new(r"\bextends\s+(\w+)").unwrap());
+static JAVA_RECORD: Lazy<Regex> = Lazy::new(|| Regex::new(r"\brecord\s+(\w+)\s*\(").unwrap());
+static JAVA_FIELD: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"^\s*(?:@\w+(?:\([^)]*\))?\s*)*(?:(?:private|protected|public|final)\s+)+([\w<>,?\[\] ]+?)\s+(\w+)\s*(?:=[^;]*)?;")
+        .unwrap()
+});
+static JSON_PROPERTY: Lazy<Regex> = Lazy::new(|| Regex::new(r#"@JsonProperty\(\s*(?:value\s*=\s*)?"([^"]+)""#).unwrap());
+
+fn java_types(content: &str) -> Vec<TypeDef> {
+    let mut types = Vec::new();
+    for cap in JAVA_CLASS.captures_iter(content) {
+        let header = cap.get(0).unwrap();
+        let parents = JAVA_EXTENDS.captures(header.as_str()).map(|c| vec![c[1].to_string()]).unwrap_or_default();
+        let mut def = TypeDef {
+            name: cap[1].to_string(),
+            fields: Vec::new(),
+            open: false,
+        };
+        let mut annotations = String::new();
+        for line in top_level_members(enclosed(content, header.end(), '{', '}'), &['\n']) {
+            if line.trim_start().starts_with('@') && !line.contains(';') {
+                annotations.push_str(line);
+                continue;
+            }
+            let annotated = std::mem::take(&mut annotations) + line;
+            let Some(field) = JAVA_FIELD.captures(line) else { continue };
+            if line.contains(" static ") || annotated.contains("@JsonIgnore") {
+                continue;
+            }
+            let name = JSON_PROPERTY.captures(&annotated).map_or(field[2].to_string(), |p| p[1].to_string());
+            def.fields.push(Field {
+                name,
+                optional: field[1].starts_with("Optional<"),
+            });
+        }
+        types.push((def, parents));
+    }
+    for cap in JAVA_RECORD.captures_iter(content) {
+        let components = enclosed(content, cap.get(0).unwrap().end(), '(', ')');
+        let fields = top_level_members(components, &[','])
+            .into_iter()
+            .filter_map(|component| {
+                let name = component.split_whitespace().last()?;
+                let name = JSON_PROPERTY.captures(component).map_or(name.to_string(), |p| p[1].to_string());
+                Some(Field {
+                    name,
+                    optional: component.contains("Optional<"),
+                })
+            })
+            .collect();
+        let def = TypeDef {
+            name: cap[1].to_string(),
+            fields,
+            open: false,
+        };
+        types.push((def, Vec::new()));
+    }
+    inherit(types)
+}
+
+/// Add fields inherited from parents defined in the same file; types with other
+/// parents are open
+fn inherit(types: Vec<(TypeDef, Vec<String>)>) -> Vec<TypeDef> {
+    let mut resolved: Vec<TypeDef> = Vec::new();
+    for (mut def, parents) in types {
+        for parent in parents.iter().rev() {
+            // Parents are declared first in Python; look at both orders for TS and Java
+            match resolved.iter().find(|t| t.name == *parent) {
+                Some(p) => {
+                    def.open |= p.open;
+                    let own = std::mem::take(&mut def.fields);
+                    def.fields = p.fields.iter().filter(|f| !own.iter().any(|o| o.name == f.name)).cloned().collect();
+                    def.fields.extend(own);
+                }
+                None => def.open = true,
+            }
+        }
+        resolved.push(def);
+    }
+    resolved
+}
+
+/// Text between the `open` delimiter just before `start` and its matching `close`
+fn enclosed(content: &str, start: usize, open: char, close: char) -> &str {
+    let mut depth = 1;
+    for (i, c) in content[start..].char_indices() {
+        if c == open {
+            depth += 1;
+        } else if c == close {
+            depth -= 1;
+            if depth == 0 {
+                return &content[start..start + i];
+            }
+        }
+    }
+    &content[start..]
+}
+
+/// Members of a body split at `separators` outside nested braces, brackets, and parentheses
+fn top_level_members<'a>(body: &'a str, separators: &[char]) -> Vec<&'a str> {
+    let mut members = Vec::new();
+    let mut depth = 0i32;
+    let mut start = 0;
+    for (i, c) in body.char_indices() {
+        match c {
+            '{' | '(' | '[' => depth += 1,
+            '}' | ')' | ']' => depth = (depth - 1).max(0),
+            c if depth == 0 && separators.contains(&c) => {
+                members.push(&body[start..i]);
+                start = i + c.len_utf8();
+            }
+            _ => {}
+        }
+    }
+    members.push(&body[start..]);
+    members.retain(|m| !m.trim().is_empty());
+    members
+}
+
+/// A snake_case field name in a serde `rename_all` style
+fn rename(style: &str, name: &str) -> String {
+    let words: Vec<&str> = name.split('_').filter(|w| !w.is_empty()).collect();
+    let capitalize = |w: &str| {
+        let mut chars = w.chars();
+        chars.next().map_or(String::new(), |c| c.to_uppercase().chain(chars).collect())
+    };
+    match style {
+        "camelCase" => words
+            .iter()
+            .enumerate()
+            .map(|(i, w)| if i == 0 { w.to_string() } else { capitalize(w) })
+            .collect(),
+        "PascalCase" => words.iter().map(|w| capitalize(w)).collect(),
+        "kebab-case" => words.join("-"),
+        "SCREAMING_SNAKE_CASE" => name.to_uppercase(),
+        "lowercase" => name.to_lowercase(),
+        "UPPERCASE" => name.to_uppercase(),
+        _ => name.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fields(def: &TypeDef) -> Vec<(&str, bool)> {
+        def.fields.iter().map(|f| (f.name.as_str(), f.optional)).collect()
+    }
+
+    #[test]
+    fn test_type_definitions() {
+        let ts = r#"
+export interface User {
+  id: number;
+  userName: string;
+  bio?: string;
+  address: { city: string };
+  avatar: string | undefined;
+}
+type Page = { items: User[]; next: string | null };
+export class CreateUserDto {
+  @IsString() name: string;
+  constructor(private readonly svc: Service) {}
+}
+"#;
+        let types = type_definitions("web/types.ts", ts);
+        assert_eq!(types.len(), 3);
+        assert_eq!(
+            fields(&types[0]),
+            vec![("id", false), ("userName", false), ("bio", true), ("address", false), ("avatar", true)]
+        );
+        assert_eq!(fields(&types[1]), vec![("items", false), ("next", false)]);
+        assert_eq!(fields(&types[2]), vec![("name", false)]);
+
+        let python = r#"
+class UserOut(BaseModel):
+    id: int
+    user_name: str = Field(alias="userName")
+    bio: Optional[str] = None
+
+    class Config:
+        orm_mode = True
+
+    def display(self) -> str:
+        return self.user_name
+
+class Service:
+    client: Client
+"#;
+        let types = type_definitions("api/models.py", python);
+        assert_eq!(types.len(), 1);
+        assert_eq!(fields(&types[0]), vec![("id", false), ("userName", false), ("bio", true)]);
+
+        let rust = r#"
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct User {
+    pub id: u64,
+    pub user_name: String,
+    #[serde(rename = "mail")]
+    pub email: Option<String>,
+    #[serde(skip)]
+    pub cache: Cache,
+}
+"#;
+        let types = type_definitions("src/models.rs", rust);
+        assert_eq!(fields(&types[0]), vec![("id", false), ("userName", false), ("mail", true)]);
+
+        let java = r#"
+public class UserDto {
+    private static final long serialVersionUID = 1L;
+    private Long id;
+    @JsonProperty("user_name")
+    private String userName;
+    public String getUserName() { return userName; }
+}
+public record NewUser(String name, @JsonProperty("mail") String email) {}
+"#;
+        let types = type_definitions("User.java", java);
+        assert_eq!(fields(&types[0]), vec![("id", false), ("user_name", false)]);
+        assert_eq!(fields(&types[1]), vec![("name", false), ("mail", false)]);
+    }
+
+    #[test]
+    fn test_client_payload_types() {
+        let ts = r#"
+async function create(payload: NewUser) {
+  const { data } = await axios.post<User>('/api/users', payload);
+  const res = await fetch('/api/users');
+  const users = (await res.json()) as User[];
+}
+"#;
+        let create = client_payload_types(ts, 3);
+        assert_eq!(create.request, Some(type_ref("NewUser", false)));
+        assert_eq!(create.response, Some(type_ref("User", false)));
+
+        let list = client_payload_types(ts, 4);
+        assert_eq!(list.request, None);
+        assert_eq!(list.response, Some(type_ref("User", true)));
+    }
+
+    #[test]
+    fn test_handler_payload_types() {
+        let fastapi = concat!(
+            "@app.post(\"/users\", response_model=UserOut)\n",
+            "async def create(user: UserIn, db=Depends(db)):\n",
+        );
+        let types = handler_payload_types("api/main.py", fastapi, 1, None);
+        assert_eq!(types.request, Some(type_ref("UserIn", false)));
+        assert_eq!(types.response, Some(type_ref("UserOut", false)));
+
+        let axum = r#"
+let app = Router::new().route("/users", post(create_user));
+
+async fn create_user(State(db): State<Db>, Json(body): Json<NewUser>) -> Result<Json<Vec<User>>, Error> {
+"#;
+        let types = handler_payload_types("src/main.rs", axum, 2, Some("create_user"));
+        assert_eq!(types.request, Some(type_ref("NewUser", false)));
+        assert_eq!(types.response, Some(type_ref("User", true)));
+
+        let nest = "  @Post()\n  async create(@Body() dto: CreateUserDto): Promise<User> {\n";
+        let types = handler_payload_types("users.controller.ts", nest, 1, None);
+        assert_eq!(types.request, Some(type_ref("CreateUserDto", false)));
+        assert_eq!(types.response, Some(type_ref("User", false)));
+
+        let spring = concat!(
+            "@PostMapping(\"/users\")\n",
+            "public ResponseEntity<UserDto> create(@RequestBody @Valid NewUser body) {\n",
+        );
+        let types = handler_payload_types("UserController.java", spring, 1, None);
+        assert_eq!(types.request, Some(type_ref("NewUser", false)));
+        assert_eq!(types.response, Some(type_ref("UserDto", false)));
+    }
+
+    #[test]
+    fn test_payload_mismatches() {
+        let mut index = TypeIndex::default();
+        index.insert(
+            "web/types.ts",
+            concat!(
+                "interface NewUser { name: string; nickname: string }\n",
+                "interface User { id: number; userName: string; email: string; bio: string }\n",
+            ),
+        );
+        index.insert(
+            "api/models.py",
+            concat!(
+                "class UserIn(BaseModel):\n    name: str\n    password: str\n\n",
+                "class UserOut(BaseModel):\n    id: int\n    user_name: str\n    bio: Optional[str] = None\n",
+            ),
+        );
+
+        let client = PayloadTypes {
+            request: Some(type_ref("NewUser", false)),
+            response: Some(type_ref("User", false)),
+        };
+        let server = PayloadTypes {
+            request: Some(type_ref("UserIn", false)),
+            response: Some(type_ref("UserOut", false)),
+        };
+        assert_eq!(
+            payload_mismatches(&index, "web/api.ts", &client, "api/main.py", &server),
+            vec![
+                "request field `password` required by UserIn is missing from client type NewUser",
+                "request field `nickname` sent by client type NewUser is not accepted by UserIn",
+                "response field `userName` (User): handler returns `user_name` (UserOut)",
+                "response field `email` expected by client type User is missing from UserOut",
+                "response field `bio` is optional in UserOut but required by client type User",
+            ]
+        );
+
+        let list = PayloadTypes {
+            request: None,
+            response: Some(type_ref("User", true)),
+        };
+        assert_eq!(
+            payload_mismatches(&index, "web/api.ts", &list, "api/main.py", &server),
+            vec!["response: client expects User[] but handler returns UserOut"]
+        );
+
+        // Unresolved types can't be compared
+        let unknown = PayloadTypes {
+            request: None,
+            response: Some(type_ref("Missing", false)),
+        };
+        assert!(payload_mismatches(&index, "web/api.ts", &unknown, "api/main.py", &server).is_empty());
+    }
+}
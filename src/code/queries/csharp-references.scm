@@ -0,0 +1,115 @@
+;; C# references query
+
+;; Using directives
+(using_directive
+  (identifier) @path
+) @import
+
+(using_directive
+  (qualified_name) @path
+) @import
+
+;; Method calls
+(invocation_expression
+  function: (identifier) @name
+) @call
+
+(invocation_expression
+  function: (generic_name
+    (identifier) @name
+  )
+) @call
+
+(invocation_expression
+  function: (member_access_expression
+    name: (identifier) @name
+  )
+) @call
+
+(invocation_expression
+  function: (member_access_expression
+    name: (generic_name
+      (identifier) @name
+    )
+  )
+) @call
+
+;; Object creation
+(object_creation_expression
+  type: (identifier) @name
+) @call
+
+(object_creation_expression
+  type: (generic_name
+    (identifier) @name
+  )
+) @call
+
+;; Base types. A class or record lists its base class and interfaces together, so names
+;; following the IFoo convention are treated as interfaces.
+(class_declaration
+  (base_list
+    (identifier) @superclass
+  )
+  (#not-match? @superclass "^I[A-Z]")
+) @extends
+
+(class_declaration
+  (base_list
+    (generic_name
+      (identifier) @superclass
+    )
+  )
+  (#not-match? @superclass "^I[A-Z]")
+) @extends
+
+(record_declaration
+  (base_list
+    (identifier) @superclass
+  )
+  (#not-match? @superclass "^I[A-Z]")
+) @extends
+
+(class_declaration
+  (base_list
+    (identifier) @interface
+  )
+  (#match? @interface "^I[A-Z]")
+) @implements
+
+(class_declaration
+  (base_list
+    (generic_name
+      (identifier) @interface
+    )
+  )
+  (#match? @interface "^I[A-Z]")
+) @implements
+
+(record_declaration
+  (base_list
+    (identifier) @interface
+  )
+  (#match? @interface "^I[A-Z]")
+) @implements
+
+(struct_declaration
+  (base_list
+    (identifier) @interface
+  )
+) @implements
+
+;; Interface inheritance
+(interface_declaration
+  (base_list
+    (identifier) @interface
+  )
+) @extends
+
+(interface_declaration
+  (base_list
+    (generic_name
+      (identifier) @interface
+    )
+  )
+) @extends
@@ -0,0 +1,41 @@
+;; C# symbols query
+;; Names are qualified with their enclosing namespaces and types (see LanguageConfig::scopes)
+
+;; Namespaces (block and file-scoped)
+(namespace_declaration
+  name: (_) @name
+) @module
+
+(file_scoped_namespace_declaration
+  name: (_) @name
+) @module
+
+;; Class declarations
+(class_declaration
+  name: (identifier) @name
+) @class
+
+;; Records are classes with generated members
+(record_declaration
+  name: (identifier) @name
+) @class
+
+;; Struct declarations
+(struct_declaration
+  name: (identifier) @name
+) @struct
+
+;; Interface declarations
+(interface_declaration
+  name: (identifier) @name
+) @interface
+
+;; Enum declarations
+(enum_declaration
+  name: (identifier) @name
+) @enum
+
+;; Method declarations (constructors share the type's name, so they are not separate symbols)
+(method_declaration
+  name: (identifier) @name
+) @method
@@ -0,0 +1,79 @@
+;; Java references query
+
+;; Import declarations
+(import_declaration
+  (scoped_identifier) @path
+) @import
+
+(import_declaration
+  (identifier) @path
+) @import
+
+;; Method calls
+(method_invocation
+  name: (identifier) @name
+) @call
+
+;; Constructor calls
+(object_creation_expression
+  type: (type_identifier) @name
+) @call
+
+(object_creation_expression
+  type: (generic_type
+    (type_identifier) @name
+  )
+) @call
+
+;; Class extensions
+(class_declaration
+  superclass: (superclass
+    (type_identifier) @superclass
+  )
+) @extends
+
+(class_declaration
+  superclass: (superclass
+    (generic_type
+      (type_identifier) @superclass
+    )
+  )
+) @extends
+
+;; Interface extensions
+(interface_declaration
+  (extends_interfaces
+    (type_list
+      (type_identifier) @interface
+    )
+  )
+) @extends
+
+(interface_declaration
+  (extends_interfaces
+    (type_list
+      (generic_type
+        (type_identifier) @interface
+      )
+    )
+  )
+) @extends
+
+;; Interface implementations (classes, enums, and records)
+(_
+  interfaces: (super_interfaces
+    (type_list
+      (type_identifier) @interface
+    )
+  )
+) @implements
+
+(_
+  interfaces: (super_interfaces
+    (type_list
+      (generic_type
+        (type_identifier) @interface
+      )
+    )
+  )
+) @implements
@@ -0,0 +1,31 @@
+;; Java symbols query
+
+;; Class declarations
+(class_declaration
+  name: (identifier) @name
+) @class
+
+;; Records are classes with generated members
+(record_declaration
+  name: (identifier) @name
+) @class
+
+;; Interface declarations
+(interface_declaration
+  name: (identifier) @name
+) @interface
+
+;; Annotation types (@interface)
+(annotation_type_declaration
+  name: (identifier) @name
+) @interface
+
+;; Enum declarations
+(enum_declaration
+  name: (identifier) @name
+) @enum
+
+;; Method declarations (constructors share the class's name, so they are not separate symbols)
+(method_declaration
+  name: (identifier) @name
+) @method
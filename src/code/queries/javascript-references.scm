@@ -26,6 +26,6 @@
 ;; Class extensions
 (class_declaration
   (class_heritage
-    value: (identifier) @superclass
+    (identifier) @superclass
   )
 ) @extends
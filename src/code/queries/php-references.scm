@@ -0,0 +1,103 @@
+;; PHP references query
+
+;; Namespace imports (use App\Models\User;)
+(namespace_use_clause
+  (qualified_name) @path
+) @import
+
+(namespace_use_clause
+  (name) @path
+) @import
+
+;; require / include
+(require_expression
+  (string
+    (string_content) @path
+  )
+) @import
+
+(require_once_expression
+  (string
+    (string_content) @path
+  )
+) @import
+
+(include_expression
+  (string
+    (string_content) @path
+  )
+) @import
+
+(include_once_expression
+  (string
+    (string_content) @path
+  )
+) @import
+
+;; Function calls
+(function_call_expression
+  function: (name) @name
+) @call
+
+(function_call_expression
+  function: (qualified_name
+    (name) @name
+  )
+) @call
+
+;; Method calls ($obj->foo(), $obj?->foo(), Foo::bar())
+(member_call_expression
+  name: (name) @name
+) @call
+
+(nullsafe_member_call_expression
+  name: (name) @name
+) @call
+
+(scoped_call_expression
+  name: (name) @name
+) @call
+
+;; Object creation
+(object_creation_expression
+  (name) @name
+) @call
+
+(object_creation_expression
+  (qualified_name
+    (name) @name
+  )
+) @call
+
+;; Class and interface inheritance (extends)
+(base_clause
+  (name) @superclass
+) @extends
+
+(base_clause
+  (qualified_name
+    (name) @superclass
+  )
+) @extends
+
+;; Interface implementations (classes and enums)
+(class_interface_clause
+  (name) @interface
+) @implements
+
+(class_interface_clause
+  (qualified_name
+    (name) @interface
+  )
+) @implements
+
+;; Trait use inside classes
+(use_declaration
+  (name) @trait
+) @implements
+
+(use_declaration
+  (qualified_name
+    (name) @trait
+  )
+) @implements
@@ -0,0 +1,43 @@
+;; PHP symbols query
+
+;; Namespace declarations
+(namespace_definition
+  name: (namespace_name) @name
+) @module
+
+;; Class declarations
+(class_declaration
+  name: (name) @name
+) @class
+
+;; Interface declarations
+(interface_declaration
+  name: (name) @name
+) @interface
+
+;; Trait declarations
+(trait_declaration
+  name: (name) @name
+) @trait
+
+;; Enum declarations
+(enum_declaration
+  name: (name) @name
+) @enum
+
+;; Method declarations
+(method_declaration
+  name: (name) @name
+) @method
+
+;; Function definitions
+(function_definition
+  name: (name) @name
+) @function
+
+;; Constants (top-level and class constants)
+(const_declaration
+  (const_element
+    (name) @name
+  )
+) @const
@@ -0,0 +1,52 @@
+;; Ruby references query
+
+;; require / require_relative
+(call
+  method: (identifier) @_require
+  arguments: (argument_list
+    (string
+      (string_content) @path
+    )
+  )
+  (#match? @_require "^(require|require_relative)$")
+) @import
+
+;; Method calls
+(call
+  method: (identifier) @name
+  (#not-match? @name "^(require|require_relative|include|extend|prepend)$")
+) @call
+
+;; Mixins (include, extend, prepend)
+(call
+  method: (identifier) @_mixin
+  arguments: (argument_list
+    (constant) @module
+  )
+  (#match? @_mixin "^(include|extend|prepend)$")
+) @implements
+
+(call
+  method: (identifier) @_mixin
+  arguments: (argument_list
+    (scope_resolution
+      name: (constant) @module
+    )
+  )
+  (#match? @_mixin "^(include|extend|prepend)$")
+) @implements
+
+;; Class inheritance (class Foo < Bar)
+(class
+  superclass: (superclass
+    (constant) @superclass
+  )
+) @extends
+
+(class
+  superclass: (superclass
+    (scope_resolution
+      name: (constant) @superclass
+    )
+  )
+) @extends
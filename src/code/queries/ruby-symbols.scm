@@ -0,0 +1,33 @@
+;; Ruby symbols query
+
+;; Class definitions (class Foo, class Admin::Foo)
+(class
+  name: (constant) @name
+) @class
+
+(class
+  name: (scope_resolution
+    name: (constant) @name
+  )
+) @class
+
+;; Module definitions
+(module
+  name: (constant) @name
+) @module
+
+(module
+  name: (scope_resolution
+    name: (constant) @name
+  )
+) @module
+
+;; Method definitions
+(method
+  name: (_) @name
+) @method
+
+;; Singleton methods (def self.foo)
+(singleton_method
+  name: (_) @name
+) @method
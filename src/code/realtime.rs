@@ -0,0 +1,311 @@
+//! WebSocket, socket.io, and server-sent event detection.
+//!
+//! Finds the channel endpoints server code registers and client code connects to, so
+//! cross-language inference can match them by path like HTTP routes, and the socket.io
+//! events each side emits and listens for, so emitters can be connected to listeners.
+//! Only literal paths and event names are recognized.
+
+use once_cell::sync::Lazy;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+
+/// Source files scanned for channel endpoints and socket events
+pub const REALTIME_GLOB: &str = "**/*.{js,ts,jsx,tsx,py,java,kt,go,rs}";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Transport {
+    WebSocket,
+    SocketIo,
+    Sse,
+}
+
+impl Transport {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::WebSocket => "ws",
+            Self::SocketIo => "socket.io",
+            Self::Sse => "sse",
+        }
+    }
+}
+
+/// A channel endpoint registered by a server or connected to by a client
+#[derive(Debug, Clone, PartialEq)]
+pub struct ChannelEndpoint {
+    pub transport: Transport,
+    /// Path or URL as written, without any query string
+    pub path: String,
+    /// Line of the registration or connection (1-based)
+    pub line: usize,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum EventRole {
+    Emit,
+    Listen,
+}
+
+/// One socket.io `emit` or `on` site
+#[derive(Debug, Clone, PartialEq)]
+pub struct SocketEvent {
+    pub role: EventRole,
+    pub event: String,
+    /// Line of the call (1-based)
+    pub line: usize,
+}
+
+/// A quoted path or URL, captured as `path`
+const QUOTED_PATH: &str = r#"['"`](?P<path>[^'"`\s?]+)[^'"`]*['"`]"#;
+
+/// (transport, pattern capturing the path as `path`) for server registrations
+static SERVER_PATTERNS: Lazy<Vec<(Transport, Regex)>> = Lazy::new(|| {
+    use Transport::*;
+    let q = QUOTED_PATH;
+    let patterns: Vec<(Transport, String)> = vec![
+        // ws `new WebSocketServer({ server, path: '/ws' })`, express-ws `app.ws('/ws', ...)`
+        (WebSocket, format!(r"\bnew\s+(?:WebSocketServer|WebSocket\.Server)\(\s*\{{[^}}]*?\bpath\s*:\s*{}", q)),
+        (WebSocket, format!(r"\b(?:app|router)\.ws\(\s*{}", q)),
+        // FastAPI/Starlette, Flask-Sock, Django Channels
+        (WebSocket, format!(r"@\w+\.websocket\(\s*{}", q)),
+        (WebSocket, format!(r"\bWebSocketRoute\(\s*{}", q)),
+        (WebSocket, format!(r"@sock\.route\(\s*{}", q)),
+        (WebSocket, format!(r"\b(?:re_)?path\(\s*r?{}\s*,\s*[\w.]+\.as_asgi\(", q)),
+        // Spring `@ServerEndpoint`, `registry.addHandler(handler, "/ws")`, STOMP `addEndpoint("/ws")`
+        (WebSocket, format!(r"@ServerEndpoint\(\s*(?:value\s*=\s*)?{}", q)),
+        (WebSocket, format!(r"\.addHandler\(\s*[^,()]+(?:\([^)]*\))?\s*,\s*{}", q)),
+        (WebSocket, format!(r"\.addEndpoint\(\s*{}", q)),
+        // Go and Axum routes whose handler is named for the socket
+        (WebSocket, format!(r"\bHandleFunc\(\s*{}\s*,\s*\w*(?i:ws|socket)\w*", q)),
+        (WebSocket, format!(r"\.route\(\s*{}\s*,\s*get\(\s*\w*(?i:ws|socket)\w*\s*\)", q)),
+        // socket.io namespaces
+        (SocketIo, format!(r"\bio\.of\(\s*{}", q)),
+    ];
+    patterns
+        .into_iter()
+        .map(|(transport, pattern)| (transport, Regex::new(&pattern).unwrap()))
+        .collect()
+});
+
+/// (transport, pattern capturing the path as `path`) for client connections
+static CLIENT_PATTERNS: Lazy<Vec<(Transport, Regex)>> = Lazy::new(|| {
+    use Transport::*;
+    let q = QUOTED_PATH;
+    let patterns: Vec<(Transport, String)> = vec![
+        (WebSocket, format!(r"\bnew\s+(?:WebSocket|ReconnectingWebSocket)\(\s*{}", q)),
+        // Python websockets/websocket-client, Go gorilla, Rust tokio-tungstenite
+        (WebSocket, format!(r"\b(?:websockets\.connect|create_connection|connect_async)\(\s*{}", q)),
+        (WebSocket, format!(r"\bDial(?:Context)?\(\s*(?:ctx\s*,\s*)?{}", q)),
+        // socket.io-client and python-socketio
+        (SocketIo, format!(r"\bio(?:\.connect)?\(\s*{}", q)),
+        (SocketIo, format!(r"\bsio\.connect\(\s*{}", q)),
+        (Sse, format!(r"\bnew\s+EventSource(?:Polyfill)?\(\s*{}", q)),
+    ];
+    patterns
+        .into_iter()
+        .map(|(transport, pattern)| (transport, Regex::new(&pattern).unwrap()))
+        .collect()
+});
+
+/// (role, pattern capturing the name as `event`) for socket.io events
+static EVENT_PATTERNS: Lazy<Vec<(EventRole, Regex)>> = Lazy::new(|| {
+    use EventRole::*;
+    // Sockets, servers, and namespaces, optionally through `this.`, `.to(room)`, or `.broadcast`
+    let socket = r"\b(?:this\.)?(?:socket|io|sio|socketio|nsp|namespace|client|server)(?:\.(?:to|in|of)\([^)]*\)|\.broadcast)*";
+    let q = r#"['"`](?P<event>[\w:\-./ ]+)['"`]"#;
+    let patterns: Vec<(EventRole, String)> = vec![
+        (Emit, format!(r"{}\.emit\(\s*{}", socket, q)),
+        (Listen, format!(r"{}\.(?:on|once)\(\s*{}", socket, q)),
+        // Flask-SocketIO `@socketio.on('x')` and `emit('x', ...)` inside a handler
+        (Listen, format!(r"@(?:socketio|sio)\.on\(\s*{}", q)),
+        (Emit, format!(r"(?m)^\s*emit\(\s*{}", q)),
+        // python-socketio `@sio.event` names the event after the function
+        (Listen, r"@(?:sio|socketio)\.event\s*\n\s*(?:async\s+)?def\s+(?P<event>\w+)".to_string()),
+        // netty-socketio
+        (Emit, format!(r"\.sendEvent\(\s*{}", q)),
+        (Listen, format!(r"\.addEventListener\(\s*{}\s*,\s*\w+\.class", q)),
+    ];
+    patterns
+        .into_iter()
+        .map(|(role, pattern)| (role, Regex::new(&pattern).unwrap()))
+        .collect()
+});
+
+/// Connection lifecycle events every socket has, which connect nothing
+const RESERVED_EVENTS: &[&str] = &[
+    "connect",
+    "connection",
+    "connect_error",
+    "disconnect",
+    "disconnecting",
+    "error",
+    "reconnect",
+    "reconnect_attempt",
+    "reconnect_error",
+    "reconnect_failed",
+    "ping",
+    "pong",
+    "open",
+    "close",
+    "message",
+    "newListener",
+    "removeListener",
+];
+
+/// Markers of a server streaming `text/event-stream` responses
+static SSE_RESPONSE: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"text/event-stream|\bEventSourceResponse\b|\bSseEmitter\b|\bSse<|\bsse::Event\b|\bServerSentEvent\b")
+        .unwrap()
+});
+
+fn line_of(content: &str, offset: usize) -> usize {
+    content[..offset].matches('\n').count() + 1
+}
+
+fn endpoints(patterns: &[(Transport, Regex)], content: &str) -> Vec<ChannelEndpoint> {
+    let mut endpoints: Vec<(usize, ChannelEndpoint)> = Vec::new();
+    for (transport, pattern) in patterns {
+        for cap in pattern.captures_iter(content) {
+            let (Some(whole), Some(path)) = (cap.get(0), cap.name("path")) else { continue };
+            endpoints.push((
+                whole.start(),
+                ChannelEndpoint {
+                    transport: *transport,
+                    path: path.as_str().to_string(),
+                    line: line_of(content, whole.start()),
+                },
+            ));
+        }
+    }
+    endpoints.sort_by_key(|(start, _)| *start);
+    endpoints.dedup_by(|a, b| a.1 == b.1);
+    endpoints.into_iter().map(|(_, endpoint)| endpoint).collect()
+}
+
+/// WebSocket endpoints and socket.io namespaces a server file registers, in source order
+pub fn find_channel_servers(content: &str) -> Vec<ChannelEndpoint> {
+    endpoints(&SERVER_PATTERNS, content)
+}
+
+/// WebSocket, socket.io, and EventSource connections a client file opens, in source order
+pub fn find_channel_clients(content: &str) -> Vec<ChannelEndpoint> {
+    endpoints(&CLIENT_PATTERNS, content)
+}
+
+/// Whether a server file streams server-sent events, making its GET routes SSE endpoints
+pub fn streams_events(content: &str) -> bool {
+    SSE_RESPONSE.is_match(content)
+}
+
+/// All socket.io events emitted or listened for in a file, in source order
+pub fn find_socket_events(content: &str) -> Vec<SocketEvent> {
+    let mut events: Vec<(usize, SocketEvent)> = Vec::new();
+    for (role, pattern) in EVENT_PATTERNS.iter() {
+        for cap in pattern.captures_iter(content) {
+            let (Some(whole), Some(event)) = (cap.get(0), cap.name("event")) else { continue };
+            if RESERVED_EVENTS.contains(&event.as_str()) {
+                continue;
+            }
+            events.push((
+                whole.start(),
+                SocketEvent {
+                    role: *role,
+                    event: event.as_str().to_string(),
+                    line: line_of(content, whole.start()),
+                },
+            ));
+        }
+    }
+    events.sort_by_key(|(start, _)| *start);
+    events.dedup_by(|a, b| a.1 == b.1);
+    events.into_iter().map(|(_, event)| event).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn paths(endpoints: Vec<ChannelEndpoint>) -> Vec<(Transport, String)> {
+        endpoints.into_iter().map(|e| (e.transport, e.path)).collect()
+    }
+
+    #[test]
+    fn test_channel_endpoints() {
+        let node = r#"
+const wss = new WebSocketServer({ server, path: '/ws/prices' });
+const chat = io.of("/chat");
+"#;
+        assert_eq!(
+            paths(find_channel_servers(node)),
+            vec![
+                (Transport::WebSocket, "/ws/prices".to_string()),
+                (Transport::SocketIo, "/chat".to_string()),
+            ]
+        );
+
+        let python = "@app.websocket(\"/ws/{room}\")\nasync def room(ws: WebSocket): ...\n";
+        assert_eq!(paths(find_channel_servers(python)), vec![(Transport::WebSocket, "/ws/{room}".to_string())]);
+        let django = "websocket_urlpatterns = [re_path(r'ws/chat/', ChatConsumer.as_asgi())]\n";
+        assert_eq!(paths(find_channel_servers(django)), vec![(Transport::WebSocket, "ws/chat/".to_string())]);
+        let spring = "registry.addHandler(new PriceHandler(), \"/ws/prices\").setAllowedOrigins(\"*\");\n";
+        assert_eq!(paths(find_channel_servers(spring)), vec![(Transport::WebSocket, "/ws/prices".to_string())]);
+        // Plain HTTP routes aren't channels
+        assert!(find_channel_servers("r.HandleFunc(\"/users\", listUsers)\n").is_empty());
+
+        let client = r#"
+const ws = new WebSocket(`${proto}://${location.host}/ws/prices?token=${token}`);
+const socket = io('/chat', { transports: ['websocket'] });
+const events = new EventSource('/api/notifications/stream');
+"#;
+        assert_eq!(
+            paths(find_channel_clients(client)),
+            vec![
+                (Transport::WebSocket, "${proto}://${location.host}/ws/prices".to_string()),
+                (Transport::SocketIo, "/chat".to_string()),
+                (Transport::Sse, "/api/notifications/stream".to_string()),
+            ]
+        );
+
+        assert!(streams_events("return EventSourceResponse(generator())"));
+        assert!(streams_events("res.setHeader('Content-Type', 'text/event-stream');"));
+        assert!(!streams_events("return JSONResponse(data)"));
+    }
+
+    #[test]
+    fn test_socket_events() {
+        let js = r#"
+socket.on('connect', () => socket.emit('join', room));
+socket.on("chat:message", render);
+io.to(room).emit('chat:message', msg);
+process.on('exit', cleanup);
+"#;
+        let events: Vec<_> = find_socket_events(js).into_iter().map(|e| (e.role, e.event, e.line)).collect();
+        assert_eq!(
+            events,
+            vec![
+                (EventRole::Emit, "join".to_string(), 2),
+                (EventRole::Listen, "chat:message".to_string(), 3),
+                (EventRole::Emit, "chat:message".to_string(), 4),
+            ]
+        );
+
+        let python = r#"
+@socketio.on('join')
+def on_join(data):
+    emit('chat:message', {'text': 'joined'}, to=data['room'])
+
+@sio.event
+async def typing(sid, data):
+    await sio.emit('typing', data, skip_sid=sid)
+"#;
+        let events: Vec<_> = find_socket_events(python).into_iter().map(|e| (e.role, e.event)).collect();
+        assert_eq!(
+            events,
+            vec![
+                (EventRole::Listen, "join".to_string()),
+                (EventRole::Emit, "chat:message".to_string()),
+                (EventRole::Listen, "typing".to_string()),
+                (EventRole::Emit, "typing".to_string()),
+            ]
+        );
+    }
+}
@@ -0,0 +1,375 @@
+//! Database table usage in code.
+//!
+//! Tables are found in three places: SQL in string literals (`SELECT ... FROM
+//! users`, `INSERT INTO orders`), ORM model classes mapped to a table
+//! (SQLAlchemy, Django, Rails, JPA, TypeORM, Diesel, SeaORM, GORM), and
+//! `CREATE TABLE` statements in migrations. Table names are normalized to their
+//! unqualified, lowercase form so the same table matches across languages.
+
+use once_cell::sync::Lazy;
+use regex::Regex;
+
+/// Source files scanned for SQL strings and ORM models
+pub const SQL_SOURCE_GLOB: &str = "**/*.{py,js,ts,jsx,tsx,mjs,rs,go,java,kt,rb,cs,php}";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum TableAccess {
+    Read,
+    Write,
+    Define,
+}
+
+impl TableAccess {
+    /// Edge kind from the accessing symbol to the table node
+    pub fn edge_kind(&self) -> &'static str {
+        match self {
+            Self::Read => "reads_table",
+            Self::Write => "writes_table",
+            Self::Define => "defines_table",
+        }
+    }
+}
+
+/// A table accessed by SQL at a line (1-based)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SqlReference {
+    pub table: String,
+    pub access: TableAccess,
+    pub line: usize,
+}
+
+/// An ORM model class and the table it maps to
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ModelTable {
+    pub model: String,
+    pub table: String,
+    /// Line of the model's class declaration (1-based)
+    pub line: usize,
+}
+
+/// A (possibly schema-qualified, quoted) table name
+const TABLE: &str = r#"((?:[A-Za-z_][\w$]*|"[^"]+"|`[^`]+`|\[[^\]]+\])(?:\.(?:[A-Za-z_][\w$]*|"[^"]+"|`[^`]+`|\[[^\]]+\]))?)"#;
+
+/// String literals: Python triple quotes, Rust raw strings, backtick templates, and
+/// single-line quoted strings
+static STRING_LITERAL: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r##"(?s)"""(.*?)"""|'''(.*?)'''|\br#+"(.*?)"#+|\br"([^"]*)"|`([^`]*)`|"((?:[^"\\\n]|\\.)*)"|'((?:[^'\\\n]|\\.)*)'"##)
+        .unwrap()
+});
+
+/// Literals that look like SQL statements
+static SQL_STATEMENT: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"(?i)^\s*(?:select|insert|update|delete|with|merge|replace|create|truncate)\s").unwrap());
+
+/// (access, text before the table, text required after it)
+static SQL_PATTERNS: Lazy<Vec<(TableAccess, Regex)>> = Lazy::new(|| {
+    [
+        (TableAccess::Write, r"\binsert\s+(?:or\s+\w+\s+)?into\s+", ""),
+        // Not `ON CONFLICT DO UPDATE SET` or `ON DUPLICATE KEY UPDATE col = ...`
+        (TableAccess::Write, r"\bupdate\s+(?:only\s+)?", r"(?:\s+(?:as\s+)?\w+)?\s+set\b"),
+        (TableAccess::Write, r"\bdelete\s+from\s+", ""),
+        (TableAccess::Write, r"\b(?:merge|replace)\s+into\s+", ""),
+        (TableAccess::Write, r"\btruncate\s+(?:table\s+)?", ""),
+        (TableAccess::Read, r"\b(?:from|join)\s+", ""),
+        (TableAccess::Define, r"\bcreate\s+(?:(?:temp|temporary|unlogged)\s+)?table\s+(?:if\s+not\s+exists\s+)?", ""),
+    ]
+    .into_iter()
+    .map(|(access, prefix, suffix)| (access, Regex::new(&format!("(?i){}{}{}", prefix, TABLE, suffix)).unwrap()))
+    .collect()
+});
+
+/// Text before a FROM that isn't a table source: `EXTRACT(YEAR FROM ts)`, `IS DISTINCT FROM`
+static NOT_TABLE_FROM: Lazy<Regex> = Lazy::new(|| Regex::new(r"(?i)(?:\(\s*\w+|\bdistinct|\bdelete)\s*$").unwrap());
+
+/// Words that follow FROM/JOIN/UPDATE without naming a table
+const NOT_TABLES: &[&str] = &[
+    "select", "lateral", "unnest", "generate_series", "dual", "only", "set", "where", "values", "json_each",
+];
+
+/// Class or struct declarations, for attributing table names to models
+static CLASS_DECL: Lazy<Regex> = Lazy::new(|| Regex::new(r"\b(?:class|struct)\s+([A-Za-z_]\w*)").unwrap());
+
+/// Table names set inside a model's body: SQLAlchemy, Django `Meta`, Rails
+static TABLE_IN_BODY: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r#"(?:\b__tablename__\s*=\s*|\bdb_table\s*=\s*|\bself\.table_name\s*=\s*:?)['"]?(\w+)"#).unwrap()
+});
+
+/// Table names in an annotation before the model: JPA, TypeORM, Diesel, SeaORM
+static TABLE_BEFORE_CLASS: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(
+        r#"@Table\(\s*(?:name\s*=\s*)?"(\w+)"|@Entity\(\s*(?:\{\s*name\s*:\s*)?['"](\w+)['"]|#\[(?:diesel|sea_orm)\(\s*table_name\s*=\s*"?(\w+)"?"#,
+    )
+    .unwrap()
+});
+
+/// GORM: `func (User) TableName() string { return "users" }`
+static GORM_TABLE_NAME: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r#"\bfunc\s*\(\s*(?:\w+\s+)?\*?(\w+)\s*\)\s*TableName\(\)\s*string\s*\{\s*return\s*"(\w+)""#).unwrap()
+});
+
+/// Models mapped to a table by naming convention
+static DJANGO_MODEL: Lazy<Regex> = Lazy::new(|| Regex::new(r"\bclass\s+(\w+)\s*\(\s*models\.Model\s*\)").unwrap());
+static RAILS_MODEL: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"\bclass\s+(\w+)\s*<\s*(?:ApplicationRecord|ActiveRecord::Base)\b").unwrap());
+static ENTITY_ANNOTATION: Lazy<Regex> = Lazy::new(|| Regex::new(r"@Entity\b(?:\(\s*\))?").unwrap());
+
+/// Tables read, written, or created by SQL in a source file's string literals
+pub fn find_sql_references(content: &str) -> Vec<SqlReference> {
+    let mut references = Vec::new();
+    for cap in STRING_LITERAL.captures_iter(content) {
+        let Some(literal) = cap.iter().skip(1).flatten().next() else { continue };
+        if SQL_STATEMENT.is_match(literal.as_str()) {
+            references.extend(sql_references_at(content, literal.start(), literal.as_str()));
+        }
+    }
+    references
+}
+
+/// Tables in a SQL file or statement
+pub fn find_sql_file_references(content: &str) -> Vec<SqlReference> {
+    sql_references_at(content, 0, content)
+}
+
+fn sql_references_at(content: &str, offset: usize, sql: &str) -> Vec<SqlReference> {
+    let mut found: Vec<(usize, SqlReference)> = Vec::new();
+    for (access, pattern) in SQL_PATTERNS.iter() {
+        for cap in pattern.captures_iter(sql) {
+            let (whole, name) = (cap.get(0).unwrap(), cap.get(1).unwrap());
+            // `DELETE FROM t` is only a write, `EXTRACT(YEAR FROM ts)` not a read at all
+            if *access == TableAccess::Read && NOT_TABLE_FROM.is_match(&sql[..whole.start()]) {
+                continue;
+            }
+            let Some(table) = normalize_table(name.as_str()) else { continue };
+            found.push((
+                name.start(),
+                SqlReference {
+                    table,
+                    access: *access,
+                    line: line_at(content, offset + whole.start()),
+                },
+            ));
+        }
+    }
+    found.sort_by_key(|(start, _)| *start);
+    found.into_iter().map(|(_, reference)| reference).collect()
+}
+
+/// ORM model classes in a file and their tables
+pub fn find_orm_models(path: &str, content: &str) -> Vec<ModelTable> {
+    let classes: Vec<(usize, &str)> = CLASS_DECL
+        .captures_iter(content)
+        .map(|cap| (cap.get(0).unwrap().start(), cap.get(1).unwrap().as_str()))
+        .collect();
+    let model_at = |start: usize, name: &str, table: &str| ModelTable {
+        model: name.to_string(),
+        table: table.to_lowercase(),
+        line: line_at(content, start),
+    };
+
+    let mut models: Vec<ModelTable> = Vec::new();
+    for cap in TABLE_IN_BODY.captures_iter(content) {
+        let at = cap.get(0).unwrap().start();
+        // Django's `db_table` sits in the inner `Meta` class
+        if let Some((start, name)) = classes.iter().rev().find(|(start, name)| *start < at && *name != "Meta") {
+            models.push(model_at(*start, name, &cap[1]));
+        }
+    }
+    for cap in TABLE_BEFORE_CLASS.captures_iter(content) {
+        let at = cap.get(0).unwrap().end();
+        let table = cap.iter().skip(1).flatten().next().unwrap().as_str();
+        if let Some((start, name)) = classes.iter().find(|(start, _)| *start > at) {
+            models.push(model_at(*start, name, table));
+        }
+    }
+    for cap in GORM_TABLE_NAME.captures_iter(content) {
+        let type_decl = Regex::new(&format!(r"\btype\s+{}\s+struct\b", regex::escape(&cap[1]))).unwrap();
+        let start = type_decl.find(content).map_or(cap.get(0).unwrap().start(), |m| m.start());
+        models.push(model_at(start, &cap[1], &cap[2]));
+    }
+
+    // Naming conventions, for models without an explicit table
+    let mut implicit: Vec<ModelTable> = Vec::new();
+    if let Some(app) = django_app(path) {
+        for cap in DJANGO_MODEL.captures_iter(content) {
+            let table = format!("{}_{}", app, cap[1].to_lowercase());
+            implicit.push(model_at(cap.get(0).unwrap().start(), &cap[1], &table));
+        }
+    }
+    for cap in RAILS_MODEL.captures_iter(content) {
+        implicit.push(model_at(cap.get(0).unwrap().start(), &cap[1], &pluralize(&snake_case(&cap[1]))));
+    }
+    for entity in ENTITY_ANNOTATION.find_iter(content) {
+        if let Some((start, name)) = classes.iter().find(|(start, _)| *start > entity.end()) {
+            implicit.push(model_at(*start, name, &snake_case(name)));
+        }
+    }
+    for model in implicit {
+        if !models.iter().any(|m| m.model == model.model) {
+            models.push(model);
+        }
+    }
+
+    models.sort_by_key(|m| m.line);
+    models.dedup();
+    models
+}
+
+/// Django app label of a models module: the directory holding `models.py` or `models/`
+fn django_app(path: &str) -> Option<&str> {
+    let path = path.strip_suffix(".py")?;
+    let mut parts: Vec<&str> = path.split(['/', '\\']).collect();
+    let index = parts.iter().rposition(|p| *p == "models")?;
+    parts.truncate(index);
+    parts.pop()
+}
+
+/// Unqualified, unquoted, lowercase table name, or `None` for non-tables
+fn normalize_table(name: &str) -> Option<String> {
+    let last = name.rsplit('.').next().unwrap_or(name);
+    let table = last.trim_matches(|c| matches!(c, '"' | '`' | '[' | ']')).to_lowercase();
+    if table.is_empty() || NOT_TABLES.contains(&table.as_str()) {
+        return None;
+    }
+    Some(table)
+}
+
+/// `BlogPost` -> `blog_post`, `HTTPLog` -> `http_log`
+fn snake_case(name: &str) -> String {
+    let chars: Vec<char> = name.chars().collect();
+    let mut out = String::new();
+    for (i, c) in chars.iter().enumerate() {
+        if c.is_uppercase() && i > 0 {
+            let prev = chars[i - 1];
+            let next_lower = chars.get(i + 1).is_some_and(|n| n.is_lowercase());
+            if prev.is_lowercase() || prev.is_ascii_digit() || (prev.is_uppercase() && next_lower) {
+                out.push('_');
+            }
+        }
+        out.extend(c.to_lowercase());
+    }
+    out
+}
+
+/// English plural of a table name, as Rails infers it for common words
+fn pluralize(word: &str) -> String {
+    if let Some(stem) = word.strip_suffix('y') {
+        if !stem.ends_with(['a', 'e', 'i', 'o', 'u']) {
+            return format!("{}ies", stem);
+        }
+    }
+    if ["s", "x", "z", "ch", "sh"].iter().any(|suffix| word.ends_with(suffix)) {
+        return format!("{}es", word);
+    }
+    format!("{}s", word)
+}
+
+fn line_at(content: &str, offset: usize) -> usize {
+    content[..offset].matches('\n').count() + 1
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn accesses(references: &[SqlReference]) -> Vec<(&str, TableAccess)> {
+        references.iter().map(|r| (r.table.as_str(), r.access)).collect()
+    }
+
+    #[test]
+    fn test_find_sql_references() {
+        let python = r#"
+def load(db, user_id):
+    return db.execute(
+        """
+        SELECT u.*, o.total FROM public.users u
+        JOIN "Orders" o ON o.user_id = u.id
+        """,
+    )
+
+def archive(db):
+    db.execute("INSERT INTO archived_users SELECT * FROM users WHERE deleted")
+    db.execute('DELETE FROM users WHERE deleted')
+    log("Delete failed for user")
+"#;
+        let references = find_sql_references(python);
+        assert_eq!(
+            accesses(&references),
+            vec![
+                ("users", TableAccess::Read),
+                ("orders", TableAccess::Read),
+                ("archived_users", TableAccess::Write),
+                ("users", TableAccess::Read),
+                ("users", TableAccess::Write),
+            ]
+        );
+        assert_eq!(references[0].line, 5);
+        assert_eq!(references[1].line, 6);
+        assert_eq!(references[4].line, 12);
+
+        let ts = "await sql`UPDATE accounts SET balance = ${b} WHERE id = ${id}`;\nconst q = 'select 1';\n";
+        assert_eq!(accesses(&find_sql_references(ts)), vec![("accounts", TableAccess::Write)]);
+
+        let rust = r##"conn.execute(r#"CREATE TABLE IF NOT EXISTS sessions (id TEXT)"#, ())?;"##;
+        assert_eq!(accesses(&find_sql_references(rust)), vec![("sessions", TableAccess::Define)]);
+
+        let migration = "CREATE TABLE patterns (\n  id TEXT\n);\nCREATE INDEX idx ON patterns(id);\n";
+        assert_eq!(accesses(&find_sql_file_references(migration)), vec![("patterns", TableAccess::Define)]);
+    }
+
+    #[test]
+    fn test_find_orm_models() {
+        let sqlalchemy = "class User(Base):\n    __tablename__ = 'users'\n    id = Column(Integer)\n";
+        assert_eq!(
+            find_orm_models("app/models.py", sqlalchemy),
+            vec![ModelTable {
+                model: "User".to_string(),
+                table: "users".to_string(),
+                line: 1
+            }]
+        );
+
+        let django = r#"
+class BlogPost(models.Model):
+    title = models.CharField()
+
+class Comment(models.Model):
+    class Meta:
+        db_table = "post_comments"
+"#;
+        let models = find_orm_models("blog/models.py", django);
+        let tables: Vec<(&str, &str)> = models.iter().map(|m| (m.model.as_str(), m.table.as_str())).collect();
+        assert_eq!(tables, vec![("BlogPost", "blog_blogpost"), ("Comment", "post_comments")]);
+
+        let rails = concat!(
+            "class Category < ApplicationRecord\nend\n",
+            "class Person < ApplicationRecord\n  self.table_name = 'people'\nend\n",
+        );
+        let tables: Vec<String> = find_orm_models("app/models/x.rb", rails).into_iter().map(|m| m.table).collect();
+        assert_eq!(tables, vec!["categories", "people"]);
+
+        let java = "@Entity\n@Table(name = \"app_users\")\npublic class AppUser {}\n@Entity\nclass OrderLine {}\n";
+        let tables: Vec<String> = find_orm_models("User.java", java).into_iter().map(|m| m.table).collect();
+        assert_eq!(tables, vec!["app_users", "order_line"]);
+
+        let typeorm = "@Entity('accounts')\nexport class Account {}\n";
+        assert_eq!(find_orm_models("account.ts", typeorm)[0].table, "accounts");
+
+        let diesel = "#[derive(Queryable)]\n#[diesel(table_name = posts)]\npub struct Post { id: i32 }\n";
+        assert_eq!(find_orm_models("models.rs", diesel)[0].model, "Post");
+
+        let gorm = "type Order struct {\n  ID uint\n}\n\nfunc (Order) TableName() string { return \"orders_v2\" }\n";
+        let models = find_orm_models("order.go", gorm);
+        assert_eq!((models[0].table.as_str(), models[0].line), ("orders_v2", 1));
+    }
+
+    #[test]
+    fn test_naming_helpers() {
+        assert_eq!(snake_case("BlogPost"), "blog_post");
+        assert_eq!(snake_case("HTTPLog"), "http_log");
+        assert_eq!(pluralize("category"), "categories");
+        assert_eq!(pluralize("day"), "days");
+        assert_eq!(pluralize("address"), "addresses");
+        assert_eq!(django_app("shop/models/product.py"), Some("shop"));
+        assert_eq!(django_app("models.py"), None);
+    }
+}
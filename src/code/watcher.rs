@@ -0,0 +1,145 @@
+//! Background re-indexing: watches the project for file changes and patches the store and
+//! in-memory graph, so the agent doesn't have to call `index_project` after every edit.
+//!
+//! Excluded top-level directories (`target`, `node_modules`, ...) are never watched, so builds
+//! there don't flood the watcher or use up inotify watches. Events are debounced: a batch is
+//! indexed once changes have been quiet for the configured interval.
+
+use anyhow::{Context, Result};
+use notify::{Event, EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+use std::collections::BTreeSet;
+use std::future::Future;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::sync::{mpsc, RwLock};
+use tokio::task::JoinHandle;
+use tracing::{debug, info, warn};
+use walkdir::WalkDir;
+
+use super::indexer::{IndexStats, Indexer};
+use crate::store::CodeGraph;
+
+/// Stops watching when dropped
+pub struct FileWatcher {
+    _watcher: Arc<Mutex<RecommendedWatcher>>,
+    task: JoinHandle<()>,
+}
+
+impl Drop for FileWatcher {
+    fn drop(&mut self) {
+        self.task.abort();
+    }
+}
+
+impl FileWatcher {
+    pub(crate) fn start<F, Fut>(
+        indexer: Arc<Indexer>,
+        graph: Arc<RwLock<CodeGraph>>,
+        debounce: Duration,
+        on_update: F,
+    ) -> Result<Self>
+    where
+        F: Fn(IndexStats) -> Fut + Send + 'static,
+        Fut: Future<Output = ()> + Send + 'static,
+    {
+        let (tx, rx) = mpsc::unbounded_channel();
+        let watcher = notify::recommended_watcher(move |event: notify::Result<Event>| match event {
+            Ok(event) if is_content_change(&event.kind) => {
+                for path in event.paths {
+                    let _ = tx.send(path);
+                }
+            }
+            Ok(_) => {}
+            Err(e) => warn!("File watcher error: {}", e),
+        })
+        .context("Failed to create file watcher")?;
+        let watcher = Arc::new(Mutex::new(watcher));
+
+        // The root itself is watched shallowly, so new top-level directories show up
+        let root = indexer.project_root().to_path_buf();
+        watch(&watcher, &root, RecursiveMode::NonRecursive)?;
+        for entry in std::fs::read_dir(&root).context("Failed to list project root")? {
+            let path = entry?.path();
+            if path.is_dir() && indexer.watches_dir(&path) {
+                watch(&watcher, &path, RecursiveMode::Recursive)?;
+            }
+        }
+        info!("Watching {:?} for changes", root);
+
+        let task = tokio::spawn(run(indexer, graph, watcher.clone(), debounce, rx, on_update));
+        Ok(Self {
+            _watcher: watcher,
+            task,
+        })
+    }
+}
+
+fn watch(watcher: &Mutex<RecommendedWatcher>, path: &Path, mode: RecursiveMode) -> Result<()> {
+    watcher
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .watch(path, mode)
+        .with_context(|| format!("Failed to watch {:?}", path))
+}
+
+/// Creates, writes, renames, and deletes; plain reads are ignored
+fn is_content_change(kind: &EventKind) -> bool {
+    matches!(kind, EventKind::Create(_) | EventKind::Modify(_) | EventKind::Remove(_))
+}
+
+async fn run<F, Fut>(
+    indexer: Arc<Indexer>,
+    graph: Arc<RwLock<CodeGraph>>,
+    watcher: Arc<Mutex<RecommendedWatcher>>,
+    debounce: Duration,
+    mut rx: mpsc::UnboundedReceiver<PathBuf>,
+    on_update: F,
+) where
+    F: Fn(IndexStats) -> Fut,
+    Fut: Future<Output = ()>,
+{
+    while let Some(path) = rx.recv().await {
+        let mut batch = BTreeSet::from([path]);
+        loop {
+            match tokio::time::timeout(debounce, rx.recv()).await {
+                Ok(Some(path)) => {
+                    batch.insert(path);
+                }
+                Ok(None) => return,
+                Err(_) => break,
+            }
+        }
+
+        // A directory created at the top level wasn't covered by any recursive watch yet, and
+        // files may have landed in it before the watch was added
+        let root = indexer.project_root();
+        let new_dirs: Vec<PathBuf> = batch
+            .iter()
+            .filter(|p| p.parent() == Some(root) && p.is_dir() && indexer.watches_dir(p))
+            .cloned()
+            .collect();
+        for dir in new_dirs {
+            if let Err(e) = watch(&watcher, &dir, RecursiveMode::Recursive) {
+                warn!("{:#}", e);
+            }
+            let files = WalkDir::new(&dir).into_iter().filter_map(|e| e.ok()).filter(|e| e.file_type().is_file());
+            batch.extend(files.map(|e| e.into_path()));
+        }
+
+        let paths: Vec<PathBuf> = batch.into_iter().collect();
+        debug!("{} paths changed", paths.len());
+        match indexer.index_changed(&paths).await {
+            Ok(stats) if stats.files_indexed + stats.files_removed > 0 => {
+                graph.write().await.apply(&stats.changes);
+                info!(
+                    "Re-indexed {} changed files, removed {} in {}ms",
+                    stats.files_indexed, stats.files_removed, stats.duration_ms
+                );
+                on_update(stats).await;
+            }
+            Ok(_) => {}
+            Err(e) => warn!("Re-indexing changed files failed: {:#}", e),
+        }
+    }
+}
@@ -0,0 +1,300 @@
+//! Benchmark output compression - criterion (cargo bench) and pytest-benchmark.
+//!
+//! Keeps each benchmark's central estimate and its change against the saved
+//! baseline, dropping warm-up, sampling, and outlier noise.
+
+use std::collections::HashMap;
+
+use once_cell::sync::Lazy;
+use regex::Regex;
+
+use super::{truncate_with_summary, CompressConfig, CompressResult};
+
+/// pytest-benchmark changes beyond this (percent) count as regressions/improvements
+const COMPARE_THRESHOLD: f64 = 5.0;
+
+/// "graph/insert   time:   [98.123 µs 99.456 µs 100.87 µs]" (the name may be on the line before)
+static CRITERION_TIME: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"^(\S.*?)?\s*time:\s+\[\S+ \S+ (\S+ \S+) \S+ \S+\]").unwrap()
+});
+
+/// "change: [+12.345% +14.012% +15.789%] (p = 0.00 < 0.05)"
+static CRITERION_CHANGE: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"(?:change|time):\s+\[\S+ ([+-]?[\d.]+)% \S+\]").unwrap()
+});
+
+/// "Name (time in us)   Min   Max   Mean ..."
+static PYTEST_BENCH_HEADER: Lazy<Regex> = Lazy::new(|| Regex::new(r"^Name \(time in (\w+)\)").unwrap());
+
+/// One numeric column of a pytest-benchmark row, with an optional "(1.0)" ratio
+static PYTEST_BENCH_VALUE: Lazy<Regex> = Lazy::new(|| Regex::new(r"[\d.,;]+(?:\s*\([^)]*\))?").unwrap());
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Verdict {
+    Regressed,
+    Improved,
+    Unchanged,
+}
+
+struct Benchmark {
+    name: String,
+    estimate: String,
+    /// Percent change of the estimate against the baseline
+    change: Option<f64>,
+    verdict: Verdict,
+}
+
+/// Compress benchmark output. Returns `None` if no benchmark results were found.
+pub fn compress_bench_output(output: &str, config: &CompressConfig) -> Option<CompressResult> {
+    let benchmarks = parse_criterion(output);
+    let (benchmarks, unit) = if benchmarks.is_empty() {
+        parse_pytest_benchmark(output)?
+    } else {
+        (benchmarks, None)
+    };
+    if benchmarks.is_empty() {
+        return None;
+    }
+
+    let regressed: Vec<&Benchmark> = benchmarks.iter().filter(|b| b.verdict == Verdict::Regressed).collect();
+    let improved: Vec<&Benchmark> = benchmarks.iter().filter(|b| b.verdict == Verdict::Improved).collect();
+    let unchanged: Vec<&Benchmark> = benchmarks.iter().filter(|b| b.verdict == Verdict::Unchanged).collect();
+
+    let mut result_lines: Vec<String> = Vec::new();
+    let status = if regressed.is_empty() { "📈" } else { "⚠️" };
+    let mut header = format!("{} {} benchmarks", status, benchmarks.len());
+    if !regressed.is_empty() || !improved.is_empty() {
+        header.push_str(&format!(" ({} regressed, {} improved)", regressed.len(), improved.len()));
+    }
+    if let Some(unit) = unit {
+        header.push_str(&format!(", mean time in {}", unit));
+    }
+    result_lines.push(header);
+
+    let describe = |b: &Benchmark| match b.change {
+        Some(change) => format!("  {}: {} ({:+.2}%)", b.name, b.estimate, change),
+        None => format!("  {}: {}", b.name, b.estimate),
+    };
+    for (label, group) in [("Regressed:", &regressed), ("Improved:", &improved), ("Results:", &unchanged)] {
+        if group.is_empty() {
+            continue;
+        }
+        result_lines.push(label.to_string());
+        result_lines.extend(group.iter().take(config.max_items_per_group).map(|b| describe(b)));
+        if group.len() > config.max_items_per_group {
+            result_lines.push(format!("  ... +{} more", group.len() - config.max_items_per_group));
+        }
+    }
+
+    let compressed = truncate_with_summary(&result_lines, config.max_lines);
+    Some(CompressResult::new(output, compressed))
+}
+
+fn parse_criterion(output: &str) -> Vec<Benchmark> {
+    let mut benchmarks: Vec<Benchmark> = Vec::new();
+    // Long names are printed on their own line, before the "time:" line
+    let mut pending_name: Option<String> = None;
+    // With throughput, "change:" is followed by separate time/thrpt lines
+    let mut expecting_change = false;
+
+    for line in output.lines() {
+        let trimmed = line.trim();
+
+        if let Some(bench) = benchmarks.last_mut() {
+            if expecting_change || trimmed.starts_with("change:") {
+                if let Some(caps) = CRITERION_CHANGE.captures(trimmed) {
+                    bench.change = caps[1].parse().ok();
+                    expecting_change = false;
+                    continue;
+                }
+                expecting_change = trimmed == "change:";
+                if expecting_change {
+                    continue;
+                }
+            }
+            if trimmed.starts_with("Performance has regressed") {
+                bench.verdict = Verdict::Regressed;
+                continue;
+            } else if trimmed.starts_with("Performance has improved") {
+                bench.verdict = Verdict::Improved;
+                continue;
+            }
+        }
+
+        if let Some(caps) = CRITERION_TIME.captures(line) {
+            let name = caps
+                .get(1)
+                .map(|m| m.as_str().trim().to_string())
+                .or_else(|| pending_name.take())
+                .unwrap_or_else(|| "?".to_string());
+            benchmarks.push(Benchmark {
+                name,
+                estimate: caps[2].to_string(),
+                change: None,
+                verdict: Verdict::Unchanged,
+            });
+            continue;
+        }
+
+        // Anything else at column 0 may be a long benchmark name
+        let is_noise = trimmed.is_empty()
+            || line.starts_with(char::is_whitespace)
+            || ["Benchmarking ", "Found ", "Running ", "Gnuplot ", "WARNING", "Warning", "Compiling ", "Finished "]
+                .iter()
+                .any(|p| trimmed.starts_with(p));
+        pending_name = if is_noise { pending_name } else { Some(trimmed.to_string()) };
+    }
+
+    benchmarks
+}
+
+/// Parse pytest-benchmark tables. With `--benchmark-compare`, rows named
+/// "test_x (NOW)" are compared against the baseline row of the same test.
+fn parse_pytest_benchmark(output: &str) -> Option<(Vec<Benchmark>, Option<String>)> {
+    let lines: Vec<&str> = output.lines().collect();
+    let header_at = lines.iter().position(|l| PYTEST_BENCH_HEADER.is_match(l.trim()))?;
+    let header = lines[header_at].trim();
+    let unit = PYTEST_BENCH_HEADER.captures(header)?[1].to_string();
+
+    // Column index of "Mean" among the value columns
+    let columns: Vec<&str> = header.split_whitespace().skip(4).collect();
+    let mean_column = columns.iter().position(|c| *c == "Mean")?;
+
+    let mut rows: Vec<(String, String)> = Vec::new();
+    for line in lines.iter().skip(header_at + 1) {
+        let trimmed = line.trim();
+        if trimmed.starts_with("---") {
+            if rows.is_empty() {
+                continue;
+            }
+            break;
+        }
+        let Some(split) = trimmed.find("  ") else {
+            continue;
+        };
+        let (name, values) = trimmed.split_at(split);
+        let values: Vec<&str> = PYTEST_BENCH_VALUE.find_iter(values).map(|m| m.as_str()).collect();
+        if let Some(mean) = values.get(mean_column) {
+            let mean = mean.split_whitespace().next().unwrap_or(mean);
+            rows.push((name.trim().to_string(), mean.to_string()));
+        }
+    }
+
+    // Baseline rows keyed by test name ("test_x (0001_abc)")
+    let mut baselines: HashMap<String, f64> = HashMap::new();
+    for (name, mean) in &rows {
+        if let Some((test, run)) = name.rsplit_once(" (") {
+            if run != "NOW)" {
+                if let Ok(mean) = mean.replace(',', "").parse() {
+                    baselines.insert(test.to_string(), mean);
+                }
+            }
+        }
+    }
+
+    let benchmarks = rows
+        .iter()
+        .filter_map(|(name, mean)| {
+            let Some((test, run)) = name.rsplit_once(" (") else {
+                return Some(Benchmark {
+                    name: name.clone(),
+                    estimate: mean.clone(),
+                    change: None,
+                    verdict: Verdict::Unchanged,
+                });
+            };
+            // Only the current run is reported; baselines are folded into its change
+            if run != "NOW)" {
+                return None;
+            }
+            let current: f64 = mean.replace(',', "").parse().ok()?;
+            let change = baselines
+                .get(test)
+                .filter(|base| **base > 0.0)
+                .map(|base| (current - base) / base * 100.0);
+            let verdict = match change {
+                Some(c) if c > COMPARE_THRESHOLD => Verdict::Regressed,
+                Some(c) if c < -COMPARE_THRESHOLD => Verdict::Improved,
+                _ => Verdict::Unchanged,
+            };
+            Some(Benchmark {
+                name: test.to_string(),
+                estimate: mean.clone(),
+                change,
+                verdict,
+            })
+        })
+        .collect();
+
+    Some((benchmarks, Some(unit)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_compress_criterion() {
+        let input = r#"   Compiling codegraph v0.1.0
+    Finished `bench` profile [optimized] target(s) in 30.12s
+     Running benches/graph_benchmarks.rs
+Benchmarking graph/insert_1000
+Benchmarking graph/insert_1000: Warming up for 3.0000 s
+Benchmarking graph/insert_1000: Collecting 100 samples in estimated 5.0123 s (50k iterations)
+Benchmarking graph/insert_1000: Analyzing
+graph/insert_1000       time:   [98.123 µs 99.456 µs 100.87 µs]
+                        change: [+12.345% +14.012% +15.789%] (p = 0.00 < 0.05)
+                        Performance has regressed.
+Found 5 outliers among 100 measurements (5.00%)
+  3 (3.00%) high mild
+  2 (2.00%) high severe
+graph/query             time:   [1.2345 ms 1.2456 ms 1.2567 ms]
+                        change: [-5.1234% -4.0123% -3.0012%] (p = 0.00 < 0.05)
+                        Performance has improved.
+Benchmarking search/symbols_with_a_very_long_name: Analyzing
+search/symbols_with_a_very_long_name
+                        time:   [1.0 ns 1.1 ns 1.2 ns]
+                        change: [-1.0% +0.2% +1.3%] (p = 0.61 > 0.05)
+                        No change in performance detected.
+"#;
+        let config = CompressConfig::default();
+        let result = compress_bench_output(input, &config).unwrap();
+
+        assert_eq!(
+            result.output,
+            "⚠️ 3 benchmarks (1 regressed, 1 improved)
+Regressed:
+  graph/insert_1000: 99.456 µs (+14.01%)
+Improved:
+  graph/query: 1.2456 ms (-4.01%)
+Results:
+  search/symbols_with_a_very_long_name: 1.1 ns (+0.20%)"
+        );
+    }
+
+    #[test]
+    fn test_compress_pytest_benchmark_compare() {
+        let input = r#"
+-------------------------------------------------------------------------------------- benchmark: 4 tests --------------------------------------------------------------------------------------
+Name (time in us)                  Min                 Max                Mean            StdDev              Median               IQR            Outliers  OPS (Kops/s)            Rounds  Iterations
+-----------------------------------------------------------------------------------------------------------------------------------------------------------------------------------------------
+test_parse (0001_baseline)      1.2000 (1.0)       10.3000 (1.0)        1.5000 (1.0)      0.3000 (1.0)        1.4000 (1.0)      0.1000 (1.0)      100;200  666.6667 (1.0)      10000           1
+test_parse (NOW)                1.2500 (1.04)      10.9000 (1.06)       1.5300 (1.02)     0.3100 (1.03)       1.4100 (1.01)     0.1000 (1.0)      100;210  653.5948 (0.98)     10000           1
+test_render (0001_baseline)   120.5000 (100.42)   300.0000 (29.13)    130.0000 (86.67)   10.0000 (33.33)    128.0000 (91.43)    5.0000 (50.00)        3;5    7.6923 (0.01)        100           1
+test_render (NOW)             150.5000 (125.42)   330.0000 (32.04)    160.0000 (106.67)  11.0000 (36.67)    158.0000 (112.86)   5.0000 (50.00)        3;5    6.2500 (0.01)        100           1
+-----------------------------------------------------------------------------------------------------------------------------------------------------------------------------------------------
+"#;
+        let config = CompressConfig::default();
+        let result = compress_bench_output(input, &config).unwrap();
+
+        assert_eq!(
+            result.output,
+            "⚠️ 2 benchmarks (1 regressed, 0 improved), mean time in us
+Regressed:
+  test_render: 160.0000 (+23.08%)
+Results:
+  test_parse: 1.5300 (+2.00%)"
+        );
+        assert!(compress_bench_output("running 3 tests\ntest result: ok", &config).is_none());
+    }
+}
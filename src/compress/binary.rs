@@ -0,0 +1,152 @@
+//! Binary and garbage output detection.
+//!
+//! Commands like `cat image.png` or `curl` on a tarball produce bytes that turn
+//! into mojibake in LLM context. Such output is replaced with a one-line summary
+//! of its size and detected type.
+
+use super::docker::format_bytes;
+
+/// Bytes inspected for text heuristics
+const SAMPLE_SIZE: usize = 8192;
+
+/// Share of undecodable or control characters above which output counts as binary
+const MAX_GARBAGE_RATIO: f64 = 0.1;
+
+/// File signatures: (offset, magic bytes, description)
+const SIGNATURES: &[(usize, &[u8], &str)] = &[
+    (0, b"\x7fELF", "ELF executable"),
+    (0, b"\x89PNG\r\n\x1a\n", "PNG image"),
+    (0, b"\xff\xd8\xff", "JPEG image"),
+    (0, b"GIF87a", "GIF image"),
+    (0, b"GIF89a", "GIF image"),
+    (0, b"%PDF-", "PDF document"),
+    (0, b"PK\x03\x04", "zip archive"),
+    (0, b"\x1f\x8b", "gzip data"),
+    (0, b"BZh", "bzip2 data"),
+    (0, b"\xfd7zXZ\x00", "xz data"),
+    (0, b"\x28\xb5\x2f\xfd", "zstd data"),
+    (0, b"\xcf\xfa\xed\xfe", "Mach-O executable"),
+    (0, b"\xca\xfe\xba\xbe", "Java class or Mach-O universal binary"),
+    (0, b"MZ", "Windows executable"),
+    (0, b"\x00asm", "WebAssembly module"),
+    (0, b"SQLite format 3\x00", "SQLite database"),
+    (257, b"ustar", "tar archive"),
+];
+
+/// One-line replacement for binary or encoded output, or `None` if it's readable text
+pub fn binary_summary(bytes: &[u8]) -> Option<String> {
+    let kind = detect_binary(bytes)?;
+    Some(format!("📦 {} of {} omitted", format_bytes(bytes.len() as u64), kind))
+}
+
+/// Describe output that isn't worth showing as text
+fn detect_binary(bytes: &[u8]) -> Option<&'static str> {
+    if bytes.is_empty() {
+        return None;
+    }
+
+    if let Some((_, _, kind)) = SIGNATURES
+        .iter()
+        .find(|(offset, magic, _)| bytes.get(*offset..offset + magic.len()) == Some(*magic))
+    {
+        // "MZ" and "BZh" are common text prefixes; trust them only with binary content
+        if !matches!(*kind, "Windows executable" | "bzip2 data") || has_garbage(bytes) {
+            return Some(kind);
+        }
+    }
+
+    if has_garbage(bytes) {
+        return Some("binary data");
+    }
+    if is_encoded_blob(bytes) {
+        return Some("high-entropy text (likely base64 or encoded data)");
+    }
+    None
+}
+
+/// NUL bytes or a high share of undecodable/control characters in the sample
+fn has_garbage(bytes: &[u8]) -> bool {
+    let sample = &bytes[..bytes.len().min(SAMPLE_SIZE)];
+    if sample.contains(&0) {
+        return true;
+    }
+
+    let text = String::from_utf8_lossy(sample);
+    let mut total = 0;
+    let mut garbage = 0;
+    for c in text.chars() {
+        total += 1;
+        // A sample cut mid-character ends in one replacement char; allow it
+        if c == char::REPLACEMENT_CHARACTER || (c.is_control() && !matches!(c, '\n' | '\r' | '\t' | '\x1b')) {
+            garbage += 1;
+        }
+    }
+    total > 0 && garbage > 1 && garbage as f64 / total as f64 > MAX_GARBAGE_RATIO
+}
+
+/// Long runs of high-entropy characters with almost no whitespace (base64 dumps,
+/// hex blobs, encrypted payloads)
+fn is_encoded_blob(bytes: &[u8]) -> bool {
+    let sample = &bytes[..bytes.len().min(SAMPLE_SIZE)];
+    if sample.len() < 1024 {
+        return false;
+    }
+
+    let words = sample.split(|b| b.is_ascii_whitespace()).filter(|w| !w.is_empty()).count();
+    let average_word = sample.len() as f64 / words.max(1) as f64;
+    average_word > 60.0 && entropy(sample) > 5.0
+}
+
+/// Shannon entropy in bits per byte
+fn entropy(bytes: &[u8]) -> f64 {
+    let mut counts = [0usize; 256];
+    for &b in bytes {
+        counts[b as usize] += 1;
+    }
+    let len = bytes.len() as f64;
+    counts
+        .iter()
+        .filter(|&&c| c > 0)
+        .map(|&c| {
+            let p = c as f64 / len;
+            -p * p.log2()
+        })
+        .sum()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_binary_summary() {
+        let mut png = b"\x89PNG\r\n\x1a\n".to_vec();
+        png.extend((0..2000u32).map(|i| (i * 7 % 256) as u8));
+        assert_eq!(binary_summary(&png).unwrap(), "📦 2.0 KB of PNG image omitted");
+
+        let garbage: Vec<u8> = (0..4000u32).map(|i| (i * 31 % 251) as u8 | 0x80).collect();
+        assert_eq!(binary_summary(&garbage).unwrap(), "📦 4.0 KB of binary data omitted");
+
+        let base64: String = (0..3000u32)
+            .map(|i| {
+                let alphabet = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+                alphabet[(i * 37 % 64) as usize ^ (i as usize / 64 % 64)] as char
+            })
+            .collect::<Vec<_>>()
+            .chunks(76)
+            .map(|c| c.iter().collect::<String>())
+            .collect::<Vec<_>>()
+            .join("\n");
+        assert!(binary_summary(base64.as_bytes()).unwrap().contains("high-entropy text"));
+    }
+
+    #[test]
+    fn test_text_is_not_binary() {
+        let text = "MZ is a prefix here\nerror: ünïcödé and emoji 🎉 are fine\n\x1b[31mred\x1b[0m\n".repeat(100);
+        assert!(binary_summary(text.as_bytes()).is_none());
+        assert!(binary_summary(b"").is_none());
+
+        let source = std::fs::read(file!()).unwrap();
+        assert!(binary_summary(&source).is_none());
+    }
+}
@@ -0,0 +1,350 @@
+//! Build output compression for `cargo build/check/clippy` and `rustc`.
+//!
+//! Counts unique diagnostics per file, then groups them by error code or lint and keeps
+//! only file:line and the first message line, dropping code snippets, notes, and
+//! progress lines.
+
+use std::collections::{HashMap, HashSet};
+
+use super::{CompressConfig, CompressResult};
+
+/// Compress cargo build/check/clippy output into diagnostics grouped by code
+pub fn compress_cargo_build(output: &str, config: &CompressConfig) -> CompressResult {
+    let lines: Vec<&str> = output.lines().collect();
+
+    if lines.is_empty() {
+        return CompressResult::new(output, String::new());
+    }
+
+    let mut diagnostics: Vec<Diagnostic> = Vec::new();
+    let mut current: Option<Diagnostic> = None;
+    let mut finished: Option<&str> = None;
+
+    for line in &lines {
+        let trimmed = line.trim();
+
+        if let Some(diagnostic) = Diagnostic::parse_header(line) {
+            if let Some(d) = current.take() {
+                diagnostics.push(d);
+            }
+            current = Some(diagnostic);
+            continue;
+        }
+
+        if trimmed.starts_with("Finished ") {
+            finished = Some(trimmed);
+        }
+
+        let Some(ref mut d) = current else {
+            continue;
+        };
+
+        if let Some(location) = trimmed.strip_prefix("--> ") {
+            if d.location.is_empty() {
+                d.location = strip_column(location).to_string();
+            }
+        } else if d.code.is_none() {
+            d.code = lint_name(trimmed);
+        }
+    }
+
+    if let Some(d) = current {
+        diagnostics.push(d);
+    }
+
+    // Drop cargo's own summary lines ("could not compile", "generated N warnings")
+    diagnostics.retain(|d| !is_summary(&d.message));
+
+    // rustc only prints a lint's "on by default" note once, so share codes
+    // between diagnostics with the same message shape
+    let known_codes: HashMap<String, String> = diagnostics
+        .iter()
+        .filter_map(|d| Some((message_shape(&d.message), d.code.clone()?)))
+        .collect();
+    for d in &mut diagnostics {
+        if d.code.is_none() {
+            d.code = known_codes.get(&message_shape(&d.message)).cloned();
+        }
+    }
+
+    // Each diagnostic once, however many targets (lib, tests, ...) reported it
+    let mut seen = HashSet::new();
+    let unique: Vec<&Diagnostic> = diagnostics
+        .iter()
+        .filter(|d| seen.insert((d.is_error, d.location.as_str(), d.message.as_str())))
+        .collect();
+    let errors = unique.iter().filter(|d| d.is_error).count();
+    let warnings = unique.len() - errors;
+
+    let mut files: HashMap<&str, (usize, usize)> = HashMap::new();
+    for d in &unique {
+        if let Some((file, _)) = d.location.rsplit_once(':') {
+            let counts = files.entry(file).or_default();
+            if d.is_error {
+                counts.0 += 1;
+            } else {
+                counts.1 += 1;
+            }
+        }
+    }
+
+    let mut result_lines: Vec<String> = Vec::new();
+
+    let status_emoji = if errors > 0 {
+        "❌"
+    } else if warnings > 0 {
+        "⚠️"
+    } else {
+        "✅"
+    };
+    result_lines.push(format!("{} {} errors, {} warnings", status_emoji, errors, warnings));
+
+    // Per-file counts, most errors first, once there is more than one file to tell apart
+    if files.len() > 1 {
+        let mut by_file: Vec<_> = files.into_iter().collect();
+        by_file.sort_by_key(|&(file, (errors, warnings))| {
+            (std::cmp::Reverse(errors), std::cmp::Reverse(errors + warnings), file)
+        });
+        for (file, (errors, warnings)) in by_file.iter().take(config.max_items_per_group) {
+            result_lines.push(format!("  {}: {}", file, file_counts(*errors, *warnings)));
+        }
+        if by_file.len() > config.max_items_per_group {
+            result_lines.push(format!("  ... +{} more files", by_file.len() - config.max_items_per_group));
+        }
+    }
+
+    // Group by severity + code, errors first, largest groups first
+    let mut groups: HashMap<(bool, String), Vec<&Diagnostic>> = HashMap::new();
+    for d in &diagnostics {
+        groups.entry((d.is_error, d.group_key())).or_default().push(d);
+    }
+    let mut keys: Vec<_> = groups.keys().cloned().collect();
+    keys.sort_by(|a, b| {
+        b.0.cmp(&a.0)
+            .then(groups[b].len().cmp(&groups[a].len()))
+            .then(a.1.cmp(&b.1))
+    });
+
+    for key in keys.iter().take(config.max_lines) {
+        let group = &groups[key];
+        let severity = if key.0 { "error" } else { "warning" };
+        result_lines.push(String::new());
+        result_lines.push(format!("{}[{}] ({})", severity, key.1, group.len()));
+
+        // Dedupe identical location + message pairs (e.g. repeated across targets)
+        let mut counts: HashMap<(&str, &str), usize> = HashMap::new();
+        let mut order: Vec<(&str, &str)> = Vec::new();
+        for d in group {
+            let entry = (d.location.as_str(), d.message.as_str());
+            *counts.entry(entry).or_insert(0) += 1;
+            if counts[&entry] == 1 {
+                order.push(entry);
+            }
+        }
+
+        for entry in order.iter().take(config.max_items_per_group) {
+            let (location, message) = entry;
+            let mut line = if location.is_empty() {
+                format!("  {}", message)
+            } else {
+                format!("  {} {}", location, message)
+            };
+            let count = counts[entry];
+            if config.show_counts && count >= config.dedup_threshold {
+                line.push_str(&format!(" (×{})", count));
+            }
+            result_lines.push(line);
+        }
+
+        if order.len() > config.max_items_per_group {
+            result_lines.push(format!("  ... +{} more", order.len() - config.max_items_per_group));
+        }
+    }
+
+    if keys.len() > config.max_lines {
+        result_lines.push(format!("\n... +{} more diagnostic groups", keys.len() - config.max_lines));
+    }
+
+    if let Some(finished) = finished {
+        result_lines.push(String::new());
+        result_lines.push(finished.to_string());
+    }
+
+    let compressed = result_lines.join("\n");
+    CompressResult::new(output, compressed)
+}
+
+/// A single compiler diagnostic
+struct Diagnostic {
+    is_error: bool,
+    /// Error code (E0308) or lint name (unused_variables, clippy::needless_return)
+    code: Option<String>,
+    message: String,
+    /// file:line of the primary span
+    location: String,
+}
+
+impl Diagnostic {
+    /// Parse "error[E0308]: msg", "warning: msg", etc. Indented lines are not headers.
+    fn parse_header(line: &str) -> Option<Self> {
+        let (is_error, rest) = if let Some(rest) = line.strip_prefix("error") {
+            (true, rest)
+        } else if let Some(rest) = line.strip_prefix("warning") {
+            (false, rest)
+        } else {
+            return None;
+        };
+
+        let (code, message) = if let Some(rest) = rest.strip_prefix('[') {
+            let (code, message) = rest.split_once("]: ")?;
+            (Some(code.to_string()), message)
+        } else {
+            (None, rest.strip_prefix(": ")?)
+        };
+
+        Some(Self {
+            is_error,
+            code,
+            message: message.trim().to_string(),
+            location: String::new(),
+        })
+    }
+
+    fn group_key(&self) -> String {
+        self.code.clone().unwrap_or_else(|| message_shape(&self.message))
+    }
+}
+
+/// Message with quoted identifiers blanked: "unused variable: `x`" → "unused variable: `_`"
+fn message_shape(message: &str) -> String {
+    message
+        .split('`')
+        .enumerate()
+        .map(|(i, part)| if i % 2 == 1 { "_" } else { part })
+        .collect::<Vec<_>>()
+        .join("`")
+}
+
+/// Extract the lint name from rustc/clippy notes
+fn lint_name(line: &str) -> Option<String> {
+    // = note: `#[warn(unused_variables)]` on by default
+    if let Some(rest) = line.split("#[warn(").nth(1).or_else(|| line.split("#[deny(").nth(1)) {
+        return rest.split(')').next().map(|s| s.to_string());
+    }
+    // = note: `-D clippy::needless-return` implied by `-D warnings`
+    if let Some(rest) = line.split("`-D ").nth(1).or_else(|| line.split("`-W ").nth(1)) {
+        let name = rest.split('`').next()?;
+        if name != "warnings" {
+            return Some(name.replace('-', "_"));
+        }
+    }
+    // = help: for further information visit https://rust-lang.github.io/rust-clippy/.../index.html#needless_return
+    if line.contains("rust-clippy") {
+        return line.rsplit_once('#').map(|(_, name)| format!("clippy::{}", name));
+    }
+    None
+}
+
+/// "2 errors, 1 warning", leaving out a kind with none
+pub(super) fn file_counts(errors: usize, warnings: usize) -> String {
+    let plural = |n: usize, noun: &str| format!("{} {}{}", n, noun, if n == 1 { "" } else { "s" });
+    match (errors, warnings) {
+        (0, w) => plural(w, "warning"),
+        (e, 0) => plural(e, "error"),
+        (e, w) => format!("{}, {}", plural(e, "error"), plural(w, "warning")),
+    }
+}
+
+/// Trim the column from "src/lib.rs:10:5"
+fn strip_column(location: &str) -> &str {
+    match location.rsplit_once(':') {
+        Some((rest, col)) if rest.contains(':') && col.chars().all(|c| c.is_ascii_digit()) => rest,
+        _ => location,
+    }
+}
+
+/// Cargo's trailing summary lines, which repeat information already shown
+fn is_summary(message: &str) -> bool {
+    message.starts_with("could not compile")
+        || message.starts_with("aborting due to")
+        || message.starts_with("build failed")
+        || (message.starts_with('`') && message.contains(" generated "))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CLIPPY_OUTPUT: &str = r#"    Checking foo v0.1.0 (/tmp/foo)
+warning: unused variable: `x`
+ --> src/main.rs:3:9
+  |
+3 |     let x = 5;
+  |         ^ help: if this is intentional, prefix it with an underscore: `_x`
+  |
+  = note: `#[warn(unused_variables)]` on by default
+
+warning: unused variable: `y`
+ --> src/main.rs:4:9
+  |
+4 |     let y = 5;
+  |         ^ help: if this is intentional, prefix it with an underscore: `_y`
+
+warning: unneeded `return` statement
+  --> src/lib.rs:10:5
+   |
+10 |     return 1;
+   |     ^^^^^^^^^
+   |
+   = help: for further information visit https://rust-lang.github.io/rust-clippy/master/index.html#needless_return
+   = note: `#[warn(clippy::needless_return)]` on by default
+
+error[E0308]: mismatched types
+  --> src/lib.rs:20:5
+   |
+20 |     "a"
+   |     ^^^ expected `i32`, found `&str`
+
+error[E0308]: mismatched types
+  --> src/lib.rs:30:5
+   |
+30 |     "b"
+   |     ^^^ expected `i32`, found `&str`
+
+warning: `foo` (lib) generated 3 warnings
+error: could not compile `foo` (lib) due to 2 previous errors; 3 warnings emitted
+"#;
+
+    #[test]
+    fn test_compress_cargo_build() {
+        let config = CompressConfig::default();
+        let result = compress_cargo_build(CLIPPY_OUTPUT, &config);
+
+        let summary = "❌ 2 errors, 3 warnings\n  src/lib.rs: 2 errors, 1 warning\n  src/main.rs: 2 warnings\n";
+        assert!(result.output.starts_with(summary), "{}", result.output);
+        assert!(result.output.contains("error[E0308] (2)"));
+        assert!(result.output.contains("  src/lib.rs:20 mismatched types"));
+        assert!(result.output.contains("warning[unused_variables] (2)"));
+        assert!(result.output.contains("warning[clippy::needless_return] (1)"));
+        assert!(!result.output.contains("could not compile"));
+        assert!(!result.output.contains("let x = 5"));
+
+        // Errors come before warnings
+        let error_pos = result.output.find("error[E0308]").unwrap();
+        let warning_pos = result.output.find("warning[unused_variables]").unwrap();
+        assert!(error_pos < warning_pos);
+        assert!(result.reduction_percent() > 50.0);
+    }
+
+    #[test]
+    fn test_dedupes_repeated_diagnostics() {
+        // Same warning reported for lib and test targets
+        let input = "warning: unused import: `std::fmt`\n --> src/lib.rs:1:5\n\nwarning: unused import: `std::fmt`\n --> src/lib.rs:1:5\n\n    Finished `dev` profile [unoptimized + debuginfo] target(s) in 0.5s\n";
+        let config = CompressConfig::default();
+        let result = compress_cargo_build(input, &config);
+
+        assert!(result.output.starts_with("⚠️ 0 errors, 1 warnings\n\n"), "{}", result.output);
+        assert!(result.output.contains("src/lib.rs:1 unused import: `std::fmt` (×2)"));
+        assert!(result.output.ends_with("Finished `dev` profile [unoptimized + debuginfo] target(s) in 0.5s"));
+    }
+}
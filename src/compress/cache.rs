@@ -0,0 +1,182 @@
+//! Cache of full command output behind compressed results.
+//!
+//! Compression hides lines; the original is kept here for a while under a short
+//! ID so specific sections can be fetched on demand with `expand_output`.
+
+use regex::Regex;
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+/// How long original output stays expandable
+const OUTPUT_TTL: Duration = Duration::from_secs(30 * 60);
+
+/// Most outputs kept at once; the oldest is evicted first
+const MAX_ENTRIES: usize = 32;
+
+/// Original output of one command
+#[derive(Debug, Clone)]
+pub struct CachedOutput {
+    pub command: String,
+    pub output: String,
+    created: Instant,
+    seq: u64,
+}
+
+/// In-memory store of original outputs with a TTL
+#[derive(Debug)]
+pub struct OutputCache {
+    entries: HashMap<String, CachedOutput>,
+    next_id: u64,
+}
+
+impl OutputCache {
+    pub fn new() -> Self {
+        Self {
+            entries: HashMap::new(),
+            next_id: 1,
+        }
+    }
+
+    /// Store an output and return its ID
+    pub fn insert(&mut self, command: &str, output: String) -> String {
+        self.evict_expired();
+        if self.entries.len() >= MAX_ENTRIES {
+            if let Some(oldest) = self
+                .entries
+                .iter()
+                .min_by_key(|(_, entry)| entry.seq)
+                .map(|(id, _)| id.clone())
+            {
+                self.entries.remove(&oldest);
+            }
+        }
+
+        let seq = self.next_id;
+        self.next_id += 1;
+        let id = format!("out-{}", seq);
+        self.entries.insert(
+            id.clone(),
+            CachedOutput {
+                command: command.to_string(),
+                output,
+                created: Instant::now(),
+                seq,
+            },
+        );
+        id
+    }
+
+    /// Look up an output that hasn't expired
+    pub fn get(&mut self, id: &str) -> Option<&CachedOutput> {
+        self.evict_expired();
+        self.entries.get(id)
+    }
+
+    fn evict_expired(&mut self) {
+        self.entries.retain(|_, entry| entry.created.elapsed() < OUTPUT_TTL);
+    }
+}
+
+impl Default for OutputCache {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Select sections of a cached output: line ranges (1-based, inclusive) and/or lines
+/// matching `pattern` with `context` lines around them. With neither, returns the start.
+pub fn expand_output(
+    output: &str,
+    ranges: &[(usize, usize)],
+    pattern: Option<&Regex>,
+    context: usize,
+    max_lines: usize,
+) -> String {
+    let lines: Vec<&str> = output.lines().collect();
+    let mut selected = vec![false; lines.len()];
+
+    for &(start, end) in ranges {
+        for line in selected.iter_mut().take(end).skip(start.saturating_sub(1)) {
+            *line = true;
+        }
+    }
+    if let Some(pattern) = pattern {
+        for (i, line) in lines.iter().enumerate() {
+            if pattern.is_match(line) {
+                let from = i.saturating_sub(context);
+                let to = (i + context + 1).min(lines.len());
+                selected[from..to].iter_mut().for_each(|s| *s = true);
+            }
+        }
+    }
+    if ranges.is_empty() && pattern.is_none() {
+        selected.iter_mut().for_each(|s| *s = true);
+    }
+
+    let total_selected = selected.iter().filter(|s| **s).count();
+    if total_selected == 0 {
+        return format!("No matching lines ({} lines total)", lines.len());
+    }
+
+    let mut result_lines: Vec<String> = Vec::new();
+    let mut shown = 0;
+    let mut previous: Option<usize> = None;
+    for (i, line) in lines.iter().enumerate() {
+        if !selected[i] {
+            continue;
+        }
+        if shown == max_lines {
+            result_lines.push(format!(
+                "  ... +{} more selected lines (narrow with lines/pattern)",
+                total_selected - shown
+            ));
+            break;
+        }
+        // Mark gaps between non-adjacent sections
+        if previous.is_some_and(|p| p + 1 != i) {
+            result_lines.push("  ...".to_string());
+        }
+        result_lines.push(format!("{:>5}  {}", i + 1, line));
+        previous = Some(i);
+        shown += 1;
+    }
+
+    result_lines.join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_output_cache() {
+        let mut cache = OutputCache::new();
+        let first = cache.insert("cargo test", "a\nb".to_string());
+        for i in 0..MAX_ENTRIES {
+            cache.insert("echo", i.to_string());
+        }
+
+        // The oldest entry is evicted once the cache is full
+        assert!(cache.get(&first).is_none());
+        let last = format!("out-{}", MAX_ENTRIES + 1);
+        assert_eq!(cache.get(&last).unwrap().output, (MAX_ENTRIES - 1).to_string());
+    }
+
+    #[test]
+    fn test_expand_output() {
+        let output: String = (1..=100).map(|i| format!("line {}\n", i)).collect();
+
+        let expanded = expand_output(&output, &[(2, 3)], None, 0, 50);
+        assert_eq!(expanded, "    2  line 2\n    3  line 3");
+
+        let pattern = Regex::new(r"line 50$").unwrap();
+        let expanded = expand_output(&output, &[(1, 1)], Some(&pattern), 1, 50);
+        assert_eq!(expanded, "    1  line 1\n  ...\n   49  line 49\n   50  line 50\n   51  line 51");
+
+        let expanded = expand_output(&output, &[], None, 0, 10);
+        assert!(expanded.ends_with("   10  line 10\n  ... +90 more selected lines (narrow with lines/pattern)"));
+
+        let pattern = Regex::new("missing").unwrap();
+        assert_eq!(expand_output(&output, &[], Some(&pattern), 2, 10), "No matching lines (100 lines total)");
+    }
+}
@@ -0,0 +1,303 @@
+//! Coverage report compression - lcov, cargo-llvm-cov, coverage.py.
+//!
+//! Reduces reports to the overall line coverage, the worst-covered files, and
+//! uncovered lines only for files in the session's working set.
+
+use std::collections::BTreeMap;
+
+use super::{paths_match, CompressConfig, CompressResult};
+
+/// Compress a coverage report. Returns `None` when no report is recognized.
+pub fn compress_coverage(output: &str, config: &CompressConfig) -> Option<CompressResult> {
+    let files = if output.contains("end_of_record") {
+        parse_lcov(output)
+    } else {
+        parse_table(output)
+    };
+
+    if files.is_empty() {
+        return None;
+    }
+
+    let total: usize = files.iter().map(|f| f.total).sum();
+    let missed: usize = files.iter().map(|f| f.missed).sum();
+
+    let mut result_lines: Vec<String> = Vec::new();
+    result_lines.push(format!(
+        "🧪 Coverage: {:.1}% ({}/{} lines, {} files)",
+        percent(total - missed, total),
+        total - missed,
+        total,
+        files.len()
+    ));
+
+    let mut worst: Vec<&FileCoverage> = files.iter().filter(|f| f.missed > 0).collect();
+    worst.sort_by(|a, b| {
+        a.percent()
+            .partial_cmp(&b.percent())
+            .unwrap_or(std::cmp::Ordering::Equal)
+            .then(b.missed.cmp(&a.missed))
+    });
+
+    if !worst.is_empty() {
+        result_lines.push(String::new());
+        result_lines.push("Worst covered:".to_string());
+        for file in worst.iter().take(config.max_items_per_group) {
+            result_lines.push(format!(
+                "  {:>5.1}%  {} ({}/{} missed)",
+                file.percent(),
+                file.path,
+                file.missed,
+                file.total
+            ));
+        }
+        if worst.len() > config.max_items_per_group {
+            result_lines.push(format!(
+                "  ... +{} more files below 100%",
+                worst.len() - config.max_items_per_group
+            ));
+        }
+    }
+
+    let focused: Vec<&FileCoverage> = files
+        .iter()
+        .filter(|f| f.missed > 0 && !f.uncovered.is_empty())
+        .filter(|f| config.focus_files.iter().any(|p| paths_match(&f.path, p)))
+        .collect();
+
+    if !focused.is_empty() {
+        result_lines.push(String::new());
+        result_lines.push("Uncovered lines in working set:".to_string());
+        for file in focused {
+            result_lines.push(format!(
+                "  {} ({:.1}%): {}",
+                file.path,
+                file.percent(),
+                file.uncovered
+            ));
+        }
+    }
+
+    let compressed = result_lines.join("\n");
+    Some(CompressResult::new(output, compressed))
+}
+
+struct FileCoverage {
+    path: String,
+    total: usize,
+    missed: usize,
+    /// Uncovered line ranges ("12-15, 30"), when the report includes them
+    uncovered: String,
+}
+
+impl FileCoverage {
+    fn percent(&self) -> f64 {
+        percent(self.total - self.missed, self.total)
+    }
+}
+
+/// Parse an lcov tracefile (SF/DA/end_of_record)
+fn parse_lcov(output: &str) -> Vec<FileCoverage> {
+    let mut files = Vec::new();
+    let mut path: Option<&str> = None;
+    let mut lines: BTreeMap<u32, u64> = BTreeMap::new();
+
+    for line in output.lines() {
+        let line = line.trim();
+        if let Some(p) = line.strip_prefix("SF:") {
+            path = Some(p);
+            lines.clear();
+        } else if let Some(data) = line.strip_prefix("DA:") {
+            let mut parts = data.split(',');
+            if let (Some(Ok(number)), Some(Ok(hits))) = (
+                parts.next().map(|n| n.parse::<u32>()),
+                parts.next().map(|h| h.parse::<u64>()),
+            ) {
+                // Multiple records for one line (e.g. generics) count as covered if any hit
+                let entry = lines.entry(number).or_insert(0);
+                *entry = (*entry).max(hits);
+            }
+        } else if line == "end_of_record" {
+            if let Some(p) = path.take() {
+                let uncovered: Vec<u32> = lines
+                    .iter()
+                    .filter(|(_, hits)| **hits == 0)
+                    .map(|(n, _)| *n)
+                    .collect();
+                files.push(FileCoverage {
+                    path: p.to_string(),
+                    total: lines.len(),
+                    missed: uncovered.len(),
+                    uncovered: format_ranges(&uncovered),
+                });
+            }
+        }
+    }
+
+    files
+}
+
+/// Parse a tabular summary: coverage.py (`Stmts Miss ... Missing`) or
+/// cargo-llvm-cov (`Lines  Missed Lines`), plus llvm-cov's "Uncovered Lines:" section
+fn parse_table(output: &str) -> Vec<FileCoverage> {
+    let mut files: Vec<FileCoverage> = Vec::new();
+    // (total column, missed column, index of first "Missing" token)
+    let mut columns: Option<(usize, usize, Option<usize>)> = None;
+    let mut in_uncovered_section = false;
+
+    for line in output.lines() {
+        let trimmed = line.trim();
+
+        if trimmed.starts_with("Filename") || trimmed.starts_with("Name ") {
+            // Multi-word headers ("Missed Lines") are separated by two or more spaces
+            let headers: Vec<&str> = trimmed
+                .split("  ")
+                .map(|h| h.trim())
+                .filter(|h| !h.is_empty())
+                .collect();
+            let find = |name: &str| headers.iter().position(|h| *h == name);
+            columns = match (find("Lines"), find("Missed Lines"), find("Stmts"), find("Miss")) {
+                (Some(total), Some(missed), _, _) => Some((total, missed, None)),
+                (_, _, Some(total), Some(missed)) => Some((total, missed, find("Missing"))),
+                _ => None,
+            };
+            continue;
+        }
+
+        if trimmed == "Uncovered Lines:" {
+            in_uncovered_section = true;
+            continue;
+        }
+
+        if in_uncovered_section {
+            if let Some((path, ranges)) = trimmed.split_once(": ") {
+                if let Some(file) = files.iter_mut().find(|f| paths_match(&f.path, path)) {
+                    file.uncovered = ranges.to_string();
+                }
+            }
+            continue;
+        }
+
+        let Some((total_col, missed_col, missing_col)) = columns else {
+            continue;
+        };
+        let tokens: Vec<&str> = trimmed.split_whitespace().collect();
+        if tokens.is_empty() || tokens[0] == "TOTAL" || tokens[0].starts_with('-') {
+            continue;
+        }
+        let (Some(Ok(total)), Some(Ok(missed))) = (
+            tokens.get(total_col).map(|t| t.parse::<usize>()),
+            tokens.get(missed_col).map(|t| t.parse::<usize>()),
+        ) else {
+            continue;
+        };
+
+        files.push(FileCoverage {
+            path: tokens[0].to_string(),
+            total,
+            missed,
+            uncovered: missing_col
+                .and_then(|col| tokens.get(col..))
+                .map(|rest| rest.join(" "))
+                .unwrap_or_default(),
+        });
+    }
+
+    files
+}
+
+/// Collapse sorted line numbers into ranges: [1, 2, 3, 7] → "1-3, 7"
+fn format_ranges(lines: &[u32]) -> String {
+    let mut ranges: Vec<String> = Vec::new();
+    let mut iter = lines.iter().peekable();
+
+    while let Some(&start) = iter.next() {
+        let mut end = start;
+        while iter.peek().is_some_and(|&&next| next == end + 1) {
+            end = *iter.next().unwrap();
+        }
+        ranges.push(if start == end {
+            start.to_string()
+        } else {
+            format!("{}-{}", start, end)
+        });
+    }
+
+    ranges.join(", ")
+}
+
+fn percent(covered: usize, total: usize) -> f64 {
+    if total == 0 {
+        100.0
+    } else {
+        covered as f64 / total as f64 * 100.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_compress_lcov() {
+        let input = "TN:\nSF:/repo/src/lib.rs\nDA:1,5\nDA:2,0\nDA:3,0\nDA:4,0\nDA:7,0\nDA:8,1\nLF:6\nLH:2\nend_of_record\nSF:/repo/src/util.rs\nDA:1,1\nDA:2,1\nend_of_record\n";
+        let config = CompressConfig {
+            focus_files: vec!["src/lib.rs".to_string()],
+            ..Default::default()
+        };
+        let result = compress_coverage(input, &config).unwrap();
+
+        assert!(result.output.starts_with("🧪 Coverage: 50.0% (4/8 lines, 2 files)"));
+        assert!(result.output.contains("   33.3%  /repo/src/lib.rs (4/6 missed)"));
+        assert!(result
+            .output
+            .ends_with("Uncovered lines in working set:\n  /repo/src/lib.rs (33.3%): 2-4, 7"));
+        assert!(!result.output.contains("util.rs"));
+    }
+
+    #[test]
+    fn test_compress_coverage_py() {
+        let input = r#"Name                Stmts   Miss  Cover   Missing
+-------------------------------------------------
+app/__init__.py         2      0   100%
+app/main.py            40     10    75%   12-15, 30, 41-45
+app/legacy.py          20     18    10%   3-20
+-------------------------------------------------
+TOTAL                  62     28    55%
+"#;
+        let config = CompressConfig::default();
+        let result = compress_coverage(input, &config).unwrap();
+
+        assert!(result.output.starts_with("🧪 Coverage: 54.8% (34/62 lines, 3 files)"));
+        let legacy = result.output.find("app/legacy.py").unwrap();
+        let main = result.output.find("app/main.py").unwrap();
+        assert!(legacy < main);
+        // Nothing is in the working set, so no line details
+        assert!(!result.output.contains("12-15"));
+    }
+
+    #[test]
+    fn test_compress_llvm_cov() {
+        let input = r#"Filename                      Regions    Missed Regions     Cover   Functions  Missed Functions  Executed       Lines      Missed Lines     Cover    Branches   Missed Branches     Cover
+-----------------------------------------------------------------------------------------------------------------------------------------------------------------------------------
+/repo/src/lib.rs                   10                 2    80.00%           3                 0   100.00%          50                 5    90.00%           0                 0         -
+/repo/src/main.rs                   4                 4     0.00%           1                 1     0.00%          10                10     0.00%           0                 0         -
+-----------------------------------------------------------------------------------------------------------------------------------------------------------------------------------
+TOTAL                              14                 6    57.14%           4                 1    75.00%          60                15    75.00%           0                 0         -
+
+Uncovered Lines:
+/repo/src/lib.rs: 10, 12, 15-17
+/repo/src/main.rs: 1-10
+"#;
+        let config = CompressConfig {
+            focus_files: vec!["src/lib.rs".to_string()],
+            ..Default::default()
+        };
+        let result = compress_coverage(input, &config).unwrap();
+
+        assert!(result.output.starts_with("🧪 Coverage: 75.0% (45/60 lines, 2 files)"));
+        assert!(result.output.contains("    0.0%  /repo/src/main.rs (10/10 missed)"));
+        assert!(result.output.ends_with("  /repo/src/lib.rs (90.0%): 10, 12, 15-17"));
+        assert_eq!(format_ranges(&[1, 2, 3, 7, 9, 10]), "1-3, 7, 9-10");
+    }
+}
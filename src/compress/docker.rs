@@ -0,0 +1,370 @@
+//! Docker build output compression.
+//!
+//! Summarizes BuildKit (plain and tty) and legacy builder output per stage —
+//! cached vs executed steps and time spent — and keeps logs only for the failing step.
+
+use once_cell::sync::Lazy;
+use regex::Regex;
+use std::collections::HashMap;
+use std::process::Command;
+
+use super::{truncate_with_summary, CompressConfig, CompressResult};
+
+/// BuildKit plain: "#7 [builder 3/4] RUN npm ci"
+static PLAIN_STEP: Lazy<Regex> = Lazy::new(|| Regex::new(r"^#(\d+) \[([^\]]+)\] (.+)$").unwrap());
+
+/// BuildKit plain status/log line: "#7 DONE 12.3s", "#7 CACHED", "#7 0.512 npm WARN ..."
+static PLAIN_LINE: Lazy<Regex> = Lazy::new(|| Regex::new(r"^#(\d+) (.*)$").unwrap());
+
+/// BuildKit tty: "=> CACHED [builder 2/4] WORKDIR /app   0.0s"
+static TTY_STEP: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"^=> (CACHED |ERROR )?\[([^\]]+)\] (.+?)\s+([\d.]+)s$").unwrap()
+});
+
+/// Legacy builder: "Step 3/8 : RUN npm ci"
+static LEGACY_STEP: Lazy<Regex> = Lazy::new(|| Regex::new(r"^Step \d+/\d+ : (.+)$").unwrap());
+
+/// Compress `docker build` output. `image_size` (bytes) is included when known.
+pub fn compress_docker_build(
+    output: &str,
+    image_size: Option<u64>,
+    config: &CompressConfig,
+) -> CompressResult {
+    let lines: Vec<&str> = output.lines().collect();
+
+    if lines.is_empty() {
+        return CompressResult::new(output, String::new());
+    }
+
+    let build = parse_build(&lines);
+
+    let mut result_lines: Vec<String> = Vec::new();
+    let failed = build.steps.iter().find(|s| s.status == StepStatus::Failed);
+
+    match failed {
+        Some(step) => {
+            result_lines.push(format!("❌ Docker build failed at [{}] {}", step.label, step.instruction));
+        }
+        None if !build.errors.is_empty() => {
+            result_lines.push("❌ Docker build failed".to_string());
+        }
+        None => {
+            let mut header = "🐳 Docker build succeeded".to_string();
+            if let Some(image) = &build.image {
+                header.push_str(&format!(": {}", image));
+            }
+            if let Some(size) = image_size {
+                header.push_str(&format!(" ({})", format_bytes(size)));
+            }
+            result_lines.push(header);
+        }
+    }
+
+    // Per-stage summary, in build order
+    let mut stage_order: Vec<&str> = Vec::new();
+    let mut stages: HashMap<&str, StageSummary> = HashMap::new();
+    for step in &build.steps {
+        if !stages.contains_key(step.stage.as_str()) {
+            stage_order.push(step.stage.as_str());
+        }
+        let stage = stages.entry(step.stage.as_str()).or_default();
+        match step.status {
+            StepStatus::Cached => stage.cached += 1,
+            StepStatus::Done => stage.executed += 1,
+            StepStatus::Failed | StepStatus::Running => {}
+        }
+        stage.seconds += step.seconds;
+    }
+
+    for name in &stage_order {
+        let stage = &stages[name];
+        let mut line = format!("  {}: {} cached, {} executed", name, stage.cached, stage.executed);
+        if stage.seconds > 0.0 {
+            line.push_str(&format!(" ({:.1}s)", stage.seconds));
+        }
+        result_lines.push(line);
+    }
+
+    if let Some(step) = failed {
+        if !step.logs.is_empty() {
+            result_lines.push(String::new());
+            result_lines.push("Failing step log:".to_string());
+            let skip = step.logs.len().saturating_sub(config.max_items_per_group);
+            if skip > 0 {
+                result_lines.push(format!("  ... {} earlier lines", skip));
+            }
+            result_lines.extend(step.logs.iter().skip(skip).map(|l| format!("  {}", l)));
+        }
+    }
+
+    if !build.errors.is_empty() {
+        result_lines.push(String::new());
+        result_lines.extend(build.errors.iter().cloned());
+    }
+
+    let compressed = truncate_with_summary(&result_lines, config.max_lines);
+    CompressResult::new(output, compressed)
+}
+
+/// Image reference produced by the build (tag if named, otherwise the image ID)
+pub fn built_image(output: &str) -> Option<String> {
+    parse_build(&output.lines().collect::<Vec<_>>()).image
+}
+
+/// Look up an image's size in bytes with `docker image inspect`
+pub fn image_size(image: &str) -> Option<u64> {
+    let output = Command::new("docker")
+        .args(["image", "inspect", "--format", "{{.Size}}", image])
+        .output()
+        .ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    String::from_utf8_lossy(&output.stdout).trim().parse().ok()
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum StepStatus {
+    Running,
+    Cached,
+    Done,
+    Failed,
+}
+
+struct BuildStep {
+    stage: String,
+    label: String,
+    instruction: String,
+    status: StepStatus,
+    seconds: f64,
+    logs: Vec<String>,
+}
+
+#[derive(Default)]
+struct StageSummary {
+    cached: usize,
+    executed: usize,
+    seconds: f64,
+}
+
+#[derive(Default)]
+struct ParsedBuild {
+    steps: Vec<BuildStep>,
+    errors: Vec<String>,
+    image: Option<String>,
+}
+
+fn parse_build(lines: &[&str]) -> ParsedBuild {
+    let mut build = ParsedBuild::default();
+    // BuildKit step number → index into build.steps
+    let mut by_id: HashMap<String, usize> = HashMap::new();
+    let mut image_id: Option<String> = None;
+    let mut legacy = false;
+
+    for line in lines {
+        let trimmed = line.trim();
+
+        if let Some(caps) = PLAIN_STEP.captures(trimmed) {
+            if caps[2].starts_with("internal") {
+                continue;
+            }
+            by_id.insert(caps[1].to_string(), build.steps.len());
+            build.steps.push(BuildStep::new(&caps[2], &caps[3]));
+        } else if let Some(caps) = PLAIN_LINE.captures(trimmed) {
+            let rest = &caps[2];
+            if let Some(name) = rest.strip_prefix("naming to ") {
+                build.image = name.split_whitespace().next().map(|s| s.to_string());
+            } else if let Some(id) = rest.strip_prefix("writing image ") {
+                image_id = id.split_whitespace().next().map(|s| s.to_string());
+            }
+
+            let Some(step) = by_id.get(&caps[1]).map(|&i| &mut build.steps[i]) else {
+                continue;
+            };
+            if rest == "CACHED" {
+                step.status = StepStatus::Cached;
+            } else if let Some(secs) = rest.strip_prefix("DONE ") {
+                step.status = StepStatus::Done;
+                step.seconds = secs.trim_end_matches('s').parse().unwrap_or(0.0);
+            } else if let Some(message) = rest.strip_prefix("ERROR") {
+                step.status = StepStatus::Failed;
+                step.logs.push(format!("ERROR{}", message));
+            } else {
+                // Drop the elapsed-time prefix from log lines: "0.512 npm WARN ..."
+                let log = rest
+                    .split_once(' ')
+                    .filter(|(t, _)| t.parse::<f64>().is_ok())
+                    .map(|(_, l)| l)
+                    .unwrap_or(rest);
+                step.logs.push(log.to_string());
+            }
+        } else if let Some(caps) = TTY_STEP.captures(trimmed) {
+            let mut step = BuildStep::new(&caps[2], &caps[3]);
+            step.seconds = caps[4].parse().unwrap_or(0.0);
+            step.status = match caps.get(1).map(|m| m.as_str().trim()) {
+                Some("CACHED") => StepStatus::Cached,
+                Some("ERROR") => StepStatus::Failed,
+                _ => StepStatus::Done,
+            };
+            if !caps[2].starts_with("internal") {
+                build.steps.push(step);
+            }
+        } else if let Some(caps) = LEGACY_STEP.captures(trimmed) {
+            let mut step = BuildStep::new("main", &caps[1]);
+            step.status = StepStatus::Done;
+            build.steps.push(step);
+            legacy = true;
+        } else if trimmed == "---> Using cache" {
+            if let Some(step) = build.steps.last_mut() {
+                step.status = StepStatus::Cached;
+            }
+        } else if let Some(tag) = trimmed.strip_prefix("Successfully tagged ") {
+            build.image = Some(tag.to_string());
+        } else if let Some(id) = trimmed.strip_prefix("Successfully built ") {
+            image_id = Some(id.to_string());
+        } else if trimmed.starts_with("ERROR") || trimmed.starts_with("error:") {
+            build.errors.push(trimmed.to_string());
+        } else if let Some(step) = build.steps.last_mut().filter(|_| legacy) {
+            // Legacy builder: output of the running step
+            if !trimmed.is_empty() && !trimmed.starts_with("--->") {
+                step.logs.push(trimmed.to_string());
+            }
+        }
+    }
+
+    // Legacy builder reports a failing step only via "The command ... returned a non-zero code"
+    if let Some(step) = build.steps.last_mut().filter(|_| legacy) {
+        if build.errors.is_empty() {
+            if let Some(log) = step.logs.iter().find(|l| l.contains("returned a non-zero code")) {
+                build.errors.push(log.clone());
+                step.status = StepStatus::Failed;
+            }
+        }
+    }
+
+    if build.image.is_none() {
+        build.image = image_id;
+    }
+    build
+}
+
+impl BuildStep {
+    /// `label` is "builder 2/4" (named stage) or "2/4" (single-stage build)
+    fn new(label: &str, instruction: &str) -> Self {
+        let stage = match label.rsplit_once(' ') {
+            Some((stage, position)) if position.contains('/') => stage,
+            _ => "main",
+        };
+        Self {
+            stage: stage.to_string(),
+            label: label.to_string(),
+            instruction: instruction.to_string(),
+            status: StepStatus::Running,
+            seconds: 0.0,
+            logs: Vec::new(),
+        }
+    }
+}
+
+pub(super) fn format_bytes(bytes: u64) -> String {
+    const UNITS: [&str; 4] = ["B", "KB", "MB", "GB"];
+    let mut size = bytes as f64;
+    let mut unit = 0;
+    while size >= 1000.0 && unit < UNITS.len() - 1 {
+        size /= 1000.0;
+        unit += 1;
+    }
+    if unit == 0 {
+        format!("{} B", bytes)
+    } else {
+        format!("{:.1} {}", size, UNITS[unit])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PLAIN_BUILD: &str = r#"#0 building with "default" instance using docker driver
+
+#1 [internal] load build definition from Dockerfile
+#1 transferring dockerfile: 412B done
+#1 DONE 0.0s
+
+#4 [builder 1/4] FROM docker.io/library/node:18
+#4 CACHED
+
+#5 [builder 2/4] WORKDIR /app
+#5 CACHED
+
+#6 [builder 3/4] RUN npm ci
+#6 0.512 npm WARN deprecated inflight@1.0.6
+#6 9.871 added 120 packages in 9s
+#6 DONE 10.2s
+
+#7 [builder 4/4] RUN npm run build
+#7 DONE 4.3s
+
+#8 [stage-1 1/2] FROM docker.io/library/nginx:alpine
+#8 CACHED
+
+#9 [stage-1 2/2] COPY --from=builder /app/dist /usr/share/nginx/html
+#9 DONE 0.1s
+
+#10 exporting to image
+#10 exporting layers 0.2s done
+#10 writing image sha256:abc123 done
+#10 naming to docker.io/library/myapp:latest done
+#10 DONE 0.3s
+"#;
+
+    #[test]
+    fn test_compress_buildkit_success() {
+        let config = CompressConfig::default();
+        let result = compress_docker_build(PLAIN_BUILD, Some(45_300_000), &config);
+
+        assert!(result
+            .output
+            .starts_with("🐳 Docker build succeeded: docker.io/library/myapp:latest (45.3 MB)"));
+        assert!(result.output.contains("  builder: 2 cached, 2 executed (14.5s)"));
+        assert!(result.output.contains("  stage-1: 1 cached, 1 executed (0.1s)"));
+        assert!(!result.output.contains("npm WARN"));
+        assert_eq!(built_image(PLAIN_BUILD).as_deref(), Some("docker.io/library/myapp:latest"));
+    }
+
+    #[test]
+    fn test_compress_buildkit_failure() {
+        let input = r#"#5 [2/3] COPY package.json .
+#5 CACHED
+
+#6 [3/3] RUN npm test
+#6 0.401 > jest
+#6 2.113 FAIL src/app.test.js
+#6 2.200 Tests: 1 failed, 3 passed
+#6 ERROR: process "/bin/sh -c npm test" did not complete successfully: exit code: 1
+------
+ > [3/3] RUN npm test:
+------
+ERROR: failed to solve: process "/bin/sh -c npm test" did not complete successfully: exit code: 1
+"#;
+        let config = CompressConfig::default();
+        let result = compress_docker_build(input, None, &config);
+
+        assert!(result.output.starts_with("❌ Docker build failed at [3/3] RUN npm test"));
+        assert!(result.output.contains("  main: 1 cached, 0 executed"));
+        assert!(result.output.contains("  FAIL src/app.test.js"));
+        assert!(result.output.contains("ERROR: failed to solve"));
+    }
+
+    #[test]
+    fn test_compress_legacy_build() {
+        let input = "Step 1/3 : FROM alpine\n ---> abc123\nStep 2/3 : RUN apk add curl\n ---> Using cache\n ---> def456\nStep 3/3 : COPY . .\n ---> 789abc\nSuccessfully built 789abc\nSuccessfully tagged myapp:dev\n";
+        let config = CompressConfig::default();
+        let result = compress_docker_build(input, None, &config);
+
+        assert_eq!(
+            result.output,
+            "🐳 Docker build succeeded: myapp:dev\n  main: 1 cached, 2 executed"
+        );
+    }
+}
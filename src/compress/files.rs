@@ -1,10 +1,15 @@
 //! File listing compression - groups files by directory, truncates paths.
 //!
-//! Achieves ~80% reduction on `ls` and `tree` output.
+//! Achieves ~80% reduction on `ls` and `tree` output. Recursive listings are
+//! rolled up into per-directory file counts and sizes.
 
 use std::collections::HashMap;
 use std::path::Path;
 
+use once_cell::sync::Lazy;
+use regex::Regex;
+
+use super::docker::format_bytes;
 use super::{CompressConfig, CompressResult, truncate_with_summary};
 
 /// Compress a file listing by grouping files by directory
@@ -71,7 +76,8 @@ pub fn compress_file_list(output: &str, config: &CompressConfig) -> CompressResu
     CompressResult::new(output, compressed)
 }
 
-/// Compress tree output by collapsing deep nesting
+/// Compress tree output into per-directory file counts and sizes, collapsing deep
+/// or very large directories into one line
 pub fn compress_tree(output: &str, config: &CompressConfig) -> CompressResult {
     let lines: Vec<&str> = output.lines().collect();
 
@@ -79,38 +85,177 @@ pub fn compress_tree(output: &str, config: &CompressConfig) -> CompressResult {
         return CompressResult::new(output, String::new());
     }
 
-    let mut result_lines: Vec<String> = Vec::new();
-    let mut current_depth = 0;
-    let mut collapsed_count = 0;
-
+    // (depth, name, size) per entry; depth 0 is the root line
+    let mut entries: Vec<(usize, String, Option<u64>)> = Vec::new();
     for line in &lines {
-        // Calculate depth from leading whitespace/tree chars
-        let depth = line.chars().take_while(|c| !c.is_alphanumeric() && *c != '.').count() / 2;
+        if line.trim().is_empty() || TREE_SUMMARY.is_match(line.trim()) {
+            continue;
+        }
+        let prefix = line.chars().take_while(|c| TREE_PREFIX_CHARS.contains(c)).count();
+        let rest: String = line.chars().skip(prefix).collect();
+        let (size, name) = match TREE_SIZE.captures(&rest) {
+            Some(caps) => (parse_size(caps[1].split_whitespace().last().unwrap_or("")), caps[2].to_string()),
+            None => (None, rest),
+        };
+        entries.push((prefix / 4, name, size));
+    }
 
-        if depth > 3 {
-            // Collapse deep nesting
-            collapsed_count += 1;
+    let root_name = match entries.first() {
+        Some((0, name, _)) => name.trim_end_matches('/').to_string(),
+        _ => ".".to_string(),
+    };
+    let mut root = DirNode::new(&root_name);
+    let mut stack: Vec<String> = Vec::new();
+    for (i, (depth, name, size)) in entries.iter().enumerate() {
+        if *depth == 0 {
             continue;
         }
+        // An entry is a directory if the next one is nested under it (or `tree -F` marked it)
+        let is_dir = name.ends_with('/') || entries.get(i + 1).is_some_and(|next| next.0 > *depth);
+        stack.truncate(depth - 1);
+        stack.push(name.trim_end_matches('/').to_string());
+        root.insert(&stack, *size, is_dir);
+    }
+
+    let compressed = truncate_with_summary(&render_dir_tree(&root, config), config.max_lines);
+    CompressResult::new(output, compressed)
+}
 
-        if collapsed_count > 0 && depth <= current_depth {
-            result_lines.push(format!("{}... ({} items collapsed)", "  ".repeat(current_depth + 1), collapsed_count));
-            collapsed_count = 0;
+/// Directories nested deeper than this are shown as one summary line
+const MAX_TREE_DEPTH: usize = 3;
+
+/// Directories with more files than this (recursively) are shown as one summary line
+const COLLAPSE_FILES: usize = 200;
+
+/// Box-drawing and ASCII characters `tree` uses for indentation
+const TREE_PREFIX_CHARS: [char; 9] = ['│', '├', '└', '─', ' ', '|', '`', '-', '\u{a0}'];
+
+/// "[4.0K]  name", "[       4096]  name", "[drwxr-xr-x 4.0K]  name"
+static TREE_SIZE: Lazy<Regex> = Lazy::new(|| Regex::new(r"^\[([^\]]*)\]\s+(.+)$").unwrap());
+
+/// "12 directories, 40 files"
+static TREE_SUMMARY: Lazy<Regex> = Lazy::new(|| Regex::new(r"^\d+ director(?:y|ies)(?:, \d+ files?)?$").unwrap());
+
+/// Parse "4096" or human-readable "4.0K" (tree/ls -h use 1024-based units)
+fn parse_size(size: &str) -> Option<u64> {
+    let (number, multiplier) = match size.chars().last()? {
+        'K' => (&size[..size.len() - 1], 1u64 << 10),
+        'M' => (&size[..size.len() - 1], 1 << 20),
+        'G' => (&size[..size.len() - 1], 1 << 30),
+        'T' => (&size[..size.len() - 1], 1 << 40),
+        _ => (size, 1),
+    };
+    let value: f64 = number.parse().ok()?;
+    Some((value * multiplier as f64) as u64)
+}
+
+/// A directory in a parsed listing, with its files and subdirectories
+struct DirNode {
+    name: String,
+    dirs: Vec<DirNode>,
+    files: Vec<(String, Option<u64>)>,
+}
+
+impl DirNode {
+    fn new(name: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            dirs: Vec::new(),
+            files: Vec::new(),
         }
+    }
 
-        current_depth = depth;
-        result_lines.push(line.to_string());
+    /// Add an entry at `path` (relative to this directory), creating parent directories
+    fn insert(&mut self, path: &[String], size: Option<u64>, is_dir: bool) {
+        let Some((first, rest)) = path.split_first() else {
+            return;
+        };
+        if rest.is_empty() && !is_dir {
+            self.files.push((first.clone(), size));
+            return;
+        }
+        let index = match self.dirs.iter().position(|d| d.name == *first) {
+            Some(index) => index,
+            None => {
+                self.dirs.push(DirNode::new(first));
+                self.dirs.len() - 1
+            }
+        };
+        self.dirs[index].insert(rest, size, is_dir);
     }
 
-    if collapsed_count > 0 {
-        result_lines.push(format!("... ({} items collapsed)", collapsed_count));
+    fn total_files(&self) -> usize {
+        self.files.len() + self.dirs.iter().map(|d| d.total_files()).sum::<usize>()
     }
 
-    let compressed = truncate_with_summary(&result_lines, config.max_lines);
-    CompressResult::new(output, compressed)
+    fn total_dirs(&self) -> usize {
+        self.dirs.len() + self.dirs.iter().map(|d| d.total_dirs()).sum::<usize>()
+    }
+
+    /// Total size of all files, if the listing included sizes
+    fn total_size(&self) -> Option<u64> {
+        let sizes = self
+            .files
+            .iter()
+            .map(|(_, size)| *size)
+            .chain(self.dirs.iter().map(|d| d.total_size()));
+        sizes.fold(None, |total, size| match (total, size) {
+            (None, None) => None,
+            (total, size) => Some(total.unwrap_or(0) + size.unwrap_or(0)),
+        })
+    }
+
+    /// "12 files, 4.1 MB"
+    fn summary(&self) -> String {
+        match self.total_size() {
+            Some(size) => format!("{} files, {}", self.total_files(), format_bytes(size)),
+            None => format!("{} files", self.total_files()),
+        }
+    }
+}
+
+/// Render a directory tree with per-directory totals
+fn render_dir_tree(root: &DirNode, config: &CompressConfig) -> Vec<String> {
+    let mut result_lines = vec![format!(
+        "📁 {}/ ({} in {} dirs)",
+        root.name,
+        root.summary(),
+        root.total_dirs()
+    )];
+    render_dir_contents(root, 1, config, &mut result_lines);
+    result_lines
+}
+
+fn render_dir_contents(dir: &DirNode, depth: usize, config: &CompressConfig, result_lines: &mut Vec<String>) {
+    let indent = "  ".repeat(depth);
+
+    for sub in &dir.dirs {
+        if depth >= MAX_TREE_DEPTH || sub.total_files() > COLLAPSE_FILES {
+            result_lines.push(format!("{}{}/ ({}, collapsed)", indent, sub.name, sub.summary()));
+        } else {
+            result_lines.push(format!("{}{}/ ({})", indent, sub.name, sub.summary()));
+            render_dir_contents(sub, depth + 1, config, result_lines);
+        }
+    }
+
+    for (name, size) in dir.files.iter().take(config.max_items_per_group) {
+        match size {
+            Some(size) => result_lines.push(format!("{}{} ({})", indent, name, format_bytes(*size))),
+            None => result_lines.push(format!("{}{}", indent, name)),
+        }
+    }
+    if dir.files.len() > config.max_items_per_group {
+        let hidden = &dir.files[config.max_items_per_group..];
+        let hidden_size: Option<u64> = hidden.iter().filter_map(|(_, size)| *size).reduce(|a, b| a + b);
+        match hidden_size {
+            Some(size) => result_lines.push(format!("{}... +{} more files ({})", indent, hidden.len(), format_bytes(size))),
+            None => result_lines.push(format!("{}... +{} more files", indent, hidden.len())),
+        }
+    }
 }
 
-/// Compress directory listing with file type grouping
+/// Compress directory listing with file type grouping. Long listings (`ls -l`) add
+/// sizes; recursive listings (`ls -R`) are rolled up per directory like `tree`.
 pub fn compress_ls(output: &str, config: &CompressConfig) -> CompressResult {
     let lines: Vec<&str> = output.lines().filter(|l| !l.trim().is_empty()).collect();
 
@@ -118,22 +263,29 @@ pub fn compress_ls(output: &str, config: &CompressConfig) -> CompressResult {
         return CompressResult::new(output, String::new());
     }
 
+    if LS_SECTION.is_match(lines[0]) {
+        return compress_ls_recursive(output, &lines, config);
+    }
+
     // Group by extension
-    let mut by_ext: HashMap<String, Vec<&str>> = HashMap::new();
+    let mut by_ext: HashMap<String, Vec<(&str, Option<u64>)>> = HashMap::new();
     let mut dirs: Vec<&str> = Vec::new();
 
     for line in &lines {
-        let name = line.trim();
+        if LS_TOTAL.is_match(line) {
+            continue;
+        }
+        let (name, size, is_dir) = parse_ls_entry(line);
 
-        if name.ends_with('/') {
+        if is_dir {
             dirs.push(name);
         } else if let Some(ext) = Path::new(name).extension() {
             by_ext
                 .entry(ext.to_string_lossy().to_string())
                 .or_default()
-                .push(name);
+                .push((name, size));
         } else {
-            by_ext.entry("(no ext)".to_string()).or_default().push(name);
+            by_ext.entry("(no ext)".to_string()).or_default().push((name, size));
         }
     }
 
@@ -156,9 +308,12 @@ pub fn compress_ls(output: &str, config: &CompressConfig) -> CompressResult {
 
     for ext in exts {
         let files = &by_ext[ext];
-        result_lines.push(format!("📄 .{} files ({})", ext, files.len()));
+        match files.iter().filter_map(|(_, size)| *size).reduce(|a, b| a + b) {
+            Some(size) => result_lines.push(format!("📄 .{} files ({}, {})", ext, files.len(), format_bytes(size))),
+            None => result_lines.push(format!("📄 .{} files ({})", ext, files.len())),
+        }
 
-        for f in files.iter().take(config.max_items_per_group) {
+        for (f, _) in files.iter().take(config.max_items_per_group) {
             result_lines.push(format!("  {}", f));
         }
         if files.len() > config.max_items_per_group {
@@ -170,6 +325,71 @@ pub fn compress_ls(output: &str, config: &CompressConfig) -> CompressResult {
     CompressResult::new(output, compressed)
 }
 
+/// `ls -R` output: sections headed by "dir:" lines
+fn compress_ls_recursive(output: &str, lines: &[&str], config: &CompressConfig) -> CompressResult {
+    let sections: Vec<&str> = lines
+        .iter()
+        .filter_map(|l| LS_SECTION.captures(l).map(|c| c.get(1).map_or("", |m| m.as_str())))
+        .collect();
+    let root_path = sections.first().copied().unwrap_or(".");
+    let relative = |dir: &str| -> Vec<String> {
+        dir.strip_prefix(root_path)
+            .unwrap_or(dir)
+            .split('/')
+            .filter(|c| !c.is_empty())
+            .map(String::from)
+            .collect()
+    };
+
+    let mut root = DirNode::new(root_path.trim_end_matches('/'));
+    let mut current: Vec<String> = Vec::new();
+    let mut current_dir = root_path;
+    for line in lines {
+        if let Some(caps) = LS_SECTION.captures(line) {
+            current_dir = caps.get(1).map_or("", |m| m.as_str());
+            current = relative(current_dir);
+            root.insert(&current, None, true);
+            continue;
+        }
+        if LS_TOTAL.is_match(line) {
+            continue;
+        }
+        let (name, size, is_dir) = parse_ls_entry(line);
+        let is_dir = is_dir || sections.contains(&format!("{}/{}", current_dir.trim_end_matches('/'), name).as_str());
+        let mut path = current.clone();
+        path.push(name.to_string());
+        root.insert(&path, size, is_dir);
+    }
+
+    let compressed = truncate_with_summary(&render_dir_tree(&root, config), config.max_lines);
+    CompressResult::new(output, compressed)
+}
+
+/// "./src:" section header in `ls -R` output
+static LS_SECTION: Lazy<Regex> = Lazy::new(|| Regex::new(r"^(\S.*):$").unwrap());
+
+/// "total 48" line in `ls -l` output
+static LS_TOTAL: Lazy<Regex> = Lazy::new(|| Regex::new(r"^total \d+(?:\.\d+)?[KMGT]?$").unwrap());
+
+/// "-rw-r--r--  1 user group 4096 Jan  1 12:00 name" (`ls -l`, optionally `-h`)
+static LS_LONG: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"^([dlcbps-])[rwxsStT-]{9}[.@+]?\s+\d+\s+\S+\s+\S+\s+([\d.]+[KMGT]?)\s+\w+\s+\d+\s+[\d:]+\s+(.+)$").unwrap()
+});
+
+/// Name, size, and whether it's a directory, from a short or long `ls` line
+fn parse_ls_entry(line: &str) -> (&str, Option<u64>, bool) {
+    match LS_LONG.captures(line) {
+        Some(caps) => {
+            let name = caps.get(3).map_or("", |m| m.as_str());
+            (name, parse_size(&caps[2]), &caps[1] == "d")
+        }
+        None => {
+            let name = line.trim();
+            (name, None, name.ends_with('/'))
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -207,4 +427,40 @@ test.rs"#;
         assert!(result.output.contains(".toml files"));
         assert!(result.output.contains(".md files"));
     }
+
+    #[test]
+    fn test_compress_tree_size_rollups() {
+        let mut input = String::from(".\n├── [1.0K]  Cargo.toml\n├── [4.0K]  src\n│   ├── [2.0K]  lib.rs\n│   └── [4.0K]  mcp\n│       └── [ 512]  tools.rs\n└── [4.0K]  target\n");
+        for i in 0..250 {
+            input.push_str(&format!("    ├── [1.0K]  dep{}.rlib\n", i));
+        }
+        input.push_str("\n3 directories, 253 files\n");
+
+        let config = CompressConfig::default();
+        let result = compress_tree(&input, &config);
+
+        assert_eq!(
+            result.output,
+            "📁 ./ (253 files, 259.6 KB in 3 dirs)
+  src/ (2 files, 2.6 KB)
+    mcp/ (1 files, 512 B)
+      tools.rs (512 B)
+    lib.rs (2.0 KB)
+  target/ (250 files, 256.0 KB, collapsed)
+  Cargo.toml (1.0 KB)"
+        );
+        assert!(result.reduction_percent() > 90.0);
+    }
+
+    #[test]
+    fn test_compress_ls_recursive_and_long() {
+        let input = ".:\nREADME.md\nsrc\n\n./src:\nlib.rs\nmain.rs\n";
+        let config = CompressConfig::default();
+        let result = compress_ls(input, &config);
+        assert_eq!(result.output, "📁 ./ (3 files in 1 dirs)\n  src/ (2 files)\n    lib.rs\n    main.rs\n  README.md");
+
+        let input = "total 16\ndrwxr-xr-x 2 dev dev 4096 Jan  1 12:00 src\n-rw-r--r-- 1 dev dev 1500 Jan  1 12:00 main.rs\n-rw-r--r-- 1 dev dev 2.0K Jan  1 12:00 lib.rs\n";
+        let result = compress_ls(input, &config);
+        assert_eq!(result.output, "📁 Directories (1)\n  src\n📄 .rs files (2, 3.5 KB)\n  main.rs\n  lib.rs");
+    }
 }
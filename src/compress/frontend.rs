@@ -0,0 +1,341 @@
+//! Frontend build output compression - tsc, webpack, vite.
+//!
+//! Drops repeated errors, then keeps error counts by file with the first error of each,
+//! counts per tsc error code, a bundle size summary, and the final status line;
+//! module/progress lines are dropped.
+
+use once_cell::sync::Lazy;
+use regex::Regex;
+use std::collections::{HashMap, HashSet};
+
+use super::{CompressConfig, CompressResult};
+
+/// Largest assets listed in the bundle summary
+const MAX_ASSETS: usize = 5;
+
+/// ANSI color codes (tsc --pretty, vite)
+static ANSI: Lazy<Regex> = Lazy::new(|| Regex::new(r"\x1b\[[0-9;]*m").unwrap());
+
+/// tsc: "src/app.ts(10,5): error TS2322: msg" or "src/app.ts:10:5 - error TS2322: msg"
+static TSC_ERROR: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"^(.+?)(?:\((\d+),\d+\)|:(\d+):\d+)\s*[:-] error (TS\d+): (.+)$").unwrap()
+});
+
+/// webpack: "ERROR in ./src/index.js 5:0-20" / "ERROR in src/app.ts:3:7"
+static WEBPACK_ERROR: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"^ERROR in (\S+?)(?::(\d+):\d+)?(?: (\d+):\S+)?$").unwrap());
+
+/// vite: "dist/assets/index-abc.js   143.21 kB │ gzip: 46.10 kB"
+static VITE_ASSET: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"^(\S+/\S+)\s{2,}([\d.,]+ [kKMG]?i?B)(?:\s+│ gzip:\s+([\d.,]+ [kKMG]?i?B))?").unwrap()
+});
+
+/// webpack: "asset main.js 1.2 MiB [emitted] (name: main)"
+static WEBPACK_ASSET: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"^asset (\S+) ([\d.]+ (?:[KMG]i?B|bytes))").unwrap());
+
+/// Compress tsc/webpack/vite output. Returns `None` when nothing recognizable is found.
+pub fn compress_frontend_build(output: &str, config: &CompressConfig) -> Option<CompressResult> {
+    let clean = ANSI.replace_all(output, "");
+    let lines: Vec<&str> = clean.lines().collect();
+
+    let mut errors: Vec<BuildError> = Vec::new();
+    let mut assets: Vec<Asset> = Vec::new();
+    let mut warnings: HashSet<&str> = HashSet::new();
+    let mut status: Option<&str> = None;
+
+    for (i, line) in lines.iter().enumerate() {
+        let trimmed = line.trim();
+
+        if let Some(caps) = TSC_ERROR.captures(trimmed) {
+            errors.push(BuildError {
+                file: caps[1].to_string(),
+                line: caps.get(2).or(caps.get(3)).map(|m| m.as_str().to_string()),
+                message: format!("{} {}", &caps[4], &caps[5]),
+                code: Some(caps[4].to_string()),
+            });
+        } else if let Some(caps) = WEBPACK_ERROR.captures(trimmed) {
+            // The message is on the next non-empty line
+            let message = lines[i + 1..]
+                .iter()
+                .map(|l| l.trim())
+                .find(|l| !l.is_empty())
+                .unwrap_or("");
+            errors.push(BuildError {
+                file: caps[1].to_string(),
+                line: caps.get(2).or(caps.get(3)).map(|m| m.as_str().to_string()),
+                message: message.to_string(),
+                code: None,
+            });
+        } else if trimmed == "error during build:" {
+            // vite: "[vite:esbuild] Transform failed ... file: /app/src/main.ts:3:7"
+            let message = lines.get(i + 1).map(|l| l.trim()).unwrap_or("");
+            let file = lines[i + 1..]
+                .iter()
+                .take(5)
+                .find_map(|l| l.trim().strip_prefix("file: "))
+                .unwrap_or("(build)");
+            let (file, line) = split_location(file);
+            errors.push(BuildError {
+                file: file.to_string(),
+                line,
+                message: message.to_string(),
+                code: None,
+            });
+        } else if trimmed.starts_with("WARNING in") || trimmed.starts_with("(!)") {
+            // Multi-compiler webpack configs repeat the same warning once per compiler
+            warnings.insert(trimmed);
+        } else if let Some(caps) = WEBPACK_ASSET.captures(trimmed) {
+            assets.push(Asset::new(&caps[1], &caps[2], None));
+        } else if let Some(caps) = VITE_ASSET.captures(trimmed) {
+            assets.push(Asset::new(&caps[1], &caps[2], caps.get(3).map(|m| m.as_str())));
+        } else if is_status_line(trimmed) {
+            status = Some(trimmed);
+        }
+    }
+
+    if errors.is_empty() && assets.is_empty() && status.is_none() {
+        return None;
+    }
+
+    let mut result_lines: Vec<String> = Vec::new();
+
+    if !errors.is_empty() {
+        // Each error once: `tsc -b` and multi-compiler webpack report shared files repeatedly
+        let mut seen = HashSet::new();
+        let unique: Vec<&BuildError> = errors
+            .iter()
+            .filter(|e| seen.insert((e.file.as_str(), e.line.as_deref(), e.message.as_str())))
+            .collect();
+        let duplicates = errors.len() - unique.len();
+
+        // Group by file, most errors first
+        let mut by_file: HashMap<&str, Vec<&BuildError>> = HashMap::new();
+        for error in &unique {
+            by_file.entry(error.file.as_str()).or_default().push(*error);
+        }
+        let mut files: Vec<_> = by_file.keys().copied().collect();
+        files.sort_by(|a, b| by_file[b].len().cmp(&by_file[a].len()).then(a.cmp(b)));
+
+        let mut header = format!("❌ {} errors in {} files", unique.len(), files.len());
+        if duplicates > 0 {
+            header.push_str(&format!(" ({} duplicates dropped)", duplicates));
+        }
+        result_lines.push(header);
+        for file in files.iter().take(config.max_items_per_group) {
+            let first = by_file[file][0];
+            let location = match &first.line {
+                Some(line) => format!("{}:{}", file, line),
+                None => file.to_string(),
+            };
+            result_lines.push(format!("  {} ({}): {}", location, by_file[file].len(), first.message));
+        }
+        if files.len() > config.max_items_per_group {
+            result_lines.push(format!("  ... +{} more files", files.len() - config.max_items_per_group));
+        }
+
+        // tsc error codes across all files, most frequent first
+        let mut by_code: HashMap<&str, usize> = HashMap::new();
+        for code in unique.iter().filter_map(|e| e.code.as_deref()) {
+            *by_code.entry(code).or_default() += 1;
+        }
+        if !by_code.is_empty() {
+            let mut codes: Vec<_> = by_code.into_iter().collect();
+            codes.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(b.0)));
+            let mut listed: Vec<String> = codes
+                .iter()
+                .take(config.max_items_per_group)
+                .map(|(code, count)| format!("{} ({})", code, count))
+                .collect();
+            if codes.len() > config.max_items_per_group {
+                listed.push(format!("+{} more", codes.len() - config.max_items_per_group));
+            }
+            result_lines.push(format!("  Codes: {}", listed.join(", ")));
+        }
+    }
+
+    if !warnings.is_empty() {
+        result_lines.push(format!("⚠️ {} warnings", warnings.len()));
+    }
+
+    if !assets.is_empty() {
+        let total: f64 = assets.iter().map(|a| a.bytes).sum();
+        result_lines.push(format!("📦 {} assets, {} total", assets.len(), format_size(total)));
+
+        assets.sort_by(|a, b| b.bytes.partial_cmp(&a.bytes).unwrap_or(std::cmp::Ordering::Equal));
+        for asset in assets.iter().take(MAX_ASSETS) {
+            let gzip = asset
+                .gzip
+                .as_ref()
+                .map(|g| format!(" (gzip {})", g))
+                .unwrap_or_default();
+            result_lines.push(format!("  {} {}{}", asset.name, asset.size, gzip));
+        }
+        if assets.len() > MAX_ASSETS {
+            result_lines.push(format!("  ... +{} smaller assets", assets.len() - MAX_ASSETS));
+        }
+    }
+
+    if let Some(status) = status {
+        result_lines.push(status.to_string());
+    }
+
+    let compressed = result_lines.join("\n");
+    Some(CompressResult::new(output, compressed))
+}
+
+struct BuildError {
+    file: String,
+    line: Option<String>,
+    message: String,
+    /// tsc error code ("TS2322")
+    code: Option<String>,
+}
+
+struct Asset {
+    name: String,
+    size: String,
+    gzip: Option<String>,
+    bytes: f64,
+}
+
+impl Asset {
+    fn new(name: &str, size: &str, gzip: Option<&str>) -> Self {
+        Self {
+            name: name.to_string(),
+            size: size.to_string(),
+            gzip: gzip.map(|g| g.to_string()),
+            bytes: parse_size(size),
+        }
+    }
+}
+
+/// Final status lines printed by each tool
+fn is_status_line(line: &str) -> bool {
+    line.starts_with("✓ built in")
+        || (line.starts_with("Found ") && line.contains(" error"))
+        || line.contains("compiled successfully")
+        || line.contains("compiled with ")
+        || line.starts_with("Build failed")
+        || line.starts_with("✗ Build failed")
+}
+
+/// Split "/app/src/main.ts:3:7" into ("/app/src/main.ts", Some("3"))
+fn split_location(location: &str) -> (&str, Option<String>) {
+    let mut parts = location.splitn(3, ':');
+    let file = parts.next().unwrap_or(location);
+    let line = parts.next().filter(|l| l.chars().all(|c| c.is_ascii_digit()));
+    (file, line.map(|l| l.to_string()))
+}
+
+/// Parse "143.21 kB", "1.2 MiB", "300 bytes" into bytes
+fn parse_size(size: &str) -> f64 {
+    let mut parts = size.split_whitespace();
+    let value: f64 = parts
+        .next()
+        .and_then(|v| v.replace(',', "").parse().ok())
+        .unwrap_or(0.0);
+    let multiplier = match parts.next().unwrap_or("B") {
+        "kB" | "KB" => 1e3,
+        "KiB" => 1024.0,
+        "MB" => 1e6,
+        "MiB" => 1024.0 * 1024.0,
+        "GB" => 1e9,
+        "GiB" => 1024.0 * 1024.0 * 1024.0,
+        _ => 1.0,
+    };
+    value * multiplier
+}
+
+fn format_size(bytes: f64) -> String {
+    if bytes >= 1e6 {
+        format!("{:.2} MB", bytes / 1e6)
+    } else if bytes >= 1e3 {
+        format!("{:.2} kB", bytes / 1e3)
+    } else {
+        format!("{:.0} B", bytes)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_compress_tsc() {
+        let input = r#"src/app.ts(10,5): error TS2322: Type 'string' is not assignable to type 'number'.
+src/app.ts(22,1): error TS2304: Cannot find name 'foo'.
+src/util.ts:4:9 - error TS7006: Parameter 'x' implicitly has an 'any' type.
+
+4 function f(x) {}
+          ~
+
+src/app.ts(10,5): error TS2322: Type 'string' is not assignable to type 'number'.
+
+Found 3 errors in 2 files.
+"#;
+        let config = CompressConfig::default();
+        let result = compress_frontend_build(input, &config).unwrap();
+
+        assert!(result.output.starts_with("❌ 3 errors in 2 files (1 duplicates dropped)"));
+        assert!(result
+            .output
+            .contains("  src/app.ts:10 (2): TS2322 Type 'string' is not assignable to type 'number'."));
+        assert!(result.output.contains("  src/util.ts:4 (1): TS7006"));
+        assert!(!result.output.contains("Cannot find name"));
+        assert!(result.output.contains("  Codes: TS2304 (1), TS2322 (1), TS7006 (1)\n"));
+        assert!(result.output.ends_with("Found 3 errors in 2 files."));
+    }
+
+    #[test]
+    fn test_compress_vite() {
+        let mut input = String::from("vite v5.0.0 building for production...\ntransforming...\n");
+        for i in 0..200 {
+            input.push_str(&format!("resolved module src/components/C{}.tsx\n", i));
+        }
+        input.push_str(
+            "✓ 1234 modules transformed.\nrendering chunks...\ncomputing gzip size...\n\
+dist/index.html                   0.46 kB │ gzip:  0.30 kB\n\
+dist/assets/index-abc.css         1.23 kB │ gzip:  0.60 kB\n\
+dist/assets/index-def.js        143.21 kB │ gzip: 46.10 kB\n\
+✓ built in 1.23s\n",
+        );
+        let config = CompressConfig::default();
+        let result = compress_frontend_build(&input, &config).unwrap();
+
+        assert_eq!(
+            result.output,
+            "📦 3 assets, 144.90 kB total\n  dist/assets/index-def.js 143.21 kB (gzip 46.10 kB)\n  dist/assets/index-abc.css 1.23 kB (gzip 0.60 kB)\n  dist/index.html 0.46 kB (gzip 0.30 kB)\n✓ built in 1.23s"
+        );
+        assert!(result.reduction_percent() > 90.0);
+    }
+
+    #[test]
+    fn test_compress_webpack_errors() {
+        let input = r#"asset main.js 1.2 MiB [emitted] (name: main)
+asset index.html 300 bytes [emitted]
+runtime modules 1.2 KiB 5 modules
+./src/index.js 1 KiB [built] [code generated]
+
+ERROR in ./src/index.js 5:0-20
+Module not found: Error: Can't resolve './foo' in '/app/src'
+
+WARNING in asset size limit: The following asset(s) exceed the recommended size limit (244 KiB).
+
+WARNING in asset size limit: The following asset(s) exceed the recommended size limit (244 KiB).
+
+webpack 5.88.0 compiled with 1 error and 1 warning in 500 ms
+"#;
+        let config = CompressConfig::default();
+        let result = compress_frontend_build(input, &config).unwrap();
+
+        assert!(result.output.contains(
+            "  ./src/index.js:5 (1): Module not found: Error: Can't resolve './foo' in '/app/src'"
+        ));
+        assert!(result.output.contains("⚠️ 1 warnings"));
+        assert!(!result.output.contains("Codes:"));
+        assert!(result.output.contains("📦 2 assets"));
+        assert!(result.output.ends_with("webpack 5.88.0 compiled with 1 error and 1 warning in 500 ms"));
+        assert!(compress_frontend_build("nothing to see", &config).is_none());
+    }
+}
@@ -3,8 +3,12 @@
 //! Achieves ~75-80% reduction on git operations.
 
 use std::collections::HashMap;
+use std::path::Path;
+use std::process::Command;
 
-use super::{CompressConfig, CompressResult, deduplicate_lines, truncate_with_summary};
+use regex::Regex;
+
+use super::{paths_match, CompressConfig, CompressResult, deduplicate_lines, truncate_with_summary};
 
 /// Compress git status output by grouping by status type
 pub fn compress_git_status(output: &str, config: &CompressConfig) -> CompressResult {
@@ -21,14 +25,15 @@ pub fn compress_git_status(output: &str, config: &CompressConfig) -> CompressRes
     let mut other: Vec<&str> = Vec::new();
 
     for line in &lines {
-        let trimmed = line.trim();
-        if trimmed.is_empty() {
+        // Leading spaces are part of the status code (" M" = modified, unstaged)
+        let trimmed = line.trim_end();
+        if trimmed.trim().is_empty() {
             continue;
         }
 
         // Parse git status short format (e.g., "M  file.rs", "?? newfile")
-        if trimmed.len() < 3 {
-            other.push(trimmed);
+        if trimmed.len() < 3 || !trimmed.is_char_boundary(3) {
+            other.push(trimmed.trim());
             continue;
         }
 
@@ -41,7 +46,7 @@ pub fn compress_git_status(output: &str, config: &CompressConfig) -> CompressRes
             "D " | " D" => deleted.push(file),
             "??" => untracked.push(file),
             "R " | " R" => staged.push(file), // renamed
-            _ => other.push(trimmed),
+            _ => other.push(trimmed.trim()),
         }
     }
 
@@ -78,7 +83,10 @@ pub fn compress_git_status(output: &str, config: &CompressConfig) -> CompressRes
     CompressResult::new(output, compressed)
 }
 
-/// Compress git diff output by summarizing changes per file
+/// Compress git diff output by summarizing changes per file.
+///
+/// With a session working set (`focus_files`/`focus_symbols`), hunks in working files
+/// or touching working symbols are shown in full and everything else is summarized.
 pub fn compress_git_diff(output: &str, config: &CompressConfig) -> CompressResult {
     let lines: Vec<&str> = output.lines().collect();
 
@@ -102,16 +110,20 @@ pub fn compress_git_diff(output: &str, config: &CompressConfig) -> CompressResul
                 name: filename.to_string(),
                 additions: 0,
                 deletions: 0,
-                hunks: 0,
+                hunks: Vec::new(),
             });
         } else if let Some(ref mut f) = current_file {
             if line.starts_with("@@") {
-                f.hunks += 1;
+                f.hunks.push(vec![line.to_string()]);
+                continue;
             } else if line.starts_with('+') && !line.starts_with("+++") {
                 f.additions += 1;
             } else if line.starts_with('-') && !line.starts_with("---") {
                 f.deletions += 1;
             }
+            if let Some(hunk) = f.hunks.last_mut() {
+                hunk.push(line.to_string());
+            }
         }
     }
 
@@ -131,8 +143,14 @@ pub fn compress_git_diff(output: &str, config: &CompressConfig) -> CompressResul
     result_lines.push(format!("📊 {} files changed, +{} -{}", files.len(), total_add, total_del));
     result_lines.push(String::new());
 
+    if !config.focus_files.is_empty() || !config.focus_symbols.is_empty() {
+        result_lines.extend(focused_diff(&files, config));
+        let compressed = result_lines.join("\n");
+        return CompressResult::new(output, compressed);
+    }
+
     for f in files.iter().take(config.max_items_per_group) {
-        result_lines.push(format!("  {} (+{} -{}, {} hunks)", f.name, f.additions, f.deletions, f.hunks));
+        result_lines.push(format!("  {} (+{} -{}, {} hunks)", f.name, f.additions, f.deletions, f.hunks.len()));
     }
 
     if files.len() > config.max_items_per_group {
@@ -143,11 +161,66 @@ pub fn compress_git_diff(output: &str, config: &CompressConfig) -> CompressResul
     CompressResult::new(output, compressed)
 }
 
+/// Full hunks for the working set, then a one-line summary per other file
+fn focused_diff(files: &[FileDiff], config: &CompressConfig) -> Vec<String> {
+    // Working symbols are graph IDs ("src/lib.rs::parse", "src/A.cs::App.A.Run") or bare names
+    let symbols: Vec<Regex> = config
+        .focus_symbols
+        .iter()
+        .filter_map(|s| s.rsplit("::").next()?.rsplit('.').next())
+        .filter(|name| !name.is_empty())
+        .filter_map(|name| Regex::new(&format!(r"\b{}\b", regex::escape(name))).ok())
+        .collect();
+
+    let mut hunk_lines: Vec<String> = Vec::new();
+    let mut others: Vec<String> = Vec::new();
+    for f in files {
+        let in_focus = config.focus_files.iter().any(|p| paths_match(&f.name, p));
+        let relevant: Vec<&Vec<String>> = f
+            .hunks
+            .iter()
+            .filter(|hunk| in_focus || hunk.iter().any(|line| symbols.iter().any(|re| re.is_match(line))))
+            .collect();
+
+        if relevant.is_empty() {
+            others.push(format!("  {} (+{} -{}, {} hunks)", f.name, f.additions, f.deletions, f.hunks.len()));
+            continue;
+        }
+
+        hunk_lines.push(format!("📄 {} (+{} -{})", f.name, f.additions, f.deletions));
+        for hunk in &relevant {
+            hunk_lines.extend(hunk.iter().cloned());
+        }
+        if relevant.len() < f.hunks.len() {
+            hunk_lines.push(format!("  ... +{} other hunks", f.hunks.len() - relevant.len()));
+        }
+    }
+
+    let mut result_lines: Vec<String> = Vec::new();
+    if !hunk_lines.is_empty() {
+        result_lines.push("🎯 Working set:".to_string());
+        result_lines.push(truncate_with_summary(&hunk_lines, config.max_lines));
+    }
+    if !others.is_empty() {
+        if !result_lines.is_empty() {
+            result_lines.push(String::new());
+        }
+        result_lines.push(format!("Other changes ({} files):", others.len()));
+        let shown = others.len().min(config.max_items_per_group);
+        result_lines.extend(others.drain(..shown));
+        if !others.is_empty() {
+            result_lines.push(format!("  ... +{} more files", others.len()));
+        }
+    }
+    result_lines
+}
+
 struct FileDiff {
     name: String,
     additions: usize,
     deletions: usize,
-    hunks: usize,
+    /// Each hunk's lines, starting with its "@@" header
+    hunks: Vec<Vec<String>>,
 }
 
 /// Compress git log output
@@ -270,6 +343,64 @@ pub fn compress_git_blame(output: &str, config: &CompressConfig) -> CompressResu
     CompressResult::new(output, compressed)
 }
 
+/// Combine `git status --porcelain -b`, `git diff HEAD`, and `git log` output into
+/// one working-tree summary
+pub fn compress_git_context(status: &str, diff: &str, log: &str, config: &CompressConfig) -> CompressResult {
+    let original = format!("{}\n{}\n{}", status, diff, log);
+
+    // `-b` puts the branch and upstream tracking on a "## " line
+    let (branch, status): (Vec<&str>, Vec<&str>) = status.lines().partition(|l| l.starts_with("## "));
+    let mut sections: Vec<String> = Vec::new();
+    if let Some(branch) = branch.first() {
+        sections.push(format!("🌿 {}", branch.trim_start_matches("## ")));
+    }
+
+    let status = compress_git_status(&status.join("\n"), config);
+    sections.push(if status.output.is_empty() {
+        "Clean working tree".to_string()
+    } else {
+        status.output
+    });
+
+    let diff = compress_git_diff(diff, config);
+    if !diff.output.is_empty() && diff.output != "No changes" {
+        sections.push(diff.output);
+    }
+
+    let log = compress_git_log(log, config);
+    if !log.output.is_empty() {
+        sections.push(log.output);
+    }
+
+    CompressResult::new(&original, sections.join("\n\n"))
+}
+
+/// Run status, diff, and log in `root` and summarize them with `compress_git_context`
+pub fn git_context(root: &Path, log_count: usize, config: &CompressConfig) -> Result<CompressResult, String> {
+    let git = |args: &[&str]| -> Result<String, String> {
+        let output = Command::new("git")
+            .args(args)
+            .current_dir(root)
+            .output()
+            .map_err(|e| format!("Failed to run git: {}", e))?;
+        if !output.status.success() {
+            return Err(String::from_utf8_lossy(&output.stderr).trim().to_string());
+        }
+        Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+    };
+
+    let status = git(&["status", "--porcelain", "-b"])?;
+    // A repo without commits has no HEAD to diff or log against
+    let has_head = git(&["rev-parse", "--verify", "--quiet", "HEAD"]).is_ok();
+    let (diff, log) = if has_head {
+        (git(&["diff", "HEAD"])?, git(&["log", "-n", &log_count.to_string()])?)
+    } else {
+        (git(&["diff", "--cached"])?, String::new())
+    };
+
+    Ok(compress_git_context(&status, &diff, &log, config))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -291,6 +422,34 @@ D  deleted.rs"#;
         // Note: reduction may be 0% for small inputs where grouping adds overhead
     }
 
+    #[test]
+    fn test_compress_git_status_unstaged() {
+        let input = " M src/main.rs\n D old.rs\nMM both.rs";
+
+        let config = CompressConfig::default();
+        let result = compress_git_status(input, &config);
+
+        assert_eq!(result.output, "📝 Modified (2)\n  src/main.rs\n  both.rs\n🗑️ Deleted (1)\n  old.rs");
+    }
+
+    #[test]
+    fn test_compress_git_context() {
+        let status = "## main...origin/main [ahead 1]\n M src/lib.rs\n?? notes.txt\n";
+        let diff = "diff --git a/src/lib.rs b/src/lib.rs\n@@ -1 +1 @@\n-old\n+new\n";
+        let log = "commit abc1234567890\nAuthor: Jane <jane@example.com>\nDate:   Mon Jan 1 12:00:00 2024\n\n    Add parser\n";
+
+        let config = CompressConfig::default();
+        let result = compress_git_context(status, diff, log, &config);
+
+        assert_eq!(
+            result.output,
+            "🌿 main...origin/main [ahead 1]\n\n📝 Modified (1)\n  src/lib.rs\n❓ Untracked (1)\n  notes.txt\n\n📊 1 files changed, +1 -1\n\n  src/lib.rs (+1 -1, 1 hunks)\n\n📜 1 commits\n  abc1234 Jane - Add parser"
+        );
+
+        let clean = compress_git_context("## main\n", "", "", &config);
+        assert_eq!(clean.output, "🌿 main\n\nClean working tree");
+    }
+
     #[test]
     fn test_compress_git_diff() {
         let input = r#"diff --git a/src/main.rs b/src/main.rs
@@ -315,6 +474,54 @@ diff --git a/src/lib.rs b/src/lib.rs
         assert!(result.output.contains("src/lib.rs"));
     }
 
+    #[test]
+    fn test_compress_git_diff_focused() {
+        let input = r#"diff --git a/src/parser.rs b/src/parser.rs
+--- a/src/parser.rs
++++ b/src/parser.rs
+@@ -10,3 +10,3 @@ impl Parser {
+-    fn parse(&self) {}
++    fn parse(&self) -> Result<()> {}
+@@ -40,2 +40,3 @@ impl Parser {
++    // unrelated
+diff --git a/src/main.rs b/src/main.rs
+--- a/src/main.rs
++++ b/src/main.rs
+@@ -1,2 +1,2 @@
+-    let x = 1;
++    let x = 2;
+diff --git a/src/lib.rs b/src/lib.rs
+@@ -5,1 +5,1 @@
+-old
++new"#;
+
+        let config = CompressConfig {
+            focus_files: vec!["src/lib.rs".to_string()],
+            focus_symbols: vec!["src/parser.rs::parse".to_string()],
+            ..Default::default()
+        };
+        let result = compress_git_diff(input, &config);
+
+        assert_eq!(
+            result.output,
+            "📊 3 files changed, +4 -3
+
+🎯 Working set:
+📄 src/parser.rs (+2 -1)
+@@ -10,3 +10,3 @@ impl Parser {
+-    fn parse(&self) {}
++    fn parse(&self) -> Result<()> {}
+  ... +1 other hunks
+📄 src/lib.rs (+1 -1)
+@@ -5,1 +5,1 @@
+-old
++new
+
+Other changes (1 files):
+  src/main.rs (+1 -1, 1 hunks)"
+        );
+    }
+
     #[test]
     fn test_compress_git_log() {
         let input = r#"commit abc1234567890
@@ -0,0 +1,294 @@
+//! Native project search.
+//!
+//! Searches the project in-process with the ripgrep crates, so `.gitignore` is
+//! respected and nothing is shelled out. Results are grouped by file in the same
+//! shape as `compress_grep`, with context lines around each match.
+
+use std::path::Path;
+
+use grep_regex::RegexMatcherBuilder;
+use grep_searcher::{BinaryDetection, Searcher, SearcherBuilder, Sink, SinkContext, SinkMatch};
+use ignore::overrides::OverrideBuilder;
+use ignore::WalkBuilder;
+
+use super::{truncate_with_summary, CompressConfig, CompressResult};
+
+/// Files larger than this are skipped
+const MAX_FILE_SIZE: u64 = 2 * 1024 * 1024;
+
+/// Matches shown per file before summarizing the rest
+const MATCHES_PER_FILE: usize = 5;
+
+/// Longest line shown before it is cut
+const MAX_LINE_CHARS: usize = 160;
+
+/// What to search for and where
+#[derive(Debug, Clone, Default)]
+pub struct GrepOptions {
+    pub pattern: String,
+    /// Treat the pattern as a literal string
+    pub fixed_strings: bool,
+    pub case_insensitive: bool,
+    /// Include globs (e.g. `*.rs`); a leading `!` excludes
+    pub globs: Vec<String>,
+    /// Lines of context before and after each match
+    pub context: usize,
+    /// Stop searching after this many matches (0 = unlimited)
+    pub max_matches: usize,
+}
+
+/// One line of a search result
+#[derive(Debug, Clone)]
+pub struct GrepLine {
+    pub line_num: u64,
+    pub text: String,
+    /// False for context lines
+    pub is_match: bool,
+}
+
+/// All result lines in one file
+#[derive(Debug, Clone)]
+pub struct FileMatches {
+    /// Path relative to the search root
+    pub path: String,
+    pub lines: Vec<GrepLine>,
+}
+
+impl FileMatches {
+    pub fn match_count(&self) -> usize {
+        self.lines.iter().filter(|l| l.is_match).count()
+    }
+}
+
+/// Search every non-ignored file under `root`. Files are visited in path order.
+pub fn grep_project(root: &Path, options: &GrepOptions) -> Result<Vec<FileMatches>, String> {
+    let matcher = RegexMatcherBuilder::new()
+        .case_insensitive(options.case_insensitive)
+        .fixed_strings(options.fixed_strings)
+        .build(&options.pattern)
+        .map_err(|e| format!("Invalid pattern: {}", e))?;
+
+    let mut overrides = OverrideBuilder::new(root);
+    for glob in &options.globs {
+        overrides
+            .add(glob)
+            .map_err(|e| format!("Invalid glob '{}': {}", glob, e))?;
+    }
+    let overrides = overrides
+        .build()
+        .map_err(|e| format!("Invalid globs: {}", e))?;
+
+    let walker = WalkBuilder::new(root)
+        .require_git(false)
+        .max_filesize(Some(MAX_FILE_SIZE))
+        .overrides(overrides)
+        .sort_by_file_path(|a, b| a.cmp(b))
+        .build();
+
+    let mut searcher = SearcherBuilder::new()
+        .line_number(true)
+        .before_context(options.context)
+        .after_context(options.context)
+        .binary_detection(BinaryDetection::quit(b'\x00'))
+        .build();
+
+    let mut results = Vec::new();
+    let mut total = 0;
+    for entry in walker.flatten() {
+        if !entry.file_type().is_some_and(|t| t.is_file()) {
+            continue;
+        }
+
+        let mut sink = CollectSink::default();
+        // Unreadable files are skipped, like ripgrep does
+        if searcher.search_path(&matcher, entry.path(), &mut sink).is_err() || sink.lines.is_empty() {
+            continue;
+        }
+
+        let path = entry
+            .path()
+            .strip_prefix(root)
+            .unwrap_or(entry.path())
+            .to_string_lossy()
+            .to_string();
+        let file = FileMatches { path, lines: sink.lines };
+        total += file.match_count();
+        results.push(file);
+
+        if options.max_matches > 0 && total >= options.max_matches {
+            break;
+        }
+    }
+
+    Ok(results)
+}
+
+/// Collects match and context lines for one file
+#[derive(Default)]
+struct CollectSink {
+    lines: Vec<GrepLine>,
+}
+
+impl CollectSink {
+    fn push(&mut self, line_num: Option<u64>, bytes: &[u8], is_match: bool) {
+        let text = String::from_utf8_lossy(bytes).trim_end_matches(['\n', '\r']).to_string();
+        self.lines.push(GrepLine {
+            line_num: line_num.unwrap_or(0),
+            text,
+            is_match,
+        });
+    }
+}
+
+impl Sink for CollectSink {
+    type Error = std::io::Error;
+
+    fn matched(&mut self, _searcher: &Searcher, mat: &SinkMatch<'_>) -> Result<bool, Self::Error> {
+        self.push(mat.line_number(), mat.bytes(), true);
+        Ok(true)
+    }
+
+    fn context(&mut self, _searcher: &Searcher, context: &SinkContext<'_>) -> Result<bool, Self::Error> {
+        self.push(context.line_number(), context.bytes(), false);
+        Ok(true)
+    }
+}
+
+/// Format search results grouped by file, like `compress_grep` but with context lines
+pub fn compress_grep_matches(files: &[FileMatches], config: &CompressConfig) -> CompressResult {
+    // The equivalent `rg -n` output, for measuring savings
+    let original: String = files
+        .iter()
+        .flat_map(|file| {
+            file.lines.iter().map(move |line| {
+                let sep = if line.is_match { ':' } else { '-' };
+                format!("{}{}{}{}{}\n", file.path, sep, line.line_num, sep, line.text)
+            })
+        })
+        .collect();
+
+    if files.is_empty() {
+        return CompressResult::new(&original, "No matches".to_string());
+    }
+
+    let total_matches: usize = files.iter().map(|f| f.match_count()).sum();
+    let mut result_lines: Vec<String> = Vec::new();
+    result_lines.push(format!("🔍 {} matches in {} files", total_matches, files.len()));
+    result_lines.push(String::new());
+
+    for file in files.iter().take(config.max_items_per_group) {
+        let count = file.match_count();
+        result_lines.push(format!("📄 {} ({} matches)", file.path, count));
+
+        let mut shown = 0;
+        let mut previous: Option<u64> = None;
+        for line in &file.lines {
+            if line.is_match {
+                if shown == MATCHES_PER_FILE {
+                    break;
+                }
+                shown += 1;
+            } else if shown == MATCHES_PER_FILE {
+                break;
+            }
+            // Mark gaps between non-adjacent context groups
+            if previous.is_some_and(|p| p + 1 != line.line_num) {
+                result_lines.push("  ...".to_string());
+            }
+            let marker = if line.is_match { ':' } else { '-' };
+            let text = format!("  L{}{} {}", line.line_num, marker, shorten(&line.text));
+            result_lines.push(text.trim_end().to_string());
+            previous = Some(line.line_num);
+        }
+
+        if count > shown {
+            result_lines.push(format!("  ... +{} more matches", count - shown));
+        }
+    }
+
+    if files.len() > config.max_items_per_group {
+        result_lines.push(format!(
+            "\n... +{} more files",
+            files.len() - config.max_items_per_group
+        ));
+    }
+
+    CompressResult::new(&original, truncate_with_summary(&result_lines, config.max_lines))
+}
+
+fn shorten(text: &str) -> String {
+    if text.chars().count() > MAX_LINE_CHARS {
+        let cut: String = text.chars().take(MAX_LINE_CHARS - 3).collect();
+        format!("{}...", cut)
+    } else {
+        text.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn project() -> tempfile::TempDir {
+        let dir = tempfile::TempDir::new().unwrap();
+        std::fs::create_dir_all(dir.path().join("src")).unwrap();
+        std::fs::create_dir_all(dir.path().join("target")).unwrap();
+        std::fs::write(dir.path().join(".gitignore"), "target/\n").unwrap();
+        std::fs::write(
+            dir.path().join("src/lib.rs"),
+            "use std::io;\n\nfn parse_config() {\n    todo!()\n}\n\nfn main() {\n    parse_config();\n}\n",
+        )
+        .unwrap();
+        std::fs::write(dir.path().join("src/notes.md"), "parse_config docs\n").unwrap();
+        std::fs::write(dir.path().join("target/out.rs"), "fn parse_config() {}\n").unwrap();
+        dir
+    }
+
+    #[test]
+    fn test_grep_project_respects_gitignore_and_globs() {
+        let dir = project();
+        let options = GrepOptions {
+            pattern: "parse_config".to_string(),
+            ..Default::default()
+        };
+        let files = grep_project(dir.path(), &options).unwrap();
+        let paths: Vec<&str> = files.iter().map(|f| f.path.as_str()).collect();
+        assert_eq!(paths, vec!["src/lib.rs", "src/notes.md"]);
+        assert_eq!(files[0].match_count(), 2);
+
+        let options = GrepOptions {
+            pattern: "PARSE_CONFIG".to_string(),
+            case_insensitive: true,
+            globs: vec!["*.rs".to_string()],
+            ..Default::default()
+        };
+        let files = grep_project(dir.path(), &options).unwrap();
+        assert_eq!(files.len(), 1);
+        assert_eq!(files[0].path, "src/lib.rs");
+
+        let options = GrepOptions {
+            pattern: "(unclosed".to_string(),
+            ..Default::default()
+        };
+        assert!(grep_project(dir.path(), &options).is_err());
+    }
+
+    #[test]
+    fn test_compress_grep_matches_with_context() {
+        let dir = project();
+        let options = GrepOptions {
+            pattern: "parse_config".to_string(),
+            globs: vec!["*.rs".to_string()],
+            context: 1,
+            ..Default::default()
+        };
+        let files = grep_project(dir.path(), &options).unwrap();
+        let result = compress_grep_matches(&files, &CompressConfig::default());
+
+        assert_eq!(
+            result.output,
+            "🔍 2 matches in 1 files\n\n📄 src/lib.rs (2 matches)\n  L2-\n  L3: fn parse_config() {\n  L4-     todo!()\n  ...\n  L7- fn main() {\n  L8:     parse_config();\n  L9- }"
+        );
+        assert_eq!(compress_grep_matches(&[], &CompressConfig::default()).output, "No matches");
+    }
+}
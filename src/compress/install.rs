@@ -0,0 +1,236 @@
+//! Package install log compression - npm, yarn, pnpm, pip, cargo fetch.
+//!
+//! Reduces install logs to package counts, warning and vulnerability summaries,
+//! and shows only errors verbatim.
+
+use once_cell::sync::Lazy;
+use regex::Regex;
+
+use super::{deduplicate_lines, truncate_with_summary, CompressConfig, CompressResult};
+
+/// npm: "added 120 packages, removed 3 packages, changed 5 packages, and audited 500 packages in 10s"
+static NPM_COUNT: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"\b(added|removed|changed|audited) (\d+) packages?").unwrap());
+
+/// Trailing duration: "in 10s", "in 1m", "Done in 5.21s."
+static DURATION: Lazy<Regex> = Lazy::new(|| Regex::new(r"\bin ([\d.]+m?s)\.?$").unwrap());
+
+/// pnpm: "Packages: +120 -3"
+static PNPM_COUNT: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"^Packages: \+(\d+)(?: -(\d+))?").unwrap());
+
+/// yarn: "success Saved 3 new dependencies."
+static YARN_SAVED: Lazy<Regex> = Lazy::new(|| Regex::new(r"Saved (\d+) new dependenc").unwrap());
+
+/// cargo: "Downloaded 12 crates (3.1 MB) in 1.2s"
+static CARGO_DOWNLOADED: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"^Downloaded (\d+) crates?").unwrap());
+
+/// Compress package install output to counts plus verbatim errors
+pub fn compress_install_output(output: &str, config: &CompressConfig) -> CompressResult {
+    let lines: Vec<&str> = output.lines().collect();
+
+    if lines.is_empty() {
+        return CompressResult::new(output, String::new());
+    }
+
+    let mut summary = InstallSummary::default();
+    let mut errors: Vec<&str> = Vec::new();
+
+    for line in &lines {
+        let trimmed = line.trim();
+        let lower = trimmed.to_lowercase();
+
+        if is_error_line(trimmed, &lower) {
+            errors.push(trimmed);
+        } else if lower.starts_with("npm warn")
+            || lower.starts_with("warn")
+            || lower.starts_with("warning")
+        {
+            summary.warnings += 1;
+            if lower.contains("deprecated") {
+                summary.deprecated += 1;
+            }
+        } else if lower.contains("vulnerabilit") && !lower.starts_with("run ") {
+            summary.vulnerabilities = Some(trimmed.to_string());
+        } else if let Some(installed) = trimmed.strip_prefix("Successfully installed ") {
+            // pip
+            summary.added += installed.split_whitespace().count();
+        } else if trimmed.starts_with("Requirement already satisfied") {
+            summary.already_installed += 1;
+        } else if let Some(caps) = PNPM_COUNT.captures(trimmed) {
+            summary.added += caps[1].parse().unwrap_or(0);
+            summary.removed += caps.get(2).and_then(|m| m.as_str().parse().ok()).unwrap_or(0);
+        } else if let Some(caps) = YARN_SAVED.captures(trimmed) {
+            summary.added += caps[1].parse().unwrap_or(0);
+        } else if let Some(caps) = CARGO_DOWNLOADED.captures(trimmed) {
+            summary.downloaded = caps[1].parse().unwrap_or(0);
+        } else if trimmed.starts_with("Downloaded ") && trimmed.contains(" v") {
+            // cargo prints one line per crate before the total
+            summary.crates_seen += 1;
+        } else if NPM_COUNT.is_match(trimmed) {
+            for caps in NPM_COUNT.captures_iter(trimmed) {
+                let count: usize = caps[2].parse().unwrap_or(0);
+                match &caps[1] {
+                    "added" => summary.added += count,
+                    "removed" => summary.removed += count,
+                    "changed" => summary.changed += count,
+                    _ => summary.audited = count,
+                }
+            }
+        } else if lower.starts_with("up to date") {
+            summary.up_to_date = true;
+        }
+
+        if let Some(caps) = DURATION.captures(trimmed) {
+            summary.duration = Some(caps[1].to_string());
+        }
+    }
+
+    if summary.downloaded == 0 {
+        summary.downloaded = summary.crates_seen;
+    }
+
+    let mut result_lines: Vec<String> = Vec::new();
+
+    let status_emoji = if errors.is_empty() { "📦" } else { "❌" };
+    result_lines.push(format!("{} {}", status_emoji, summary.counts_line()));
+
+    if summary.warnings > 0 {
+        let deprecated = if summary.deprecated > 0 {
+            format!(" ({} deprecated)", summary.deprecated)
+        } else {
+            String::new()
+        };
+        result_lines.push(format!("⚠️ {} warnings{}", summary.warnings, deprecated));
+    }
+
+    if let Some(vulnerabilities) = &summary.vulnerabilities {
+        result_lines.push(format!("🔒 {}", vulnerabilities));
+    }
+
+    if !errors.is_empty() {
+        result_lines.push(String::new());
+        result_lines.push("Errors:".to_string());
+        result_lines.extend(deduplicate_lines(&errors, config.dedup_threshold));
+    }
+
+    let compressed = truncate_with_summary(&result_lines, config.max_lines);
+    CompressResult::new(output, compressed)
+}
+
+#[derive(Default)]
+struct InstallSummary {
+    added: usize,
+    removed: usize,
+    changed: usize,
+    audited: usize,
+    downloaded: usize,
+    crates_seen: usize,
+    already_installed: usize,
+    up_to_date: bool,
+    warnings: usize,
+    deprecated: usize,
+    vulnerabilities: Option<String>,
+    duration: Option<String>,
+}
+
+impl InstallSummary {
+    fn counts_line(&self) -> String {
+        let counts: Vec<String> = [
+            (self.added, "added"),
+            (self.removed, "removed"),
+            (self.changed, "changed"),
+            (self.downloaded, "downloaded"),
+            (self.already_installed, "already installed"),
+            (self.audited, "audited"),
+        ]
+        .iter()
+        .filter(|(count, _)| *count > 0)
+        .map(|(count, label)| format!("{} {}", count, label))
+        .collect();
+
+        let mut line = if counts.is_empty() {
+            if self.up_to_date {
+                "up to date".to_string()
+            } else {
+                "no package changes".to_string()
+            }
+        } else {
+            format!("{} packages", counts.join(", "))
+        };
+
+        if let Some(duration) = &self.duration {
+            line.push_str(&format!(" in {}", duration));
+        }
+        line
+    }
+}
+
+/// Errors as printed by npm, yarn, pnpm, pip, and cargo
+fn is_error_line(trimmed: &str, lower: &str) -> bool {
+    lower.starts_with("npm err")
+        || lower.starts_with("error")
+        || trimmed.starts_with("ERR_PNPM")
+        || trimmed.starts_with("➤ YN0001")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_compress_npm_install() {
+        let input = r#"npm WARN deprecated inflight@1.0.6: This module is not supported
+npm WARN deprecated glob@7.2.3: Glob versions prior to v9 are no longer supported
+npm WARN EBADENGINE Unsupported engine
+
+added 120 packages, removed 3 packages, changed 5 packages, and audited 500 packages in 10s
+
+25 packages are looking for funding
+  run `npm fund` for details
+
+3 vulnerabilities (1 moderate, 2 high)
+
+To address all issues, run:
+  npm audit fix
+"#;
+        let config = CompressConfig::default();
+        let result = compress_install_output(input, &config);
+
+        assert!(result
+            .output
+            .starts_with("📦 120 added, 3 removed, 5 changed, 500 audited packages in 10s"));
+        assert!(result.output.contains("⚠️ 3 warnings (2 deprecated)"));
+        assert!(result.output.contains("🔒 3 vulnerabilities (1 moderate, 2 high)"));
+        assert!(!result.output.contains("inflight"));
+    }
+
+    #[test]
+    fn test_compress_pip_install_with_error() {
+        let input = r#"Collecting requests
+  Downloading requests-2.31.0-py3-none-any.whl (62 kB)
+Requirement already satisfied: idna<4,>=2.5 in ./venv/lib/python3.11/site-packages (from requests) (3.4)
+Requirement already satisfied: certifi>=2017.4.17 in ./venv/lib/python3.11/site-packages (from requests) (2023.7.22)
+ERROR: Could not find a version that satisfies the requirement nonexistent-pkg
+ERROR: No matching distribution found for nonexistent-pkg
+"#;
+        let config = CompressConfig::default();
+        let result = compress_install_output(input, &config);
+
+        assert!(result.output.starts_with("❌ 2 already installed packages"));
+        assert!(result
+            .output
+            .contains("ERROR: No matching distribution found for nonexistent-pkg"));
+        assert!(!result.output.contains("Collecting"));
+    }
+
+    #[test]
+    fn test_compress_cargo_fetch() {
+        let input = "    Updating crates.io index\n  Downloaded serde v1.0.190\n  Downloaded tokio v1.33.0\n  Downloaded 2 crates (1.2 MB) in 0.84s\n";
+        let config = CompressConfig::default();
+        let result = compress_install_output(input, &config);
+
+        assert_eq!(result.output, "📦 2 downloaded packages in 0.84s");
+    }
+}
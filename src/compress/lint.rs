@@ -0,0 +1,308 @@
+//! Linter output compression - ESLint, Ruff, golangci-lint.
+//!
+//! Drops repeated problems, counts the rest per file, and groups them by rule code with
+//! counts and representative locations; only the most frequent rules keep their full
+//! messages.
+
+use once_cell::sync::Lazy;
+use regex::Regex;
+use std::collections::{HashMap, HashSet};
+
+use super::build::file_counts;
+use super::{CompressConfig, CompressResult};
+
+/// Rules shown with full messages; the rest get a count and a few locations
+const TOP_RULES: usize = 3;
+
+/// Locations listed for each summarized rule
+const REPRESENTATIVE_LOCATIONS: usize = 3;
+
+/// ESLint "stylish": `  1:10  error  'foo' is defined but never used  no-unused-vars`
+static STYLISH_ISSUE: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"^\s+(\d+):\d+\s+(error|warning)\s+(.+?)\s{2,}(\S+)$").unwrap());
+
+/// `path:line[:col]: message` as printed by Ruff (concise), golangci-lint, and ESLint (unix)
+static LOCATED_ISSUE: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"^(\S+?):(\d+):(?:\d+:)?\s+(.+)$").unwrap());
+
+/// Ruff rule code prefix: `F401 [*] message`
+static RUFF_RULE: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"^([A-Z]+\d+)\s+(?:\[\*\]\s+)?(.+)$").unwrap());
+
+/// golangci-lint linter suffix: `message (errcheck)`
+static GOLANGCI_RULE: Lazy<Regex> = Lazy::new(|| Regex::new(r"^(.+?)\s+\(([\w-]+)\)$").unwrap());
+
+/// ESLint unix suffix: `message [Error/no-unused-vars]`
+static ESLINT_UNIX_RULE: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"^(.+?)\s+\[(Error|Warning)/([^\]]+)\]$").unwrap());
+
+/// Compress linter output. Returns `None` when no lint problems are recognized,
+/// so the caller can fall back to generic compression.
+pub fn compress_lint_output(output: &str, config: &CompressConfig) -> Option<CompressResult> {
+    let parsed = parse_lint_issues(output);
+    if parsed.is_empty() {
+        return None;
+    }
+
+    // Each problem once, however many times overlapping globs or configs reported it
+    let mut seen = HashSet::new();
+    let issues: Vec<&LintIssue> = parsed
+        .iter()
+        .filter(|i| seen.insert((i.file.as_str(), i.line, i.rule.as_str(), i.message.as_str())))
+        .collect();
+    let duplicates = parsed.len() - issues.len();
+
+    // Group by rule, most frequent first
+    let mut by_rule: HashMap<&str, Vec<&LintIssue>> = HashMap::new();
+    for issue in &issues {
+        by_rule.entry(issue.rule.as_str()).or_default().push(*issue);
+    }
+    let mut rules: Vec<_> = by_rule.keys().copied().collect();
+    rules.sort_by(|a, b| by_rule[b].len().cmp(&by_rule[a].len()).then(a.cmp(b)));
+
+    let mut files: HashMap<&str, (usize, usize)> = HashMap::new();
+    for issue in &issues {
+        let counts = files.entry(issue.file.as_str()).or_default();
+        if issue.is_warning {
+            counts.1 += 1;
+        } else {
+            counts.0 += 1;
+        }
+    }
+
+    let mut result_lines: Vec<String> = Vec::new();
+    let mut header = format!(
+        "❌ {} lint problems: {} rules in {} files",
+        issues.len(),
+        rules.len(),
+        files.len()
+    );
+    if duplicates > 0 {
+        header.push_str(&format!(" ({} duplicates dropped)", duplicates));
+    }
+    result_lines.push(header);
+
+    // Per-file counts, most errors first, once there is more than one file to tell apart
+    if files.len() > 1 {
+        let mut by_file: Vec<_> = files.into_iter().collect();
+        by_file.sort_by_key(|&(file, (errors, warnings))| {
+            (std::cmp::Reverse(errors), std::cmp::Reverse(errors + warnings), file)
+        });
+        for (file, (errors, warnings)) in by_file.iter().take(config.max_items_per_group) {
+            result_lines.push(format!("  {}: {}", file, file_counts(*errors, *warnings)));
+        }
+        if by_file.len() > config.max_items_per_group {
+            result_lines.push(format!("  ... +{} more files", by_file.len() - config.max_items_per_group));
+        }
+    }
+    result_lines.push(String::new());
+
+    for (rank, rule) in rules.iter().take(config.max_lines).enumerate() {
+        let group = &by_rule[rule];
+
+        if rank < TOP_RULES {
+            result_lines.push(format!("{} ({})", rule, group.len()));
+            for issue in group.iter().take(config.max_items_per_group) {
+                result_lines.push(format!("  {}:{} {}", issue.file, issue.line, issue.message));
+            }
+            if group.len() > config.max_items_per_group {
+                result_lines.push(format!("  ... +{} more", group.len() - config.max_items_per_group));
+            }
+        } else {
+            let mut locations: Vec<String> = group
+                .iter()
+                .take(REPRESENTATIVE_LOCATIONS)
+                .map(|i| format!("{}:{}", i.file, i.line))
+                .collect();
+            if group.len() > REPRESENTATIVE_LOCATIONS {
+                locations.push(format!("+{} more", group.len() - REPRESENTATIVE_LOCATIONS));
+            }
+            result_lines.push(format!("{} ({}): {}", rule, group.len(), locations.join(", ")));
+        }
+    }
+
+    if rules.len() > config.max_lines {
+        result_lines.push(format!("... +{} more rules", rules.len() - config.max_lines));
+    }
+
+    // "2 errors and 0 warnings potentially fixable with the `--fix` option."
+    if let Some(fixable) = output.lines().map(str::trim).find(|l| l.contains("fixable with the `--fix` option")) {
+        result_lines.push(String::new());
+        result_lines.push(fixable.to_string());
+    }
+
+    let compressed = result_lines.join("\n");
+    Some(CompressResult::new(output, compressed))
+}
+
+/// A single lint problem
+struct LintIssue {
+    rule: String,
+    file: String,
+    line: u32,
+    message: String,
+    /// ESLint warnings; other linters' problems all count as errors
+    is_warning: bool,
+}
+
+/// Parse lint problems from any of the supported formats
+fn parse_lint_issues(output: &str) -> Vec<LintIssue> {
+    let mut issues = Vec::new();
+    let mut current_file: Option<&str> = None;
+    // Ruff's full format puts the rule first and the location on a following "-->" line
+    let mut pending_ruff: Option<(String, String)> = None;
+
+    for line in output.lines() {
+        let trimmed = line.trim();
+
+        if let Some(caps) = STYLISH_ISSUE.captures(line) {
+            if let Some(file) = current_file {
+                issues.push(LintIssue {
+                    rule: caps[4].to_string(),
+                    file: file.to_string(),
+                    line: caps[1].parse().unwrap_or(0),
+                    message: caps[3].to_string(),
+                    is_warning: &caps[2] == "warning",
+                });
+            }
+            continue;
+        }
+
+        if let Some(location) = trimmed.strip_prefix("--> ") {
+            if let Some((rule, message)) = pending_ruff.take() {
+                let mut parts = location.split(':');
+                issues.push(LintIssue {
+                    rule,
+                    file: parts.next().unwrap_or(location).to_string(),
+                    line: parts.next().and_then(|l| l.parse().ok()).unwrap_or(0),
+                    message,
+                    is_warning: false,
+                });
+            }
+            continue;
+        }
+
+        if let Some(caps) = LOCATED_ISSUE.captures(trimmed) {
+            if let Some((rule, message, is_warning)) = split_rule(&caps[3]) {
+                issues.push(LintIssue {
+                    rule,
+                    file: caps[1].to_string(),
+                    line: caps[2].parse().unwrap_or(0),
+                    message,
+                    is_warning,
+                });
+                continue;
+            }
+        }
+
+        if let Some(caps) = RUFF_RULE.captures(line) {
+            pending_ruff = Some((caps[1].to_string(), caps[2].to_string()));
+            continue;
+        }
+
+        // ESLint stylish file header: an unindented path on its own line
+        if !line.is_empty() && !line.starts_with(char::is_whitespace) && !trimmed.contains(' ') {
+            current_file = Some(trimmed);
+        }
+    }
+
+    issues
+}
+
+/// Split a located message into (rule, message, is_warning) using the known rule notations
+fn split_rule(message: &str) -> Option<(String, String, bool)> {
+    if let Some(caps) = RUFF_RULE.captures(message) {
+        return Some((caps[1].to_string(), caps[2].to_string(), false));
+    }
+    if let Some(caps) = ESLINT_UNIX_RULE.captures(message) {
+        return Some((caps[3].to_string(), caps[1].to_string(), &caps[2] == "Warning"));
+    }
+    if let Some(caps) = GOLANGCI_RULE.captures(message) {
+        return Some((caps[2].to_string(), caps[1].to_string(), false));
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_compress_eslint_stylish() {
+        let input = r#"
+/app/src/a.js
+  1:10  error    'foo' is defined but never used  no-unused-vars
+  2:5   warning  Unexpected console statement     no-console
+  7:10  error    'bar' is defined but never used  no-unused-vars
+
+/app/src/b.js
+  3:1  error  'baz' is defined but never used  no-unused-vars
+
+✖ 4 problems (3 errors, 1 warning)
+  0 errors and 1 warning potentially fixable with the `--fix` option.
+"#;
+        let config = CompressConfig::default();
+        let result = compress_lint_output(input, &config).unwrap();
+
+        let summary =
+            "❌ 4 lint problems: 2 rules in 2 files\n  /app/src/a.js: 2 errors, 1 warning\n  /app/src/b.js: 1 error\n";
+        assert!(result.output.starts_with(summary), "{}", result.output);
+        assert!(result.output.contains("no-unused-vars (3)"));
+        assert!(result.output.contains("  /app/src/b.js:3 'baz' is defined but never used"));
+        assert!(result.output.contains("no-console (1)"));
+        assert!(result
+            .output
+            .ends_with("\n\n0 errors and 1 warning potentially fixable with the `--fix` option."));
+    }
+
+    #[test]
+    fn test_duplicate_problems_dropped() {
+        // The same file linted twice through overlapping globs
+        let input = "/app/src/a.js:1:10: 'foo' is defined but never used [Error/no-unused-vars]\n\
+/app/src/a.js:2:5: Unexpected console statement [Warning/no-console]\n\
+/app/src/a.js:1:10: 'foo' is defined but never used [Error/no-unused-vars]\n";
+        let config = CompressConfig::default();
+        let result = compress_lint_output(input, &config).unwrap();
+
+        assert!(result
+            .output
+            .starts_with("❌ 2 lint problems: 2 rules in 1 files (1 duplicates dropped)\n\n"));
+        assert!(result.output.contains("no-unused-vars (1)\n  /app/src/a.js:1 'foo' is defined but never used"));
+        assert!(result.output.contains("no-console (1)"));
+    }
+
+    #[test]
+    fn test_compress_ruff_and_golangci() {
+        let ruff = "src/app.py:1:8: F401 [*] `os` imported but unused\nsrc/app.py:5:1: E722 Do not use bare `except`\nsrc/util.py:2:8: F401 [*] `sys` imported but unused\nFound 3 errors.\n";
+        let config = CompressConfig::default();
+        let result = compress_lint_output(ruff, &config).unwrap();
+        assert!(result.output.contains("F401 (2)"));
+        assert!(result.output.contains("  src/util.py:2 `sys` imported but unused"));
+
+        let ruff_full = "F401 [*] `os` imported but unused\n --> src/app.py:1:8\n  |\n1 | import os\n  |        ^^\n";
+        let result = compress_lint_output(ruff_full, &config).unwrap();
+        assert!(result.output.contains("  src/app.py:1 `os` imported but unused"));
+
+        let golangci = "main.go:10:2: Error return value of `f.Close` is not checked (errcheck)\n\tf.Close()\n\t^\nmain.go:14:6: func `unused` is unused (unused)\n";
+        let result = compress_lint_output(golangci, &config).unwrap();
+        assert!(result.output.contains("errcheck (1)"));
+        assert!(result.output.contains("  main.go:14 func `unused` is unused"));
+    }
+
+    #[test]
+    fn test_top_offenders_get_detail() {
+        let mut input = String::new();
+        for (rule, count) in [("a", 5), ("b", 4), ("c", 3), ("d", 2)] {
+            for i in 0..count {
+                input.push_str(&format!("x.go:{}:1: problem {} ({})\n", i + 1, rule, rule));
+            }
+        }
+        let config = CompressConfig::default();
+        let result = compress_lint_output(&input, &config).unwrap();
+
+        assert!(result.output.contains("c (3)\n  x.go:1 problem c"));
+        assert!(result.output.contains("d (2): x.go:1, x.go:2"));
+        assert!(!result.output.contains("problem d"));
+        assert!(compress_lint_output("All checks passed!", &config).is_none());
+    }
+}
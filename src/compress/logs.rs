@@ -0,0 +1,277 @@
+//! Application log compression.
+//!
+//! Groups log lines by level and message template (timestamps, IDs, numbers
+//! stripped) with counts and first/last occurrence times.
+
+use once_cell::sync::Lazy;
+use regex::Regex;
+use serde_json::Value;
+use std::collections::HashMap;
+
+use super::{CompressConfig, CompressResult};
+
+/// ISO 8601 / RFC 3339, syslog ("Jan  2 15:04:05"), or bare time ("15:04:05.123")
+static TIMESTAMP: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(
+        r"\d{4}-\d{2}-\d{2}[T ]\d{2}:\d{2}:\d{2}(?:[.,]\d+)?(?:Z|[+-]\d{2}:?\d{2})?|[A-Z][a-z]{2} +\d{1,2} \d{2}:\d{2}:\d{2}|\b\d{2}:\d{2}:\d{2}(?:[.,]\d+)?\b",
+    )
+    .unwrap()
+});
+
+/// Level keyword, optionally bracketed or written as level=...
+static LEVEL: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"(?i)(?:^|[\s\[(|:=])(trace|debug|info|warn|warning|error|err|fatal|critical|panic)(?:$|[\s\]):|])").unwrap()
+});
+
+/// Variable parts replaced in message templates, most specific first
+static TEMPLATE_PARTS: Lazy<Vec<(Regex, &'static str)>> = Lazy::new(|| {
+    vec![
+        (
+            Regex::new(r"[0-9a-fA-F]{8}-[0-9a-fA-F]{4}-[0-9a-fA-F]{4}-[0-9a-fA-F]{4}-[0-9a-fA-F]{12}").unwrap(),
+            "<uuid>",
+        ),
+        (Regex::new(r"\b\d{1,3}(?:\.\d{1,3}){3}(?::\d+)?\b").unwrap(), "<ip>"),
+        (Regex::new(r"\b0x[0-9a-fA-F]+\b|\b[0-9a-f]{8,}\b").unwrap(), "<hex>"),
+        (Regex::new(r#""[^"]*""#).unwrap(), "\"<str>\""),
+        (Regex::new(r"\b\d+(?:\.\d+)?(?:ms|s|KB|MB|%)?\b").unwrap(), "<n>"),
+    ]
+});
+
+/// Levels from most to least severe
+const LEVELS: [&str; 6] = ["FATAL", "ERROR", "WARN", "INFO", "DEBUG", "TRACE"];
+
+/// Compress application log output
+pub fn compress_logs(output: &str, config: &CompressConfig) -> CompressResult {
+    let lines: Vec<&str> = output.lines().filter(|l| !l.trim().is_empty()).collect();
+
+    if lines.is_empty() {
+        return CompressResult::new(output, String::new());
+    }
+
+    let mut groups: HashMap<(&'static str, String), LogGroup> = HashMap::new();
+    let mut level_counts: HashMap<&'static str, usize> = HashMap::new();
+    let mut unleveled = 0;
+
+    for line in &lines {
+        let Some(entry) = parse_log_line(line) else {
+            // Continuation lines (stack traces, wrapped messages)
+            unleveled += 1;
+            continue;
+        };
+
+        *level_counts.entry(entry.level).or_insert(0) += 1;
+        let group = groups
+            .entry((entry.level, template(&entry.message)))
+            .or_insert_with(|| LogGroup {
+                count: 0,
+                example: entry.message.clone(),
+                first: entry.time.clone(),
+                last: None,
+            });
+        group.count += 1;
+        if entry.time.is_some() {
+            if group.first.is_none() {
+                group.first = entry.time.clone();
+            }
+            group.last = entry.time;
+        }
+    }
+
+    let mut result_lines: Vec<String> = Vec::new();
+
+    let level_summary: Vec<String> = LEVELS
+        .iter()
+        .filter_map(|level| level_counts.get(level).map(|n| format!("{} {}", n, level)))
+        .collect();
+    let mut header = format!("📜 {} log lines", lines.len());
+    if !level_summary.is_empty() {
+        header.push_str(&format!(": {}", level_summary.join(", ")));
+    }
+    if unleveled > 0 {
+        header.push_str(&format!(" (+{} continuation lines)", unleveled));
+    }
+    result_lines.push(header);
+
+    for level in LEVELS {
+        let mut level_groups: Vec<(&String, &LogGroup)> = groups
+            .iter()
+            .filter(|((l, _), _)| *l == level)
+            .map(|((_, t), g)| (t, g))
+            .collect();
+        if level_groups.is_empty() {
+            continue;
+        }
+        level_groups.sort_by(|a, b| b.1.count.cmp(&a.1.count).then(a.0.cmp(b.0)));
+
+        result_lines.push(String::new());
+        result_lines.push(format!("{} ({} patterns)", level, level_groups.len()));
+
+        for (template, group) in level_groups.iter().take(config.max_items_per_group) {
+            // A one-off message reads better verbatim than as a template
+            let text = if group.count == 1 { &group.example } else { *template };
+            let mut line = format!("  ×{} {}", group.count, truncate(text, 120));
+            match (&group.first, &group.last) {
+                (Some(first), Some(last)) if first != last => {
+                    line.push_str(&format!("  [{} … {}]", first, last));
+                }
+                (Some(first), _) => line.push_str(&format!("  [{}]", first)),
+                _ => {}
+            }
+            result_lines.push(line);
+        }
+
+        if level_groups.len() > config.max_items_per_group {
+            result_lines.push(format!(
+                "  ... +{} more patterns",
+                level_groups.len() - config.max_items_per_group
+            ));
+        }
+    }
+
+    let compressed = result_lines.join("\n");
+    CompressResult::new(output, compressed)
+}
+
+/// Check whether most lines of the output look like leveled log lines
+pub fn looks_like_logs(output: &str) -> bool {
+    let lines: Vec<&str> = output.lines().filter(|l| !l.trim().is_empty()).collect();
+    if lines.len() < 20 {
+        return false;
+    }
+    let leveled = lines
+        .iter()
+        .filter(|l| parse_log_line(l).is_some_and(|e| e.time.is_some()))
+        .count();
+    leveled * 2 > lines.len()
+}
+
+struct LogEntry {
+    level: &'static str,
+    message: String,
+    time: Option<String>,
+}
+
+struct LogGroup {
+    count: usize,
+    example: String,
+    first: Option<String>,
+    last: Option<String>,
+}
+
+/// Parse a plain-text or JSON log line. Returns `None` for lines without a level.
+fn parse_log_line(line: &str) -> Option<LogEntry> {
+    let trimmed = line.trim();
+
+    if trimmed.starts_with('{') {
+        if let Ok(Value::Object(obj)) = serde_json::from_str::<Value>(trimmed) {
+            let field = |keys: &[&str]| {
+                keys.iter()
+                    .find_map(|k| obj.get(*k))
+                    .map(|v| v.as_str().map(|s| s.to_string()).unwrap_or_else(|| v.to_string()))
+            };
+            return Some(LogEntry {
+                level: normalize_level(&field(&["level", "severity", "lvl"])?)?,
+                message: field(&["msg", "message"]).unwrap_or_default(),
+                time: field(&["time", "timestamp", "ts", "@timestamp"]),
+            });
+        }
+    }
+
+    // Continuation lines are indented (stack frames, wrapped text)
+    if line.starts_with(char::is_whitespace) {
+        return None;
+    }
+
+    let time = TIMESTAMP.find(trimmed).map(|m| m.as_str().to_string());
+    let without_time = TIMESTAMP.replace(trimmed, "");
+    let caps = LEVEL.captures(&without_time)?;
+    let level_match = caps.get(1)?;
+    // The level should be near the start, not a word inside the message
+    if level_match.start() > 40 {
+        return None;
+    }
+    let level = normalize_level(level_match.as_str())?;
+
+    let message = without_time[level_match.end()..]
+        .trim_start_matches(|c: char| c == ']' || c == ')' || c == ':' || c == '|' || c.is_whitespace())
+        .to_string();
+
+    Some(LogEntry { level, message, time })
+}
+
+fn normalize_level(level: &str) -> Option<&'static str> {
+    match level.to_uppercase().as_str() {
+        "FATAL" | "CRITICAL" | "PANIC" => Some("FATAL"),
+        "ERROR" | "ERR" => Some("ERROR"),
+        "WARN" | "WARNING" => Some("WARN"),
+        "INFO" => Some("INFO"),
+        "DEBUG" => Some("DEBUG"),
+        "TRACE" => Some("TRACE"),
+        _ => None,
+    }
+}
+
+/// Replace IDs, numbers, addresses, and quoted values with placeholders
+fn template(message: &str) -> String {
+    TEMPLATE_PARTS
+        .iter()
+        .fold(message.to_string(), |acc, (pattern, placeholder)| {
+            pattern.replace_all(&acc, *placeholder).into_owned()
+        })
+}
+
+fn truncate(text: &str, max: usize) -> String {
+    if text.chars().count() > max {
+        format!("{}...", text.chars().take(max - 3).collect::<String>())
+    } else {
+        text.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_compress_logs() {
+        let mut input = String::new();
+        for i in 0..50 {
+            input.push_str(&format!(
+                "2024-05-01T10:{:02}:00Z INFO Handled request id={} in {}ms\n",
+                i, 1000 + i, i * 3
+            ));
+        }
+        input.push_str("2024-05-01T10:20:00Z ERROR Failed to connect to 10.0.0.5:5432 after 3 retries\n");
+        input.push_str("  at db::connect (src/db.rs:42)\n");
+        input.push_str("2024-05-01T10:30:00Z ERROR Failed to connect to 10.0.0.6:5432 after 5 retries\n");
+        input.push_str("2024-05-01T10:31:00Z [WARN] Slow query took 2300ms\n");
+
+        let config = CompressConfig::default();
+        let result = compress_logs(&input, &config);
+
+        assert!(result
+            .output
+            .starts_with("📜 54 log lines: 2 ERROR, 1 WARN, 50 INFO (+1 continuation lines)"));
+        assert!(result.output.contains(
+            "  ×2 Failed to connect to <ip> after <n> retries  [2024-05-01T10:20:00Z … 2024-05-01T10:30:00Z]"
+        ));
+        assert!(result.output.contains("  ×1 Slow query took 2300ms  [2024-05-01T10:31:00Z]"));
+        assert!(result.output.contains(
+            "  ×50 Handled request id=<n> in <n>  [2024-05-01T10:00:00Z … 2024-05-01T10:49:00Z]"
+        ));
+        assert!(result.reduction_percent() > 70.0);
+        assert!(looks_like_logs(&input));
+    }
+
+    #[test]
+    fn test_json_logs() {
+        let input = r#"{"time":"2024-05-01T10:00:00Z","level":"error","msg":"payment 8f14e45f-ceea-4e67-a9a6-0e5c2f8d1b7a declined"}
+{"time":"2024-05-01T10:05:00Z","level":"error","msg":"payment 1c9a2b3d-4e5f-4a6b-8c7d-9e0f1a2b3c4d declined"}
+"#;
+        let config = CompressConfig::default();
+        let result = compress_logs(input, &config);
+
+        assert!(result.output.contains("  ×2 payment <uuid> declined  [2024-05-01T10:00:00Z … 2024-05-01T10:05:00Z]"));
+        assert!(!looks_like_logs("just\nsome\noutput"));
+    }
+}
@@ -2,23 +2,59 @@
 //!
 //! This module provides compression algorithms that reduce token consumption
 //! by 60-90% on common command outputs like file listings, git operations,
-//! grep results, and test output.
-
+//! grep results, test output, build diagnostics (cargo, tsc, bundlers), linter reports,
+//! install logs, docker builds, terraform plans, coverage reports, benchmarks, application logs,
+//! and stack traces, plus graph-aware partial reads of large files, persistent
+//! shell sessions, and native project search.
+
+mod bench;
+mod binary;
+mod build;
+mod cache;
+mod coverage;
+mod docker;
 mod files;
+mod frontend;
 mod git;
+mod grep;
+mod install;
+mod lint;
+mod logs;
+mod read;
+mod redact;
 mod search;
+mod shell;
+mod stacktrace;
+mod terraform;
 mod test_output;
 mod bash;
 mod analytics;
 
+pub use bench::compress_bench_output;
+pub use binary::binary_summary;
+pub use coverage::compress_coverage;
+pub use cache::{expand_output, OutputCache};
+pub use docker::compress_docker_build;
 pub use files::{compress_file_list, compress_tree, compress_ls};
-pub use git::{compress_git_status, compress_git_diff, compress_git_log, compress_git_blame};
+pub use frontend::compress_frontend_build;
+pub use git::{compress_git_status, compress_git_diff, compress_git_log, compress_git_blame, compress_git_context, git_context};
+pub use grep::{compress_grep_matches, grep_project, FileMatches, GrepLine, GrepOptions};
+pub use logs::compress_logs;
+pub use read::{compress_file_read, OutlineSymbol, ReadRequest};
+pub use redact::redact_secrets;
+pub use shell::{compress_shell_output, ShellOutput, ShellSession};
 pub use search::{compress_grep, compress_find, compress_symbol_search, SymbolResult};
 pub use test_output::compress_test_output;
-pub use bash::{exec_compressed, compress_for_command, categorize_command};
+pub use build::compress_cargo_build;
+pub use install::compress_install_output;
+pub use lint::compress_lint_output;
+pub use stacktrace::{compress_stack_trace, link_frames};
+pub use terraform::compress_terraform_plan;
+pub use bash::{exec_compressed, exec_compressed_streaming, CommandPolicy, ExecOptions, compress_for_command, categorize_command};
 pub use analytics::{CompressionAnalytics, CompressionStats, CompressionRecord};
 
 use std::collections::HashMap;
+use std::path::PathBuf;
 
 /// Configuration for compression behavior
 #[derive(Debug, Clone)]
@@ -33,6 +69,12 @@ pub struct CompressConfig {
     pub group_items: bool,
     /// Minimum occurrences before deduplicating
     pub dedup_threshold: usize,
+    /// Files in the session's working set; compressors keep extra detail for these
+    pub focus_files: Vec<String>,
+    /// Symbols in the session's working set (graph IDs like `src/lib.rs::parse` or names)
+    pub focus_symbols: Vec<String>,
+    /// Project root; stack frames at absolute paths outside it count as framework frames
+    pub project_root: Option<PathBuf>,
 }
 
 impl Default for CompressConfig {
@@ -43,6 +85,22 @@ impl Default for CompressConfig {
             show_counts: true,
             group_items: true,
             dedup_threshold: 2,
+            focus_files: Vec::new(),
+            focus_symbols: Vec::new(),
+            project_root: None,
+        }
+    }
+}
+
+impl CompressConfig {
+    /// Settings from the `[compression]` config section for a command category
+    pub fn for_category(settings: &crate::config::CompressionConfig, category: &str) -> Self {
+        let profile = settings.categories.get(category).cloned().unwrap_or_default();
+        Self {
+            max_lines: profile.max_lines.unwrap_or(settings.max_lines),
+            max_items_per_group: profile.max_items_per_group.unwrap_or(settings.max_items_per_group),
+            dedup_threshold: profile.dedup_threshold.unwrap_or(settings.dedup_threshold),
+            ..Default::default()
         }
     }
 }
@@ -58,6 +116,8 @@ pub struct CompressResult {
     pub compressed_size: usize,
     /// Estimated token savings (chars / 4 approximation)
     pub estimated_token_savings: usize,
+    /// Full (redacted) command output, kept by `exec_compressed` for later expansion
+    pub raw_output: Option<String>,
 }
 
 impl CompressResult {
@@ -71,6 +131,7 @@ impl CompressResult {
             original_size,
             compressed_size,
             estimated_token_savings: savings / 4, // rough token estimate
+            raw_output: None,
         }
     }
 
@@ -107,6 +168,14 @@ pub fn deduplicate_lines(lines: &[&str], threshold: usize) -> Vec<String> {
         .collect()
 }
 
+/// Compare paths from tool output with working-set paths. Report paths are often
+/// absolute while session paths are project-relative.
+pub(crate) fn paths_match(a: &str, b: &str) -> bool {
+    let a = a.trim_start_matches("./");
+    let b = b.trim_start_matches("./");
+    a == b || a.ends_with(&format!("/{}", b)) || b.ends_with(&format!("/{}", a))
+}
+
 /// Truncate output with a summary of what was hidden
 pub fn truncate_with_summary(lines: &[String], max_lines: usize) -> String {
     if lines.len() <= max_lines {
@@ -148,6 +217,24 @@ mod tests {
         assert!(!result.contains("line 10"));
     }
 
+    #[test]
+    fn test_config_for_category() {
+        let mut settings = crate::config::CompressionConfig::default();
+        settings.max_lines = 30;
+        settings.categories.insert(
+            "test".to_string(),
+            crate::config::CompressionProfile {
+                max_lines: Some(120),
+                ..Default::default()
+            },
+        );
+
+        let test = CompressConfig::for_category(&settings, "test");
+        assert_eq!(test.max_lines, 120);
+        assert_eq!(test.max_items_per_group, 10);
+        assert_eq!(CompressConfig::for_category(&settings, "git").max_lines, 30);
+    }
+
     #[test]
     fn test_compress_result_reduction() {
         let original = "a".repeat(1000);
@@ -0,0 +1,269 @@
+//! Graph-aware partial file reads.
+//!
+//! Big files are returned as their import lines and an outline of their symbols, plus
+//! only the symbol bodies and line ranges that were asked for.
+
+use super::{CompressConfig, CompressResult};
+
+/// Files at or below this size are returned whole when nothing specific is requested
+const SMALL_FILE_LINES: usize = 200;
+
+/// Imports listed before the outline
+const MAX_IMPORT_LINES: usize = 30;
+
+/// Symbol from the parser (or the code graph) used to build the outline
+#[derive(Debug, Clone)]
+pub struct OutlineSymbol {
+    pub name: String,
+    pub kind: String,
+    pub line_start: usize,
+    pub line_end: usize,
+    /// First line of the definition; the outline shows `name` when empty
+    pub signature: String,
+}
+
+/// What to include besides the outline
+#[derive(Debug, Clone, Default)]
+pub struct ReadRequest {
+    /// Symbol names whose bodies should be included
+    pub symbols: Vec<String>,
+    /// 1-based inclusive line ranges
+    pub ranges: Vec<(usize, usize)>,
+}
+
+/// Read a file as imports + outline + requested sections. `content` is the full file text.
+pub fn compress_file_read(
+    path: &str,
+    content: &str,
+    outline: &[OutlineSymbol],
+    request: &ReadRequest,
+    config: &CompressConfig,
+) -> CompressResult {
+    let lines: Vec<&str> = content.lines().collect();
+    let nothing_requested = request.symbols.is_empty() && request.ranges.is_empty();
+
+    if nothing_requested && (lines.len() <= SMALL_FILE_LINES || outline.is_empty()) {
+        let mut result_lines = vec![format!("📄 {} ({} lines)", path, lines.len())];
+        result_lines.extend(numbered(&lines, 1, lines.len(), config.max_lines.max(SMALL_FILE_LINES)));
+        return CompressResult::new(content, result_lines.join("\n"));
+    }
+
+    let mut symbols: Vec<&OutlineSymbol> = outline.iter().collect();
+    symbols.sort_by(|a, b| a.line_start.cmp(&b.line_start).then(b.line_end.cmp(&a.line_end)));
+
+    let mut result_lines: Vec<String> = Vec::new();
+    result_lines.push(format!("📄 {} ({} lines, {} symbols)", path, lines.len(), symbols.len()));
+
+    let imports = import_lines(&lines, &symbols);
+    if !imports.is_empty() {
+        result_lines.push(String::new());
+        result_lines.push("Imports:".to_string());
+        for &line in imports.iter().take(MAX_IMPORT_LINES) {
+            result_lines.push(format!("{:>5}  {}", line, lines[line - 1]));
+        }
+        if imports.len() > MAX_IMPORT_LINES {
+            result_lines.push(format!("  ... +{} more import lines", imports.len() - MAX_IMPORT_LINES));
+        }
+    }
+
+    if !symbols.is_empty() {
+        result_lines.push(String::new());
+        result_lines.push("Outline:".to_string());
+        // Indent symbols nested inside an enclosing symbol's range (methods in impls/classes)
+        let mut enclosing: Vec<usize> = Vec::new();
+        for symbol in &symbols {
+            while enclosing.last().is_some_and(|&end| symbol.line_start > end) {
+                enclosing.pop();
+            }
+            // "fn parse(&self) -> u32 {" / "def parse(self):" without the body opener
+            let signature = symbol.signature.trim_end_matches(['{', ':']).trim_end();
+            result_lines.push(format!(
+                "{}  L{}-{} [{}] {}",
+                "  ".repeat(enclosing.len()),
+                symbol.line_start,
+                symbol.line_end,
+                symbol.kind,
+                if signature.is_empty() { &symbol.name } else { signature }
+            ));
+            if symbol.line_end > symbol.line_start {
+                enclosing.push(symbol.line_end);
+            }
+        }
+    }
+
+    for name in &request.symbols {
+        let matches: Vec<&&OutlineSymbol> = symbols.iter().filter(|s| &s.name == name).collect();
+        if matches.is_empty() {
+            result_lines.push(String::new());
+            result_lines.push(format!("⚠️ Symbol not found: {}", name));
+            continue;
+        }
+        for symbol in matches {
+            result_lines.push(String::new());
+            result_lines.push(format!(
+                "── {} {} (L{}-{}) ──",
+                symbol.kind, symbol.name, symbol.line_start, symbol.line_end
+            ));
+            result_lines.extend(numbered(&lines, symbol.line_start, symbol.line_end, config.max_lines));
+        }
+    }
+
+    for &(start, end) in &request.ranges {
+        let end = end.min(lines.len());
+        result_lines.push(String::new());
+        if start == 0 || start > end {
+            result_lines.push(format!("⚠️ Invalid range: L{}-{} (file has {} lines)", start, end, lines.len()));
+            continue;
+        }
+        result_lines.push(format!("── L{}-{} ──", start, end));
+        result_lines.extend(numbered(&lines, start, end, config.max_lines));
+    }
+
+    let compressed = result_lines.join("\n");
+    CompressResult::new(content, compressed)
+}
+
+/// 1-based lines of top-level import statements (`use`, `import`, `from .. import`,
+/// `#include`, `using`, `require`), including the rest of multi-line `{ .. }` / `( .. )`
+/// lists. Lines inside a symbol are skipped so function-local imports stay out.
+fn import_lines(lines: &[&str], symbols: &[&OutlineSymbol]) -> Vec<usize> {
+    const PREFIXES: &[&str] = &[
+        "use ",
+        "pub use ",
+        "pub(crate) use ",
+        "import ",
+        "from ",
+        "#include",
+        "using ",
+        "require ",
+        "require_once",
+    ];
+
+    let mut result = Vec::new();
+    let mut open_list = false;
+    for (i, line) in lines.iter().enumerate() {
+        let number = i + 1;
+        let trimmed = line.trim();
+        if open_list {
+            result.push(number);
+            open_list = !(trimmed.starts_with('}') || trimmed.starts_with(')'));
+            continue;
+        }
+        if symbols.iter().any(|s| (s.line_start..=s.line_end).contains(&number)) {
+            continue;
+        }
+        let is_import = PREFIXES.iter().any(|p| trimmed.starts_with(p))
+            && (!trimmed.starts_with("from ") || trimmed.contains(" import "));
+        if is_import || trimmed.contains("= require(") {
+            result.push(number);
+            open_list = trimmed.ends_with('{') || trimmed.ends_with('(');
+        }
+    }
+    result
+}
+
+/// Lines `start..=end` (1-based) with line numbers, truncated after `max` lines
+fn numbered(lines: &[&str], start: usize, end: usize, max: usize) -> Vec<String> {
+    let end = end.min(lines.len());
+    if start == 0 || start > end {
+        return Vec::new();
+    }
+
+    let mut result: Vec<String> = lines[start - 1..end]
+        .iter()
+        .take(max)
+        .enumerate()
+        .map(|(i, line)| format!("{:>5}  {}", start + i, line))
+        .collect();
+
+    let total = end - start + 1;
+    if total > max {
+        result.push(format!(
+            "  ... +{} more lines (request L{}-{})",
+            total - max,
+            start + max,
+            end
+        ));
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn symbol(name: &str, kind: &str, line_start: usize, line_end: usize) -> OutlineSymbol {
+        OutlineSymbol {
+            name: name.to_string(),
+            kind: kind.to_string(),
+            line_start,
+            line_end,
+            signature: String::new(),
+        }
+    }
+
+    #[test]
+    fn test_outline_with_requested_sections() {
+        let content: String = (1..=400).map(|i| format!("line {}\n", i)).collect();
+        let outline = vec![
+            symbol("helper", "function", 300, 302),
+            symbol("Parser", "struct", 10, 12),
+            symbol("impl Parser", "impl", 20, 200),
+            symbol("parse", "method", 30, 33),
+        ];
+        let request = ReadRequest {
+            symbols: vec!["parse".to_string(), "missing".to_string()],
+            ranges: vec![(399, 500)],
+        };
+        let config = CompressConfig::default();
+        let result = compress_file_read("src/parser.rs", &content, &outline, &request, &config);
+
+        assert!(result.output.starts_with("📄 src/parser.rs (400 lines, 4 symbols)"));
+        assert!(result.output.contains(
+            "Outline:\n  L10-12 [struct] Parser\n  L20-200 [impl] impl Parser\n    L30-33 [method] parse\n  L300-302 [function] helper"
+        ));
+        assert!(result.output.contains("── method parse (L30-33) ──\n   30  line 30\n   31  line 31\n   32  line 32\n   33  line 33"));
+        assert!(result.output.contains("⚠️ Symbol not found: missing"));
+        assert!(result.output.ends_with("── L399-400 ──\n  399  line 399\n  400  line 400"));
+        assert!(!result.output.contains("line 100\n"));
+        assert!(result.reduction_percent() > 80.0);
+    }
+
+    #[test]
+    fn test_imports_and_signatures() {
+        let mut content = String::from("use std::fmt;\nuse std::{\n    io,\n};\n\n");
+        content.push_str("pub fn render(x: u32) -> String {\n    use std::fmt::Write;\n");
+        content.extend((8..=300).map(|i| format!("    // line {}\n", i)));
+        content.push_str("}\n");
+        let outline = vec![OutlineSymbol {
+            signature: "pub fn render(x: u32) -> String {".to_string(),
+            ..symbol("render", "function", 6, 301)
+        }];
+        let config = CompressConfig::default();
+        let result = compress_file_read("src/render.rs", &content, &outline, &ReadRequest::default(), &config);
+
+        let expected = "Imports:\n    1  use std::fmt;\n    2  use std::{\n    3      io,\n    4  };\n\n\
+                        Outline:\n  L6-301 [function] pub fn render(x: u32) -> String";
+        assert!(result.output.contains(expected), "{}", result.output);
+        assert!(!result.output.contains("Write"));
+        assert!(!result.output.contains("line 8"));
+    }
+
+    #[test]
+    fn test_small_file_read_whole() {
+        let content = "fn main() {\n    run();\n}\n";
+        let outline = vec![symbol("main", "function", 1, 3)];
+        let config = CompressConfig::default();
+        let result = compress_file_read("src/main.rs", content, &outline, &ReadRequest::default(), &config);
+
+        assert_eq!(result.output, "📄 src/main.rs (3 lines)\n    1  fn main() {\n    2      run();\n    3  }");
+    }
+
+    #[test]
+    fn test_long_body_truncated() {
+        let lines: Vec<&str> = vec!["x"; 120];
+        let result = numbered(&lines, 1, 120, 50);
+        assert_eq!(result.len(), 51);
+        assert_eq!(result[50], "  ... +70 more lines (request L51-120)");
+    }
+}
@@ -161,6 +161,7 @@ pub fn compress_symbol_search(results: &[SymbolResult], config: &CompressConfig)
         original_size,
         compressed_size: compressed.len(),
         estimated_token_savings: original_size.saturating_sub(compressed.len()) / 4,
+        raw_output: None,
     }
 }
 
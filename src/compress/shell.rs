@@ -0,0 +1,210 @@
+//! Persistent shell sessions.
+//!
+//! One long-lived shell per session name, so `cd`, exported variables, and
+//! virtualenv activation carry over between commands. Each command's end is
+//! detected by a marker line carrying its exit code and the shell's cwd.
+
+use std::io::{BufRead, BufReader, Write};
+use std::path::Path;
+use std::process::{Child, ChildStdin, Command, Stdio};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError};
+use std::time::{Duration, Instant};
+
+use super::bash::{compress_for_command, kill_process_group};
+use super::{redact, CompressConfig, CompressResult};
+
+/// A shell process kept alive between commands
+pub struct ShellSession {
+    child: Child,
+    stdin: ChildStdin,
+    lines: Receiver<String>,
+    marker: String,
+}
+
+/// Raw result of one command in a session
+#[derive(Debug, Clone)]
+pub struct ShellOutput {
+    pub output: String,
+    /// `None` if the shell exited or timed out before the command finished
+    pub exit_code: Option<i32>,
+    pub cwd: Option<String>,
+    pub timed_out: bool,
+}
+
+impl ShellSession {
+    /// Start a shell (bash if available, else sh) in `cwd`
+    pub fn start(cwd: &Path) -> Result<Self, String> {
+        let spawn = |program: &str, args: &[&str]| {
+            let mut cmd = Command::new(program);
+            cmd.args(args)
+                .current_dir(cwd)
+                .stdin(Stdio::piped())
+                .stdout(Stdio::piped())
+                .stderr(Stdio::null());
+            #[cfg(unix)]
+            std::os::unix::process::CommandExt::process_group(&mut cmd, 0);
+            cmd.spawn()
+        };
+        let mut child = spawn("bash", &["--noprofile", "--norc"])
+            .or_else(|_| spawn("sh", &[]))
+            .map_err(|e| format!("Failed to start shell: {}", e))?;
+
+        let stdin = child.stdin.take().ok_or("Failed to open shell stdin")?;
+        let stdout = child.stdout.take().ok_or("Failed to open shell stdout")?;
+
+        // Lines are forwarded over a channel so reads can time out
+        let (sender, lines) = mpsc::channel();
+        std::thread::spawn(move || {
+            let mut reader = BufReader::new(stdout);
+            let mut line = Vec::new();
+            while matches!(reader.read_until(b'\n', &mut line), Ok(n) if n > 0) {
+                let text = String::from_utf8_lossy(&line).trim_end_matches(['\n', '\r']).to_string();
+                if sender.send(text).is_err() {
+                    break;
+                }
+                line.clear();
+            }
+        });
+
+        Ok(Self {
+            child,
+            stdin,
+            lines,
+            marker: format!("__CODEGRAPH_DONE_{}__", uuid::Uuid::new_v4().simple()),
+        })
+    }
+
+    /// Run a command in the session. Stdin is empty; stderr is merged into the output.
+    /// On timeout the shell is killed and the session should be discarded.
+    pub fn run(&mut self, command: &str, timeout: Duration) -> Result<ShellOutput, String> {
+        // A brace group runs in the current shell, so state changes persist
+        let script = format!(
+            "{{ {}\n}} < /dev/null 2>&1\nprintf '\\n{} %s %s\\n' \"$?\" \"$PWD\"\n",
+            command, self.marker
+        );
+        self.stdin
+            .write_all(script.as_bytes())
+            .and_then(|_| self.stdin.flush())
+            .map_err(|e| format!("Shell session has exited: {}", e))?;
+
+        let deadline = Instant::now() + timeout;
+        let mut output: Vec<String> = Vec::new();
+        loop {
+            let remaining = deadline.saturating_duration_since(Instant::now());
+            match self.lines.recv_timeout(remaining) {
+                Ok(line) => {
+                    if let Some(status) = line.strip_prefix(&self.marker) {
+                        // Drop the blank line printed before the marker
+                        if output.last().is_some_and(|l| l.is_empty()) {
+                            output.pop();
+                        }
+                        let (code, cwd) = status.trim_start().split_once(' ').unwrap_or((status.trim(), ""));
+                        return Ok(ShellOutput {
+                            output: output.join("\n"),
+                            exit_code: code.parse().ok(),
+                            cwd: Some(cwd.to_string()).filter(|c| !c.is_empty()),
+                            timed_out: false,
+                        });
+                    }
+                    output.push(line);
+                }
+                Err(RecvTimeoutError::Timeout) => {
+                    kill_process_group(&mut self.child);
+                    let _ = self.child.wait();
+                    return Ok(ShellOutput {
+                        output: output.join("\n"),
+                        exit_code: None,
+                        cwd: None,
+                        timed_out: true,
+                    });
+                }
+                // The command exited the shell
+                Err(RecvTimeoutError::Disconnected) => {
+                    let _ = self.child.wait();
+                    return Ok(ShellOutput {
+                        output: output.join("\n"),
+                        exit_code: None,
+                        cwd: None,
+                        timed_out: false,
+                    });
+                }
+            }
+        }
+    }
+}
+
+impl Drop for ShellSession {
+    fn drop(&mut self) {
+        if matches!(self.child.try_wait(), Ok(None)) {
+            kill_process_group(&mut self.child);
+            let _ = self.child.wait();
+        }
+    }
+}
+
+/// Compress one session command's output, with a header for exit code and cwd
+pub fn compress_shell_output(command: &str, result: &ShellOutput, config: &CompressConfig) -> CompressResult {
+    let (output, redacted) = redact::redact_secrets(&result.output);
+    let mut compressed = compress_for_command(command, &output, config);
+
+    let status = match (result.exit_code, result.timed_out) {
+        (_, true) => "⏱️ timed out (shell killed, session reset)".to_string(),
+        (Some(code), _) => format!("exit {}", code),
+        (None, _) => "shell exited (session reset)".to_string(),
+    };
+    let header = match &result.cwd {
+        Some(cwd) => format!("{} · {}", status, cwd),
+        None => status,
+    };
+
+    compressed.output = if compressed.output.is_empty() {
+        header
+    } else {
+        format!("{}\n{}", header, compressed.output)
+    };
+    if redacted > 0 {
+        compressed.output.push_str(&format!("\n🔒 {} secrets redacted", redacted));
+    }
+    compressed
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_shell_session_keeps_state() {
+        let dir = tempfile::TempDir::new().unwrap();
+        std::fs::create_dir(dir.path().join("sub")).unwrap();
+        let mut session = ShellSession::start(dir.path()).unwrap();
+        let timeout = Duration::from_secs(10);
+
+        let result = session.run("cd sub && export GREETING=hello", timeout).unwrap();
+        assert_eq!(result.exit_code, Some(0));
+        assert!(result.cwd.unwrap().ends_with("/sub"));
+
+        let result = session.run("echo $GREETING; echo oops >&2; false", timeout).unwrap();
+        assert_eq!(result.output, "hello\noops");
+        assert_eq!(result.exit_code, Some(1));
+
+        let config = CompressConfig::default();
+        let compressed = compress_shell_output("echo", &result, &config);
+        assert!(compressed.output.starts_with("exit 1 · "), "{}", compressed.output);
+    }
+
+    #[test]
+    fn test_shell_session_timeout_and_exit() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let mut session = ShellSession::start(dir.path()).unwrap();
+
+        let result = session.run("echo started; sleep 30", Duration::from_millis(300)).unwrap();
+        assert!(result.timed_out);
+        assert_eq!(result.output, "started");
+
+        let mut session = ShellSession::start(dir.path()).unwrap();
+        let result = session.run("exit 3", Duration::from_secs(10)).unwrap();
+        assert_eq!(result.exit_code, None);
+        assert!(!result.timed_out);
+        assert!(session.run("echo again", Duration::from_secs(1)).is_err());
+    }
+}
@@ -0,0 +1,359 @@
+//! Stack trace compression - Rust panics, Python tracebacks, JS stack traces.
+//!
+//! Keeps the error message and the first project-local frames, collapsing
+//! stdlib, dependency, and runtime frames into "… N framework frames". Frames at
+//! absolute paths outside `project_root` count as framework frames too, and
+//! `link_frames` tags the kept frames with the graph symbols they point into.
+
+use once_cell::sync::Lazy;
+use regex::Regex;
+use std::path::Path;
+
+use super::{truncate_with_summary, CompressConfig, CompressResult};
+
+/// Project frames kept before the rest are collapsed
+const MAX_PROJECT_FRAMES: usize = 5;
+
+/// Rust backtrace frame: `   3: myapp::main`
+static RUST_FRAME: Lazy<Regex> = Lazy::new(|| Regex::new(r"^\s*\d+:\s+\S").unwrap());
+
+/// Python frame location: `File "app/main.py", line 12, in run`
+static PYTHON_LOCATION: Lazy<Regex> = Lazy::new(|| Regex::new(r#"^File "([^"]+)", line (\d+)"#).unwrap());
+
+/// Rust/JS frame location: `at ./src/main.rs:5:9`, `at fn (/app/src/routes.js:1:5)`
+static AT_LOCATION: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"^at (?:.*?\()?(?:file://)?([^()\s]+?):(\d+)(?::\d+)?\)?$").unwrap());
+
+/// Locations that belong to the standard library, dependencies, or the runtime
+const FRAMEWORK_LOCATIONS: &[&str] = &[
+    "/rustc/",
+    "/library/std/",
+    "/library/core/",
+    "/library/alloc/",
+    ".cargo/registry",
+    ".cargo/git",
+    "site-packages",
+    "dist-packages",
+    "/lib/python",
+    "<frozen",
+    "node_modules",
+    "node:",
+    "(internal/",
+    "<anonymous>",
+];
+
+/// Rust symbols from the standard library or common runtimes (used when a frame has no location)
+const FRAMEWORK_SYMBOLS: &[&str] = &[
+    "std::", "core::", "alloc::", "tokio::", "futures::", "rust_begin_unwind", "__", "_start",
+];
+
+/// Compress output containing a stack trace. Returns `None` when it doesn't look like one.
+pub fn compress_stack_trace(output: &str, config: &CompressConfig) -> Option<CompressResult> {
+    let segments = parse_segments(output, config.project_root.as_deref());
+    // A single frame-like line is more likely ordinary output than a trace
+    let frames = segments
+        .iter()
+        .filter(|s| matches!(s, Segment::Frame { .. }))
+        .count();
+    if frames < 2 {
+        return None;
+    }
+
+    let mut result_lines: Vec<String> = Vec::new();
+    let mut project_frames = 0;
+    let mut hidden_framework = 0;
+    let mut hidden_project = 0;
+
+    let flush = |lines: &mut Vec<String>, framework: &mut usize, project: &mut usize| {
+        if *framework > 0 {
+            lines.push(format!("  … {} framework {}", framework, plural_frames(*framework)));
+            *framework = 0;
+        }
+        if *project > 0 {
+            lines.push(format!("  … {} more project {}", project, plural_frames(*project)));
+            *project = 0;
+        }
+    };
+
+    for segment in &segments {
+        match segment {
+            Segment::Text(line) => {
+                flush(&mut result_lines, &mut hidden_framework, &mut hidden_project);
+                if !line.trim().is_empty() {
+                    result_lines.push(line.to_string());
+                }
+            }
+            Segment::Frame { local: false, .. } => {
+                hidden_framework += 1;
+            }
+            Segment::Frame { lines, local: true } => {
+                if project_frames < MAX_PROJECT_FRAMES {
+                    flush(&mut result_lines, &mut hidden_framework, &mut hidden_project);
+                    result_lines.extend(lines.iter().map(|l| l.to_string()));
+                    project_frames += 1;
+                } else {
+                    hidden_project += 1;
+                }
+            }
+        }
+    }
+    flush(&mut result_lines, &mut hidden_framework, &mut hidden_project);
+
+    let compressed = truncate_with_summary(&result_lines, config.max_lines);
+    Some(CompressResult::new(output, compressed))
+}
+
+/// A piece of crash output: plain text (messages, headers) or one stack frame
+enum Segment<'a> {
+    Text(&'a str),
+    Frame { lines: Vec<&'a str>, local: bool },
+}
+
+/// Append ` → <symbol id>` to each project frame that `resolve` maps to a symbol. `resolve`
+/// gets the frame's path relative to `project_root` and its 1-based line.
+pub fn link_frames(output: &str, project_root: &Path, resolve: impl Fn(&str, usize) -> Option<String>) -> String {
+    let mut linked = false;
+    let lines: Vec<String> = output
+        .lines()
+        .map(|line| {
+            let symbol = frame_location(line.trim())
+                .filter(|(path, _)| !is_framework_location(path))
+                .and_then(|(path, number)| resolve(&relative_path(path, project_root)?, number));
+            match symbol {
+                Some(symbol) => {
+                    linked = true;
+                    format!("{} → {}", line, symbol)
+                }
+                None => line.to_string(),
+            }
+        })
+        .collect();
+
+    if linked {
+        lines.join("\n")
+    } else {
+        output.to_string()
+    }
+}
+
+/// Split output into text lines and stack frames
+fn parse_segments<'a>(output: &'a str, project_root: Option<&Path>) -> Vec<Segment<'a>> {
+    let lines: Vec<&str> = output.lines().collect();
+    let mut segments = Vec::new();
+    let mut i = 0;
+
+    while i < lines.len() {
+        let line = lines[i];
+        let trimmed = line.trim();
+
+        // Rust: "  3: symbol" optionally followed by "      at path:line:col"
+        if RUST_FRAME.is_match(line) {
+            let symbol = trimmed.split_once(": ").map(|(_, s)| s).unwrap_or(trimmed);
+            let mut frame = vec![line];
+            let local = match lines.get(i + 1).and_then(|l| l.trim().strip_prefix("at ")) {
+                Some(location) => {
+                    frame.push(lines[i + 1]);
+                    i += 1;
+                    is_project_location(location, project_root)
+                }
+                None => !FRAMEWORK_SYMBOLS.iter().any(|s| symbol.starts_with(s)),
+            };
+            segments.push(Segment::Frame { lines: frame, local });
+        }
+        // Python: '  File "path", line N, in func' followed by the (more indented) source line
+        else if let Some(rest) = trimmed.strip_prefix("File \"") {
+            let mut frame = vec![line];
+            if let Some(next) = lines.get(i + 1) {
+                if indent(next) > indent(line) && !next.trim().starts_with("File \"") {
+                    frame.push(next);
+                    i += 1;
+                }
+            }
+            let path = rest.split('"').next().unwrap_or(rest);
+            segments.push(Segment::Frame { lines: frame, local: is_project_location(path, project_root) });
+        }
+        // JS: "    at fn (path:line:col)" or "    at path:line:col"
+        else if trimmed.starts_with("at ") && (trimmed.contains(':') || trimmed.contains('(')) {
+            let path = frame_location(trimmed).map_or(trimmed, |(path, _)| path);
+            segments.push(Segment::Frame {
+                lines: vec![line],
+                local: !is_framework_location(trimmed) && is_project_location(path, project_root),
+            });
+        } else {
+            segments.push(Segment::Text(line));
+        }
+
+        i += 1;
+    }
+
+    segments
+}
+
+fn plural_frames(count: usize) -> &'static str {
+    if count == 1 {
+        "frame"
+    } else {
+        "frames"
+    }
+}
+
+fn is_framework_location(location: &str) -> bool {
+    FRAMEWORK_LOCATIONS.iter().any(|f| location.contains(f))
+}
+
+/// Not a framework location, and inside `project_root` when the path is absolute
+fn is_project_location(location: &str, project_root: Option<&Path>) -> bool {
+    if is_framework_location(location) {
+        return false;
+    }
+    let path = location.split(':').next().unwrap_or(location);
+    match project_root {
+        Some(root) if Path::new(path).is_absolute() => Path::new(path).starts_with(root),
+        _ => true,
+    }
+}
+
+/// (path, line) of a trimmed frame line that names its source location
+fn frame_location(line: &str) -> Option<(&str, usize)> {
+    let caps = PYTHON_LOCATION.captures(line).or_else(|| AT_LOCATION.captures(line))?;
+    let path = caps.get(1)?.as_str();
+    Some((path, caps[2].parse().ok()?))
+}
+
+/// `path` relative to `project_root` without a leading `./`; `None` if it lies outside
+fn relative_path(path: &str, project_root: &Path) -> Option<String> {
+    let relative = match Path::new(path).strip_prefix(project_root) {
+        Ok(relative) => relative,
+        Err(_) if Path::new(path).is_absolute() => return None,
+        Err(_) => Path::new(path),
+    };
+    Some(relative.to_string_lossy().trim_start_matches("./").to_string())
+}
+
+fn indent(line: &str) -> usize {
+    line.len() - line.trim_start().len()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_compress_rust_panic() {
+        let input = r#"thread 'main' panicked at src/main.rs:5:9:
+index out of bounds: the len is 3 but the index is 10
+stack backtrace:
+   0: rust_begin_unwind
+             at /rustc/abc123/library/std/src/panicking.rs:645:5
+   1: core::panicking::panic_fmt
+             at /rustc/abc123/library/core/src/panicking.rs:72:14
+   2: core::panicking::panic_bounds_check
+             at /rustc/abc123/library/core/src/panicking.rs:208:5
+   3: myapp::parse
+             at ./src/main.rs:5:9
+   4: myapp::main
+             at ./src/main.rs:10:5
+   5: core::ops::function::FnOnce::call_once
+             at /rustc/abc123/library/core/src/ops/function.rs:250:5
+note: Some details are omitted, run with `RUST_BACKTRACE=full` for a verbose backtrace.
+"#;
+        let config = CompressConfig::default();
+        let result = compress_stack_trace(input, &config).unwrap();
+
+        assert!(result.output.contains("index out of bounds"));
+        assert!(result.output.contains("  … 3 framework frames\n   3: myapp::parse"));
+        assert!(result.output.contains("at ./src/main.rs:10:5\n  … 1 framework frame\n"));
+        assert!(!result.output.contains("panic_fmt"));
+    }
+
+    #[test]
+    fn test_compress_python_traceback() {
+        let input = r#"Traceback (most recent call last):
+  File "/app/main.py", line 12, in <module>
+    run()
+  File "/app/main.py", line 8, in run
+    requests.get(url)
+  File "/usr/lib/python3.11/site-packages/requests/api.py", line 73, in get
+    return request("get", url, params=params, **kwargs)
+  File "/usr/lib/python3.11/site-packages/requests/api.py", line 59, in request
+    return session.request(method=method, url=url, **kwargs)
+requests.exceptions.ConnectionError: connection refused
+"#;
+        let config = CompressConfig::default();
+        let result = compress_stack_trace(input, &config).unwrap();
+
+        assert!(result.output.starts_with("Traceback (most recent call last):"));
+        assert!(result.output.contains("    requests.get(url)\n  … 2 framework frames\n"));
+        assert!(result.output.ends_with("requests.exceptions.ConnectionError: connection refused"));
+    }
+
+    #[test]
+    fn test_compress_js_stack() {
+        let mut input = String::from("TypeError: Cannot read properties of undefined (reading 'id')\n");
+        for i in 0..7 {
+            input.push_str(&format!("    at handler{} (/app/src/routes.js:{}:5)\n", i, i + 1));
+        }
+        input.push_str("    at Layer.handle (/app/node_modules/express/lib/router/layer.js:95:5)\n");
+        input.push_str("    at process.processTicksAndRejections (node:internal/process/task_queues:95:5)\n");
+
+        let config = CompressConfig::default();
+        let result = compress_stack_trace(&input, &config).unwrap();
+
+        assert!(result.output.contains("at handler4 "));
+        assert!(!result.output.contains("at handler5 "));
+        assert!(result.output.ends_with("  … 2 framework frames\n  … 2 more project frames"));
+        assert!(compress_stack_trace("just some output", &config).is_none());
+    }
+
+    #[test]
+    fn test_frames_outside_project_root() {
+        let input = r#"Traceback (most recent call last):
+  File "/srv/app/main.py", line 12, in <module>
+    run()
+  File "/opt/tools/runner.py", line 40, in run
+    job()
+  File "/srv/app/jobs.py", line 8, in job
+    raise ValueError("bad input")
+ValueError: bad input
+"#;
+        let config = CompressConfig {
+            project_root: Some("/srv/app".into()),
+            ..Default::default()
+        };
+        let result = compress_stack_trace(input, &config).unwrap();
+
+        assert!(result.output.contains("    run()\n  … 1 framework frame\n  File \"/srv/app/jobs.py\""));
+        assert!(!result.output.contains("runner.py"));
+
+        // Without a root, only the framework markers decide
+        let result = compress_stack_trace(input, &CompressConfig::default()).unwrap();
+        assert!(result.output.contains("runner.py"));
+    }
+
+    #[test]
+    fn test_link_frames() {
+        let output = [
+            "thread 'main' panicked at src/main.rs:5:9:",
+            "   3: myapp::parse",
+            "             at ./src/main.rs:5:9",
+            "  File \"/srv/app/jobs.py\", line 8, in job",
+            "    at handler (/srv/app/src/routes.js:3:5)",
+            "    at Layer.handle (/srv/app/node_modules/express/lib/router/layer.js:95:5)",
+            "  File \"/usr/lib/x.py\", line 1",
+        ]
+        .join("\n");
+        let resolve = |path: &str, line: usize| match (path, line) {
+            ("src/main.rs", 5) => Some("src/main.rs::parse".to_string()),
+            ("jobs.py", 8) => Some("jobs.py::job".to_string()),
+            ("src/routes.js", 3) => Some("src/routes.js::handler".to_string()),
+            _ => panic!("unexpected lookup {}:{}", path, line),
+        };
+        let linked = link_frames(&output, Path::new("/srv/app"), resolve);
+
+        assert!(linked.contains("at ./src/main.rs:5:9 → src/main.rs::parse\n"));
+        assert!(linked.contains("line 8, in job → jobs.py::job\n"));
+        assert!(linked.contains("(/srv/app/src/routes.js:3:5) → src/routes.js::handler\n"));
+        assert!(linked.starts_with("thread 'main' panicked at src/main.rs:5:9:\n"));
+        assert!(linked.ends_with("layer.js:95:5)\n  File \"/usr/lib/x.py\", line 1"));
+    }
+}
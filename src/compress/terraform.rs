@@ -0,0 +1,227 @@
+//! Terraform plan compression.
+//!
+//! Reduces plans to add/change/destroy counts per resource type. Destroyed and
+//! replaced resources are always listed in full so destructive changes stay reviewable.
+
+use std::collections::HashMap;
+
+use super::{CompressConfig, CompressResult};
+
+/// Compress `terraform plan` (or OpenTofu) output
+pub fn compress_terraform_plan(output: &str, _config: &CompressConfig) -> CompressResult {
+    let lines: Vec<&str> = output.lines().collect();
+
+    if lines.is_empty() {
+        return CompressResult::new(output, String::new());
+    }
+
+    let mut changes: Vec<(String, Action)> = Vec::new();
+    let mut plan_line: Option<&str> = None;
+    let mut no_changes: Option<&str> = None;
+    let mut errors: Vec<String> = Vec::new();
+
+    for line in &lines {
+        // Newer versions frame diagnostics in box-drawing characters
+        let trimmed = line.trim().trim_start_matches(['│', '╷', '╵']).trim();
+
+        if let Some(header) = trimmed.strip_prefix("# ") {
+            if let Some((address, action)) = parse_change_header(header) {
+                changes.push((address.to_string(), action));
+            }
+        } else if trimmed.starts_with("Plan: ") {
+            plan_line = Some(trimmed);
+        } else if trimmed.starts_with("No changes.") {
+            no_changes = Some(trimmed);
+        } else if trimmed.starts_with("Error: ") {
+            errors.push(trimmed.to_string());
+        }
+    }
+
+    let mut result_lines: Vec<String> = Vec::new();
+
+    if !errors.is_empty() {
+        result_lines.push(format!("❌ {} errors", errors.len()));
+        result_lines.extend(errors.iter().map(|e| format!("  {}", e)));
+    }
+
+    if let Some(no_changes) = no_changes {
+        result_lines.push(format!("✅ {}", no_changes));
+    }
+
+    if let Some(plan) = plan_line {
+        result_lines.push(format!("📋 {}", plan));
+    }
+
+    // Counts per resource type, in order of first appearance
+    let mut type_order: Vec<String> = Vec::new();
+    let mut by_type: HashMap<String, HashMap<Action, usize>> = HashMap::new();
+    for (address, action) in &changes {
+        let resource_type = resource_type(address);
+        if !by_type.contains_key(&resource_type) {
+            type_order.push(resource_type.clone());
+        }
+        *by_type
+            .entry(resource_type)
+            .or_default()
+            .entry(*action)
+            .or_insert(0) += 1;
+    }
+
+    for resource_type in &type_order {
+        let counts = &by_type[resource_type];
+        let summary: Vec<String> = Action::ALL
+            .iter()
+            .filter_map(|action| counts.get(action).map(|n| format!("{}{}", action.symbol(), n)))
+            .collect();
+        result_lines.push(format!("  {}: {}", resource_type, summary.join(" ")));
+    }
+
+    // Destructive changes are never truncated
+    let destroyed: Vec<&(String, Action)> = changes
+        .iter()
+        .filter(|(_, action)| matches!(action, Action::Destroy | Action::Replace))
+        .collect();
+    if !destroyed.is_empty() {
+        result_lines.push(String::new());
+        result_lines.push(format!("⚠️ {} resources destroyed:", destroyed.len()));
+        for (address, action) in destroyed {
+            let note = if *action == Action::Replace { " (replaced)" } else { "" };
+            result_lines.push(format!("  {} {}{}", action.symbol(), address, note));
+        }
+    }
+
+    let compressed = result_lines.join("\n");
+    CompressResult::new(output, compressed)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+enum Action {
+    Create,
+    Update,
+    Replace,
+    Destroy,
+    Read,
+}
+
+impl Action {
+    const ALL: [Action; 5] = [
+        Action::Create,
+        Action::Update,
+        Action::Replace,
+        Action::Destroy,
+        Action::Read,
+    ];
+
+    fn symbol(&self) -> &'static str {
+        match self {
+            Action::Create => "+",
+            Action::Update => "~",
+            Action::Replace => "-/+",
+            Action::Destroy => "-",
+            Action::Read => "<=",
+        }
+    }
+}
+
+/// Parse "aws_instance.web will be created" into its address and action
+fn parse_change_header(header: &str) -> Option<(&str, Action)> {
+    let suffixes = [
+        (" will be created", Action::Create),
+        (" will be updated in-place", Action::Update),
+        (" must be replaced", Action::Replace),
+        (" will be replaced", Action::Replace),
+        (" will be destroyed", Action::Destroy),
+        (" will be read during apply", Action::Read),
+    ];
+    suffixes
+        .iter()
+        .find_map(|(suffix, action)| header.strip_suffix(suffix).map(|address| (address, *action)))
+}
+
+/// Resource type from an address: "module.vpc.aws_subnet.a[0]" → "aws_subnet",
+/// "data.aws_ami.ubuntu" → "data.aws_ami"
+fn resource_type(address: &str) -> String {
+    let without_index = address.split('[').next().unwrap_or(address);
+    let parts: Vec<&str> = without_index.split('.').collect();
+    if parts.len() < 2 {
+        return address.to_string();
+    }
+    let resource_type = parts[parts.len() - 2];
+    if parts.len() >= 3 && parts[parts.len() - 3] == "data" {
+        format!("data.{}", resource_type)
+    } else {
+        resource_type.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_compress_terraform_plan() {
+        let input = r#"Terraform used the selected providers to generate the following execution plan.
+Resource actions are indicated with the following symbols:
+  + create
+  ~ update in-place
+  - destroy
+-/+ destroy and then create replacement
+
+Terraform will perform the following actions:
+
+  # aws_instance.web[0] will be created
+  + resource "aws_instance" "web" {
+      + ami           = "ami-123"
+      + instance_type = "t3.micro"
+    }
+
+  # aws_instance.web[1] will be created
+  + resource "aws_instance" "web" {
+      + ami = "ami-123"
+    }
+
+  # module.vpc.aws_subnet.private["a"] will be updated in-place
+  ~ resource "aws_subnet" "private" {
+      ~ tags = {
+          + "Env" = "prod"
+        }
+    }
+
+  # aws_db_instance.main must be replaced
+-/+ resource "aws_db_instance" "main" {
+      ~ engine_version = "13" -> "15" # forces replacement
+    }
+
+  # aws_s3_bucket.logs will be destroyed
+  - resource "aws_s3_bucket" "logs" {
+      - bucket = "logs" -> null
+    }
+
+Plan: 3 to add, 1 to change, 2 to destroy.
+"#;
+        let config = CompressConfig::default();
+        let result = compress_terraform_plan(input, &config);
+
+        assert_eq!(
+            result.output,
+            "📋 Plan: 3 to add, 1 to change, 2 to destroy.\n  aws_instance: +2\n  aws_subnet: ~1\n  aws_db_instance: -/+1\n  aws_s3_bucket: -1\n\n⚠️ 2 resources destroyed:\n  -/+ aws_db_instance.main (replaced)\n  - aws_s3_bucket.logs"
+        );
+    }
+
+    #[test]
+    fn test_terraform_no_changes_and_errors() {
+        let config = CompressConfig::default();
+        let result = compress_terraform_plan(
+            "Refreshing state...\n\nNo changes. Your infrastructure matches the configuration.\n",
+            &config,
+        );
+        assert_eq!(result.output, "✅ No changes. Your infrastructure matches the configuration.");
+
+        let result = compress_terraform_plan(
+            "╷\n│ Error: Unsupported argument\n│\n│   on main.tf line 3, in resource \"aws_instance\" \"web\":\n╵\n",
+            &config,
+        );
+        assert!(result.output.starts_with("❌ 1 errors\n  Error: Unsupported argument"));
+        assert_eq!(resource_type("data.aws_ami.ubuntu"), "data.aws_ami");
+    }
+}
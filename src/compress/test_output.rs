@@ -2,6 +2,11 @@
 //!
 //! Achieves ~90% reduction on test runner output.
 
+use std::collections::HashMap;
+
+use once_cell::sync::Lazy;
+use regex::Regex;
+
 use super::{CompressConfig, CompressResult, deduplicate_lines};
 
 /// Compress test output by extracting failures only
@@ -13,7 +18,12 @@ pub fn compress_test_output(output: &str, config: &CompressConfig) -> CompressRe
     }
 
     // Detect test framework
-    if output.contains("running ") && output.contains(" test") {
+    if is_go_test_output(output) {
+        return compress_go_test(output, config);
+    } else if is_pytest_output(output) {
+        // Before jest: pytest's PASSED/FAILED would match jest's PASS/FAIL
+        return compress_pytest(output, config);
+    } else if output.contains("running ") && output.contains(" test") {
         return compress_cargo_test(output, config);
     } else if output.contains("PASS") || output.contains("FAIL") {
         return compress_jest_test(output, config);
@@ -212,66 +222,403 @@ fn compress_jest_test(output: &str, config: &CompressConfig) -> CompressResult {
 fn compress_pytest(output: &str, config: &CompressConfig) -> CompressResult {
     let lines: Vec<&str> = output.lines().collect();
 
-    let mut passed = 0;
-    let mut failed = 0;
-    let mut failures: Vec<TestFailure> = Vec::new();
-    let mut in_failure = false;
-    let mut current_failure: Option<TestFailure> = None;
+    let mut counts: HashMap<String, usize> = HashMap::new();
+    let mut duration: Option<String> = None;
+    // `-v` results per module, in first-seen order
+    let mut modules: Vec<(String, ModuleCounts)> = Vec::new();
+    let mut verbose = ModuleCounts::default();
+    let mut slowest: Vec<(f64, String)> = Vec::new();
+    // Failure blocks from the FAILURES section, keyed by block title
+    let mut blocks: Vec<TestFailure> = Vec::new();
+    let mut summary_failures: Vec<TestFailure> = Vec::new();
+    let mut section = PytestSection::Other;
 
     for line in &lines {
         let trimmed = line.trim();
 
-        // Count from summary line
-        if trimmed.contains(" passed") || trimmed.contains(" failed") {
-            // Parse "X passed, Y failed"
-            for part in trimmed.split(',') {
-                let part = part.trim();
-                if part.ends_with("passed") {
-                    if let Some(num) = part.split_whitespace().next() {
-                        passed = num.parse().unwrap_or(0);
-                    }
-                } else if part.ends_with("failed") {
-                    if let Some(num) = part.split_whitespace().next() {
-                        failed = num.parse().unwrap_or(0);
+        if let Some(caps) = PYTEST_FINAL_SUMMARY.captures(trimmed) {
+            counts.clear();
+            for count in PYTEST_COUNT.captures_iter(&caps[1]) {
+                let outcome = count[2].trim_end_matches('s').replace("warning", "warnings");
+                *counts.entry(outcome).or_default() += count[1].parse::<usize>().unwrap_or(0);
+            }
+            duration = Some(caps[2].to_string());
+            continue;
+        }
+
+        if let Some(caps) = PYTEST_SECTION.captures(trimmed) {
+            let title = caps[1].to_lowercase();
+            section = if title == "failures" || title == "errors" {
+                PytestSection::Failures
+            } else if title.starts_with("slowest") && title.ends_with("durations") {
+                PytestSection::Durations
+            } else if title == "short test summary info" {
+                PytestSection::ShortSummary
+            } else {
+                PytestSection::Other
+            };
+            continue;
+        }
+
+        if let Some((nodeid, outcome)) = parse_pytest_verbose(trimmed) {
+            let module = nodeid.split("::").next().unwrap_or(nodeid).to_string();
+            let index = match modules.iter().position(|(m, _)| *m == module) {
+                Some(index) => index,
+                None => {
+                    modules.push((module, ModuleCounts::default()));
+                    modules.len() - 1
+                }
+            };
+            modules[index].1.record(outcome);
+            verbose.record(outcome);
+            continue;
+        }
+
+        match section {
+            PytestSection::Failures => {
+                if let Some(caps) = PYTEST_BLOCK.captures(trimmed) {
+                    blocks.push(TestFailure {
+                        name: caps[1].to_string(),
+                        message: String::new(),
+                        location: String::new(),
+                    });
+                } else if let Some(block) = blocks.last_mut() {
+                    if let Some(message) = trimmed.strip_prefix("E ") {
+                        if block.message.is_empty() {
+                            block.message = message.trim().to_string();
+                        }
+                    } else if let Some(caps) = PYTEST_LOCATION.captures(trimmed) {
+                        // The last location is where the assertion failed
+                        block.location = caps[1].to_string();
                     }
                 }
             }
+            PytestSection::Durations => {
+                if let Some(caps) = PYTEST_DURATION.captures(trimmed) {
+                    let seconds = caps[1].parse().unwrap_or(0.0);
+                    slowest.push((seconds, format!("{} ({})", &caps[3], &caps[2])));
+                }
+            }
+            PytestSection::ShortSummary | PytestSection::Other => {
+                // "FAILED tests/test_api.py::test_post - assert 1 == 2"
+                let entry = trimmed
+                    .strip_prefix("FAILED ")
+                    .or_else(|| trimmed.strip_prefix("ERROR "));
+                if let Some(entry) = entry {
+                    let (name, message) = entry.split_once(" - ").unwrap_or((entry, ""));
+                    summary_failures.push(TestFailure {
+                        name: name.trim().to_string(),
+                        message: message.trim().to_string(),
+                        location: String::new(),
+                    });
+                }
+            }
         }
+    }
 
-        // Capture failures
-        if trimmed.starts_with("FAILED") || trimmed.starts_with("E ") {
-            in_failure = true;
-            if trimmed.starts_with("FAILED") {
-                let name = trimmed.strip_prefix("FAILED ").unwrap_or(trimmed);
-                current_failure = Some(TestFailure {
-                    name: name.to_string(),
-                    message: String::new(),
-                    location: String::new(),
-                });
-            } else if let Some(ref mut f) = current_failure {
-                if f.message.is_empty() {
-                    f.message = trimmed.strip_prefix("E ").unwrap_or(trimmed).to_string();
+    // Without a final summary line (e.g. interrupted runs), count the -v results
+    if counts.is_empty() {
+        counts.insert("passed".to_string(), verbose.passed);
+        counts.insert("failed".to_string(), verbose.failed);
+        counts.insert("skipped".to_string(), verbose.skipped);
+        counts.insert("error".to_string(), verbose.errors);
+    }
+
+    // The short summary has full node IDs; blocks add the location and message
+    let failures: Vec<TestFailure> = if summary_failures.is_empty() {
+        blocks
+    } else {
+        summary_failures
+            .into_iter()
+            .map(|mut failure| {
+                let short_name = failure.name.rsplit("::").next().unwrap_or(&failure.name).to_string();
+                let class_name = failure.name.split("::").skip(1).collect::<Vec<_>>().join(".");
+                if let Some(block) = blocks
+                    .iter()
+                    .find(|b| b.name == class_name || b.name == short_name || b.name.ends_with(&format!(" {}", short_name)))
+                {
+                    if failure.message.is_empty() {
+                        failure.message = block.message.clone();
+                    }
+                    failure.location = block.location.clone();
                 }
+                failure
+            })
+            .collect()
+    };
+
+    // Format output
+    let count = |outcome: &str| counts.get(outcome).copied().unwrap_or(0);
+    let (passed, failed, skipped, errors) = (count("passed"), count("failed"), count("skipped"), count("error"));
+    let mut result_lines: Vec<String> = Vec::new();
+
+    let status_emoji = if failed > 0 || errors > 0 { "❌" } else { "✅" };
+    let mut summary = format!("{} {} passed, {} failed", status_emoji, passed, failed);
+    if skipped > 0 {
+        summary.push_str(&format!(", {} skipped", skipped));
+    }
+    if errors > 0 {
+        summary.push_str(&format!(", {} errors", errors));
+    }
+    if let Some(duration) = duration {
+        summary.push_str(&format!(" in {}", duration));
+    }
+    result_lines.push(summary);
+
+    // Per-module counts are only worth showing when there's more than one module
+    if modules.len() > 1 {
+        result_lines.push("Modules:".to_string());
+        // Modules with failures first
+        modules.sort_by_key(|(_, counts)| counts.failed + counts.errors == 0);
+        for (module, counts) in modules.iter().take(config.max_items_per_group) {
+            let emoji = if counts.failed + counts.errors > 0 { "❌" } else { "✅" };
+            result_lines.push(format!("  {} {}: {}", emoji, module, counts.describe()));
+        }
+        if modules.len() > config.max_items_per_group {
+            result_lines.push(format!("  ... +{} more modules", modules.len() - config.max_items_per_group));
+        }
+    }
+
+    if !slowest.is_empty() {
+        slowest.sort_by(|a, b| b.0.total_cmp(&a.0));
+        result_lines.push("Slowest:".to_string());
+        for (seconds, test) in slowest.iter().take(SLOWEST_TESTS) {
+            result_lines.push(format!("  {:.2}s {}", seconds, test));
+        }
+    }
+
+    if !failures.is_empty() {
+        result_lines.push(String::new());
+        result_lines.push("Failures:".to_string());
+
+        for f in failures.iter().take(config.max_items_per_group) {
+            result_lines.push(format!("  ❌ {}", f.name));
+            if !f.message.is_empty() {
+                result_lines.push(format!("     {}", f.message));
+            }
+            if !f.location.is_empty() {
+                result_lines.push(format!("     at {}", f.location));
             }
         }
+        if failures.len() > config.max_items_per_group {
+            result_lines.push(format!("  ... +{} more failures", failures.len() - config.max_items_per_group));
+        }
+    }
 
-        if in_failure && trimmed.starts_with("_") && trimmed.ends_with("_") {
-            if let Some(f) = current_failure.take() {
-                failures.push(f);
+    let compressed = result_lines.join("\n");
+    CompressResult::new(output, compressed)
+}
+
+/// Slowest tests listed from `--durations`
+const SLOWEST_TESTS: usize = 5;
+
+/// "==== 1 failed, 2 passed, 1 skipped in 2.60s ====" (duration may be "0:01:05" style too)
+static PYTEST_FINAL_SUMMARY: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"^=+ (\d+ \w+(?:, \d+ \w+)*) in ([\d.]+s(?: \([\d:]+\))?) =+$").unwrap()
+});
+
+static PYTEST_COUNT: Lazy<Regex> = Lazy::new(|| Regex::new(r"(\d+) (\w+)").unwrap());
+
+/// "==== FAILURES ====", "==== slowest 10 durations ===="
+static PYTEST_SECTION: Lazy<Regex> = Lazy::new(|| Regex::new(r"^=+ (.+?) =+$").unwrap());
+
+/// "____ TestConn.test_open ____"
+static PYTEST_BLOCK: Lazy<Regex> = Lazy::new(|| Regex::new(r"^_{3,} (.+?) _{3,}$").unwrap());
+
+/// "tests/test_api.py:8: AssertionError"
+static PYTEST_LOCATION: Lazy<Regex> = Lazy::new(|| Regex::new(r"^(\S+\.py:\d+): \w+").unwrap());
+
+/// "2.01s call     tests/test_db.py::test_open"
+static PYTEST_DURATION: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"^([\d.]+)s\s+(call|setup|teardown)\s+(\S+)").unwrap());
+
+const PYTEST_OUTCOMES: [&str; 6] = ["PASSED", "FAILED", "SKIPPED", "ERROR", "XFAIL", "XPASS"];
+
+/// Parse a `-v` result line: "tests/a.py::test_x PASSED [ 20%]" or, with
+/// xdist, "[gw0] [ 20%] PASSED tests/a.py::test_x"
+fn parse_pytest_verbose(line: &str) -> Option<(&str, &str)> {
+    let words: Vec<&str> = line.split_whitespace().collect();
+    let outcome_at = words.iter().position(|w| PYTEST_OUTCOMES.contains(w))?;
+    let outcome = words[outcome_at];
+
+    if outcome_at > 0 && words[outcome_at - 1].contains("::") && !words[0].starts_with("[gw") {
+        return Some((words[outcome_at - 1], outcome));
+    }
+    let nodeid = words.get(outcome_at + 1)?;
+    (words[0].starts_with("[gw") && nodeid.contains("::")).then_some((*nodeid, outcome))
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum PytestSection {
+    Failures,
+    Durations,
+    ShortSummary,
+    Other,
+}
+
+#[derive(Default)]
+struct ModuleCounts {
+    passed: usize,
+    failed: usize,
+    skipped: usize,
+    errors: usize,
+}
+
+impl ModuleCounts {
+    fn record(&mut self, outcome: &str) {
+        match outcome {
+            "PASSED" | "XPASS" => self.passed += 1,
+            "FAILED" => self.failed += 1,
+            "ERROR" => self.errors += 1,
+            _ => self.skipped += 1,
+        }
+    }
+
+    fn describe(&self) -> String {
+        let mut parts = vec![format!("{} passed", self.passed)];
+        if self.failed > 0 {
+            parts.push(format!("{} failed", self.failed));
+        }
+        if self.skipped > 0 {
+            parts.push(format!("{} skipped", self.skipped));
+        }
+        if self.errors > 0 {
+            parts.push(format!("{} errors", self.errors));
+        }
+        parts.join(", ")
+    }
+}
+
+/// Check for pytest output: session header, `-v` result lines, or the final summary
+fn is_pytest_output(output: &str) -> bool {
+    output.lines().any(|line| {
+        let trimmed = line.trim();
+        trimmed.contains("test session starts")
+            || parse_pytest_verbose(trimmed).is_some()
+            || PYTEST_FINAL_SUMMARY.is_match(trimmed)
+    })
+}
+
+/// Check for `go test` output: verbose markers or package result lines
+fn is_go_test_output(output: &str) -> bool {
+    output.lines().any(|line| {
+        line.starts_with("=== RUN")
+            || line.trim_start().starts_with("--- FAIL: ")
+            || line.trim_start().starts_with("--- PASS: ")
+            || line.starts_with("ok  \t")
+            || line.starts_with("FAIL\t")
+    })
+}
+
+/// Compress go test output (with or without -v)
+fn compress_go_test(output: &str, config: &CompressConfig) -> CompressResult {
+    let lines: Vec<&str> = output.lines().collect();
+
+    let mut passed = 0;
+    let mut failed = 0;
+    let mut skipped = 0;
+    let mut packages_ok = 0;
+    let mut failed_packages: Vec<String> = Vec::new();
+    let mut build_errors: Vec<String> = Vec::new();
+    let mut failures: Vec<TestFailure> = Vec::new();
+    // First message logged by each test, keyed by name
+    let mut messages: HashMap<String, String> = HashMap::new();
+    let mut current_test: Option<String> = None;
+    let mut in_build_section = false;
+    let mut in_panic = false;
+
+    for line in &lines {
+        let trimmed = line.trim();
+
+        if let Some(rest) = trimmed
+            .strip_prefix("=== RUN")
+            .or_else(|| trimmed.strip_prefix("=== CONT"))
+        {
+            current_test = Some(rest.trim().to_string());
+        } else if trimmed.starts_with("--- PASS: ") {
+            passed += 1;
+        } else if trimmed.starts_with("--- SKIP: ") {
+            skipped += 1;
+        } else if let Some(rest) = trimmed.strip_prefix("--- FAIL: ") {
+            failed += 1;
+            let name = rest.split(" (").next().unwrap_or(rest).to_string();
+            current_test = Some(name.clone());
+            failures.push(TestFailure {
+                name,
+                message: String::new(),
+                location: String::new(),
+            });
+        } else if trimmed.starts_with("ok  ") || trimmed.starts_with("ok\t") {
+            packages_ok += 1;
+            in_build_section = false;
+        } else if let Some(rest) = line.strip_prefix("FAIL\t") {
+            failed_packages.push(rest.split_whitespace().collect::<Vec<_>>().join(" "));
+            in_build_section = false;
+        } else if trimmed.starts_with("# ") {
+            in_build_section = true;
+        } else if in_build_section {
+            build_errors.push(trimmed.to_string());
+        } else if trimmed.starts_with("panic: ") {
+            // A panic belongs to the test that was running; recovered panics repeat the message
+            if in_panic {
+                continue;
+            }
+            in_panic = true;
+            let name = current_test.clone().unwrap_or_else(|| "panic".to_string());
+            match failures.iter_mut().rev().find(|f| f.name == name) {
+                Some(f) => f.message = trimmed.to_string(),
+                None => failures.push(TestFailure {
+                    name,
+                    message: trimmed.to_string(),
+                    location: String::new(),
+                }),
+            }
+        } else if in_panic && trimmed.contains(".go:") && !is_go_runtime_frame(trimmed) {
+            // First project frame of the panic's stack
+            if let Some(f) = failures.last_mut() {
+                if f.location.is_empty() {
+                    f.location = trimmed.split(" +0x").next().unwrap_or(trimmed).to_string();
+                }
+            }
+        } else if is_go_test_log(trimmed) {
+            if let Some(name) = &current_test {
+                messages.entry(name.clone()).or_insert_with(|| trimmed.to_string());
             }
-            in_failure = false;
         }
     }
 
-    if let Some(f) = current_failure {
-        failures.push(f);
+    for f in &mut failures {
+        if f.message.is_empty() {
+            if let Some(message) = messages.get(&f.name) {
+                f.message = message.clone();
+            }
+        }
     }
 
     // Format output
     let mut result_lines: Vec<String> = Vec::new();
 
-    let status_emoji = if failed > 0 { "❌" } else { "✅" };
-    result_lines.push(format!("{} {} passed, {} failed", status_emoji, passed, failed));
+    let any_failed = failed > 0 || !failed_packages.is_empty();
+    let status_emoji = if any_failed { "❌" } else { "✅" };
+    result_lines.push(format!(
+        "{} {} passed, {} failed, {} skipped",
+        status_emoji, passed, failed, skipped
+    ));
+    if packages_ok > 0 || !failed_packages.is_empty() {
+        result_lines.push(format!(
+            "Packages: {} ok, {} failed",
+            packages_ok,
+            failed_packages.len()
+        ));
+    }
+
+    if !build_errors.is_empty() {
+        result_lines.push(String::new());
+        result_lines.push("Build errors:".to_string());
+        for e in build_errors.iter().take(config.max_items_per_group) {
+            result_lines.push(format!("  {}", e));
+        }
+    }
 
     if !failures.is_empty() {
         result_lines.push(String::new());
@@ -282,6 +629,21 @@ fn compress_pytest(output: &str, config: &CompressConfig) -> CompressResult {
             if !f.message.is_empty() {
                 result_lines.push(format!("     {}", f.message));
             }
+            if !f.location.is_empty() {
+                result_lines.push(format!("     at {}", f.location));
+            }
+        }
+
+        if failures.len() > config.max_items_per_group {
+            result_lines.push(format!("  ... +{} more failures", failures.len() - config.max_items_per_group));
+        }
+    }
+
+    if !failed_packages.is_empty() {
+        result_lines.push(String::new());
+        result_lines.push("Failed packages:".to_string());
+        for p in &failed_packages {
+            result_lines.push(format!("  {}", p));
         }
     }
 
@@ -289,6 +651,22 @@ fn compress_pytest(output: &str, config: &CompressConfig) -> CompressResult {
     CompressResult::new(output, compressed)
 }
 
+/// `t.Log`/`t.Error` output: "math_test.go:15: expected 1, got 2"
+fn is_go_test_log(line: &str) -> bool {
+    line.split_once(": ")
+        .map(|(location, _)| {
+            location
+                .split_once(".go:")
+                .is_some_and(|(_, n)| !n.is_empty() && n.chars().all(|c| c.is_ascii_digit()))
+        })
+        .unwrap_or(false)
+}
+
+/// Stack frames inside the Go runtime, testing package, or module cache
+fn is_go_runtime_frame(line: &str) -> bool {
+    line.contains("/src/runtime/") || line.contains("/src/testing/") || line.contains("/pkg/mod/")
+}
+
 /// Generic test output compression
 fn compress_generic_test(output: &str, config: &CompressConfig) -> CompressResult {
     let lines: Vec<&str> = output.lines().collect();
@@ -396,4 +774,83 @@ Test Suites: 1 passed, 1 failed, 2 total
         assert!(result.output.contains("passed"));
         assert!(result.output.contains("failed"));
     }
+
+    #[test]
+    fn test_compress_go_test() {
+        let input = "=== RUN   TestAdd\n--- PASS: TestAdd (0.00s)\n=== RUN   TestSub\n    math_test.go:15: expected 1, got 2\n    math_test.go:16: second message\n--- FAIL: TestSub (0.00s)\n=== RUN   TestSkip\n    math_test.go:20: needs network\n--- SKIP: TestSkip (0.00s)\n=== RUN   TestDiv\n--- FAIL: TestDiv (0.00s)\npanic: runtime error: integer divide by zero [recovered]\n\tpanic: runtime error: integer divide by zero\n\ngoroutine 7 [running]:\ntesting.tRunner.func1.2({0x1234, 0x5678})\n\t/usr/local/go/src/testing/testing.go:1545 +0x238\nexample.com/math.Div(...)\n\t/home/dev/math/math.go:12 +0x1d\nFAIL\texample.com/math\t0.005s\nok  \texample.com/other\t0.003s\n";
+
+        let config = CompressConfig::default();
+        let result = compress_test_output(input, &config);
+
+        assert!(result.output.starts_with("❌ 1 passed, 2 failed, 1 skipped\nPackages: 1 ok, 1 failed"));
+        assert!(result.output.contains("  ❌ TestSub\n     math_test.go:15: expected 1, got 2\n"));
+        assert!(!result.output.contains("second message"));
+        assert!(result.output.contains(
+            "  ❌ TestDiv\n     panic: runtime error: integer divide by zero [recovered]\n     at /home/dev/math/math.go:12"
+        ));
+        assert!(result.output.contains("Failed packages:\n  example.com/math 0.005s"));
+        assert!(!result.output.contains("goroutine"));
+    }
+
+    #[test]
+    fn test_compress_pytest_verbose_durations() {
+        let input = r#"============================= test session starts ==============================
+platform linux -- Python 3.11.4, pytest-7.4.0, pluggy-1.2.0 -- /usr/bin/python3
+collected 5 items
+
+tests/test_api.py::test_get PASSED                                       [ 20%]
+tests/test_api.py::test_post FAILED                                      [ 40%]
+tests/test_db.py::TestConn::test_open PASSED                             [ 60%]
+tests/test_db.py::test_cache SKIPPED (needs redis)                       [ 80%]
+tests/test_db.py::test_close PASSED                                      [100%]
+
+=================================== FAILURES ===================================
+__________________________________ test_post ___________________________________
+
+    def test_post():
+>       assert create(1) == 2
+E       assert 1 == 2
+E        +  where 1 = create(1)
+
+tests/test_api.py:8: AssertionError
+============================= slowest 3 durations ==============================
+2.01s call     tests/test_db.py::TestConn::test_open
+0.50s setup    tests/test_api.py::test_get
+3.10s call     tests/test_db.py::test_close
+=========================== short test summary info ============================
+FAILED tests/test_api.py::test_post - assert 1 == 2
+=============== 1 failed, 3 passed, 1 skipped, 2 warnings in 5.72s ===============
+"#;
+
+        let config = CompressConfig::default();
+        let result = compress_test_output(input, &config);
+
+        assert_eq!(
+            result.output,
+            "❌ 3 passed, 1 failed, 1 skipped in 5.72s
+Modules:
+  ❌ tests/test_api.py: 1 passed, 1 failed
+  ✅ tests/test_db.py: 2 passed, 1 skipped
+Slowest:
+  3.10s tests/test_db.py::test_close (call)
+  2.01s tests/test_db.py::TestConn::test_open (call)
+  0.50s tests/test_api.py::test_get (setup)
+
+Failures:
+  ❌ tests/test_api.py::test_post
+     assert 1 == 2
+     at tests/test_api.py:8"
+        );
+    }
+
+    #[test]
+    fn test_compress_pytest_without_summary() {
+        // xdist output from an interrupted run: counts come from the -v lines
+        let input = "[gw0] [ 50%] PASSED tests/test_a.py::test_one\n[gw1] [100%] FAILED tests/test_a.py::test_two\n";
+
+        let config = CompressConfig::default();
+        let result = compress_test_output(input, &config);
+
+        assert_eq!(result.output, "❌ 1 passed, 1 failed");
+    }
 }
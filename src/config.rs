@@ -1,11 +1,14 @@
 use anyhow::{Context, Result};
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, RwLock};
+use std::time::SystemTime;
 use tracing::info;
 use xxhash_rust::xxh3::xxh3_64;
 
 /// Runtime-resolved paths and settings
-#[derive(Debug, Clone)]
+#[derive(Debug)]
 pub struct Config {
     /// Detected project root directory
     pub project_root: PathBuf,
@@ -17,8 +20,39 @@ pub struct Config {
     pub store_db_path: PathBuf,
     /// Learning database: {codegraph_dir}/learning.db
     pub learning_db_path: PathBuf,
-    /// Parsed config file settings
-    pub settings: ConfigFile,
+    /// Parsed config file settings, replaced in place by `reload`
+    pub settings: SharedSettings,
+}
+
+/// Config file settings that can be swapped while the server runs.
+///
+/// Readers take an `Arc` snapshot, so an operation already in progress keeps
+/// the settings it started with.
+#[derive(Debug, Default)]
+pub struct SharedSettings {
+    current: RwLock<Arc<ConfigFile>>,
+    /// Modification times of the config files (global, project) when last loaded
+    loaded_mtimes: Mutex<Vec<Option<SystemTime>>>,
+}
+
+impl SharedSettings {
+    /// Current settings snapshot
+    pub fn get(&self) -> Arc<ConfigFile> {
+        self.current.read().unwrap_or_else(|e| e.into_inner()).clone()
+    }
+
+    fn replace(&self, settings: ConfigFile) {
+        *self.current.write().unwrap_or_else(|e| e.into_inner()) = Arc::new(settings);
+    }
+}
+
+impl From<ConfigFile> for SharedSettings {
+    fn from(settings: ConfigFile) -> Self {
+        Self {
+            current: RwLock::new(Arc::new(settings)),
+            loaded_mtimes: Mutex::new(vec![]),
+        }
+    }
 }
 
 /// Parsed from .codegraph/config.toml (all fields have defaults)
@@ -28,6 +62,12 @@ pub struct ConfigFile {
     pub indexing: IndexingConfig,
     pub learning: LearningConfig,
     pub cross_language: CrossLanguageConfig,
+    pub skill: SkillConfig,
+    pub bash: BashConfig,
+    pub compression: CompressionConfig,
+    pub storage: StorageConfig,
+    pub tools: ToolsConfig,
+    pub graph: GraphConfig,
 }
 
 impl Default for ConfigFile {
@@ -36,6 +76,12 @@ impl Default for ConfigFile {
             indexing: IndexingConfig::default(),
             learning: LearningConfig::default(),
             cross_language: CrossLanguageConfig::default(),
+            skill: SkillConfig::default(),
+            bash: BashConfig::default(),
+            compression: CompressionConfig::default(),
+            storage: StorageConfig::default(),
+            tools: ToolsConfig::default(),
+            graph: GraphConfig::default(),
         }
     }
 }
@@ -43,13 +89,22 @@ impl Default for ConfigFile {
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(default)]
 pub struct IndexingConfig {
+    /// If non-empty, only files matching one of these globs (relative to the project root) are indexed
+    pub include: Vec<String>,
     pub exclude: Vec<String>,
     pub max_file_size: usize,
+    /// Per-language settings keyed by language name (rust, typescript, javascript, python, go, java, ruby, php, csharp)
+    pub languages: HashMap<String, LanguageSettings>,
+    /// Re-index changed files in the background while the server runs
+    pub watch: bool,
+    /// Quiet period after the last file change before re-indexing
+    pub watch_debounce_ms: u64,
 }
 
 impl Default for IndexingConfig {
     fn default() -> Self {
         Self {
+            include: vec![],
             exclude: vec![
                 "node_modules".into(),
                 "target".into(),
@@ -66,6 +121,27 @@ impl Default for IndexingConfig {
                 ".tox".into(),
             ],
             max_file_size: 1_048_576, // 1 MiB
+            languages: HashMap::new(),
+            watch: true,
+            watch_debounce_ms: 500,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct LanguageSettings {
+    /// Set to false to skip this language's files entirely
+    pub enabled: bool,
+    /// Extra file extensions parsed with this language's grammar (e.g. "pyi", ".cjs")
+    pub extensions: Vec<String>,
+}
+
+impl Default for LanguageSettings {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            extensions: vec![],
         }
     }
 }
@@ -74,16 +150,126 @@ impl Default for IndexingConfig {
 #[serde(default)]
 pub struct LearningConfig {
     pub decay_half_life: u32,
+    /// recall_patterns result limit
+    pub recall_limit: usize,
+    /// recall_patterns hides patterns below this effective confidence
+    pub recall_min_confidence: f32,
+    /// query_lineage result limit
+    pub lineage_limit: usize,
+    /// Confidence given to patterns from extract_pattern
+    pub pattern_confidence: f32,
+    /// Min effective confidence for patterns written by sync_learnings
+    pub sync_confidence_threshold: f32,
+    /// Min effective confidence for patterns in SKILL.md
+    pub distill_confidence_threshold: f32,
+    /// How strictly reflect checks root causes and lessons
+    pub reflection_validation: ReflectionValidation,
+    /// Similarity at which consolidate_patterns merges two patterns
+    pub consolidate_threshold: f32,
+    /// Semantic ranking for recall_patterns, recall_failures, and suggest_approach
+    pub embeddings: EmbeddingsConfig,
+    /// Team-shared learning database; read once at startup
+    pub remote: RemoteLearningConfig,
 }
 
 impl Default for LearningConfig {
     fn default() -> Self {
         Self {
-            decay_half_life: 90,
+            decay_half_life: crate::learning::confidence::DEFAULT_HALF_LIFE_DAYS as u32,
+            recall_limit: 5,
+            recall_min_confidence: 0.0,
+            lineage_limit: 10,
+            pattern_confidence: 0.7,
+            sync_confidence_threshold: 0.7,
+            distill_confidence_threshold: crate::skill::distill::DEFAULT_CONFIDENCE_THRESHOLD,
+            reflection_validation: ReflectionValidation::default(),
+            consolidate_threshold: crate::learning::patterns::DUPLICATE_THRESHOLD,
+            embeddings: EmbeddingsConfig::default(),
+            remote: RemoteLearningConfig::default(),
         }
     }
 }
 
+/// Where vectors for semantic pattern and failure recall come from
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct EmbeddingsConfig {
+    pub provider: EmbeddingProvider,
+    /// OpenAI-compatible embeddings endpoint used by the http provider
+    pub url: String,
+    /// Model name sent to the http provider
+    pub model: String,
+    /// Environment variable holding the http provider's API key; empty if it needs none
+    pub api_key_env: String,
+}
+
+impl Default for EmbeddingsConfig {
+    fn default() -> Self {
+        Self {
+            provider: EmbeddingProvider::default(),
+            url: "http://localhost:11434/v1/embeddings".into(),
+            model: "nomic-embed-text".into(),
+            api_key_env: String::new(),
+        }
+    }
+}
+
+/// A libsql server (e.g. Turso) that learning.db is replicated from, so a team shares
+/// patterns and failures. Reads hit a local embedded replica; writes go to the server.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RemoteLearningConfig {
+    /// libsql://, https://, or http:// URL of the primary database; empty keeps learning.db local
+    pub url: String,
+    /// Environment variable holding the auth token; empty if the server needs none
+    pub auth_token_env: String,
+    /// Pull the team's changes this often in the background (0 = only at startup and sync_learnings)
+    pub sync_interval_secs: u64,
+}
+
+impl RemoteLearningConfig {
+    pub fn is_enabled(&self) -> bool {
+        !self.url.is_empty()
+    }
+}
+
+impl Default for RemoteLearningConfig {
+    fn default() -> Self {
+        Self {
+            url: String::new(),
+            auth_token_env: "TURSO_AUTH_TOKEN".into(),
+            sync_interval_secs: 60,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum EmbeddingProvider {
+    /// Keyword matching only
+    Off,
+    /// Hashed word and character trigram vectors computed in-process, with no model download
+    #[default]
+    Local,
+    /// An OpenAI-compatible /embeddings endpoint (Ollama, llama.cpp server, OpenAI, ...)
+    Http,
+}
+
+/// Strictness of `reflect` input checks, from none to rejecting loosely worded lessons
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ReflectionValidation {
+    /// No checks
+    Off,
+    /// Problems are reported as warnings only
+    Lenient,
+    /// Generic root causes and out-of-range confidence are rejected
+    #[default]
+    Standard,
+    /// Lessons must also follow "When X, do Y because Z"
+    Strict,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(default)]
 pub struct CrossLanguageConfig {
@@ -96,11 +282,172 @@ impl Default for CrossLanguageConfig {
     }
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SkillConfig {
+    /// Regenerate SKILL.md after this many new patterns/failures (0 = only report staleness)
+    pub auto_distill_after: usize,
+    /// Instruction categories written to their own file instead of SKILL.md
+    pub split_files: Vec<String>,
+    /// Team-defined instruction categories in addition to the built-in ones
+    pub categories: Vec<String>,
+}
+
+impl Default for SkillConfig {
+    fn default() -> Self {
+        Self {
+            auto_distill_after: 5,
+            split_files: vec![],
+            categories: vec![],
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct BashConfig {
+    /// If non-empty, bash_compressed only runs commands matching one of these regexes
+    pub allow: Vec<String>,
+    /// Commands matching any of these regexes are refused, even if allowed. Only the text
+    /// as written is matched, so this catches accidents rather than containing commands.
+    pub deny: Vec<String>,
+}
+
+impl Default for BashConfig {
+    fn default() -> Self {
+        Self {
+            allow: vec![],
+            deny: vec![
+                // rm -rf / rm -fr / rm -rvf ...
+                r"\brm\s+(-\S+\s+)*-[a-zA-Z]*([rR][a-zA-Z]*f|f[a-zA-Z]*[rR])".into(),
+                r"\brm\s+.*(-[rR]|--recursive)\b.*(-f|--force)\b".into(),
+                r"\brm\s+.*(-f|--force)\b.*(-[rR]|--recursive)\b".into(),
+                r"(^|[;&|(]\s*)sudo\b".into(),
+                r"(^|[;&|(]\s*)(curl|wget|nc|ncat|telnet|ssh|scp|rsync|ftp)\b".into(),
+            ],
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CompressionConfig {
+    pub max_lines: usize,
+    pub max_items_per_group: usize,
+    pub dedup_threshold: usize,
+    /// Overrides keyed by command category (git, files, search, build, lint, test, ...)
+    pub categories: HashMap<String, CompressionProfile>,
+}
+
+impl Default for CompressionConfig {
+    fn default() -> Self {
+        Self {
+            max_lines: 50,
+            max_items_per_group: 10,
+            dedup_threshold: 2,
+            categories: HashMap::new(),
+        }
+    }
+}
+
+/// Per-category compression settings; unset fields use the [compression] defaults
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct CompressionProfile {
+    pub max_lines: Option<usize>,
+    pub max_items_per_group: Option<usize>,
+    pub dedup_threshold: Option<usize>,
+}
+
+/// Defaults for tool parameters that a call leaves out
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ToolsConfig {
+    /// search_symbols result limit
+    pub search_limit: usize,
+    /// get_neighbors traversal depth
+    pub neighbor_depth: u32,
+    /// bash_compressed timeout in seconds (0 = no timeout)
+    pub bash_timeout_secs: u64,
+    /// shell_session timeout in seconds
+    pub shell_timeout_secs: u64,
+    /// read_file_compressed max lines per body or range
+    pub read_max_lines: usize,
+    /// git_context recent commits shown
+    pub git_log_count: usize,
+    /// grep_project context lines around each match
+    pub grep_context: usize,
+    /// grep_project stops after this many matches
+    pub grep_max_matches: usize,
+}
+
+impl Default for ToolsConfig {
+    fn default() -> Self {
+        Self {
+            search_limit: 10,
+            neighbor_depth: 1,
+            bash_timeout_secs: 0,
+            shell_timeout_secs: 300,
+            read_max_lines: 200,
+            git_log_count: 5,
+            grep_context: 2,
+            grep_max_matches: 1000,
+        }
+    }
+}
+
+/// How much of the code graph is kept in memory; applied when the graph is (re)loaded
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct GraphConfig {
+    pub mode: GraphMode,
+    /// In auto mode, load slim when the full graph is estimated to need more than this.
+    /// A threshold for picking the mode, not a cap: slim still keeps every node and edge.
+    pub slim_above_mb: u64,
+}
+
+impl Default for GraphConfig {
+    fn default() -> Self {
+        Self {
+            mode: GraphMode::default(),
+            slim_above_mb: 1024,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum GraphMode {
+    /// Slim if the full graph would exceed `slim_above_mb`, otherwise full
+    #[default]
+    Auto,
+    /// Every node and edge with all of its data
+    Full,
+    /// Every node and edge, but without signatures, docstrings, or edge data; those are
+    /// read from the store when shown
+    #[serde(alias = "lazy")]
+    Slim,
+}
+
+/// Where the code graph database lives; read once at startup
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct StorageConfig {
+    /// Base directory for code graph databases instead of ~/.cache/codegraph (`~/` is expanded)
+    pub cache_dir: Option<String>,
+    /// Names this project's store instead of a hash of its path, so it survives moves and re-clones
+    pub project_alias: Option<String>,
+}
+
 const DEFAULT_CONFIG_TOML: &str = r#"# Codegraph configuration
 # See https://github.com/anthropics/codegraph-mcp for documentation
 
 [indexing]
-# Directories to exclude from indexing (matched as exact path components)
+# Globs to index, relative to the project root (empty = whole project), e.g.
+# include = ["services/auth/**", "libs/**"]
+include = []
+
+# Directories to exclude from indexing (matched as exact path components).
+# A .codegraphignore file in the project root can add gitignore-style patterns.
 exclude = [
     "node_modules",
     "target",
@@ -120,18 +467,130 @@ exclude = [
 # Maximum file size in bytes (files larger than this are skipped)
 max_file_size = 1048576  # 1 MiB
 
+# Re-index changed files in the background while the MCP server runs, once edits
+# have been quiet for watch_debounce_ms
+watch = true
+watch_debounce_ms = 500
+
+# Per-language settings: turn a language off or map extra extensions to its grammar
+# [indexing.languages.go]
+# enabled = false
+# [indexing.languages.python]
+# extensions = ["pyi"]
+
 [learning]
 # Half-life for confidence decay in days
 decay_half_life = 90
 
+# Defaults for learning tools (per-call parameters still take precedence)
+recall_limit = 5                    # recall_patterns results
+recall_min_confidence = 0.0         # recall_patterns hides patterns below this
+lineage_limit = 10                  # query_lineage results
+pattern_confidence = 0.7            # extract_pattern starting confidence
+sync_confidence_threshold = 0.7     # sync_learnings
+distill_confidence_threshold = 0.7  # distill_project_skill and automatic SKILL.md updates
+consolidate_threshold = 0.6         # consolidate_patterns merges patterns at least this similar
+
+# reflect input checks: "off", "lenient" (warn only), "standard", or "strict"
+# (also require "When X, do Y because Z" lessons)
+reflection_validation = "standard"
+
+[learning.embeddings]
+# Rank recalled patterns and failures by similarity to the task description:
+# "local" (hashed n-grams, no download), "http" (OpenAI-compatible endpoint), or "off"
+provider = "local"
+url = "http://localhost:11434/v1/embeddings"  # http provider endpoint (default: Ollama)
+model = "nomic-embed-text"
+api_key_env = ""                             # env var holding the API key, e.g. "OPENAI_API_KEY"
+
+[learning.remote]
+# Share patterns and failures with a team through a libsql server such as Turso. Each
+# machine keeps an embedded replica (.codegraph/learning-replica.db) and writes go to
+# the server; an existing learning.db is merged in on first start and kept as
+# learning-local.db. Requires a restart.
+# url = "libsql://team-learnings.turso.io"
+url = ""
+auth_token_env = "TURSO_AUTH_TOKEN"  # env var holding the auth token ("" if none is needed)
+sync_interval_secs = 60              # background pull interval (0 = startup and sync_learnings only)
+
 [cross_language]
 # Enable cross-language API inference
 enabled = true
+
+[skill]
+# Regenerate SKILL.md after this many new patterns/failures (0 = only report staleness)
+auto_distill_after = 5
+
+# Categories written to their own file instead of SKILL.md,
+# e.g. ["navigation", "gotchas"] produces NAVIGATION.md and GOTCHAS.md
+split_files = []
+
+# Extra instruction categories, each rendered as its own SKILL.md section
+# e.g. ["security", "api-design"]
+categories = []
+
+[bash]
+# Regexes matched against the command text; a best-effort guard against accidents,
+# not a sandbox (`bash -c`, `env sudo`, `$(...)` and scripts get around them)
+# If non-empty, bash_compressed only runs commands matching one of them
+allow = []
+
+# Commands bash_compressed refuses to run (checked after allow)
+deny = [
+    '\brm\s+(-\S+\s+)*-[a-zA-Z]*([rR][a-zA-Z]*f|f[a-zA-Z]*[rR])',
+    '\brm\s+.*(-[rR]|--recursive)\b.*(-f|--force)\b',
+    '\brm\s+.*(-f|--force)\b.*(-[rR]|--recursive)\b',
+    '(^|[;&|(]\s*)sudo\b',
+    '(^|[;&|(]\s*)(curl|wget|nc|ncat|telnet|ssh|scp|rsync|ftp)\b',
+]
+
+[compression]
+# Defaults for bash_compressed (per-call parameters still take precedence)
+max_lines = 50
+max_items_per_group = 10
+dedup_threshold = 2
+
+# Per-category overrides: git, files, search, build, lint, frontend, coverage, bench,
+# test, docker, terraform, package, other
+# [compression.categories.test]
+# max_lines = 100
+
+[tools]
+# Defaults for tool parameters a call leaves out
+search_limit = 10         # search_symbols results
+neighbor_depth = 1        # get_neighbors depth
+bash_timeout_secs = 0     # bash_compressed timeout (0 = none)
+shell_timeout_secs = 300  # shell_session timeout
+read_max_lines = 200      # read_file_compressed lines per body or range
+git_log_count = 5         # git_context recent commits
+grep_context = 2          # grep_project context lines
+grep_max_matches = 1000   # grep_project stops after this many matches
+
+[graph]
+# How much of the code graph to keep in memory: "full", "slim" (every node and edge,
+# but signatures, docstrings, and edge data are read from the store when shown),
+# or "auto" (slim once the full graph is estimated above slim_above_mb). This only
+# picks the mode and is not a memory limit: slim still holds every node and edge,
+# trimming per-symbol data.
+# Applies the next time the graph is loaded (startup or a full index_project).
+mode = "auto"
+slim_above_mb = 1024
+
+[storage]
+# Base directory for code graph databases (default: ~/.cache/codegraph), e.g. a
+# local disk when the home directory is network-mounted. Requires a restart.
+# cache_dir = "/var/tmp/codegraph"
+
+# Stable name for this project's store. Without it the store is keyed by the
+# project path, so moving or re-cloning the repo starts a fresh index.
+# project_alias = "my-service"
 "#;
 
 const CODEGRAPH_GITIGNORE: &str = r#"# Codegraph - SQLite databases (user-local, not shared)
 learning.db
 learning.db-*
+learning-replica.db*
+learning-local.db*
 "#;
 
 impl Config {
@@ -143,23 +602,26 @@ impl Config {
     /// Create configuration from a specific path
     pub fn from_path(start_path: &Path) -> Result<Self> {
         let project_root = Self::find_project_root(start_path)?;
-        let project_hash = Self::hash_project_path(&project_root);
-
-        // Cache directory in user's cache dir
-        let cache_base = dirs::cache_dir()
-            .context("Failed to determine cache directory")?
-            .join("codegraph");
-        let cache_dir = cache_base.join(&project_hash);
 
         // Project-local directory
         let codegraph_dir = project_root.join(".codegraph");
 
+        // Load config files
+        let file_settings = Self::load_config_file(&codegraph_dir);
+
+        // Cache directory in user's cache dir, unless [storage] says otherwise
+        let cache_dir = Self::resolve_cache_dir(&file_settings.storage, &project_root).or_else(|e| {
+            eprintln!("Warning: {:#}; using the default cache directory", e);
+            Self::resolve_cache_dir(&StorageConfig::default(), &project_root)
+        })?;
+
         // Database paths
         let store_db_path = cache_dir.join("store.db");
         let learning_db_path = codegraph_dir.join("learning.db");
 
-        // Load config file
-        let settings = Self::load_config_file(&codegraph_dir);
+        let settings = SharedSettings::from(file_settings);
+        *settings.loaded_mtimes.lock().unwrap_or_else(|e| e.into_inner()) =
+            Self::config_mtimes(&codegraph_dir);
 
         Ok(Self {
             project_root,
@@ -171,23 +633,95 @@ impl Config {
         })
     }
 
-    /// Load config.toml from .codegraph/ directory, falling back to defaults
-    fn load_config_file(codegraph_dir: &Path) -> ConfigFile {
-        let config_path = codegraph_dir.join("config.toml");
-        if config_path.is_file() {
-            match std::fs::read_to_string(&config_path) {
-                Ok(contents) => match toml::from_str(&contents) {
-                    Ok(config) => return config,
-                    Err(e) => {
-                        eprintln!("Warning: Failed to parse config.toml: {}", e);
-                    }
-                },
-                Err(e) => {
-                    eprintln!("Warning: Failed to read config.toml: {}", e);
-                }
+    /// Current config file settings
+    pub fn settings(&self) -> Arc<ConfigFile> {
+        self.settings.get()
+    }
+
+    /// Embedded replica of the shared learning database, used instead of learning.db
+    /// when [learning.remote] is configured
+    pub fn learning_replica_path(&self) -> PathBuf {
+        self.codegraph_dir.join("learning-replica.db")
+    }
+
+    /// Where learning.db is kept once it has been merged into the shared database
+    pub fn merged_learning_db_path(&self) -> PathBuf {
+        self.codegraph_dir.join("learning-local.db")
+    }
+
+    /// Path of the project config file
+    pub fn config_path(&self) -> PathBuf {
+        self.codegraph_dir.join("config.toml")
+    }
+
+    /// Re-read the global and project config files and swap in the new settings.
+    ///
+    /// Returns the names of the sections that changed. If a file can't be read
+    /// or parsed, the current settings are kept and the error is returned.
+    pub fn reload(&self) -> Result<Vec<String>> {
+        let mtimes = Self::config_mtimes(&self.codegraph_dir);
+        let updated = Self::read_config_file(&self.codegraph_dir)?;
+        *self.settings.loaded_mtimes.lock().unwrap_or_else(|e| e.into_inner()) = mtimes;
+
+        let changed = changed_sections(&self.settings(), &updated);
+        if !changed.is_empty() {
+            info!("Reloaded config.toml, changed sections: {}", changed.join(", "));
+            self.settings.replace(updated);
+        }
+        Ok(changed)
+    }
+
+    /// Reload if a config file was modified, created, or removed since it was last loaded.
+    ///
+    /// Returns `None` if the files are unchanged.
+    pub fn reload_if_changed(&self) -> Option<Result<Vec<String>>> {
+        let mtimes = Self::config_mtimes(&self.codegraph_dir);
+        {
+            let mut loaded = self.settings.loaded_mtimes.lock().unwrap_or_else(|e| e.into_inner());
+            if *loaded == mtimes {
+                return None;
             }
+            // Record the attempt so a broken file is reported once, not on every call
+            *loaded = mtimes;
         }
-        ConfigFile::default()
+        Some(self.reload())
+    }
+
+    /// User-level config applied under every project's config: ~/.config/codegraph/config.toml
+    pub fn global_config_path() -> Option<PathBuf> {
+        dirs::home_dir().map(|home| home.join(".config/codegraph/config.toml"))
+    }
+
+    /// Config files in merge order: global first, then the project's
+    fn config_files(codegraph_dir: &Path) -> Vec<PathBuf> {
+        Self::global_config_path()
+            .into_iter()
+            .chain([codegraph_dir.join("config.toml")])
+            .collect()
+    }
+
+    fn config_mtimes(codegraph_dir: &Path) -> Vec<Option<SystemTime>> {
+        Self::config_files(codegraph_dir)
+            .iter()
+            .map(|path| std::fs::metadata(path).and_then(|m| m.modified()).ok())
+            .collect()
+    }
+
+    /// Load the global and project config files, falling back to defaults
+    fn load_config_file(codegraph_dir: &Path) -> ConfigFile {
+        Self::read_config_file(codegraph_dir).unwrap_or_else(|e| {
+            eprintln!("Warning: {:#}", e);
+            ConfigFile::default()
+        })
+    }
+
+    fn read_config_file(codegraph_dir: &Path) -> Result<ConfigFile> {
+        read_layered_config(&Self::config_files(codegraph_dir))
+    }
+
+    /// Check the global and project config files without applying them
+    pub fn validate(&self) -> ConfigReport {
+        validate_config_files(&Self::config_files(&self.codegraph_dir))
     }
 
     /// Find project root by walking up from start path
@@ -222,6 +756,24 @@ impl Config {
     }
 
     /// Hash project path to create a unique cache key
+    /// Per-project cache directory: {cache base}/{project alias or path hash}
+    fn resolve_cache_dir(storage: &StorageConfig, project_root: &Path) -> Result<PathBuf> {
+        let cache_base = match &storage.cache_dir {
+            Some(dir) => expand_cache_dir(dir)?,
+            None => dirs::cache_dir()
+                .context("Failed to determine cache directory")?
+                .join("codegraph"),
+        };
+        let project_key = match &storage.project_alias {
+            Some(alias) => {
+                check_project_alias(alias)?;
+                alias.clone()
+            }
+            None => Self::hash_project_path(project_root),
+        };
+        Ok(cache_base.join(project_key))
+    }
+
     fn hash_project_path(path: &Path) -> String {
         let canonical = path.to_string_lossy();
         let hash = xxh3_64(canonical.as_bytes());
@@ -271,6 +823,270 @@ impl Config {
     }
 }
 
+/// Parse config files and merge them in order, later files overriding earlier ones.
+///
+/// Tables are merged key by key; arrays and plain values are replaced whole. Missing
+/// files are skipped, so no files at all means defaults.
+fn read_layered_config(paths: &[PathBuf]) -> Result<ConfigFile> {
+    let mut merged = toml::Table::new();
+    for path in paths.iter().filter(|path| path.is_file()) {
+        let contents = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read {}", path.display()))?;
+        let layer: toml::Table =
+            toml::from_str(&contents).with_context(|| format!("Failed to parse {}", path.display()))?;
+        // Check each file on its own so errors name the file at fault
+        ConfigFile::deserialize(layer.clone()).with_context(|| format!("Failed to parse {}", path.display()))?;
+        merge_tables(&mut merged, layer);
+    }
+    Ok(ConfigFile::deserialize(merged)?)
+}
+
+fn merge_tables(base: &mut toml::Table, layer: toml::Table) {
+    for (key, value) in layer {
+        match (base.get_mut(&key), value) {
+            (Some(toml::Value::Table(existing)), toml::Value::Table(table)) => merge_tables(existing, table),
+            (_, value) => {
+                base.insert(key, value);
+            }
+        }
+    }
+}
+
+/// `[storage] cache_dir` as an absolute path, with a leading `~/` expanded
+fn expand_cache_dir(dir: &str) -> Result<PathBuf> {
+    let path = match dir.strip_prefix("~/") {
+        Some(rest) => dirs::home_dir().context("Failed to determine home directory")?.join(rest),
+        None => PathBuf::from(dir),
+    };
+    if !path.is_absolute() {
+        anyhow::bail!("[storage] cache_dir must be an absolute path, got '{}'", dir);
+    }
+    Ok(path)
+}
+
+/// An alias becomes a single directory name under the cache base
+fn check_project_alias(alias: &str) -> Result<()> {
+    if alias.is_empty() || alias == "." || alias == ".." || alias.contains(['/', '\\']) {
+        anyhow::bail!("[storage] project_alias must be a plain directory name, got '{}'", alias);
+    }
+    Ok(())
+}
+
+/// Problems found in the config files, and the settings they resolve to
+#[derive(Debug, Default)]
+pub struct ConfigReport {
+    /// Config files that exist, in merge order
+    pub files: Vec<PathBuf>,
+    /// Problems that stop the settings from loading or being applied
+    pub errors: Vec<String>,
+    /// Settings that load but probably don't do what was intended
+    pub warnings: Vec<String>,
+    /// Merged settings, if every file parsed
+    pub effective: Option<ConfigFile>,
+}
+
+fn validate_config_files(paths: &[PathBuf]) -> ConfigReport {
+    let mut report = ConfigReport::default();
+    let schema = config_schema();
+
+    for path in paths.iter().filter(|path| path.is_file()) {
+        report.files.push(path.clone());
+        let layer = std::fs::read_to_string(path)
+            .map_err(|e| e.to_string())
+            .and_then(|contents| toml::from_str::<toml::Table>(&contents).map_err(|e| e.to_string()));
+        match layer {
+            Ok(layer) => {
+                // One alias shared by every project would point them all at the same store
+                let is_project_file = Some(path) == paths.last();
+                let storage = layer.get("storage").and_then(|s| s.as_table());
+                if !is_project_file && storage.is_some_and(|s| s.contains_key("project_alias")) {
+                    report.warnings.push(format!(
+                        "{}: [storage] project_alias belongs in the project's config.toml, not a shared one",
+                        path.display()
+                    ));
+                }
+
+                let mut unknown = Vec::new();
+                unknown_keys(&layer, &schema, "", &mut unknown);
+                report.warnings.extend(
+                    unknown
+                        .into_iter()
+                        .map(|key| format!("{}: unknown key '{}'", path.display(), key)),
+                );
+            }
+            Err(e) => report.errors.push(format!("{}: {}", path.display(), e.trim_end())),
+        }
+    }
+
+    // Type errors (e.g. a string where a number belongs) only show up on deserialize
+    if report.errors.is_empty() {
+        match read_layered_config(paths) {
+            Ok(settings) => {
+                check_settings(&settings, &mut report);
+                report.effective = Some(settings);
+            }
+            Err(e) => report.errors.push(format!("{:#}", e).trim_end().to_string()),
+        }
+    }
+    report
+}
+
+/// Every key the config file understands, with default values. Sections keyed by
+/// user-chosen names hold a single "*" entry describing each value.
+fn config_schema() -> toml::Table {
+    let any_key = |value: toml::Table| toml::Value::Table(toml::Table::from_iter([("*".to_string(), value.into())]));
+    let profile = CompressionProfile {
+        max_lines: Some(0),
+        max_items_per_group: Some(0),
+        dedup_threshold: Some(0),
+    };
+
+    let mut schema = toml::Table::try_from(ConfigFile::default()).unwrap_or_default();
+    if let Some(toml::Value::Table(indexing)) = schema.get_mut("indexing") {
+        let language = toml::Table::try_from(LanguageSettings::default()).unwrap_or_default();
+        indexing.insert("languages".to_string(), any_key(language));
+    }
+    if let Some(toml::Value::Table(compression)) = schema.get_mut("compression") {
+        compression.insert("categories".to_string(), any_key(toml::Table::try_from(profile).unwrap_or_default()));
+    }
+    let storage = StorageConfig {
+        cache_dir: Some(String::new()),
+        project_alias: Some(String::new()),
+    };
+    schema.insert("storage".to_string(), toml::Table::try_from(storage).unwrap_or_default().into());
+    schema
+}
+
+/// Dotted paths of keys in `table` that `schema` doesn't have
+fn unknown_keys(table: &toml::Table, schema: &toml::Table, prefix: &str, unknown: &mut Vec<String>) {
+    for (key, value) in table {
+        let path = if prefix.is_empty() { key.clone() } else { format!("{}.{}", prefix, key) };
+        match (schema.get(key).or_else(|| schema.get("*")), value) {
+            (None, _) => unknown.push(path),
+            (Some(toml::Value::Table(expected)), toml::Value::Table(value)) => {
+                unknown_keys(value, expected, &path, unknown)
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Checks on values that parse but can't work as configured
+fn check_settings(settings: &ConfigFile, report: &mut ConfigReport) {
+    let indexing = &settings.indexing;
+    for include in &indexing.include {
+        if let Err(e) = glob::Pattern::new(include) {
+            report.errors.push(format!("[indexing] include: invalid glob '{}': {}", include, e));
+        }
+        let mut literal = include.split('/').take_while(|part| !part.contains(['*', '?', '[']));
+        if let Some(dir) = literal.find(|part| indexing.exclude.iter().any(|e| e == part)) {
+            report.warnings.push(format!(
+                "[indexing] include '{}' is inside excluded directory '{}', so it matches nothing",
+                include, dir
+            ));
+        }
+    }
+
+    let mut extension_owners: HashMap<String, Vec<&str>> = HashMap::new();
+    let mut languages: Vec<_> = indexing.languages.iter().collect();
+    languages.sort_by_key(|(name, _)| name.as_str());
+    for (name, language) in languages {
+        if !crate::code::languages::LANGUAGE_REGISTRY.contains_key(name) {
+            report.warnings.push(format!("[indexing.languages] unknown language '{}'", name));
+        }
+        if !language.enabled && !language.extensions.is_empty() {
+            report.warnings.push(format!(
+                "[indexing.languages.{}] is disabled, so its extensions are ignored",
+                name
+            ));
+        }
+        for extension in &language.extensions {
+            let extension = extension.trim_start_matches('.').to_string();
+            extension_owners.entry(extension).or_default().push(name);
+        }
+    }
+    let mut shared: Vec<_> = extension_owners.into_iter().filter(|(_, owners)| owners.len() > 1).collect();
+    shared.sort();
+    for (extension, owners) in shared {
+        report.warnings.push(format!(
+            "[indexing.languages] extension '{}' is claimed by {}",
+            extension,
+            owners.join(", ")
+        ));
+    }
+
+    for (key, patterns) in [("allow", &settings.bash.allow), ("deny", &settings.bash.deny)] {
+        for pattern in patterns {
+            if let Err(e) = regex::Regex::new(pattern) {
+                report.errors.push(format!("[bash] {}: invalid regex '{}': {}", key, pattern, e));
+            }
+        }
+    }
+
+    let storage = &settings.storage;
+    let storage_errors = [
+        storage.cache_dir.as_deref().map(|dir| expand_cache_dir(dir).err()),
+        storage.project_alias.as_deref().map(|alias| check_project_alias(alias).err()),
+    ];
+    report.errors.extend(storage_errors.into_iter().flatten().flatten().map(|e| e.to_string()));
+
+    let embeddings = &settings.learning.embeddings;
+    if embeddings.provider == EmbeddingProvider::Http {
+        if embeddings.url.is_empty() || embeddings.model.is_empty() {
+            report.errors.push("[learning.embeddings] the http provider needs a url and a model".to_string());
+        }
+        if !embeddings.api_key_env.is_empty() && std::env::var_os(&embeddings.api_key_env).is_none() {
+            report.warnings.push(format!(
+                "[learning.embeddings] api_key_env: ${} is not set",
+                embeddings.api_key_env
+            ));
+        }
+    }
+
+    let remote = &settings.learning.remote;
+    if remote.is_enabled() {
+        if !["libsql://", "https://", "http://"].iter().any(|scheme| remote.url.starts_with(scheme)) {
+            report.errors.push(format!(
+                "[learning.remote] url '{}' must start with libsql://, https://, or http://",
+                remote.url
+            ));
+        }
+        if !remote.auth_token_env.is_empty() && std::env::var_os(&remote.auth_token_env).is_none() {
+            report.warnings.push(format!(
+                "[learning.remote] auth_token_env: ${} is not set",
+                remote.auth_token_env
+            ));
+        }
+    }
+
+    let skill = &settings.skill;
+    for category in &skill.split_files {
+        let parsed = crate::skill::categories::InstructionCategory::from_str(category);
+        if parsed.is_custom() && !skill.categories.iter().any(|c| c.eq_ignore_ascii_case(category)) {
+            report.warnings.push(format!(
+                "[skill] split_files: '{}' is neither a built-in category nor listed in [skill] categories",
+                category
+            ));
+        }
+    }
+}
+
+/// Top-level config sections (e.g. "bash", "compression") that differ between two settings
+fn changed_sections(old: &ConfigFile, new: &ConfigFile) -> Vec<String> {
+    let as_table = |settings: &ConfigFile| match toml::Value::try_from(settings) {
+        Ok(toml::Value::Table(table)) => table,
+        _ => toml::map::Map::new(),
+    };
+    let (old, new) = (as_table(old), as_table(new));
+    let mut changed: Vec<String> = new
+        .iter()
+        .filter(|(key, value)| old.get(*key) != Some(*value))
+        .map(|(key, _)| key.clone())
+        .collect();
+    changed.sort();
+    changed
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -329,16 +1145,34 @@ mod tests {
     #[test]
     fn test_config_file_defaults() {
         let config = ConfigFile::default();
+        assert!(config.indexing.include.is_empty());
         assert!(config.indexing.exclude.contains(&"node_modules".to_string()));
         assert_eq!(config.indexing.max_file_size, 1_048_576);
         assert_eq!(config.learning.decay_half_life, 90);
         assert!(config.cross_language.enabled);
+        assert!(config.bash.allow.is_empty());
+        assert_eq!(config.bash.deny.len(), 5);
+    }
+
+    #[test]
+    fn test_default_config_toml_matches_defaults() {
+        let parsed: ConfigFile = toml::from_str(DEFAULT_CONFIG_TOML).unwrap();
+        assert_eq!(parsed.bash.deny, BashConfig::default().deny);
+        let defaults = toml::Value::try_from(ToolsConfig::default()).unwrap();
+        assert_eq!(toml::Value::try_from(parsed.tools).unwrap(), defaults);
+        let defaults = toml::Value::try_from(GraphConfig::default()).unwrap();
+        assert_eq!(toml::Value::try_from(parsed.graph).unwrap(), defaults);
+        assert_eq!(parsed.indexing.watch, IndexingConfig::default().watch);
+        assert_eq!(parsed.indexing.watch_debounce_ms, IndexingConfig::default().watch_debounce_ms);
+        let defaults = toml::Value::try_from(EmbeddingsConfig::default()).unwrap();
+        assert_eq!(toml::Value::try_from(parsed.learning.embeddings).unwrap(), defaults);
     }
 
     #[test]
     fn test_config_file_parse() {
         let toml_str = r#"
 [indexing]
+include = ["services/auth/**"]
 exclude = ["vendor", "build"]
 max_file_size = 500000
 
@@ -346,6 +1180,7 @@ max_file_size = 500000
 decay_half_life = 30
 "#;
         let config: ConfigFile = toml::from_str(toml_str).unwrap();
+        assert_eq!(config.indexing.include, vec!["services/auth/**"]);
         assert_eq!(config.indexing.exclude, vec!["vendor", "build"]);
         assert_eq!(config.indexing.max_file_size, 500_000);
         assert_eq!(config.learning.decay_half_life, 30);
@@ -353,6 +1188,220 @@ decay_half_life = 30
         assert!(config.cross_language.enabled);
     }
 
+    #[test]
+    fn test_language_settings_parse() {
+        let toml_str = r#"
+[indexing.languages.go]
+enabled = false
+
+[indexing.languages.javascript]
+extensions = ["cjs"]
+"#;
+        let config: ConfigFile = toml::from_str(toml_str).unwrap();
+        let languages = &config.indexing.languages;
+        assert!(!languages["go"].enabled);
+        assert!(languages["javascript"].enabled);
+        assert_eq!(languages["javascript"].extensions, vec!["cjs"]);
+        assert!(config.indexing.exclude.contains(&"node_modules".to_string()));
+    }
+
+    #[test]
+    fn test_graph_mode_parse() {
+        let config: ConfigFile = toml::from_str("[graph]\nmode = \"slim\"").unwrap();
+        assert_eq!(config.graph.mode, GraphMode::Slim);
+        // The mode's earlier name still loads
+        let config: ConfigFile = toml::from_str("[graph]\nmode = \"lazy\"").unwrap();
+        assert_eq!(config.graph.mode, GraphMode::Slim);
+    }
+
+    #[test]
+    fn test_compression_profiles_parse() {
+        let toml_str = r#"
+[compression]
+max_lines = 30
+
+[compression.categories.test]
+max_lines = 120
+dedup_threshold = 3
+"#;
+        let config: ConfigFile = toml::from_str(toml_str).unwrap();
+        assert_eq!(config.compression.max_lines, 30);
+        assert_eq!(config.compression.max_items_per_group, 10);
+        let test = &config.compression.categories["test"];
+        assert_eq!(test.max_lines, Some(120));
+        assert_eq!(test.max_items_per_group, None);
+        assert_eq!(test.dedup_threshold, Some(3));
+    }
+
+    #[test]
+    fn test_global_config_layered_under_project() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let global = temp_dir.path().join("global.toml");
+        let project = temp_dir.path().join("project.toml");
+        let global_toml = r#"
+[indexing]
+exclude = ["vendor"]
+max_file_size = 1000
+
+[compression]
+max_lines = 20
+
+[compression.categories.test]
+max_lines = 80
+"#;
+        let project_toml = r#"
+[indexing]
+max_file_size = 2000
+
+[compression.categories.git]
+max_lines = 10
+"#;
+        fs::write(&global, global_toml).unwrap();
+        fs::write(&project, project_toml).unwrap();
+
+        let config = read_layered_config(&[global.clone(), project.clone()]).unwrap();
+        assert_eq!(config.indexing.exclude, vec!["vendor"]);
+        assert_eq!(config.indexing.max_file_size, 2000);
+        assert_eq!(config.compression.max_lines, 20);
+        assert_eq!(config.compression.categories["test"].max_lines, Some(80));
+        assert_eq!(config.compression.categories["git"].max_lines, Some(10));
+
+        // Missing layers are skipped; errors name the broken file
+        let config = read_layered_config(&[temp_dir.path().join("missing.toml"), project.clone()]).unwrap();
+        assert_eq!(config.compression.max_lines, 50);
+        fs::write(&global, "[indexing]\nmax_file_size = \"big\"\n").unwrap();
+        let err = read_layered_config(&[global, project]).unwrap_err();
+        assert!(format!("{:#}", err).contains("global.toml"), "{:#}", err);
+    }
+
+    #[test]
+    fn test_validate_config_files() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let path = temp_dir.path().join("config.toml");
+        let config_toml = r#"
+[indexing]
+include = ["build/gen/**", "src/[**"]
+exlude = ["dist"]
+
+[indexing.languages.python]
+extensions = ["pyi"]
+
+[indexing.languages.cobol]
+enabled = false
+extensions = [".pyi"]
+
+[bash]
+deny = ["(unclosed"]
+
+[skill]
+split_files = ["gotchas", "secrets"]
+
+[compression.categories.test]
+max_lines = 100
+max_line = 5
+
+[storage]
+cache_dir = "relative/cache"
+project_alias = "a/b"
+
+[learning.remote]
+url = "postgres://db.internal/learnings"
+auth_token_env = "CODEGRAPH_TEST_UNSET_TOKEN"
+"#;
+        fs::write(&path, config_toml).unwrap();
+
+        let report = validate_config_files(&[temp_dir.path().join("missing.toml"), path.clone()]);
+        assert_eq!(report.files, vec![path.clone()]);
+        assert_eq!(report.errors.len(), 5, "{:?}", report.errors);
+        assert!(report.errors[0].starts_with("[indexing] include: invalid glob 'src/[**'"));
+        assert!(report.errors[1].starts_with("[bash] deny: invalid regex '(unclosed'"));
+        assert_eq!(report.errors[2], "[storage] cache_dir must be an absolute path, got 'relative/cache'");
+        assert_eq!(report.errors[3], "[storage] project_alias must be a plain directory name, got 'a/b'");
+        assert!(report.errors[4].starts_with("[learning.remote] url 'postgres://db.internal/learnings' must start"));
+
+        let warnings: Vec<&str> = report
+            .warnings
+            .iter()
+            .map(|w| w.strip_prefix(&format!("{}: ", path.display())).unwrap_or(w))
+            .collect();
+        assert_eq!(
+            warnings,
+            vec![
+                "unknown key 'compression.categories.test.max_line'",
+                "unknown key 'indexing.exlude'",
+                "[indexing] include 'build/gen/**' is inside excluded directory 'build', so it matches nothing",
+                "[indexing.languages] unknown language 'cobol'",
+                "[indexing.languages.cobol] is disabled, so its extensions are ignored",
+                "[indexing.languages] extension 'pyi' is claimed by cobol, python",
+                "[learning.remote] auth_token_env: $CODEGRAPH_TEST_UNSET_TOKEN is not set",
+                "[skill] split_files: 'secrets' is neither a built-in category nor listed in [skill] categories",
+            ]
+        );
+        assert!(report.effective.is_some());
+
+        // An alias in a shared config would give every project the same store
+        let global = temp_dir.path().join("global.toml");
+        fs::write(&global, "[storage]\nproject_alias = \"shared\"\n").unwrap();
+        fs::write(&path, "").unwrap();
+        let report = validate_config_files(&[global.clone(), path.clone()]);
+        assert_eq!(report.warnings.len(), 1);
+        assert!(report.warnings[0].contains("project_alias belongs in the project's config.toml"));
+
+        fs::write(&path, "[indexing]\nmax_file_size = \"big\"\n").unwrap();
+        let report = validate_config_files(&[path]);
+        assert_eq!(report.errors.len(), 1);
+        assert!(report.effective.is_none());
+    }
+
+    #[test]
+    fn test_reload_keeps_settings_on_error() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let project_root = temp_dir.path();
+        fs::create_dir_all(project_root.join(".codegraph")).unwrap();
+
+        let config = Config::from_path(project_root).unwrap();
+        assert!(config.reload_if_changed().is_none());
+        assert_eq!(config.settings().compression.max_lines, 50);
+
+        fs::write(config.config_path(), "[compression]\nmax_lines = 5\n").unwrap();
+        let changed = config.reload_if_changed().unwrap().unwrap();
+        assert_eq!(changed, vec!["compression"]);
+        assert_eq!(config.settings().compression.max_lines, 5);
+        assert!(config.reload().unwrap().is_empty());
+
+        // A snapshot taken before a reload is unaffected by it
+        let snapshot = config.settings();
+        fs::write(config.config_path(), "[compression\nmax_lines = 7\n").unwrap();
+        assert!(config.reload().is_err());
+        assert_eq!(config.settings().compression.max_lines, 5);
+        assert!(Arc::ptr_eq(&snapshot, &config.settings()));
+    }
+
+    #[test]
+    fn test_storage_cache_dir_and_alias() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let project_root = temp_dir.path().join("project");
+        fs::create_dir_all(project_root.join(".codegraph")).unwrap();
+        let cache = temp_dir.path().join("cache");
+        let config_toml = format!("[storage]\ncache_dir = {:?}\nproject_alias = \"my-service\"\n", cache);
+        fs::write(project_root.join(".codegraph/config.toml"), config_toml).unwrap();
+
+        let config = Config::from_path(&project_root).unwrap();
+        assert_eq!(config.cache_dir, cache.join("my-service"));
+        assert_eq!(config.store_db_path, cache.join("my-service/store.db"));
+
+        // The alias, not the path, names the store
+        let moved = temp_dir.path().join("moved");
+        fs::rename(&project_root, &moved).unwrap();
+        assert_eq!(Config::from_path(&moved).unwrap().cache_dir, cache.join("my-service"));
+
+        // An unusable alias falls back to the path hash
+        fs::write(moved.join(".codegraph/config.toml"), "[storage]\nproject_alias = \"..\"\n").unwrap();
+        let config = Config::from_path(&moved).unwrap();
+        let hash = Config::hash_project_path(&moved.canonicalize().unwrap());
+        assert!(config.cache_dir.ends_with(hash));
+    }
+
     #[test]
     fn test_init_codegraph_dir() {
         let temp_dir = tempfile::tempdir().unwrap();
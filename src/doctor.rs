@@ -0,0 +1,366 @@
+//! `doctor`: environment and installation checks with suggested fixes.
+//!
+//! Each step runs on its own, so it works when the server can't start: a broken config
+//! or database is reported instead of aborting the remaining checks that don't need it.
+
+use anyhow::Result;
+use serde::Serialize;
+use std::path::Path;
+
+use crate::code::languages::LANGUAGE_REGISTRY;
+use crate::config::Config;
+use crate::store::migrations::{latest_versions, schema_version};
+use crate::store::{CodeGraph, Store};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Status {
+    Ok,
+    Warning,
+    Error,
+}
+
+/// Outcome of one check
+#[derive(Debug, Clone, Serialize)]
+pub struct Diagnosis {
+    pub check: &'static str,
+    pub status: Status,
+    pub detail: String,
+    /// What to do about a warning or error
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub fix: Option<String>,
+}
+
+impl Diagnosis {
+    fn ok(check: &'static str, detail: impl Into<String>) -> Self {
+        Self { check, status: Status::Ok, detail: detail.into(), fix: None }
+    }
+
+    fn warning(check: &'static str, detail: impl Into<String>, fix: impl Into<String>) -> Self {
+        Self { check, status: Status::Warning, detail: detail.into(), fix: Some(fix.into()) }
+    }
+
+    fn error(check: &'static str, detail: impl Into<String>, fix: impl Into<String>) -> Self {
+        Self { check, status: Status::Error, detail: detail.into(), fix: Some(fix.into()) }
+    }
+}
+
+/// Run every check for the project at `project_root`. `graph` is the running server's
+/// in-memory graph, compared against the store when given.
+pub async fn diagnose(project_root: &Path, graph: Option<&tokio::sync::RwLock<CodeGraph>>) -> Vec<Diagnosis> {
+    let mut results = Vec::new();
+
+    let config = match Config::from_path(project_root) {
+        Ok(config) => config,
+        Err(e) => {
+            results.push(Diagnosis::error(
+                "project",
+                format!("Can't open {}: {:#}", project_root.display(), e),
+                "Check that the path exists and is readable, or pass --root",
+            ));
+            results.push(check_git(project_root));
+            return results;
+        }
+    };
+    results.push(Diagnosis::ok("project", format!("Project root: {}", config.project_root.display())));
+
+    results.push(check_config(&config));
+    results.extend(check_directories(&config));
+    results.extend(check_databases(&config).await);
+    results.push(check_grammars());
+    results.extend(check_consistency(&config, graph).await);
+    results.push(check_git(&config.project_root));
+    results
+}
+
+fn check_config(config: &Config) -> Diagnosis {
+    let report = config.validate();
+    if !report.errors.is_empty() {
+        Diagnosis::error(
+            "config",
+            report.errors.join("; "),
+            "Fix the listed keys in config.toml; until then the previous (or default) settings are used",
+        )
+    } else if !report.warnings.is_empty() {
+        Diagnosis::warning(
+            "config",
+            report.warnings.join("; "),
+            "Run validate_config for details and remove or correct the listed keys",
+        )
+    } else {
+        let files = match report.files.len() {
+            0 => "no config files, using defaults".to_string(),
+            n => format!("{} config files valid", n),
+        };
+        Diagnosis::ok("config", files)
+    }
+}
+
+/// The cache and .codegraph directories must exist and accept new files
+fn check_directories(config: &Config) -> Vec<Diagnosis> {
+    let dirs = [
+        ("cache_dir", &config.cache_dir, "Fix its permissions, or point [storage] cache_dir at a writable directory"),
+        ("codegraph_dir", &config.codegraph_dir, "Fix the permissions of .codegraph/ in the project root"),
+    ];
+    dirs.into_iter()
+        .map(|(check, dir, fix)| {
+            let probe = dir.join(".codegraph-doctor");
+            let writable = std::fs::create_dir_all(dir)
+                .and_then(|_| std::fs::write(&probe, b"ok"))
+                .and_then(|_| std::fs::remove_file(&probe));
+            match writable {
+                Ok(()) => Diagnosis::ok(check, format!("{} is writable", dir.display())),
+                Err(e) => Diagnosis::error(check, format!("{} is not writable: {}", dir.display(), e), fix),
+            }
+        })
+        .collect()
+}
+
+/// Both databases open, read, and have a schema this build understands
+async fn check_databases(config: &Config) -> Vec<Diagnosis> {
+    let (store_latest, learning_latest) = latest_versions();
+    // With [learning.remote], learnings live in the replica of the shared database
+    let learning_db_path = if config.settings().learning.remote.is_enabled() {
+        config.learning_replica_path()
+    } else {
+        config.learning_db_path.clone()
+    };
+    let databases = [
+        (
+            "store_db",
+            &config.store_db_path,
+            store_latest,
+            "store.db only holds the code graph: delete it and run index_project(full: true) to rebuild",
+        ),
+        (
+            "learning_db",
+            &learning_db_path,
+            learning_latest,
+            "learning.db holds patterns and failures: restore it from a backup (or git history) if you have one",
+        ),
+    ];
+
+    let mut results = Vec::new();
+    for (check, path, latest, fix) in databases {
+        if !path.exists() {
+            results.push(Diagnosis::ok(check, format!("{} will be created on first start", path.display())));
+            continue;
+        }
+        let version = match Store::open_database(path).await {
+            Ok(conn) => schema_version(&conn).await,
+            Err(e) => Err(e),
+        };
+        results.push(match version {
+            Ok(version) if version > latest => Diagnosis::error(
+                check,
+                format!("{} has schema v{}, newer than this build supports (v{})", path.display(), version, latest),
+                "Upgrade codegraph to the version that created it",
+            ),
+            Ok(version) if version < latest => Diagnosis::ok(
+                check,
+                format!("{} has schema v{}; it will be migrated to v{} on start", path.display(), version, latest),
+            ),
+            Ok(version) => Diagnosis::ok(check, format!("{} has schema v{}", path.display(), version)),
+            Err(e) => Diagnosis::error(check, format!("Can't read {}: {:#}", path.display(), e), fix),
+        });
+    }
+    results
+}
+
+/// Every bundled grammar loads and its queries compile against it
+fn check_grammars() -> Diagnosis {
+    let mut names: Vec<&String> = LANGUAGE_REGISTRY.keys().collect();
+    names.sort();
+    let mut broken = Vec::new();
+    for name in &names {
+        let language = &LANGUAGE_REGISTRY[*name];
+        let mut parser = tree_sitter::Parser::new();
+        let result = parser
+            .set_language(&language.tree_sitter_language)
+            .map_err(|e| e.to_string())
+            .and_then(|_| {
+                [language.queries.symbols, language.queries.references]
+                    .into_iter()
+                    .try_for_each(|query| {
+                        tree_sitter::Query::new(&language.tree_sitter_language, query)
+                            .map(|_| ())
+                            .map_err(|e| e.to_string())
+                    })
+            });
+        if let Err(e) = result {
+            broken.push(format!("{}: {}", name, e));
+        }
+    }
+
+    if broken.is_empty() {
+        let names: Vec<&str> = names.iter().map(|name| name.as_str()).collect();
+        Diagnosis::ok("grammars", format!("{} grammars loaded ({})", names.len(), names.join(", ")))
+    } else {
+        Diagnosis::error(
+            "grammars",
+            broken.join("; "),
+            "Reinstall codegraph from a clean build; the grammar and tree-sitter versions don't match",
+        )
+    }
+}
+
+/// Stored edges and nodes reference things that exist, and the running graph matches the store
+async fn check_consistency(config: &Config, graph: Option<&tokio::sync::RwLock<CodeGraph>>) -> Vec<Diagnosis> {
+    let store = match Store::open(config).await {
+        Ok(store) => store,
+        // Already reported by check_databases
+        Err(_) => return Vec::new(),
+    };
+    match consistency(&store, graph).await {
+        Ok(results) => results,
+        Err(e) => vec![Diagnosis::error(
+            "consistency",
+            format!("Consistency queries failed: {:#}", e),
+            "Run index_project(full: true); if it keeps failing, delete store.db to rebuild it",
+        )],
+    }
+}
+
+async fn consistency(store: &Store, graph: Option<&tokio::sync::RwLock<CodeGraph>>) -> Result<Vec<Diagnosis>> {
+    let mut results = Vec::new();
+    let stats = store.stats().await?;
+    if stats.files == 0 {
+        results.push(Diagnosis::warning(
+            "index",
+            "Nothing is indexed yet",
+            "Run index_project(full: true) (or `codegraph index --full`)",
+        ));
+    } else {
+        results.push(Diagnosis::ok("index", format!("{} files, {} symbols indexed", stats.files, stats.symbols)));
+    }
+
+    let dangling = store.dangling_edges().await?.len();
+    let orphaned = store.orphaned_nodes().await?.len();
+    results.push(if dangling + orphaned > 0 {
+        Diagnosis::warning(
+            "store",
+            format!("{} dangling edges, {} nodes from files no longer indexed", dangling, orphaned),
+            "Run index_project(full: true) to rebuild the code graph",
+        )
+    } else {
+        Diagnosis::ok("store", "No dangling edges or orphaned nodes")
+    });
+
+    if let Some(graph) = graph {
+        let count = |table: &str| format!("SELECT COUNT(*) FROM {} WHERE graph = 'code'", table);
+        let mut stored = Vec::new();
+        for table in ["nodes", "edges"] {
+            let mut rows = store.code_db.query(&count(table), ()).await?;
+            stored.push(rows.next().await?.map_or(0, |row| row.get::<i64>(0).unwrap_or(0)) as usize);
+        }
+        let graph = graph.read().await;
+        let loaded = [graph.graph.node_count(), graph.graph.edge_count()];
+        results.push(if loaded[..] == stored[..] {
+            Diagnosis::ok("graph", format!("In-memory graph matches the store ({} nodes, {} edges)", loaded[0], loaded[1]))
+        } else {
+            Diagnosis::warning(
+                "graph",
+                format!(
+                    "In-memory graph has {} nodes and {} edges, the store {} and {}",
+                    loaded[0], loaded[1], stored[0], stored[1]
+                ),
+                "Run index_project(full: true) to reload the graph",
+            )
+        });
+    }
+    Ok(results)
+}
+
+/// git is optional, but root detection, git_context, and diff compression rely on it
+fn check_git(project_root: &Path) -> Diagnosis {
+    let version = std::process::Command::new("git").arg("--version").output();
+    let version = match version {
+        Ok(output) if output.status.success() => String::from_utf8_lossy(&output.stdout).trim().to_string(),
+        _ => {
+            return Diagnosis::warning(
+                "git",
+                "git is not installed or not on PATH",
+                "Install git so git_context and diff compression work",
+            )
+        }
+    };
+    if project_root.ancestors().any(|dir| dir.join(".git").exists()) {
+        Diagnosis::ok("git", version)
+    } else {
+        Diagnosis::warning(
+            "git",
+            format!("{}, but {} is not in a git repository", version, project_root.display()),
+            "Run `git init`, or create .codegraph/ in the project root so it's detected without git",
+        )
+    }
+}
+
+/// Human-readable report, one line per check with its fix underneath
+pub fn format_report(results: &[Diagnosis]) -> String {
+    let mut lines = Vec::new();
+    for result in results {
+        let icon = match result.status {
+            Status::Ok => "✅",
+            Status::Warning => "⚠️",
+            Status::Error => "❌",
+        };
+        lines.push(format!("{} {}: {}", icon, result.check, result.detail));
+        if let Some(fix) = &result.fix {
+            lines.push(format!("   → {}", fix));
+        }
+    }
+    let errors = results.iter().filter(|r| r.status == Status::Error).count();
+    let warnings = results.iter().filter(|r| r.status == Status::Warning).count();
+    lines.push(String::new());
+    lines.push(format!("{} errors, {} warnings", errors, warnings));
+    lines.join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn status_of<'a>(results: &'a [Diagnosis], check: &str) -> Option<&'a Diagnosis> {
+        results.iter().find(|r| r.check == check)
+    }
+
+    #[tokio::test]
+    async fn test_doctor_reports_problems() {
+        let temp = TempDir::new().unwrap();
+        let root = temp.path();
+        std::fs::create_dir_all(root.join(".codegraph")).unwrap();
+        std::fs::write(root.join(".codegraph/config.toml"), "[indexing]\ninclude = [\"src/[\"]\n").unwrap();
+        let cache = temp.path().join("cache");
+        let config = Config::from_path(root).unwrap();
+        // Keep the databases inside the temp dir instead of the user's cache
+        let config = Config {
+            cache_dir: cache.clone(),
+            store_db_path: cache.join("store.db"),
+            ..config
+        };
+
+        assert_eq!(check_config(&config).status, Status::Error);
+        assert!(check_directories(&config).iter().all(|d| d.status == Status::Ok));
+        assert_eq!(check_grammars().status, Status::Ok);
+
+        // A store.db that isn't a database
+        std::fs::create_dir_all(&cache).unwrap();
+        std::fs::write(&config.store_db_path, "not a database").unwrap();
+        let databases = check_databases(&config).await;
+        let store = status_of(&databases, "store_db").unwrap();
+        assert_eq!(store.status, Status::Error);
+        assert!(store.fix.as_deref().unwrap().contains("delete it"));
+
+        std::fs::remove_file(&config.store_db_path).unwrap();
+        let graph = tokio::sync::RwLock::new(CodeGraph::new());
+        graph.write().await.add_node("a.rs::f".to_string(), "function".to_string(), serde_json::json!({}));
+        let results = check_consistency(&config, Some(&graph)).await;
+        assert_eq!(status_of(&results, "index").unwrap().status, Status::Warning);
+        assert_eq!(status_of(&results, "store").unwrap().status, Status::Ok);
+        assert_eq!(status_of(&results, "graph").unwrap().status, Status::Warning);
+
+        let report = format_report(&databases);
+        assert!(report.contains("❌ store_db") && report.ends_with("1 errors, 0 warnings"), "{}", report);
+    }
+}
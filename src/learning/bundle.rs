@@ -0,0 +1,443 @@
+//! Learning bundles: patterns, failures, manual instructions, and niches packaged as one
+//! versioned JSON file, so learnings can move between repos and teammates through git.
+//!
+//! Imported items get new IDs, except those that duplicate an existing item, which map to
+//! it instead. Pins travel with the bundle and follow the same mapping.
+
+use anyhow::{bail, Result};
+use libsql::Connection;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::sync::Arc;
+use uuid::Uuid;
+
+use super::failures::{Failure, FailureStore};
+use super::niches::{Niche, NicheStore};
+use super::patterns::{find_duplicate_of, Pattern, PatternStore};
+use super::Scope;
+
+/// Bundle format version; bumped when a change would make older builds misread a bundle
+pub const BUNDLE_VERSION: u32 = 1;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LearningBundle {
+    pub version: u32,
+    pub exported_at: String,
+    /// Name of the project the bundle was exported from
+    pub source: String,
+    pub patterns: Vec<Pattern>,
+    pub failures: Vec<Failure>,
+    pub instructions: Vec<BundledInstruction>,
+    pub niches: Vec<Niche>,
+    /// Priorities of pinned items in the bundle, by bundle ID
+    #[serde(default)]
+    pub priorities: BTreeMap<String, String>,
+}
+
+/// A manual instruction as stored, with its raw category and scope
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BundledInstruction {
+    pub id: String,
+    pub instruction: String,
+    pub category: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reason: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub scope: Option<Scope>,
+}
+
+/// New and skipped items of one kind
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ImportCounts {
+    pub imported: usize,
+    pub duplicates: usize,
+}
+
+/// A bundle item that wasn't imported because an equivalent one already exists
+#[derive(Debug, Clone, Serialize)]
+pub struct Duplicate {
+    /// pattern, failure, instruction, or niche
+    pub kind: &'static str,
+    pub bundle_id: String,
+    pub existing_id: String,
+    /// Intent, cause, or instruction text of the bundle item
+    pub text: String,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct ImportStats {
+    pub patterns: ImportCounts,
+    pub failures: ImportCounts,
+    pub instructions: ImportCounts,
+    pub niches: ImportCounts,
+    /// Pins set on imported or duplicate items
+    pub priorities: usize,
+    pub duplicates: Vec<Duplicate>,
+    /// Local ID of every bundle item, imported or duplicate
+    pub id_map: HashMap<String, String>,
+}
+
+/// Package the learning database's patterns (at or above `min_confidence`), failures,
+/// manual instructions, and niches
+pub async fn export_bundle(db: &Connection, source: &str, min_confidence: f32) -> Result<LearningBundle> {
+    let shared = Arc::new(db.clone());
+    let mut patterns = PatternStore::new(shared.clone()).list_all().await?;
+    patterns.retain(|p| p.confidence >= min_confidence);
+    patterns.sort_by(|a, b| a.created_at.cmp(&b.created_at).then_with(|| a.id.cmp(&b.id)));
+    let mut failures = FailureStore::new(shared.clone()).list_all().await?;
+    failures.sort_by(|a, b| a.created_at.cmp(&b.created_at).then_with(|| a.id.cmp(&b.id)));
+    let niches = NicheStore::new(shared).list_niches(None).await?.into_iter().map(|n| n.niche).collect();
+
+    let mut instructions = Vec::new();
+    let mut rows = db
+        .query(
+            "SELECT id, instruction, category, reason, scope FROM instructions ORDER BY created_at, id",
+            (),
+        )
+        .await?;
+    while let Some(row) = rows.next().await? {
+        let reason: Option<String> = row.get(3)?;
+        instructions.push(BundledInstruction {
+            id: row.get(0)?,
+            instruction: row.get(1)?,
+            category: row.get(2)?,
+            reason: reason.filter(|r| !r.is_empty()),
+            scope: row.get::<Option<String>>(4)?.map(|json| serde_json::from_str(&json)).transpose()?,
+        });
+    }
+
+    let ids: Vec<&str> = patterns
+        .iter()
+        .map(|p| p.id.as_str())
+        .chain(failures.iter().map(|f| f.id.as_str()))
+        .chain(instructions.iter().map(|i| i.id.as_str()))
+        .collect();
+    let mut priorities = BTreeMap::new();
+    let mut rows = db.query("SELECT id, priority FROM instruction_priorities", ()).await?;
+    while let Some(row) = rows.next().await? {
+        let id: String = row.get(0)?;
+        if ids.contains(&id.as_str()) {
+            priorities.insert(id, row.get(1)?);
+        }
+    }
+
+    Ok(LearningBundle {
+        version: BUNDLE_VERSION,
+        exported_at: chrono::Utc::now().to_rfc3339(),
+        source: source.to_string(),
+        patterns,
+        failures,
+        instructions,
+        niches,
+        priorities,
+    })
+}
+
+/// Add a bundle's learnings to the database in one transaction. A pattern counts as a
+/// duplicate when `consolidate_patterns` would merge it into an existing one at
+/// `duplicate_threshold`; failures and instructions when their text matches ignoring case.
+/// With `dry_run`, nothing is written but the stats are the same.
+pub async fn import_bundle(
+    db: &Connection,
+    bundle: &LearningBundle,
+    duplicate_threshold: f32,
+    dry_run: bool,
+) -> Result<ImportStats> {
+    if bundle.version > BUNDLE_VERSION {
+        bail!(
+            "Bundle version {} is newer than this build supports ({}); upgrade codegraph to import it",
+            bundle.version,
+            BUNDLE_VERSION
+        );
+    }
+
+    let shared = Arc::new(db.clone());
+    let mut patterns = PatternStore::new(shared.clone()).list_all().await?;
+    let mut failures = FailureStore::new(shared.clone()).list_all().await?;
+    let mut niches: Vec<String> =
+        NicheStore::new(shared).list_niches(None).await?.into_iter().map(|n| n.niche.id).collect();
+    let mut instructions = Vec::new();
+    let mut rows = db.query("SELECT id, instruction FROM instructions", ()).await?;
+    while let Some(row) = rows.next().await? {
+        instructions.push((row.get::<String>(0)?, normalize(&row.get::<String>(1)?)));
+    }
+
+    let mut stats = ImportStats::default();
+    let now = chrono::Utc::now().timestamp();
+    let tx = db.transaction().await?;
+
+    for pattern in &bundle.patterns {
+        if let Some((existing, _)) = find_duplicate_of(pattern, &patterns, duplicate_threshold) {
+            stats.record_duplicate("pattern", &pattern.id, &existing.id, &pattern.intent);
+            stats.patterns.duplicates += 1;
+            continue;
+        }
+        let imported = Pattern {
+            id: Uuid::new_v4().to_string(),
+            created_at: now,
+            ..pattern.clone()
+        };
+        if !dry_run {
+            tx.execute(
+                "INSERT INTO patterns (id, intent, mechanism, examples, scope, confidence, usage_count,
+                     success_count, last_validated, created_at, updated_at)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11)",
+                libsql::params![
+                    imported.id.as_str(),
+                    imported.intent.as_str(),
+                    imported.mechanism.as_deref().unwrap_or(""),
+                    serde_json::to_string(&imported.examples)?,
+                    serde_json::to_string(&imported.scope)?,
+                    imported.confidence as f64,
+                    imported.usage_count,
+                    imported.success_count,
+                    imported.last_validated,
+                    imported.created_at,
+                    imported.updated_at
+                ],
+            )
+            .await?;
+        }
+        stats.id_map.insert(pattern.id.clone(), imported.id.clone());
+        stats.patterns.imported += 1;
+        patterns.push(imported);
+    }
+
+    for failure in &bundle.failures {
+        let existing = failures.iter().find(|f| {
+            normalize(&f.cause) == normalize(&failure.cause)
+                && normalize(&f.avoidance_rule) == normalize(&failure.avoidance_rule)
+        });
+        if let Some(existing) = existing {
+            stats.record_duplicate("failure", &failure.id, &existing.id, &failure.cause);
+            stats.failures.duplicates += 1;
+            continue;
+        }
+        let imported = Failure {
+            id: Uuid::new_v4().to_string(),
+            created_at: now,
+            ..failure.clone()
+        };
+        if !dry_run {
+            tx.execute(
+                "INSERT INTO failures (id, cause, avoidance_rule, severity, scope, times_prevented, created_at,
+                     updated_at)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+                libsql::params![
+                    imported.id.as_str(),
+                    imported.cause.as_str(),
+                    imported.avoidance_rule.as_str(),
+                    imported.severity.to_str(),
+                    serde_json::to_string(&imported.scope)?,
+                    imported.times_prevented,
+                    imported.created_at,
+                    imported.updated_at
+                ],
+            )
+            .await?;
+        }
+        stats.id_map.insert(failure.id.clone(), imported.id.clone());
+        stats.failures.imported += 1;
+        failures.push(imported);
+    }
+
+    for instruction in &bundle.instructions {
+        let text = normalize(&instruction.instruction);
+        if let Some((existing, _)) = instructions.iter().find(|(_, existing)| *existing == text) {
+            stats.record_duplicate("instruction", &instruction.id, existing, &instruction.instruction);
+            stats.instructions.duplicates += 1;
+            continue;
+        }
+        let id = Uuid::new_v4().to_string();
+        if !dry_run {
+            let scope = instruction.scope.as_ref().map(serde_json::to_string).transpose()?;
+            tx.execute(
+                "INSERT INTO instructions (id, instruction, category, reason, created_at, scope)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+                libsql::params![
+                    id.as_str(),
+                    instruction.instruction.as_str(),
+                    instruction.category.as_str(),
+                    instruction.reason.as_deref().unwrap_or(""),
+                    now,
+                    scope
+                ],
+            )
+            .await?;
+        }
+        stats.id_map.insert(instruction.id.clone(), id.clone());
+        stats.instructions.imported += 1;
+        instructions.push((id, text));
+    }
+
+    // Niche IDs are names shared by every project, so they map to themselves
+    for niche in &bundle.niches {
+        if niches.contains(&niche.id) {
+            stats.record_duplicate("niche", &niche.id, &niche.id, &niche.feature_description);
+            stats.niches.duplicates += 1;
+            continue;
+        }
+        if !dry_run {
+            tx.execute(
+                "INSERT INTO niches (id, task_type, feature_description, created_at) VALUES (?1, ?2, ?3, ?4)",
+                libsql::params![niche.id.as_str(), niche.task_type.as_str(), niche.feature_description.as_str(), now],
+            )
+            .await?;
+        }
+        stats.id_map.insert(niche.id.clone(), niche.id.clone());
+        stats.niches.imported += 1;
+        niches.push(niche.id.clone());
+    }
+
+    // A pin from the bundle applies to the item it mapped to, but never replaces a local pin
+    for (bundle_id, priority) in &bundle.priorities {
+        let Some(id) = stats.id_map.get(bundle_id) else {
+            continue;
+        };
+        if !dry_run {
+            let pinned = tx
+                .execute(
+                    "INSERT OR IGNORE INTO instruction_priorities (id, priority, updated_at) VALUES (?1, ?2, ?3)",
+                    libsql::params![id.as_str(), priority.as_str(), now],
+                )
+                .await?;
+            if pinned == 0 {
+                continue;
+            }
+        }
+        stats.priorities += 1;
+    }
+
+    if !dry_run {
+        tx.commit().await?;
+    }
+    Ok(stats)
+}
+
+impl ImportStats {
+    fn record_duplicate(&mut self, kind: &'static str, bundle_id: &str, existing_id: &str, text: &str) {
+        self.id_map.insert(bundle_id.to_string(), existing_id.to_string());
+        self.duplicates.push(Duplicate {
+            kind,
+            bundle_id: bundle_id.to_string(),
+            existing_id: existing_id.to_string(),
+            text: text.to_string(),
+        });
+    }
+}
+
+/// Text compared for duplicate failures and instructions
+fn normalize(text: &str) -> String {
+    text.split_whitespace().collect::<Vec<_>>().join(" ").to_lowercase()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::learning::failures::{NewFailure, Severity};
+    use crate::learning::patterns::{NewPattern, DUPLICATE_THRESHOLD};
+    use crate::skill::categories::{InstructionCategory, InstructionPriority};
+    use crate::skill::distill::ManualInstructionStore;
+    use crate::store::Store;
+    use tempfile::TempDir;
+
+    async fn open(temp: &TempDir, name: &str) -> Connection {
+        let conn = Store::open_database(&temp.path().join(name)).await.unwrap();
+        crate::store::migrations::apply_learning_migrations(&conn).await.unwrap();
+        conn
+    }
+
+    #[tokio::test]
+    async fn test_export_import_round_trip() {
+        let temp = TempDir::new().unwrap();
+        let source = open(&temp, "source.db").await;
+        let target = open(&temp, "target.db").await;
+        let scope = Scope::default();
+
+        let patterns = PatternStore::new(Arc::new(source.clone()));
+        let retry = patterns
+            .create(&NewPattern {
+                intent: "Retry flaky network calls with exponential backoff".to_string(),
+                mechanism: None,
+                examples: vec!["retry(3, backoff)".to_string()],
+                scope: scope.clone(),
+                confidence: 0.9,
+            })
+            .await
+            .unwrap();
+        patterns.update_usage(&retry.id, true).await.unwrap();
+        patterns
+            .create(&NewPattern {
+                intent: "Maybe cache config lookups".to_string(),
+                mechanism: None,
+                examples: vec![],
+                scope: scope.clone(),
+                confidence: 0.3,
+            })
+            .await
+            .unwrap();
+        let failure = FailureStore::new(Arc::new(source.clone()))
+            .create(&NewFailure {
+                cause: "SQL injection risk".to_string(),
+                avoidance_rule: "Always use parameterized queries".to_string(),
+                severity: Severity::Critical,
+                scope: scope.clone(),
+            })
+            .await
+            .unwrap();
+        let instructions = ManualInstructionStore::new(Arc::new(source.clone()));
+        let instruction = instructions
+            .add("Run migrations before tests", InstructionCategory::Testing, Some("CI order"), &scope)
+            .await
+            .unwrap();
+        instructions.set_priority(&failure.id, InstructionPriority::Critical).await.unwrap();
+
+        let bundle = export_bundle(&source, "api", 0.5).await.unwrap();
+        assert_eq!(bundle.version, BUNDLE_VERSION);
+        assert_eq!(bundle.patterns.len(), 1, "low-confidence patterns are left out");
+        assert_eq!((bundle.failures.len(), bundle.instructions.len()), (1, 1));
+        assert_eq!(bundle.priorities.get(&failure.id).map(String::as_str), Some("critical"));
+        let bundle: LearningBundle = serde_json::from_str(&serde_json::to_string(&bundle).unwrap()).unwrap();
+
+        // The target already knows a reworded version of the pattern
+        let existing = PatternStore::new(Arc::new(target.clone()))
+            .create(&NewPattern {
+                intent: "Retry flaky network calls using exponential backoff".to_string(),
+                mechanism: None,
+                examples: vec![],
+                scope: scope.clone(),
+                confidence: 0.8,
+            })
+            .await
+            .unwrap();
+
+        let preview = import_bundle(&target, &bundle, DUPLICATE_THRESHOLD, true).await.unwrap();
+        assert_eq!(preview.failures.imported, 1);
+        assert_eq!(FailureStore::new(Arc::new(target.clone())).list_all().await.unwrap().len(), 0);
+
+        let stats = import_bundle(&target, &bundle, DUPLICATE_THRESHOLD, false).await.unwrap();
+        assert_eq!(stats.patterns, ImportCounts { imported: 0, duplicates: 1 });
+        assert_eq!(stats.failures, ImportCounts { imported: 1, duplicates: 0 });
+        assert_eq!(stats.instructions, ImportCounts { imported: 1, duplicates: 0 });
+        assert_eq!(stats.id_map[&retry.id], existing.id);
+        assert_eq!(stats.priorities, 1);
+
+        // Imported items get new IDs, and pins follow them
+        let imported_failure = &stats.id_map[&failure.id];
+        assert_ne!(imported_failure, &failure.id);
+        let target_instructions = ManualInstructionStore::new(Arc::new(target.clone()));
+        let pins = target_instructions.priorities().await.unwrap();
+        assert_eq!(pins.get(imported_failure), Some(&InstructionPriority::Critical));
+        let copied = target_instructions.get(&stats.id_map[&instruction]).await.unwrap().unwrap();
+        assert_eq!(copied.instruction, "Run migrations before tests");
+
+        // Importing again finds everything already there
+        let again = import_bundle(&target, &bundle, DUPLICATE_THRESHOLD, false).await.unwrap();
+        assert_eq!(again.duplicates.len(), 3);
+        assert_eq!(again.failures.imported + again.instructions.imported + again.priorities, 0);
+
+        let mut newer = bundle.clone();
+        newer.version = BUNDLE_VERSION + 1;
+        assert!(import_bundle(&target, &newer, DUPLICATE_THRESHOLD, false).await.is_err());
+    }
+}
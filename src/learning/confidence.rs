@@ -1,6 +1,9 @@
 use super::patterns::Pattern;
 use crate::store::CodeGraph;
 
+/// Default half-life for confidence decay, matching `[learning] decay_half_life`
+pub const DEFAULT_HALF_LIFE_DAYS: i64 = 90;
+
 /// Calculate effective confidence for a pattern
 ///
 /// Formula:
@@ -48,7 +48,7 @@ pub fn detect_conflicts(patterns: &[Pattern]) -> Vec<Conflict> {
 }
 
 /// Check if two scopes overlap
-fn scopes_overlap(scope_a: &Scope, scope_b: &Scope) -> bool {
+pub(crate) fn scopes_overlap(scope_a: &Scope, scope_b: &Scope) -> bool {
     // If both scopes are empty, they overlap everywhere
     if scope_a.include_paths.is_empty()
         && scope_a.exclude_paths.is_empty()
@@ -112,7 +112,7 @@ fn patterns_overlap(pattern_a: &str, pattern_b: &str) -> bool {
 }
 
 /// Detect if two patterns contradict each other
-fn detect_contradiction(pattern_a: &Pattern, pattern_b: &Pattern) -> Option<String> {
+pub(crate) fn detect_contradiction(pattern_a: &Pattern, pattern_b: &Pattern) -> Option<String> {
     // Tokenize descriptions
     let tokens_a = tokenize(&pattern_a.intent);
     let tokens_b = tokenize(&pattern_b.intent);
@@ -0,0 +1,321 @@
+//! Semantic similarity for recalling patterns and failures. Each item's text is embedded once
+//! and cached in learning.db's `embeddings` table, keyed by model and a hash of the text, so
+//! edits and provider changes only re-embed what changed.
+//!
+//! The provider is pluggable: the built-in local embedder needs no model download, and the
+//! http one talks to any OpenAI-compatible `/embeddings` endpoint.
+
+use anyhow::{Context, Result};
+use libsql::Connection;
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+use tracing::{debug, warn};
+use xxhash_rust::xxh3::xxh3_64;
+
+use crate::config::{Config, EmbeddingProvider};
+
+/// Dimensions of the local embedder's vectors
+const HASH_DIM: usize = 512;
+
+/// How long the http provider may take per batch
+const HTTP_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Turns text into vectors whose cosine similarity reflects how related the texts are
+pub trait Embedder: Send + Sync {
+    /// Identifies the vector space; vectors from different models are never compared
+    fn model(&self) -> String;
+
+    /// One vector per text, in order. Called off the async runtime, so it may block.
+    fn embed(&self, texts: &[String]) -> Result<Vec<Vec<f32>>>;
+}
+
+/// Feature-hashed bag of words and character trigrams. Picks up shared vocabulary and word
+/// variants (retry/retries, auth/authentication) rather than meaning, but works offline.
+pub struct HashEmbedder;
+
+impl Embedder for HashEmbedder {
+    fn model(&self) -> String {
+        format!("local-hash-{}", HASH_DIM)
+    }
+
+    fn embed(&self, texts: &[String]) -> Result<Vec<Vec<f32>>> {
+        Ok(texts.iter().map(|text| hash_embed(text)).collect())
+    }
+}
+
+fn hash_embed(text: &str) -> Vec<f32> {
+    let mut vector = vec![0.0; HASH_DIM];
+    let mut add = |feature: &str| {
+        let hash = xxh3_64(feature.as_bytes());
+        let sign = if hash >> 63 == 0 { 1.0 } else { -1.0 };
+        vector[(hash % HASH_DIM as u64) as usize] += sign;
+    };
+
+    for word in text.split(|c: char| !c.is_alphanumeric()).filter(|w| w.len() >= 3) {
+        let word = word.to_lowercase();
+        add(&word);
+        let chars: Vec<char> = format!("<{}>", word).chars().collect();
+        for gram in chars.windows(3) {
+            add(&gram.iter().collect::<String>());
+        }
+    }
+    vector
+}
+
+/// An OpenAI-compatible embeddings endpoint (Ollama, llama.cpp server, OpenAI, ...)
+pub struct HttpEmbedder {
+    pub url: String,
+    pub model: String,
+    pub api_key: Option<String>,
+}
+
+impl Embedder for HttpEmbedder {
+    fn model(&self) -> String {
+        format!("http:{}", self.model)
+    }
+
+    fn embed(&self, texts: &[String]) -> Result<Vec<Vec<f32>>> {
+        let mut request = ureq::post(&self.url).timeout(HTTP_TIMEOUT);
+        if let Some(key) = &self.api_key {
+            request = request.set("Authorization", &format!("Bearer {}", key));
+        }
+        let response: Value = request
+            .send_json(json!({ "model": self.model, "input": texts }))
+            .with_context(|| format!("Embedding request to {} failed", self.url))?
+            .into_json()?;
+
+        let mut data: Vec<&Value> = response
+            .get("data")
+            .and_then(|v| v.as_array())
+            .context("Embedding response has no data array")?
+            .iter()
+            .collect();
+        data.sort_by_key(|item| item.get("index").and_then(|v| v.as_u64()).unwrap_or(0));
+        let vectors: Vec<Vec<f32>> = data
+            .iter()
+            .map(|item| {
+                let values = item.get("embedding").and_then(|v| v.as_array());
+                values.map(|values| values.iter().filter_map(|v| v.as_f64()).map(|v| v as f32).collect())
+            })
+            .collect::<Option<_>>()
+            .context("Embedding response item has no embedding")?;
+        if vectors.len() != texts.len() {
+            anyhow::bail!("Embedding response has {} vectors for {} texts", vectors.len(), texts.len());
+        }
+        Ok(vectors)
+    }
+}
+
+enum Source {
+    /// Follow `[learning.embeddings]`, so config reloads switch providers
+    Settings(Arc<Config>),
+    Fixed(Arc<dyn Embedder>),
+}
+
+/// Cached embeddings of patterns and failures, compared against a query
+pub struct EmbeddingIndex {
+    db: Arc<Connection>,
+    source: Source,
+}
+
+impl EmbeddingIndex {
+    /// Use the provider configured in `[learning.embeddings]`
+    pub fn new(db: Arc<Connection>, config: Arc<Config>) -> Self {
+        Self {
+            db,
+            source: Source::Settings(config),
+        }
+    }
+
+    /// Use a specific embedder regardless of config
+    pub fn with_embedder(db: Arc<Connection>, embedder: Arc<dyn Embedder>) -> Self {
+        Self {
+            db,
+            source: Source::Fixed(embedder),
+        }
+    }
+
+    fn embedder(&self) -> Option<Arc<dyn Embedder>> {
+        let config = match &self.source {
+            Source::Fixed(embedder) => return Some(embedder.clone()),
+            Source::Settings(config) => config.settings().learning.embeddings.clone(),
+        };
+        match config.provider {
+            EmbeddingProvider::Off => None,
+            EmbeddingProvider::Local => Some(Arc::new(HashEmbedder)),
+            EmbeddingProvider::Http => Some(Arc::new(HttpEmbedder {
+                url: config.url,
+                model: config.model,
+                api_key: Some(config.api_key_env)
+                    .filter(|name| !name.is_empty())
+                    .and_then(|name| std::env::var(name).ok()),
+            })),
+        }
+    }
+
+    /// Similarity (0.0-1.0) of each `(id, text)` item to the query. Items not yet embedded
+    /// with the current model, or whose text changed, are embedded and cached first. Empty
+    /// when embeddings are off or the provider fails, so callers fall back to keywords.
+    pub async fn similarities(&self, kind: &str, query: &str, items: &[(String, String)]) -> HashMap<String, f32> {
+        let Some(embedder) = self.embedder() else {
+            return HashMap::new();
+        };
+        match self.try_similarities(embedder, kind, query, items).await {
+            Ok(similarities) => similarities,
+            Err(e) => {
+                warn!("Semantic {} recall unavailable, using keywords only: {:#}", kind, e);
+                HashMap::new()
+            }
+        }
+    }
+
+    async fn try_similarities(
+        &self,
+        embedder: Arc<dyn Embedder>,
+        kind: &str,
+        query: &str,
+        items: &[(String, String)],
+    ) -> Result<HashMap<String, f32>> {
+        let model = embedder.model();
+        let mut cached: HashMap<String, (String, Vec<f32>)> = HashMap::new();
+        let mut rows = self
+            .db
+            .query(
+                "SELECT id, text_hash, vector FROM embeddings WHERE kind = ?1 AND model = ?2",
+                [kind, model.as_str()],
+            )
+            .await?;
+        while let Some(row) = rows.next().await? {
+            let bytes: Vec<u8> = row.get(2)?;
+            cached.insert(row.get(0)?, (row.get(1)?, from_bytes(&bytes)));
+        }
+
+        let mut vectors: HashMap<&str, Vec<f32>> = HashMap::new();
+        let mut stale = Vec::new();
+        for (id, text) in items {
+            let hash = format!("{:016x}", xxh3_64(text.as_bytes()));
+            match cached.remove(id) {
+                Some((cached_hash, vector)) if cached_hash == hash => {
+                    vectors.insert(id, vector);
+                }
+                _ => stale.push((id.as_str(), text.clone(), hash)),
+            }
+        }
+
+        // The query goes in the same batch, so a warm cache costs one call
+        let mut texts: Vec<String> = stale.iter().map(|(_, text, _)| text.clone()).collect();
+        texts.push(query.to_string());
+        debug!("Embedding {} {} texts with {}", texts.len() - 1, kind, model);
+        let mut embedded = tokio::task::spawn_blocking(move || embedder.embed(&texts)).await??;
+        let query_vector = embedded.pop().context("Embedder returned no vectors")?;
+
+        for ((id, _, hash), vector) in stale.into_iter().zip(embedded) {
+            self.db
+                .execute(
+                    "INSERT OR REPLACE INTO embeddings (kind, id, model, text_hash, vector)
+                     VALUES (?1, ?2, ?3, ?4, ?5)",
+                    libsql::params![kind, id, model.as_str(), hash, to_bytes(&vector)],
+                )
+                .await?;
+            vectors.insert(id, vector);
+        }
+
+        Ok(vectors
+            .into_iter()
+            .map(|(id, vector)| (id.to_string(), cosine(&query_vector, &vector).max(0.0)))
+            .collect())
+    }
+}
+
+fn cosine(a: &[f32], b: &[f32]) -> f32 {
+    if a.len() != b.len() {
+        return 0.0;
+    }
+    let dot: f32 = a.iter().zip(b).map(|(x, y)| x * y).sum();
+    let norm = |v: &[f32]| v.iter().map(|x| x * x).sum::<f32>().sqrt();
+    let norms = norm(a) * norm(b);
+    if norms == 0.0 {
+        0.0
+    } else {
+        dot / norms
+    }
+}
+
+fn to_bytes(vector: &[f32]) -> Vec<u8> {
+    vector.iter().flat_map(|v| v.to_le_bytes()).collect()
+}
+
+fn from_bytes(bytes: &[u8]) -> Vec<f32> {
+    bytes
+        .chunks_exact(4)
+        .map(|chunk| f32::from_le_bytes([chunk[0], chunk[1], chunk[2], chunk[3]]))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::store::Store;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use tempfile::TempDir;
+
+    /// Counts how many texts it has been asked to embed
+    struct CountingEmbedder(AtomicUsize);
+
+    impl Embedder for CountingEmbedder {
+        fn model(&self) -> String {
+            "counting".to_string()
+        }
+
+        fn embed(&self, texts: &[String]) -> Result<Vec<Vec<f32>>> {
+            self.0.fetch_add(texts.len(), Ordering::SeqCst);
+            HashEmbedder.embed(texts)
+        }
+    }
+
+    #[test]
+    fn test_hash_embedder_similarity() {
+        let vectors = HashEmbedder
+            .embed(&[
+                "add retries to fetching".to_string(),
+                "Retry flaky HTTP requests with exponential backoff".to_string(),
+                "Use the builder pattern for config structs".to_string(),
+            ])
+            .unwrap();
+        let related = cosine(&vectors[0], &vectors[1]);
+        let unrelated = cosine(&vectors[0], &vectors[2]);
+        assert!(related > unrelated + 0.1, "{} vs {}", related, unrelated);
+        assert!((cosine(&vectors[1], &vectors[1]) - 1.0).abs() < 1e-5);
+    }
+
+    #[tokio::test]
+    async fn test_embeddings_are_cached() {
+        let temp = TempDir::new().unwrap();
+        let config = Config::from_path(temp.path()).unwrap();
+        config.ensure_dirs().unwrap();
+        let store = Store::open(&config).await.unwrap();
+        let embedder = Arc::new(CountingEmbedder(AtomicUsize::new(0)));
+        let index = EmbeddingIndex::with_embedder(Arc::new(store.learning_db), embedder.clone());
+
+        let mut items = vec![
+            ("a".to_string(), "Retry flaky requests".to_string()),
+            ("b".to_string(), "Builder pattern for config".to_string()),
+        ];
+        let first = index.similarities("pattern", "retries", &items).await;
+        assert_eq!(embedder.0.load(Ordering::SeqCst), 3);
+        assert!(first["a"] > first["b"]);
+
+        // Only the query is embedded once the items are cached
+        let second = index.similarities("pattern", "retries", &items).await;
+        assert_eq!(embedder.0.load(Ordering::SeqCst), 4);
+        assert_eq!(first, second);
+
+        // An edited item is re-embedded
+        items[1].1 = "Retry with backoff".to_string();
+        let third = index.similarities("pattern", "retries", &items).await;
+        assert_eq!(embedder.0.load(Ordering::SeqCst), 6);
+        assert!(third["b"] > first["b"]);
+    }
+}
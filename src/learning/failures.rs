@@ -4,6 +4,7 @@ use serde::{Deserialize, Serialize};
 use std::sync::Arc;
 use uuid::Uuid;
 
+use super::embeddings::EmbeddingIndex;
 use super::{QueryContext, Scope};
 
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
@@ -15,7 +16,7 @@ pub enum Severity {
 }
 
 impl Severity {
-    fn to_str(&self) -> &'static str {
+    pub(crate) fn to_str(&self) -> &'static str {
         match self {
             Severity::Critical => "critical",
             Severity::Major => "major",
@@ -54,11 +55,18 @@ pub struct NewFailure {
 
 pub struct FailureStore {
     db: Arc<Connection>,
+    embeddings: Option<Arc<EmbeddingIndex>>,
 }
 
 impl FailureStore {
     pub fn new(db: Arc<Connection>) -> Self {
-        Self { db }
+        Self { db, embeddings: None }
+    }
+
+    /// Rank queried failures by semantic similarity as well as keyword overlap
+    pub fn with_embeddings(mut self, embeddings: Arc<EmbeddingIndex>) -> Self {
+        self.embeddings = Some(embeddings);
+        self
     }
 
     /// Create a new failure
@@ -195,9 +203,17 @@ impl FailureStore {
             }
         }
 
+        let similarities = match &self.embeddings {
+            Some(index) if !context.description.trim().is_empty() => {
+                let items: Vec<(String, String)> = failures.iter().map(|f| (f.id.clone(), failure_text(f))).collect();
+                index.similarities("failure", &context.description, &items).await
+            }
+            _ => Default::default(),
+        };
+
         // Rank by relevance to query (critical failures stay at top due to stable sort)
         let query_words = text_tokens(&context.description);
-        if !query_words.is_empty() {
+        if !query_words.is_empty() || !similarities.is_empty() {
             failures.sort_by(|a, b| {
                 // Critical failures always first
                 let sev_a = severity_rank(&a.severity);
@@ -205,9 +221,12 @@ impl FailureStore {
                 if sev_a != sev_b {
                     return sev_a.cmp(&sev_b);
                 }
-                // Within same severity, rank by keyword relevance
-                let score_a = failure_relevance(a, &query_words);
-                let score_b = failure_relevance(b, &query_words);
+                // Within same severity, rank by keyword or semantic relevance
+                let score = |f: &Failure| {
+                    failure_relevance(f, &query_words).max(similarities.get(&f.id).copied().unwrap_or(0.0))
+                };
+                let score_a = score(a);
+                let score_b = score(b);
                 score_b.partial_cmp(&score_a).unwrap_or(std::cmp::Ordering::Equal)
             });
         }
@@ -215,6 +234,23 @@ impl FailureStore {
         Ok(failures)
     }
 
+    /// Count failures created after the given Unix timestamp
+    pub async fn count_created_since(&self, since: i64) -> Result<usize> {
+        let count: i64 = self
+            .db
+            .query(
+                "SELECT COUNT(*) FROM failures WHERE created_at > ?1",
+                [since],
+            )
+            .await?
+            .next()
+            .await?
+            .map(|row| row.get::<i64>(0))
+            .transpose()?
+            .unwrap_or(0);
+        Ok(count as usize)
+    }
+
     /// List all failures
     pub async fn list_all(&self) -> Result<Vec<Failure>> {
         let mut rows = self
@@ -278,11 +314,8 @@ fn severity_rank(s: &Severity) -> u8 {
     }
 }
 
-/// Score a failure's relevance to query keywords
-fn failure_relevance(failure: &Failure, query_words: &[String]) -> f32 {
-    if query_words.is_empty() {
-        return 0.0;
-    }
+/// All searchable text of a failure
+fn failure_text(failure: &Failure) -> String {
     let mut text = failure.cause.clone();
     text.push(' ');
     text.push_str(&failure.avoidance_rule);
@@ -290,7 +323,15 @@ fn failure_relevance(failure: &Failure, query_words: &[String]) -> f32 {
         text.push(' ');
         text.push_str(tag);
     }
-    let tokens = text_tokens(&text);
+    text
+}
+
+/// Score a failure's relevance to query keywords
+fn failure_relevance(failure: &Failure, query_words: &[String]) -> f32 {
+    if query_words.is_empty() {
+        return 0.0;
+    }
+    let tokens = text_tokens(&failure_text(failure));
     if tokens.is_empty() {
         return 0.0;
     }
@@ -318,7 +359,7 @@ mod tests {
             codegraph_dir: temp_path.join(".codegraph"),
             store_db_path: temp_path.join("cache/store.db"),
             learning_db_path: temp_path.join(".codegraph/learning.db"),
-            settings: crate::config::ConfigFile::default(),
+            settings: crate::config::ConfigFile::default().into(),
         };
 
         let store = Store::open(&config).await.unwrap();
@@ -13,7 +13,7 @@ pub enum Outcome {
 }
 
 impl Outcome {
-    fn to_str(&self) -> &'static str {
+    pub(crate) fn to_str(&self) -> &'static str {
         match self {
             Outcome::Success => "success",
             Outcome::Failure => "failure",
@@ -286,7 +286,7 @@ mod tests {
             codegraph_dir: temp_path.join(".codegraph"),
             store_db_path: temp_path.join("cache/store.db"),
             learning_db_path: temp_path.join(".codegraph/learning.db"),
-            settings: crate::config::ConfigFile::default(),
+            settings: crate::config::ConfigFile::default().into(),
         };
 
         let store = Store::open(&config).await.unwrap();
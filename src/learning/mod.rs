@@ -1,5 +1,7 @@
+pub mod bundle;
 pub mod confidence;
 pub mod conflicts;
+pub mod embeddings;
 pub mod failures;
 pub mod lineage;
 pub mod niches;
@@ -10,7 +12,7 @@ pub mod sync;
 use serde::{Deserialize, Serialize};
 
 /// Scope defines where a pattern or failure applies
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct Scope {
     /// Include path patterns (glob)
     #[serde(default)]
@@ -1,11 +1,17 @@
 use anyhow::Result;
 use libsql::Connection;
 use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
 use std::sync::Arc;
 use uuid::Uuid;
 
+use super::conflicts::{detect_contradiction, scopes_overlap};
+use super::embeddings::EmbeddingIndex;
 use super::{QueryContext, Scope};
 
+/// Default similarity at which two patterns count as duplicates
+pub const DUPLICATE_THRESHOLD: f32 = 0.6;
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Pattern {
     pub id: String,
@@ -21,6 +27,26 @@ pub struct Pattern {
     pub updated_at: i64,
 }
 
+/// A group of near-duplicate patterns and the one they should be merged into
+#[derive(Debug, Clone)]
+pub struct DuplicateCluster {
+    pub keep: Pattern,
+    /// Duplicates with their similarity to `keep`
+    pub duplicates: Vec<(Pattern, f32)>,
+}
+
+/// A pattern that was folded into another by consolidation
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PatternMerge {
+    pub merged_id: String,
+    pub into_id: String,
+    pub intent: String,
+    pub usage_count: i64,
+    pub success_count: i64,
+    pub similarity: f32,
+    pub merged_at: i64,
+}
+
 #[derive(Debug, Clone)]
 pub struct NewPattern {
     pub intent: String,
@@ -32,11 +58,18 @@ pub struct NewPattern {
 
 pub struct PatternStore {
     db: Arc<Connection>,
+    embeddings: Option<Arc<EmbeddingIndex>>,
 }
 
 impl PatternStore {
     pub fn new(db: Arc<Connection>) -> Self {
-        Self { db }
+        Self { db, embeddings: None }
+    }
+
+    /// Rank queried patterns by semantic similarity as well as keyword overlap
+    pub fn with_embeddings(mut self, embeddings: Arc<EmbeddingIndex>) -> Self {
+        self.embeddings = Some(embeddings);
+        self
     }
 
     /// Create a new pattern
@@ -192,13 +225,18 @@ impl PatternStore {
             }
         }
 
-        // Rank by relevance: keyword overlap between query and intent+tags+mechanism
-        if !query_words.is_empty() {
-            patterns.sort_by(|a, b| {
-                let score_a = relevance_score(a, &query_words);
-                let score_b = relevance_score(b, &query_words);
-                score_b.partial_cmp(&score_a).unwrap_or(std::cmp::Ordering::Equal)
-            });
+        let similarities = match &self.embeddings {
+            Some(index) if !context.description.trim().is_empty() => {
+                let items: Vec<(String, String)> = patterns.iter().map(|p| (p.id.clone(), pattern_text(p))).collect();
+                index.similarities("pattern", &context.description, &items).await
+            }
+            _ => Default::default(),
+        };
+
+        // Rank by relevance: keyword overlap or semantic similarity between query and pattern text
+        if !query_words.is_empty() || !similarities.is_empty() {
+            let score = |p: &Pattern| relevance_score(p, &query_words, similarities.get(&p.id).copied().unwrap_or(0.0));
+            patterns.sort_by(|a, b| score(b).partial_cmp(&score(a)).unwrap_or(std::cmp::Ordering::Equal));
         }
 
         patterns.truncate(limit);
@@ -206,6 +244,23 @@ impl PatternStore {
         Ok(patterns)
     }
 
+    /// Count patterns created after the given Unix timestamp
+    pub async fn count_created_since(&self, since: i64) -> Result<usize> {
+        let count: i64 = self
+            .db
+            .query(
+                "SELECT COUNT(*) FROM patterns WHERE created_at > ?1",
+                [since],
+            )
+            .await?
+            .next()
+            .await?
+            .map(|row| row.get::<i64>(0))
+            .transpose()?
+            .unwrap_or(0);
+        Ok(count as usize)
+    }
+
     /// List all patterns
     pub async fn list_all(&self) -> Result<Vec<Pattern>> {
         let mut rows = self
@@ -256,6 +311,217 @@ impl PatternStore {
             .await?;
         Ok(())
     }
+
+    /// Fold a cluster's duplicates into its kept pattern: examples and scopes are unioned, usage
+    /// and success counts summed, and each duplicate is deleted with a row in `pattern_merges`.
+    /// Pins on a duplicate carry over to the kept pattern.
+    pub async fn merge(&self, cluster: &DuplicateCluster) -> Result<Pattern> {
+        let merged = merged_pattern(cluster);
+        let now = chrono::Utc::now().timestamp();
+
+        let tx = self.db.transaction().await?;
+        tx.execute(
+            "UPDATE patterns SET mechanism = ?1, examples = ?2, scope = ?3, confidence = ?4, usage_count = ?5,
+                 success_count = ?6, last_validated = ?7, updated_at = ?8
+             WHERE id = ?9",
+            libsql::params![
+                merged.mechanism.as_deref().unwrap_or(""),
+                serde_json::to_string(&merged.examples)?,
+                serde_json::to_string(&merged.scope)?,
+                merged.confidence as f64,
+                merged.usage_count,
+                merged.success_count,
+                merged.last_validated,
+                now,
+                merged.id.as_str()
+            ],
+        )
+        .await?;
+        for (duplicate, similarity) in &cluster.duplicates {
+            let id = duplicate.id.as_str();
+            tx.execute(
+                "INSERT OR REPLACE INTO pattern_merges
+                     (merged_id, into_id, intent, usage_count, success_count, similarity, merged_at)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+                libsql::params![
+                    id,
+                    merged.id.as_str(),
+                    duplicate.intent.as_str(),
+                    duplicate.usage_count,
+                    duplicate.success_count,
+                    *similarity as f64,
+                    now
+                ],
+            )
+            .await?;
+            tx.execute(
+                "UPDATE OR IGNORE instruction_priorities SET id = ?1 WHERE id = ?2",
+                [merged.id.as_str(), id],
+            )
+            .await?;
+            tx.execute("DELETE FROM instruction_priorities WHERE id = ?1", [id]).await?;
+            tx.execute("DELETE FROM patterns WHERE id = ?1", [id]).await?;
+        }
+        tx.commit().await?;
+
+        Ok(Pattern { updated_at: now, ..merged })
+    }
+
+    /// Patterns merged into this one, directly or through earlier merges, newest first
+    pub async fn merge_history(&self, id: &str) -> Result<Vec<PatternMerge>> {
+        let mut history = Vec::new();
+        let mut pending = vec![id.to_string()];
+        while let Some(into_id) = pending.pop() {
+            let mut rows = self
+                .db
+                .query(
+                    "SELECT merged_id, into_id, intent, usage_count, success_count, similarity, merged_at
+                     FROM pattern_merges WHERE into_id = ?1",
+                    [into_id.as_str()],
+                )
+                .await?;
+            while let Some(row) = rows.next().await? {
+                let merge = PatternMerge {
+                    merged_id: row.get(0)?,
+                    into_id: row.get(1)?,
+                    intent: row.get(2)?,
+                    usage_count: row.get(3)?,
+                    success_count: row.get(4)?,
+                    similarity: row.get::<f64>(5)? as f32,
+                    merged_at: row.get(6)?,
+                };
+                pending.push(merge.merged_id.clone());
+                history.push(merge);
+            }
+        }
+        history.sort_by_key(|m| std::cmp::Reverse(m.merged_at));
+        Ok(history)
+    }
+}
+
+/// Group near-duplicate patterns. The most trusted pattern (confidence, then successes, then
+/// age) of each group is kept; others join it when their intent and mechanism are at least
+/// `threshold` similar to it, their scopes overlap, and they don't give opposing advice.
+pub fn find_duplicates(patterns: &[Pattern], threshold: f32) -> Vec<DuplicateCluster> {
+    let mut ranked: Vec<&Pattern> = patterns.iter().collect();
+    ranked.sort_by(|a, b| {
+        b.confidence
+            .partial_cmp(&a.confidence)
+            .unwrap_or(std::cmp::Ordering::Equal)
+            .then(b.success_count.cmp(&a.success_count))
+            .then(a.created_at.cmp(&b.created_at))
+    });
+    let tokens: Vec<HashSet<String>> = ranked
+        .iter()
+        .map(|p| {
+            let text = format!("{} {}", p.intent, p.mechanism.as_deref().unwrap_or(""));
+            text_tokens(&text).into_iter().collect()
+        })
+        .collect();
+
+    let mut merged = vec![false; ranked.len()];
+    let mut clusters = Vec::new();
+    for i in 0..ranked.len() {
+        if merged[i] {
+            continue;
+        }
+        let keep = ranked[i];
+        let mut duplicates = Vec::new();
+        for j in (i + 1)..ranked.len() {
+            if merged[j] {
+                continue;
+            }
+            let similarity = token_similarity(&tokens[i], &tokens[j]);
+            if similarity >= threshold
+                && scopes_overlap(&keep.scope, &ranked[j].scope)
+                && detect_contradiction(keep, ranked[j]).is_none()
+            {
+                merged[j] = true;
+                duplicates.push((ranked[j].clone(), similarity));
+            }
+        }
+        if !duplicates.is_empty() {
+            clusters.push(DuplicateCluster {
+                keep: keep.clone(),
+                duplicates,
+            });
+        }
+    }
+    clusters
+}
+
+/// The pattern in `existing` that `pattern` would be merged into by `find_duplicates`, if any,
+/// with their similarity
+pub fn find_duplicate_of<'a>(pattern: &Pattern, existing: &'a [Pattern], threshold: f32) -> Option<(&'a Pattern, f32)> {
+    let tokens = |p: &Pattern| -> HashSet<String> {
+        text_tokens(&format!("{} {}", p.intent, p.mechanism.as_deref().unwrap_or(""))).into_iter().collect()
+    };
+    let wanted = tokens(pattern);
+    existing
+        .iter()
+        .map(|candidate| (candidate, token_similarity(&wanted, &tokens(candidate))))
+        .filter(|(candidate, similarity)| {
+            *similarity >= threshold
+                && scopes_overlap(&pattern.scope, &candidate.scope)
+                && detect_contradiction(pattern, candidate).is_none()
+        })
+        .max_by(|a, b| a.1.partial_cmp(&b.1).unwrap_or(std::cmp::Ordering::Equal))
+}
+
+/// Jaccard similarity where word variants sharing a 4+ letter stem (retry/retries) also match
+fn token_similarity(a: &HashSet<String>, b: &HashSet<String>) -> f32 {
+    if a.is_empty() || b.is_empty() {
+        return 0.0;
+    }
+    let matched = a
+        .iter()
+        .filter(|ta| {
+            b.iter().any(|tb| {
+                let shared = ta.chars().zip(tb.chars()).take_while(|(x, y)| x == y).count();
+                ta == &tb || shared >= 4
+            })
+        })
+        .count();
+    matched as f32 / (a.len() + b.len() - matched) as f32
+}
+
+/// The kept pattern with its duplicates folded in
+fn merged_pattern(cluster: &DuplicateCluster) -> Pattern {
+    let mut merged = cluster.keep.clone();
+    for (duplicate, _) in &cluster.duplicates {
+        for example in &duplicate.examples {
+            if !merged.examples.contains(example) {
+                merged.examples.push(example.clone());
+            }
+        }
+        if merged.mechanism.is_none() {
+            merged.mechanism = duplicate.mechanism.clone();
+        }
+        merged.scope = union_scope(&merged.scope, &duplicate.scope);
+        merged.confidence = merged.confidence.max(duplicate.confidence);
+        merged.usage_count += duplicate.usage_count;
+        merged.success_count += duplicate.success_count;
+        merged.last_validated = merged.last_validated.max(duplicate.last_validated);
+    }
+    merged
+}
+
+/// A scope matching wherever either does. An empty list means "anywhere", so it absorbs the other.
+fn union_scope(a: &Scope, b: &Scope) -> Scope {
+    let union = |x: &[String], y: &[String]| -> Vec<String> {
+        if x.is_empty() || y.is_empty() {
+            return vec![];
+        }
+        let mut all = x.to_vec();
+        all.extend(y.iter().filter(|v| !x.contains(v)).cloned());
+        all
+    };
+    Scope {
+        include_paths: union(&a.include_paths, &b.include_paths),
+        exclude_paths: a.exclude_paths.iter().filter(|p| b.exclude_paths.contains(p)).cloned().collect(),
+        symbols: union(&a.symbols, &b.symbols),
+        tags: union(&a.tags, &b.tags),
+    }
 }
 
 /// Extract lowercase keywords from text (3+ chars, no stop words)
@@ -271,14 +537,8 @@ fn text_tokens(text: &str) -> Vec<String> {
         .collect()
 }
 
-/// Score a pattern's relevance to query keywords
-/// Combines keyword overlap (0.0-1.0) with confidence (0.0-1.0)
-fn relevance_score(pattern: &Pattern, query_words: &[String]) -> f32 {
-    if query_words.is_empty() {
-        return pattern.confidence;
-    }
-
-    // Collect all searchable text from the pattern
+/// All searchable text of a pattern
+fn pattern_text(pattern: &Pattern) -> String {
     let mut pattern_text = pattern.intent.clone();
     if let Some(ref mech) = pattern.mechanism {
         pattern_text.push(' ');
@@ -292,10 +552,18 @@ fn relevance_score(pattern: &Pattern, query_words: &[String]) -> f32 {
         pattern_text.push(' ');
         pattern_text.push_str(ex);
     }
+    pattern_text
+}
 
-    let pattern_tokens = text_tokens(&pattern_text);
-    if pattern_tokens.is_empty() {
-        return pattern.confidence * 0.1;
+/// Score a pattern's relevance to query keywords
+/// Combines keyword overlap or `semantic` similarity (0.0-1.0, 0.0 when unavailable) with confidence (0.0-1.0)
+fn relevance_score(pattern: &Pattern, query_words: &[String], semantic: f32) -> f32 {
+    let pattern_tokens = text_tokens(&pattern_text(pattern));
+    if query_words.is_empty() || pattern_tokens.is_empty() {
+        if semantic > 0.0 {
+            return semantic * 0.6 + pattern.confidence * 0.4;
+        }
+        return if query_words.is_empty() { pattern.confidence } else { pattern.confidence * 0.1 };
     }
 
     // Count how many query words appear in the pattern
@@ -306,8 +574,8 @@ fn relevance_score(pattern: &Pattern, query_words: &[String]) -> f32 {
 
     let keyword_score = matches as f32 / query_words.len() as f32;
 
-    // Weighted: 60% keyword relevance, 40% confidence
-    keyword_score * 0.6 + pattern.confidence * 0.4
+    // Weighted: 60% relevance (the stronger of keywords and meaning), 40% confidence
+    keyword_score.max(semantic) * 0.6 + pattern.confidence * 0.4
 }
 
 #[cfg(test)]
@@ -327,7 +595,7 @@ mod tests {
             codegraph_dir: temp_path.join(".codegraph"),
             store_db_path: temp_path.join("cache/store.db"),
             learning_db_path: temp_path.join(".codegraph/learning.db"),
-            settings: crate::config::ConfigFile::default(),
+            settings: crate::config::ConfigFile::default().into(),
         };
 
         let store = Store::open(&config).await.unwrap();
@@ -421,4 +689,92 @@ mod tests {
         let results2 = pattern_store.query(&context2, 10).await.unwrap();
         assert_eq!(results2.len(), 0);
     }
+
+    #[tokio::test]
+    async fn test_pattern_query_semantic() {
+        let (store, _temp) = setup_test_store().await;
+        let db = Arc::new(store.learning_db);
+        let embeddings = EmbeddingIndex::with_embedder(db.clone(), Arc::new(super::super::embeddings::HashEmbedder));
+        let keyword_store = PatternStore::new(db.clone());
+        let semantic_store = PatternStore::new(db).with_embeddings(Arc::new(embeddings));
+
+        for (intent, confidence) in [
+            ("Retry flaky HTTP requests with exponential backoff", 0.6),
+            ("Use builder pattern for config structs", 0.7),
+        ] {
+            let pattern = NewPattern {
+                intent: intent.to_string(),
+                mechanism: None,
+                examples: vec![],
+                scope: Scope::default(),
+                confidence,
+            };
+            keyword_store.create(&pattern).await.unwrap();
+        }
+
+        // No shared keyword, so only the embedding connects "retries" to "Retry"
+        let context = QueryContext {
+            description: "add retries to fetching".to_string(),
+            current_file: None,
+            relevant_symbols: vec![],
+            tags: vec![],
+        };
+        let keyword = keyword_store.query(&context, 10).await.unwrap();
+        assert_eq!(keyword[0].intent, "Use builder pattern for config structs");
+        let semantic = semantic_store.query(&context, 10).await.unwrap();
+        assert_eq!(semantic[0].intent, "Retry flaky HTTP requests with exponential backoff");
+    }
+
+    #[tokio::test]
+    async fn test_consolidate_duplicates() {
+        let (store, _temp) = setup_test_store().await;
+        let pattern_store = PatternStore::new(Arc::new(store.learning_db));
+
+        let pattern = |intent: &str, example: &str, tags: &[&str], confidence: f32| NewPattern {
+            intent: intent.to_string(),
+            mechanism: None,
+            examples: vec![example.to_string()],
+            scope: Scope {
+                tags: tags.iter().map(|t| t.to_string()).collect(),
+                ..Scope::default()
+            },
+            confidence,
+        };
+        let keep = pattern_store
+            .create(&pattern("Always retry flaky network requests with backoff", "backoff(fetch)", &["http"], 0.9))
+            .await
+            .unwrap();
+        let duplicate = pattern_store
+            .create(&pattern("Retry flaky network request using backoff", "retry(get)", &["network"], 0.6))
+            .await
+            .unwrap();
+        pattern_store.update_usage(&duplicate.id, true).await.unwrap();
+        // Same words, opposite advice
+        pattern_store
+            .create(&pattern("Never retry flaky network requests with backoff", "-", &["http"], 0.5))
+            .await
+            .unwrap();
+        pattern_store
+            .create(&pattern("Use builder pattern for config structs", "-", &[], 0.5))
+            .await
+            .unwrap();
+
+        let clusters = find_duplicates(&pattern_store.list_all().await.unwrap(), DUPLICATE_THRESHOLD);
+        assert_eq!(clusters.len(), 1);
+        assert_eq!(clusters[0].keep.id, keep.id);
+        assert_eq!(clusters[0].duplicates.len(), 1);
+        assert_eq!(clusters[0].duplicates[0].0.id, duplicate.id);
+
+        let merged = pattern_store.merge(&clusters[0]).await.unwrap();
+        assert_eq!(merged.examples, vec!["backoff(fetch)", "retry(get)"]);
+        assert_eq!(merged.scope.tags, vec!["http", "network"]);
+        assert_eq!((merged.usage_count, merged.success_count), (1, 1));
+        assert_eq!(pattern_store.get(&keep.id).await.unwrap().unwrap().examples, merged.examples);
+        assert!(pattern_store.get(&duplicate.id).await.unwrap().is_none());
+
+        let history = pattern_store.merge_history(&keep.id).await.unwrap();
+        assert_eq!(history.len(), 1);
+        assert_eq!(history[0].merged_id, duplicate.id);
+        assert_eq!(history[0].intent, "Retry flaky network request using backoff");
+    }
 }
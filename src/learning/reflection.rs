@@ -1,5 +1,6 @@
 use anyhow::{Context as AnyhowContext, Result};
 
+use crate::config::ReflectionValidation;
 use super::failures::{FailureStore, NewFailure, Severity};
 use super::lineage::{LineageStore, Outcome};
 use super::patterns::{NewPattern, Pattern, PatternStore};
@@ -22,13 +23,14 @@ pub enum ReflectionResult {
     Both { pattern: Pattern, failure: Failure },
 }
 
-/// Reflect on a solution and create pattern or failure
+/// Reflect on a solution and create pattern or failure.
+///
+/// Input is stored as given; check it first with `validate_reflection`.
 pub async fn reflect(
     input: &ReflectionInput,
     lineage: &LineageStore,
     patterns: &PatternStore,
     failures: &FailureStore,
-    validate: bool,
 ) -> Result<ReflectionResult> {
     // Get the solution
     let solution = lineage
@@ -36,11 +38,6 @@ pub async fn reflect(
         .await?
         .context("Solution not found")?;
 
-    // Validate reflection if enabled
-    if validate {
-        validate_reflection(input)?;
-    }
-
     // Build scope from input
     let scope = Scope {
         include_paths: input.scope_paths.clone(),
@@ -100,8 +97,24 @@ pub async fn reflect(
     }
 }
 
-/// Validate reflection input
-fn validate_reflection(input: &ReflectionInput) -> Result<()> {
+/// Check reflection input at the given strictness.
+///
+/// Returns warnings for problems that don't reject the input. `Standard` rejects
+/// generic root causes and out-of-range confidence; `Strict` also rejects lessons
+/// not in "When X, do Y because Z" form; `Lenient` only warns.
+pub fn validate_reflection(input: &ReflectionInput, validation: ReflectionValidation) -> Result<Vec<String>> {
+    if validation == ReflectionValidation::Off {
+        return Ok(vec![]);
+    }
+    let mut warnings = Vec::new();
+    let mut check = |problem: &str, rejected_from: ReflectionValidation| -> Result<()> {
+        if validation >= rejected_from {
+            return Err(anyhow::anyhow!("{}", problem));
+        }
+        warnings.push(problem.to_string());
+        Ok(())
+    };
+
     // Check that root_cause is not generic
     let generic_causes = [
         "it failed",
@@ -114,9 +127,11 @@ fn validate_reflection(input: &ReflectionInput) -> Result<()> {
     let root_cause_lower = input.root_cause.to_lowercase();
     for generic in &generic_causes {
         if root_cause_lower.contains(generic) && root_cause_lower.len() < 30 {
-            return Err(anyhow::anyhow!(
-                "Root cause is too generic. Please provide specific details about what went wrong."
-            ));
+            check(
+                "Root cause is too generic. Please provide specific details about what went wrong.",
+                ReflectionValidation::Standard,
+            )?;
+            break;
         }
     }
 
@@ -132,19 +147,20 @@ fn validate_reflection(input: &ReflectionInput) -> Result<()> {
         lesson_lower.contains("because") || lesson_lower.contains("since") || lesson_lower.contains("to");
 
     if !has_when || !has_action {
-        eprintln!(
-            "Warning: Lesson should follow 'When X, do Y because Z' format for better reusability"
-        );
+        check(
+            "Lesson should follow 'When X, do Y because Z' format for better reusability",
+            ReflectionValidation::Strict,
+        )?;
     }
 
     // Check confidence is reasonable
     if let Some(conf) = input.confidence {
         if conf < 0.0 || conf > 1.0 {
-            return Err(anyhow::anyhow!("Confidence must be between 0.0 and 1.0"));
+            check("Confidence must be between 0.0 and 1.0", ReflectionValidation::Standard)?;
         }
     }
 
-    Ok(())
+    Ok(warnings)
 }
 
 /// Infer severity from root cause description
@@ -192,7 +208,7 @@ mod tests {
             codegraph_dir: temp_path.join(".codegraph"),
             store_db_path: temp_path.join("cache/store.db"),
             learning_db_path: temp_path.join(".codegraph/learning.db"),
-            settings: crate::config::ConfigFile::default(),
+            settings: crate::config::ConfigFile::default().into(),
         };
 
         let store = Store::open(&config).await.unwrap();
@@ -230,7 +246,7 @@ mod tests {
             scope_tags: vec!["performance".to_string()],
         };
 
-        let result = reflect(&input, &lineage, &patterns, &failures, false)
+        let result = reflect(&input, &lineage, &patterns, &failures)
             .await
             .unwrap();
 
@@ -274,7 +290,7 @@ mod tests {
             scope_tags: vec!["concurrency".to_string()],
         };
 
-        let result = reflect(&input, &lineage, &patterns, &failures, false)
+        let result = reflect(&input, &lineage, &patterns, &failures)
             .await
             .unwrap();
 
@@ -310,7 +326,8 @@ mod tests {
             scope_tags: vec![],
         };
 
-        assert!(validate_reflection(&good_input).is_ok());
+        assert!(validate_reflection(&good_input, ReflectionValidation::Standard).is_ok());
+        assert!(validate_reflection(&good_input, ReflectionValidation::Strict).unwrap().is_empty());
 
         let bad_input = ReflectionInput {
             attempt_id: "test".to_string(),
@@ -323,6 +340,17 @@ mod tests {
             scope_tags: vec![],
         };
 
-        assert!(validate_reflection(&bad_input).is_err());
+        assert!(validate_reflection(&bad_input, ReflectionValidation::Standard).is_err());
+        assert_eq!(validate_reflection(&bad_input, ReflectionValidation::Lenient).unwrap().len(), 3);
+        assert!(validate_reflection(&bad_input, ReflectionValidation::Off).unwrap().is_empty());
+
+        // A well-formed cause with a loosely worded lesson only fails strict validation
+        let loose_lesson = ReflectionInput {
+            lesson: "Check for null pointers first".to_string(),
+            ..good_input
+        };
+        let warnings = validate_reflection(&loose_lesson, ReflectionValidation::Standard).unwrap();
+        assert_eq!(warnings.len(), 1);
+        assert!(validate_reflection(&loose_lesson, ReflectionValidation::Strict).is_err());
     }
 }
@@ -1,4 +1,5 @@
 use anyhow::Result;
+use libsql::Connection;
 use serde::{Deserialize, Serialize};
 use std::path::Path;
 
@@ -171,6 +172,413 @@ fn distill_failure(failure: &Failure) -> Result<DistilledFailure> {
     })
 }
 
+/// What `merge_learnings` did with the source database's rows
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct MergeStats {
+    /// Rows the target didn't have
+    pub inserted: usize,
+    /// Rows both had under one ID, where the source's counters were higher or its text newer
+    pub updated: usize,
+    /// Patterns and failures recorded separately with the same intent (or cause and rule)
+    /// as one the target has, added to that one's counts
+    pub folded: usize,
+    /// Rows the target already had as they are, or patterns it has since merged away
+    pub unchanged: usize,
+}
+
+/// Pattern columns, in the order `PatternRow::read` expects
+const PATTERN_COLUMNS: &str = "id, intent, mechanism, examples, scope, confidence, usage_count, success_count, \
+                               last_validated, created_at, updated_at";
+
+/// Failure columns, in the order `FailureRow::read` expects
+const FAILURE_COLUMNS: &str = "id, cause, avoidance_rule, severity, scope, times_prevented, created_at, updated_at";
+
+#[derive(Debug, Clone, PartialEq)]
+struct PatternRow {
+    id: String,
+    intent: String,
+    mechanism: Option<String>,
+    examples: String,
+    scope: String,
+    confidence: f64,
+    usage_count: i64,
+    success_count: i64,
+    last_validated: Option<i64>,
+    created_at: i64,
+    updated_at: i64,
+}
+
+impl PatternRow {
+    fn read(row: &libsql::Row) -> Result<Self> {
+        Ok(Self {
+            id: row.get(0)?,
+            intent: row.get(1)?,
+            mechanism: row.get(2)?,
+            examples: row.get(3)?,
+            scope: row.get(4)?,
+            confidence: row.get(5)?,
+            usage_count: row.get(6)?,
+            success_count: row.get(7)?,
+            last_validated: row.get(8)?,
+            created_at: row.get(9)?,
+            updated_at: row.get(10)?,
+        })
+    }
+
+    /// Both copies of one pattern combined: the newer edit's text, and the larger counters,
+    /// since each copy counts the same uses up to the point they diverged
+    fn combine(&self, other: &Self) -> Self {
+        let newer = if other.updated_at > self.updated_at { other } else { self };
+        Self {
+            usage_count: self.usage_count.max(other.usage_count),
+            success_count: self.success_count.max(other.success_count),
+            last_validated: self.last_validated.max(other.last_validated),
+            created_at: self.created_at.min(other.created_at),
+            updated_at: self.updated_at.max(other.updated_at),
+            ..newer.clone()
+        }
+    }
+
+    async fn find(conn: &Connection, sql: &str, params: impl libsql::params::IntoParams) -> Result<Option<Self>> {
+        let mut rows = conn.query(sql, params).await?;
+        rows.next().await?.map(|row| Self::read(&row)).transpose()
+    }
+
+    /// Insert the row, or overwrite the one with its ID
+    async fn write(&self, conn: &Connection) -> Result<()> {
+        conn.execute(
+            &format!(
+                "INSERT INTO patterns ({}) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11)
+                 ON CONFLICT(id) DO UPDATE SET intent = excluded.intent, mechanism = excluded.mechanism,
+                     examples = excluded.examples, scope = excluded.scope, confidence = excluded.confidence,
+                     usage_count = excluded.usage_count, success_count = excluded.success_count,
+                     last_validated = excluded.last_validated, created_at = excluded.created_at,
+                     updated_at = excluded.updated_at",
+                PATTERN_COLUMNS
+            ),
+            libsql::params![
+                self.id.as_str(),
+                self.intent.as_str(),
+                self.mechanism.clone(),
+                self.examples.as_str(),
+                self.scope.as_str(),
+                self.confidence,
+                self.usage_count,
+                self.success_count,
+                self.last_validated,
+                self.created_at,
+                self.updated_at
+            ],
+        )
+        .await?;
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct FailureRow {
+    id: String,
+    cause: String,
+    avoidance_rule: String,
+    severity: String,
+    scope: String,
+    times_prevented: i64,
+    created_at: i64,
+    updated_at: i64,
+}
+
+impl FailureRow {
+    fn read(row: &libsql::Row) -> Result<Self> {
+        Ok(Self {
+            id: row.get(0)?,
+            cause: row.get(1)?,
+            avoidance_rule: row.get(2)?,
+            severity: row.get(3)?,
+            scope: row.get(4)?,
+            times_prevented: row.get(5)?,
+            created_at: row.get(6)?,
+            updated_at: row.get(7)?,
+        })
+    }
+
+    /// Both copies of one failure combined, like `PatternRow::combine`
+    fn combine(&self, other: &Self) -> Self {
+        let newer = if other.updated_at > self.updated_at { other } else { self };
+        Self {
+            times_prevented: self.times_prevented.max(other.times_prevented),
+            created_at: self.created_at.min(other.created_at),
+            updated_at: self.updated_at.max(other.updated_at),
+            ..newer.clone()
+        }
+    }
+
+    async fn find(conn: &Connection, sql: &str, params: impl libsql::params::IntoParams) -> Result<Option<Self>> {
+        let mut rows = conn.query(sql, params).await?;
+        rows.next().await?.map(|row| Self::read(&row)).transpose()
+    }
+
+    /// Insert the row, or overwrite the one with its ID
+    async fn write(&self, conn: &Connection) -> Result<()> {
+        conn.execute(
+            &format!(
+                "INSERT INTO failures ({}) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)
+                 ON CONFLICT(id) DO UPDATE SET cause = excluded.cause, avoidance_rule = excluded.avoidance_rule,
+                     severity = excluded.severity, scope = excluded.scope,
+                     times_prevented = excluded.times_prevented, created_at = excluded.created_at,
+                     updated_at = excluded.updated_at",
+                FAILURE_COLUMNS
+            ),
+            libsql::params![
+                self.id.as_str(),
+                self.cause.as_str(),
+                self.avoidance_rule.as_str(),
+                self.severity.as_str(),
+                self.scope.as_str(),
+                self.times_prevented,
+                self.created_at,
+                self.updated_at
+            ],
+        )
+        .await?;
+        Ok(())
+    }
+}
+
+/// Merge the learnings in `from` into `into`, e.g. a machine's learning.db into the team's
+/// shared database, in one transaction.
+///
+/// Conflicts are resolved per row:
+/// - a pattern or failure both have keeps the newer edit's text and the larger counters
+/// - one recorded separately with the same intent (or cause and avoidance rule) is folded
+///   into the target's copy, adding its counters; folds are logged in pattern_merges and
+///   failure_merges, so merging the same source again skips folded patterns and adds only
+///   the prevention count a folded failure gained since
+/// - solutions and instructions the target has are kept as they are; for priorities and
+///   connection reviews, the more recent decision wins
+///
+/// Embeddings and inferred cross-language connections are caches and aren't copied.
+pub async fn merge_learnings(from: &Connection, into: &Connection) -> Result<MergeStats> {
+    let mut stats = MergeStats::default();
+    let now = chrono::Utc::now().timestamp();
+
+    let tx = into.transaction().await?;
+    merge_patterns(from, &tx, now, &mut stats).await?;
+    merge_failures(from, &tx, now, &mut stats).await?;
+    merge_table(from, &tx, "solutions", &["id"], None, &mut stats).await?;
+    merge_table(from, &tx, "instructions", &["id"], None, &mut stats).await?;
+    merge_table(from, &tx, "instruction_priorities", &["id"], Some("updated_at"), &mut stats).await?;
+    let review_key = ["client_file", "server_file", "api_path"];
+    merge_table(from, &tx, "cross_language_reviews", &review_key, Some("reviewed_at"), &mut stats).await?;
+    tx.commit().await?;
+
+    Ok(stats)
+}
+
+async fn merge_patterns(from: &Connection, into: &Connection, now: i64, stats: &mut MergeStats) -> Result<()> {
+    let mut rows = from
+        .query(&format!("SELECT {} FROM patterns ORDER BY created_at, id", PATTERN_COLUMNS), ())
+        .await?;
+    let mut incoming = Vec::new();
+    while let Some(row) = rows.next().await? {
+        incoming.push(PatternRow::read(&row)?);
+    }
+
+    let by_id = format!("SELECT {} FROM patterns WHERE id = ?1", PATTERN_COLUMNS);
+    let by_intent = format!(
+        "SELECT {} FROM patterns WHERE lower(trim(intent)) = lower(trim(?1)) ORDER BY created_at, id LIMIT 1",
+        PATTERN_COLUMNS
+    );
+    // Copies of the same pattern first, so folding a duplicate into one can't be undone by
+    // taking the larger of its counters afterwards
+    let mut new = Vec::new();
+    for pattern in incoming {
+        let merged_away = "SELECT 1 FROM pattern_merges WHERE merged_id = ?1";
+        if into.query(merged_away, [pattern.id.as_str()]).await?.next().await?.is_some() {
+            stats.unchanged += 1;
+        } else if let Some(existing) = PatternRow::find(into, &by_id, [pattern.id.as_str()]).await? {
+            let combined = existing.combine(&pattern);
+            if combined == existing {
+                stats.unchanged += 1;
+            } else {
+                combined.write(into).await?;
+                stats.updated += 1;
+            }
+        } else {
+            new.push(pattern);
+        }
+    }
+
+    for pattern in new {
+        if let Some(existing) = PatternRow::find(into, &by_intent, [pattern.intent.as_str()]).await? {
+            let folded = PatternRow {
+                confidence: existing.confidence.max(pattern.confidence),
+                usage_count: existing.usage_count + pattern.usage_count,
+                success_count: existing.success_count + pattern.success_count,
+                last_validated: existing.last_validated.max(pattern.last_validated),
+                updated_at: now,
+                ..existing.clone()
+            };
+            folded.write(into).await?;
+            into.execute(
+                "INSERT OR REPLACE INTO pattern_merges
+                     (merged_id, into_id, intent, usage_count, success_count, similarity, merged_at)
+                 VALUES (?1, ?2, ?3, ?4, ?5, 1.0, ?6)",
+                libsql::params![
+                    pattern.id.as_str(),
+                    existing.id.as_str(),
+                    pattern.intent.as_str(),
+                    pattern.usage_count,
+                    pattern.success_count,
+                    now
+                ],
+            )
+            .await?;
+            stats.folded += 1;
+        } else {
+            pattern.write(into).await?;
+            stats.inserted += 1;
+        }
+    }
+    Ok(())
+}
+
+async fn merge_failures(from: &Connection, into: &Connection, now: i64, stats: &mut MergeStats) -> Result<()> {
+    let mut rows = from
+        .query(&format!("SELECT {} FROM failures ORDER BY created_at, id", FAILURE_COLUMNS), ())
+        .await?;
+    let mut incoming = Vec::new();
+    while let Some(row) = rows.next().await? {
+        incoming.push(FailureRow::read(&row)?);
+    }
+
+    let by_id = format!("SELECT {} FROM failures WHERE id = ?1", FAILURE_COLUMNS);
+    let by_text = format!(
+        "SELECT {} FROM failures
+         WHERE lower(trim(cause)) = lower(trim(?1)) AND lower(trim(avoidance_rule)) = lower(trim(?2))
+         ORDER BY created_at, id LIMIT 1",
+        FAILURE_COLUMNS
+    );
+    let record_fold = "INSERT OR REPLACE INTO failure_merges (merged_id, into_id, times_prevented, merged_at)
+                       VALUES (?1, ?2, ?3, ?4)";
+    let mut new = Vec::new();
+    for failure in incoming {
+        let folded_before = "SELECT into_id, times_prevented FROM failure_merges WHERE merged_id = ?1";
+        let mut rows = into.query(folded_before, [failure.id.as_str()]).await?;
+        if let Some(row) = rows.next().await? {
+            let (into_id, carried): (String, i64) = (row.get(0)?, row.get(1)?);
+            let gained = failure.times_prevented - carried;
+            let added = if gained > 0 {
+                into.execute(
+                    "UPDATE failures SET times_prevented = times_prevented + ?1, updated_at = max(updated_at, ?2)
+                     WHERE id = ?3",
+                    libsql::params![gained, failure.updated_at, into_id.as_str()],
+                )
+                .await?
+            } else {
+                0
+            };
+            if added > 0 {
+                let params = libsql::params![failure.id.as_str(), into_id.as_str(), failure.times_prevented, now];
+                into.execute(record_fold, params).await?;
+                stats.updated += 1;
+            } else {
+                stats.unchanged += 1;
+            }
+        } else if let Some(existing) = FailureRow::find(into, &by_id, [failure.id.as_str()]).await? {
+            let combined = existing.combine(&failure);
+            if combined == existing {
+                stats.unchanged += 1;
+            } else {
+                combined.write(into).await?;
+                stats.updated += 1;
+            }
+        } else {
+            new.push(failure);
+        }
+    }
+
+    for failure in new {
+        if let Some(existing) = FailureRow::find(
+            into,
+            &by_text,
+            [failure.cause.as_str(), failure.avoidance_rule.as_str()],
+        )
+        .await?
+        {
+            let folded = FailureRow {
+                times_prevented: existing.times_prevented + failure.times_prevented,
+                updated_at: existing.updated_at.max(failure.updated_at),
+                ..existing.clone()
+            };
+            folded.write(into).await?;
+            into.execute(
+                record_fold,
+                libsql::params![failure.id.as_str(), existing.id.as_str(), failure.times_prevented, now],
+            )
+            .await?;
+            stats.folded += 1;
+        } else {
+            failure.write(into).await?;
+            stats.inserted += 1;
+        }
+    }
+    Ok(())
+}
+
+/// Copy the rows of `table` that `into` lacks, matching rows on the `key` columns. For rows
+/// both have, `from`'s replaces `into`'s only if its `newest` column is greater.
+async fn merge_table(
+    from: &Connection,
+    into: &Connection,
+    table: &str,
+    key: &[&str],
+    newest: Option<&str>,
+    stats: &mut MergeStats,
+) -> Result<()> {
+    let mut rows = from.query(&format!("SELECT * FROM {} ORDER BY rowid", table), ()).await?;
+    let columns: Vec<String> = (0..rows.column_count())
+        .filter_map(|i| rows.column_name(i).map(String::from))
+        .collect();
+    let mut incoming = Vec::new();
+    while let Some(row) = rows.next().await? {
+        let values = (0..columns.len() as i32).map(|i| row.get_value(i)).collect::<Result<Vec<_>, _>>()?;
+        incoming.push(values);
+    }
+
+    let placeholders: Vec<String> = (1..=columns.len()).map(|i| format!("?{}", i)).collect();
+    let on_conflict = match newest {
+        Some(newest) => format!(
+            "DO UPDATE SET {} WHERE excluded.{newest} > {table}.{newest}",
+            columns.iter().map(|c| format!("{c} = excluded.{c}")).collect::<Vec<_>>().join(", "),
+        ),
+        None => "DO NOTHING".to_string(),
+    };
+    let upsert = format!(
+        "INSERT INTO {} ({}) VALUES ({}) ON CONFLICT({}) {}",
+        table,
+        columns.join(", "),
+        placeholders.join(", "),
+        key.join(", "),
+        on_conflict
+    );
+    let key_filter: Vec<String> = key.iter().enumerate().map(|(i, k)| format!("{} = ?{}", k, i + 1)).collect();
+    let exists = format!("SELECT 1 FROM {} WHERE {}", table, key_filter.join(" AND "));
+    let key_positions: Vec<usize> =
+        key.iter().filter_map(|k| columns.iter().position(|c| c == k)).collect();
+
+    for values in incoming {
+        let key_values = key_positions.iter().map(|&i| values[i].clone());
+        let existed = into.query(&exists, libsql::params_from_iter(key_values)).await?.next().await?.is_some();
+        let changed = into.execute(&upsert, libsql::params_from_iter(values)).await? > 0;
+        match (existed, changed) {
+            (false, _) => stats.inserted += 1,
+            (true, true) => stats.updated += 1,
+            (true, false) => stats.unchanged += 1,
+        }
+    }
+    Ok(())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -192,7 +600,7 @@ mod tests {
             codegraph_dir: temp_path.join(".codegraph"),
             store_db_path: temp_path.join("cache/store.db"),
             learning_db_path: temp_path.join(".codegraph/learning.db"),
-            settings: crate::config::ConfigFile::default(),
+            settings: crate::config::ConfigFile::default().into(),
         };
 
         config.ensure_dirs().unwrap();
@@ -264,4 +672,121 @@ mod tests {
         let failures_content = std::fs::read_to_string(config.codegraph_dir.join("failures.json")).unwrap();
         assert!(failures_content.contains("SQL injection"));
     }
+
+    #[tokio::test]
+    async fn test_merge_learnings() {
+        let temp = TempDir::new().unwrap();
+        let open = |name: &str| {
+            let path = temp.path().join(name);
+            async move {
+                let conn = Store::open_database(&path).await.unwrap();
+                crate::store::migrations::apply_learning_migrations(&conn).await.unwrap();
+                conn
+            }
+        };
+        let shared = open("shared.db").await;
+        let local = open("local.db").await;
+        let scope = Scope {
+            include_paths: vec![],
+            exclude_paths: vec![],
+            symbols: vec![],
+            tags: vec![],
+        };
+        let new_pattern = |intent: &str| NewPattern {
+            intent: intent.to_string(),
+            mechanism: None,
+            examples: vec![],
+            scope: scope.clone(),
+            confidence: 0.8,
+        };
+        let new_failure = || NewFailure {
+            cause: "SQL injection risk".to_string(),
+            avoidance_rule: "Always use parameterized queries".to_string(),
+            severity: Severity::Critical,
+            scope: scope.clone(),
+        };
+
+        // Both machines start from the shared learnings
+        let shared_patterns = PatternStore::new(Arc::new(shared.clone()));
+        let shared_failures = FailureStore::new(Arc::new(shared.clone()));
+        let known = shared_patterns.create(&new_pattern("Use async for DB calls")).await.unwrap();
+        shared_failures.create(&new_failure()).await.unwrap();
+        let stats = merge_learnings(&shared, &local).await.unwrap();
+        assert_eq!(stats, MergeStats { inserted: 2, ..Default::default() });
+
+        // This machine uses the shared pattern, and records a duplicate and a new one
+        let local_patterns = PatternStore::new(Arc::new(local.clone()));
+        let local_failures = FailureStore::new(Arc::new(local.clone()));
+        local_patterns.update_usage(&known.id, true).await.unwrap();
+        local_patterns.update_usage(&known.id, false).await.unwrap();
+        let duplicate = local_patterns.create(&new_pattern("use async for DB calls ")).await.unwrap();
+        local_patterns.update_usage(&duplicate.id, true).await.unwrap();
+        local_patterns.create(&new_pattern("Batch inserts in a transaction")).await.unwrap();
+        let failure = local_failures.create(&new_failure()).await.unwrap();
+        local_failures.increment_prevented(&failure.id).await.unwrap();
+
+        let stats = merge_learnings(&local, &shared).await.unwrap();
+        assert_eq!(stats, MergeStats { inserted: 1, updated: 1, folded: 2, unchanged: 1 });
+        let merged = shared_patterns.get(&known.id).await.unwrap().unwrap();
+        assert_eq!((merged.usage_count, merged.success_count), (3, 2));
+        assert!(shared_patterns.get(&duplicate.id).await.unwrap().is_none());
+        assert_eq!(shared_patterns.merge_history(&known.id).await.unwrap().len(), 1);
+        assert_eq!(shared_patterns.list_all().await.unwrap().len(), 2);
+        let failures = shared_failures.list_all().await.unwrap();
+        assert_eq!(failures.len(), 1);
+        assert_eq!(failures[0].times_prevented, 1);
+
+        // Patterns already merged are left alone the second time
+        let stats = merge_learnings(&local, &shared).await.unwrap();
+        assert_eq!((stats.inserted, stats.updated), (0, 0));
+        let merged = shared_patterns.get(&known.id).await.unwrap().unwrap();
+        assert_eq!(merged.usage_count, 3);
+    }
+
+    #[tokio::test]
+    async fn test_merge_same_source_twice() {
+        let temp = TempDir::new().unwrap();
+        let open = |name: &str| {
+            let path = temp.path().join(name);
+            async move {
+                let conn = Store::open_database(&path).await.unwrap();
+                crate::store::migrations::apply_learning_migrations(&conn).await.unwrap();
+                conn
+            }
+        };
+        let shared = open("shared.db").await;
+        let local = open("local.db").await;
+        let new_failure = || NewFailure {
+            cause: "Stale cache after deploy".to_string(),
+            avoidance_rule: "Bust the cache key on deploy".to_string(),
+            severity: Severity::Major,
+            scope: Scope::default(),
+        };
+
+        // Recorded separately on both, so the local copy is folded into the shared one
+        let shared_failures = FailureStore::new(Arc::new(shared.clone()));
+        let known = shared_failures.create(&new_failure()).await.unwrap();
+        shared_failures.increment_prevented(&known.id).await.unwrap();
+        let local_failures = FailureStore::new(Arc::new(local.clone()));
+        let copy = local_failures.create(&new_failure()).await.unwrap();
+        local_failures.increment_prevented(&copy.id).await.unwrap();
+        local_failures.increment_prevented(&copy.id).await.unwrap();
+
+        let stats = merge_learnings(&local, &shared).await.unwrap();
+        assert_eq!(stats.folded, 1);
+        let prevented = || async { shared_failures.get(&known.id).await.unwrap().unwrap().times_prevented };
+        assert_eq!(prevented().await, 3);
+
+        // As if moving learning.db aside failed and the merge ran again on the next start
+        let stats = merge_learnings(&local, &shared).await.unwrap();
+        assert_eq!(stats, MergeStats { unchanged: 1, ..Default::default() });
+        assert_eq!(prevented().await, 3);
+
+        // Only what the local copy prevented since is added
+        local_failures.increment_prevented(&copy.id).await.unwrap();
+        let stats = merge_learnings(&local, &shared).await.unwrap();
+        assert_eq!(stats, MergeStats { updated: 1, ..Default::default() });
+        assert_eq!(prevented().await, 4);
+        assert_eq!(shared_failures.list_all().await.unwrap().len(), 1);
+    }
 }
@@ -1,6 +1,7 @@
 pub mod code;
 pub mod compress;
 pub mod config;
+pub mod doctor;
 pub mod learning;
 pub mod mcp;
 pub mod session;
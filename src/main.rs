@@ -1,6 +1,8 @@
+mod cli;
 mod code;
 mod compress;
 mod config;
+mod doctor;
 mod learning;
 mod mcp;
 mod session;
@@ -8,27 +10,39 @@ mod skill;
 mod store;
 
 use anyhow::Result;
+use clap::Parser;
+use std::process::ExitCode;
 use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt};
 
 #[tokio::main]
-async fn main() -> Result<()> {
-    // Initialize tracing - logs to stderr
+async fn main() -> Result<ExitCode> {
+    let cli = cli::Cli::parse();
+    let command = cli.command.filter(|command| !matches!(command, cli::Command::Serve));
+
+    // Initialize tracing - logs to stderr; terminal commands only show warnings
+    let default_filter = if command.is_some() { "warn" } else { "codegraph=debug,warn" };
     tracing_subscriber::registry()
         .with(
             tracing_subscriber::EnvFilter::try_from_default_env()
-                .unwrap_or_else(|_| "codegraph=debug,warn".into()),
+                .unwrap_or_else(|_| default_filter.into()),
         )
         .with(tracing_subscriber::fmt::layer().with_writer(std::io::stderr))
         .init();
 
+    if let Some(command) = command {
+        return cli::run(command, cli.root).await;
+    }
+
     tracing::info!("Starting Codegraph MCP server v{}", env!("CARGO_PKG_VERSION"));
 
     // Create server — deps will be initialized lazily on MCP initialize handshake,
     // using the project root from the client's roots parameter
     let server = mcp::Server::new();
 
-    // Run stdio transport
-    mcp::run_stdio(server).await?;
+    match cli.transport {
+        cli::Transport::Stdio => mcp::run_stdio(server).await?,
+        cli::Transport::Http => mcp::run_http(server, std::net::SocketAddr::new(cli.host, cli.port), cli.token).await?,
+    }
 
-    Ok(())
+    Ok(ExitCode::SUCCESS)
 }
@@ -0,0 +1,258 @@
+//! Runtime metrics for the running server: tool calls, index runs, database time, and
+//! query cache hit rates. Reported by `server_metrics` as text, JSON, or the Prometheus
+//! text exposition format.
+
+use serde_json::{json, Value};
+use std::collections::BTreeMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// Count and latency of one kind of operation
+#[derive(Debug, Clone, Copy, Default)]
+struct Timing {
+    count: u64,
+    errors: u64,
+    total: Duration,
+    max: Duration,
+}
+
+impl Timing {
+    fn record(&mut self, elapsed: Duration, ok: bool) {
+        self.count += 1;
+        if !ok {
+            self.errors += 1;
+        }
+        self.total += elapsed;
+        self.max = self.max.max(elapsed);
+    }
+
+    fn mean(&self) -> Duration {
+        if self.count == 0 {
+            Duration::ZERO
+        } else {
+            self.total / self.count as u32
+        }
+    }
+
+    fn to_json(self) -> Value {
+        json!({
+            "count": self.count,
+            "errors": self.errors,
+            "total_ms": self.total.as_secs_f64() * 1000.0,
+            "mean_ms": self.mean().as_secs_f64() * 1000.0,
+            "max_ms": self.max.as_secs_f64() * 1000.0,
+        })
+    }
+}
+
+#[derive(Debug, Default)]
+struct Counters {
+    /// By tool name
+    tools: BTreeMap<String, Timing>,
+    /// "full" or "incremental"
+    index_runs: BTreeMap<String, Timing>,
+    /// By operation: graph_load, index_writes, resolution
+    db: BTreeMap<String, Timing>,
+    cache_hits: u64,
+    cache_misses: u64,
+}
+
+/// Shared by all tool calls; each record takes the lock only briefly and never across an await
+#[derive(Debug)]
+pub struct Metrics {
+    started: Instant,
+    counters: Mutex<Counters>,
+}
+
+impl Default for Metrics {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Metrics {
+    pub fn new() -> Self {
+        Self {
+            started: Instant::now(),
+            counters: Mutex::new(Counters::default()),
+        }
+    }
+
+    fn counters(&self) -> std::sync::MutexGuard<'_, Counters> {
+        self.counters.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// A tool call finished; `ok` is false if it failed or returned an error result
+    pub fn record_tool(&self, name: &str, elapsed: Duration, ok: bool) {
+        self.counters().tools.entry(name.to_string()).or_default().record(elapsed, ok);
+    }
+
+    pub fn record_index(&self, full: bool, elapsed: Duration) {
+        let kind = if full { "full" } else { "incremental" };
+        self.counters().index_runs.entry(kind.to_string()).or_default().record(elapsed, true);
+    }
+
+    pub fn record_db(&self, operation: &str, elapsed: Duration) {
+        self.counters().db.entry(operation.to_string()).or_default().record(elapsed, true);
+    }
+
+    pub fn record_cache(&self, hit: bool) {
+        let mut counters = self.counters();
+        if hit {
+            counters.cache_hits += 1;
+        } else {
+            counters.cache_misses += 1;
+        }
+    }
+
+    pub fn reset(&self) {
+        *self.counters() = Counters::default();
+    }
+
+    pub fn to_json(&self) -> Value {
+        let counters = self.counters();
+        let timings = |map: &BTreeMap<String, Timing>| -> serde_json::Map<String, Value> {
+            map.iter().map(|(name, timing)| (name.clone(), timing.to_json())).collect()
+        };
+        json!({
+            "uptime_secs": self.started.elapsed().as_secs(),
+            "tools": timings(&counters.tools),
+            "index_runs": timings(&counters.index_runs),
+            "db": timings(&counters.db),
+            "query_cache": {
+                "hits": counters.cache_hits,
+                "misses": counters.cache_misses,
+                "hit_rate": hit_rate(counters.cache_hits, counters.cache_misses),
+            },
+        })
+    }
+
+    pub fn format_report(&self) -> String {
+        let counters = self.counters();
+        let mut lines = vec![format!("Uptime: {}s", self.started.elapsed().as_secs())];
+
+        let sections = [
+            ("Tool calls", &counters.tools),
+            ("Index runs", &counters.index_runs),
+            ("Database", &counters.db),
+        ];
+        for (title, timings) in sections {
+            lines.push(String::new());
+            lines.push(format!("## {}", title));
+            if timings.is_empty() {
+                lines.push("(none)".to_string());
+            }
+            for (name, timing) in timings {
+                let errors = match timing.errors {
+                    0 => String::new(),
+                    n => format!(", {} errors", n),
+                };
+                lines.push(format!(
+                    "{}: {} calls{}, mean {:.1}ms, max {:.1}ms",
+                    name,
+                    timing.count,
+                    errors,
+                    timing.mean().as_secs_f64() * 1000.0,
+                    timing.max.as_secs_f64() * 1000.0
+                ));
+            }
+        }
+
+        lines.push(String::new());
+        lines.push(format!(
+            "Query cache: {} hits, {} misses ({:.0}% hit rate)",
+            counters.cache_hits,
+            counters.cache_misses,
+            hit_rate(counters.cache_hits, counters.cache_misses) * 100.0
+        ));
+        lines.join("\n")
+    }
+
+    /// Prometheus text exposition format, for scraping by an HTTP endpoint
+    pub fn to_prometheus(&self) -> String {
+        let counters = self.counters();
+        let mut out = String::new();
+        out.push_str("# HELP codegraph_uptime_seconds Seconds since the server started\n");
+        out.push_str("# TYPE codegraph_uptime_seconds gauge\n");
+        out.push_str(&format!("codegraph_uptime_seconds {}\n", self.started.elapsed().as_secs()));
+
+        let families = [
+            ("tool_calls", "tool", "Tool calls", &counters.tools),
+            ("index_runs", "kind", "Index runs", &counters.index_runs),
+            ("db_operations", "operation", "Database operations", &counters.db),
+        ];
+        for (metric, label, help, timings) in families {
+            out.push_str(&format!("# HELP codegraph_{}_total {}\n", metric, help));
+            out.push_str(&format!("# TYPE codegraph_{}_total counter\n", metric));
+            for (name, timing) in timings {
+                out.push_str(&format!("codegraph_{}_total{{{}=\"{}\"}} {}\n", metric, label, name, timing.count));
+            }
+            out.push_str(&format!("# HELP codegraph_{}_seconds_total Time spent in {}\n", metric, help.to_lowercase()));
+            out.push_str(&format!("# TYPE codegraph_{}_seconds_total counter\n", metric));
+            for (name, timing) in timings {
+                out.push_str(&format!(
+                    "codegraph_{}_seconds_total{{{}=\"{}\"}} {}\n",
+                    metric,
+                    label,
+                    name,
+                    timing.total.as_secs_f64()
+                ));
+            }
+        }
+
+        out.push_str("# HELP codegraph_tool_errors_total Tool calls that failed or returned an error\n");
+        out.push_str("# TYPE codegraph_tool_errors_total counter\n");
+        for (name, timing) in &counters.tools {
+            out.push_str(&format!("codegraph_tool_errors_total{{tool=\"{}\"}} {}\n", name, timing.errors));
+        }
+
+        out.push_str("# HELP codegraph_query_cache_requests_total Query cache lookups\n");
+        out.push_str("# TYPE codegraph_query_cache_requests_total counter\n");
+        out.push_str(&format!("codegraph_query_cache_requests_total{{result=\"hit\"}} {}\n", counters.cache_hits));
+        out.push_str(&format!("codegraph_query_cache_requests_total{{result=\"miss\"}} {}\n", counters.cache_misses));
+        out
+    }
+}
+
+fn hit_rate(hits: u64, misses: u64) -> f64 {
+    match hits + misses {
+        0 => 0.0,
+        total => hits as f64 / total as f64,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_metrics_report() {
+        let metrics = Metrics::new();
+        metrics.record_tool("search_symbols", Duration::from_millis(4), true);
+        metrics.record_tool("search_symbols", Duration::from_millis(8), false);
+        metrics.record_index(true, Duration::from_millis(100));
+        metrics.record_db("graph_load", Duration::from_millis(20));
+        metrics.record_cache(true);
+        metrics.record_cache(true);
+        metrics.record_cache(false);
+
+        let json = metrics.to_json();
+        assert_eq!(json["tools"]["search_symbols"]["count"], 2);
+        assert_eq!(json["tools"]["search_symbols"]["errors"], 1);
+        assert_eq!(json["tools"]["search_symbols"]["mean_ms"], 6.0);
+        assert_eq!(json["index_runs"]["full"]["count"], 1);
+        assert!((json["query_cache"]["hit_rate"].as_f64().unwrap() - 2.0 / 3.0).abs() < 1e-9);
+
+        let report = metrics.format_report();
+        assert!(report.contains("search_symbols: 2 calls, 1 errors, mean 6.0ms, max 8.0ms"), "{}", report);
+        assert!(report.contains("Query cache: 2 hits, 1 misses (67% hit rate)"));
+
+        let prometheus = metrics.to_prometheus();
+        assert!(prometheus.contains("codegraph_tool_calls_total{tool=\"search_symbols\"} 2\n"));
+        assert!(prometheus.contains("codegraph_db_operations_seconds_total{operation=\"graph_load\"} 0.02\n"));
+        assert!(prometheus.contains("codegraph_query_cache_requests_total{result=\"miss\"} 1\n"));
+
+        metrics.reset();
+        assert_eq!(metrics.to_json()["tools"], json!({}));
+    }
+}
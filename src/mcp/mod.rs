@@ -1,7 +1,11 @@
+pub mod metrics;
+pub mod prompts;
 pub mod protocol;
+pub mod query_cache;
+pub mod resources;
 pub mod server;
 pub mod tools;
 pub mod transport;
 
 pub use server::Server;
-pub use transport::run_stdio;
+pub use transport::{run_http, run_stdio};
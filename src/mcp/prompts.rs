@@ -0,0 +1,179 @@
+//! MCP prompts: canned workflows filled in with live session, learning, and graph data.
+//!
+//! Each prompt runs the same tools an agent would call by hand and stitches their output
+//! into one user message, so a client can start a workflow with a single `prompts/get`.
+
+use anyhow::Result;
+use serde_json::{json, Value};
+
+use super::protocol::{Content, Prompt, PromptArgument, PromptMessage};
+use super::tools::ToolRegistry;
+
+pub fn list() -> Vec<Prompt> {
+    vec![
+        Prompt {
+            name: "restore_context".to_string(),
+            description: Some(
+                "Resume the active session: task, progress, decisions, working symbols, and relevant patterns"
+                    .to_string(),
+            ),
+            arguments: Vec::new(),
+        },
+        Prompt {
+            name: "reflect_on_attempt".to_string(),
+            description: Some(
+                "Review a solution attempt (the latest by default) and record its lesson as a pattern or failure"
+                    .to_string(),
+            ),
+            arguments: vec![PromptArgument {
+                name: "attempt_id".to_string(),
+                description: Some("Solution ID from record_attempt (default: the most recent)".to_string()),
+                required: false,
+            }],
+        },
+        Prompt {
+            name: "plan_change".to_string(),
+            description: Some(
+                "Plan a change to a symbol from its references, dependencies, and past patterns".to_string(),
+            ),
+            arguments: vec![
+                PromptArgument {
+                    name: "symbol".to_string(),
+                    description: Some("Symbol ID from search_symbols, or a bare name".to_string()),
+                    required: true,
+                },
+                PromptArgument {
+                    name: "goal".to_string(),
+                    description: Some("What the change should accomplish".to_string()),
+                    required: false,
+                },
+            ],
+        },
+    ]
+}
+
+/// Messages for a prompt, or `None` if there is no prompt by that name. Required arguments
+/// are checked by the caller.
+pub async fn get(registry: &ToolRegistry, name: &str, args: &Value) -> Result<Option<Vec<PromptMessage>>> {
+    let arg = |key: &str| args.get(key).and_then(|v| v.as_str()).filter(|v| !v.is_empty());
+    let text = match name {
+        "restore_context" => restore_context(registry).await?,
+        "reflect_on_attempt" => reflect_on_attempt(registry, arg("attempt_id")).await?,
+        "plan_change" => plan_change(registry, arg("symbol").unwrap_or_default(), arg("goal")).await?,
+        _ => return Ok(None),
+    };
+    Ok(Some(vec![PromptMessage {
+        role: "user".to_string(),
+        content: Content::Text { text },
+    }]))
+}
+
+/// Text of a tool's result, so prompts can quote it
+async fn tool_text(registry: &ToolRegistry, tool: &str, args: Value) -> Result<String> {
+    let result = registry.execute(tool, args).await?;
+    Ok(result
+        .content
+        .iter()
+        .map(|Content::Text { text }| text.as_str())
+        .collect::<Vec<_>>()
+        .join("\n"))
+}
+
+async fn restore_context(registry: &ToolRegistry) -> Result<String> {
+    let Some(session) = registry.context().session_manager.get_session().await? else {
+        return Ok("There is no active session. Ask what to work on, then call start_session.".to_string());
+    };
+
+    let context = tool_text(registry, "smart_context", json!({})).await?;
+    let task = json!({ "description": session.task });
+    let patterns = tool_text(registry, "recall_patterns", task.clone()).await?;
+    let failures = tool_text(registry, "recall_failures", task).await?;
+    Ok(format!(
+        "I'm resuming work on this project. Here is the saved session:\n\n{}\n\n\
+         ## Patterns that apply\n\n{}\n\n## Known pitfalls\n\n{}\n\n\
+         Continue with the current task item. Before editing, check that the working symbols \
+         still look as described; record decisions with add_decision as you go.",
+        context, patterns, failures
+    ))
+}
+
+async fn reflect_on_attempt(registry: &ToolRegistry, attempt_id: Option<&str>) -> Result<String> {
+    let lineage = &registry.context().lineage_store;
+    let attempt = match attempt_id {
+        Some(id) => lineage.get(id).await?,
+        None => lineage.query("", true, 1).await?.into_iter().next(),
+    };
+    let Some(attempt) = attempt else {
+        return Ok(match attempt_id {
+            Some(id) => format!("No solution attempt with ID '{}'. Use query_lineage to find it.", id),
+            None => "No solution attempts are recorded yet. Call record_attempt before starting a change \
+                     so it can be reflected on afterwards."
+                .to_string(),
+        });
+    };
+
+    let history = tool_text(
+        registry,
+        "query_lineage",
+        json!({ "task": attempt.task, "include_failures": true }),
+    )
+    .await?;
+    let list = |items: &[String]| if items.is_empty() { "(none)".to_string() } else { items.join(", ") };
+    Ok(format!(
+        "Reflect on this solution attempt and record what it taught us.\n\n\
+         ## Attempt {}\n\nTask: {}\nPlan: {}\nApproach: {}\nOutcome: {}\nFiles: {}\nSymbols: {}\n\n\
+         ## Earlier attempts at similar tasks\n\n{}\n\n\
+         1. If the outcome above is out of date, call record_outcome first.\n\
+         2. Call reflect with attempt_id \"{}\": the intent, the root cause (what made it work or fail), \
+         and a lesson in the form \"When X, do Y because Z\", scoped to the files involved.",
+        attempt.id,
+        attempt.task,
+        attempt.plan,
+        attempt.approach.as_deref().unwrap_or("(not recorded)"),
+        attempt.outcome.to_str(),
+        list(&attempt.files_modified),
+        list(&attempt.symbols_modified),
+        history,
+        attempt.id
+    ))
+}
+
+async fn plan_change(registry: &ToolRegistry, symbol: &str, goal: Option<&str>) -> Result<String> {
+    let file = {
+        let graph = registry.context().graph.read().await;
+        graph
+            .get_node(symbol)
+            .and_then(|node| node.data.get("file").and_then(|v| v.as_str()).map(String::from))
+    };
+
+    let mut sections = vec![format!(
+        "Plan a change to `{}`{}.",
+        symbol,
+        goal.map(|goal| format!(": {}", goal)).unwrap_or_default()
+    )];
+    if file.is_some() {
+        let source = tool_text(registry, "get_symbol_source", json!({ "id": symbol })).await?;
+        sections.push(format!("## Current source\n\n{}", source));
+    }
+    let references = tool_text(registry, "find_references", json!({ "symbol": symbol })).await?;
+    sections.push(format!("## Where it's used\n\n{}", references));
+    if file.is_some() {
+        let args = json!({ "id": symbol, "direction": "outgoing", "compact": true });
+        let dependencies = tool_text(registry, "get_neighbors", args).await?;
+        sections.push(format!("## What it depends on\n\n{}", dependencies));
+    }
+    let task = goal.map_or_else(|| format!("Change {}", symbol), String::from);
+    let mut args = json!({ "task": task });
+    if let Some(file) = &file {
+        args["current_file"] = json!(file);
+    }
+    let approach = tool_text(registry, "suggest_approach", args).await?;
+    sections.push(format!("## Past patterns, pitfalls, and attempts\n\n{}", approach));
+    sections.push(
+        "Write a step-by-step plan: every file and call site to update, in order, and how to verify \
+         each step. Call out callers the graph can't see (dynamic dispatch, other services). Once the \
+         plan is agreed, call record_attempt with it."
+            .to_string(),
+    );
+    Ok(sections.join("\n\n"))
+}
@@ -11,6 +11,16 @@ pub struct JsonRpcRequest {
     pub params: Option<Value>,
 }
 
+impl JsonRpcRequest {
+    /// ID of the request a `notifications/cancelled` message cancels
+    pub fn cancelled_request_id(&self) -> Option<&Value> {
+        if self.method != "notifications/cancelled" {
+            return None;
+        }
+        self.params.as_ref()?.get("requestId")
+    }
+}
+
 /// JSON-RPC 2.0 response envelope
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct JsonRpcResponse {
@@ -22,6 +32,15 @@ pub struct JsonRpcResponse {
     pub error: Option<JsonRpcError>,
 }
 
+/// JSON-RPC 2.0 notification (server-initiated, no response expected)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct JsonRpcNotification {
+    pub jsonrpc: String,
+    pub method: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub params: Option<Value>,
+}
+
 /// JSON-RPC 2.0 error object
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct JsonRpcError {
@@ -37,6 +56,8 @@ pub const INVALID_REQUEST: i32 = -32600;
 pub const METHOD_NOT_FOUND: i32 = -32601;
 pub const INVALID_PARAMS: i32 = -32602;
 pub const INTERNAL_ERROR: i32 = -32603;
+/// MCP: `resources/read` for a URI the server doesn't have
+pub const RESOURCE_NOT_FOUND: i32 = -32002;
 
 // MCP Lifecycle
 
@@ -84,6 +105,10 @@ pub struct ServerCapabilities {
     pub experimental: Option<Value>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub tools: Option<Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub resources: Option<Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub prompts: Option<Value>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -115,6 +140,15 @@ pub struct ToolCall {
     pub name: String,
     #[serde(default)]
     pub arguments: Value,
+    /// Request metadata; carries `progressToken` when the client wants progress notifications
+    #[serde(default, rename = "_meta", skip_serializing_if = "Option::is_none")]
+    pub meta: Option<Value>,
+}
+
+impl ToolCall {
+    pub fn progress_token(&self) -> Option<&Value> {
+        self.meta.as_ref()?.get("progressToken")
+    }
 }
 
 /// Tool execution result
@@ -126,6 +160,56 @@ pub struct ToolResult {
     pub is_error: Option<bool>,
 }
 
+// MCP Resources
+
+/// Resource definition
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Resource {
+    pub uri: String,
+    pub name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub mime_type: Option<String>,
+}
+
+/// Text contents of a resource, as returned by `resources/read`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ResourceContents {
+    pub uri: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub mime_type: Option<String>,
+    pub text: String,
+}
+
+/// Prompt definition
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Prompt {
+    pub name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty", default)]
+    pub arguments: Vec<PromptArgument>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PromptArgument {
+    pub name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    #[serde(default)]
+    pub required: bool,
+}
+
+/// Message of a filled-in prompt, as returned by `prompts/get`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PromptMessage {
+    pub role: String,
+    pub content: Content,
+}
+
 /// Content block
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(tag = "type", rename_all = "lowercase")]
@@ -157,6 +241,30 @@ impl JsonRpcResponse {
     }
 }
 
+impl JsonRpcNotification {
+    /// `notifications/progress` for a request that supplied a progress token
+    pub fn progress(token: &Value, progress: u64, message: &str) -> Self {
+        Self {
+            jsonrpc: "2.0".to_string(),
+            method: "notifications/progress".to_string(),
+            params: Some(serde_json::json!({
+                "progressToken": token,
+                "progress": progress,
+                "message": message,
+            })),
+        }
+    }
+
+    /// `notifications/resources/updated` for a resource the client subscribed to
+    pub fn resource_updated(uri: &str) -> Self {
+        Self {
+            jsonrpc: "2.0".to_string(),
+            method: "notifications/resources/updated".to_string(),
+            params: Some(serde_json::json!({ "uri": uri })),
+        }
+    }
+}
+
 impl ToolResult {
     pub fn text(text: impl Into<String>) -> Self {
         Self {
@@ -227,4 +335,21 @@ mod tests {
             Content::Text { text } => assert_eq!(text, "Hello, world!"),
         }
     }
+
+    #[test]
+    fn test_tool_call_progress_token() {
+        let call: ToolCall = serde_json::from_value(serde_json::json!({
+            "name": "bash_compressed",
+            "arguments": {"command": "cargo test"},
+            "_meta": {"progressToken": "tok-1"}
+        }))
+        .unwrap();
+        assert_eq!(call.progress_token(), Some(&Value::from("tok-1")));
+
+        let notification = JsonRpcNotification::progress(&Value::from("tok-1"), 2, "running");
+        let json = serde_json::to_value(&notification).unwrap();
+        assert_eq!(json["method"], "notifications/progress");
+        assert_eq!(json["params"]["progress"], 2);
+        assert!(json.get("id").is_none());
+    }
 }
@@ -0,0 +1,137 @@
+//! LRU cache for read-only graph queries.
+//!
+//! Agents repeat the same lookups (search, neighbors, file symbols) many times in a
+//! session. Results are keyed by tool name and arguments and tagged with the graph
+//! generation they were computed against; changing the graph bumps the generation.
+
+use serde_json::Value;
+use std::collections::HashMap;
+
+use super::protocol::ToolResult;
+
+/// Tools whose results depend only on their arguments and the indexed code
+const CACHED_TOOLS: &[&str] = &[
+    "search_symbols",
+    "search_code",
+    "get_neighbors",
+    "find_references",
+    "get_file_symbols",
+    "call_path",
+    "find_unreferenced_symbols",
+    "detect_cycles",
+];
+
+/// Most results kept at once; the least recently used is evicted first
+const MAX_ENTRIES: usize = 256;
+
+#[derive(Debug)]
+struct Entry {
+    result: ToolResult,
+    last_used: u64,
+}
+
+#[derive(Debug, Default)]
+pub struct QueryCache {
+    entries: HashMap<String, Entry>,
+    generation: u64,
+    clock: u64,
+}
+
+impl QueryCache {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Cache key for a tool call, or `None` if its result can't be cached. serde_json sorts
+    /// object keys, so equal arguments match. File source is read from disk, not the graph,
+    /// so calls that include it always run.
+    pub fn key(tool: &str, args: &Value) -> Option<String> {
+        let reads_files = args.get("include_source").and_then(|v| v.as_bool()).unwrap_or(false);
+        (CACHED_TOOLS.contains(&tool) && !reads_files).then(|| format!("{}:{}", tool, args))
+    }
+
+    /// Graph generation results are currently cached against
+    pub fn generation(&self) -> u64 {
+        self.generation
+    }
+
+    pub fn get(&mut self, key: &str) -> Option<ToolResult> {
+        self.clock += 1;
+        let entry = self.entries.get_mut(key)?;
+        entry.last_used = self.clock;
+        Some(entry.result.clone())
+    }
+
+    /// Store a result computed against `generation`. Results from before the latest
+    /// invalidation are dropped, since the graph may have changed while they ran.
+    pub fn insert(&mut self, key: String, generation: u64, result: ToolResult) {
+        if generation != self.generation {
+            return;
+        }
+        if self.entries.len() >= MAX_ENTRIES && !self.entries.contains_key(&key) {
+            if let Some(oldest) = self
+                .entries
+                .iter()
+                .min_by_key(|(_, entry)| entry.last_used)
+                .map(|(key, _)| key.clone())
+            {
+                self.entries.remove(&oldest);
+            }
+        }
+        self.clock += 1;
+        self.entries.insert(
+            key,
+            Entry {
+                result,
+                last_used: self.clock,
+            },
+        );
+    }
+
+    /// Drop everything after the graph (or config affecting queries) changed
+    pub fn invalidate(&mut self) {
+        self.generation += 1;
+        self.entries.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_lru_eviction() {
+        let mut cache = QueryCache::new();
+        for i in 0..MAX_ENTRIES {
+            cache.insert(format!("q{}", i), 0, ToolResult::text(i.to_string()));
+        }
+        // Touch the oldest so the next insert evicts q1 instead
+        assert!(cache.get("q0").is_some());
+        cache.insert("new".to_string(), 0, ToolResult::text("new"));
+
+        assert!(cache.get("q0").is_some());
+        assert!(cache.get("q1").is_none());
+        assert!(cache.get("new").is_some());
+    }
+
+    #[test]
+    fn test_invalidation() {
+        let mut cache = QueryCache::new();
+        let key = QueryCache::key("search_symbols", &json!({"query": "foo", "limit": 5})).unwrap();
+        assert_eq!(Some(&key), QueryCache::key("search_symbols", &json!({"limit": 5, "query": "foo"})).as_ref());
+        assert!(QueryCache::key("get_file_symbols", &json!({"path": "a.rs", "include_source": true})).is_none());
+        assert!(QueryCache::key("index_project", &json!({})).is_none());
+
+        let started = cache.generation();
+        cache.insert(key.clone(), started, ToolResult::text("old"));
+        cache.invalidate();
+        assert!(cache.get(&key).is_none());
+
+        // A query that started before the reindex must not repopulate the cache
+        cache.insert(key.clone(), started, ToolResult::text("stale"));
+        assert!(cache.get(&key).is_none());
+        cache.insert(key.clone(), cache.generation(), ToolResult::text("fresh"));
+        assert!(cache.get(&key).is_some());
+    }
+}
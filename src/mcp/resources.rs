@@ -0,0 +1,141 @@
+//! MCP resources: read-only views of session, skill, and index state.
+//!
+//! Clients list them with `resources/list`, fetch them with `resources/read`, and can
+//! `resources/subscribe` to get `notifications/resources/updated` when a tool call changes
+//! one (or, for stats, when the file watcher reindexes), instead of polling with tool calls.
+
+use anyhow::Result;
+use serde_json::json;
+
+use super::protocol::{Resource, ResourceContents};
+use super::tools::ToolContext;
+
+pub const SESSION_URI: &str = "codegraph://session/current";
+pub const SKILL_URI: &str = "codegraph://skill";
+pub const STATS_URI: &str = "codegraph://stats";
+
+/// Tools whose calls can change each resource
+const SESSION_WRITE_TOOLS: &[&str] = &[
+    "start_session",
+    "switch_session",
+    "archive_session",
+    "restore_checkpoint",
+    "update_task",
+    "add_decision",
+    "set_context",
+];
+const SKILL_WRITE_TOOLS: &[&str] = &[
+    "distill_project_skill",
+    // These regenerate SKILL.md once enough new learnings pile up
+    "extract_pattern",
+    "record_failure",
+    "record_outcome",
+    "reflect",
+    "sync_learnings",
+    "import_learnings",
+];
+const STATS_WRITE_TOOLS: &[&str] = &[
+    "index_project",
+    "index_profile",
+    "infer_cross_edges",
+    "extract_pattern",
+    "record_failure",
+    "record_outcome",
+    "reflect",
+    "sync_learnings",
+    "import_learnings",
+    "consolidate_patterns",
+];
+
+pub fn list() -> Vec<Resource> {
+    vec![
+        Resource {
+            uri: SESSION_URI.to_string(),
+            name: "Current session".to_string(),
+            description: Some("The active session: task, items, decisions, and working context".to_string()),
+            mime_type: Some("application/json".to_string()),
+        },
+        Resource {
+            uri: SKILL_URI.to_string(),
+            name: "Project skill".to_string(),
+            description: Some("SKILL.md distilled from patterns, failures, and instructions".to_string()),
+            mime_type: Some("text/markdown".to_string()),
+        },
+        Resource {
+            uri: STATS_URI.to_string(),
+            name: "Index stats".to_string(),
+            description: Some("Counts of indexed files, symbols, edges, connections, and learnings".to_string()),
+            mime_type: Some("application/json".to_string()),
+        },
+    ]
+}
+
+/// Current contents of a resource, or `None` if the URI isn't one
+pub async fn read(ctx: &ToolContext, uri: &str) -> Result<Option<ResourceContents>> {
+    let (mime_type, text) = match uri {
+        SESSION_URI => {
+            let session = ctx.session_manager.get_session().await?;
+            ("application/json", serde_json::to_string_pretty(&session)?)
+        }
+        SKILL_URI => {
+            let path = ctx.config.codegraph_dir.join(crate::skill::render::SKILL_FILE);
+            let text = std::fs::read_to_string(&path)
+                .unwrap_or_else(|_| "No SKILL.md yet. Run distill_project_skill to generate it.\n".to_string());
+            ("text/markdown", text)
+        }
+        STATS_URI => {
+            let stats = ctx.store.stats().await?;
+            let in_memory = {
+                let graph = ctx.graph.read().await;
+                json!({
+                    "nodes": graph.graph.node_count(),
+                    "edges": graph.graph.edge_count(),
+                    "slim": graph.is_slim(),
+                })
+            };
+            let stats = json!({
+                "files": stats.files,
+                "symbols": stats.symbols,
+                "edges": stats.edges,
+                "connections": stats.connections,
+                "patterns": stats.patterns,
+                "failures": stats.failures,
+                "graph": in_memory,
+            });
+            ("application/json", serde_json::to_string_pretty(&stats)?)
+        }
+        _ => return Ok(None),
+    };
+    Ok(Some(ResourceContents {
+        uri: uri.to_string(),
+        mime_type: Some(mime_type.to_string()),
+        text,
+    }))
+}
+
+/// Resources a call to `tool` may have changed
+pub fn changed_by(tool: &str) -> Vec<&'static str> {
+    [
+        (SESSION_URI, SESSION_WRITE_TOOLS),
+        (SKILL_URI, SKILL_WRITE_TOOLS),
+        (STATS_URI, STATS_WRITE_TOOLS),
+    ]
+    .into_iter()
+    .filter(|(_, tools)| tools.contains(&tool))
+    .map(|(uri, _)| uri)
+    .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_changed_by() {
+        assert_eq!(changed_by("update_task"), vec![SESSION_URI]);
+        assert_eq!(changed_by("extract_pattern"), vec![SKILL_URI, STATS_URI]);
+        assert!(changed_by("search_symbols").is_empty());
+        // Every listed resource is one read() serves
+        assert!(list().iter().all(|r| [SESSION_URI, SKILL_URI, STATS_URI].contains(&r.uri.as_str())));
+    }
+}
@@ -1,14 +1,18 @@
-use serde_json::json;
+use serde_json::{json, Value};
 use std::path::PathBuf;
-use std::sync::{Arc, RwLock};
+use std::sync::Arc;
+use tokio::sync::RwLock;
 use tokio::sync::OnceCell;
 use tracing::{debug, info, warn};
 
 use super::protocol::*;
+use super::{prompts, resources};
 use super::tools::{ToolContext, ToolRegistry};
-use super::transport::Handler;
+use super::transport::{Handler, Notifier, ProgressReporter, RequestControl};
+use crate::code::watcher::FileWatcher;
 use crate::code::{CrossLanguageInferrer, Indexer};
 use crate::config::Config;
+use crate::learning::embeddings::EmbeddingIndex;
 use crate::learning::failures::FailureStore;
 use crate::learning::lineage::LineageStore;
 use crate::learning::niches::NicheStore;
@@ -24,6 +28,13 @@ const SERVER_VERSION: &str = env!("CARGO_PKG_VERSION");
 pub struct Server {
     tools: OnceCell<ToolRegistry>,
     initialized: std::sync::atomic::AtomicBool,
+    /// Shared with the file watcher, which announces background reindexes
+    notifier: Arc<std::sync::OnceLock<Notifier>>,
+    watcher: std::sync::OnceLock<FileWatcher>,
+    /// Resource URIs the client asked to be notified about
+    subscriptions: Arc<std::sync::Mutex<std::collections::HashSet<String>>>,
+    /// Cancellation flags of the tool calls being handled, by JSON-encoded request ID
+    in_flight: std::sync::Mutex<std::collections::HashMap<String, Arc<std::sync::atomic::AtomicBool>>>,
 }
 
 impl Server {
@@ -31,6 +42,10 @@ impl Server {
         Self {
             tools: OnceCell::new(),
             initialized: std::sync::atomic::AtomicBool::new(false),
+            notifier: Arc::new(std::sync::OnceLock::new()),
+            watcher: std::sync::OnceLock::new(),
+            subscriptions: Arc::new(std::sync::Mutex::new(std::collections::HashSet::new())),
+            in_flight: std::sync::Mutex::new(std::collections::HashMap::new()),
         }
     }
 
@@ -53,8 +68,9 @@ impl Server {
         cwd
     }
 
-    /// Initialize all dependencies from the resolved project root
-    async fn init_dependencies(project_root: &std::path::Path) -> Result<ToolRegistry, String> {
+    /// Initialize all dependencies from the resolved project root. The CLI subcommands
+    /// use the same registry, so they behave like the matching tools.
+    pub async fn init_dependencies(project_root: &std::path::Path) -> Result<ToolRegistry, String> {
         let config = Arc::new(
             Config::from_path(project_root)
                 .map_err(|e| format!("Failed to detect config: {}", e))?,
@@ -69,24 +85,36 @@ impl Server {
         let store = Arc::new(
             Store::open(&config)
                 .await
-                .map_err(|e| format!("Failed to open databases: {}", e))?,
+                .map_err(|e| format!("Failed to open databases: {:#}", e))?,
         );
 
         let graph = Arc::new(RwLock::new(
-            CodeGraph::load_from_store(&store)
+            CodeGraph::load(&store, &config.settings().graph)
                 .await
                 .map_err(|e| format!("Failed to load code graph: {}", e))?,
         ));
 
         let indexer = Arc::new(Indexer::new(store.clone(), config.clone()));
         let session_manager = Arc::new(SessionManager::new(store.clone(), graph.clone()));
-        let pattern_store = Arc::new(PatternStore::new(Arc::new(store.learning_db.clone())));
-        let failure_store = Arc::new(FailureStore::new(Arc::new(store.learning_db.clone())));
+        match session_manager.recover().await {
+            Ok(report) if !report.is_empty() => info!(
+                "Repaired session after an interrupted update: {} reattached, {} removed",
+                report.reattached.len(),
+                report.removed.len()
+            ),
+            Ok(_) => {}
+            Err(e) => warn!("Session recovery failed: {}", e),
+        }
+        let embeddings = Arc::new(EmbeddingIndex::new(Arc::new(store.learning_db.clone()), config.clone()));
+        let pattern_store =
+            Arc::new(PatternStore::new(Arc::new(store.learning_db.clone())).with_embeddings(embeddings.clone()));
+        let failure_store =
+            Arc::new(FailureStore::new(Arc::new(store.learning_db.clone())).with_embeddings(embeddings));
         let lineage_store = Arc::new(LineageStore::new(Arc::new(store.learning_db.clone())));
         let niche_store = Arc::new(NicheStore::new(Arc::new(store.learning_db.clone())));
         let manual_instruction_store =
             Arc::new(ManualInstructionStore::new(Arc::new(store.learning_db.clone())));
-        let cross_language_inferrer = Arc::new(CrossLanguageInferrer::new(store.clone()));
+        let cross_language_inferrer = Arc::new(CrossLanguageInferrer::new(store.clone(), config.clone()));
 
         let ctx = Arc::new(ToolContext {
             store,
@@ -103,6 +131,10 @@ impl Server {
             compression_analytics: tokio::sync::Mutex::new(
                 crate::compress::CompressionAnalytics::new(),
             ),
+            output_cache: tokio::sync::Mutex::new(crate::compress::OutputCache::new()),
+            query_cache: tokio::sync::Mutex::new(crate::mcp::query_cache::QueryCache::new()),
+            metrics: crate::mcp::metrics::Metrics::new(),
+            shell_sessions: tokio::sync::Mutex::new(std::collections::HashMap::new()),
         });
 
         Ok(ToolRegistry::new(ctx))
@@ -139,6 +171,16 @@ impl Server {
 
         match Self::init_dependencies(&project_root).await {
             Ok(registry) => {
+                if registry.context().config.settings().indexing.watch {
+                    // Background reindexes change the stats without a tool call to announce them
+                    let (notifier, subscriptions) = (self.notifier.clone(), self.subscriptions.clone());
+                    match registry.watch(move || notify_subscribed(&notifier, &subscriptions, &[resources::STATS_URI])) {
+                        Ok(watcher) => {
+                            let _ = self.watcher.set(watcher);
+                        }
+                        Err(e) => warn!("File watching disabled: {:#}", e),
+                    }
+                }
                 let _ = self.tools.set(registry);
                 info!("Dependencies initialized for project: {:?}", project_root);
             }
@@ -155,6 +197,8 @@ impl Server {
             protocol_version: MCP_VERSION.to_string(),
             capabilities: ServerCapabilities {
                 tools: Some(json!({})),
+                resources: Some(json!({ "subscribe": true })),
+                prompts: Some(json!({})),
                 experimental: None,
             },
             server_info: ServerInfo {
@@ -235,16 +279,33 @@ impl Server {
 
         debug!("Tool call: {}", tool_call.name);
 
-        let result = match tools_registry
-            .execute(&tool_call.name, tool_call.arguments)
-            .await
-        {
+        let progress = match (tool_call.progress_token(), self.notifier.get()) {
+            (Some(token), Some(notifier)) => Some(ProgressReporter::new(notifier.clone(), token.clone())),
+            _ => None,
+        };
+        let control = RequestControl { progress, ..Default::default() };
+        let request_key = request.id.as_ref().map(Value::to_string);
+        if let Some(key) = &request_key {
+            let mut in_flight = self.in_flight.lock().unwrap_or_else(|e| e.into_inner());
+            in_flight.insert(key.clone(), control.cancelled.clone());
+        }
+
+        let result = tools_registry
+            .execute_with(&tool_call.name, tool_call.arguments, control)
+            .await;
+        if let Some(key) = &request_key {
+            self.in_flight.lock().unwrap_or_else(|e| e.into_inner()).remove(key);
+        }
+        let result = match result {
             Ok(result) => result,
             Err(e) => {
                 warn!("Tool execution failed: {}", e);
                 ToolResult::error(format!("Tool execution failed: {}", e))
             }
         };
+        if result.is_error.is_none() {
+            self.notify_resources_changed(&tool_call.name);
+        }
 
         match serde_json::to_value(result) {
             Ok(v) => JsonRpcResponse::success(request.id, v),
@@ -256,6 +317,139 @@ impl Server {
         }
     }
 
+    fn handle_resources_list(&self, request: JsonRpcRequest) -> JsonRpcResponse {
+        if self.tools.get().is_none() {
+            return JsonRpcResponse::error(
+                request.id,
+                INTERNAL_ERROR,
+                "Server not initialized yet".to_string(),
+            );
+        }
+        JsonRpcResponse::success(request.id, json!({ "resources": resources::list() }))
+    }
+
+    async fn handle_resources_read(&self, request: JsonRpcRequest) -> JsonRpcResponse {
+        let Some(tools_registry) = self.tools.get() else {
+            return JsonRpcResponse::error(
+                request.id,
+                INTERNAL_ERROR,
+                "Server not initialized yet".to_string(),
+            );
+        };
+        let Some(uri) = Self::resource_uri(&request) else {
+            return JsonRpcResponse::error(request.id, INVALID_PARAMS, "Missing resource uri".to_string());
+        };
+
+        match resources::read(tools_registry.context(), &uri).await {
+            Ok(Some(contents)) => JsonRpcResponse::success(request.id, json!({ "contents": [contents] })),
+            Ok(None) => JsonRpcResponse::error(
+                request.id,
+                RESOURCE_NOT_FOUND,
+                format!("Resource not found: {}", uri),
+            ),
+            Err(e) => JsonRpcResponse::error(
+                request.id,
+                INTERNAL_ERROR,
+                format!("Failed to read {}: {}", uri, e),
+            ),
+        }
+    }
+
+    fn handle_resources_subscribe(&self, request: JsonRpcRequest, subscribe: bool) -> JsonRpcResponse {
+        let Some(uri) = Self::resource_uri(&request) else {
+            return JsonRpcResponse::error(request.id, INVALID_PARAMS, "Missing resource uri".to_string());
+        };
+        if !resources::list().iter().any(|resource| resource.uri == uri) {
+            return JsonRpcResponse::error(
+                request.id,
+                RESOURCE_NOT_FOUND,
+                format!("Resource not found: {}", uri),
+            );
+        }
+
+        let mut subscriptions = self.subscriptions.lock().unwrap_or_else(|e| e.into_inner());
+        if subscribe {
+            subscriptions.insert(uri);
+        } else {
+            subscriptions.remove(&uri);
+        }
+        JsonRpcResponse::success(request.id, json!({}))
+    }
+
+    fn resource_uri(request: &JsonRpcRequest) -> Option<String> {
+        let uri = request.params.as_ref()?.get("uri")?.as_str()?;
+        Some(uri.to_string())
+    }
+
+    /// Tell the client about subscribed resources a tool call may have changed
+    fn notify_resources_changed(&self, tool: &str) {
+        notify_subscribed(&self.notifier, &self.subscriptions, &resources::changed_by(tool));
+    }
+
+    fn handle_prompts_list(&self, request: JsonRpcRequest) -> JsonRpcResponse {
+        if self.tools.get().is_none() {
+            return JsonRpcResponse::error(
+                request.id,
+                INTERNAL_ERROR,
+                "Server not initialized yet".to_string(),
+            );
+        }
+        JsonRpcResponse::success(request.id, json!({ "prompts": prompts::list() }))
+    }
+
+    async fn handle_prompts_get(&self, request: JsonRpcRequest) -> JsonRpcResponse {
+        let Some(tools_registry) = self.tools.get() else {
+            return JsonRpcResponse::error(
+                request.id,
+                INTERNAL_ERROR,
+                "Server not initialized yet".to_string(),
+            );
+        };
+        let params = request.params.clone().unwrap_or_default();
+        let name = params.get("name").and_then(|v| v.as_str()).unwrap_or_default();
+        let Some(prompt) = prompts::list().into_iter().find(|prompt| prompt.name == name) else {
+            return JsonRpcResponse::error(request.id, INVALID_PARAMS, format!("Unknown prompt: {}", name));
+        };
+        let args = params.get("arguments").cloned().unwrap_or_else(|| json!({}));
+        let missing = prompt.arguments.iter().find(|arg| {
+            arg.required && args.get(&arg.name).and_then(|v| v.as_str()).is_none_or(|v| v.is_empty())
+        });
+        if let Some(arg) = missing {
+            return JsonRpcResponse::error(
+                request.id,
+                INVALID_PARAMS,
+                format!("Missing required argument '{}' for prompt {}", arg.name, name),
+            );
+        }
+
+        match prompts::get(tools_registry, name, &args).await {
+            Ok(Some(messages)) => JsonRpcResponse::success(
+                request.id,
+                json!({ "description": prompt.description, "messages": messages }),
+            ),
+            Ok(None) => JsonRpcResponse::error(request.id, INVALID_PARAMS, format!("Unknown prompt: {}", name)),
+            Err(e) => JsonRpcResponse::error(
+                request.id,
+                INTERNAL_ERROR,
+                format!("Failed to build prompt {}: {}", name, e),
+            ),
+        }
+    }
+
+    /// `notifications/cancelled` for a request that already finished, or one the transport
+    /// didn't pass to `cancel`
+    fn handle_cancelled(&self, request: JsonRpcRequest) -> JsonRpcResponse {
+        if let Some(id) = request.cancelled_request_id() {
+            self.cancel(id);
+        }
+        JsonRpcResponse {
+            jsonrpc: "2.0".to_string(),
+            id: None,
+            result: None,
+            error: None,
+        }
+    }
+
     fn handle_initialized(&self, _request: JsonRpcRequest) -> JsonRpcResponse {
         debug!("Received initialized notification");
         JsonRpcResponse {
@@ -267,13 +461,53 @@ impl Server {
     }
 }
 
+/// Send `notifications/resources/updated` for each of `uris` the client subscribed to
+fn notify_subscribed(
+    notifier: &std::sync::OnceLock<Notifier>,
+    subscriptions: &std::sync::Mutex<std::collections::HashSet<String>>,
+    uris: &[&str],
+) {
+    let Some(notifier) = notifier.get() else {
+        return;
+    };
+    let subscriptions = subscriptions.lock().unwrap_or_else(|e| e.into_inner());
+    for uri in uris {
+        if subscriptions.contains(*uri) {
+            let _ = notifier.send(JsonRpcNotification::resource_updated(uri));
+        }
+    }
+}
+
 impl Handler for Server {
+    fn set_notifier(&self, notifier: Notifier) {
+        let _ = self.notifier.set(notifier);
+    }
+
+    fn metrics(&self) -> Option<String> {
+        Some(self.tools.get()?.context().metrics.to_prometheus())
+    }
+
+    fn cancel(&self, request_id: &Value) {
+        let in_flight = self.in_flight.lock().unwrap_or_else(|e| e.into_inner());
+        if let Some(cancelled) = in_flight.get(&request_id.to_string()) {
+            debug!("Cancelling request {}", request_id);
+            cancelled.store(true, std::sync::atomic::Ordering::Relaxed);
+        }
+    }
+
     async fn handle(&self, request: JsonRpcRequest) -> JsonRpcResponse {
         match request.method.as_str() {
             "initialize" => self.handle_initialize(request).await,
             "notifications/initialized" => self.handle_initialized(request),
+            "notifications/cancelled" => self.handle_cancelled(request),
             "tools/list" => self.handle_tools_list(request),
             "tools/call" => self.handle_tool_call(request).await,
+            "resources/list" => self.handle_resources_list(request),
+            "resources/read" => self.handle_resources_read(request).await,
+            "resources/subscribe" => self.handle_resources_subscribe(request, true),
+            "resources/unsubscribe" => self.handle_resources_subscribe(request, false),
+            "prompts/list" => self.handle_prompts_list(request),
+            "prompts/get" => self.handle_prompts_get(request).await,
             _ => {
                 warn!("Unknown method: {}", request.method);
                 JsonRpcResponse::error(
@@ -426,6 +660,116 @@ mod tests {
         assert_eq!(response.error.unwrap().code, METHOD_NOT_FOUND);
     }
 
+    fn request(id: i64, method: &str, params: Value) -> JsonRpcRequest {
+        JsonRpcRequest {
+            jsonrpc: "2.0".to_string(),
+            id: Some(Value::from(id)),
+            method: method.to_string(),
+            params: Some(params),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_resources() {
+        let (server, _temp) = setup_test_server().await;
+        let (notifier, mut notifications) = tokio::sync::mpsc::unbounded_channel();
+        server.set_notifier(notifier);
+
+        let response = server.handle(request(5, "resources/list", json!({}))).await;
+        let result = response.result.unwrap();
+        let listed = result["resources"].as_array().unwrap();
+        let uris: Vec<&str> = listed.iter().filter_map(|r| r["uri"].as_str()).collect();
+        assert_eq!(uris, vec![resources::SESSION_URI, resources::SKILL_URI, resources::STATS_URI]);
+
+        let response = server.handle(request(6, "resources/read", json!({"uri": resources::STATS_URI}))).await;
+        let result = response.result.unwrap();
+        assert_eq!(result["contents"][0]["mimeType"], "application/json");
+        let stats: Value = serde_json::from_str(result["contents"][0]["text"].as_str().unwrap()).unwrap();
+        assert_eq!(stats["symbols"], 0);
+
+        let response = server.handle(request(7, "resources/read", json!({"uri": "codegraph://nope"}))).await;
+        assert_eq!(response.error.unwrap().code, RESOURCE_NOT_FOUND);
+
+        // Subscribed resources are announced after tool calls that change them
+        let response = server.handle(request(8, "resources/subscribe", json!({"uri": resources::SESSION_URI}))).await;
+        assert!(response.error.is_none());
+        let call = json!({"name": "start_session", "arguments": {"task": "Add resources"}});
+        server.handle(request(9, "tools/call", call)).await;
+        let notification = notifications.try_recv().unwrap();
+        assert_eq!(notification.method, "notifications/resources/updated");
+        assert_eq!(notification.params.unwrap()["uri"], resources::SESSION_URI);
+
+        let response = server.handle(request(10, "resources/read", json!({"uri": resources::SESSION_URI}))).await;
+        let text = response.result.unwrap()["contents"][0]["text"].as_str().unwrap().to_string();
+        assert!(text.contains("\"task\": \"Add resources\""), "{}", text);
+
+        server.handle(request(11, "resources/unsubscribe", json!({"uri": resources::SESSION_URI}))).await;
+        let call = json!({"name": "add_decision", "arguments": {"what": "Use subscriptions", "why": "No polling"}});
+        let response = server.handle(request(12, "tools/call", call)).await;
+        assert!(response.result.unwrap().get("isError").is_none());
+        assert!(notifications.try_recv().is_err());
+    }
+
+    #[tokio::test]
+    async fn test_watcher_reindex_notifies_stats_subscribers() {
+        let (server, temp) = setup_test_server().await;
+        let (notifier, mut notifications) = tokio::sync::mpsc::unbounded_channel();
+        server.set_notifier(notifier);
+        server.handle(request(14, "resources/subscribe", json!({"uri": resources::STATS_URI}))).await;
+
+        std::fs::write(temp.path().join("lib.rs"), "fn added() {}\n").unwrap();
+        let notification = tokio::time::timeout(std::time::Duration::from_secs(10), notifications.recv())
+            .await
+            .expect("no notification after the watcher reindexed")
+            .unwrap();
+        assert_eq!(notification.method, "notifications/resources/updated");
+        assert_eq!(notification.params.unwrap()["uri"], resources::STATS_URI);
+    }
+
+    #[tokio::test]
+    async fn test_prompts() {
+        let (server, _temp) = setup_test_server().await;
+
+        let response = server.handle(request(13, "prompts/list", json!({}))).await;
+        let result = response.result.unwrap();
+        let listed = result["prompts"].as_array().unwrap();
+        let names: Vec<&str> = listed.iter().filter_map(|p| p["name"].as_str()).collect();
+        assert_eq!(names, vec!["restore_context", "reflect_on_attempt", "plan_change"]);
+        assert_eq!(result["prompts"][2]["arguments"][0]["required"], true);
+
+        let get = |id, name: &str, arguments: Value| {
+            request(id, "prompts/get", json!({"name": name, "arguments": arguments}))
+        };
+        let text = |response: JsonRpcResponse| {
+            let result = response.result.unwrap();
+            assert_eq!(result["messages"][0]["role"], "user");
+            result["messages"][0]["content"]["text"].as_str().unwrap().to_string()
+        };
+
+        let response = server.handle(get(14, "restore_context", json!({}))).await;
+        assert!(text(response).contains("call start_session"));
+
+        let call = json!({"name": "start_session", "arguments": {"task": "Add prompts"}});
+        server.handle(request(15, "tools/call", call)).await;
+        let response = server.handle(get(16, "restore_context", json!({}))).await;
+        let restored = text(response);
+        assert!(restored.contains("Add prompts"), "{}", restored);
+        assert!(restored.contains("## Known pitfalls"), "{}", restored);
+
+        let response = server.handle(get(17, "reflect_on_attempt", json!({}))).await;
+        assert!(text(response).contains("record_attempt"));
+
+        let response = server.handle(get(18, "plan_change", json!({"symbol": "parse", "goal": "Stream input"}))).await;
+        let plan = text(response);
+        assert!(plan.starts_with("Plan a change to `parse`: Stream input."), "{}", plan);
+        assert!(plan.contains("## Where it's used"), "{}", plan);
+
+        let response = server.handle(get(19, "plan_change", json!({}))).await;
+        assert_eq!(response.error.unwrap().code, INVALID_PARAMS);
+        let response = server.handle(get(20, "nope", json!({}))).await;
+        assert_eq!(response.error.unwrap().code, INVALID_PARAMS);
+    }
+
     #[tokio::test]
     async fn test_tool_call_search() {
         let (server, _temp) = setup_test_server().await;
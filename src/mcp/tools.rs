@@ -1,11 +1,22 @@
 use anyhow::Result;
 use serde_json::{json, Value};
-use std::sync::{Arc, RwLock};
-use tokio::sync::Mutex;
-
-use super::protocol::{Tool, ToolResult};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use tokio::sync::{Mutex, RwLock};
+
+use super::metrics::Metrics;
+use super::protocol::{Content, Tool, ToolResult};
+use super::query_cache::QueryCache;
+use super::transport::{ProgressReporter, RequestControl};
+use crate::code::cross_language::{ApiConnection, ReviewOutcome, ServerRoute, Verdict};
+use crate::code::indexer::{IndexProgress, IndexStats};
+use crate::code::watcher::FileWatcher;
 use crate::code::{CrossLanguageInferrer, Indexer};
-use crate::compress::{CompressionAnalytics, CompressConfig};
+use crate::compress::{
+    CommandPolicy, CompressionAnalytics, CompressConfig, ExecOptions, GrepOptions, OutlineSymbol, OutputCache,
+    ReadRequest, ShellSession,
+};
 use crate::config::Config;
 use crate::learning::failures::FailureStore;
 use crate::learning::lineage::LineageStore;
@@ -13,6 +24,7 @@ use crate::learning::niches::NicheStore;
 use crate::learning::patterns::PatternStore;
 use crate::session::SessionManager;
 use crate::skill::distill::ManualInstructionStore;
+use crate::store::graph::NodeData;
 use crate::store::{CodeGraph, Store};
 
 /// Shared dependencies available to all tool handlers
@@ -29,12 +41,69 @@ pub struct ToolContext {
     pub manual_instruction_store: Arc<ManualInstructionStore>,
     pub cross_language_inferrer: Arc<CrossLanguageInferrer>,
     pub compression_analytics: Mutex<CompressionAnalytics>,
+    pub output_cache: Mutex<OutputCache>,
+    /// Recent search_symbols/get_neighbors/find_references/get_file_symbols/call_path results
+    pub query_cache: Mutex<QueryCache>,
+    /// Tool call, index, database, and cache counters for server_metrics
+    pub metrics: Metrics,
+    /// Long-lived shells for shell_session, by session name
+    pub shell_sessions: Mutex<HashMap<String, Arc<std::sync::Mutex<ShellSession>>>>,
+}
+
+/// Error message for a category that is neither built-in nor configured
+fn unknown_category(name: &str) -> String {
+    format!(
+        "Unknown category: {}. Add custom categories under [skill] categories in .codegraph/config.toml",
+        name
+    )
 }
 
+/// Tools that add patterns or failures to the learning DB
+const LEARNING_WRITE_TOOLS: &[&str] = &[
+    "extract_pattern",
+    "record_failure",
+    "record_outcome",
+    "reflect",
+    "sync_learnings",
+    "import_learnings",
+];
+
+/// Where export_learnings writes and import_learnings reads by default
+const DEFAULT_BUNDLE_PATH: &str = ".codegraph/learnings-bundle.json";
+
+/// Tools that change the in-memory code graph
+const GRAPH_WRITE_TOOLS: &[&str] = &["index_project", "index_profile", "infer_cross_edges", "reject_connection"];
+
+/// Default cap on the locations find_references lists
+const REFERENCES_LIMIT: usize = 100;
+
+/// Default longest path call_path searches for, in edges
+const CALL_PATH_MAX_LENGTH: u32 = 6;
+
+/// Edges that count as a use of a symbol for find_unreferenced_symbols by default
+const REFERENCE_EDGES: &[&str] = &["calls", "imports"];
+
+/// Example edges detect_cycles shows per dependency between two members
+const CYCLE_EDGE_EXAMPLES: usize = 3;
+
+/// How often bash_compressed sends output snapshots for long-running commands
+const PROGRESS_INTERVAL: std::time::Duration = std::time::Duration::from_secs(10);
+
 pub struct ToolRegistry {
     ctx: Arc<ToolContext>,
 }
 
+/// Parse "120-180" or "42" into a 1-based inclusive line range
+fn parse_line_range(range: &str) -> Option<(usize, usize)> {
+    match range.split_once('-') {
+        Some((start, end)) => Some((start.trim().parse().ok()?, end.trim().parse().ok()?)),
+        None => {
+            let line = range.trim().parse().ok()?;
+            Some((line, line))
+        }
+    }
+}
+
 /// Truncate a signature to a maximum length with ellipsis
 fn truncate_sig(sig: &str, max: usize) -> String {
     if sig.len() <= max {
@@ -44,12 +113,79 @@ fn truncate_sig(sig: &str, max: usize) -> String {
     }
 }
 
+/// Milliseconds with one decimal, for phases that can take well under 1ms
+fn format_ms(duration: std::time::Duration) -> String {
+    format!("{:.1}ms", duration.as_secs_f64() * 1000.0)
+}
+
+/// Time per indexing phase as a table, then the slowest files
+fn format_index_profile(stats: &IndexStats) -> String {
+    let profile = &stats.profile;
+    let mut parse: Vec<_> = profile.parse.iter().collect();
+    parse.sort_by(|a, b| b.1.cmp(a.1).then_with(|| a.0.cmp(b.0)));
+    let parse_total: std::time::Duration = parse.iter().map(|(_, elapsed)| **elapsed).sum();
+
+    let mut rows = vec![("walk".to_string(), profile.walk), ("hash".to_string(), profile.hash)];
+    rows.push(("parse".to_string(), parse_total));
+    rows.extend(parse.iter().map(|(language, elapsed)| (format!("  {}", language), **elapsed)));
+    rows.push(("db writes".to_string(), profile.db_writes));
+    rows.push(("resolution".to_string(), profile.resolution));
+
+    let total = std::time::Duration::from_millis(stats.duration_ms.max(1));
+    let mut output = format!(
+        "Indexed {} files ({} new/changed, {} skipped, {} removed) in {}ms\n\n",
+        stats.files_scanned, stats.files_indexed, stats.files_skipped, stats.files_removed, stats.duration_ms
+    );
+    if stats.cancelled {
+        output.insert_str(0, "Cancelled before finishing; timings cover the partial run.\n");
+    }
+    for (phase, elapsed) in rows {
+        let share = elapsed.as_secs_f64() / total.as_secs_f64() * 100.0;
+        output.push_str(&format!("{:<14} {:>10} {:>5.1}%\n", phase, format_ms(elapsed), share));
+    }
+
+    if !profile.slowest_files.is_empty() {
+        output.push_str("\nSlowest files:\n");
+        for (path, elapsed) in &profile.slowest_files {
+            output.push_str(&format!("{:>10}  {}\n", format_ms(*elapsed), path));
+        }
+    }
+    output.trim_end().to_string()
+}
+
 impl ToolRegistry {
     pub fn new(ctx: Arc<ToolContext>) -> Self {
         Self { ctx }
     }
 
+    pub fn context(&self) -> &Arc<ToolContext> {
+        &self.ctx
+    }
+
+    /// Re-index files as they change. Each batch counts as an incremental index run and
+    /// clears the query cache, the same as `index_project`, then calls `on_reindex`.
+    pub fn watch(&self, on_reindex: impl Fn() + Send + Sync + 'static) -> Result<FileWatcher> {
+        let ctx = self.ctx.clone();
+        let on_reindex = Arc::new(on_reindex);
+        self.ctx.indexer.watch(self.ctx.graph.clone(), move |stats| {
+            let (ctx, on_reindex) = (ctx.clone(), on_reindex.clone());
+            async move {
+                let metrics = &ctx.metrics;
+                metrics.record_index(false, std::time::Duration::from_millis(stats.duration_ms));
+                metrics.record_db("index_writes", stats.profile.db_writes);
+                metrics.record_db("resolution", stats.profile.resolution);
+                ctx.query_cache.lock().await.invalidate();
+                on_reindex();
+            }
+        })
+    }
+
     pub fn list(&self) -> Vec<Tool> {
+        let categories = self.category_names();
+        let settings = self.ctx.config.settings();
+        let defaults = &settings.tools;
+        let learning = &settings.learning;
+
         vec![
             // Code Graph tools
             Tool {
@@ -62,6 +198,16 @@ impl ToolRegistry {
                     }
                 }),
             },
+            Tool {
+                name: "index_profile".into(),
+                description: "Index the project and report time per phase (walk, hash, parse per language, DB writes, resolution) and the slowest files. Use to find what to exclude when indexing is slow.".into(),
+                input_schema: json!({
+                    "type": "object",
+                    "properties": {
+                        "full": { "type": "boolean", "default": true, "description": "Profile a full rebuild; false times only changed files" }
+                    }
+                }),
+            },
             Tool {
                 name: "search_symbols".into(),
                 description: "Find symbols by name. Returns signatures and locations. Start here before reading files.".into(),
@@ -71,12 +217,26 @@ impl ToolRegistry {
                         "query": { "type": "string", "description": "Symbol name (partial match)" },
                         "kind": { "type": "string", "enum": ["function", "method", "class", "struct", "interface", "trait", "type", "variable", "const", "static", "module", "enum", "impl"] },
                         "file_pattern": { "type": "string", "description": "Filter by file path substring" },
-                        "limit": { "type": "integer", "default": 10, "maximum": 50 },
+                        "limit": { "type": "integer", "default": defaults.search_limit, "maximum": 50 },
                         "compact": { "type": "boolean", "default": true, "description": "Compact output (no signatures/IDs)" }
                     },
                     "required": ["query"]
                 }),
             },
+            Tool {
+                name: "search_code".into(),
+                description: "Full-text search over symbol names, signatures, docstrings, and bodies, ranked by relevance. Use when you know what code does or contains but not what it's called.".into(),
+                input_schema: json!({
+                    "type": "object",
+                    "properties": {
+                        "query": { "type": "string", "description": "Words that must all appear (e.g. \"retry backoff\", \"config.reload\")" },
+                        "kind": { "type": "string", "enum": ["function", "method", "class", "struct", "interface", "trait", "type", "variable", "const", "static", "module", "enum", "impl"] },
+                        "file_pattern": { "type": "string", "description": "Filter by file path substring" },
+                        "limit": { "type": "integer", "default": defaults.search_limit, "maximum": 50 }
+                    },
+                    "required": ["query"]
+                }),
+            },
             Tool {
                 name: "get_file_symbols".into(),
                 description: "List all symbols defined in a file with signatures. Use before reading full file to understand structure.".into(),
@@ -97,7 +257,7 @@ impl ToolRegistry {
                     "type": "object",
                     "properties": {
                         "id": { "type": "string", "description": "Symbol ID from search_symbols" },
-                        "depth": { "type": "integer", "default": 1, "minimum": 1, "maximum": 3 },
+                        "depth": { "type": "integer", "default": defaults.neighbor_depth, "minimum": 1, "maximum": 3 },
                         "direction": { "type": "string", "enum": ["outgoing", "incoming", "both"], "default": "both" },
                         "edge_types": { "type": "array", "items": { "type": "string" }, "description": "Filter by edge type: calls, imports, inherits, etc." },
                         "compact": { "type": "boolean", "default": true, "description": "Compact output (no IDs, short edge paths)" }
@@ -105,19 +265,153 @@ impl ToolRegistry {
                     "required": ["id"]
                 }),
             },
+            Tool {
+                name: "find_references".into(),
+                description: "Find every call site, import, and type usage of a symbol, with file:line locations. Use before renaming or changing a signature to see exactly which lines to edit.".into(),
+                input_schema: json!({
+                    "type": "object",
+                    "properties": {
+                        "symbol": { "type": "string", "description": "Symbol ID from search_symbols, or a bare name to cover every definition with that name" },
+                        "edge_types": { "type": "array", "items": { "type": "string" }, "description": "Filter by reference type: calls, imports, inherits, implements, uses_type" },
+                        "limit": { "type": "integer", "default": REFERENCES_LIMIT, "maximum": 500 }
+                    },
+                    "required": ["symbol"]
+                }),
+            },
+            Tool {
+                name: "get_symbol_source".into(),
+                description: "Get the source of a symbol with line numbers. Use after search_symbols instead of reading the whole file.".into(),
+                input_schema: json!({
+                    "type": "object",
+                    "properties": {
+                        "id": { "type": "string", "description": "Symbol ID from search_symbols" },
+                        "context": { "type": "integer", "default": 0, "maximum": 50, "description": "Lines to include before and after the symbol" }
+                    },
+                    "required": ["id"]
+                }),
+            },
+            Tool {
+                name: "call_path".into(),
+                description: "Find the shortest paths from one symbol to another (e.g. how main reaches a store write). Use to trace how code gets from an entry point to an implementation.".into(),
+                input_schema: json!({
+                    "type": "object",
+                    "properties": {
+                        "from": { "type": "string", "description": "Symbol ID where the path starts" },
+                        "to": { "type": "string", "description": "Symbol ID where the path ends" },
+                        "edge_types": { "type": "array", "items": { "type": "string" }, "description": "Follow only these edge types (default: all), e.g. [\"calls\"]" },
+                        "max_length": { "type": "integer", "default": CALL_PATH_MAX_LENGTH, "minimum": 1, "maximum": 12, "description": "Longest path to look for, in edges" },
+                        "limit": { "type": "integer", "default": 5, "maximum": 20, "description": "Maximum number of equally short paths" }
+                    },
+                    "required": ["from", "to"]
+                }),
+            },
+            Tool {
+                name: "find_unreferenced_symbols".into(),
+                description: "List public symbols nothing in the project calls or imports: candidates for dead code cleanup. Dynamic dispatch, reflection, and external callers aren't visible, so confirm before deleting.".into(),
+                input_schema: json!({
+                    "type": "object",
+                    "properties": {
+                        "kind": { "type": "string", "enum": ["function", "method", "class", "struct", "interface", "trait", "type", "variable", "const", "static", "module", "enum"] },
+                        "path": { "type": "string", "description": "Only files matching this glob (e.g. \"src/**/*.py\")" },
+                        "edge_types": { "type": "array", "items": { "type": "string" }, "description": "Edge types that count as a reference (default: calls, imports)" },
+                        "include_private": { "type": "boolean", "default": false, "description": "Also list private symbols" },
+                        "limit": { "type": "integer", "default": 50, "maximum": 500 }
+                    }
+                }),
+            },
+            Tool {
+                name: "detect_cycles".into(),
+                description: "Find dependency cycles between files or directories, with the calls/imports that form them. Use to spot architectural tangles before refactoring.".into(),
+                input_schema: json!({
+                    "type": "object",
+                    "properties": {
+                        "granularity": { "type": "string", "enum": ["file", "module"], "default": "file", "description": "Group symbols by file, or by directory (module)" },
+                        "edge_types": { "type": "array", "items": { "type": "string" }, "description": "Edge types that count as a dependency (default: calls, imports, inherits, implements, uses_type)" },
+                        "limit": { "type": "integer", "default": 10, "maximum": 100, "description": "Maximum number of cycles" }
+                    }
+                }),
+            },
+            Tool {
+                name: "export_graph".into(),
+                description: "Export the code graph (or a filtered part of it) as JSON, DOT for Graphviz, or GraphML for Gephi/yEd. Write large graphs to a file with output.".into(),
+                input_schema: json!({
+                    "type": "object",
+                    "properties": {
+                        "format": { "type": "string", "enum": ["json", "dot", "graphml"], "default": "json" },
+                        "path": { "type": "string", "description": "Only symbols in files matching this glob (e.g. \"src/api/**\")" },
+                        "kinds": { "type": "array", "items": { "type": "string" }, "description": "Only nodes of these kinds (e.g. [\"function\", \"class\"])" },
+                        "edge_types": { "type": "array", "items": { "type": "string" }, "description": "Only edges of these types (e.g. [\"calls\"])" },
+                        "include_unresolved": { "type": "boolean", "default": false, "description": "Keep unresolved:: stubs for symbols outside the project" },
+                        "output": { "type": "string", "description": "Write to this project-relative path instead of returning the export" }
+                    }
+                }),
+            },
             // Session tools
             Tool {
                 name: "start_session".into(),
-                description: "Start a new session with a task description.".into(),
+                description: "Start a new session with a task description and make it active. Other sessions are kept; see list_sessions.".into(),
                 input_schema: json!({
                     "type": "object",
                     "properties": {
                         "task": { "type": "string", "description": "What you're trying to accomplish" },
-                        "items": { "type": "array", "items": { "type": "string" }, "description": "Breakdown of subtasks" }
+                        "items": { "type": "array", "items": { "type": "string" }, "description": "Breakdown of subtasks" },
+                        "name": { "type": "string", "description": "Short name to switch back with (letters, digits, - _ .); derived from the task if omitted" }
                     },
                     "required": ["task"]
                 }),
             },
+            Tool {
+                name: "list_sessions".into(),
+                description: "List sessions with their task and progress, marking the active one. Use to find a work stream to resume.".into(),
+                input_schema: json!({
+                    "type": "object",
+                    "properties": {
+                        "include_archived": { "type": "boolean", "default": true }
+                    }
+                }),
+            },
+            Tool {
+                name: "switch_session".into(),
+                description: "Make another session active; session tools then act on it. Reopens archived sessions.".into(),
+                input_schema: json!({
+                    "type": "object",
+                    "properties": {
+                        "name": { "type": "string", "description": "Session name from list_sessions" }
+                    },
+                    "required": ["name"]
+                }),
+            },
+            Tool {
+                name: "session_history".into(),
+                description: "List checkpoints of the active session, taken at start, on each decision, and on each completed item. Use to find a point to roll back to.".into(),
+                input_schema: json!({
+                    "type": "object",
+                    "properties": {
+                        "limit": { "type": "integer", "default": 20 }
+                    }
+                }),
+            },
+            Tool {
+                name: "restore_checkpoint".into(),
+                description: "Roll the active session's items, decisions, and context back to a checkpoint from session_history. The current state is checkpointed first, so this can be undone.".into(),
+                input_schema: json!({
+                    "type": "object",
+                    "properties": {
+                        "checkpoint": { "type": "integer", "description": "Checkpoint number (#N) from session_history" }
+                    },
+                    "required": ["checkpoint"]
+                }),
+            },
+            Tool {
+                name: "archive_session".into(),
+                description: "Archive a finished or abandoned session, keeping its history.".into(),
+                input_schema: json!({
+                    "type": "object",
+                    "properties": {
+                        "name": { "type": "string", "description": "Session to archive (default: the active one)" }
+                    }
+                }),
+            },
             Tool {
                 name: "get_session".into(),
                 description: "Load current session state: task, items, decisions, context.".into(),
@@ -172,7 +466,9 @@ impl ToolRegistry {
                 description: "One-shot full context restoration. Returns task, decisions, working symbols with signatures, related symbols from graph. Call on startup and after compaction.".into(),
                 input_schema: json!({
                     "type": "object",
-                    "properties": {}
+                    "properties": {
+                        "tags": { "type": "array", "items": { "type": "string" }, "description": "Also limit attached instructions to those sharing one of these tags (untagged ones always apply)" }
+                    }
                 }),
             },
             // Learning System - Phase 4
@@ -186,7 +482,8 @@ impl ToolRegistry {
                         "current_file": { "type": "string", "description": "Current file path" },
                         "symbols": { "type": "array", "items": { "type": "string" }, "description": "Relevant symbol names" },
                         "tags": { "type": "array", "items": { "type": "string" }, "description": "Tags (e.g., 'async', 'db')" },
-                        "limit": { "type": "integer", "default": 5, "maximum": 20 }
+                        "limit": { "type": "integer", "default": learning.recall_limit, "maximum": 20 },
+                        "min_confidence": { "type": "number", "minimum": 0.0, "maximum": 1.0, "default": learning.recall_min_confidence, "description": "Hide patterns below this effective confidence" }
                     },
                     "required": ["description"]
                 }),
@@ -216,7 +513,7 @@ impl ToolRegistry {
                         "examples": { "type": "array", "items": { "type": "string" }, "description": "Code examples or descriptions" },
                         "scope_paths": { "type": "array", "items": { "type": "string" }, "description": "Include path patterns (globs)" },
                         "scope_tags": { "type": "array", "items": { "type": "string" }, "description": "Tags for categorization" },
-                        "confidence": { "type": "number", "minimum": 0.0, "maximum": 1.0, "default": 0.7 }
+                        "confidence": { "type": "number", "minimum": 0.0, "maximum": 1.0, "default": learning.pattern_confidence }
                     },
                     "required": ["intent", "examples"]
                 }),
@@ -236,6 +533,18 @@ impl ToolRegistry {
                     "required": ["cause", "avoidance_rule"]
                 }),
             },
+            Tool {
+                name: "consolidate_patterns".into(),
+                description: "Merge near-duplicate patterns into the most trusted one, combining examples and usage counts. With pattern_id, shows what was merged into that pattern instead.".into(),
+                input_schema: json!({
+                    "type": "object",
+                    "properties": {
+                        "threshold": { "type": "number", "minimum": 0.0, "maximum": 1.0, "default": learning.consolidate_threshold, "description": "How similar intents must be to merge" },
+                        "dry_run": { "type": "boolean", "default": false, "description": "Only report what would be merged" },
+                        "pattern_id": { "type": "string", "description": "Show this pattern's merge lineage" }
+                    }
+                }),
+            },
             // Learning System - Phase 5
             Tool {
                 name: "record_attempt".into(),
@@ -291,7 +600,7 @@ impl ToolRegistry {
                     "properties": {
                         "task": { "type": "string", "description": "Task description to search for" },
                         "include_failures": { "type": "boolean", "default": false },
-                        "limit": { "type": "integer", "default": 10, "maximum": 50 }
+                        "limit": { "type": "integer", "default": learning.lineage_limit, "maximum": 50 }
                     },
                     "required": ["task"]
                 }),
@@ -327,8 +636,11 @@ impl ToolRegistry {
                 input_schema: json!({
                     "type": "object",
                     "properties": {
-                        "confidence_threshold": { "type": "number", "minimum": 0.0, "maximum": 1.0, "default": 0.7, "description": "Min confidence for patterns" },
-                        "write_file": { "type": "boolean", "default": true, "description": "Write to .codegraph/SKILL.md" }
+                        "confidence_threshold": { "type": "number", "minimum": 0.0, "maximum": 1.0, "default": learning.distill_confidence_threshold, "description": "Min confidence for patterns" },
+                        "paths": { "type": "array", "items": { "type": "string" }, "description": "Only include knowledge scoped to these files (per-module skill; not written to SKILL.md)" },
+                        "tags": { "type": "array", "items": { "type": "string" }, "description": "Only include knowledge sharing one of these tags; untagged knowledge always applies (not written to SKILL.md)" },
+                        "write_file": { "type": "boolean", "default": true, "description": "Write to .codegraph/SKILL.md" },
+                        "keep_history": { "type": "integer", "default": 5, "description": "Previous versions to keep in .codegraph/skill-history/ (0 disables)" }
                     }
                 }),
             },
@@ -339,8 +651,10 @@ impl ToolRegistry {
                     "type": "object",
                     "properties": {
                         "instruction": { "type": "string", "description": "The instruction text" },
-                        "category": { "type": "string", "enum": ["architecture", "testing", "style", "navigation", "workflow", "tooling", "gotchas"], "description": "Category" },
-                        "reason": { "type": "string", "description": "Why this instruction is needed" }
+                        "category": { "type": "string", "enum": categories, "description": "Category" },
+                        "reason": { "type": "string", "description": "Why this instruction is needed" },
+                        "scope_paths": { "type": "array", "items": { "type": "string" }, "description": "Include path patterns (globs); omit to apply everywhere" },
+                        "scope_tags": { "type": "array", "items": { "type": "string" }, "description": "Tags for categorization" }
                     },
                     "required": ["instruction", "category"]
                 }),
@@ -353,10 +667,58 @@ impl ToolRegistry {
                     "properties": {}
                 }),
             },
+            Tool {
+                name: "update_instruction".into(),
+                description: "Edit a manual instruction. Omitted fields keep their current value.".into(),
+                input_schema: json!({
+                    "type": "object",
+                    "properties": {
+                        "id": { "type": "string", "description": "Instruction ID from get_project_instructions" },
+                        "instruction": { "type": "string", "description": "New instruction text" },
+                        "category": { "type": "string", "enum": categories, "description": "New category" },
+                        "reason": { "type": "string", "description": "New reason" }
+                    },
+                    "required": ["id"]
+                }),
+            },
+            Tool {
+                name: "remove_instruction".into(),
+                description: "Delete an outdated manual instruction.".into(),
+                input_schema: json!({
+                    "type": "object",
+                    "properties": {
+                        "id": { "type": "string", "description": "Instruction ID from get_project_instructions" }
+                    },
+                    "required": ["id"]
+                }),
+            },
+            Tool {
+                name: "pin_instruction".into(),
+                description: "Pin a pattern, failure, or manual instruction to the top of SKILL.md. Pinned items bypass confidence thresholds.".into(),
+                input_schema: json!({
+                    "type": "object",
+                    "properties": {
+                        "id": { "type": "string", "description": "Pattern, failure, or instruction ID" },
+                        "priority": { "type": "string", "enum": ["critical", "high", "normal"], "default": "critical", "description": "Priority (normal unpins)" }
+                    },
+                    "required": ["id"]
+                }),
+            },
+            Tool {
+                name: "import_skill".into(),
+                description: "Import a hand-edited .codegraph/SKILL.md back into manual instructions. Generated lines are left alone.".into(),
+                input_schema: json!({
+                    "type": "object",
+                    "properties": {
+                        "dry_run": { "type": "boolean", "default": false, "description": "Only report what would change" },
+                        "remove_missing": { "type": "boolean", "default": true, "description": "Delete manual instructions no longer in the file" }
+                    }
+                }),
+            },
             // Phase 8: Cross-Language Inference
             Tool {
                 name: "infer_cross_edges".into(),
-                description: "Infer cross-language edges (e.g., frontend API calls to backend routes).".into(),
+                description: "Infer cross-language edges (frontend API calls to backend routes, GraphQL operations to resolvers, gRPC clients to services, message-queue producers to consumers, WebSocket/socket.io/SSE clients to endpoints and socket.io emitters to listeners).".into(),
                 input_schema: json!({
                     "type": "object",
                     "properties": {
@@ -366,41 +728,198 @@ impl ToolRegistry {
             },
             Tool {
                 name: "get_api_connections".into(),
-                description: "Get API connections for a specific file path.".into(),
+                description: "Get API connections for a file path, an endpoint (\"who calls POST /api/orders\"), or a backend handler symbol, with any request/response field mismatches between the client's and the handler's payload types.".into(),
+                input_schema: json!({
+                    "type": "object",
+                    "properties": {
+                        "path": { "type": "string", "description": "Client or server file path" },
+                        "endpoint": { "type": "string", "description": "API path, optionally prefixed by its method: \"POST /api/orders\", \"/api/orders/{id}\"" },
+                        "method": { "type": "string", "description": "HTTP method for endpoint (when not given in it)" },
+                        "symbol": { "type": "string", "description": "Handler symbol ID from search_symbols" }
+                    }
+                }),
+            },
+            Tool {
+                name: "confirm_connection".into(),
+                description: "Confirm an inferred cross-language connection (from get_api_connections). Confirmed connections are locked at 100% confidence and kept across rebuilds. Also lifts an earlier rejection.".into(),
                 input_schema: json!({
                     "type": "object",
                     "properties": {
-                        "path": { "type": "string", "description": "File path" }
+                        "client_file": { "type": "string", "description": "Client (calling/producing) file" },
+                        "server_file": { "type": "string", "description": "Server (handling/consuming) file" },
+                        "api_path": { "type": "string", "description": "Connection path as shown by get_api_connections" }
                     },
-                    "required": ["path"]
+                    "required": ["client_file", "server_file", "api_path"]
+                }),
+            },
+            Tool {
+                name: "reject_connection".into(),
+                description: "Reject a wrong inferred cross-language connection. It is removed and infer_cross_edges won't infer it again.".into(),
+                input_schema: json!({
+                    "type": "object",
+                    "properties": {
+                        "client_file": { "type": "string", "description": "Client (calling/producing) file" },
+                        "server_file": { "type": "string", "description": "Server (handling/consuming) file" },
+                        "api_path": { "type": "string", "description": "Connection path as shown by get_api_connections" }
+                    },
+                    "required": ["client_file", "server_file", "api_path"]
+                }),
+            },
+            Tool {
+                name: "where_is_env_used".into(),
+                description: "Find where an environment variable is defined (.env, docker-compose, Dockerfile, CI yaml) and read in code. Without a name, lists all variables and flags ones read but never defined or defined but never read. Uses the results of infer_cross_edges.".into(),
+                input_schema: json!({
+                    "type": "object",
+                    "properties": {
+                        "name": { "type": "string", "description": "Variable name, e.g. DATABASE_URL" }
+                    }
+                }),
+            },
+            Tool {
+                name: "get_table_usage".into(),
+                description: "Find the code that touches a database table across languages: SQL strings that read or write it, ORM models mapped to it (and code using them), and migrations creating it. Without a table, lists all detected tables. Uses the results of infer_cross_edges.".into(),
+                input_schema: json!({
+                    "type": "object",
+                    "properties": {
+                        "table": { "type": "string", "description": "Table name, e.g. users" }
+                    }
+                }),
+            },
+            Tool {
+                name: "export_openapi".into(),
+                description: "Generate a draft OpenAPI 3 spec (JSON) from detected server routes: paths, methods, path parameters, and handler symbols.".into(),
+                input_schema: json!({
+                    "type": "object",
+                    "properties": {
+                        "title": { "type": "string", "description": "API title (default: project directory name)" },
+                        "output": { "type": "string", "description": "Write the spec to this project-relative path instead of returning it" }
+                    }
                 }),
             },
             // Phase 9: Sync + Persistence
             Tool {
                 name: "sync_learnings".into(),
-                description: "Sync patterns and failures to JSON files in .codegraph/.".into(),
+                description: "Sync patterns and failures to JSON files in .codegraph/, first pulling the team's latest learnings if [learning.remote] is configured.".into(),
                 input_schema: json!({
                     "type": "object",
                     "properties": {
-                        "confidence_threshold": { "type": "number", "minimum": 0.0, "maximum": 1.0, "default": 0.7, "description": "Min effective confidence for patterns" },
+                        "confidence_threshold": { "type": "number", "minimum": 0.0, "maximum": 1.0, "default": learning.sync_confidence_threshold, "description": "Min effective confidence for patterns" },
                         "include_all_critical": { "type": "boolean", "default": true, "description": "Include all critical failures" }
                     }
                 }),
             },
+            Tool {
+                name: "export_learnings".into(),
+                description: "Package patterns, failures, manual instructions, and niches into one versioned JSON bundle that can be committed and imported into another repo with import_learnings.".into(),
+                input_schema: json!({
+                    "type": "object",
+                    "properties": {
+                        "output": { "type": "string", "default": DEFAULT_BUNDLE_PATH, "description": "Project-relative path to write the bundle to" },
+                        "min_confidence": { "type": "number", "minimum": 0.0, "maximum": 1.0, "default": 0.0, "description": "Leave out patterns below this confidence" }
+                    }
+                }),
+            },
+            Tool {
+                name: "import_learnings".into(),
+                description: "Import a bundle from export_learnings. Items get new IDs; ones that duplicate an existing pattern, failure, or instruction are skipped and mapped to it.".into(),
+                input_schema: json!({
+                    "type": "object",
+                    "properties": {
+                        "path": { "type": "string", "default": DEFAULT_BUNDLE_PATH, "description": "Project-relative path of the bundle" },
+                        "dry_run": { "type": "boolean", "default": false, "description": "Report what would be imported without writing anything" }
+                    }
+                }),
+            },
             // RTK-style compression tools
             Tool {
                 name: "bash_compressed".into(),
-                description: "Execute a bash command with RTK-style output compression. Saves 60-90% tokens on git, ls, grep, test output.".into(),
+                description: "Execute a bash command with RTK-style output compression. Saves 60-90% tokens on git, ls, grep, test output, cargo build/clippy diagnostics, linter reports, coverage reports, stack traces. Long-running commands send compressed progress snapshots when the client provides a progress token.".into(),
                 input_schema: json!({
                     "type": "object",
                     "properties": {
                         "command": { "type": "string", "description": "The bash command to execute" },
-                        "max_lines": { "type": "integer", "default": 50, "description": "Max lines before truncating" },
-                        "max_items_per_group": { "type": "integer", "default": 10, "description": "Max items per category" }
+                        "timeout_secs": { "type": "integer", "minimum": 1, "description": "Kill the command and its children after this many seconds (default from [tools] bash_timeout_secs; none if 0)" },
+                        "cwd": { "type": "string", "description": "Working directory, relative to the project root" },
+                        "env": { "type": "object", "additionalProperties": { "type": "string" }, "description": "Extra environment variables" },
+                        "stdin": { "type": "string", "description": "Text passed to the command's stdin" },
+                        "max_lines": { "type": "integer", "description": "Max lines before truncating (default from [compression] in config.toml, 50)" },
+                        "max_items_per_group": { "type": "integer", "description": "Max items per category (default from [compression] in config.toml, 10)" }
+                    },
+                    "required": ["command"]
+                }),
+            },
+            Tool {
+                name: "shell_session".into(),
+                description: "Run a command in a persistent shell that keeps cwd, env, and virtualenv activation between calls. Output is compressed like bash_compressed.".into(),
+                input_schema: json!({
+                    "type": "object",
+                    "properties": {
+                        "command": { "type": "string", "description": "The command to run in the session" },
+                        "name": { "type": "string", "default": "default", "description": "Session name; each name has its own shell" },
+                        "reset": { "type": "boolean", "default": false, "description": "Start a fresh shell before running" },
+                        "timeout_secs": { "type": "integer", "default": defaults.shell_timeout_secs, "minimum": 1, "description": "Kill the shell (resetting the session) after this many seconds" },
+                        "max_lines": { "type": "integer", "description": "Max lines before truncating (default from [compression] in config.toml)" }
                     },
                     "required": ["command"]
                 }),
             },
+            Tool {
+                name: "read_file_compressed".into(),
+                description: "Read a file as its imports and a symbol outline with signatures, plus only the requested symbol bodies or line ranges. Use instead of reading large files whole; small files are returned in full.".into(),
+                input_schema: json!({
+                    "type": "object",
+                    "properties": {
+                        "path": { "type": "string", "description": "File path relative to project root" },
+                        "symbols": { "type": "array", "items": { "type": "string" }, "description": "Symbol names whose bodies to include" },
+                        "lines": { "type": "array", "items": { "type": "string" }, "description": "Line ranges to include, e.g. [\"120-180\", \"42\"]" },
+                        "max_lines": { "type": "integer", "default": defaults.read_max_lines, "description": "Max lines per body or range" }
+                    },
+                    "required": ["path"]
+                }),
+            },
+            Tool {
+                name: "git_context".into(),
+                description: "Summarize the working tree in one call: branch, compressed status, diff against HEAD, and recent commits. Use at session start instead of separate git commands.".into(),
+                input_schema: json!({
+                    "type": "object",
+                    "properties": {
+                        "log_count": { "type": "integer", "default": defaults.git_log_count, "description": "Number of recent commits to include" },
+                        "max_items_per_group": { "type": "integer", "description": "Max files/commits listed per section (default from [compression] in config.toml)" }
+                    }
+                }),
+            },
+            Tool {
+                name: "grep_project".into(),
+                description: "Search project files in-process (respects .gitignore). Returns matches grouped by file with context lines. Prefer over running grep/rg through bash_compressed.".into(),
+                input_schema: json!({
+                    "type": "object",
+                    "properties": {
+                        "pattern": { "type": "string", "description": "Regex to search for" },
+                        "path": { "type": "string", "description": "Directory or file to search, relative to project root (default: whole project)" },
+                        "glob": { "type": "array", "items": { "type": "string" }, "description": "Include globs, e.g. [\"*.rs\"]; prefix with ! to exclude" },
+                        "context": { "type": "integer", "default": defaults.grep_context, "description": "Lines of context around each match" },
+                        "case_insensitive": { "type": "boolean", "default": false },
+                        "fixed_strings": { "type": "boolean", "default": false, "description": "Treat pattern as a literal string" },
+                        "max_lines": { "type": "integer", "description": "Max lines before truncating (default from [compression] in config.toml)" }
+                    },
+                    "required": ["pattern"]
+                }),
+            },
+            Tool {
+                name: "expand_output".into(),
+                description: "Fetch lines hidden by bash_compressed. Pass the id from the compressed header plus line ranges and/or a regex pattern.".into(),
+                input_schema: json!({
+                    "type": "object",
+                    "properties": {
+                        "id": { "type": "string", "description": "Output ID from a bash_compressed header (e.g. out-3)" },
+                        "lines": { "type": "array", "items": { "type": "string" }, "description": "Line ranges of the original output, e.g. [\"120-180\"]" },
+                        "pattern": { "type": "string", "description": "Regex; matching lines are returned with context" },
+                        "context": { "type": "integer", "default": 3, "description": "Lines of context around pattern matches" },
+                        "max_lines": { "type": "integer", "default": 200, "description": "Max lines returned" }
+                    },
+                    "required": ["id"]
+                }),
+            },
             Tool {
                 name: "compression_stats".into(),
                 description: "Get token compression statistics. Shows total savings, by-category breakdown.".into(),
@@ -411,17 +930,145 @@ impl ToolRegistry {
                     }
                 }),
             },
+            Tool {
+                name: "server_metrics".into(),
+                description: "Get runtime metrics: tool call counts and latencies, index runs, database time, and query cache hit rate.".into(),
+                input_schema: json!({
+                    "type": "object",
+                    "properties": {
+                        "format": { "type": "string", "enum": ["text", "json", "prometheus"], "default": "text" },
+                        "reset": { "type": "boolean", "default": false, "description": "Reset counters after returning" }
+                    }
+                }),
+            },
+            Tool {
+                name: "doctor".into(),
+                description: "Diagnose setup problems: config validity, database access and schema version, tree-sitter grammars, cache dir permissions, graph/store consistency, and git. Each problem comes with a suggested fix.".into(),
+                input_schema: json!({
+                    "type": "object",
+                    "properties": {}
+                }),
+            },
+            Tool {
+                name: "validate_config".into(),
+                description: "Check .codegraph/config.toml and ~/.config/codegraph/config.toml without applying them. Reports parse errors, unknown keys, invalid globs and regexes, and conflicting settings, then shows the merged effective configuration.".into(),
+                input_schema: json!({
+                    "type": "object",
+                    "properties": {}
+                }),
+            },
+            Tool {
+                name: "reload_config".into(),
+                description: "Re-read .codegraph/config.toml and ~/.config/codegraph/config.toml and apply them without restarting the server. Edits are also picked up automatically on the next tool call; on a parse error the previous settings are kept.".into(),
+                input_schema: json!({
+                    "type": "object",
+                    "properties": {}
+                }),
+            },
         ]
     }
 
     pub async fn execute(&self, name: &str, args: Value) -> Result<ToolResult> {
+        self.execute_with(name, args, RequestControl::default()).await
+    }
+
+    /// Execute a tool, sending progress notifications if the client asked for them and
+    /// stopping long-running work early once the request is cancelled
+    pub async fn execute_with(&self, name: &str, args: Value, control: RequestControl) -> Result<ToolResult> {
+        // Pick up edits to config.toml before running the tool; reload_config reports its own
+        let reload = match name {
+            "reload_config" => None,
+            _ => self.ctx.config.reload_if_changed(),
+        };
+        let reload_error = match reload {
+            Some(Ok(changed)) => {
+                // Defaults like search_limit feed into cached results
+                if !changed.is_empty() {
+                    self.ctx.query_cache.lock().await.invalidate();
+                }
+                None
+            }
+            Some(Err(e)) => Some(e),
+            None => None,
+        };
+
+        let start = std::time::Instant::now();
+        let result = self.dispatch_cached(name, args, control).await;
+        let ok = result.as_ref().is_ok_and(|result| result.is_error.is_none());
+        self.ctx.metrics.record_tool(name, start.elapsed(), ok);
+        let mut result = result?;
+
+        if let Some(e) = reload_error {
+            tracing::warn!("Config reload failed: {:#}", e);
+            result.content.push(Content::Text {
+                text: format!("⚠️ config.toml changed but was not applied ({:#}); previous settings kept", e),
+            });
+        }
+
+        // Keep SKILL.md from drifting behind the learning DB
+        if LEARNING_WRITE_TOOLS.contains(&name) && result.is_error.is_none() {
+            match self.check_skill_freshness(name == "sync_learnings").await {
+                Ok(Some(note)) => result.content.push(Content::Text { text: note }),
+                Ok(None) => {}
+                Err(e) => tracing::warn!("Skill freshness check failed: {}", e),
+            }
+        }
+
+        Ok(result)
+    }
+
+    /// Serve repeated graph queries from the query cache, and clear it when the graph changes
+    async fn dispatch_cached(
+        &self,
+        name: &str,
+        args: Value,
+        control: RequestControl,
+    ) -> Result<ToolResult> {
+        let Some(key) = QueryCache::key(name, &args) else {
+            let result = self.dispatch(name, args, control).await?;
+            if GRAPH_WRITE_TOOLS.contains(&name) {
+                self.ctx.query_cache.lock().await.invalidate();
+            }
+            return Ok(result);
+        };
+
+        let generation = {
+            let mut cache = self.ctx.query_cache.lock().await;
+            let cached = cache.get(&key);
+            self.ctx.metrics.record_cache(cached.is_some());
+            if let Some(result) = cached {
+                return Ok(result);
+            }
+            cache.generation()
+        };
+        let result = self.dispatch(name, args, control).await?;
+        if result.is_error.is_none() {
+            self.ctx.query_cache.lock().await.insert(key, generation, result.clone());
+        }
+        Ok(result)
+    }
+
+    async fn dispatch(&self, name: &str, args: Value, control: RequestControl) -> Result<ToolResult> {
         match name {
-            "index_project" => self.handle_index_project(args).await,
+            "index_project" => self.handle_index_project(args, &control).await,
+            "index_profile" => self.handle_index_profile(args, &control).await,
             "search_symbols" => self.handle_search_symbols(args).await,
+            "search_code" => self.handle_search_code(args).await,
             "get_file_symbols" => self.handle_get_file_symbols(args).await,
             "get_neighbors" => self.handle_get_neighbors(args).await,
+            "find_references" => self.handle_find_references(args).await,
+            "get_symbol_source" => self.handle_get_symbol_source(args).await,
+            "call_path" => self.handle_call_path(args).await,
+            "find_unreferenced_symbols" => self.handle_find_unreferenced_symbols(args).await,
+            "detect_cycles" => self.handle_detect_cycles(args).await,
+            "export_graph" => self.handle_export_graph(args).await,
             "start_session" => self.handle_start_session(args).await,
             "get_session" => self.handle_get_session(args).await,
+            "list_sessions" => self.handle_list_sessions(args).await,
+            "switch_session" => self.handle_switch_session(args).await,
+            "archive_session" => self.handle_archive_session(args).await,
+            "session_history" => self.handle_session_history(args).await,
+            "restore_checkpoint" => self.handle_restore_checkpoint(args).await,
             "update_task" => self.handle_update_task(args).await,
             "add_decision" => self.handle_add_decision(args).await,
             "set_context" => self.handle_set_context(args).await,
@@ -430,6 +1077,7 @@ impl ToolRegistry {
             "recall_failures" => self.handle_recall_failures(args).await,
             "extract_pattern" => self.handle_extract_pattern(args).await,
             "record_failure" => self.handle_record_failure(args).await,
+            "consolidate_patterns" => self.handle_consolidate_patterns(args).await,
             "record_attempt" => self.handle_record_attempt(args).await,
             "record_outcome" => self.handle_record_outcome(args).await,
             "reflect" => self.handle_reflect(args).await,
@@ -439,30 +1087,48 @@ impl ToolRegistry {
             "distill_project_skill" => self.handle_distill_project_skill(args).await,
             "add_instruction" => self.handle_add_instruction(args).await,
             "get_project_instructions" => self.handle_get_project_instructions(args).await,
+            "update_instruction" => self.handle_update_instruction(args).await,
+            "remove_instruction" => self.handle_remove_instruction(args).await,
+            "pin_instruction" => self.handle_pin_instruction(args).await,
+            "import_skill" => self.handle_import_skill(args).await,
             "infer_cross_edges" => self.handle_infer_cross_edges(args).await,
             "get_api_connections" => self.handle_get_api_connections(args).await,
+            "confirm_connection" => self.handle_review_connection(args, Verdict::Confirmed).await,
+            "reject_connection" => self.handle_review_connection(args, Verdict::Rejected).await,
+            "where_is_env_used" => self.handle_where_is_env_used(args).await,
+            "get_table_usage" => self.handle_get_table_usage(args).await,
+            "export_openapi" => self.handle_export_openapi(args).await,
             "sync_learnings" => self.handle_sync_learnings(args).await,
-            "bash_compressed" => self.handle_bash_compressed(args).await,
+            "export_learnings" => self.handle_export_learnings(args).await,
+            "import_learnings" => self.handle_import_learnings(args).await,
+            "bash_compressed" => self.handle_bash_compressed(args, control.progress).await,
+            "shell_session" => self.handle_shell_session(args).await,
+            "read_file_compressed" => self.handle_read_file_compressed(args).await,
+            "git_context" => self.handle_git_context(args).await,
+            "grep_project" => self.handle_grep_project(args).await,
+            "expand_output" => self.handle_expand_output(args).await,
             "compression_stats" => self.handle_compression_stats(args).await,
+            "server_metrics" => self.handle_server_metrics(args).await,
+            "reload_config" => self.handle_reload_config().await,
+            "validate_config" => self.handle_validate_config().await,
+            "doctor" => self.handle_doctor().await,
             _ => Ok(ToolResult::error(format!("Tool not found: {}", name))),
         }
     }
 
     // === Code Graph Tools ===
 
-    async fn handle_index_project(&self, args: Value) -> Result<ToolResult> {
+    async fn handle_index_project(&self, args: Value, control: &RequestControl) -> Result<ToolResult> {
         let full = args.get("full").and_then(|v| v.as_bool()).unwrap_or(false);
 
-        let stats = if full {
-            self.ctx.indexer.index_full().await?
-        } else {
-            self.ctx.indexer.index_incremental().await?
-        };
+        let stats = self.index(full, control).await?;
 
-        // Rebuild in-memory graph after indexing
-        {
-            let mut graph = self.ctx.graph.write().map_err(|e| anyhow::anyhow!("Graph lock poisoned: {}", e))?;
-            graph.rebuild_from_store(&self.ctx.store).await?;
+        if stats.cancelled {
+            return Ok(ToolResult::text(format!(
+                "Cancelled after writing {} new/changed files of {} scanned ({} symbols, {} edges). \
+                 Run index_project again to pick up the rest.\n({}ms)",
+                stats.files_indexed, stats.files_scanned, stats.symbols_found, stats.edges_found, stats.duration_ms
+            )));
         }
 
         let mut output = format!(
@@ -487,6 +1153,46 @@ impl ToolRegistry {
         Ok(ToolResult::text(output))
     }
 
+    async fn handle_index_profile(&self, args: Value, control: &RequestControl) -> Result<ToolResult> {
+        let full = args.get("full").and_then(|v| v.as_bool()).unwrap_or(true);
+        let stats = self.index(full, control).await?;
+        Ok(ToolResult::text(format_index_profile(&stats)))
+    }
+
+    /// Index the project and bring the in-memory graph up to date. Progress goes to the client
+    /// if it asked for it; a cancelled run still applies the files it wrote.
+    async fn index(&self, full: bool, control: &RequestControl) -> Result<IndexStats> {
+        let report = |progress: &IndexProgress| {
+            if let Some(reporter) = &control.progress {
+                reporter.report(&progress.message());
+            }
+        };
+        let stats = self.ctx.indexer.index_with(full, &report, &control.cancelled).await?;
+
+        let metrics = &self.ctx.metrics;
+        metrics.record_index(full, std::time::Duration::from_millis(stats.duration_ms));
+        metrics.record_db("index_writes", stats.profile.db_writes);
+        metrics.record_db("resolution", stats.profile.resolution);
+
+        // Update the in-memory graph: reload after a full index, otherwise patch in the changed files.
+        // A reload happens outside the lock so searches keep using the old graph until the swap.
+        if full {
+            self.reload_graph().await?;
+        } else {
+            self.ctx.graph.write().await.apply(&stats.changes);
+        }
+        Ok(stats)
+    }
+
+    /// Load the graph from the store and swap it in
+    async fn reload_graph(&self) -> Result<()> {
+        let start = std::time::Instant::now();
+        let graph = CodeGraph::load(&self.ctx.store, &self.ctx.config.settings().graph).await?;
+        self.ctx.metrics.record_db("graph_load", start.elapsed());
+        *self.ctx.graph.write().await = graph;
+        Ok(())
+    }
+
     async fn handle_search_symbols(&self, args: Value) -> Result<ToolResult> {
         let query = args
             .get("query")
@@ -502,10 +1208,10 @@ impl ToolRegistry {
         let limit = args
             .get("limit")
             .and_then(|v| v.as_u64())
-            .unwrap_or(10) as usize;
+            .map_or(self.ctx.config.settings().tools.search_limit, |v| v as usize);
         let compact = args.get("compact").and_then(|v| v.as_bool()).unwrap_or(true);
 
-        let graph = self.ctx.graph.read().map_err(|e| anyhow::anyhow!("Graph lock poisoned: {}", e))?;
+        let graph = CodeGraph::read_ranked(&self.ctx.graph).await;
         let results = graph.search(query, kind, file_pattern, limit);
 
         if results.is_empty() {
@@ -517,12 +1223,13 @@ impl ToolRegistry {
             let name = node.data.get("name").and_then(|v| v.as_str()).unwrap_or("?");
             let file = node.data.get("file").and_then(|v| v.as_str()).unwrap_or("?");
             let line = node.data.get("line_start").and_then(|v| v.as_u64()).unwrap_or(0);
-            let sig = node.data.get("signature").and_then(|v| v.as_str()).unwrap_or("");
             let kind_str = &node.kind;
 
             if compact {
                 output.push_str(&format!("{} ({}:{}) [{}]\n", name, file, line, kind_str));
             } else {
+                let data = graph.node_data(&self.ctx.store, node).await?;
+                let sig = data.get("signature").and_then(|v| v.as_str()).unwrap_or("");
                 output.push_str(&format!(
                     "[{}] {} ({}:{})\n  {}\n  id: {}\n\n",
                     kind_str, name, file, line, truncate_sig(sig, 80), node.id
@@ -533,6 +1240,41 @@ impl ToolRegistry {
         Ok(ToolResult::text(output.trim_end()))
     }
 
+    async fn handle_search_code(&self, args: Value) -> Result<ToolResult> {
+        let query = args.get("query").and_then(|v| v.as_str()).unwrap_or("");
+        if query.trim().is_empty() {
+            return Ok(ToolResult::error("Missing required parameter: query"));
+        }
+
+        let kind = args.get("kind").and_then(|v| v.as_str());
+        let file_pattern = args.get("file_pattern").and_then(|v| v.as_str());
+        let limit = args
+            .get("limit")
+            .and_then(|v| v.as_u64())
+            .map_or(self.ctx.config.settings().tools.search_limit, |v| v as usize);
+
+        let matches = self.ctx.store.search_code(query, kind, file_pattern, limit).await?;
+        if matches.is_empty() {
+            // Indexes built before full-text search have symbols but no text for them
+            if !self.ctx.store.has_symbol_text().await? && self.ctx.store.stats().await?.symbols > 0 {
+                return Ok(ToolResult::text(
+                    "The full-text index is empty. Run index_project(full: true) to build it.",
+                ));
+            }
+            return Ok(ToolResult::text(format!("No code found matching '{}'", query)));
+        }
+
+        let mut output = String::new();
+        for m in &matches {
+            let snippet = m.snippet.split_whitespace().collect::<Vec<_>>().join(" ");
+            output.push_str(&format!(
+                "[{}] {} ({}:{})\n  {}\n  id: {}\n\n",
+                m.kind, m.name, m.file, m.line, snippet, m.id
+            ));
+        }
+        Ok(ToolResult::text(output.trim_end()))
+    }
+
     async fn handle_get_file_symbols(&self, args: Value) -> Result<ToolResult> {
         let path = args
             .get("path")
@@ -546,7 +1288,7 @@ impl ToolRegistry {
         let compact = args.get("compact").and_then(|v| v.as_bool()).unwrap_or(true);
         let include_source = args.get("include_source").and_then(|v| v.as_bool()).unwrap_or(false);
 
-        let graph = self.ctx.graph.read().map_err(|e| anyhow::anyhow!("Graph lock poisoned: {}", e))?;
+        let graph = self.ctx.graph.read().await;
         let symbols = graph.file_symbols(path);
 
         if symbols.is_empty() {
@@ -570,11 +1312,12 @@ impl ToolRegistry {
             let name = node.data.get("name").and_then(|v| v.as_str()).unwrap_or("?");
             let line_start = node.data.get("line_start").and_then(|v| v.as_u64()).unwrap_or(0);
             let line_end = node.data.get("line_end").and_then(|v| v.as_u64()).unwrap_or(0);
-            let sig = node.data.get("signature").and_then(|v| v.as_str()).unwrap_or("");
 
             if compact {
                 output.push_str(&format!("L{} [{}] {}\n", line_start, node.kind, name));
             } else {
+                let data = graph.node_data(&self.ctx.store, node).await?;
+                let sig = data.get("signature").and_then(|v| v.as_str()).unwrap_or("");
                 output.push_str(&format!(
                     "L{}-{} [{}] {}\n  {}\n",
                     line_start, line_end, node.kind, name, truncate_sig(sig, 80)
@@ -610,7 +1353,7 @@ impl ToolRegistry {
         let depth = args
             .get("depth")
             .and_then(|v| v.as_u64())
-            .unwrap_or(1) as u32;
+            .map_or(self.ctx.config.settings().tools.neighbor_depth, |v| v as u32);
 
         let direction = match args.get("direction").and_then(|v| v.as_str()) {
             Some("outgoing") => crate::store::Direction::Outgoing,
@@ -633,7 +1376,7 @@ impl ToolRegistry {
 
         let compact = args.get("compact").and_then(|v| v.as_bool()).unwrap_or(true);
 
-        let graph = self.ctx.graph.read().map_err(|e| anyhow::anyhow!("Graph lock poisoned: {}", e))?;
+        let graph = self.ctx.graph.read().await;
         let neighbors = graph.neighbors(id, depth, direction, edge_refs.as_deref());
 
         if neighbors.is_empty() {
@@ -666,6 +1409,397 @@ impl ToolRegistry {
         Ok(ToolResult::text(output.trim_end()))
     }
 
+    async fn handle_find_references(&self, args: Value) -> Result<ToolResult> {
+        let symbol = args.get("symbol").and_then(|v| v.as_str()).unwrap_or("");
+        if symbol.is_empty() {
+            return Ok(ToolResult::error("Missing required parameter: symbol"));
+        }
+        let edge_types: Option<Vec<&str>> = args
+            .get("edge_types")
+            .and_then(|v| v.as_array())
+            .map(|arr| arr.iter().filter_map(|v| v.as_str()).collect());
+        let limit = args.get("limit").and_then(|v| v.as_u64()).map_or(REFERENCES_LIMIT, |v| v as usize);
+
+        let graph = self.ctx.graph.read().await;
+        let targets = match graph.get_node(symbol) {
+            Some(node) => vec![node],
+            None => graph.nodes_named(symbol),
+        };
+        if targets.is_empty() {
+            return Ok(ToolResult::text(format!(
+                "No symbol found matching '{}'. Use search_symbols to find its ID.",
+                symbol
+            )));
+        }
+
+        let mut output = String::new();
+        let mut total = 0;
+        for target in targets {
+            // A slim graph keeps no edge data, so reference locations come from the store
+            let stored: HashMap<(String, String), Value> = if graph.is_slim() {
+                let edges = self.ctx.store.get_edges_to(&target.id).await?;
+                edges.into_iter().filter_map(|e| Some(((e.source, e.kind), e.data?))).collect()
+            } else {
+                HashMap::new()
+            };
+
+            let mut references: Vec<(String, u64, &str, &str)> = graph
+                .incoming(&target.id)
+                .into_iter()
+                .filter(|(_, edge)| edge_types.as_ref().is_none_or(|types| types.contains(&edge.kind.as_str())))
+                .map(|(source, edge)| {
+                    let data = edge.data.as_ref().or_else(|| stored.get(&(source.id.clone(), edge.kind.clone())));
+                    let field = |edge_field: &str, node_field: &str| {
+                        data.and_then(|d| d.get(edge_field)).or_else(|| source.data.get(node_field)).cloned()
+                    };
+                    let file = field("file", "file").and_then(|v| v.as_str().map(String::from));
+                    let line = field("line", "line_start").and_then(|v| v.as_u64()).unwrap_or(0);
+                    let from = if source.kind == "file" {
+                        "(top level)"
+                    } else {
+                        source.data.get("name").and_then(|v| v.as_str()).unwrap_or("?")
+                    };
+                    (file.unwrap_or_else(|| "?".to_string()), line, edge.kind.as_str(), from)
+                })
+                .collect();
+            if references.is_empty() {
+                continue;
+            }
+            references.sort();
+
+            let name = target.data.get("name").and_then(|v| v.as_str()).unwrap_or(&target.id);
+            if target.kind == "unresolved" {
+                output.push_str(&format!("## {} (unresolved, no definition indexed)
+", name));
+            } else {
+                let file = target.data.get("file").and_then(|v| v.as_str()).unwrap_or("?");
+                let line = target.data.get("line_start").and_then(|v| v.as_u64()).unwrap_or(0);
+                output.push_str(&format!("## {} [{}] ({}:{})
+", name, target.kind, file, line));
+            }
+            for (file, line, kind, from) in &references {
+                if total < limit {
+                    output.push_str(&format!("{}:{} {} from {}\n", file, line, kind, from));
+                }
+                total += 1;
+            }
+            output.push('\n');
+        }
+
+        if total == 0 {
+            return Ok(ToolResult::text(format!("No references to '{}'", symbol)));
+        }
+        if total > limit {
+            output.push_str(&format!("... {} more (raise limit to see them)", total - limit));
+        }
+        Ok(ToolResult::text(output.trim_end()))
+    }
+
+    async fn handle_call_path(&self, args: Value) -> Result<ToolResult> {
+        let from = args.get("from").and_then(|v| v.as_str()).unwrap_or("");
+        let to = args.get("to").and_then(|v| v.as_str()).unwrap_or("");
+        if from.is_empty() || to.is_empty() {
+            return Ok(ToolResult::error("Missing required parameters: from, to"));
+        }
+        let edge_types: Option<Vec<&str>> = args
+            .get("edge_types")
+            .and_then(|v| v.as_array())
+            .map(|arr| arr.iter().filter_map(|v| v.as_str()).collect());
+        let max_length = args
+            .get("max_length")
+            .and_then(|v| v.as_u64())
+            .map_or(CALL_PATH_MAX_LENGTH, |v| v.clamp(1, 12) as u32);
+        let limit = args.get("limit").and_then(|v| v.as_u64()).map_or(5, |v| v.clamp(1, 20) as usize);
+
+        let graph = self.ctx.graph.read().await;
+        for id in [from, to] {
+            if graph.get_node(id).is_none() {
+                return Ok(ToolResult::error(format!(
+                    "No symbol with ID '{}'. Use search_symbols to find its ID.",
+                    id
+                )));
+            }
+        }
+
+        let paths = graph.shortest_paths(from, to, max_length, edge_types.as_deref(), limit);
+        if paths.is_empty() {
+            return Ok(ToolResult::text(format!(
+                "No path from '{}' to '{}' within max_length {}",
+                from, to, max_length
+            )));
+        }
+
+        let describe = |node: &crate::store::graph::NodeData| {
+            let name = node.data.get("name").and_then(|v| v.as_str()).unwrap_or(&node.id);
+            match node.data.get("file").and_then(|v| v.as_str()) {
+                Some(file) => {
+                    let line = node.data.get("line_start").and_then(|v| v.as_u64()).unwrap_or(0);
+                    format!("{} ({}:{})", name, file, line)
+                }
+                None => format!("{} [{}]", name, node.kind),
+            }
+        };
+        let hops = paths[0].edges.len();
+        let mut output = format!(
+            "## {} → {} ({} edge{}, {} shortest path{})\n",
+            from,
+            to,
+            hops,
+            if hops == 1 { "" } else { "s" },
+            paths.len(),
+            if paths.len() == 1 { "" } else { "s" }
+        );
+        for (i, path) in paths.iter().enumerate() {
+            output.push_str(&format!("\n{}. {}\n", i + 1, describe(&path.nodes[0])));
+            for (edge, node) in path.edges.iter().zip(&path.nodes[1..]) {
+                output.push_str(&format!("   -{}→ {}\n", edge, describe(node)));
+            }
+        }
+        Ok(ToolResult::text(output.trim_end()))
+    }
+
+    async fn handle_find_unreferenced_symbols(&self, args: Value) -> Result<ToolResult> {
+        let kind = args.get("kind").and_then(|v| v.as_str());
+        let path = match args.get("path").and_then(|v| v.as_str()) {
+            Some(glob) => match glob::Pattern::new(glob) {
+                Ok(pattern) => Some(pattern),
+                Err(e) => return Ok(ToolResult::error(format!("Invalid path glob '{}': {}", glob, e))),
+            },
+            None => None,
+        };
+        let edge_types: Vec<&str> = args
+            .get("edge_types")
+            .and_then(|v| v.as_array())
+            .map_or_else(|| REFERENCE_EDGES.to_vec(), |arr| arr.iter().filter_map(|v| v.as_str()).collect());
+        let include_private = args.get("include_private").and_then(|v| v.as_bool()).unwrap_or(false);
+        let limit = args.get("limit").and_then(|v| v.as_u64()).map_or(50, |v| v as usize);
+
+        let graph = self.ctx.graph.read().await;
+        let symbols: Vec<&crate::store::graph::NodeData> =
+            crate::code::checks::unreferenced_symbols(&graph, &self.ctx.store, Some(&edge_types))
+                .await?
+                .into_iter()
+                .filter(|node| kind.is_none_or(|kind| node.kind == kind))
+                .filter(|node| include_private || crate::code::checks::is_public(node))
+                .filter(|node| {
+                    let file = node.data.get("file").and_then(|v| v.as_str()).unwrap_or("");
+                    path.as_ref().is_none_or(|pattern| pattern.matches(file))
+                })
+                .collect();
+
+        let scope = if include_private { "" } else { " public" };
+        if symbols.is_empty() {
+            return Ok(ToolResult::text(format!(
+                "No unreferenced{} symbols (by {} edges)",
+                scope,
+                edge_types.join("/")
+            )));
+        }
+
+        let mut output = format!(
+            "## {} unreferenced{} symbols (no incoming {} edges)\n\n",
+            symbols.len(),
+            scope,
+            edge_types.join("/")
+        );
+        for node in symbols.iter().take(limit) {
+            let name = node.data.get("name").and_then(|v| v.as_str()).unwrap_or("?");
+            let file = node.data.get("file").and_then(|v| v.as_str()).unwrap_or("?");
+            let line = node.data.get("line_start").and_then(|v| v.as_u64()).unwrap_or(0);
+            output.push_str(&format!("{}:{} [{}] {}\n", file, line, node.kind, name));
+        }
+        if symbols.len() > limit {
+            output.push_str(&format!("... and {} more\n", symbols.len() - limit));
+        }
+        output.push_str("\nCallers through dynamic dispatch, reflection, or other projects aren't in the graph.");
+        Ok(ToolResult::text(output))
+    }
+
+    async fn handle_detect_cycles(&self, args: Value) -> Result<ToolResult> {
+        let granularity = match args.get("granularity").and_then(|v| v.as_str()) {
+            None | Some("file") => crate::store::Granularity::File,
+            Some("module") => crate::store::Granularity::Module,
+            Some(other) => {
+                return Ok(ToolResult::error(format!(
+                    "Unknown granularity '{}'. Use \"file\" or \"module\"",
+                    other
+                )))
+            }
+        };
+        let edge_types: Vec<&str> = args
+            .get("edge_types")
+            .and_then(|v| v.as_array())
+            .map_or_else(
+                || crate::code::checks::DEPENDENCY_EDGES.to_vec(),
+                |arr| arr.iter().filter_map(|v| v.as_str()).collect(),
+            );
+        let limit = args.get("limit").and_then(|v| v.as_u64()).map_or(10, |v| v as usize);
+
+        let graph = self.ctx.graph.read().await;
+        // Same rules as `codegraph check`, reading only the files with calls to check
+        let mut sources: HashMap<String, Option<String>> = HashMap::new();
+        let source_line = |file: &str, line: u64| {
+            let source = sources
+                .entry(file.to_string())
+                .or_insert_with(|| std::fs::read_to_string(self.ctx.config.project_root.join(file)).ok());
+            source.as_ref()?.lines().nth((line as usize).saturating_sub(1)).map(String::from)
+        };
+        let cycles =
+            crate::code::checks::dependency_cycles(&graph, &self.ctx.store, &edge_types, granularity, source_line)
+                .await?;
+        let unit = match granularity {
+            crate::store::Granularity::File => "file",
+            crate::store::Granularity::Module => "module",
+        };
+        if cycles.is_empty() {
+            return Ok(ToolResult::text(format!(
+                "No {}-level cycles through {} edges",
+                unit,
+                edge_types.join("/")
+            )));
+        }
+
+        let name_of = |id: &str| {
+            graph
+                .get_node(id)
+                .and_then(|node| node.data.get("name").and_then(|v| v.as_str()).map(String::from))
+                .unwrap_or_else(|| id.to_string())
+        };
+        let mut output = format!(
+            "## {} {}-level cycle{} through {} edges\n",
+            cycles.len(),
+            unit,
+            if cycles.len() == 1 { "" } else { "s" },
+            edge_types.join("/")
+        );
+        for (i, cycle) in cycles.iter().take(limit).enumerate() {
+            let members = cycle.members.join(" ↔ ");
+            output.push_str(&format!("\n{}. {} ({} {}s)\n", i + 1, members, cycle.members.len(), unit));
+            for dependency in cycle.edges.chunk_by(|a, b| a.from == b.from && a.to == b.to) {
+                let examples: Vec<String> = dependency
+                    .iter()
+                    .take(CYCLE_EDGE_EXAMPLES)
+                    .map(|e| format!("{} -{}→ {}", name_of(&e.source), e.kind, name_of(&e.target)))
+                    .collect();
+                let more = dependency.len().saturating_sub(CYCLE_EDGE_EXAMPLES);
+                output.push_str(&format!(
+                    "   {} → {}: {}{}\n",
+                    dependency[0].from,
+                    dependency[0].to,
+                    examples.join(", "),
+                    if more > 0 { format!(" (+{} more)", more) } else { String::new() }
+                ));
+            }
+        }
+        if cycles.len() > limit {
+            output.push_str(&format!("\n... and {} more cycles\n", cycles.len() - limit));
+        }
+        Ok(ToolResult::text(output.trim_end()))
+    }
+
+    async fn handle_export_graph(&self, args: Value) -> Result<ToolResult> {
+        let format = match args.get("format").and_then(|v| v.as_str()).unwrap_or("json").parse() {
+            Ok(format) => format,
+            Err(e) => return Ok(ToolResult::error(e)),
+        };
+        let path = match args.get("path").and_then(|v| v.as_str()) {
+            Some(glob) => match glob::Pattern::new(glob) {
+                Ok(pattern) => Some(pattern),
+                Err(e) => return Ok(ToolResult::error(format!("Invalid path glob '{}': {}", glob, e))),
+            },
+            None => None,
+        };
+        let strings = |key: &str| -> Vec<String> {
+            args.get(key)
+                .and_then(|v| v.as_array())
+                .map(|arr| arr.iter().filter_map(|v| v.as_str()).map(String::from).collect())
+                .unwrap_or_default()
+        };
+        let filter = crate::store::graph::ExportFilter {
+            path,
+            kinds: strings("kinds"),
+            edge_kinds: strings("edge_types"),
+            include_unresolved: args.get("include_unresolved").and_then(|v| v.as_bool()).unwrap_or(false),
+        };
+        let output = args.get("output").and_then(|v| v.as_str());
+        let output_path = match output {
+            Some(output) => match self.project_path(output) {
+                Some(path) => Some(path),
+                None => return Ok(ToolResult::error(format!("output must be a path inside the project: '{}'", output))),
+            },
+            None => None,
+        };
+
+        let export = CodeGraph::read_ranked(&self.ctx.graph).await.export(&self.ctx.store, format, &filter).await?;
+
+        let (Some(output), Some(path)) = (output, output_path) else {
+            return Ok(ToolResult::text(export.text));
+        };
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(&path, &export.text)?;
+        Ok(ToolResult::text(format!(
+            "✅ Wrote {} nodes and {} edges to {}",
+            export.nodes, export.edges, output
+        )))
+    }
+
+    /// A project-relative path from a tool argument, or `None` if it would leave the project
+    fn project_path(&self, relative: &str) -> Option<PathBuf> {
+        let relative = Path::new(relative);
+        if relative.is_absolute() || relative.components().any(|c| c == std::path::Component::ParentDir) {
+            return None;
+        }
+        Some(self.ctx.config.project_root.join(relative))
+    }
+
+    async fn handle_get_symbol_source(&self, args: Value) -> Result<ToolResult> {
+        let id = args.get("id").and_then(|v| v.as_str()).unwrap_or("");
+        if id.is_empty() {
+            return Ok(ToolResult::error("Missing required parameter: id"));
+        }
+        let context = args.get("context").and_then(|v| v.as_u64()).unwrap_or(0).min(50) as usize;
+
+        let (kind, file, line_start, line_end) = {
+            let graph = self.ctx.graph.read().await;
+            let Some(node) = graph.get_node(id) else {
+                return Ok(ToolResult::error(format!(
+                    "No symbol with ID '{}'. Use search_symbols to find its ID.",
+                    id
+                )));
+            };
+            let line = |field: &str| node.data.get(field).and_then(|v| v.as_u64()).unwrap_or(0) as usize;
+            let file = node.data.get("file").and_then(|v| v.as_str()).unwrap_or("").to_string();
+            (node.kind.clone(), file, line("line_start"), line("line_end"))
+        };
+        if file.is_empty() || line_start == 0 {
+            return Ok(ToolResult::error(format!("'{}' has no source location ({})", id, kind)));
+        }
+
+        let content = match std::fs::read_to_string(self.ctx.config.project_root.join(&file)) {
+            Ok(content) => content,
+            Err(e) => return Ok(ToolResult::error(format!("Failed to read '{}': {}", file, e))),
+        };
+        let lines: Vec<&str> = content.lines().collect();
+        if line_start > lines.len() {
+            return Ok(ToolResult::error(format!(
+                "'{}' is past the end of {} ({} lines). Run index_project to refresh the graph.",
+                id,
+                file,
+                lines.len()
+            )));
+        }
+
+        let first = line_start.saturating_sub(context).max(1);
+        let last = line_end.max(line_start).saturating_add(context).min(lines.len());
+        let mut output = format!("## {} [{}] ({}:{}-{})\n", id, kind, file, line_start, line_end);
+        for (number, line) in lines[first - 1..last].iter().enumerate().map(|(i, line)| (first + i, line)) {
+            output.push_str(&format!("{:>5}  {}\n", number, line));
+        }
+        Ok(ToolResult::text(output.trim_end()))
+    }
+
     // === Session Tools ===
 
     async fn handle_start_session(&self, args: Value) -> Result<ToolResult> {
@@ -688,9 +1822,13 @@ impl ToolRegistry {
             })
             .unwrap_or_default();
 
-        let session = self.ctx.session_manager.start_session(task, &items).await?;
+        let name = args.get("name").and_then(|v| v.as_str());
+        let session = match self.ctx.session_manager.start_session(name, task, &items).await {
+            Ok(session) => session,
+            Err(e) => return Ok(ToolResult::error(e.to_string())),
+        };
 
-        let mut output = format!("Session started: {}\n", session.task);
+        let mut output = format!("Session '{}' started: {}\n", session.name, session.task);
         if !session.items.is_empty() {
             output.push_str("\nItems:\n");
             for item in &session.items {
@@ -706,7 +1844,128 @@ impl ToolRegistry {
             Some(session) => Ok(ToolResult::text(
                 serde_json::to_string_pretty(&session)?,
             )),
-            None => Ok(ToolResult::text("No active session. Use start_session to begin.")),
+            None => Ok(ToolResult::text(
+                "No active session. Use start_session to begin, or list_sessions to resume one.",
+            )),
+        }
+    }
+
+    async fn handle_list_sessions(&self, args: Value) -> Result<ToolResult> {
+        let include_archived = args.get("include_archived").and_then(|v| v.as_bool()).unwrap_or(true);
+        let sessions: Vec<_> = self
+            .ctx
+            .session_manager
+            .list_sessions()
+            .await?
+            .into_iter()
+            .filter(|s| include_archived || !s.archived)
+            .collect();
+        if sessions.is_empty() {
+            return Ok(ToolResult::text("No sessions. Use start_session to begin."));
+        }
+
+        let mut output = String::new();
+        for session in &sessions {
+            let marker = if session.active { "*" } else { " " };
+            let archived = if session.archived { " [archived]" } else { "" };
+            output.push_str(&format!(
+                "{} {}{} — {} ({}/{} done)\n",
+                marker, session.name, archived, session.task, session.completed, session.total
+            ));
+        }
+        Ok(ToolResult::text(output.trim_end()))
+    }
+
+    async fn handle_switch_session(&self, args: Value) -> Result<ToolResult> {
+        let name = args.get("name").and_then(|v| v.as_str()).unwrap_or("");
+        if name.is_empty() {
+            return Ok(ToolResult::error("Missing required parameter: name"));
+        }
+
+        match self.ctx.session_manager.switch_session(name).await {
+            Ok(session) => {
+                let completed = session
+                    .items
+                    .iter()
+                    .filter(|i| i.status == crate::session::TaskStatus::Completed)
+                    .count();
+                Ok(ToolResult::text(format!(
+                    "Switched to session '{}': {} ({}/{} tasks completed)",
+                    session.name,
+                    session.task,
+                    completed,
+                    session.items.len()
+                )))
+            }
+            Err(e) => Ok(ToolResult::error(e.to_string())),
+        }
+    }
+
+    async fn handle_archive_session(&self, args: Value) -> Result<ToolResult> {
+        let name = args.get("name").and_then(|v| v.as_str());
+        let was_active = self.ctx.session_manager.active_name().await?;
+
+        match self.ctx.session_manager.archive_session(name).await {
+            Ok(name) if was_active.as_deref() == Some(name.as_str()) => Ok(ToolResult::text(format!(
+                "Archived session '{}'. No session is active; use start_session or switch_session.",
+                name
+            ))),
+            Ok(name) => Ok(ToolResult::text(format!("Archived session '{}'", name))),
+            Err(e) => Ok(ToolResult::error(e.to_string())),
+        }
+    }
+
+    async fn handle_session_history(&self, args: Value) -> Result<ToolResult> {
+        let limit = args.get("limit").and_then(|v| v.as_u64()).unwrap_or(20) as usize;
+        let history = match self.ctx.session_manager.history().await {
+            Ok(history) => history,
+            Err(e) => return Ok(ToolResult::error(e.to_string())),
+        };
+        if history.is_empty() {
+            return Ok(ToolResult::text("No checkpoints yet"));
+        }
+
+        let mut output = String::new();
+        for checkpoint in history.iter().take(limit) {
+            let session = &checkpoint.session;
+            let completed = session
+                .items
+                .iter()
+                .filter(|i| i.status == crate::session::TaskStatus::Completed)
+                .count();
+            let when = chrono::DateTime::<chrono::Utc>::from_timestamp(checkpoint.timestamp, 0)
+                .map(|dt| dt.format("%Y-%m-%d %H:%M").to_string())
+                .unwrap_or_default();
+            output.push_str(&format!(
+                "#{} {} {} ({}/{} items done, {} decisions)\n",
+                checkpoint.seq,
+                when,
+                checkpoint.reason,
+                completed,
+                session.items.len(),
+                session.decisions.len()
+            ));
+        }
+        if history.len() > limit {
+            output.push_str(&format!("... {} older", history.len() - limit));
+        }
+        Ok(ToolResult::text(output.trim_end()))
+    }
+
+    async fn handle_restore_checkpoint(&self, args: Value) -> Result<ToolResult> {
+        let Some(seq) = args.get("checkpoint").and_then(|v| v.as_u64()) else {
+            return Ok(ToolResult::error("Missing required parameter: checkpoint"));
+        };
+
+        match self.ctx.session_manager.restore_checkpoint(seq).await {
+            Ok(session) => Ok(ToolResult::text(format!(
+                "Restored checkpoint #{}: {} items, {} decisions. Undo with restore_checkpoint on the \
+                 \"before restoring\" checkpoint.",
+                seq,
+                session.items.len(),
+                session.decisions.len()
+            ))),
+            Err(e) => Ok(ToolResult::error(e.to_string())),
         }
     }
 
@@ -772,8 +2031,24 @@ impl ToolRegistry {
         Ok(ToolResult::text("Context updated"))
     }
 
-    async fn handle_smart_context(&self, _args: Value) -> Result<ToolResult> {
-        let result = self.ctx.session_manager.smart_context().await?;
+    async fn handle_smart_context(&self, args: Value) -> Result<ToolResult> {
+        let tags: Vec<String> = args
+            .get("tags")
+            .and_then(|v| v.as_array())
+            .map(|arr| arr.iter().filter_map(|v| v.as_str().map(String::from)).collect())
+            .unwrap_or_default();
+        let mut result = self.ctx.session_manager.smart_context().await?;
+
+        // Attach manual instructions scoped to the files being worked on (and tags asked for)
+        result.instructions = self
+            .ctx
+            .manual_instruction_store
+            .list_relevant(&result.files_modified, &tags)
+            .await?
+            .into_iter()
+            .map(|inst| inst.instruction)
+            .collect();
+
         Ok(ToolResult::text(serde_json::to_string_pretty(&result)?))
     }
 
@@ -800,7 +2075,15 @@ impl ToolRegistry {
             .and_then(|v| v.as_array())
             .map(|arr| arr.iter().filter_map(|v| v.as_str().map(String::from)).collect())
             .unwrap_or_default();
-        let limit = args.get("limit").and_then(|v| v.as_u64()).unwrap_or(5) as usize;
+        let settings = self.ctx.config.settings();
+        let limit = args
+            .get("limit")
+            .and_then(|v| v.as_u64())
+            .map_or(settings.learning.recall_limit, |v| v as usize);
+        let min_confidence = args
+            .get("min_confidence")
+            .and_then(|v| v.as_f64())
+            .map_or(settings.learning.recall_min_confidence, |v| v as f32);
 
         let context = crate::learning::QueryContext {
             description: description.to_string(),
@@ -813,13 +2096,16 @@ impl ToolRegistry {
 
         // Calculate effective confidence for each pattern
         let now = chrono::Utc::now().timestamp();
-        let graph = self.ctx.graph.read().map_err(|e| anyhow::anyhow!("Graph lock poisoned: {}", e))?;
+        let graph = self.ctx.graph.read().await;
 
         patterns.sort_by(|a, b| {
             let eff_a = crate::learning::confidence::effective_confidence(a, Some(&graph), now, 90);
             let eff_b = crate::learning::confidence::effective_confidence(b, Some(&graph), now, 90);
             eff_b.partial_cmp(&eff_a).unwrap_or(std::cmp::Ordering::Equal)
         });
+        patterns.retain(|p| {
+            crate::learning::confidence::effective_confidence(p, Some(&graph), now, 90) >= min_confidence
+        });
 
         if patterns.is_empty() {
             return Ok(ToolResult::text("No matching patterns found. Use extract_pattern to record new patterns."));
@@ -931,7 +2217,10 @@ impl ToolRegistry {
             .and_then(|v| v.as_array())
             .map(|arr| arr.iter().filter_map(|v| v.as_str().map(String::from)).collect())
             .unwrap_or_default();
-        let confidence = args.get("confidence").and_then(|v| v.as_f64()).unwrap_or(0.7) as f32;
+        let confidence = args
+            .get("confidence")
+            .and_then(|v| v.as_f64())
+            .map_or(self.ctx.config.settings().learning.pattern_confidence, |v| v as f32);
 
         let new_pattern = crate::learning::patterns::NewPattern {
             intent: intent.to_string(),
@@ -956,6 +2245,76 @@ impl ToolRegistry {
         )))
     }
 
+    async fn handle_consolidate_patterns(&self, args: Value) -> Result<ToolResult> {
+        if let Some(id) = args.get("pattern_id").and_then(|v| v.as_str()) {
+            let Some(pattern) = self.ctx.pattern_store.get(id).await? else {
+                return Ok(ToolResult::error(format!("Pattern not found: {}", id)));
+            };
+            let history = self.ctx.pattern_store.merge_history(id).await?;
+            if history.is_empty() {
+                return Ok(ToolResult::text(format!("Nothing has been merged into '{}'", pattern.intent)));
+            }
+            let mut output = format!("## {} ({} merged)\n", pattern.intent, history.len());
+            for merge in &history {
+                let date = chrono::DateTime::from_timestamp(merge.merged_at, 0)
+                    .map(|t| t.format("%Y-%m-%d").to_string())
+                    .unwrap_or_default();
+                output.push_str(&format!(
+                    "- {} \"{}\" ({:.0}% similar, {} uses{})\n",
+                    date,
+                    merge.intent,
+                    merge.similarity * 100.0,
+                    merge.usage_count,
+                    if merge.into_id == id { String::new() } else { format!(", via {}", merge.into_id) }
+                ));
+            }
+            return Ok(ToolResult::text(output.trim_end()));
+        }
+
+        let threshold = args
+            .get("threshold")
+            .and_then(|v| v.as_f64())
+            .map_or(self.ctx.config.settings().learning.consolidate_threshold, |v| v as f32);
+        let dry_run = args.get("dry_run").and_then(|v| v.as_bool()).unwrap_or(false);
+
+        let patterns = self.ctx.pattern_store.list_all().await?;
+        let clusters = crate::learning::patterns::find_duplicates(&patterns, threshold);
+        if clusters.is_empty() {
+            return Ok(ToolResult::text(format!(
+                "No duplicate patterns among {} (threshold {:.0}%)",
+                patterns.len(),
+                threshold * 100.0
+            )));
+        }
+
+        let duplicates: usize = clusters.iter().map(|c| c.duplicates.len()).sum();
+        let mut output = format!(
+            "{} {} duplicate patterns into {}:\n",
+            if dry_run { "Would merge" } else { "Merged" },
+            duplicates,
+            clusters.len()
+        );
+        for cluster in &clusters {
+            let kept = if dry_run {
+                cluster.keep.clone()
+            } else {
+                self.ctx.pattern_store.merge(cluster).await?
+            };
+            output.push_str(&format!(
+                "\n## {} ({})\n{} examples, {} uses, confidence {:.0}%\n",
+                kept.intent,
+                kept.id,
+                kept.examples.len(),
+                kept.usage_count,
+                kept.confidence * 100.0
+            ));
+            for (duplicate, similarity) in &cluster.duplicates {
+                output.push_str(&format!("- \"{}\" ({:.0}% similar)\n", duplicate.intent, similarity * 100.0));
+            }
+        }
+        Ok(ToolResult::text(output.trim_end()))
+    }
+
     async fn handle_record_failure(&self, args: Value) -> Result<ToolResult> {
         let cause = args.get("cause").and_then(|v| v.as_str()).unwrap_or("");
         let avoidance_rule = args.get("avoidance_rule").and_then(|v| v.as_str()).unwrap_or("");
@@ -1099,16 +2458,18 @@ impl ToolRegistry {
             scope_tags,
         };
 
+        let validation = self.ctx.config.settings().learning.reflection_validation;
+        let warnings = crate::learning::reflection::validate_reflection(&input, validation)?;
+
         let result = crate::learning::reflection::reflect(
             &input,
             &self.ctx.lineage_store,
             &self.ctx.pattern_store,
             &self.ctx.failure_store,
-            true, // Enable validation
         )
         .await?;
 
-        let output = match result {
+        let mut output = match result {
             crate::learning::reflection::ReflectionResult::PatternCreated(p) => {
                 format!("Pattern created:\n  Intent: {}\n  ID: {}", p.intent, p.id)
             }
@@ -1122,6 +2483,9 @@ impl ToolRegistry {
                 )
             }
         };
+        for warning in warnings {
+            output.push_str(&format!("\n⚠️ {}", warning));
+        }
 
         Ok(ToolResult::text(output))
     }
@@ -1136,7 +2500,10 @@ impl ToolRegistry {
             .get("include_failures")
             .and_then(|v| v.as_bool())
             .unwrap_or(false);
-        let limit = args.get("limit").and_then(|v| v.as_u64()).unwrap_or(10) as usize;
+        let limit = args
+            .get("limit")
+            .and_then(|v| v.as_u64())
+            .map_or(self.ctx.config.settings().learning.lineage_limit, |v| v as usize);
 
         let solutions = self
             .ctx
@@ -1195,7 +2562,7 @@ impl ToolRegistry {
         // Show relevant patterns
         if !patterns.is_empty() {
             output.push_str("## Relevant Patterns\n\n");
-            let graph = self.ctx.graph.read().map_err(|e| anyhow::anyhow!("Graph lock poisoned: {}", e))?;
+            let graph = self.ctx.graph.read().await;
             let now = chrono::Utc::now().timestamp();
             for pattern in patterns.iter().take(3) {
                 let eff_conf = crate::learning::confidence::effective_confidence(pattern, Some(&graph), now, 90);
@@ -1292,35 +2659,66 @@ impl ToolRegistry {
         let confidence_threshold = args
             .get("confidence_threshold")
             .and_then(|v| v.as_f64())
-            .unwrap_or(0.7) as f32;
+            .map_or(self.ctx.config.settings().learning.distill_confidence_threshold, |v| v as f32);
         let write_file = args
             .get("write_file")
             .and_then(|v| v.as_bool())
             .unwrap_or(true);
+        let keep_history = args
+            .get("keep_history")
+            .and_then(|v| v.as_u64())
+            .map(|v| v as usize)
+            .unwrap_or(crate::skill::history::DEFAULT_KEEP);
+        let strings = |key: &str| -> Vec<String> {
+            args.get(key)
+                .and_then(|v| v.as_array())
+                .map(|arr| arr.iter().filter_map(|v| v.as_str().map(String::from)).collect())
+                .unwrap_or_default()
+        };
+        let scope = crate::skill::distill::SkillScope {
+            paths: strings("paths"),
+            tags: strings("tags"),
+        };
 
         let result = crate::skill::distill::distill_project_skill(
             &self.ctx.pattern_store,
             &self.ctx.failure_store,
             &self.ctx.manual_instruction_store,
+            &self.ctx.store,
+            &self.ctx.graph,
             confidence_threshold,
+            &scope,
         )
         .await?;
 
-        let markdown = crate::skill::render::generate_project_skill_md(&result);
+        let files = match crate::skill::render::render_skill_files(
+            &result,
+            &self.ctx.config.codegraph_dir,
+            &self.ctx.config.settings().skill.split_files,
+        ) {
+            Ok(files) => files,
+            Err(e) => return Ok(ToolResult::error(format!("{:#}", e))),
+        };
+        let markdown = &files[0].content;
 
-        if write_file {
-            let skill_path = self.ctx.config.codegraph_dir.join("SKILL.md");
-            std::fs::write(&skill_path, &markdown)?;
+        // A scoped skill is a partial view, so never overwrite the project SKILL.md with it
+        if write_file && scope.is_project() {
+            let diff = self.write_project_skill(&files, keep_history)?;
+            let written: Vec<_> = files
+                .iter()
+                .map(|f| self.ctx.config.codegraph_dir.join(&f.name).display().to_string())
+                .collect();
 
             Ok(ToolResult::text(format!(
-                "Project skill distilled successfully!\n\nInstructions: {} (patterns: {}, failures: {}, conventions: {}, manual: {})\nNavigation hints: {}\nWritten to: {}\n\nPreview:\n{}",
+                "Project skill distilled successfully!\n\nInstructions: {} (patterns: {}, failures: {}, conventions: {}, manual: {})\nNavigation hints: {}\nWritten to: {}\n\n{}\n\nPreview:\n{}",
                 result.instructions.len(),
                 result.instructions.iter().filter(|i| matches!(i.source, crate::skill::categories::InstructionSource::Pattern { .. })).count(),
                 result.instructions.iter().filter(|i| matches!(i.source, crate::skill::categories::InstructionSource::Failure { .. })).count(),
                 result.instructions.iter().filter(|i| matches!(i.source, crate::skill::categories::InstructionSource::Convention { .. })).count(),
                 result.instructions.iter().filter(|i| matches!(i.source, crate::skill::categories::InstructionSource::Manual { .. })).count(),
                 result.navigation_hints.len(),
-                skill_path.display(),
+                written.join(", "),
+                diff,
                 markdown.lines().take(20).collect::<Vec<_>>().join("\n")
             )))
         } else {
@@ -1333,6 +2731,107 @@ impl ToolRegistry {
         }
     }
 
+    /// Write the skill files, archiving and diffing against the previous version.
+    /// Returns a rendered diff covering all files.
+    fn write_project_skill(
+        &self,
+        files: &[crate::skill::render::SkillFile],
+        keep_history: usize,
+    ) -> Result<String> {
+        let dir = &self.ctx.config.codegraph_dir;
+
+        let previous: Vec<String> = files
+            .iter()
+            .filter_map(|f| std::fs::read_to_string(dir.join(&f.name)).ok())
+            .collect();
+        let current: Vec<&str> = files.iter().map(|f| f.content.as_str()).collect();
+
+        // Diff against the previous version and archive it before overwriting
+        let diff = if previous.is_empty() {
+            "First version of SKILL.md.".to_string()
+        } else {
+            let previous = previous.join("\n");
+            let current = current.join("\n");
+            // An unchanged regeneration would only push real versions out of the history
+            if previous != current {
+                crate::skill::history::save_history(dir, &previous, keep_history)?;
+            }
+            crate::skill::history::diff_skill_md(&previous, &current).render()
+        };
+
+        for file in files {
+            std::fs::write(dir.join(&file.name), &file.content)?;
+        }
+
+        Ok(diff)
+    }
+
+    /// Count learnings recorded since SKILL.md was last written, and regenerate it when
+    /// enough have accumulated (or after a sync). Returns a note for the tool response.
+    async fn check_skill_freshness(&self, after_sync: bool) -> Result<Option<String>> {
+        let skill_path = self.ctx.config.codegraph_dir.join("SKILL.md");
+        let last_distilled = std::fs::metadata(&skill_path)
+            .and_then(|m| m.modified())
+            .ok()
+            .and_then(|t| t.duration_since(std::time::UNIX_EPOCH).ok())
+            .map(|d| d.as_secs() as i64)
+            .unwrap_or(0);
+
+        let pending = self.ctx.pattern_store.count_created_since(last_distilled).await?
+            + self.ctx.failure_store.count_created_since(last_distilled).await?;
+        if pending == 0 {
+            return Ok(None);
+        }
+
+        let threshold = self.ctx.config.settings().skill.auto_distill_after;
+        if threshold == 0 || (pending < threshold && !after_sync) {
+            return Ok(Some(format!(
+                "Note: SKILL.md is stale ({} new patterns/failures since last distill). Run distill_project_skill to update it.",
+                pending
+            )));
+        }
+
+        let result = crate::skill::distill::distill_project_skill(
+            &self.ctx.pattern_store,
+            &self.ctx.failure_store,
+            &self.ctx.manual_instruction_store,
+            &self.ctx.store,
+            &self.ctx.graph,
+            self.ctx.config.settings().learning.distill_confidence_threshold,
+            &crate::skill::distill::SkillScope::default(),
+        )
+        .await?;
+        let files = crate::skill::render::render_skill_files(
+            &result,
+            &self.ctx.config.codegraph_dir,
+            &self.ctx.config.settings().skill.split_files,
+        )?;
+        let diff = self.write_project_skill(&files, crate::skill::history::DEFAULT_KEEP)?;
+
+        Ok(Some(format!(
+            "SKILL.md regenerated after {} new patterns/failures.\n{}",
+            pending, diff
+        )))
+    }
+
+    /// Built-in category names followed by the custom ones from config
+    fn category_names(&self) -> Vec<String> {
+        crate::skill::categories::InstructionCategory::BUILTIN
+            .iter()
+            .map(|c| c.to_str().to_string())
+            .chain(self.ctx.config.settings().skill.categories.iter().map(|c| c.to_lowercase()))
+            .collect()
+    }
+
+    /// Parse a category name, accepting custom categories only if configured
+    fn parse_category(&self, name: &str) -> Option<crate::skill::categories::InstructionCategory> {
+        let category = crate::skill::categories::InstructionCategory::from_str(name);
+        if category.is_custom() && !self.category_names().contains(&category.to_str().to_string()) {
+            return None;
+        }
+        Some(category)
+    }
+
     async fn handle_add_instruction(&self, args: Value) -> Result<ToolResult> {
         let instruction = args
             .get("instruction")
@@ -1342,28 +2841,53 @@ impl ToolRegistry {
             return Ok(ToolResult::error("Missing required parameter: instruction"));
         }
 
-        let category = args
-            .get("category")
-            .and_then(|v| v.as_str())
-            .map(crate::skill::categories::InstructionCategory::from_str)
-            .unwrap_or(crate::skill::categories::InstructionCategory::Gotchas);
+        let category = match args.get("category").and_then(|v| v.as_str()) {
+            Some(name) => match self.parse_category(name) {
+                Some(category) => category,
+                None => return Ok(ToolResult::error(unknown_category(name))),
+            },
+            None => crate::skill::categories::InstructionCategory::Gotchas,
+        };
 
         let reason = args.get("reason").and_then(|v| v.as_str());
+        let scope_paths: Vec<String> = args
+            .get("scope_paths")
+            .and_then(|v| v.as_array())
+            .map(|arr| arr.iter().filter_map(|v| v.as_str().map(String::from)).collect())
+            .unwrap_or_default();
+        let scope_tags: Vec<String> = args
+            .get("scope_tags")
+            .and_then(|v| v.as_array())
+            .map(|arr| arr.iter().filter_map(|v| v.as_str().map(String::from)).collect())
+            .unwrap_or_default();
+
+        let scope = crate::learning::Scope {
+            include_paths: scope_paths,
+            exclude_paths: vec![],
+            symbols: vec![],
+            tags: scope_tags,
+        };
 
         let id = self
             .ctx
             .manual_instruction_store
-            .add(instruction, category.clone(), reason)
+            .add(instruction, category.clone(), reason, &scope)
             .await?;
 
-        Ok(ToolResult::text(format!(
+        let mut output = format!(
             "Instruction added:\n  ID: {}\n  Category: {:?}\n  Instruction: {}",
             id, category, instruction
-        )))
+        );
+        if !scope.include_paths.is_empty() {
+            output.push_str(&format!("\n  Scope: {}", scope.include_paths.join(", ")));
+        }
+
+        Ok(ToolResult::text(output))
     }
 
     async fn handle_get_project_instructions(&self, _args: Value) -> Result<ToolResult> {
         let instructions = self.ctx.manual_instruction_store.list_all().await?;
+        let priorities = self.ctx.manual_instruction_store.priorities().await?;
 
         if instructions.is_empty() {
             return Ok(ToolResult::text(
@@ -1377,6 +2901,9 @@ impl ToolRegistry {
             if let Some(scope) = &inst.scope {
                 output.push_str(&format!("**Scope:** {}\n", scope));
             }
+            if let Some(priority) = priorities.get(&inst.id) {
+                output.push_str(&format!("**Pinned:** {}\n", priority.to_str()));
+            }
             if let crate::skill::categories::InstructionSource::Manual { reason } = &inst.source {
                 if let Some(reason) = reason {
                     output.push_str(&format!("**Reason:** {}\n", reason));
@@ -1388,6 +2915,144 @@ impl ToolRegistry {
         Ok(ToolResult::text(output.trim_end()))
     }
 
+    async fn handle_update_instruction(&self, args: Value) -> Result<ToolResult> {
+        let id = args.get("id").and_then(|v| v.as_str()).unwrap_or("");
+        if id.is_empty() {
+            return Ok(ToolResult::error("Missing required parameter: id"));
+        }
+
+        let instruction = args.get("instruction").and_then(|v| v.as_str());
+        let category = match args.get("category").and_then(|v| v.as_str()) {
+            Some(name) => match self.parse_category(name) {
+                Some(category) => Some(category),
+                None => return Ok(ToolResult::error(unknown_category(name))),
+            },
+            None => None,
+        };
+        let reason = args.get("reason").and_then(|v| v.as_str());
+
+        if instruction.is_none() && category.is_none() && reason.is_none() {
+            return Ok(ToolResult::error(
+                "Nothing to update: provide instruction, category, or reason",
+            ));
+        }
+
+        let updated = self
+            .ctx
+            .manual_instruction_store
+            .update(id, instruction, category, reason)
+            .await?;
+
+        if !updated {
+            return Ok(ToolResult::error(format!("Instruction not found: {}", id)));
+        }
+
+        match self.ctx.manual_instruction_store.get(id).await? {
+            Some(inst) => Ok(ToolResult::text(format!(
+                "Instruction updated:\n  ID: {}\n  Category: {:?}\n  Instruction: {}",
+                inst.id, inst.category, inst.instruction
+            ))),
+            None => Ok(ToolResult::text(format!("Instruction updated: {}", id))),
+        }
+    }
+
+    async fn handle_remove_instruction(&self, args: Value) -> Result<ToolResult> {
+        let id = args.get("id").and_then(|v| v.as_str()).unwrap_or("");
+        if id.is_empty() {
+            return Ok(ToolResult::error("Missing required parameter: id"));
+        }
+
+        if self.ctx.manual_instruction_store.delete(id).await? {
+            Ok(ToolResult::text(format!("Instruction removed: {}", id)))
+        } else {
+            Ok(ToolResult::error(format!("Instruction not found: {}", id)))
+        }
+    }
+
+    async fn handle_import_skill(&self, args: Value) -> Result<ToolResult> {
+        let dry_run = args.get("dry_run").and_then(|v| v.as_bool()).unwrap_or(false);
+        let remove_missing = args
+            .get("remove_missing")
+            .and_then(|v| v.as_bool())
+            .unwrap_or(true);
+
+        let dir = &self.ctx.config.codegraph_dir;
+        let skill_path = dir.join(crate::skill::render::SKILL_FILE);
+        let Ok(mut markdown) = std::fs::read_to_string(&skill_path) else {
+            return Ok(ToolResult::error(format!(
+                "No SKILL.md found at {}. Run distill_project_skill first.",
+                skill_path.display()
+            )));
+        };
+
+        // Split-out categories live in their own files
+        for category in &self.ctx.config.settings().skill.split_files {
+            let path = dir.join(format!("{}.md", category.to_uppercase()));
+            if let Ok(content) = std::fs::read_to_string(path) {
+                markdown.push('\n');
+                markdown.push_str(&content);
+            }
+        }
+
+        let parsed = crate::skill::import::parse_skill_md(&markdown);
+        let current = crate::skill::distill::distill_project_skill(
+            &self.ctx.pattern_store,
+            &self.ctx.failure_store,
+            &self.ctx.manual_instruction_store,
+            &self.ctx.store,
+            &self.ctx.graph,
+            self.ctx.config.settings().learning.distill_confidence_threshold,
+            &crate::skill::distill::SkillScope::default(),
+        )
+        .await?;
+
+        let report = crate::skill::import::reconcile_skill(
+            &parsed,
+            &current,
+            &self.ctx.manual_instruction_store,
+            dry_run,
+            remove_missing,
+        )
+        .await?;
+
+        Ok(ToolResult::text(report.render(dry_run)))
+    }
+
+    async fn handle_pin_instruction(&self, args: Value) -> Result<ToolResult> {
+        let id = args.get("id").and_then(|v| v.as_str()).unwrap_or("");
+        if id.is_empty() {
+            return Ok(ToolResult::error("Missing required parameter: id"));
+        }
+
+        let priority = match args.get("priority").and_then(|v| v.as_str()).map(str::parse) {
+            Some(Ok(priority)) => priority,
+            Some(Err(e)) => return Ok(ToolResult::error(e)),
+            None => crate::skill::categories::InstructionPriority::Critical,
+        };
+
+        let exists = self.ctx.manual_instruction_store.get(id).await?.is_some()
+            || self.ctx.pattern_store.get(id).await?.is_some()
+            || self.ctx.failure_store.get(id).await?.is_some();
+        if !exists {
+            return Ok(ToolResult::error(format!("Instruction not found: {}", id)));
+        }
+
+        self.ctx
+            .manual_instruction_store
+            .set_priority(id, priority)
+            .await?;
+
+        if priority.is_pinned() {
+            Ok(ToolResult::text(format!(
+                "Pinned {} with priority {}. Run distill_project_skill to update SKILL.md.",
+                id,
+                priority.to_str()
+            )))
+        } else {
+            Ok(ToolResult::text(format!("Unpinned {}", id)))
+        }
+    }
+
     // === Phase 8: Cross-Language Inference ===
 
     async fn handle_infer_cross_edges(&self, args: Value) -> Result<ToolResult> {
@@ -1398,31 +3063,129 @@ impl ToolRegistry {
 
         let stats = self.ctx.cross_language_inferrer.infer(force_rebuild).await?;
 
-        Ok(ToolResult::text(format!(
-            "Cross-language edges inferred:\n  Client calls found: {}\n  Server routes found: {}\n  Connections made: {}\n  Duration: {}ms",
+        // Pick up the inferred calls_api and topic edges
+        self.reload_graph().await?;
+
+        let mut output = format!(
+            "Cross-language edges inferred:\n  Client calls found: {}\n  Server routes found: {}\n  Topics found: {}\n  Channels found: {}\n  Connections made: {}\n  Duration: {}ms",
             stats.client_calls_found,
             stats.server_routes_found,
+            stats.topics_found,
+            stats.channels_found,
             stats.connections_made,
             stats.duration_ms
-        )))
+        );
+
+        if let Some(report) = &stats.openapi {
+            output.push_str(&format!("\n\nOpenAPI spec {} ({} operations):", report.spec_file, report.operations));
+            if report.undocumented_routes.is_empty() && report.uncalled_operations.is_empty() {
+                output.push_str(" ✅ all routes documented and called");
+            }
+            if !report.undocumented_routes.is_empty() {
+                output.push_str(&format!("\n  ⚠️ Routes not in the spec ({}):", report.undocumented_routes.len()));
+                for route in &report.undocumented_routes {
+                    output.push_str(&format!("\n    - {}", route));
+                }
+            }
+            if !report.uncalled_operations.is_empty() {
+                output.push_str(&format!(
+                    "\n  Spec operations with no client callers ({}):",
+                    report.uncalled_operations.len()
+                ));
+                for operation in &report.uncalled_operations {
+                    output.push_str(&format!("\n    - {}", operation));
+                }
+            }
+        }
+
+        if !stats.payload_mismatches.is_empty() {
+            output.push_str(&format!("\n\n⚠️ Payload mismatches ({} connections):", stats.payload_mismatches.len()));
+            for conn in &stats.payload_mismatches {
+                output.push_str(&format!(
+                    "\n  {} /{} ({} → {})",
+                    conn.method.as_deref().unwrap_or("ANY"),
+                    conn.api_path,
+                    conn.client_file,
+                    conn.server_file
+                ));
+                for warning in &conn.warnings {
+                    output.push_str(&format!("\n    - {}", warning));
+                }
+            }
+        }
+
+        Ok(ToolResult::text(output))
     }
 
     async fn handle_get_api_connections(&self, args: Value) -> Result<ToolResult> {
-        let path = args.get("path").and_then(|v| v.as_str()).unwrap_or("");
-        if path.is_empty() {
-            return Ok(ToolResult::error("Missing required parameter: path"));
-        }
+        let arg = |key: &str| args.get(key).and_then(|v| v.as_str()).map(str::trim).filter(|v| !v.is_empty());
+        let inferrer = &self.ctx.cross_language_inferrer;
+
+        let (title, mut output, connections) = if let Some(symbol) = arg("symbol") {
+            // Routes in the symbol's file that resolve to it as their handler
+            let routes: Vec<ServerRoute> = {
+                let graph = self.ctx.graph.read().await;
+                let Some(file) = graph.get_node(symbol).and_then(|n| n.data.get("file")?.as_str()) else {
+                    return Ok(ToolResult::error(format!("Symbol not found: {}", symbol)));
+                };
+                inferrer
+                    .file_routes(file)
+                    .into_iter()
+                    .filter(|route| {
+                        crate::code::openapi::handler_symbol(&graph, route).is_some_and(|handler| handler.id == symbol)
+                    })
+                    .collect()
+            };
+            if routes.is_empty() {
+                return Ok(ToolResult::text(format!("'{}' doesn't handle any detected routes", symbol)));
+            }
 
-        let connections = self.ctx.cross_language_inferrer.get_api_connections(path).await?;
+            let mut handles = String::from("**Handles:**\n");
+            let mut connections: Vec<ApiConnection> = Vec::new();
+            for route in &routes {
+                handles.push_str(&format!(
+                    "- {} /{} ({}:{})\n",
+                    route.method.as_deref().unwrap_or("ANY"),
+                    route.path,
+                    route.file,
+                    route.line
+                ));
+                for conn in inferrer.get_route_connections(route).await? {
+                    if !connections.iter().any(|c| c.client_file == conn.client_file && c.api_path == conn.api_path) {
+                        connections.push(conn);
+                    }
+                }
+            }
+            handles.push('\n');
+            (format!("handler {}", symbol), handles, connections)
+        } else if let Some(endpoint) = arg("endpoint") {
+            // "POST /api/orders" or "/api/orders" with a separate method
+            let (method, path) = match endpoint.split_once(char::is_whitespace) {
+                Some((method, path)) if method.chars().all(|c| c.is_ascii_alphabetic()) => {
+                    (Some(method.to_uppercase()), path.trim())
+                }
+                _ => (arg("method").map(str::to_uppercase), endpoint),
+            };
+            let connections = inferrer.get_endpoint_connections(path, method.as_deref()).await?;
+            let title = match &method {
+                Some(method) => format!("{} {}", method, path),
+                None => path.to_string(),
+            };
+            (title, String::new(), connections)
+        } else if let Some(path) = arg("path") {
+            (path.to_string(), String::new(), inferrer.get_api_connections(path).await?)
+        } else {
+            return Ok(ToolResult::error("Missing required parameter: path, endpoint, or symbol"));
+        };
 
-        if connections.is_empty() {
-            return Ok(ToolResult::text(format!(
-                "No API connections found for '{}'",
-                path
-            )));
+        if connections.is_empty() && output.is_empty() {
+            return Ok(ToolResult::text(format!("No API connections found for '{}'", title)));
         }
 
-        let mut output = format!("# API Connections for {}\n\n", path);
+        output = format!("# API Connections for {}\n\n{}", title, output);
+        if connections.is_empty() {
+            output.push_str("No client calls found.");
+        }
         for conn in &connections {
             output.push_str(&format!(
                 "## {} → {}\n",
@@ -1432,251 +3195,2170 @@ impl ToolRegistry {
             if let Some(method) = &conn.method {
                 output.push_str(&format!("**Method:** {}\n", method));
             }
-            output.push_str(&format!("**Confidence:** {:.1}%\n\n", conn.confidence * 100.0));
+            if let Some(operation) = &conn.operation {
+                let name = operation.operation_id.as_deref().unwrap_or("(no operationId)");
+                match &operation.summary {
+                    Some(summary) => output.push_str(&format!("**Operation:** {} — {}\n", name, summary)),
+                    None => output.push_str(&format!("**Operation:** {}\n", name)),
+                }
+                if !operation.tags.is_empty() {
+                    output.push_str(&format!("**Tags:** {}\n", operation.tags.join(", ")));
+                }
+            }
+            let confirmed = if conn.confirmed { " (confirmed)" } else { "" };
+            output.push_str(&format!("**Confidence:** {:.1}%{}\n", conn.confidence * 100.0, confirmed));
+            if !conn.warnings.is_empty() {
+                output.push_str("**Warnings:**\n");
+                for warning in &conn.warnings {
+                    output.push_str(&format!("- ⚠️ {}\n", warning));
+                }
+            }
+            output.push('\n');
         }
 
         Ok(ToolResult::text(output.trim_end()))
     }
 
-    // === Phase 9: Sync + Persistence ===
-
-    async fn handle_sync_learnings(&self, args: Value) -> Result<ToolResult> {
-        let confidence_threshold = args
-            .get("confidence_threshold")
-            .and_then(|v| v.as_f64())
-            .unwrap_or(0.7) as f32;
-        let include_all_critical = args
-            .get("include_all_critical")
-            .and_then(|v| v.as_bool())
-            .unwrap_or(true);
-
-        let graph = self.ctx.graph.read().map_err(|e| anyhow::anyhow!("Graph lock poisoned: {}", e))?;
-        let stats = crate::learning::sync::sync_learnings(
-            &self.ctx.pattern_store,
-            &self.ctx.failure_store,
-            &self.ctx.config,
-            Some(&graph),
-            confidence_threshold,
-            include_all_critical,
-        )
-        .await?;
-
-        let mut output = format!(
-            "Learnings synced successfully!\n\nPatterns: {}\nFailures: {}\n\nFiles written:\n",
-            stats.patterns_synced, stats.failures_synced
-        );
-        for file in &stats.files_written {
-            output.push_str(&format!("  - {}\n", file));
+    async fn handle_review_connection(&self, args: Value, verdict: Verdict) -> Result<ToolResult> {
+        let arg = |key: &str| args.get(key).and_then(|v| v.as_str()).unwrap_or("");
+        let (client_file, server_file, api_path) = (arg("client_file"), arg("server_file"), arg("api_path"));
+        if client_file.is_empty() || server_file.is_empty() || api_path.is_empty() {
+            return Ok(ToolResult::error("Missing required parameters: client_file, server_file, api_path"));
         }
-        output.push_str(&format!("\nDuration: {}ms", stats.duration_ms));
 
-        Ok(ToolResult::text(output.trim_end()))
+        let outcome = self
+            .ctx
+            .cross_language_inferrer
+            .review_connection(client_file, server_file, api_path, verdict)
+            .await?;
+        let connection = format!("{} → {} ({})", client_file, server_file, api_path);
+        let text = match (outcome, verdict) {
+            (ReviewOutcome::NotFound, _) => {
+                return Ok(ToolResult::error(format!(
+                    "No inferred connection {}. Use get_api_connections to find it.",
+                    connection
+                )));
+            }
+            (ReviewOutcome::Updated, Verdict::Confirmed) => {
+                format!("✅ Confirmed {}, locked at 100% confidence", connection)
+            }
+            (ReviewOutcome::Pending, _) => {
+                format!("✅ Lifted the rejection of {}; infer_cross_edges will restore it as confirmed", connection)
+            }
+            (ReviewOutcome::Updated, Verdict::Rejected) => {
+                // A rejected gRPC connection loses its calls_api edge in the store
+                self.reload_graph().await?;
+                format!("🚫 Rejected {}; infer_cross_edges won't infer it again", connection)
+            }
+        };
+
+        Ok(ToolResult::text(text))
     }
 
-    // === RTK-style Compression Tools ===
+    async fn handle_where_is_env_used(&self, args: Value) -> Result<ToolResult> {
+        let name = args.get("name").and_then(|v| v.as_str()).filter(|n| !n.is_empty());
+        let usages = self.ctx.cross_language_inferrer.env_var_usage(name).await?;
 
-    async fn handle_bash_compressed(&self, args: Value) -> Result<ToolResult> {
-        let command = args.get("command").and_then(|v| v.as_str()).unwrap_or("");
-        if command.is_empty() {
+        let Some(name) = name else {
+            if usages.is_empty() {
+                return Ok(ToolResult::text(
+                    "No environment variables found. Run infer_cross_edges to index them.",
+                ));
+            }
+            let mut output = format!("# Environment variables ({})\n", usages.len());
+            for usage in &usages {
+                let flag = match (usage.definitions.is_empty(), usage.reads.is_empty()) {
+                    (true, _) => " ⚠️ never defined",
+                    (_, true) => " (never read)",
+                    _ => "",
+                };
+                output.push_str(&format!(
+                    "\n- {}: {} definitions, {} reads{}",
+                    usage.name,
+                    usage.definitions.len(),
+                    usage.reads.len(),
+                    flag
+                ));
+            }
+            return Ok(ToolResult::text(output));
+        };
+
+        let Some(usage) = usages.first() else {
+            return Ok(ToolResult::text(format!(
+                "No references to environment variable '{}' found. Run infer_cross_edges to index them.",
+                name
+            )));
+        };
+
+        let mut output = format!("# {}\n\n## Defined in\n", usage.name);
+        if usage.definitions.is_empty() {
+            output.push_str("⚠️ No definition found (.env, docker-compose, Dockerfile, or CI config)\n");
+        }
+        for (file, line) in &usage.definitions {
+            output.push_str(&format!("- {}:{}\n", file, line));
+        }
+        output.push_str("\n## Read in\n");
+        if usage.reads.is_empty() {
+            output.push_str("Not read by any indexed code\n");
+        }
+        for (file, line) in &usage.reads {
+            output.push_str(&format!("- {}:{}\n", file, line));
+        }
+
+        Ok(ToolResult::text(output.trim_end()))
+    }
+
+    async fn handle_get_table_usage(&self, args: Value) -> Result<ToolResult> {
+        let table = args.get("table").and_then(|v| v.as_str()).filter(|t| !t.is_empty());
+        let usages = self.ctx.cross_language_inferrer.table_usage(table).await?;
+
+        let Some(table) = table else {
+            if usages.is_empty() {
+                return Ok(ToolResult::text("No database tables found. Run infer_cross_edges to detect them."));
+            }
+            let mut output = format!("# Database tables ({})\n", usages.len());
+            for usage in &usages {
+                output.push_str(&format!(
+                    "\n- {}: {} reads, {} writes, {} models",
+                    usage.name,
+                    usage.reads.len(),
+                    usage.writes.len(),
+                    usage.models.len()
+                ));
+            }
+            return Ok(ToolResult::text(output));
+        };
+
+        let Some(usage) = usages.first() else {
+            return Ok(ToolResult::text(format!(
+                "No code found touching table '{}'. Run infer_cross_edges to detect table usage.",
+                table
+            )));
+        };
+
+        let mut output = format!("# Table {}\n", usage.name);
+        let sections = [
+            ("Defined in", &usage.definitions),
+            ("ORM models", &usage.models),
+            ("Writes", &usage.writes),
+            ("Reads", &usage.reads),
+        ];
+        for (title, sites) in sections {
+            if sites.is_empty() {
+                continue;
+            }
+            output.push_str(&format!("\n## {} ({})\n", title, sites.len()));
+            for site in sites {
+                match site.symbol.strip_prefix("file::") {
+                    Some(_) => output.push_str(&format!("- {}:{}\n", site.file, site.line)),
+                    None => output.push_str(&format!("- {} ({}:{})\n", site.symbol, site.file, site.line)),
+                }
+            }
+        }
+        if !usage.model_references.is_empty() {
+            output.push_str(&format!("\n## Using the models ({})\n", usage.model_references.len()));
+            for symbol in &usage.model_references {
+                output.push_str(&format!("- {}\n", symbol));
+            }
+        }
+
+        Ok(ToolResult::text(output.trim_end()))
+    }
+
+    async fn handle_export_openapi(&self, args: Value) -> Result<ToolResult> {
+        let routes = self.ctx.cross_language_inferrer.detect_routes().await?;
+        if routes.is_empty() {
+            return Ok(ToolResult::text(
+                "No server routes detected. Run index_project if route files were recently added.",
+            ));
+        }
+
+        let project_root = &self.ctx.config.project_root;
+        let title = args
+            .get("title")
+            .and_then(|v| v.as_str())
+            .map(String::from)
+            .or_else(|| project_root.file_name().map(|n| n.to_string_lossy().to_string()))
+            .unwrap_or_else(|| "API".to_string());
+
+        let spec = {
+            let graph = self.ctx.graph.read().await;
+            // Owned copies with full data, since a slim graph leaves out the docstrings used as summaries
+            let mut handlers = Vec::with_capacity(routes.len());
+            for route in &routes {
+                let handler = match crate::code::openapi::handler_symbol(&graph, route) {
+                    Some(node) => Some(NodeData {
+                        data: graph.node_data(&self.ctx.store, node).await?,
+                        ..node.clone()
+                    }),
+                    None => None,
+                };
+                handlers.push(handler);
+            }
+            let routes: Vec<_> = routes.into_iter().zip(handlers.iter().map(Option::as_ref)).collect();
+            crate::code::openapi::draft_spec(&title, &routes)
+        };
+        let text = serde_json::to_string_pretty(&spec)?;
+        let operations: usize = spec["paths"]
+            .as_object()
+            .map_or(0, |paths| paths.values().filter_map(|item| item.as_object()).map(|item| item.len()).sum());
+
+        let Some(output) = args.get("output").and_then(|v| v.as_str()) else {
+            return Ok(ToolResult::text(text));
+        };
+        let Some(path) = self.project_path(output) else {
+            return Ok(ToolResult::error(format!("output must be a path inside the project: '{}'", output)));
+        };
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(&path, format!("{}\n", text))?;
+
+        Ok(ToolResult::text(format!(
+            "✅ Wrote draft OpenAPI spec with {} operations to {}",
+            operations, output
+        )))
+    }
+
+    // === Phase 9: Sync + Persistence ===
+
+    async fn handle_sync_learnings(&self, args: Value) -> Result<ToolResult> {
+        let confidence_threshold = args
+            .get("confidence_threshold")
+            .and_then(|v| v.as_f64())
+            .map_or(self.ctx.config.settings().learning.sync_confidence_threshold, |v| v as f32);
+        let include_all_critical = args
+            .get("include_all_critical")
+            .and_then(|v| v.as_bool())
+            .unwrap_or(true);
+
+        // Pull first, so the files include what teammates recorded since the last sync
+        let remote = match self.ctx.store.sync_learning().await {
+            Ok(Some(frames)) => Some(format!(
+                "Pulled {} frames from {}",
+                frames,
+                self.ctx.config.settings().learning.remote.url
+            )),
+            Ok(None) => None,
+            Err(e) => Some(format!("⚠️ Couldn't pull the team's learnings, using the last synced copy: {:#}", e)),
+        };
+
+        let graph = self.ctx.graph.read().await;
+        let stats = crate::learning::sync::sync_learnings(
+            &self.ctx.pattern_store,
+            &self.ctx.failure_store,
+            &self.ctx.config,
+            Some(&graph),
+            confidence_threshold,
+            include_all_critical,
+        )
+        .await?;
+
+        let mut output = format!(
+            "Learnings synced successfully!\n\nPatterns: {}\nFailures: {}\n\nFiles written:\n",
+            stats.patterns_synced, stats.failures_synced
+        );
+        for file in &stats.files_written {
+            output.push_str(&format!("  - {}\n", file));
+        }
+        output.push_str(&format!("\nDuration: {}ms", stats.duration_ms));
+        if let Some(remote) = remote {
+            output.push_str(&format!("\n{}", remote));
+        }
+
+        Ok(ToolResult::text(output.trim_end()))
+    }
+
+    async fn handle_export_learnings(&self, args: Value) -> Result<ToolResult> {
+        let output = args.get("output").and_then(|v| v.as_str()).unwrap_or(DEFAULT_BUNDLE_PATH);
+        let Some(path) = self.project_path(output) else {
+            return Ok(ToolResult::error(format!("output must be a path inside the project: '{}'", output)));
+        };
+        let min_confidence = args.get("min_confidence").and_then(|v| v.as_f64()).unwrap_or(0.0) as f32;
+
+        let source = self
+            .ctx
+            .config
+            .project_root
+            .file_name()
+            .map_or_else(|| "project".to_string(), |name| name.to_string_lossy().to_string());
+        let db = &self.ctx.store.learning_db;
+        let bundle = crate::learning::bundle::export_bundle(db, &source, min_confidence).await?;
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(&path, serde_json::to_string_pretty(&bundle)?)?;
+
+        Ok(ToolResult::text(format!(
+            "✅ Wrote {} patterns, {} failures, {} instructions, and {} niches to {}\n\n\
+             Commit it and run import_learnings in the other repo to bring them in.",
+            bundle.patterns.len(),
+            bundle.failures.len(),
+            bundle.instructions.len(),
+            bundle.niches.len(),
+            output
+        )))
+    }
+
+    async fn handle_import_learnings(&self, args: Value) -> Result<ToolResult> {
+        let input = args.get("path").and_then(|v| v.as_str()).unwrap_or(DEFAULT_BUNDLE_PATH);
+        let Some(path) = self.project_path(input) else {
+            return Ok(ToolResult::error(format!("path must be inside the project: '{}'", input)));
+        };
+        let dry_run = args.get("dry_run").and_then(|v| v.as_bool()).unwrap_or(false);
+
+        let text = match std::fs::read_to_string(&path) {
+            Ok(text) => text,
+            Err(e) => return Ok(ToolResult::error(format!("Can't read {}: {}", input, e))),
+        };
+        let bundle: crate::learning::bundle::LearningBundle = match serde_json::from_str(&text) {
+            Ok(bundle) => bundle,
+            Err(e) => return Ok(ToolResult::error(format!("{} is not a learning bundle: {}", input, e))),
+        };
+        let threshold = self.ctx.config.settings().learning.consolidate_threshold;
+        let db = &self.ctx.store.learning_db;
+        let stats = match crate::learning::bundle::import_bundle(db, &bundle, threshold, dry_run).await {
+            Ok(stats) => stats,
+            Err(e) => return Ok(ToolResult::error(format!("{:#}", e))),
+        };
+
+        let mut output = format!(
+            "{} {} from {} (exported {})\n",
+            if dry_run { "Would import" } else { "✅ Imported" },
+            input,
+            bundle.source,
+            bundle.exported_at
+        );
+        for (kind, counts) in [
+            ("Patterns", &stats.patterns),
+            ("Failures", &stats.failures),
+            ("Instructions", &stats.instructions),
+            ("Niches", &stats.niches),
+        ] {
+            output.push_str(&format!("\n{}: {} new, {} duplicate", kind, counts.imported, counts.duplicates));
+        }
+        if stats.priorities > 0 {
+            output.push_str(&format!("\nPins: {}", stats.priorities));
+        }
+        let duplicates: Vec<_> = stats.duplicates.iter().filter(|d| d.kind != "niche").collect();
+        if !duplicates.is_empty() {
+            output.push_str("\n\nDuplicates (kept the existing entry):");
+            for duplicate in duplicates {
+                output.push_str(&format!(
+                    "\n  - {} \"{}\" → {}",
+                    duplicate.kind,
+                    duplicate.text,
+                    duplicate.existing_id
+                ));
+            }
+        }
+
+        Ok(ToolResult::text(output))
+    }
+
+    // === RTK-style Compression Tools ===
+
+    async fn handle_bash_compressed(&self, args: Value, progress: Option<ProgressReporter>) -> Result<ToolResult> {
+        let command = args.get("command").and_then(|v| v.as_str()).unwrap_or("");
+        if command.is_empty() {
+            return Ok(ToolResult::error("Missing required parameter: command"));
+        }
+
+        // [compression] profile for this command's category; call parameters win
+        let category = crate::compress::categorize_command(command);
+        let mut config = CompressConfig::for_category(&self.ctx.config.settings().compression, category);
+        if let Some(max_lines) = args.get("max_lines").and_then(|v| v.as_u64()) {
+            config.max_lines = max_lines as usize;
+        }
+        if let Some(max_items) = args.get("max_items_per_group").and_then(|v| v.as_u64()) {
+            config.max_items_per_group = max_items as usize;
+        }
+
+        // The session's working set gets extra detail (e.g. uncovered lines, full diff hunks)
+        if let Ok(Some(session)) = self.ctx.session_manager.get_session().await {
+            config.focus_files = session.context.modified_files;
+            config.focus_symbols = session.context.working_symbols;
+        }
+        config.project_root = Some(self.ctx.config.project_root.clone());
+
+        let settings = self.ctx.config.settings();
+        let bash = &settings.bash;
+        let policy = match CommandPolicy::new(&bash.allow, &bash.deny) {
+            Ok(policy) => policy,
+            Err(e) => return Ok(ToolResult::error(e)),
+        };
+
+        let mut options = ExecOptions {
+            policy: Some(policy),
+            timeout: args
+                .get("timeout_secs")
+                .and_then(|v| v.as_u64())
+                .or(Some(settings.tools.bash_timeout_secs).filter(|&secs| secs > 0))
+                .map(std::time::Duration::from_secs),
+            stdin: args.get("stdin").and_then(|v| v.as_str()).map(String::from),
+            ..Default::default()
+        };
+        if let Some(cwd) = args.get("cwd").and_then(|v| v.as_str()) {
+            // Relative directories are resolved against the project root
+            let cwd = self.ctx.config.project_root.join(cwd);
+            if !cwd.is_dir() {
+                return Ok(ToolResult::error(format!("cwd is not a directory: {}", cwd.display())));
+            }
+            options.cwd = Some(cwd);
+        }
+        if let Some(env) = args.get("env").and_then(|v| v.as_object()) {
+            for (key, value) in env {
+                let Some(value) = value.as_str() else {
+                    return Ok(ToolResult::error(format!("env value for '{}' must be a string", key)));
+                };
+                options.env.push((key.clone(), value.to_string()));
+            }
+        }
+
+        // With a progress token, stream compressed snapshots so long runs aren't silent
+        let owned_command = command.to_string();
+        let result = tokio::task::spawn_blocking(move || match progress {
+            Some(progress) => crate::compress::exec_compressed_streaming(
+                &owned_command,
+                &config,
+                &options,
+                PROGRESS_INTERVAL,
+                |snapshot| progress.report(snapshot),
+            ),
+            None => crate::compress::exec_compressed(&owned_command, &config, &options),
+        })
+        .await?;
+
+        match result {
+            Ok(mut compressed) => {
+                // Track analytics
+                let original_tokens = compressed.original_size / 4;
+                let compressed_tokens = compressed.compressed_size / 4;
+
+                {
+                    let mut analytics = self.ctx.compression_analytics.lock().await;
+                    analytics.record(category, original_tokens, compressed_tokens);
+                }
+
+                let reduction = compressed.reduction_percent();
+                let header = if reduction > 10.0 {
+                    // Keep the original so hidden lines can be fetched with expand_output
+                    let expand_hint = match compressed.raw_output.take() {
+                        Some(raw) => {
+                            let id = self.ctx.output_cache.lock().await.insert(command, raw);
+                            format!(" · expand_output id={}", id)
+                        }
+                        None => String::new(),
+                    };
+                    format!(
+                        "📦 Compressed ({:.0}% reduction, ~{} tokens saved){}\n\n",
+                        reduction, compressed.estimated_token_savings, expand_hint
+                    )
+                } else {
+                    String::new()
+                };
+
+                let output = self.link_stack_frames(&compressed.output).await;
+                Ok(ToolResult::text(format!("{}{}", header, output)))
+            }
+            Err(e) => Ok(ToolResult::error(e)),
+        }
+    }
+
+    async fn handle_shell_session(&self, args: Value) -> Result<ToolResult> {
+        let command = args.get("command").and_then(|v| v.as_str()).unwrap_or("");
+        if command.is_empty() {
             return Ok(ToolResult::error("Missing required parameter: command"));
         }
+        let name = args.get("name").and_then(|v| v.as_str()).unwrap_or("default").to_string();
+        let reset = args.get("reset").and_then(|v| v.as_bool()).unwrap_or(false);
+        let settings = self.ctx.config.settings();
+        let timeout_secs = args.get("timeout_secs").and_then(|v| v.as_u64());
+        let timeout = std::time::Duration::from_secs(timeout_secs.unwrap_or(settings.tools.shell_timeout_secs));
+        let bash = &settings.bash;
+        if let Err(e) = CommandPolicy::new(&bash.allow, &bash.deny).and_then(|policy| policy.check(command)) {
+            return Ok(ToolResult::error(e));
+        }
+
+        let category = crate::compress::categorize_command(command);
+        let mut config = CompressConfig::for_category(&self.ctx.config.settings().compression, category);
+        if let Some(max_lines) = args.get("max_lines").and_then(|v| v.as_u64()) {
+            config.max_lines = max_lines as usize;
+        }
+        config.project_root = Some(self.ctx.config.project_root.clone());
+
+        let session = {
+            let mut sessions = self.ctx.shell_sessions.lock().await;
+            if reset {
+                sessions.remove(&name);
+            }
+            match sessions.get(&name) {
+                Some(session) => session.clone(),
+                None => match ShellSession::start(&self.ctx.config.project_root) {
+                    Ok(session) => {
+                        let session = Arc::new(std::sync::Mutex::new(session));
+                        sessions.insert(name.clone(), session.clone());
+                        session
+                    }
+                    Err(e) => return Ok(ToolResult::error(e)),
+                },
+            }
+        };
+
+        let owned_command = command.to_string();
+        let result = tokio::task::spawn_blocking(move || {
+            let mut session = session.lock().map_err(|e| format!("Shell session lock poisoned: {}", e))?;
+            session.run(&owned_command, timeout)
+        })
+        .await?;
+
+        let output = match result {
+            Ok(output) => output,
+            Err(e) => {
+                self.ctx.shell_sessions.lock().await.remove(&name);
+                return Ok(ToolResult::error(e));
+            }
+        };
+        // Killed or exited shells can't be reused
+        if output.exit_code.is_none() {
+            self.ctx.shell_sessions.lock().await.remove(&name);
+        }
+
+        let compressed = crate::compress::compress_shell_output(command, &output, &config);
+        {
+            let mut analytics = self.ctx.compression_analytics.lock().await;
+            analytics.record(category, compressed.original_size / 4, compressed.compressed_size / 4);
+        }
+
+        let output = self.link_stack_frames(&compressed.output).await;
+        Ok(ToolResult::text(format!("$ [{}] {}\n{}", name, command, output)))
+    }
+
+    /// Tag the stack frames left in compressed output with the graph symbols they point into
+    async fn link_stack_frames(&self, output: &str) -> String {
+        let graph = self.ctx.graph.read().await;
+        crate::compress::link_frames(output, &self.ctx.config.project_root, |path, line| {
+            graph.symbol_at(path, line).map(|node| node.id.clone())
+        })
+    }
+
+    async fn handle_read_file_compressed(&self, args: Value) -> Result<ToolResult> {
+        let path = args.get("path").and_then(|v| v.as_str()).unwrap_or("");
+        if path.is_empty() {
+            return Ok(ToolResult::error("Missing required parameter: path"));
+        }
+
+        let symbols: Vec<String> = args
+            .get("symbols")
+            .and_then(|v| v.as_array())
+            .map(|arr| arr.iter().filter_map(|v| v.as_str().map(String::from)).collect())
+            .unwrap_or_default();
+
+        let mut ranges = Vec::new();
+        for range in args.get("lines").and_then(|v| v.as_array()).into_iter().flatten() {
+            let range = range.as_str().unwrap_or("");
+            match parse_line_range(range) {
+                Some(r) => ranges.push(r),
+                None => return Ok(ToolResult::error(format!("Invalid line range: '{}'. Use \"start-end\" or a single line", range))),
+            }
+        }
+
+        let max_lines = args
+            .get("max_lines")
+            .and_then(|v| v.as_u64())
+            .map_or(self.ctx.config.settings().tools.read_max_lines, |v| v as usize);
+
+        let Some(full_path) = self.project_path(path) else {
+            return Ok(ToolResult::error(format!("path must be inside the project: '{}'", path)));
+        };
+        let content = match std::fs::read_to_string(&full_path) {
+            Ok(content) => content,
+            Err(e) => return Ok(ToolResult::error(format!("Failed to read '{}': {}", path, e))),
+        };
+
+        // Parse the file as it is on disk so line numbers hold even after unindexed edits;
+        // fall back to the indexed symbols if the parser can't handle it
+        let languages = self.ctx.config.settings().indexing.languages.clone();
+        let parsed = crate::code::languages::detect_language_with(path, &languages)
+            .and_then(|lang| crate::code::parser::parse_file(&full_path, content.as_bytes(), lang).ok());
+        let outline: Vec<OutlineSymbol> = match parsed {
+            Some(parsed) => parsed
+                .symbols
+                .into_iter()
+                .map(|symbol| OutlineSymbol {
+                    kind: crate::code::indexer::symbol_kind_to_str(&symbol.kind).to_string(),
+                    name: symbol.name,
+                    line_start: symbol.line_start as usize,
+                    line_end: symbol.line_end as usize,
+                    signature: symbol.signature,
+                })
+                .collect(),
+            None => {
+                let graph = self.ctx.graph.read().await;
+                let mut outline = Vec::new();
+                for node in graph.file_symbols(path) {
+                    let data = graph.node_data(&self.ctx.store, node).await?;
+                    outline.push(OutlineSymbol {
+                        name: data.get("name").and_then(|v| v.as_str()).unwrap_or("?").to_string(),
+                        kind: node.kind.clone(),
+                        line_start: data.get("line_start").and_then(|v| v.as_u64()).unwrap_or(0) as usize,
+                        line_end: data.get("line_end").and_then(|v| v.as_u64()).unwrap_or(0) as usize,
+                        signature: data.get("signature").and_then(|v| v.as_str()).unwrap_or("").to_string(),
+                    });
+                }
+                outline
+            }
+        };
+
+        let config = CompressConfig {
+            max_lines,
+            ..Default::default()
+        };
+        let request = ReadRequest { symbols, ranges };
+        let result = crate::compress::compress_file_read(path, &content, &outline, &request, &config);
+
+        {
+            let mut analytics = self.ctx.compression_analytics.lock().await;
+            analytics.record("read", result.original_size / 4, result.compressed_size / 4);
+        }
+
+        Ok(ToolResult::text(result.output))
+    }
+
+    async fn handle_git_context(&self, args: Value) -> Result<ToolResult> {
+        let log_count = args
+            .get("log_count")
+            .and_then(|v| v.as_u64())
+            .map_or(self.ctx.config.settings().tools.git_log_count, |v| v as usize);
+
+        let mut config = CompressConfig::for_category(&self.ctx.config.settings().compression, "git");
+        if let Some(max_items) = args.get("max_items_per_group").and_then(|v| v.as_u64()) {
+            config.max_items_per_group = max_items as usize;
+        }
+
+        let root = self.ctx.config.project_root.clone();
+        let result = tokio::task::spawn_blocking(move || crate::compress::git_context(&root, log_count, &config))
+            .await
+            .map_err(|e| anyhow::anyhow!("git task failed: {}", e))?;
+        let result = match result {
+            Ok(result) => result,
+            Err(e) => return Ok(ToolResult::error(format!("git_context failed: {}", e))),
+        };
+
+        {
+            let mut analytics = self.ctx.compression_analytics.lock().await;
+            analytics.record("git", result.original_size / 4, result.compressed_size / 4);
+        }
+
+        Ok(ToolResult::text(result.output))
+    }
+
+    async fn handle_grep_project(&self, args: Value) -> Result<ToolResult> {
+        let pattern = args.get("pattern").and_then(|v| v.as_str()).unwrap_or("");
+        if pattern.is_empty() {
+            return Ok(ToolResult::error("Missing required parameter: pattern"));
+        }
+
+        let root = match args.get("path").and_then(|v| v.as_str()) {
+            Some(path) => self.ctx.config.project_root.join(path),
+            None => self.ctx.config.project_root.clone(),
+        };
+        if !root.exists() {
+            return Ok(ToolResult::error(format!("Path not found: {}", root.display())));
+        }
+
+        let settings = self.ctx.config.settings();
+        let defaults = &settings.tools;
+        let options = GrepOptions {
+            pattern: pattern.to_string(),
+            fixed_strings: args.get("fixed_strings").and_then(|v| v.as_bool()).unwrap_or(false),
+            case_insensitive: args.get("case_insensitive").and_then(|v| v.as_bool()).unwrap_or(false),
+            globs: args
+                .get("glob")
+                .and_then(|v| v.as_array())
+                .map(|arr| arr.iter().filter_map(|v| v.as_str().map(String::from)).collect())
+                .unwrap_or_default(),
+            context: args
+                .get("context")
+                .and_then(|v| v.as_u64())
+                .map_or(defaults.grep_context, |v| v as usize),
+            max_matches: defaults.grep_max_matches,
+        };
+
+        let mut config = CompressConfig::for_category(&settings.compression, "search");
+        if let Some(max_lines) = args.get("max_lines").and_then(|v| v.as_u64()) {
+            config.max_lines = max_lines as usize;
+        }
+
+        // Walking a large tree is blocking work
+        let search_root = root.clone();
+        let files = tokio::task::spawn_blocking(move || crate::compress::grep_project(&search_root, &options))
+            .await
+            .map_err(|e| anyhow::anyhow!("Search task failed: {}", e))?;
+        let files = match files {
+            Ok(files) => files,
+            Err(e) => return Ok(ToolResult::error(e)),
+        };
+
+        let result = crate::compress::compress_grep_matches(&files, &config);
+        {
+            let mut analytics = self.ctx.compression_analytics.lock().await;
+            analytics.record("search", result.original_size / 4, result.compressed_size / 4);
+        }
+
+        Ok(ToolResult::text(result.output))
+    }
+
+    async fn handle_expand_output(&self, args: Value) -> Result<ToolResult> {
+        let id = args.get("id").and_then(|v| v.as_str()).unwrap_or("");
+        if id.is_empty() {
+            return Ok(ToolResult::error("Missing required parameter: id"));
+        }
+
+        let mut ranges = Vec::new();
+        for range in args.get("lines").and_then(|v| v.as_array()).into_iter().flatten() {
+            let range = range.as_str().unwrap_or("");
+            match parse_line_range(range) {
+                Some(r) => ranges.push(r),
+                None => return Ok(ToolResult::error(format!("Invalid line range: '{}'. Use \"start-end\" or a single line", range))),
+            }
+        }
+
+        let pattern = match args.get("pattern").and_then(|v| v.as_str()) {
+            Some(p) => match regex::Regex::new(p) {
+                Ok(re) => Some(re),
+                Err(e) => return Ok(ToolResult::error(format!("Invalid pattern: {}", e))),
+            },
+            None => None,
+        };
+        let context = args.get("context").and_then(|v| v.as_u64()).unwrap_or(3) as usize;
+        let max_lines = args.get("max_lines").and_then(|v| v.as_u64()).unwrap_or(200) as usize;
+
+        let mut cache = self.ctx.output_cache.lock().await;
+        let Some(cached) = cache.get(id) else {
+            return Ok(ToolResult::error(format!(
+                "Output '{}' not found or expired. Re-run the command with bash_compressed.",
+                id
+            )));
+        };
+
+        let expanded = crate::compress::expand_output(&cached.output, &ranges, pattern.as_ref(), context, max_lines);
+        Ok(ToolResult::text(format!("$ {}\n{}", cached.command, expanded)))
+    }
+
+    async fn handle_compression_stats(&self, args: Value) -> Result<ToolResult> {
+        let reset = args.get("reset").and_then(|v| v.as_bool()).unwrap_or(false);
+
+        let mut analytics = self.ctx.compression_analytics.lock().await;
+        let report = analytics.format_report();
+        let stats_json = analytics.to_json();
+
+        if reset {
+            analytics.reset();
+        }
+
+        Ok(ToolResult::text(format!("{}\n\nJSON:\n{}", report, serde_json::to_string_pretty(&stats_json)?)))
+    }
+
+    async fn handle_server_metrics(&self, args: Value) -> Result<ToolResult> {
+        let metrics = &self.ctx.metrics;
+        let text = match args.get("format").and_then(|v| v.as_str()).unwrap_or("text") {
+            "text" => metrics.format_report(),
+            "json" => serde_json::to_string_pretty(&metrics.to_json())?,
+            "prometheus" => metrics.to_prometheus(),
+            other => {
+                return Ok(ToolResult::error(format!("Unknown format '{}': use text, json, or prometheus", other)))
+            }
+        };
+        if args.get("reset").and_then(|v| v.as_bool()).unwrap_or(false) {
+            metrics.reset();
+        }
+        Ok(ToolResult::text(text))
+    }
+
+    async fn handle_reload_config(&self) -> Result<ToolResult> {
+        match self.ctx.config.reload() {
+            Ok(changed) if changed.is_empty() => Ok(ToolResult::text("No config changes")),
+            Ok(changed) => {
+                self.ctx.query_cache.lock().await.invalidate();
+                let mut text = format!("✅ Reloaded config.toml (changed: {})", changed.join(", "));
+                // The store is already open at the old location
+                if changed.iter().any(|section| section == "storage") {
+                    text.push_str("\n[storage] changes take effect after a restart");
+                }
+                Ok(ToolResult::text(text))
+            }
+            Err(e) => Ok(ToolResult::error(format!("{:#}; previous settings kept", e))),
+        }
+    }
+
+    async fn handle_doctor(&self) -> Result<ToolResult> {
+        let results = crate::doctor::diagnose(&self.ctx.config.project_root, Some(&self.ctx.graph)).await;
+        Ok(ToolResult::text(crate::doctor::format_report(&results)))
+    }
+
+    async fn handle_validate_config(&self) -> Result<ToolResult> {
+        let report = self.ctx.config.validate();
+        let mut lines = Vec::new();
+
+        lines.push(if report.errors.is_empty() {
+            format!("✅ Config is valid ({} warnings)", report.warnings.len())
+        } else {
+            format!("❌ {} errors, {} warnings", report.errors.len(), report.warnings.len())
+        });
+        if report.files.is_empty() {
+            lines.push("Files: none (using defaults)".to_string());
+        } else {
+            let files: Vec<String> = report.files.iter().map(|f| f.display().to_string()).collect();
+            lines.push(format!("Files: {}", files.join(", ")));
+        }
+
+        for (title, issues) in [("Errors:", &report.errors), ("Warnings:", &report.warnings)] {
+            if !issues.is_empty() {
+                lines.push(String::new());
+                lines.push(title.to_string());
+                lines.extend(issues.iter().map(|issue| format!("  - {}", issue)));
+            }
+        }
+
+        if let Some(effective) = &report.effective {
+            lines.push(String::new());
+            lines.push("Effective configuration:".to_string());
+            lines.push(toml::to_string_pretty(effective)?.trim_end().to_string());
+        }
+
+        let text = lines.join("\n");
+        Ok(if report.errors.is_empty() {
+            ToolResult::text(text)
+        } else {
+            ToolResult::error(text)
+        })
+    }
+}
+
+fn parse_task_status(s: &str) -> crate::session::TaskStatus {
+    match s {
+        "pending" => crate::session::TaskStatus::Pending,
+        "in_progress" => crate::session::TaskStatus::InProgress,
+        "completed" => crate::session::TaskStatus::Completed,
+        "blocked" => crate::session::TaskStatus::Blocked,
+        _ => crate::session::TaskStatus::Pending,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::code::Indexer;
+    use crate::config::Config;
+    use crate::session::SessionManager;
+    use crate::store::{CodeGraph, Store};
+    use tempfile::TempDir;
+
+    async fn setup_ctx() -> (Arc<ToolContext>, TempDir) {
+        let temp_dir = TempDir::new().unwrap();
+        let temp_path = temp_dir.path();
+
+        let config = Arc::new(Config {
+            project_root: temp_path.to_path_buf(),
+            cache_dir: temp_path.join("cache"),
+            codegraph_dir: temp_path.join(".codegraph"),
+            store_db_path: temp_path.join("cache/store.db"),
+            learning_db_path: temp_path.join(".codegraph/learning.db"),
+            settings: crate::config::ConfigFile::default().into(),
+        });
+
+        config.ensure_dirs().unwrap();
+
+        let store = Arc::new(Store::open(&config).await.unwrap());
+        let graph = Arc::new(RwLock::new(
+            CodeGraph::load_from_store(&store).await.unwrap(),
+        ));
+        let indexer = Arc::new(Indexer::new(store.clone(), config.clone()));
+        let session_manager = Arc::new(SessionManager::new(store.clone(), graph.clone()));
+
+        let embeddings = Arc::new(crate::learning::embeddings::EmbeddingIndex::new(
+            Arc::new(store.learning_db.clone()),
+            config.clone(),
+        ));
+        let pattern_store = Arc::new(
+            crate::learning::patterns::PatternStore::new(Arc::new(store.learning_db.clone()))
+                .with_embeddings(embeddings.clone()),
+        );
+        let failure_store = Arc::new(
+            crate::learning::failures::FailureStore::new(Arc::new(store.learning_db.clone()))
+                .with_embeddings(embeddings),
+        );
+        let lineage_store = Arc::new(crate::learning::lineage::LineageStore::new(Arc::new(
+            store.learning_db.clone(),
+        )));
+        let niche_store = Arc::new(crate::learning::niches::NicheStore::new(Arc::new(
+            store.learning_db.clone(),
+        )));
+        let manual_instruction_store = Arc::new(crate::skill::distill::ManualInstructionStore::new(Arc::new(
+            store.learning_db.clone(),
+        )));
+        let cross_language_inferrer = Arc::new(crate::code::CrossLanguageInferrer::new(store.clone(), config.clone()));
+
+        let ctx = Arc::new(ToolContext {
+            store,
+            config,
+            indexer,
+            graph,
+            session_manager,
+            pattern_store,
+            failure_store,
+            lineage_store,
+            niche_store,
+            manual_instruction_store,
+            cross_language_inferrer,
+            compression_analytics: tokio::sync::Mutex::new(crate::compress::CompressionAnalytics::new()),
+            output_cache: tokio::sync::Mutex::new(crate::compress::OutputCache::new()),
+            query_cache: tokio::sync::Mutex::new(crate::mcp::query_cache::QueryCache::new()),
+            metrics: Metrics::new(),
+            shell_sessions: tokio::sync::Mutex::new(HashMap::new()),
+        });
+
+        (ctx, temp_dir)
+    }
+
+    #[tokio::test]
+    async fn test_list_tools() {
+        let (ctx, _temp) = setup_ctx().await;
+        let registry = ToolRegistry::new(ctx);
+        let tools = registry.list();
+
+        assert!(tools.len() >= 26); // 10 original + 9 phase 4-5 + 7 phase 6-9
+        assert!(tools.iter().any(|t| t.name == "search_symbols"));
+        assert!(tools.iter().any(|t| t.name == "smart_context"));
+        assert!(tools.iter().any(|t| t.name == "index_project"));
+        // Learning tools
+        assert!(tools.iter().any(|t| t.name == "recall_patterns"));
+        assert!(tools.iter().any(|t| t.name == "record_attempt"));
+        assert!(tools.iter().any(|t| t.name == "reflect"));
+        // Phase 6-9 tools
+        assert!(tools.iter().any(|t| t.name == "list_niches"));
+        assert!(tools.iter().any(|t| t.name == "distill_project_skill"));
+        assert!(tools.iter().any(|t| t.name == "sync_learnings"));
+    }
+
+    #[tokio::test]
+    async fn test_unknown_tool() {
+        let (ctx, _temp) = setup_ctx().await;
+        let registry = ToolRegistry::new(ctx);
+        let result = registry.execute("unknown", json!({})).await.unwrap();
+
+        assert!(result.is_error == Some(true));
+    }
+
+    #[tokio::test]
+    async fn test_search_empty_graph() {
+        let (ctx, _temp) = setup_ctx().await;
+        let registry = ToolRegistry::new(ctx);
+        let result = registry
+            .execute("search_symbols", json!({"query": "foo"}))
+            .await
+            .unwrap();
+
+        // Should return "no symbols found", not an error
+        assert!(result.is_error.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_query_cache_invalidated_by_index() {
+        let (ctx, temp) = setup_ctx().await;
+        std::fs::write(temp.path().join("a.rs"), "fn alpha() {}\n").unwrap();
+        let registry = ToolRegistry::new(ctx.clone());
+        registry.execute("index_project", json!({"full": true})).await.unwrap();
+
+        let search = || registry.execute("search_symbols", json!({"query": "alpha", "compact": false}));
+        let Content::Text { text } = &search().await.unwrap().content[0];
+        assert!(text.contains("alpha") && !text.contains("alphabet"));
+        assert!(ctx.query_cache.lock().await.get("search_symbols:{\"compact\":false,\"query\":\"alpha\"}").is_some());
+
+        std::fs::write(temp.path().join("b.rs"), "fn alphabet() {}\n").unwrap();
+        registry.execute("index_project", json!({})).await.unwrap();
+        let Content::Text { text } = &search().await.unwrap().content[0];
+        assert!(text.contains("alphabet"));
+    }
+
+    #[tokio::test]
+    async fn test_server_metrics() {
+        let (ctx, temp) = setup_ctx().await;
+        std::fs::write(temp.path().join("a.rs"), "fn alpha() {}\n").unwrap();
+        let registry = ToolRegistry::new(ctx);
+        registry.execute("index_project", json!({"full": true})).await.unwrap();
+        for _ in 0..2 {
+            registry.execute("search_symbols", json!({"query": "alpha"})).await.unwrap();
+        }
+        registry.execute("get_file_symbols", json!({})).await.unwrap();
+
+        let result = registry.execute("server_metrics", json!({"format": "json"})).await.unwrap();
+        let Content::Text { text } = &result.content[0];
+        let metrics: Value = serde_json::from_str(text).unwrap();
+        assert_eq!(metrics["tools"]["search_symbols"]["count"], 2);
+        assert_eq!(metrics["tools"]["get_file_symbols"]["errors"], 1);
+        assert_eq!(metrics["index_runs"]["full"]["count"], 1);
+        assert_eq!(metrics["db"]["graph_load"]["count"], 1);
+        assert_eq!(metrics["query_cache"]["hits"], 1);
+        // The failed get_file_symbols was a cache miss too
+        assert_eq!(metrics["query_cache"]["misses"], 2);
+
+        let result = registry.execute("server_metrics", json!({"format": "yaml"})).await.unwrap();
+        assert_eq!(result.is_error, Some(true));
+    }
+
+    #[tokio::test]
+    async fn test_index_profile() {
+        let (ctx, temp) = setup_ctx().await;
+        std::fs::write(temp.path().join("a.rs"), "fn alpha() { beta() }\n").unwrap();
+        std::fs::write(temp.path().join("b.py"), "def beta():\n    pass\n").unwrap();
+        let registry = ToolRegistry::new(ctx.clone());
+
+        let result = registry.execute("index_profile", json!({})).await.unwrap();
+        let Content::Text { text } = &result.content[0];
+        assert!(text.starts_with("Indexed 2 files (2 new/changed"), "{}", text);
+        for phase in ["walk", "hash", "parse", "  rust", "  python", "db writes", "resolution", "Slowest files:"] {
+            assert!(text.contains(phase), "missing {}: {}", phase, text);
+        }
+        assert!(text.contains("  a.rs") && text.contains("  b.py"));
+        // The graph is updated like index_project
+        assert!(ctx.graph.read().await.get_node("a.rs::alpha").is_some());
+    }
+
+    #[tokio::test]
+    async fn test_slim_graph_reads_signatures_from_store() {
+        let (ctx, temp) = setup_ctx().await;
+        std::fs::write(temp.path().join("a.rs"), "/// Adds one\nfn alpha(x: u32) -> u32 { x + 1 }\n").unwrap();
+        let registry = ToolRegistry::new(ctx.clone());
+        registry.execute("index_project", json!({"full": true})).await.unwrap();
+        assert!(!ctx.graph.read().await.is_slim());
+
+        // Any graph is estimated above zero
+        let settings = crate::config::GraphConfig {
+            slim_above_mb: 0,
+            ..Default::default()
+        };
+        *ctx.graph.write().await = CodeGraph::load(&ctx.store, &settings).await.unwrap();
+        {
+            let graph = ctx.graph.read().await;
+            assert!(graph.is_slim());
+            let node = graph.get_node("a.rs::alpha").unwrap();
+            assert!(node.data.get("signature").is_none());
+            assert_eq!(node.data["line_start"], 2);
+        }
+
+        let result = registry
+            .execute("get_file_symbols", json!({"path": "a.rs", "compact": false}))
+            .await
+            .unwrap();
+        let Content::Text { text } = &result.content[0];
+        assert!(text.contains("fn alpha(x: u32) -> u32"), "{}", text);
+
+        // Nodes patched in by an incremental index are slimmed too
+        std::fs::write(temp.path().join("b.rs"), "fn beta() {}\n").unwrap();
+        registry.execute("index_project", json!({})).await.unwrap();
+        assert!(ctx.graph.read().await.get_node("b.rs::beta").unwrap().data.get("signature").is_none());
+        let result = registry
+            .execute("search_symbols", json!({"query": "beta", "compact": false}))
+            .await
+            .unwrap();
+        let Content::Text { text } = &result.content[0];
+        assert!(text.contains("fn beta()"), "{}", text);
+    }
+
+    #[tokio::test]
+    async fn test_search_code() {
+        let (ctx, temp) = setup_ctx().await;
+        let registry = ToolRegistry::new(ctx.clone());
+        std::fs::write(
+            temp.path().join("net.py"),
+            "def fetch(url):\n    \"\"\"Download a page\"\"\"\n    return retry_with_jitter(url)\n",
+        )
+        .unwrap();
+        registry.execute("index_project", json!({"full": true})).await.unwrap();
+
+        let result = registry.execute("search_code", json!({"query": "jitter"})).await.unwrap();
+        let Content::Text { text } = &result.content[0];
+        assert!(text.contains("[function] fetch (net.py:1)"), "{}", text);
+        assert!(text.contains("id: net.py::fetch"), "{}", text);
+
+        // A deleted file's text goes with it
+        std::fs::remove_file(temp.path().join("net.py")).unwrap();
+        registry.execute("index_project", json!({})).await.unwrap();
+        let result = registry.execute("search_code", json!({"query": "jitter"})).await.unwrap();
+        let Content::Text { text } = &result.content[0];
+        assert_eq!(text, "No code found matching 'jitter'");
+
+        std::fs::write(temp.path().join("b.py"), "def beta():\n    pass\n").unwrap();
+        registry.execute("index_project", json!({})).await.unwrap();
+        ctx.store.code_db.execute("DELETE FROM symbol_text", ()).await.unwrap();
+        let result = registry.execute("search_code", json!({"query": "pass"})).await.unwrap();
+        let Content::Text { text } = &result.content[0];
+        assert!(text.contains("full-text index is empty"), "{}", text);
+    }
+
+    #[tokio::test]
+    async fn test_find_references() {
+        let (ctx, temp) = setup_ctx().await;
+        let registry = ToolRegistry::new(ctx);
+        std::fs::write(temp.path().join("util.py"), "def helper():\n    return 1\n").unwrap();
+        std::fs::write(
+            temp.path().join("app.py"),
+            "def main():\n    x = 1\n    return helper()\n\ndef other():\n    return missing()\n",
+        )
+        .unwrap();
+        registry.execute("index_project", json!({"full": true})).await.unwrap();
+
+        for symbol in ["helper", "util.py::helper"] {
+            let result = registry.execute("find_references", json!({"symbol": symbol})).await.unwrap();
+            let Content::Text { text } = &result.content[0];
+            assert!(text.contains("## helper [function] (util.py:1)"), "{}", text);
+            assert!(text.contains("app.py:3 calls from main"), "{}", text);
+        }
+
+        // References that never resolved are still listed by name
+        let result = registry.execute("find_references", json!({"symbol": "missing"})).await.unwrap();
+        let Content::Text { text } = &result.content[0];
+        assert!(text.contains("## missing (unresolved"), "{}", text);
+        assert!(text.contains("app.py:6 calls from other"), "{}", text);
+
+        let result = registry
+            .execute("find_references", json!({"symbol": "helper", "edge_types": ["imports"]}))
+            .await
+            .unwrap();
+        let Content::Text { text } = &result.content[0];
+        assert_eq!(text, "No references to 'helper'");
+
+        let result = registry.execute("find_references", json!({"symbol": "nope"})).await.unwrap();
+        let Content::Text { text } = &result.content[0];
+        assert!(text.starts_with("No symbol found matching 'nope'"), "{}", text);
+    }
+
+    #[tokio::test]
+    async fn test_detect_cycles() {
+        let (ctx, temp) = setup_ctx().await;
+        let registry = ToolRegistry::new(ctx);
+        std::fs::create_dir_all(temp.path().join("orders")).unwrap();
+        std::fs::create_dir_all(temp.path().join("billing")).unwrap();
+        std::fs::write(temp.path().join("orders/place.py"), "def place_order():\n    return charge()\n").unwrap();
+        std::fs::write(
+            temp.path().join("billing/charge.py"),
+            "def charge():\n    return 1\n\ndef refund():\n    return cancel_order()\n",
+        )
+        .unwrap();
+        std::fs::write(temp.path().join("orders/cancel.py"), "def cancel_order():\n    return 0\n").unwrap();
+        registry.execute("index_project", json!({"full": true})).await.unwrap();
+
+        let result = registry.execute("detect_cycles", json!({})).await.unwrap();
+        let Content::Text { text } = &result.content[0];
+        assert_eq!(text, "No file-level cycles through calls/imports/inherits/implements/uses_type edges");
+
+        let result = registry
+            .execute("detect_cycles", json!({"granularity": "module", "edge_types": ["calls", "imports"]}))
+            .await
+            .unwrap();
+        let Content::Text { text } = &result.content[0];
+        assert_eq!(
+            text,
+            "## 1 module-level cycle through calls/imports edges\n\n\
+             1. billing ↔ orders (2 modules)\n   \
+             billing → orders: refund -calls→ cancel_order\n   \
+             orders → billing: place_order -calls→ charge"
+        );
+
+        let result = registry.execute("detect_cycles", json!({"granularity": "package"})).await.unwrap();
+        assert_eq!(result.is_error, Some(true));
+
+        // `conn.ping()` links to the project's only `ping`, but isn't a call to it, as in `codegraph check`
+        std::fs::write(temp.path().join("orders/ping.py"), "def ping():\n    return pong()\n").unwrap();
+        std::fs::write(temp.path().join("orders/pong.py"), "def pong(conn):\n    return conn.ping()\n").unwrap();
+        registry.execute("index_project", json!({})).await.unwrap();
+        let result = registry.execute("detect_cycles", json!({})).await.unwrap();
+        let Content::Text { text } = &result.content[0];
+        assert!(text.starts_with("No file-level cycles"), "{}", text);
+    }
+
+    #[tokio::test]
+    async fn test_export_graph() {
+        let (ctx, temp) = setup_ctx().await;
+        let registry = ToolRegistry::new(ctx);
+        std::fs::write(temp.path().join("app.py"), "def main():\n    return helper()\n\ndef helper():\n    return 1\n")
+            .unwrap();
+        registry.execute("index_project", json!({"full": true})).await.unwrap();
+
+        let result = registry.execute("export_graph", json!({"format": "dot", "kinds": ["function"]})).await.unwrap();
+        let Content::Text { text } = &result.content[0];
+        assert!(text.contains("    \"app.py::main\" [label=\"main [function]\"];\n"), "{}", text);
+        assert!(text.contains("  \"app.py::main\" -> \"app.py::helper\" [label=\"calls\"];\n"), "{}", text);
+
+        let args = json!({"format": "graphml", "edge_types": ["calls"], "output": "docs/graph.graphml"});
+        let result = registry.execute("export_graph", args).await.unwrap();
+        let Content::Text { text } = &result.content[0];
+        assert_eq!(text, "✅ Wrote 2 nodes and 1 edges to docs/graph.graphml");
+        let written = std::fs::read_to_string(temp.path().join("docs/graph.graphml")).unwrap();
+        assert!(written.contains("<data key=\"edge_kind\">calls</data>"), "{}", written);
+
+        for args in [json!({"format": "svg"}), json!({"path": "[", "format": "json"}), json!({"output": "../g.json"})] {
+            let result = registry.execute("export_graph", args.clone()).await.unwrap();
+            assert_eq!(result.is_error, Some(true), "{}", args);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_find_unreferenced_symbols() {
+        let (ctx, temp) = setup_ctx().await;
+        let registry = ToolRegistry::new(ctx);
+        std::fs::create_dir_all(temp.path().join("lib")).unwrap();
+        std::fs::write(
+            temp.path().join("lib/util.py"),
+            "def helper():\n    return 1\n\ndef legacy():\n    return 2\n\ndef _old():\n    return 3\n",
+        )
+        .unwrap();
+        std::fs::write(
+            temp.path().join("app.py"),
+            "from lib.util import helper\n\ndef main():\n    return helper()\n\ndef test_main():\n    main()\n\ndef unused():\n    pass\n",
+        )
+        .unwrap();
+        registry.execute("index_project", json!({"full": true})).await.unwrap();
+
+        let result = registry.execute("find_unreferenced_symbols", json!({})).await.unwrap();
+        let Content::Text { text } = &result.content[0];
+        assert!(text.starts_with("## 2 unreferenced public symbols (no incoming calls/imports edges)"), "{}", text);
+        assert!(text.contains("app.py:9 [function] unused\nlib/util.py:4 [function] legacy\n"), "{}", text);
+
+        let result = registry
+            .execute("find_unreferenced_symbols", json!({"path": "lib/**", "include_private": true}))
+            .await
+            .unwrap();
+        let Content::Text { text } = &result.content[0];
+        assert!(text.contains("lib/util.py:4 [function] legacy\nlib/util.py:7 [function] _old\n"), "{}", text);
+        assert!(!text.contains("app.py"), "{}", text);
+
+        let result = registry.execute("find_unreferenced_symbols", json!({"kind": "class"})).await.unwrap();
+        let Content::Text { text } = &result.content[0];
+        assert_eq!(text, "No unreferenced public symbols (by calls/imports edges)");
+    }
+
+    #[tokio::test]
+    async fn test_call_path() {
+        let (ctx, temp) = setup_ctx().await;
+        let registry = ToolRegistry::new(ctx);
+        std::fs::write(temp.path().join("store.py"), "def upsert():\n    return 1\n").unwrap();
+        std::fs::write(
+            temp.path().join("app.py"),
+            "def main():\n    return index()\n\ndef index():\n    return upsert()\n",
+        )
+        .unwrap();
+        registry.execute("index_project", json!({"full": true})).await.unwrap();
+
+        let result = registry
+            .execute("call_path", json!({"from": "app.py::main", "to": "store.py::upsert"}))
+            .await
+            .unwrap();
+        let Content::Text { text } = &result.content[0];
+        assert_eq!(
+            text,
+            "## app.py::main → store.py::upsert (2 edges, 1 shortest path)\n\n\
+             1. main (app.py:1)\n   -calls→ index (app.py:4)\n   -calls→ upsert (store.py:1)"
+        );
+
+        let result = registry
+            .execute("call_path", json!({"from": "app.py::main", "to": "store.py::upsert", "max_length": 1}))
+            .await
+            .unwrap();
+        let Content::Text { text } = &result.content[0];
+        assert_eq!(text, "No path from 'app.py::main' to 'store.py::upsert' within max_length 1");
+
+        let result = registry
+            .execute("call_path", json!({"from": "app.py::main", "to": "store.py::upsert", "edge_types": ["imports"]}))
+            .await
+            .unwrap();
+        let Content::Text { text } = &result.content[0];
+        assert!(text.starts_with("No path"), "{}", text);
+
+        let result = registry.execute("call_path", json!({"from": "app.py::main", "to": "nope"})).await.unwrap();
+        assert_eq!(result.is_error, Some(true));
+    }
+
+    #[tokio::test]
+    async fn test_get_symbol_source() {
+        let (ctx, temp) = setup_ctx().await;
+        let registry = ToolRegistry::new(ctx);
+        std::fs::write(
+            temp.path().join("app.py"),
+            "import os\n\ndef main():\n    x = 1\n    return x\n\ndef other():\n    return 2\n",
+        )
+        .unwrap();
+        registry.execute("index_project", json!({"full": true})).await.unwrap();
+
+        let result = registry.execute("get_symbol_source", json!({"id": "app.py::main"})).await.unwrap();
+        let Content::Text { text } = &result.content[0];
+        assert_eq!(
+            text,
+            "## app.py::main [function] (app.py:3-5)\n    3  def main():\n    4      x = 1\n    5      return x"
+        );
+
+        // Context is clamped to the file
+        let result = registry
+            .execute("get_symbol_source", json!({"id": "app.py::other", "context": 3}))
+            .await
+            .unwrap();
+        let Content::Text { text } = &result.content[0];
+        assert!(text.contains("    5      return x\n    6  \n    7  def other():"), "{}", text);
+        assert!(text.ends_with("    8      return 2"), "{}", text);
+
+        let result = registry.execute("get_symbol_source", json!({"id": "app.py::nope"})).await.unwrap();
+        assert_eq!(result.is_error, Some(true));
+    }
+
+    #[tokio::test]
+    async fn test_graph_survives_panic_while_locked() {
+        let (ctx, _temp) = setup_ctx().await;
+        let graph = ctx.graph.clone();
+        let panicked = tokio::spawn(async move {
+            let _guard = graph.write().await;
+            panic!("handler failed mid-update");
+        })
+        .await;
+        assert!(panicked.is_err());
+
+        // The lock isn't poisoned, so graph tools keep working
+        let registry = ToolRegistry::new(ctx);
+        let result = registry
+            .execute("search_symbols", json!({"query": "foo"}))
+            .await
+            .unwrap();
+        assert!(result.is_error.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_session_lifecycle() {
+        let (ctx, _temp) = setup_ctx().await;
+        let registry = ToolRegistry::new(ctx);
+
+        // Start session
+        let result = registry
+            .execute(
+                "start_session",
+                json!({"task": "Build feature", "items": ["Step 1", "Step 2"]}),
+            )
+            .await
+            .unwrap();
+        assert!(result.is_error.is_none());
+
+        // Get session
+        let result = registry
+            .execute("get_session", json!({}))
+            .await
+            .unwrap();
+        assert!(result.is_error.is_none());
+
+        // Smart context
+        let result = registry
+            .execute("smart_context", json!({}))
+            .await
+            .unwrap();
+        assert!(result.is_error.is_none());
+
+        // A second session leaves the first one resumable
+        let result = registry
+            .execute("start_session", json!({"task": "Fix flaky test", "name": "flaky"}))
+            .await
+            .unwrap();
+        let Content::Text { text } = &result.content[0];
+        assert_eq!(text, "Session 'flaky' started: Fix flaky test");
+
+        let result = registry.execute("list_sessions", json!({})).await.unwrap();
+        let Content::Text { text } = &result.content[0];
+        assert_eq!(text, "* flaky — Fix flaky test (0/0 done)\n  build-feature — Build feature (0/2 done)");
+
+        let result = registry.execute("archive_session", json!({})).await.unwrap();
+        let Content::Text { text } = &result.content[0];
+        assert!(text.starts_with("Archived session 'flaky'. No session is active"), "{}", text);
+        let result = registry
+            .execute("list_sessions", json!({"include_archived": false}))
+            .await
+            .unwrap();
+        let Content::Text { text } = &result.content[0];
+        assert_eq!(text, "  build-feature — Build feature (0/2 done)");
+
+        let result = registry
+            .execute("switch_session", json!({"name": "build-feature"}))
+            .await
+            .unwrap();
+        let Content::Text { text } = &result.content[0];
+        assert_eq!(text, "Switched to session 'build-feature': Build feature (0/2 tasks completed)");
+        let result = registry.execute("switch_session", json!({"name": "nope"})).await.unwrap();
+        assert_eq!(result.is_error, Some(true));
+
+        registry
+            .execute("add_decision", json!({"what": "Use a feature flag", "why": "Safer rollout"}))
+            .await
+            .unwrap();
+        let result = registry.execute("session_history", json!({})).await.unwrap();
+        let Content::Text { text } = &result.content[0];
+        let lines: Vec<&str> = text.lines().collect();
+        assert_eq!(lines.len(), 2, "{}", text);
+        assert!(lines[0].starts_with("#2 "), "{}", text);
+        assert!(lines[0].ends_with("decision: Use a feature flag (0/2 items done, 1 decisions)"), "{}", text);
+        assert!(lines[1].ends_with("started (0/2 items done, 0 decisions)"), "{}", text);
+
+        let result = registry.execute("restore_checkpoint", json!({"checkpoint": 1})).await.unwrap();
+        let Content::Text { text } = &result.content[0];
+        assert!(text.starts_with("Restored checkpoint #1: 2 items, 0 decisions"), "{}", text);
+        let result = registry.execute("restore_checkpoint", json!({"checkpoint": 42})).await.unwrap();
+        assert_eq!(result.is_error, Some(true));
+    }
+
+    #[tokio::test]
+    async fn test_instruction_tags_select_instructions() {
+        let (ctx, _temp) = setup_ctx().await;
+        let registry = ToolRegistry::new(ctx);
+
+        for (instruction, tag) in [("Wrap queries in a transaction", "db"), ("Keep components pure", "ui")] {
+            registry
+                .execute(
+                    "add_instruction",
+                    json!({"instruction": instruction, "category": "architecture", "scope_tags": [tag]}),
+                )
+                .await
+                .unwrap();
+        }
+        registry
+            .execute("add_instruction", json!({"instruction": "Run cargo fmt", "category": "style"}))
+            .await
+            .unwrap();
+
+        let result = registry.execute("distill_project_skill", json!({"tags": ["db"]})).await.unwrap();
+        let Content::Text { text } = &result.content[0];
+        assert!(text.contains("Wrap queries in a transaction"), "{}", text);
+        assert!(text.contains("Run cargo fmt"), "{}", text);
+        assert!(!text.contains("Keep components pure"), "{}", text);
+        assert!(!text.contains("Written to"), "{}", text);
+
+        registry.execute("start_session", json!({"task": "Add a migration"})).await.unwrap();
+        let result = registry.execute("smart_context", json!({"tags": ["ui"]})).await.unwrap();
+        let Content::Text { text } = &result.content[0];
+        let context: Value = serde_json::from_str(text).unwrap();
+        assert_eq!(context["instructions"], json!(["Keep components pure", "Run cargo fmt"]));
+    }
+
+    #[tokio::test]
+    async fn test_distill_unchanged_skill_not_archived() {
+        let (ctx, _temp) = setup_ctx().await;
+        let history_dir = ctx.config.codegraph_dir.join(crate::skill::history::HISTORY_DIR);
+        let registry = ToolRegistry::new(ctx);
+
+        for _ in 0..2 {
+            let result = registry.execute("distill_project_skill", json!({})).await.unwrap();
+            assert_ne!(result.is_error, Some(true));
+        }
+        let archived = std::fs::read_dir(&history_dir).map_or(0, |entries| entries.count());
+        assert_eq!(archived, 0);
+
+        registry
+            .execute("add_instruction", json!({"instruction": "Run cargo fmt", "category": "style"}))
+            .await
+            .unwrap();
+        registry.execute("distill_project_skill", json!({})).await.unwrap();
+        assert_eq!(std::fs::read_dir(&history_dir).unwrap().count(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_auto_distill_after_new_failures() {
+        let (ctx, _temp) = setup_ctx().await;
+        let skill_path = ctx.config.codegraph_dir.join("SKILL.md");
+        let threshold = ctx.config.settings().skill.auto_distill_after;
+        let registry = ToolRegistry::new(ctx);
+
+        let text = |result: &ToolResult| -> String {
+            result
+                .content
+                .iter()
+                .map(|Content::Text { text }| text.as_str())
+                .collect::<Vec<_>>()
+                .join("\n")
+        };
+
+        for i in 1..=threshold {
+            let result = registry
+                .execute(
+                    "record_failure",
+                    json!({"cause": format!("Cause {}", i), "avoidance_rule": "Avoid it", "severity": "major"}),
+                )
+                .await
+                .unwrap();
+
+            if i < threshold {
+                assert!(text(&result).contains("SKILL.md is stale"));
+                assert!(!skill_path.exists());
+            } else {
+                assert!(text(&result).contains("SKILL.md regenerated"));
+            }
+        }
+
+        let skill = std::fs::read_to_string(&skill_path).unwrap();
+        assert!(skill.contains("Cause 1"));
+    }
+
+    #[tokio::test]
+    async fn test_add_instruction_rejects_unconfigured_category() {
+        let (ctx, _temp) = setup_ctx().await;
+        let registry = ToolRegistry::new(ctx);
+
+        let result = registry
+            .execute(
+                "add_instruction",
+                json!({"instruction": "Validate all input", "category": "security"}),
+            )
+            .await
+            .unwrap();
+        assert_eq!(result.is_error, Some(true));
+
+        let result = registry
+            .execute(
+                "add_instruction",
+                json!({"instruction": "Validate all input", "category": "gotchas"}),
+            )
+            .await
+            .unwrap();
+        assert!(result.is_error.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_pin_instruction_rejects_unknown_priority() {
+        use crate::skill::categories::InstructionPriority;
+
+        let (ctx, _temp) = setup_ctx().await;
+        let registry = ToolRegistry::new(ctx.clone());
+        let pattern = ctx
+            .pattern_store
+            .create(&crate::learning::patterns::NewPattern {
+                intent: "Use async for DB calls".to_string(),
+                mechanism: None,
+                examples: vec![],
+                scope: crate::learning::Scope::default(),
+                confidence: 0.9,
+            })
+            .await
+            .unwrap();
+        registry.execute("pin_instruction", json!({"id": pattern.id})).await.unwrap();
 
-        let max_lines = args.get("max_lines").and_then(|v| v.as_u64()).unwrap_or(50) as usize;
-        let max_items = args.get("max_items_per_group").and_then(|v| v.as_u64()).unwrap_or(10) as usize;
+        // A typo must not quietly unpin it
+        let result = registry
+            .execute("pin_instruction", json!({"id": pattern.id, "priority": "crtical"}))
+            .await
+            .unwrap();
+        assert_eq!(result.is_error, Some(true));
+        let priorities = ctx.manual_instruction_store.priorities().await.unwrap();
+        assert_eq!(priorities.get(&pattern.id), Some(&InstructionPriority::Critical));
 
-        let config = CompressConfig {
-            max_lines,
-            max_items_per_group: max_items,
-            ..Default::default()
-        };
+        registry
+            .execute("pin_instruction", json!({"id": pattern.id, "priority": "Normal"}))
+            .await
+            .unwrap();
+        let priorities = ctx.manual_instruction_store.priorities().await.unwrap();
+        assert!(!priorities.get(&pattern.id).is_some_and(|p| p.is_pinned()));
+    }
 
-        let result = crate::compress::exec_compressed(command, &config);
+    #[tokio::test]
+    async fn test_read_file_compressed_line_ranges() {
+        let (ctx, temp) = setup_ctx().await;
+        let content: String = (1..=300).map(|i| format!("line {}\n", i)).collect();
+        std::fs::write(temp.path().join("big.txt"), content).unwrap();
+        let registry = ToolRegistry::new(ctx);
 
-        match result {
-            Ok(compressed) => {
-                // Track analytics
-                let category = crate::compress::categorize_command(command);
-                let original_tokens = compressed.original_size / 4;
-                let compressed_tokens = compressed.compressed_size / 4;
+        let result = registry
+            .execute("read_file_compressed", json!({"path": "big.txt", "lines": ["10-11", "250"]}))
+            .await
+            .unwrap();
+        let Content::Text { text } = &result.content[0];
+        assert!(text.contains("── L10-11 ──\n   10  line 10\n   11  line 11"));
+        assert!(text.ends_with("── L250-250 ──\n  250  line 250"));
+        assert!(!text.contains("line 100\n"));
 
-                {
-                    let mut analytics = self.ctx.compression_analytics.lock().await;
-                    analytics.record(category, original_tokens, compressed_tokens);
-                }
+        let result = registry
+            .execute("read_file_compressed", json!({"path": "big.txt", "lines": ["ten"]}))
+            .await
+            .unwrap();
+        assert_eq!(result.is_error, Some(true));
 
-                let reduction = compressed.reduction_percent();
-                let header = if reduction > 10.0 {
-                    format!("📦 Compressed ({:.0}% reduction, ~{} tokens saved)\n\n", reduction, compressed.estimated_token_savings)
-                } else {
-                    String::new()
-                };
+        let result = registry
+            .execute("read_file_compressed", json!({"path": "../outside.txt"}))
+            .await
+            .unwrap();
+        assert_eq!(result.is_error, Some(true));
+    }
 
-                Ok(ToolResult::text(format!("{}{}", header, compressed.output)))
-            }
-            Err(e) => Ok(ToolResult::error(e)),
-        }
+    #[tokio::test]
+    async fn test_read_file_compressed_outline_from_parser() {
+        let (ctx, temp) = setup_ctx().await;
+        let mut content = String::from("use std::fmt;\n\npub struct Parser;\n\nimpl Parser {\n");
+        content.push_str("    pub fn parse(&self, input: &str) -> usize {\n");
+        content.extend((0..250).map(|i| format!("        let _x{} = {};\n", i, i)));
+        content.push_str("        input.len()\n    }\n}\n");
+        std::fs::create_dir_all(temp.path().join("src")).unwrap();
+        std::fs::write(temp.path().join("src/parser.rs"), content).unwrap();
+        let registry = ToolRegistry::new(ctx);
+
+        // Not indexed: the outline comes from parsing the file
+        let result = registry
+            .execute("read_file_compressed", json!({"path": "src/parser.rs"}))
+            .await
+            .unwrap();
+        let Content::Text { text } = &result.content[0];
+        assert!(text.contains("Imports:\n    1  use std::fmt;\n"), "{}", text);
+        assert!(text.contains("L6-258 [function] pub fn parse(&self, input: &str) -> usize"), "{}", text);
+        assert!(!text.contains("_x100"));
     }
 
-    async fn handle_compression_stats(&self, args: Value) -> Result<ToolResult> {
-        let reset = args.get("reset").and_then(|v| v.as_bool()).unwrap_or(false);
+    #[tokio::test]
+    async fn test_read_file_compressed_outline_from_slim_graph() {
+        let (ctx, temp) = setup_ctx().await;
+        let mut content = String::from("pub fn parse(input: &str) -> usize {\n");
+        content.extend((0..250).map(|i| format!("    let _x{} = {};\n", i, i)));
+        content.push_str("    input.len()\n}\n");
+        std::fs::write(temp.path().join("a.rs"), content).unwrap();
+        let registry = ToolRegistry::new(ctx.clone());
+        registry.execute("index_project", json!({"full": true})).await.unwrap();
+        let settings = crate::config::GraphConfig {
+            mode: crate::config::GraphMode::Slim,
+            ..Default::default()
+        };
+        *ctx.graph.write().await = CodeGraph::load(&ctx.store, &settings).await.unwrap();
 
-        let mut analytics = self.ctx.compression_analytics.lock().await;
-        let report = analytics.format_report();
-        let stats_json = analytics.to_json();
+        // With Rust disabled the file can't be parsed, so the outline comes from the graph
+        std::fs::create_dir_all(temp.path().join(".codegraph")).unwrap();
+        std::fs::write(
+            temp.path().join(".codegraph/config.toml"),
+            "[indexing.languages.rust]\nenabled = false\n",
+        )
+        .unwrap();
+        registry.execute("reload_config", json!({})).await.unwrap();
 
-        if reset {
-            analytics.reset();
-        }
+        let result = registry
+            .execute("read_file_compressed", json!({"path": "a.rs"}))
+            .await
+            .unwrap();
+        let Content::Text { text } = &result.content[0];
+        assert!(text.contains("[function] pub fn parse(input: &str) -> usize"), "{}", text);
+    }
 
-        Ok(ToolResult::text(format!("{}\n\nJSON:\n{}", report, serde_json::to_string_pretty(&stats_json)?)))
+    #[tokio::test]
+    async fn test_config_reload() {
+        let (ctx, temp) = setup_ctx().await;
+        let registry = ToolRegistry::new(ctx);
+        let config_path = temp.path().join(".codegraph/config.toml");
+        std::fs::create_dir_all(temp.path().join(".codegraph")).unwrap();
+
+        // Edits are applied on the next tool call
+        std::fs::write(&config_path, "[bash]\ndeny = [\"^echo\"]\n").unwrap();
+        let result = registry.execute("bash_compressed", json!({"command": "echo hi"})).await.unwrap();
+        assert_eq!(result.is_error, Some(true));
+
+        let result = registry.execute("reload_config", json!({})).await.unwrap();
+        let Content::Text { text } = &result.content[0];
+        assert_eq!(text, "No config changes");
+
+        // A broken file is reported and the previous settings stay in effect
+        std::fs::write(&config_path, "[bash\n").unwrap();
+        let result = registry.execute("reload_config", json!({})).await.unwrap();
+        assert_eq!(result.is_error, Some(true));
+        let result = registry.execute("bash_compressed", json!({"command": "echo hi"})).await.unwrap();
+        assert_eq!(result.is_error, Some(true));
+
+        std::fs::write(&config_path, "[bash]\ndeny = []\n").unwrap();
+        let result = registry.execute("reload_config", json!({})).await.unwrap();
+        let Content::Text { text } = &result.content[0];
+        assert_eq!(text, "✅ Reloaded config.toml (changed: bash)");
+        let result = registry.execute("bash_compressed", json!({"command": "echo hi"})).await.unwrap();
+        assert_eq!(result.is_error, None);
     }
-}
 
-fn parse_task_status(s: &str) -> crate::session::TaskStatus {
-    match s {
-        "pending" => crate::session::TaskStatus::Pending,
-        "in_progress" => crate::session::TaskStatus::InProgress,
-        "completed" => crate::session::TaskStatus::Completed,
-        "blocked" => crate::session::TaskStatus::Blocked,
-        _ => crate::session::TaskStatus::Pending,
+    #[tokio::test]
+    async fn test_tool_defaults_from_config() {
+        let (ctx, temp) = setup_ctx().await;
+        let registry = ToolRegistry::new(ctx);
+        std::fs::create_dir_all(temp.path().join(".codegraph")).unwrap();
+        std::fs::write(temp.path().join(".codegraph/config.toml"), "[tools]\nsearch_limit = 25\ngrep_context = 0\n")
+            .unwrap();
+        std::fs::write(temp.path().join("a.txt"), "before\nneedle\nafter\n").unwrap();
+
+        let result = registry.execute("grep_project", json!({"pattern": "needle"})).await.unwrap();
+        let Content::Text { text } = &result.content[0];
+        assert!(text.contains("L2: needle"));
+        assert!(!text.contains("before"), "{}", text);
+
+        let tools = registry.list();
+        let search = tools.iter().find(|t| t.name == "search_symbols").unwrap();
+        assert_eq!(search.input_schema["properties"]["limit"]["default"], 25);
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use crate::code::Indexer;
-    use crate::config::Config;
-    use crate::session::SessionManager;
-    use crate::store::{CodeGraph, Store};
-    use tempfile::TempDir;
+    #[tokio::test]
+    async fn test_export_import_learnings() {
+        let (ctx, temp) = setup_ctx().await;
+        let registry = ToolRegistry::new(ctx.clone());
+        let args = json!({"intent": "Validate request bodies with serde before handlers", "examples": ["Json<Body>"]});
+        registry.execute("extract_pattern", args).await.unwrap();
+
+        let result = registry.execute("export_learnings", json!({})).await.unwrap();
+        let Content::Text { text } = &result.content[0];
+        assert!(text.starts_with("✅ Wrote 1 patterns, 0 failures"), "{}", text);
+        assert!(temp.path().join(DEFAULT_BUNDLE_PATH).exists());
+
+        // Everything in the bundle came from this project, so nothing is new
+        let result = registry.execute("import_learnings", json!({"dry_run": true})).await.unwrap();
+        let Content::Text { text } = &result.content[0];
+        assert!(text.starts_with("Would import .codegraph/learnings-bundle.json"), "{}", text);
+        assert!(text.contains("Patterns: 0 new, 1 duplicate"), "{}", text);
+        assert!(text.contains("pattern \"Validate request bodies"), "{}", text);
+
+        let result = registry.execute("import_learnings", json!({"path": "../bundle.json"})).await.unwrap();
+        assert_eq!(result.is_error, Some(true));
+        let result = registry.execute("import_learnings", json!({"path": "missing.json"})).await.unwrap();
+        assert_eq!(result.is_error, Some(true));
+    }
 
-    async fn setup_ctx() -> (Arc<ToolContext>, TempDir) {
-        let temp_dir = TempDir::new().unwrap();
-        let temp_path = temp_dir.path();
+    #[tokio::test]
+    async fn test_consolidate_patterns() {
+        let (ctx, _temp) = setup_ctx().await;
+        let registry = ToolRegistry::new(ctx.clone());
+        for (intent, example) in [
+            ("Validate request bodies with serde before handlers", "#[derive(Deserialize)]"),
+            ("Validate request body with serde before the handler", "Json<Body>"),
+        ] {
+            let args = json!({"intent": intent, "examples": [example]});
+            registry.execute("extract_pattern", args).await.unwrap();
+        }
 
-        let config = Arc::new(Config {
-            project_root: temp_path.to_path_buf(),
-            cache_dir: temp_path.join("cache"),
-            codegraph_dir: temp_path.join(".codegraph"),
-            store_db_path: temp_path.join("cache/store.db"),
-            learning_db_path: temp_path.join(".codegraph/learning.db"),
-            settings: crate::config::ConfigFile::default(),
-        });
+        let result = registry.execute("consolidate_patterns", json!({"dry_run": true})).await.unwrap();
+        let Content::Text { text } = &result.content[0];
+        assert!(text.starts_with("Would merge 1 duplicate patterns into 1:"), "{}", text);
 
-        config.ensure_dirs().unwrap();
+        let result = registry.execute("consolidate_patterns", json!({})).await.unwrap();
+        let Content::Text { text } = &result.content[0];
+        assert!(text.starts_with("Merged 1 duplicate patterns into 1:"), "{}", text);
+        assert!(text.contains("2 examples"), "{}", text);
 
-        let store = Arc::new(Store::open(&config).await.unwrap());
-        let graph = Arc::new(RwLock::new(
-            CodeGraph::load_from_store(&store).await.unwrap(),
-        ));
-        let indexer = Arc::new(Indexer::new(store.clone(), config.clone()));
-        let session_manager = Arc::new(SessionManager::new(store.clone(), graph.clone()));
+        let result = registry.execute("consolidate_patterns", json!({})).await.unwrap();
+        let Content::Text { text } = &result.content[0];
+        assert!(text.starts_with("No duplicate patterns among 1"), "{}", text);
 
-        let pattern_store = Arc::new(crate::learning::patterns::PatternStore::new(Arc::new(
-            store.learning_db.clone(),
-        )));
-        let failure_store = Arc::new(crate::learning::failures::FailureStore::new(Arc::new(
-            store.learning_db.clone(),
-        )));
-        let lineage_store = Arc::new(crate::learning::lineage::LineageStore::new(Arc::new(
-            store.learning_db.clone(),
-        )));
-        let niche_store = Arc::new(crate::learning::niches::NicheStore::new(Arc::new(
-            store.learning_db.clone(),
-        )));
-        let manual_instruction_store = Arc::new(crate::skill::distill::ManualInstructionStore::new(Arc::new(
-            store.learning_db.clone(),
-        )));
-        let cross_language_inferrer = Arc::new(crate::code::CrossLanguageInferrer::new(store.clone()));
+        let id = ctx.pattern_store.list_all().await.unwrap()[0].id.clone();
+        let result = registry.execute("consolidate_patterns", json!({"pattern_id": id})).await.unwrap();
+        let Content::Text { text } = &result.content[0];
+        assert!(text.contains("(1 merged)"), "{}", text);
+    }
 
-        let ctx = Arc::new(ToolContext {
-            store,
-            config,
-            indexer,
-            graph,
-            session_manager,
-            pattern_store,
-            failure_store,
-            lineage_store,
-            niche_store,
-            manual_instruction_store,
-            cross_language_inferrer,
-            compression_analytics: tokio::sync::Mutex::new(crate::compress::CompressionAnalytics::new()),
+    #[tokio::test]
+    async fn test_learning_defaults_from_config() {
+        let (ctx, temp) = setup_ctx().await;
+        let registry = ToolRegistry::new(ctx);
+        std::fs::create_dir_all(temp.path().join(".codegraph")).unwrap();
+        let config_toml = r#"
+[learning]
+pattern_confidence = 0.5
+recall_min_confidence = 0.6
+reflection_validation = "strict"
+"#;
+        std::fs::write(temp.path().join(".codegraph/config.toml"), config_toml).unwrap();
+
+        let args = json!({"intent": "Retry flaky network calls", "examples": ["wrap calls in backoff()"]});
+        let result = registry.execute("extract_pattern", args).await.unwrap();
+        let Content::Text { text } = &result.content[0];
+        assert!(text.contains("Confidence: 50.0%"), "{}", text);
+
+        let result = registry.execute("recall_patterns", json!({"description": "retry network calls"})).await.unwrap();
+        let Content::Text { text } = &result.content[0];
+        assert!(text.starts_with("No matching patterns found"), "{}", text);
+        let args = json!({"description": "retry network calls", "min_confidence": 0.0});
+        let result = registry.execute("recall_patterns", args).await.unwrap();
+        let Content::Text { text } = &result.content[0];
+        assert!(text.contains("Retry flaky network calls"), "{}", text);
+
+        // Strict validation rejects a lesson that isn't "When X, do Y because Z"
+        let args = json!({"task": "Fix timeouts", "plan": "Add retries"});
+        let result = registry.execute("record_attempt", args).await.unwrap();
+        let Content::Text { text } = &result.content[0];
+        let attempt_id = text.lines().find_map(|l| l.strip_prefix("ID: ")).unwrap();
+        let args = json!({
+            "attempt_id": attempt_id,
+            "intent": "Fix timeouts",
+            "root_cause": "The HTTP client had no retry on connection resets",
+            "lesson": "Retries help",
         });
+        assert!(registry.execute("reflect", args).await.is_err());
+    }
 
-        (ctx, temp_dir)
+    #[tokio::test]
+    async fn test_validate_config_tool() {
+        let (ctx, temp) = setup_ctx().await;
+        let registry = ToolRegistry::new(ctx);
+        let config_path = temp.path().join(".codegraph/config.toml");
+        std::fs::create_dir_all(temp.path().join(".codegraph")).unwrap();
+
+        std::fs::write(&config_path, "[compression]\nmax_lines = 30\nmax_line = 5\n").unwrap();
+        let result = registry.execute("validate_config", json!({})).await.unwrap();
+        assert_eq!(result.is_error, None);
+        let Content::Text { text } = &result.content[0];
+        assert!(text.starts_with("✅ Config is valid (1 warnings)"), "{}", text);
+        assert!(text.contains("unknown key 'compression.max_line'"));
+        assert!(text.contains("Effective configuration:\n"));
+        assert!(text.contains("max_lines = 30"));
+
+        std::fs::write(&config_path, "[compression\n").unwrap();
+        let result = registry.execute("validate_config", json!({})).await.unwrap();
+        assert_eq!(result.is_error, Some(true));
+        let Content::Text { text } = &result.content[0];
+        assert!(text.starts_with("❌ 1 errors, 0 warnings"), "{}", text);
+        assert!(!text.contains("Effective configuration"));
     }
 
     #[tokio::test]
-    async fn test_list_tools() {
-        let (ctx, _temp) = setup_ctx().await;
+    async fn test_git_context_tool() {
+        let (ctx, temp) = setup_ctx().await;
         let registry = ToolRegistry::new(ctx);
-        let tools = registry.list();
 
-        assert!(tools.len() >= 26); // 10 original + 9 phase 4-5 + 7 phase 6-9
-        assert!(tools.iter().any(|t| t.name == "search_symbols"));
-        assert!(tools.iter().any(|t| t.name == "smart_context"));
-        assert!(tools.iter().any(|t| t.name == "index_project"));
-        // Learning tools
-        assert!(tools.iter().any(|t| t.name == "recall_patterns"));
-        assert!(tools.iter().any(|t| t.name == "record_attempt"));
-        assert!(tools.iter().any(|t| t.name == "reflect"));
-        // Phase 6-9 tools
-        assert!(tools.iter().any(|t| t.name == "list_niches"));
-        assert!(tools.iter().any(|t| t.name == "distill_project_skill"));
-        assert!(tools.iter().any(|t| t.name == "sync_learnings"));
+        // Not a repository yet
+        let result = registry.execute("git_context", json!({})).await.unwrap();
+        assert_eq!(result.is_error, Some(true));
+
+        let git = |args: &[&str]| {
+            let status = std::process::Command::new("git")
+                .args(["-c", "user.name=Test", "-c", "user.email=test@example.com"])
+                .args(args)
+                .current_dir(temp.path())
+                .output()
+                .unwrap()
+                .status;
+            assert!(status.success(), "git {:?}", args);
+        };
+        git(&["init", "-q", "-b", "main"]);
+        std::fs::write(temp.path().join("a.txt"), "one\n").unwrap();
+        git(&["add", "a.txt"]);
+        git(&["commit", "-q", "-m", "Initial commit"]);
+        std::fs::write(temp.path().join("a.txt"), "two\n").unwrap();
+
+        let result = registry.execute("git_context", json!({})).await.unwrap();
+        let Content::Text { text } = &result.content[0];
+        assert!(text.starts_with("🌿 main\n"), "{}", text);
+        assert!(text.contains("📝 Modified (1)\n  a.txt"), "{}", text);
+        assert!(text.contains("a.txt (+1 -1, 1 hunks)"), "{}", text);
+        assert!(text.contains("📜 1 commits"), "{}", text);
     }
 
     #[tokio::test]
-    async fn test_unknown_tool() {
-        let (ctx, _temp) = setup_ctx().await;
+    async fn test_grep_project_tool() {
+        let (ctx, temp) = setup_ctx().await;
+        std::fs::create_dir_all(temp.path().join("src")).unwrap();
+        std::fs::write(temp.path().join("src/a.rs"), "fn alpha() {}\nfn beta() {}\n").unwrap();
+        std::fs::write(temp.path().join("src/b.py"), "def alpha(): pass\n").unwrap();
         let registry = ToolRegistry::new(ctx);
-        let result = registry.execute("unknown", json!({})).await.unwrap();
 
-        assert!(result.is_error == Some(true));
+        let result = registry
+            .execute("grep_project", json!({"pattern": "alpha", "path": "src", "glob": ["*.rs"], "context": 0}))
+            .await
+            .unwrap();
+        let Content::Text { text } = &result.content[0];
+        assert_eq!(text, "🔍 1 matches in 1 files\n\n📄 a.rs (1 matches)\n  L1: fn alpha() {}");
+
+        let result = registry.execute("grep_project", json!({"pattern": "("})).await.unwrap();
+        assert_eq!(result.is_error, Some(true));
     }
 
     #[tokio::test]
-    async fn test_search_empty_graph() {
+    async fn test_bash_compressed_expand_output() {
         let (ctx, _temp) = setup_ctx().await;
         let registry = ToolRegistry::new(ctx);
+
         let result = registry
-            .execute("search_symbols", json!({"query": "foo"}))
+            .execute("bash_compressed", json!({"command": "for i in $(seq 1 200); do echo same; done; echo needle"}))
             .await
             .unwrap();
+        let Content::Text { text } = &result.content[0];
+        let id = text
+            .split("expand_output id=")
+            .nth(1)
+            .and_then(|rest| rest.split_whitespace().next())
+            .expect("compressed header should include an output id");
 
-        // Should return "no symbols found", not an error
-        assert!(result.is_error.is_none());
+        let result = registry
+            .execute("expand_output", json!({"id": id, "pattern": "needle", "context": 0}))
+            .await
+            .unwrap();
+        let Content::Text { text } = &result.content[0];
+        assert!(text.ends_with("  201  needle"), "{}", text);
+
+        let result = registry.execute("expand_output", json!({"id": "out-999"})).await.unwrap();
+        assert_eq!(result.is_error, Some(true));
     }
 
     #[tokio::test]
-    async fn test_session_lifecycle() {
+    async fn test_bash_compressed_links_stack_frames() {
         let (ctx, _temp) = setup_ctx().await;
+        let data = json!({"name": "job", "file": "app/jobs.py", "line_start": 6, "line_end": 9});
+        ctx.graph.write().await.add_node("app/jobs.py::job".into(), "function".into(), data);
         let registry = ToolRegistry::new(ctx);
 
-        // Start session
+        let trace = [
+            "Traceback (most recent call last):",
+            "  File \"app/main.py\", line 12, in <module>",
+            "    run()",
+            "  File \"/usr/lib/python3.11/site-packages/rq/worker.py\", line 40, in run",
+            "    job()",
+            "  File \"app/jobs.py\", line 8, in job",
+            "    raise ValueError(\"bad input\")",
+            "ValueError: bad input",
+        ];
+        let args: Vec<String> = trace.iter().map(|line| format!("'{}'", line)).collect();
+        let command = format!("printf '%s\\n' {}", args.join(" "));
+        let result = registry.execute("bash_compressed", json!({"command": command})).await.unwrap();
+        let Content::Text { text } = &result.content[0];
+
+        assert!(text.contains("  File \"app/jobs.py\", line 8, in job → app/jobs.py::job\n"), "{}", text);
+        assert!(text.contains("  File \"app/main.py\", line 12, in <module>\n"));
+        assert!(text.contains("… 1 framework frame"));
+    }
+
+    #[tokio::test]
+    async fn test_export_openapi() {
+        let (ctx, temp) = setup_ctx().await;
+        let registry = ToolRegistry::new(ctx);
+
+        let result = registry.execute("export_openapi", json!({})).await.unwrap();
+        let Content::Text { text } = &result.content[0];
+        assert!(text.starts_with("No server routes detected"), "{}", text);
+
+        std::fs::write(
+            temp.path().join("app.py"),
+            "@app.get(\"/api/items/{item_id}\")\ndef read_item(item_id: int):\n    return item_id\n",
+        )
+        .unwrap();
+        registry.execute("index_project", json!({"full": true})).await.unwrap();
+
         let result = registry
-            .execute(
-                "start_session",
-                json!({"task": "Build feature", "items": ["Step 1", "Step 2"]}),
-            )
+            .execute("export_openapi", json!({"title": "Items", "output": "docs/openapi.json"}))
             .await
             .unwrap();
-        assert!(result.is_error.is_none());
+        let Content::Text { text } = &result.content[0];
+        assert_eq!(text, "✅ Wrote draft OpenAPI spec with 1 operations to docs/openapi.json");
+
+        let spec: Value =
+            serde_json::from_str(&std::fs::read_to_string(temp.path().join("docs/openapi.json")).unwrap()).unwrap();
+        assert_eq!(spec["info"]["title"], "Items");
+        let operation = &spec["paths"]["/api/items/{item_id}"]["get"];
+        assert_eq!(operation["operationId"], "read_item");
+        assert_eq!(operation["x-codegraph-handler"], "app.py::read_item");
+
+        let result = registry.execute("export_openapi", json!({"output": "../spec.json"})).await.unwrap();
+        assert_eq!(result.is_error, Some(true));
+    }
+
+    #[tokio::test]
+    async fn test_confirm_and_reject_connections() {
+        let (ctx, temp) = setup_ctx().await;
+        let registry = ToolRegistry::new(ctx);
+        std::fs::write(temp.path().join("client.ts"), "fetch('/api/items');\nfetch('/api/status');\n").unwrap();
+        std::fs::write(
+            temp.path().join("app.py"),
+            "@app.get('/api/items')\ndef items(): ...\n@app.get('/status')\ndef status(): ...\n",
+        )
+        .unwrap();
+        registry.execute("index_project", json!({"full": true})).await.unwrap();
+        registry.execute("infer_cross_edges", json!({"force_rebuild": true})).await.unwrap();
+
+        let connection =
+            |api_path: &str| json!({"client_file": "client.ts", "server_file": "app.py", "api_path": api_path});
+        let result = registry.execute("confirm_connection", connection("api/items")).await.unwrap();
+        let Content::Text { text } = &result.content[0];
+        assert_eq!(text, "✅ Confirmed client.ts → app.py (api/items), locked at 100% confidence");
+        let result = registry.execute("reject_connection", connection("status")).await.unwrap();
+        let Content::Text { text } = &result.content[0];
+        assert!(text.starts_with("🚫 Rejected client.ts → app.py (status)"), "{}", text);
+        let result = registry.execute("reject_connection", connection("api/orders")).await.unwrap();
+        assert_eq!(result.is_error, Some(true));
+
+        registry.execute("infer_cross_edges", json!({"force_rebuild": true})).await.unwrap();
+        let result = registry.execute("get_api_connections", json!({"path": "client.ts"})).await.unwrap();
+        let Content::Text { text } = &result.content[0];
+        assert!(text.contains("**Confidence:** 100.0% (confirmed)"), "{}", text);
+        assert!(!text.contains("**Path:** status"), "{}", text);
+    }
+
+    #[tokio::test]
+    async fn test_reject_connection_updates_graph() {
+        let (ctx, temp) = setup_ctx().await;
+        let registry = ToolRegistry::new(ctx);
+        let root = temp.path();
+        for dir in ["proto", "client", "server"] {
+            std::fs::create_dir_all(root.join(dir)).unwrap();
+        }
+        std::fs::write(
+            root.join("proto/users.proto"),
+            "package users;\nservice UserService {\n  rpc GetUser (GetUserRequest) returns (User);\n}\n",
+        )
+        .unwrap();
+        std::fs::write(root.join("client/app.py"), "stub = users_pb2_grpc.UserServiceStub(channel)\nstub.GetUser(req)\n")
+            .unwrap();
+        std::fs::write(
+            root.join("server/main.go"),
+            "package main\n\nfunc main() { pb.RegisterUserServiceServer(s, &server{}) }\n\nfunc (s *server) GetUser(ctx context.Context) {}\n",
+        )
+        .unwrap();
+        registry.execute("index_project", json!({"full": true})).await.unwrap();
+        registry.execute("infer_cross_edges", json!({"force_rebuild": true})).await.unwrap();
+
+        let neighbors = json!({"id": "file::client/app.py", "edge_types": ["calls_api"], "compact": false});
+        let result = registry.execute("get_neighbors", neighbors.clone()).await.unwrap();
+        let Content::Text { text } = &result.content[0];
+        assert!(text.contains("GetUser"), "{}", text);
+
+        let connection = json!({
+            "client_file": "client/app.py",
+            "server_file": "server/main.go",
+            "api_path": "/users.UserService/GetUser",
+        });
+        let result = registry.execute("reject_connection", connection).await.unwrap();
+        assert_ne!(result.is_error, Some(true));
+
+        let result = registry.execute("get_neighbors", neighbors).await.unwrap();
+        let Content::Text { text } = &result.content[0];
+        assert!(!text.contains("GetUser"), "{}", text);
+    }
+
+    #[tokio::test]
+    async fn test_get_api_connections_by_endpoint_and_handler() {
+        let (ctx, temp) = setup_ctx().await;
+        let registry = ToolRegistry::new(ctx);
+        std::fs::write(temp.path().join("client.ts"), "export const add = () => axios.post('/api/orders');\n").unwrap();
+        std::fs::write(
+            temp.path().join("orders.py"),
+            concat!(
+                "@app.post(\"/api/orders\")\ndef create_order():\n    return 1\n\n",
+                "@app.get(\"/api/orders\")\ndef list_orders():\n    return []\n",
+            ),
+        )
+        .unwrap();
+        registry.execute("index_project", json!({"full": true})).await.unwrap();
+        registry.execute("infer_cross_edges", json!({"force_rebuild": true})).await.unwrap();
 
-        // Get session
         let result = registry
-            .execute("get_session", json!({}))
+            .execute("get_api_connections", json!({"endpoint": "POST /api/orders"}))
             .await
             .unwrap();
-        assert!(result.is_error.is_none());
+        let Content::Text { text } = &result.content[0];
+        assert!(text.starts_with("# API Connections for POST /api/orders"), "{}", text);
+        assert!(text.contains("## client.ts → orders.py"), "{}", text);
 
-        // Smart context
         let result = registry
-            .execute("smart_context", json!({}))
+            .execute("get_api_connections", json!({"endpoint": "/api/orders", "method": "get"}))
             .await
             .unwrap();
-        assert!(result.is_error.is_none());
+        let Content::Text { text } = &result.content[0];
+        assert_eq!(text, "No API connections found for 'GET /api/orders'");
+
+        let result = registry
+            .execute("get_api_connections", json!({"symbol": "orders.py::create_order"}))
+            .await
+            .unwrap();
+        let Content::Text { text } = &result.content[0];
+        assert!(text.contains("- POST /api/orders (orders.py:1)"), "{}", text);
+        assert!(text.contains("## client.ts → orders.py"), "{}", text);
+
+        let result = registry
+            .execute("get_api_connections", json!({"symbol": "orders.py::list_orders"}))
+            .await
+            .unwrap();
+        let Content::Text { text } = &result.content[0];
+        assert!(text.contains("- GET /api/orders (orders.py:5)"), "{}", text);
+        assert!(text.ends_with("No client calls found."), "{}", text);
+
+        let result = registry.execute("get_api_connections", json!({})).await.unwrap();
+        assert_eq!(result.is_error, Some(true));
+    }
+
+    #[tokio::test]
+    async fn test_where_is_env_used() {
+        let (ctx, temp) = setup_ctx().await;
+        let registry = ToolRegistry::new(ctx);
+
+        std::fs::write(temp.path().join(".env"), "DATABASE_URL=postgres://localhost\nUNUSED_FLAG=1\n").unwrap();
+        std::fs::write(
+            temp.path().join("app.py"),
+            "import os\n\nurl = os.environ[\"DATABASE_URL\"]\nkey = os.getenv(\"API_KEY\")\n",
+        )
+        .unwrap();
+        registry.execute("index_project", json!({"full": true})).await.unwrap();
+        registry.execute("infer_cross_edges", json!({"force_rebuild": true})).await.unwrap();
+
+        let result = registry
+            .execute("where_is_env_used", json!({"name": "DATABASE_URL"}))
+            .await
+            .unwrap();
+        let Content::Text { text } = &result.content[0];
+        assert!(text.contains("## Defined in\n- .env:1"), "{}", text);
+        assert!(text.contains("## Read in\n- app.py:3"), "{}", text);
+
+        let result = registry.execute("where_is_env_used", json!({})).await.unwrap();
+        let Content::Text { text } = &result.content[0];
+        assert!(text.contains("- API_KEY: 0 definitions, 1 reads ⚠️ never defined"), "{}", text);
+        assert!(text.contains("- UNUSED_FLAG: 1 definitions, 0 reads (never read)"), "{}", text);
+    }
+
+    #[tokio::test]
+    async fn test_get_table_usage() {
+        let (ctx, temp) = setup_ctx().await;
+        let registry = ToolRegistry::new(ctx);
+
+        std::fs::create_dir_all(temp.path().join("migrations")).unwrap();
+        std::fs::write(temp.path().join("migrations/001.sql"), "CREATE TABLE users (id INTEGER);\n").unwrap();
+        std::fs::write(
+            temp.path().join("repo.py"),
+            "def active_users(db):\n    return db.execute(\"SELECT * FROM users WHERE active\")\n",
+        )
+        .unwrap();
+        std::fs::write(
+            temp.path().join("jobs.ts"),
+            "export function purge(db) {\n  return db.query('DELETE FROM users WHERE deleted');\n}\n",
+        )
+        .unwrap();
+        registry.execute("index_project", json!({"full": true})).await.unwrap();
+        registry.execute("infer_cross_edges", json!({"force_rebuild": true})).await.unwrap();
+
+        let result = registry.execute("get_table_usage", json!({"table": "Users"})).await.unwrap();
+        let Content::Text { text } = &result.content[0];
+        assert!(text.contains("## Defined in (1)\n- migrations/001.sql:1"), "{}", text);
+        assert!(text.contains("## Writes (1)\n- jobs.ts::purge (jobs.ts:2)"), "{}", text);
+        assert!(text.contains("## Reads (1)\n- repo.py::active_users (repo.py:2)"), "{}", text);
+
+        let result = registry.execute("get_table_usage", json!({"table": "orders"})).await.unwrap();
+        let Content::Text { text } = &result.content[0];
+        assert!(text.starts_with("No code found touching table 'orders'"), "{}", text);
+    }
+
+    #[tokio::test]
+    async fn test_shell_session_tool() {
+        let (ctx, _temp) = setup_ctx().await;
+        let registry = ToolRegistry::new(ctx);
+
+        registry
+            .execute("shell_session", json!({"command": "export STEP=one"}))
+            .await
+            .unwrap();
+        let result = registry
+            .execute("shell_session", json!({"command": "echo $STEP"}))
+            .await
+            .unwrap();
+        let Content::Text { text } = &result.content[0];
+        assert!(text.contains("exit 0"), "{}", text);
+        assert!(text.ends_with("one"), "{}", text);
+
+        let result = registry
+            .execute("shell_session", json!({"command": "echo ${STEP:-unset}", "reset": true}))
+            .await
+            .unwrap();
+        let Content::Text { text } = &result.content[0];
+        assert!(text.ends_with("unset"), "{}", text);
     }
 }
@@ -1,11 +1,79 @@
-use anyhow::Result;
+use anyhow::{Context, Result};
+use axum::extract::{Request, State};
+use axum::http::{header, HeaderMap, HeaderValue, StatusCode};
+use axum::middleware::{self, Next};
+use axum::response::sse::{Event, Sse};
+use axum::response::{IntoResponse, Response};
+use axum::routing::{get, post};
+use axum::{Json, Router};
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::{HashSet, VecDeque};
+use std::net::SocketAddr;
+use std::sync::atomic::AtomicBool;
+use std::sync::{Arc, Mutex};
 use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
-use tracing::{debug, error, trace};
+use tokio::sync::{mpsc, oneshot};
+use tokio_stream::wrappers::UnboundedReceiverStream;
+use tokio_stream::StreamExt;
+use tracing::{debug, error, info, trace};
 
-use super::protocol::{JsonRpcRequest, JsonRpcResponse};
+use super::protocol::{
+    JsonRpcNotification, JsonRpcRequest, JsonRpcResponse, INVALID_REQUEST, PARSE_ERROR,
+};
+
+/// Channel for notifications sent while a request is being handled
+pub type Notifier = mpsc::UnboundedSender<JsonRpcNotification>;
 
 pub trait Handler: Send + Sync {
     async fn handle(&self, request: JsonRpcRequest) -> JsonRpcResponse;
+
+    /// Receive the channel for server-initiated notifications
+    fn set_notifier(&self, _notifier: Notifier) {}
+
+    /// The client cancelled a request. Called while that request is being handled, so the
+    /// work can stop early.
+    fn cancel(&self, _request_id: &Value) {}
+
+    /// Runtime metrics in the Prometheus text format, once there are any to report
+    fn metrics(&self) -> Option<String> {
+        None
+    }
+}
+
+/// Sends progress notifications for one request's progress token
+#[derive(Clone)]
+pub struct ProgressReporter {
+    notifier: Notifier,
+    token: Value,
+    count: std::sync::Arc<std::sync::atomic::AtomicU64>,
+}
+
+impl ProgressReporter {
+    pub fn new(notifier: Notifier, token: Value) -> Self {
+        Self {
+            notifier,
+            token,
+            count: Default::default(),
+        }
+    }
+
+    /// Send a progress update; progress values increase with each call
+    pub fn report(&self, message: &str) {
+        let progress = self.count.fetch_add(1, std::sync::atomic::Ordering::Relaxed) + 1;
+        // The receiver is gone only when the transport is shutting down
+        let _ = self
+            .notifier
+            .send(JsonRpcNotification::progress(&self.token, progress, message));
+    }
+}
+
+/// Per-request state handed to tools: progress notifications if the client asked for them,
+/// and whether the client has cancelled the request
+#[derive(Clone, Default)]
+pub struct RequestControl {
+    pub progress: Option<ProgressReporter>,
+    pub cancelled: Arc<AtomicBool>,
 }
 
 /// Run the MCP server over stdio transport
@@ -18,24 +86,28 @@ pub async fn run_stdio<H: Handler>(handler: H) -> Result<()> {
     let reader = BufReader::new(stdin);
     let mut lines = reader.lines();
 
+    let (notifier, mut notifications) = mpsc::unbounded_channel();
+    handler.set_notifier(notifier);
+
     debug!("MCP server started, listening on stdio");
 
-    while let Some(line) = lines.next_line().await? {
-        trace!("Received line: {}", line);
-
-        // Parse JSON-RPC request
-        let request: JsonRpcRequest = match serde_json::from_str(&line) {
-            Ok(req) => req,
-            Err(e) => {
-                error!("Failed to parse request: {}", e);
-                let error_response = JsonRpcResponse::error(
-                    None,
-                    super::protocol::PARSE_ERROR,
-                    format!("Parse error: {}", e),
-                );
-                write_response(&mut stdout, &error_response).await?;
-                continue;
-            }
+    // Requests that arrived while another was being handled
+    let mut queued = VecDeque::new();
+    let mut eof = false;
+    loop {
+        let request = match queued.pop_front() {
+            Some(request) => request,
+            None if eof => break,
+            None => match lines.next_line().await? {
+                Some(line) => match parse_line(&line) {
+                    Ok(request) => request,
+                    Err(error_response) => {
+                        write_message(&mut stdout, &error_response).await?;
+                        continue;
+                    }
+                },
+                None => break,
+            },
         };
 
         debug!(
@@ -43,12 +115,40 @@ pub async fn run_stdio<H: Handler>(handler: H) -> Result<()> {
             request.method, request.id
         );
 
-        // Handle request
-        let response = handler.handle(request).await;
+        // Handle request, forwarding progress notifications while it runs. Input is still
+        // read meanwhile so a cancellation reaches the handler; anything else waits its turn.
+        let response = {
+            let handling = handler.handle(request);
+            tokio::pin!(handling);
+            loop {
+                tokio::select! {
+                    response = &mut handling => break response,
+                    Some(notification) = notifications.recv() => {
+                        write_message(&mut stdout, &notification).await?;
+                    }
+                    line = lines.next_line(), if !eof => match line? {
+                        Some(line) => match parse_line(&line) {
+                            Ok(request) => match request.cancelled_request_id() {
+                                Some(id) => {
+                                    handler.cancel(id);
+                                    // A cancelled request that hasn't started gets no response
+                                    queued.retain(|queued: &JsonRpcRequest| queued.id.as_ref() != Some(id));
+                                }
+                                None => queued.push_back(request),
+                            },
+                            Err(error_response) => write_message(&mut stdout, &error_response).await?,
+                        },
+                        None => eof = true,
+                    },
+                }
+            }
+        };
+        // Progress sent just before completion is stale once the response is out
+        while notifications.try_recv().is_ok() {}
 
         // Write response (unless it's a notification)
         if response.id.is_some() || response.error.is_some() {
-            write_response(&mut stdout, &response).await?;
+            write_message(&mut stdout, &response).await?;
         }
     }
 
@@ -56,12 +156,21 @@ pub async fn run_stdio<H: Handler>(handler: H) -> Result<()> {
     Ok(())
 }
 
-async fn write_response(
+/// Parse one line of stdio input, or return the error response to send for it
+fn parse_line(line: &str) -> std::result::Result<JsonRpcRequest, Box<JsonRpcResponse>> {
+    trace!("Received line: {}", line);
+    serde_json::from_str(line).map_err(|e| {
+        error!("Failed to parse request: {}", e);
+        Box::new(JsonRpcResponse::error(None, PARSE_ERROR, format!("Parse error: {}", e)))
+    })
+}
+
+async fn write_message<T: Serialize>(
     stdout: &mut tokio::io::Stdout,
-    response: &JsonRpcResponse,
+    message: &T,
 ) -> Result<()> {
-    let json = serde_json::to_string(response)?;
-    trace!("Sending response: {}", json);
+    let json = serde_json::to_string(message)?;
+    trace!("Sending message: {}", json);
 
     stdout.write_all(json.as_bytes()).await?;
     stdout.write_all(b"\n").await?;
@@ -70,6 +179,281 @@ async fn write_response(
     Ok(())
 }
 
+/// Endpoint for Streamable HTTP messages
+const MCP_PATH: &str = "/mcp";
+const SESSION_HEADER: &str = "mcp-session-id";
+
+/// A message from an HTTP request to the dispatcher, which handles one at a time like stdio
+enum Incoming {
+    /// The request, and where to send its notifications followed by its response
+    Request(JsonRpcRequest, mpsc::UnboundedSender<String>),
+    Metrics(oneshot::Sender<Option<String>>),
+}
+
+#[derive(Clone)]
+struct HttpState {
+    incoming: mpsc::UnboundedSender<Incoming>,
+    /// Session IDs handed out on initialize
+    sessions: Arc<Mutex<HashSet<String>>>,
+    /// Bound to a loopback address, so browsers on other origins must be kept out
+    local_only: bool,
+    /// Bearer token every request must carry, if the server was started with one
+    token: Option<Arc<str>>,
+}
+
+/// Run the MCP server over Streamable HTTP
+///
+/// Clients POST JSON-RPC messages to `/mcp`. Responses come back as JSON, or as an SSE
+/// stream when the request asks for progress notifications. `initialize` hands out an
+/// `Mcp-Session-Id` that later requests must send, and DELETE ends the session. `/metrics`
+/// serves the `server_metrics` counters in the Prometheus text format.
+///
+/// With a `token`, every request must send it as `Authorization: Bearer <token>`. Tools run
+/// shell commands, so listening on anything but loopback requires one.
+pub async fn run_http<H: Handler>(handler: H, addr: SocketAddr, token: Option<String>) -> Result<()> {
+    let token = token.filter(|token| !token.is_empty());
+    if token.is_none() && !addr.ip().is_loopback() {
+        anyhow::bail!(
+            "Refusing to listen on {} without authentication; pass --token or set CODEGRAPH_TOKEN",
+            addr
+        );
+    }
+    let listener = tokio::net::TcpListener::bind(addr)
+        .await
+        .with_context(|| format!("Failed to bind {}", addr))?;
+    serve_http(handler, listener, token).await
+}
+
+async fn serve_http<H: Handler>(
+    handler: H,
+    listener: tokio::net::TcpListener,
+    token: Option<String>,
+) -> Result<()> {
+    let addr = listener.local_addr()?;
+    let (notifier, notifications) = mpsc::unbounded_channel();
+    handler.set_notifier(notifier);
+
+    let (incoming, requests) = mpsc::unbounded_channel();
+    let state = HttpState {
+        incoming,
+        sessions: Default::default(),
+        local_only: addr.ip().is_loopback(),
+        token: token.map(Arc::from),
+    };
+    let app = Router::new()
+        .route(MCP_PATH, post(post_message).get(get_stream).delete(delete_session))
+        .route("/metrics", get(get_metrics))
+        .layer(middleware::from_fn_with_state(state.clone(), guard))
+        .with_state(state);
+
+    info!("MCP server listening on http://{}{}", addr, MCP_PATH);
+
+    // The handler's futures aren't Send, so it runs here rather than in the server's tasks
+    tokio::select! {
+        result = axum::serve(listener, app) => result?,
+        _ = dispatch(&handler, requests, notifications) => {}
+    }
+    debug!("MCP server shutting down");
+    Ok(())
+}
+
+async fn dispatch<H: Handler>(
+    handler: &H,
+    mut requests: mpsc::UnboundedReceiver<Incoming>,
+    mut notifications: mpsc::UnboundedReceiver<JsonRpcNotification>,
+) {
+    // Requests that arrived while another was being handled
+    let mut queued = VecDeque::new();
+    loop {
+        let incoming = match queued.pop_front() {
+            Some(incoming) => incoming,
+            None => match requests.recv().await {
+                Some(incoming) => incoming,
+                None => break,
+            },
+        };
+        let (request, replies) = match incoming {
+            Incoming::Request(request, replies) => (request, replies),
+            Incoming::Metrics(reply) => {
+                let _ = reply.send(handler.metrics());
+                continue;
+            }
+        };
+        debug!("Processing request: method={}, id={:?}", request.method, request.id);
+
+        let response = {
+            let handling = handler.handle(request);
+            tokio::pin!(handling);
+            loop {
+                tokio::select! {
+                    response = &mut handling => break response,
+                    Some(notification) = notifications.recv() => {
+                        if let Ok(json) = serde_json::to_string(&notification) {
+                            let _ = replies.send(json);
+                        }
+                    }
+                    // Cancellations and metrics go through; other requests wait their turn
+                    Some(incoming) = requests.recv() => match incoming {
+                        Incoming::Request(request, replies) => match request.cancelled_request_id() {
+                            Some(id) => handler.cancel(id),
+                            None => queued.push_back(Incoming::Request(request, replies)),
+                        },
+                        Incoming::Metrics(reply) => {
+                            let _ = reply.send(handler.metrics());
+                        }
+                    },
+                }
+            }
+        };
+        while notifications.try_recv().is_ok() {}
+
+        if response.id.is_some() || response.error.is_some() {
+            match serde_json::to_string(&response) {
+                Ok(json) => {
+                    let _ = replies.send(json);
+                }
+                Err(e) => error!("Failed to serialize response: {}", e),
+            }
+        }
+    }
+}
+
+fn json_rpc_error(status: StatusCode, code: i32, message: &str) -> Response {
+    let body = JsonRpcResponse::error(None, code, message.to_string());
+    (status, Json(body)).into_response()
+}
+
+/// Reject browsers on other sites reaching a server bound to localhost (DNS rebinding)
+fn allowed_origin(state: &HttpState, headers: &HeaderMap) -> bool {
+    let Some(origin) = headers.get(header::ORIGIN).and_then(|v| v.to_str().ok()) else {
+        return true;
+    };
+    let host = origin.split("://").nth(1).unwrap_or(origin);
+    let host = host.rsplit_once(':').filter(|(_, port)| port.parse::<u16>().is_ok()).map_or(host, |(h, _)| h);
+    !state.local_only || matches!(host, "localhost" | "127.0.0.1" | "[::1]")
+}
+
+/// Check the bearer token, when the server was started with one
+fn authorized(state: &HttpState, headers: &HeaderMap) -> bool {
+    let Some(token) = &state.token else {
+        return true;
+    };
+    let sent = headers
+        .get(header::AUTHORIZATION)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.strip_prefix("Bearer "));
+    // Compare every byte, so response times don't reveal how much of the token matched
+    sent.is_some_and(|sent| {
+        sent.len() == token.len() && sent.bytes().zip(token.bytes()).fold(0, |diff, (a, b)| diff | (a ^ b)) == 0
+    })
+}
+
+/// Refuse requests from browser pages on other origins and requests without the token,
+/// before any route handles them
+async fn guard(State(state): State<HttpState>, request: Request, next: Next) -> Response {
+    if !allowed_origin(&state, request.headers()) {
+        return StatusCode::FORBIDDEN.into_response();
+    }
+    if !authorized(&state, request.headers()) {
+        return (StatusCode::UNAUTHORIZED, [(header::WWW_AUTHENTICATE, "Bearer")]).into_response();
+    }
+    next.run(request).await
+}
+
+/// Check the session header, or return the response to send instead
+fn check_session(state: &HttpState, headers: &HeaderMap) -> Option<Response> {
+    let Some(id) = headers.get(SESSION_HEADER).and_then(|v| v.to_str().ok()) else {
+        return Some(json_rpc_error(StatusCode::BAD_REQUEST, INVALID_REQUEST, "Missing Mcp-Session-Id header"));
+    };
+    let known = state.sessions.lock().unwrap_or_else(|e| e.into_inner()).contains(id);
+    (!known).then(|| json_rpc_error(StatusCode::NOT_FOUND, INVALID_REQUEST, "Unknown or expired session"))
+}
+
+async fn post_message(State(state): State<HttpState>, headers: HeaderMap, body: String) -> Response {
+    let request: JsonRpcRequest = match serde_json::from_str(&body) {
+        Ok(request) => request,
+        Err(e) => {
+            return json_rpc_error(StatusCode::BAD_REQUEST, PARSE_ERROR, &format!("Parse error: {}", e));
+        }
+    };
+    let initialize = request.method == "initialize";
+    if !initialize {
+        if let Some(rejection) = check_session(&state, &headers) {
+            return rejection;
+        }
+    }
+
+    let is_notification = request.id.is_none();
+    let wants_progress = request
+        .params
+        .as_ref()
+        .and_then(|params| params.get("_meta")?.get("progressToken"))
+        .is_some();
+    let accepts_sse = headers
+        .get(header::ACCEPT)
+        .and_then(|v| v.to_str().ok())
+        .is_some_and(|accept| accept.contains("text/event-stream"));
+
+    let (replies, mut messages) = mpsc::unbounded_channel();
+    if state.incoming.send(Incoming::Request(request, replies)).is_err() {
+        return StatusCode::SERVICE_UNAVAILABLE.into_response();
+    }
+    if is_notification {
+        return StatusCode::ACCEPTED.into_response();
+    }
+
+    if wants_progress && accepts_sse {
+        let events = UnboundedReceiverStream::new(messages)
+            .map(|json| Ok::<_, std::convert::Infallible>(Event::default().event("message").data(json)));
+        return Sse::new(events).into_response();
+    }
+
+    // Notifications are dropped without a stream to put them on; the response comes last
+    let mut response = None;
+    while let Some(json) = messages.recv().await {
+        response = Some(json);
+    }
+    let Some(json) = response else {
+        return StatusCode::INTERNAL_SERVER_ERROR.into_response();
+    };
+    let mut response = ([(header::CONTENT_TYPE, "application/json")], json).into_response();
+    if initialize {
+        let id = uuid::Uuid::new_v4().to_string();
+        state.sessions.lock().unwrap_or_else(|e| e.into_inner()).insert(id.clone());
+        if let Ok(value) = HeaderValue::from_str(&id) {
+            response.headers_mut().insert(SESSION_HEADER, value);
+        }
+    }
+    response
+}
+
+/// There are no server-initiated messages outside a request, so no standalone stream
+async fn get_stream() -> StatusCode {
+    StatusCode::METHOD_NOT_ALLOWED
+}
+
+async fn delete_session(State(state): State<HttpState>, headers: HeaderMap) -> Response {
+    if let Some(rejection) = check_session(&state, &headers) {
+        return rejection;
+    }
+    if let Some(id) = headers.get(SESSION_HEADER).and_then(|v| v.to_str().ok()) {
+        state.sessions.lock().unwrap_or_else(|e| e.into_inner()).remove(id);
+    }
+    StatusCode::OK.into_response()
+}
+
+async fn get_metrics(State(state): State<HttpState>) -> Response {
+    let (reply, metrics) = oneshot::channel();
+    if state.incoming.send(Incoming::Metrics(reply)).is_err() {
+        return StatusCode::SERVICE_UNAVAILABLE.into_response();
+    }
+    match metrics.await {
+        Ok(Some(text)) => ([(header::CONTENT_TYPE, "text/plain; version=0.0.4")], text).into_response(),
+        // Metrics start counting once a client has initialized the server
+        _ => StatusCode::SERVICE_UNAVAILABLE.into_response(),
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -100,4 +484,152 @@ mod tests {
         let response = handler.handle(request).await;
         assert!(response.result.is_some());
     }
+
+    /// Send one HTTP/1.1 request to `/mcp` and return the status line, headers, and body
+    async fn http(addr: SocketAddr, method: &str, headers: &[(&str, &str)], body: &str) -> String {
+        http_to(addr, method, MCP_PATH, headers, body).await
+    }
+
+    async fn http_to(addr: SocketAddr, method: &str, path: &str, headers: &[(&str, &str)], body: &str) -> String {
+        let mut stream = tokio::net::TcpStream::connect(addr).await.unwrap();
+        let mut request = format!("{} {} HTTP/1.1\r\nHost: {}\r\nConnection: close\r\n", method, path, addr);
+        for (name, value) in headers {
+            request.push_str(&format!("{}: {}\r\n", name, value));
+        }
+        request.push_str(&format!("Content-Length: {}\r\n\r\n{}", body.len(), body));
+        stream.write_all(request.as_bytes()).await.unwrap();
+        let mut response = String::new();
+        tokio::io::AsyncReadExt::read_to_string(&mut stream, &mut response).await.unwrap();
+        response
+    }
+
+    #[tokio::test]
+    async fn test_http_sessions() {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move { serve_http(TestHandler, listener, None).await });
+
+        let json = [("Content-Type", "application/json")];
+        let response = http(addr, "POST", &json, r#"{"jsonrpc":"2.0","id":1,"method":"initialize"}"#).await;
+        assert!(response.starts_with("HTTP/1.1 200"), "{}", response);
+        assert!(response.ends_with(r#""result":{"method":"initialize"}}"#), "{}", response);
+        let session = response
+            .lines()
+            .find_map(|line| line.strip_prefix("mcp-session-id: "))
+            .unwrap()
+            .to_string();
+
+        let list = r#"{"jsonrpc":"2.0","id":2,"method":"tools/list"}"#;
+        let response = http(addr, "POST", &json, list).await;
+        assert!(response.starts_with("HTTP/1.1 400"), "{}", response);
+        let with_session = [("Content-Type", "application/json"), ("Mcp-Session-Id", session.as_str())];
+        let response = http(addr, "POST", &with_session, list).await;
+        assert!(response.ends_with(r#""result":{"method":"tools/list"}}"#), "{}", response);
+
+        let notification = r#"{"jsonrpc":"2.0","method":"notifications/initialized"}"#;
+        assert!(http(addr, "POST", &with_session, notification).await.starts_with("HTTP/1.1 202"));
+        assert!(http(addr, "POST", &json, "not json").await.starts_with("HTTP/1.1 400"));
+        let foreign = [("Content-Type", "application/json"), ("Origin", "https://example.com")];
+        assert!(http(addr, "POST", &foreign, list).await.starts_with("HTTP/1.1 403"));
+        assert!(http(addr, "GET", &[], "").await.starts_with("HTTP/1.1 405"));
+
+        assert!(http(addr, "DELETE", &with_session, "").await.starts_with("HTTP/1.1 200"));
+        assert!(http(addr, "POST", &with_session, list).await.starts_with("HTTP/1.1 404"));
+    }
+
+    #[tokio::test]
+    async fn test_http_token() {
+        let open = "0.0.0.0:0".parse().unwrap();
+        let err = run_http(TestHandler, open, None).await.unwrap_err();
+        assert!(err.to_string().starts_with("Refusing to listen on 0.0.0.0:0"), "{}", err);
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move { serve_http(TestHandler, listener, Some("s3cret".to_string())).await });
+
+        let initialize = r#"{"jsonrpc":"2.0","id":1,"method":"initialize"}"#;
+        let json = [("Content-Type", "application/json")];
+        let response = http(addr, "POST", &json, initialize).await;
+        assert!(response.starts_with("HTTP/1.1 401"), "{}", response);
+        let wrong = [("Content-Type", "application/json"), ("Authorization", "Bearer s3creT")];
+        assert!(http(addr, "POST", &wrong, initialize).await.starts_with("HTTP/1.1 401"));
+        let authorized = [("Content-Type", "application/json"), ("Authorization", "Bearer s3cret")];
+        assert!(http(addr, "POST", &authorized, initialize).await.starts_with("HTTP/1.1 200"));
+
+        // Sessions can't be ended by strangers or by pages on other origins
+        let session = [("Mcp-Session-Id", "any")];
+        assert!(http(addr, "DELETE", &session, "").await.starts_with("HTTP/1.1 401"));
+        let foreign = [("Authorization", "Bearer s3cret"), ("Origin", "https://example.com")];
+        assert!(http(addr, "DELETE", &foreign, "").await.starts_with("HTTP/1.1 403"));
+
+        assert!(http_to(addr, "GET", "/metrics", &[], "").await.starts_with("HTTP/1.1 401"));
+        let foreign = [("Authorization", "Bearer s3cret"), ("Origin", "https://example.com")];
+        assert!(http_to(addr, "GET", "/metrics", &foreign, "").await.starts_with("HTTP/1.1 403"));
+    }
+
+    #[tokio::test]
+    async fn test_progress_reporter() {
+        let (notifier, mut notifications) = mpsc::unbounded_channel();
+        let reporter = ProgressReporter::new(notifier, Value::from(7));
+
+        reporter.report("first");
+        reporter.clone().report("second");
+
+        let first = notifications.recv().await.unwrap();
+        let second = notifications.recv().await.unwrap();
+        assert_eq!(first.params.unwrap()["progress"], 1);
+        let params = second.params.unwrap();
+        assert_eq!(params["progress"], 2);
+        assert_eq!(params["progressToken"], 7);
+        assert_eq!(params["message"], "second");
+    }
+
+    /// Handles `slow` requests until request 1 is cancelled
+    #[derive(Default)]
+    struct CancellableHandler {
+        cancelled: AtomicBool,
+    }
+
+    impl Handler for CancellableHandler {
+        async fn handle(&self, request: JsonRpcRequest) -> JsonRpcResponse {
+            while request.method == "slow" && !self.cancelled.load(std::sync::atomic::Ordering::Relaxed) {
+                tokio::time::sleep(std::time::Duration::from_millis(5)).await;
+            }
+            JsonRpcResponse::success(request.id, serde_json::json!({"method": request.method}))
+        }
+
+        fn cancel(&self, request_id: &Value) {
+            if *request_id == 1 {
+                self.cancelled.store(true, std::sync::atomic::Ordering::Relaxed);
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn test_dispatch_cancellation() {
+        let handler = CancellableHandler::default();
+        let (incoming, requests) = mpsc::unbounded_channel();
+        let (_notifier, notifications) = mpsc::unbounded_channel();
+        let send = |id: Option<i64>, method: &str, params: Option<Value>| {
+            let (replies, messages) = mpsc::unbounded_channel();
+            let request = JsonRpcRequest {
+                jsonrpc: "2.0".to_string(),
+                id: id.map(Value::from),
+                method: method.to_string(),
+                params,
+            };
+            incoming.send(Incoming::Request(request, replies)).unwrap();
+            messages
+        };
+
+        // Arriving while `slow` runs: the next request waits, the cancellation goes straight through
+        let mut slow = send(Some(1), "slow", None);
+        let mut next = send(Some(2), "next", None);
+        send(None, "notifications/cancelled", Some(serde_json::json!({"requestId": 1})));
+        drop(incoming);
+        dispatch(&handler, requests, notifications).await;
+
+        assert!(slow.recv().await.unwrap().contains(r#""result":{"method":"slow"}"#));
+        assert!(next.recv().await.unwrap().contains(r#""result":{"method":"next"}"#));
+    }
 }
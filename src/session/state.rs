@@ -1,12 +1,27 @@
 use anyhow::{Context, Result};
 use serde::{Deserialize, Serialize};
 use serde_json::json;
+use std::collections::HashSet;
+use std::time::{SystemTime, UNIX_EPOCH};
 use std::sync::Arc;
 use tracing::debug;
 use uuid::Uuid;
 
 use crate::store::{CodeGraph, Store};
 
+/// Node recording which session the session tools act on
+const ACTIVE_ID: &str = "session::active";
+
+/// Sessions from before they were named: one `session::current` whose items, decisions, and
+/// context have unprefixed IDs (`session::item::0`, `session::context`)
+const LEGACY_NAME: &str = "current";
+
+/// Names that would collide with the active pointer or legacy child IDs
+const RESERVED_NAMES: &[&str] = &["active", "item", "decision", "context"];
+
+/// Checkpoints kept per session; the oldest are pruned first
+const MAX_CHECKPOINTS: usize = 50;
+
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
 pub enum TaskStatus {
     Pending,
@@ -40,6 +55,7 @@ pub struct SessionContext {
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Session {
+    pub name: String,
     pub task: String,
     pub items: Vec<TaskItem>,
     pub decisions: Vec<Decision>,
@@ -58,34 +74,88 @@ pub struct SmartContextResult {
     pub related_symbols: Vec<String>,
     pub files_modified: Vec<String>,
     pub blockers: Vec<String>,
+    /// Manual instructions relevant to `files_modified` (filled in by the MCP layer)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub instructions: Vec<String>,
+}
+
+/// One line of `SessionManager::list_sessions`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SessionSummary {
+    pub name: String,
+    pub task: String,
+    /// The session the session tools currently act on
+    pub active: bool,
+    pub archived: bool,
+    pub completed: usize,
+    pub total: usize,
+    pub created_at: i64,
+}
+
+/// A snapshot of a session taken after a significant update
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Checkpoint {
+    pub seq: u64,
+    /// What changed, e.g. `decision: Use JWT`
+    pub reason: String,
+    pub timestamp: i64,
+    pub session: Session,
+}
+
+/// Session nodes fixed up by `SessionManager::recover`
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct RecoveryReport {
+    pub reattached: Vec<String>,
+    pub removed: Vec<String>,
+}
+
+impl RecoveryReport {
+    pub fn is_empty(&self) -> bool {
+        self.reattached.is_empty() && self.removed.is_empty()
+    }
 }
 
 pub struct SessionManager {
     store: Arc<Store>,
-    graph: Arc<std::sync::RwLock<CodeGraph>>,
+    graph: Arc<tokio::sync::RwLock<CodeGraph>>,
 }
 
 impl SessionManager {
-    pub fn new(store: Arc<Store>, graph: Arc<std::sync::RwLock<CodeGraph>>) -> Self {
+    pub fn new(store: Arc<Store>, graph: Arc<tokio::sync::RwLock<CodeGraph>>) -> Self {
         Self { store, graph }
     }
 
-    /// Start a new session
-    pub async fn start_session(&self, task: &str, items: &[String]) -> Result<Session> {
+    /// Start a new session and make it active. The previous session is kept and can be
+    /// switched back to. Without a name, one is derived from the task.
+    pub async fn start_session(&self, name: Option<&str>, task: &str, items: &[String]) -> Result<Session> {
         debug!("Starting new session: {}", task);
-
-        // Clear old session first
-        self.clear_session().await?;
+        let tx = self.store.transaction().await?;
+
+        let name = match name {
+            Some(name) => {
+                validate_name(name)?;
+                if self.store.get_node(&session_id(name)).await?.is_some() {
+                    anyhow::bail!(
+                        "Session '{}' already exists. Use switch_session to resume it, or pick another name.",
+                        name
+                    );
+                }
+                name.to_string()
+            }
+            None => self.unused_name(task).await?,
+        };
 
         // Create session task node
-        let session_id = "session::current";
+        let session_id = session_id(&name);
         self.store
             .upsert_node(
-                session_id,
+                &session_id,
                 "session",
                 "task",
                 &json!({
                     "description": task,
+                    "status": "open",
+                    "created_at": unix_now()?,
                 }),
             )
             .await?;
@@ -93,7 +163,7 @@ impl SessionManager {
         // Create task items
         let mut task_items = Vec::new();
         for (idx, item_desc) in items.iter().enumerate() {
-            let item_id = format!("session::item::{}", idx);
+            let item_id = format!("{}::item::{}", session_id, idx);
             task_items.push(TaskItem {
                 id: item_id.clone(),
                 description: item_desc.clone(),
@@ -120,10 +190,10 @@ impl SessionManager {
         }
 
         // Create context node
-        let context_id = "session::context";
+        let context_id = format!("{}::context", session_id);
         self.store
             .upsert_node(
-                context_id,
+                &context_id,
                 "session",
                 "context",
                 &json!({
@@ -134,10 +204,14 @@ impl SessionManager {
             .await?;
 
         self.store
-            .upsert_edge(session_id, context_id, "has_context", "session", None)
+            .upsert_edge(&session_id, &context_id, "has_context", "session", None)
             .await?;
+        self.set_active(Some(&name)).await?;
+        self.checkpoint(&session_id, "started").await?;
+        tx.commit().await?;
 
         Ok(Session {
+            name,
             task: task.to_string(),
             items: task_items,
             decisions: Vec::new(),
@@ -150,12 +224,19 @@ impl SessionManager {
         })
     }
 
-    /// Get current session
+    /// Get the active session
     pub async fn get_session(&self) -> Result<Option<Session>> {
-        let session_id = "session::current";
+        match self.active_name().await? {
+            Some(name) => self.load_session(&name).await,
+            None => Ok(None),
+        }
+    }
+
+    async fn load_session(&self, name: &str) -> Result<Option<Session>> {
+        let session_id = session_id(name);
 
         // Check if session exists
-        let session_node = match self.store.get_node(session_id).await? {
+        let session_node = match self.store.get_node(&session_id).await? {
             Some(node) => node,
             None => return Ok(None),
         };
@@ -168,7 +249,7 @@ impl SessionManager {
             .to_string();
 
         // Get task items
-        let item_edges = self.store.get_edges_from(session_id).await?;
+        let item_edges = self.store.get_edges_from(&session_id).await?;
         let mut items = Vec::new();
 
         for edge in &item_edges {
@@ -244,8 +325,11 @@ impl SessionManager {
         }
 
         // Get context
-        let context_id = "session::context";
-        let context = if let Some(context_node) = self.store.get_node(context_id).await? {
+        let context_node = match item_edges.iter().find(|edge| edge.kind == "has_context") {
+            Some(edge) => self.store.get_node(&edge.target).await?,
+            None => None,
+        };
+        let context = if let Some(context_node) = context_node {
             SessionContext {
                 modified_files: context_node
                     .data
@@ -276,6 +360,7 @@ impl SessionManager {
         };
 
         Ok(Some(Session {
+            name: name.to_string(),
             task,
             items,
             decisions,
@@ -294,9 +379,18 @@ impl SessionManager {
         add_blocker: Option<&str>,
         remove_blocker: Option<&str>,
     ) -> Result<Session> {
+        let tx = self.store.transaction().await?;
+        let session_id = self.active_session_id().await?;
+
+        let mut completed = None;
+
         // Update existing item status
         if let (Some(id), Some(new_status)) = (item_id, status) {
             if let Some(mut node) = self.store.get_node(id).await? {
+                let was_completed = node.data.get("status").and_then(|v| v.as_str()) == Some("completed");
+                if new_status == TaskStatus::Completed && !was_completed {
+                    completed = node.data.get("description").and_then(|v| v.as_str()).map(String::from);
+                }
                 node.data["status"] = json!(status_to_str(&new_status));
 
                 if let Some(blocker) = add_blocker {
@@ -341,8 +435,7 @@ impl SessionManager {
 
         // Add new item
         if let Some(desc) = add_item {
-            let session_id = "session::current";
-            let item_id = format!("session::item::{}", Uuid::new_v4());
+            let item_id = format!("{}::item::{}", session_id, Uuid::new_v4());
 
             self.store
                 .upsert_node(
@@ -360,6 +453,10 @@ impl SessionManager {
                 .upsert_edge(&session_id, &item_id, "has_item", "session", None)
                 .await?;
         }
+        if let Some(description) = completed {
+            self.checkpoint(&session_id, &format!("completed: {}", description)).await?;
+        }
+        tx.commit().await?;
 
         self.get_session()
             .await?
@@ -373,12 +470,10 @@ impl SessionManager {
         why: &str,
         related_symbols: &[String],
     ) -> Result<()> {
-        let session_id = "session::current";
-        let decision_id = format!("session::decision::{}", Uuid::new_v4());
-
-        let timestamp = std::time::SystemTime::now()
-            .duration_since(std::time::UNIX_EPOCH)?
-            .as_secs() as i64;
+        let tx = self.store.transaction().await?;
+        let session_id = self.active_session_id().await?;
+        let decision_id = format!("{}::decision::{}", session_id, Uuid::new_v4());
+        let timestamp = unix_now()?;
 
         self.store
             .upsert_node(
@@ -397,8 +492,15 @@ impl SessionManager {
         self.store
             .upsert_edge(&session_id, &decision_id, "has_decision", "session", None)
             .await?;
+        self.link_related_symbols(&decision_id, related_symbols).await?;
+        self.checkpoint(&session_id, &format!("decision: {}", what)).await?;
+        tx.commit().await?;
 
-        // Link to related symbols in code graph (resolve names to node IDs)
+        Ok(())
+    }
+
+    /// Link a decision to the code graph nodes it mentions (resolving names to node IDs)
+    async fn link_related_symbols(&self, decision_id: &str, related_symbols: &[String]) -> Result<()> {
         for symbol in related_symbols {
             // Try the symbol name as-is first (might already be qualified like file::name)
             let resolved = if self.store.get_node(symbol).await?.is_some() {
@@ -410,11 +512,10 @@ impl SessionManager {
 
             if let Some(node_id) = resolved {
                 self.store
-                    .upsert_edge(&decision_id, &node_id, "related_to", "cross", None)
+                    .upsert_edge(decision_id, &node_id, "related_to", "cross", None)
                     .await?;
             }
         }
-
         Ok(())
     }
 
@@ -427,15 +528,22 @@ impl SessionManager {
         remove_symbol: Option<&str>,
         _add_note: Option<&str>,
     ) -> Result<()> {
-        let context_id = "session::context";
+        let tx = self.store.transaction().await?;
+        let session_id = self.active_session_id().await?;
 
-        let mut context = if let Some(node) = self.store.get_node(context_id).await? {
+        let linked = self.store.get_edges_from(&session_id).await?;
+        let existing = match linked.iter().find(|edge| edge.kind == "has_context") {
+            Some(edge) => self.store.get_node(&edge.target).await?,
+            None => None,
+        };
+        let mut context = if let Some(node) = existing {
             node
         } else {
             // Create context if it doesn't exist
+            let context_id = format!("{}::context", session_id);
             self.store
                 .upsert_node(
-                    context_id,
+                    &context_id,
                     "session",
                     "context",
                     &json!({
@@ -444,7 +552,10 @@ impl SessionManager {
                     }),
                 )
                 .await?;
-            self.store.get_node(context_id).await?.context("Context node not found after creation")?
+            self.store
+                .upsert_edge(&session_id, &context_id, "has_context", "session", None)
+                .await?;
+            self.store.get_node(&context_id).await?.context("Context node not found after creation")?
         };
 
         let mut files: Vec<String> = context
@@ -495,6 +606,7 @@ impl SessionManager {
         self.store
             .upsert_node(&context.id, &context.graph, &context.kind, &context.data)
             .await?;
+        tx.commit().await?;
 
         Ok(())
     }
@@ -537,9 +649,9 @@ impl SessionManager {
             .cloned()
             .collect();
 
-        // Get related symbols (1-hop neighbors of working symbols)
-        let graph = self.graph.read().map_err(|e| anyhow::anyhow!("Graph lock poisoned: {}", e))?;
-        let mut related_symbols = Vec::new();
+        // Get related symbols (1-hop neighbors of working symbols), most central first
+        let graph = CodeGraph::read_ranked(&self.graph).await;
+        let mut related: Vec<(String, f64)> = Vec::new();
 
         for symbol in &session.context.working_symbols {
             let neighbors = graph.neighbors(
@@ -551,12 +663,15 @@ impl SessionManager {
 
             for neighbor in neighbors {
                 if let Some(name) = neighbor.node.data.get("name").and_then(|v| v.as_str()) {
-                    if !related_symbols.contains(&name.to_string()) {
-                        related_symbols.push(name.to_string());
+                    match related.iter_mut().find(|(existing, _)| existing == name) {
+                        Some((_, rank)) => *rank = rank.max(neighbor.node.rank),
+                        None => related.push((name.to_string(), neighbor.node.rank)),
                     }
                 }
             }
         }
+        related.sort_by(|a, b| b.1.total_cmp(&a.1));
+        let related_symbols = related.into_iter().map(|(name, _)| name).collect();
 
         Ok(SmartContextResult {
             task: session.task,
@@ -567,15 +682,352 @@ impl SessionManager {
             related_symbols,
             files_modified: session.context.modified_files,
             blockers: session.blockers,
+            instructions: Vec::new(),
         })
     }
 
-    async fn clear_session(&self) -> Result<()> {
-        // Delete all session and cross-graph nodes/edges
-        self.store.delete_graph("cross").await?;
-        self.store.delete_graph("session").await?;
+    /// Checkpoints of the active session, newest first
+    pub async fn history(&self) -> Result<Vec<Checkpoint>> {
+        let session_id = self.active_session_id().await?;
+        let mut checkpoints = Vec::new();
+        for edge in self.store.get_edges_from(&session_id).await? {
+            if edge.kind != "has_checkpoint" {
+                continue;
+            }
+            if let Some(node) = self.store.get_node(&edge.target).await? {
+                checkpoints.push(serde_json::from_value::<Checkpoint>(node.data)?);
+            }
+        }
+        checkpoints.sort_by_key(|c| std::cmp::Reverse(c.seq));
+        Ok(checkpoints)
+    }
+
+    /// Put the active session's items, decisions, and context back the way a checkpoint
+    /// recorded them. The state being replaced is checkpointed first, so a restore can
+    /// itself be undone.
+    pub async fn restore_checkpoint(&self, seq: u64) -> Result<Session> {
+        let tx = self.store.transaction().await?;
+        let session_id = self.active_session_id().await?;
+        let node = self
+            .store
+            .get_node(&format!("{}::checkpoint::{}", session_id, seq))
+            .await?
+            .with_context(|| format!("No checkpoint #{} in this session. Use session_history to see them.", seq))?;
+        let checkpoint: Checkpoint = serde_json::from_value(node.data)?;
+        self.checkpoint(&session_id, &format!("before restoring #{}", seq)).await?;
+
+        // Edges (including decisions' related_to links) cascade
+        for edge in self.store.get_edges_from(&session_id).await? {
+            if matches!(edge.kind.as_str(), "has_item" | "has_decision" | "has_context") {
+                self.store.delete_node(&edge.target).await?;
+            }
+        }
+
+        let snapshot = checkpoint.session;
+        for item in &snapshot.items {
+            self.store
+                .upsert_node(
+                    &item.id,
+                    "session",
+                    "item",
+                    &json!({
+                        "description": item.description,
+                        "status": status_to_str(&item.status),
+                        "blockers": item.blockers,
+                    }),
+                )
+                .await?;
+            self.store
+                .upsert_edge(&session_id, &item.id, "has_item", "session", None)
+                .await?;
+        }
+        for decision in &snapshot.decisions {
+            self.store
+                .upsert_node(
+                    &decision.id,
+                    "session",
+                    "decision",
+                    &json!({
+                        "what": decision.what,
+                        "why": decision.why,
+                        "related_symbols": decision.related_symbols,
+                        "timestamp": decision.timestamp,
+                    }),
+                )
+                .await?;
+            self.store
+                .upsert_edge(&session_id, &decision.id, "has_decision", "session", None)
+                .await?;
+            self.link_related_symbols(&decision.id, &decision.related_symbols).await?;
+        }
+        let context_id = format!("{}::context", session_id);
+        self.store
+            .upsert_node(
+                &context_id,
+                "session",
+                "context",
+                &json!({
+                    "modified_files": snapshot.context.modified_files,
+                    "working_symbols": snapshot.context.working_symbols,
+                }),
+            )
+            .await?;
+        self.store
+            .upsert_edge(&session_id, &context_id, "has_context", "session", None)
+            .await?;
+        tx.commit().await?;
+
+        self.get_session()
+            .await?
+            .context("Session not found after restoring")
+    }
+
+    /// Snapshot a session as it is now, within the caller's transaction
+    async fn checkpoint(&self, session_id: &str, reason: &str) -> Result<()> {
+        let name = session_id.strip_prefix("session::").unwrap_or(session_id);
+        let session = self.load_session(name).await?.context("Session not found for checkpoint")?;
+
+        let mut existing: Vec<String> = self
+            .store
+            .get_edges_from(session_id)
+            .await?
+            .into_iter()
+            .filter(|edge| edge.kind == "has_checkpoint")
+            .map(|edge| edge.target)
+            .collect();
+        existing.sort_by_key(|id| checkpoint_seq(id));
+        let seq = existing.last().map_or(1, |id| checkpoint_seq(id) + 1);
+
+        let checkpoint_id = format!("{}::checkpoint::{}", session_id, seq);
+        let checkpoint = Checkpoint {
+            seq,
+            reason: reason.to_string(),
+            timestamp: unix_now()?,
+            session,
+        };
+        self.store
+            .upsert_node(&checkpoint_id, "session", "checkpoint", &serde_json::to_value(&checkpoint)?)
+            .await?;
+        self.store
+            .upsert_edge(session_id, &checkpoint_id, "has_checkpoint", "session", None)
+            .await?;
+
+        let excess = (existing.len() + 1).saturating_sub(MAX_CHECKPOINTS);
+        for id in &existing[..excess] {
+            self.store.delete_node(id).await?;
+        }
         Ok(())
     }
+
+    /// Repair partial writes left by a crash mid-update (from before session updates were
+    /// transactional, or a database copied mid-write). Items, decisions, and context nodes
+    /// not linked to their session are reattached, or deleted if the session is gone.
+    pub async fn recover(&self) -> Result<RecoveryReport> {
+        let tx = self.store.transaction().await?;
+        let mut report = RecoveryReport::default();
+
+        let mut sessions = HashSet::new();
+        let mut linked = HashSet::new();
+        for session in self.store.query_nodes("session", "task").await? {
+            for edge in self.store.get_edges_from(&session.id).await? {
+                linked.insert(edge.target);
+            }
+            sessions.insert(session.id);
+        }
+
+        for (kind, edge_kind) in [
+            ("item", "has_item"),
+            ("decision", "has_decision"),
+            ("context", "has_context"),
+            ("checkpoint", "has_checkpoint"),
+        ] {
+            for node in self.store.query_nodes("session", kind).await? {
+                if linked.contains(&node.id) {
+                    continue;
+                }
+                let owner = owner_of(&node.id);
+                if sessions.contains(&owner) {
+                    self.store
+                        .upsert_edge(&owner, &node.id, edge_kind, "session", None)
+                        .await?;
+                    report.reattached.push(node.id);
+                } else {
+                    // Edges (including a decision's related_to links) cascade
+                    self.store.delete_node(&node.id).await?;
+                    report.removed.push(node.id);
+                }
+            }
+        }
+        tx.commit().await?;
+
+        Ok(report)
+    }
+
+    /// All sessions, the active one first, then open and archived ones by most recent
+    pub async fn list_sessions(&self) -> Result<Vec<SessionSummary>> {
+        let active = self.active_name().await?;
+        let mut summaries = Vec::new();
+        for node in self.store.query_nodes("session", "task").await? {
+            let name = node.id.strip_prefix("session::").unwrap_or(&node.id).to_string();
+            let mut completed = 0;
+            let mut total = 0;
+            for edge in self.store.get_edges_from(&node.id).await? {
+                if edge.kind != "has_item" {
+                    continue;
+                }
+                total += 1;
+                if let Some(item) = self.store.get_node(&edge.target).await? {
+                    if item.data.get("status").and_then(|v| v.as_str()) == Some("completed") {
+                        completed += 1;
+                    }
+                }
+            }
+            summaries.push(SessionSummary {
+                active: active.as_deref() == Some(name.as_str()),
+                task: node.data.get("description").and_then(|v| v.as_str()).unwrap_or("").to_string(),
+                archived: node.data.get("status").and_then(|v| v.as_str()) == Some("archived"),
+                created_at: node.data.get("created_at").and_then(|v| v.as_i64()).unwrap_or(0),
+                name,
+                completed,
+                total,
+            });
+        }
+        summaries.sort_by(|a, b| {
+            (b.active, !b.archived, b.created_at).cmp(&(a.active, !a.archived, a.created_at))
+        });
+        Ok(summaries)
+    }
+
+    /// Make a session active, reopening it if it was archived
+    pub async fn switch_session(&self, name: &str) -> Result<Session> {
+        let tx = self.store.transaction().await?;
+        let mut node = self
+            .store
+            .get_node(&session_id(name))
+            .await?
+            .with_context(|| format!("No session named '{}'. Use list_sessions to see them.", name))?;
+        if node.data.get("status").and_then(|v| v.as_str()) == Some("archived") {
+            node.data["status"] = json!("open");
+            self.store
+                .upsert_node(&node.id, &node.graph, &node.kind, &node.data)
+                .await?;
+        }
+        self.set_active(Some(name)).await?;
+        tx.commit().await?;
+
+        self.load_session(name)
+            .await?
+            .context("Session not found after switching")
+    }
+
+    /// Archive a session (the active one by default), keeping its history. Returns the
+    /// archived session's name; if it was active, no session is active afterwards.
+    pub async fn archive_session(&self, name: Option<&str>) -> Result<String> {
+        let tx = self.store.transaction().await?;
+        let active = self.active_name().await?;
+        let name = match name.or(active.as_deref()) {
+            Some(name) => name.to_string(),
+            None => anyhow::bail!("No active session to archive"),
+        };
+        let mut node = self
+            .store
+            .get_node(&session_id(&name))
+            .await?
+            .with_context(|| format!("No session named '{}'. Use list_sessions to see them.", name))?;
+        node.data["status"] = json!("archived");
+        self.store
+            .upsert_node(&node.id, &node.graph, &node.kind, &node.data)
+            .await?;
+        if active.as_deref() == Some(name.as_str()) {
+            self.set_active(None).await?;
+        }
+        tx.commit().await?;
+
+        Ok(name)
+    }
+
+    /// Name of the active session. Databases from before named sessions have no pointer,
+    /// so their `session::current` is active.
+    pub async fn active_name(&self) -> Result<Option<String>> {
+        let name = match self.store.get_node(ACTIVE_ID).await? {
+            Some(pointer) => pointer.data.get("name").and_then(|v| v.as_str()).map(String::from),
+            None => Some(LEGACY_NAME.to_string()),
+        };
+        match name {
+            Some(name) if self.store.get_node(&session_id(&name)).await?.is_some() => Ok(Some(name)),
+            _ => Ok(None),
+        }
+    }
+
+    async fn active_session_id(&self) -> Result<String> {
+        let name = self
+            .active_name()
+            .await?
+            .context("No active session. Use start_session or switch_session first.")?;
+        Ok(session_id(&name))
+    }
+
+    async fn set_active(&self, name: Option<&str>) -> Result<()> {
+        self.store
+            .upsert_node(ACTIVE_ID, "session", "active", &json!({ "name": name }))
+            .await
+    }
+
+    /// A name derived from the task that no session has yet
+    async fn unused_name(&self, task: &str) -> Result<String> {
+        let mut base: String = task
+            .to_lowercase()
+            .split(|c: char| !c.is_ascii_alphanumeric())
+            .filter(|word| !word.is_empty())
+            .collect::<Vec<_>>()
+            .join("-");
+        base.truncate(40);
+        let base = base.trim_end_matches('-');
+        let base = if base.is_empty() || RESERVED_NAMES.contains(&base) { "session" } else { base };
+
+        let mut name = base.to_string();
+        let mut n = 2;
+        while self.store.get_node(&session_id(&name)).await?.is_some() {
+            name = format!("{}-{}", base, n);
+            n += 1;
+        }
+        Ok(name)
+    }
+}
+
+fn session_id(name: &str) -> String {
+    format!("session::{}", name)
+}
+
+/// The session node an item, decision, or context node belongs to
+fn owner_of(id: &str) -> String {
+    let name = id.strip_prefix("session::").unwrap_or(id).split("::").next().unwrap_or("");
+    if RESERVED_NAMES.contains(&name) {
+        session_id(LEGACY_NAME)
+    } else {
+        session_id(name)
+    }
+}
+
+fn checkpoint_seq(id: &str) -> u64 {
+    id.rsplit("::").next().and_then(|seq| seq.parse().ok()).unwrap_or(0)
+}
+
+fn validate_name(name: &str) -> Result<()> {
+    if name.is_empty() || name.len() > 64 {
+        anyhow::bail!("Session names must be 1-64 characters");
+    }
+    if !name.chars().all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.')) {
+        anyhow::bail!("Session names may only contain letters, digits, '-', '_', and '.'");
+    }
+    if RESERVED_NAMES.contains(&name) {
+        anyhow::bail!("'{}' is reserved; pick another session name", name);
+    }
+    Ok(())
+}
+
+fn unix_now() -> Result<i64> {
+    Ok(SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs() as i64)
 }
 
 fn parse_status(s: &str) -> TaskStatus {
@@ -613,11 +1065,11 @@ mod tests {
             codegraph_dir: temp_path.join(".codegraph"),
             store_db_path: temp_path.join("cache/store.db"),
             learning_db_path: temp_path.join(".codegraph/learning.db"),
-            settings: crate::config::ConfigFile::default(),
+            settings: crate::config::ConfigFile::default().into(),
         };
 
         let store = Arc::new(Store::open(&config).await.unwrap());
-        let graph = Arc::new(std::sync::RwLock::new(
+        let graph = Arc::new(tokio::sync::RwLock::new(
             CodeGraph::load_from_store(&store).await.unwrap(),
         ));
 
@@ -630,7 +1082,7 @@ mod tests {
         let (manager, _temp) = setup_test_manager().await;
 
         let session = manager
-            .start_session("Build feature X", &["Design API".to_string()])
+            .start_session(None, "Build feature X", &["Design API".to_string()])
             .await
             .unwrap();
 
@@ -644,7 +1096,7 @@ mod tests {
         let (manager, _temp) = setup_test_manager().await;
 
         let session = manager
-            .start_session("Test task", &["Item 1".to_string()])
+            .start_session(None, "Test task", &["Item 1".to_string()])
             .await
             .unwrap();
 
@@ -669,7 +1121,7 @@ mod tests {
         let (manager, _temp) = setup_test_manager().await;
 
         manager
-            .start_session("Test task", &[])
+            .start_session(None, "Test task", &[])
             .await
             .unwrap();
 
@@ -682,4 +1134,191 @@ mod tests {
         assert_eq!(session.decisions.len(), 1);
         assert_eq!(session.decisions[0].what, "Use REST API");
     }
+
+    #[tokio::test]
+    async fn test_smart_context_ranks_related_symbols() {
+        let (manager, _temp) = setup_test_manager().await;
+        {
+            let mut graph = manager.graph.write().await;
+            for id in ["a.rs::edit", "b.rs::leaf", "c.rs::core", "d.rs::x", "e.rs::y"] {
+                let name = id.rsplit("::").next().unwrap();
+                graph.add_node(id.to_string(), "function".to_string(), json!({"name": name}));
+            }
+            let calls = |source: &str, target: &str| crate::store::graph::GraphChange::UpsertEdge {
+                source: source.to_string(),
+                target: target.to_string(),
+                kind: "calls".to_string(),
+                data: None,
+            };
+            graph.apply(&[
+                calls("a.rs::edit", "b.rs::leaf"),
+                calls("a.rs::edit", "c.rs::core"),
+                calls("d.rs::x", "c.rs::core"),
+                calls("e.rs::y", "c.rs::core"),
+            ]);
+        }
+        manager.start_session(None, "Refactor edit", &[]).await.unwrap();
+        manager.set_context(None, None, Some("a.rs::edit"), None, None).await.unwrap();
+
+        let context = manager.smart_context().await.unwrap();
+        assert_eq!(context.related_symbols, vec!["core", "leaf"]);
+    }
+
+    #[tokio::test]
+    async fn test_recover_partial_writes() {
+        let (manager, _temp) = setup_test_manager().await;
+        manager.start_session(None, "Test task", &["Item 1".to_string()]).await.unwrap();
+        assert!(manager.recover().await.unwrap().is_empty());
+
+        // A decision node whose has_decision edge was never written
+        let decision = json!({"what": "Use REST", "why": "Simpler", "related_symbols": [], "timestamp": 1});
+        manager
+            .store
+            .upsert_node("session::test-task::decision::lost", "session", "decision", &decision)
+            .await
+            .unwrap();
+        assert!(manager.get_session().await.unwrap().unwrap().decisions.is_empty());
+
+        let report = manager.recover().await.unwrap();
+        assert_eq!(report.reattached, vec!["session::test-task::decision::lost".to_string()]);
+        assert_eq!(manager.get_session().await.unwrap().unwrap().decisions.len(), 1);
+
+        // The session node is gone but its items, decisions, context, and checkpoint were left behind
+        manager.store.delete_node("session::test-task").await.unwrap();
+        let report = manager.recover().await.unwrap();
+        assert_eq!(report.removed.len(), 4);
+        assert!(manager.store.query_nodes("session", "checkpoint").await.unwrap().is_empty());
+        assert!(manager.store.query_nodes("session", "item").await.unwrap().is_empty());
+        assert!(manager.store.query_nodes("session", "decision").await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_named_sessions() {
+        let (manager, _temp) = setup_test_manager().await;
+        let first = manager.start_session(None, "Fix login bug!", &["Repro".to_string()]).await.unwrap();
+        assert_eq!(first.name, "fix-login-bug");
+        manager
+            .start_session(Some("billing"), "Refactor billing", &["Split module".to_string()])
+            .await
+            .unwrap();
+        assert!(manager.start_session(Some("billing"), "Again", &[]).await.is_err());
+        assert!(manager.start_session(Some("item"), "Reserved", &[]).await.is_err());
+
+        // Starting a session keeps the previous one intact
+        let session = manager.get_session().await.unwrap().unwrap();
+        assert_eq!(session.name, "billing");
+        manager.add_decision("Keep invoices immutable", "Audit trail", &[]).await.unwrap();
+        let session = manager.switch_session("fix-login-bug").await.unwrap();
+        assert_eq!(session.task, "Fix login bug!");
+        assert!(session.decisions.is_empty());
+        manager
+            .update_task(Some(&session.items[0].id), Some(TaskStatus::Completed), None, None, None)
+            .await
+            .unwrap();
+
+        let sessions = manager.list_sessions().await.unwrap();
+        let names: Vec<&str> = sessions.iter().map(|s| s.name.as_str()).collect();
+        assert_eq!(names, vec!["fix-login-bug", "billing"]);
+        assert!(sessions[0].active);
+        assert_eq!((sessions[0].completed, sessions[0].total), (1, 1));
+
+        // Archiving the active session leaves none active until one is switched to
+        assert_eq!(manager.archive_session(None).await.unwrap(), "fix-login-bug");
+        assert!(manager.get_session().await.unwrap().is_none());
+        assert!(manager.set_context(Some("a.rs"), None, None, None, None).await.is_err());
+        let sessions = manager.list_sessions().await.unwrap();
+        assert_eq!(sessions[1].name, "fix-login-bug");
+        assert!(sessions[1].archived);
+
+        let session = manager.switch_session("billing").await.unwrap();
+        assert_eq!(session.decisions.len(), 1);
+        let session = manager.switch_session("fix-login-bug").await.unwrap();
+        assert_eq!(session.items[0].status, TaskStatus::Completed);
+        assert!(!manager.list_sessions().await.unwrap()[0].archived);
+        assert!(manager.switch_session("missing").await.is_err());
+
+        // A second session for the same task gets a fresh name
+        let again = manager.start_session(None, "Fix login bug", &[]).await.unwrap();
+        assert_eq!(again.name, "fix-login-bug-2");
+    }
+
+    #[tokio::test]
+    async fn test_legacy_session_is_active() {
+        let (manager, _temp) = setup_test_manager().await;
+        let store = &manager.store;
+        store
+            .upsert_node("session::current", "session", "task", &json!({"description": "Old task"}))
+            .await
+            .unwrap();
+        let item = json!({"description": "Old item", "status": "pending"});
+        store.upsert_node("session::item::0", "session", "item", &item).await.unwrap();
+        store.upsert_edge("session::current", "session::item::0", "has_item", "session", None).await.unwrap();
+        let context = json!({"modified_files": ["old.rs"], "working_symbols": []});
+        store.upsert_node("session::context", "session", "context", &context).await.unwrap();
+        assert_eq!(manager.recover().await.unwrap().reattached, vec!["session::context".to_string()]);
+
+        let session = manager.get_session().await.unwrap().unwrap();
+        assert_eq!(session.name, "current");
+        assert_eq!(session.items.len(), 1);
+        assert_eq!(session.context.modified_files, vec!["old.rs".to_string()]);
+
+        manager.start_session(Some("next"), "New task", &[]).await.unwrap();
+        assert_eq!(manager.switch_session("current").await.unwrap().task, "Old task");
+    }
+
+    #[tokio::test]
+    async fn test_checkpoints() {
+        let (manager, _temp) = setup_test_manager().await;
+        let session = manager
+            .start_session(None, "Ship it", &["Write code".to_string(), "Test".to_string()])
+            .await
+            .unwrap();
+        manager.add_decision("Use tokio", "Already a dependency", &[]).await.unwrap();
+        manager
+            .update_task(Some(&session.items[0].id), Some(TaskStatus::Completed), None, None, None)
+            .await
+            .unwrap();
+        // Only significant updates are checkpointed
+        manager.set_context(Some("src/lib.rs"), None, None, None, None).await.unwrap();
+
+        let history = manager.history().await.unwrap();
+        let reasons: Vec<&str> = history.iter().map(|c| c.reason.as_str()).collect();
+        assert_eq!(reasons, vec!["completed: Write code", "decision: Use tokio", "started"]);
+        assert_eq!(history[1].session.decisions.len(), 1);
+        assert_eq!(history[1].session.items[0].status, TaskStatus::Pending);
+
+        // A mistaken update, rolled back to just after the decision
+        manager.add_decision("Rewrite in Go", "Bad idea", &[]).await.unwrap();
+        let after_decision = history[1].seq;
+        let restored = manager.restore_checkpoint(after_decision).await.unwrap();
+        assert_eq!(restored.decisions.len(), 1);
+        assert_eq!(restored.decisions[0].what, "Use tokio");
+        assert_eq!(restored.items.len(), 2);
+        assert_eq!(restored.items[0].status, TaskStatus::Pending);
+        assert!(restored.context.modified_files.is_empty());
+
+        // The restore can be undone
+        let history = manager.history().await.unwrap();
+        assert_eq!(history[0].reason, format!("before restoring #{}", after_decision));
+        let undone = manager.restore_checkpoint(history[0].seq).await.unwrap();
+        assert_eq!(undone.decisions.len(), 2);
+        assert_eq!(undone.context.modified_files, vec!["src/lib.rs".to_string()]);
+        assert!(manager.recover().await.unwrap().is_empty());
+
+        assert!(manager.restore_checkpoint(999).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_checkpoints_are_pruned() {
+        let (manager, _temp) = setup_test_manager().await;
+        manager.start_session(None, "Many decisions", &[]).await.unwrap();
+        for i in 0..MAX_CHECKPOINTS {
+            manager.add_decision(&format!("Decision {}", i), "Because", &[]).await.unwrap();
+        }
+
+        let history = manager.history().await.unwrap();
+        assert_eq!(history.len(), MAX_CHECKPOINTS);
+        assert_eq!(history[0].seq, MAX_CHECKPOINTS as u64 + 1);
+        assert_eq!(history.last().unwrap().reason, "decision: Decision 0");
+    }
 }
@@ -0,0 +1,215 @@
+use crate::store::CodeGraph;
+use std::collections::{HashMap, HashSet};
+
+use super::navigation::{node_file, GraphIndex};
+
+/// How many modules and flows to list in the overview
+const TOP_MODULES: usize = 8;
+const TOP_FLOWS: usize = 5;
+
+/// A directory-level module and its size/connectivity
+#[derive(Debug, Clone)]
+pub struct ModuleSummary {
+    pub path: String,
+    pub files: usize,
+    pub symbols: usize,
+    /// Number of other modules that reference this one
+    pub used_by: usize,
+}
+
+/// A dependency between two modules
+#[derive(Debug, Clone)]
+pub struct ModuleFlow {
+    pub from: String,
+    pub to: String,
+    pub references: usize,
+}
+
+/// Summary of the module dependency graph, for orienting new agents
+#[derive(Debug, Clone, Default)]
+pub struct ArchitectureOverview {
+    /// Most connected modules first
+    pub modules: Vec<ModuleSummary>,
+    /// Dependency layers, foundation (no internal dependencies) first
+    pub layers: Vec<Vec<String>>,
+    /// Heaviest module-to-module dependencies
+    pub flows: Vec<ModuleFlow>,
+}
+
+impl ArchitectureOverview {
+    pub fn is_empty(&self) -> bool {
+        self.modules.is_empty()
+    }
+}
+
+/// Module (directory) a file belongs to
+fn module_of(file: &str) -> String {
+    file.rsplit_once('/')
+        .map(|(dir, _)| dir.to_string())
+        .unwrap_or_else(|| ".".to_string())
+}
+
+/// Summarize the code graph at module level: top modules, layering, and key flows
+pub fn analyze_architecture(graph: &CodeGraph) -> ArchitectureOverview {
+    let index = GraphIndex::build(graph);
+
+    // Module sizes
+    let mut files: HashMap<String, HashSet<String>> = HashMap::new();
+    let mut symbols: HashMap<String, usize> = HashMap::new();
+    for idx in graph.graph.node_indices() {
+        let node = &graph.graph[idx];
+        if matches!(node.kind.as_str(), "unresolved" | "file") {
+            continue;
+        }
+        if let Some(file) = node_file(graph, idx) {
+            let module = module_of(file);
+            files.entry(module.clone()).or_default().insert(file.to_string());
+            *symbols.entry(module).or_default() += 1;
+        }
+    }
+
+    if files.is_empty() {
+        return ArchitectureOverview::default();
+    }
+
+    // Module-level dependency edges
+    let mut deps: HashMap<(String, String), usize> = HashMap::new();
+    for ((from, to), count) in &index.file_edges {
+        let (from, to) = (module_of(from), module_of(to));
+        if from != to {
+            *deps.entry((from, to)).or_default() += count;
+        }
+    }
+
+    let mut used_by: HashMap<&str, usize> = HashMap::new();
+    for (_, to) in deps.keys() {
+        *used_by.entry(to.as_str()).or_default() += 1;
+    }
+
+    let mut modules: Vec<ModuleSummary> = files
+        .iter()
+        .map(|(path, module_files)| ModuleSummary {
+            path: path.clone(),
+            files: module_files.len(),
+            symbols: symbols.get(path).copied().unwrap_or(0),
+            used_by: used_by.get(path.as_str()).copied().unwrap_or(0),
+        })
+        .collect();
+    modules.sort_by(|a, b| {
+        b.used_by
+            .cmp(&a.used_by)
+            .then(b.symbols.cmp(&a.symbols))
+            .then_with(|| a.path.cmp(&b.path))
+    });
+    modules.truncate(TOP_MODULES);
+
+    let mut flows: Vec<ModuleFlow> = deps
+        .iter()
+        .map(|((from, to), references)| ModuleFlow {
+            from: from.clone(),
+            to: to.clone(),
+            references: *references,
+        })
+        .collect();
+    flows.sort_by(|a, b| {
+        b.references
+            .cmp(&a.references)
+            .then_with(|| (&a.from, &a.to).cmp(&(&b.from, &b.to)))
+    });
+    flows.truncate(TOP_FLOWS);
+
+    let module_names: Vec<&String> = files.keys().collect();
+    let layers = compute_layers(&module_names, &deps);
+
+    ArchitectureOverview {
+        modules,
+        layers,
+        flows,
+    }
+}
+
+/// Assign each module a layer: 0 for modules with no internal dependencies, otherwise
+/// one more than the deepest dependency. Cycles are cut off after one pass per module.
+fn compute_layers(modules: &[&String], deps: &HashMap<(String, String), usize>) -> Vec<Vec<String>> {
+    let mut layer: HashMap<&str, usize> = modules.iter().map(|m| (m.as_str(), 0)).collect();
+
+    for _ in 0..modules.len() {
+        let mut changed = false;
+        for (from, to) in deps.keys() {
+            let candidate = layer.get(to.as_str()).copied().unwrap_or(0) + 1;
+            if candidate < modules.len() && layer.get(from.as_str()).copied().unwrap_or(0) < candidate {
+                layer.insert(from.as_str(), candidate);
+                changed = true;
+            }
+        }
+        if !changed {
+            break;
+        }
+    }
+
+    let depth = layer.values().copied().max().unwrap_or(0);
+    let mut layers = vec![Vec::new(); depth + 1];
+    for (module, l) in layer {
+        layers[l].push(module.to_string());
+    }
+    for l in &mut layers {
+        l.sort();
+    }
+    layers.retain(|l| !l.is_empty());
+
+    layers
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_analyze_architecture() {
+        let mut graph = CodeGraph::new();
+        let symbols = [
+            ("src/mcp/tools.rs", "handle"),
+            ("src/learning/patterns.rs", "recall"),
+            ("src/store/db.rs", "query"),
+            ("src/store/graph.rs", "load"),
+        ];
+        for (file, name) in symbols {
+            graph.add_node(
+                format!("{}::{}", file, name),
+                "function".to_string(),
+                serde_json::json!({"name": name, "file": file}),
+            );
+        }
+        graph.add_edge("src/mcp/tools.rs::handle", "src/learning/patterns.rs::recall", "calls".to_string(), None);
+        graph.add_edge("src/mcp/tools.rs::handle", "src/store/db.rs::query", "calls".to_string(), None);
+        graph.add_edge("src/learning/patterns.rs::recall", "src/store/db.rs::query", "calls".to_string(), None);
+
+        let overview = analyze_architecture(&graph);
+
+        assert_eq!(overview.modules[0].path, "src/store");
+        assert_eq!(overview.modules[0].used_by, 2);
+        assert_eq!(overview.modules[0].files, 2);
+        assert_eq!(
+            overview.layers,
+            vec![
+                vec!["src/store".to_string()],
+                vec!["src/learning".to_string()],
+                vec!["src/mcp".to_string()],
+            ]
+        );
+        assert_eq!(overview.flows.len(), 3);
+    }
+
+    #[test]
+    fn test_cycles_terminate() {
+        let mut graph = CodeGraph::new();
+        graph.add_node("a/x.rs::f".into(), "function".into(), serde_json::json!({"name": "f", "file": "a/x.rs"}));
+        graph.add_node("b/y.rs::g".into(), "function".into(), serde_json::json!({"name": "g", "file": "b/y.rs"}));
+        graph.add_edge("a/x.rs::f", "b/y.rs::g", "calls".to_string(), None);
+        graph.add_edge("b/y.rs::g", "a/x.rs::f", "calls".to_string(), None);
+
+        let overview = analyze_architecture(&graph);
+        assert_eq!(overview.modules.len(), 2);
+        assert!(!overview.layers.is_empty());
+    }
+}
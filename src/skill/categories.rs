@@ -10,10 +10,24 @@ pub enum InstructionCategory {
     Workflow,
     Tooling,
     Gotchas,
+    /// Team-defined category from `[skill] categories` in config.toml
+    #[serde(untagged)]
+    Custom(String),
 }
 
 impl InstructionCategory {
-    pub fn to_str(&self) -> &'static str {
+    /// Built-in categories in display order
+    pub const BUILTIN: [InstructionCategory; 7] = [
+        InstructionCategory::Gotchas,
+        InstructionCategory::Architecture,
+        InstructionCategory::Testing,
+        InstructionCategory::Style,
+        InstructionCategory::Navigation,
+        InstructionCategory::Workflow,
+        InstructionCategory::Tooling,
+    ];
+
+    pub fn to_str(&self) -> &str {
         match self {
             InstructionCategory::Architecture => "architecture",
             InstructionCategory::Testing => "testing",
@@ -22,9 +36,12 @@ impl InstructionCategory {
             InstructionCategory::Workflow => "workflow",
             InstructionCategory::Tooling => "tooling",
             InstructionCategory::Gotchas => "gotchas",
+            InstructionCategory::Custom(name) => name,
         }
     }
 
+    /// Parse a category name. Unknown non-empty names become custom categories;
+    /// callers validate them against the configured list.
     pub fn from_str(s: &str) -> Self {
         match s.to_lowercase().as_str() {
             "architecture" => InstructionCategory::Architecture,
@@ -34,10 +51,15 @@ impl InstructionCategory {
             "workflow" => InstructionCategory::Workflow,
             "tooling" => InstructionCategory::Tooling,
             "gotchas" => InstructionCategory::Gotchas,
-            _ => InstructionCategory::Gotchas, // Default to gotchas
+            "" => InstructionCategory::Gotchas, // Default to gotchas
+            other => InstructionCategory::Custom(other.to_string()),
         }
     }
 
+    pub fn is_custom(&self) -> bool {
+        matches!(self, InstructionCategory::Custom(_))
+    }
+
     /// Order for display (gotchas first, most important)
     pub fn display_order(&self) -> u8 {
         match self {
@@ -48,6 +70,45 @@ impl InstructionCategory {
             InstructionCategory::Navigation => 4,
             InstructionCategory::Workflow => 5,
             InstructionCategory::Tooling => 6,
+            InstructionCategory::Custom(_) => 7,
+        }
+    }
+}
+
+/// How important an instruction is. Anything above `Normal` is pinned:
+/// rendered at the top of SKILL.md and never dropped by thresholds.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum InstructionPriority {
+    #[default]
+    Normal,
+    High,
+    Critical,
+}
+
+impl InstructionPriority {
+    pub fn to_str(&self) -> &'static str {
+        match self {
+            InstructionPriority::Normal => "normal",
+            InstructionPriority::High => "high",
+            InstructionPriority::Critical => "critical",
+        }
+    }
+
+    pub fn is_pinned(&self) -> bool {
+        *self > InstructionPriority::Normal
+    }
+}
+
+impl std::str::FromStr for InstructionPriority {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "critical" => Ok(InstructionPriority::Critical),
+            "high" => Ok(InstructionPriority::High),
+            "normal" => Ok(InstructionPriority::Normal),
+            _ => Err(format!("unknown priority '{}' (expected critical, high, or normal)", s)),
         }
     }
 }
@@ -60,6 +121,19 @@ pub struct ProjectInstruction {
     pub source: InstructionSource,
     pub confidence: Option<f32>,
     pub scope: Option<String>,
+    #[serde(default)]
+    pub priority: InstructionPriority,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub freshness: Option<InstructionFreshness>,
+}
+
+/// Where a learned instruction's confidence came from, for staleness checks
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct InstructionFreshness {
+    /// Confidence stored on the source pattern, before decay
+    pub source_confidence: f32,
+    /// When the source pattern was last validated (unix seconds)
+    pub last_validated: Option<i64>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -1,14 +1,27 @@
 use crate::learning::patterns::Pattern;
-use std::collections::HashMap;
+use crate::store::{CodeGraph, Store};
+use anyhow::Result;
+use std::collections::{HashMap, HashSet};
+
+use super::categories::InstructionCategory;
 
 #[derive(Debug, Clone)]
 pub struct Convention {
-    pub patterns: Vec<String>, // Pattern IDs
+    pub patterns: Vec<String>, // Pattern IDs (empty for conventions detected from the graph)
     pub common_prefix: Option<String>,
     pub common_tags: Vec<String>,
     pub summary: String,
+    pub category: InstructionCategory,
+    /// Number of observations backing this convention
+    pub evidence: usize,
 }
 
+/// Minimum number of observations before a detected convention is reported
+const MIN_EVIDENCE: usize = 3;
+
+/// Share of observations that must agree before a style counts as a convention
+const DOMINANCE: f32 = 0.8;
+
 /// Cluster patterns into conventions
 pub fn cluster_conventions(patterns: &[Pattern], min_cluster_size: usize) -> Vec<Convention> {
     let mut conventions = Vec::new();
@@ -19,10 +32,12 @@ pub fn cluster_conventions(patterns: &[Pattern], min_cluster_size: usize) -> Vec
         if pattern_ids.len() >= min_cluster_size {
             let summary = generate_convention_summary(Some(&prefix), &pattern_ids, patterns);
             conventions.push(Convention {
+                evidence: pattern_ids.len(),
                 patterns: pattern_ids,
                 common_prefix: Some(prefix),
                 common_tags: vec![],
                 summary,
+                category: InstructionCategory::Architecture,
             });
         }
     }
@@ -37,10 +52,12 @@ pub fn cluster_conventions(patterns: &[Pattern], min_cluster_size: usize) -> Vec
             }) {
                 let summary = generate_tag_summary(&tags, &pattern_ids, patterns);
                 conventions.push(Convention {
+                    evidence: pattern_ids.len(),
                     patterns: pattern_ids,
                     common_prefix: None,
                     common_tags: tags,
                     summary,
+                    category: InstructionCategory::Architecture,
                 });
             }
         }
@@ -161,10 +178,415 @@ fn summarize_intents(patterns: &[&Pattern]) -> String {
     }
 }
 
+// === Graph-based detection ===
+
+/// Detect conventions directly from the indexed code: naming, error handling,
+/// test layout and module organization.
+pub async fn detect_conventions(graph: &CodeGraph, store: &Store) -> Result<Vec<Convention>> {
+    let mut conventions = Vec::new();
+
+    conventions.extend(detect_naming(graph));
+    conventions.extend(detect_error_handling(graph, store).await?);
+
+    let files = collect_files(graph);
+    conventions.extend(detect_test_layout(graph, &files));
+    conventions.extend(detect_module_layout(&files));
+
+    Ok(conventions)
+}
+
+/// Build a convention that was observed in the code rather than learned from patterns
+fn detected(summary: String, category: InstructionCategory, evidence: usize) -> Convention {
+    Convention {
+        patterns: vec![],
+        common_prefix: None,
+        common_tags: vec![],
+        summary,
+        category,
+        evidence,
+    }
+}
+
+/// Identifier casing styles
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+enum Case {
+    Snake,
+    Camel,
+    Pascal,
+    ScreamingSnake,
+}
+
+impl Case {
+    fn as_str(&self) -> &'static str {
+        match self {
+            Case::Snake => "snake_case",
+            Case::Camel => "camelCase",
+            Case::Pascal => "PascalCase",
+            Case::ScreamingSnake => "SCREAMING_SNAKE_CASE",
+        }
+    }
+}
+
+/// Classify an identifier's casing. Single lowercase words are ambiguous and return None.
+fn classify_case(name: &str) -> Option<Case> {
+    let name = name.trim_start_matches('_');
+    let first = name.chars().next()?;
+    let has_upper = name.chars().any(|c| c.is_uppercase());
+    let has_lower = name.chars().any(|c| c.is_lowercase());
+    let has_underscore = name.contains('_');
+
+    if !name.chars().all(|c| c.is_alphanumeric() || c == '_') {
+        return None;
+    }
+
+    if has_upper && !has_lower {
+        (has_underscore || name.len() > 1).then_some(Case::ScreamingSnake)
+    } else if first.is_uppercase() {
+        (!has_underscore).then_some(Case::Pascal)
+    } else if has_upper {
+        (!has_underscore).then_some(Case::Camel)
+    } else if has_underscore {
+        Some(Case::Snake)
+    } else {
+        None
+    }
+}
+
+/// File extension used to group conventions per language
+fn extension(path: &str) -> Option<&str> {
+    let name = path.rsplit('/').next()?;
+    name.rsplit_once('.').map(|(_, ext)| ext)
+}
+
+/// Find the dominant value in a tally, if it is dominant enough to be a convention
+fn dominant<K: Copy>(tally: &HashMap<K, usize>) -> Option<(K, usize, usize)> {
+    let total: usize = tally.values().sum();
+    let (key, count) = tally.iter().max_by_key(|(_, c)| **c)?;
+
+    if total >= MIN_EVIDENCE && *count as f32 / total as f32 >= DOMINANCE {
+        Some((*key, *count, total))
+    } else {
+        None
+    }
+}
+
+/// Detect naming conventions for functions and types, per file extension
+fn detect_naming(graph: &CodeGraph) -> Vec<Convention> {
+    let mut functions: HashMap<&str, HashMap<Case, usize>> = HashMap::new();
+    let mut types: HashMap<&str, HashMap<Case, usize>> = HashMap::new();
+
+    for node in graph.graph.node_weights() {
+        let bucket = match node.kind.as_str() {
+            "function" | "method" => &mut functions,
+            "struct" | "class" | "enum" | "trait" | "interface" | "type" => &mut types,
+            _ => continue,
+        };
+
+        let (Some(name), Some(ext)) = (
+            node.data.get("name").and_then(|v| v.as_str()),
+            node.data.get("file").and_then(|v| v.as_str()).and_then(extension),
+        ) else {
+            continue;
+        };
+
+        if let Some(case) = classify_case(name) {
+            *bucket.entry(ext).or_default().entry(case).or_default() += 1;
+        }
+    }
+
+    let mut conventions = Vec::new();
+
+    for (label, tallies) in [("Functions", &functions), ("Types", &types)] {
+        let mut exts: Vec<_> = tallies.keys().collect();
+        exts.sort();
+
+        for ext in exts {
+            if let Some((case, count, total)) = dominant(&tallies[ext]) {
+                conventions.push(detected(
+                    format!(
+                        "{} in `.{}` files use {} ({} of {})",
+                        label,
+                        ext,
+                        case.as_str(),
+                        count,
+                        total
+                    ),
+                    InstructionCategory::Style,
+                    count,
+                ));
+            }
+        }
+    }
+
+    conventions
+}
+
+/// Detect how fallible functions report errors
+async fn detect_error_handling(graph: &CodeGraph, store: &Store) -> Result<Vec<Convention>> {
+    let mut result_alias = 0;
+    let mut result_explicit = 0;
+    let mut go_error = 0;
+
+    for node in graph.graph.node_weights() {
+        if !matches!(node.kind.as_str(), "function" | "method") {
+            continue;
+        }
+
+        let file = node.data.get("file").and_then(|v| v.as_str()).unwrap_or("");
+        if !matches!(extension(file), Some("rs" | "go")) {
+            continue;
+        }
+        // A slim graph leaves signatures in the store
+        let data = graph.node_data(store, node).await?;
+        let signature = data
+            .get("signature")
+            .and_then(|v| v.as_str())
+            .unwrap_or("");
+
+        match extension(file) {
+            Some("rs") => {
+                if let Some(ret) = signature.split("-> ").nth(1) {
+                    if let Some(args) = ret.trim_start().strip_prefix("Result<") {
+                        if has_top_level_comma(args) {
+                            result_explicit += 1;
+                        } else {
+                            result_alias += 1;
+                        }
+                    }
+                }
+            }
+            Some("go") => {
+                let ret = signature.trim_end_matches('{').trim_end();
+                if ret.ends_with(" error") || ret.ends_with(" error)") {
+                    go_error += 1;
+                }
+            }
+            _ => {}
+        }
+    }
+
+    let mut conventions = Vec::new();
+
+    let rust_total = result_alias + result_explicit;
+    if rust_total >= MIN_EVIDENCE {
+        let summary = if result_alias as f32 / rust_total as f32 >= DOMINANCE {
+            Some(format!(
+                "Fallible Rust functions return a `Result<T>` alias (anyhow-style) ({} of {})",
+                result_alias, rust_total
+            ))
+        } else if result_explicit as f32 / rust_total as f32 >= DOMINANCE {
+            Some(format!(
+                "Fallible Rust functions return `Result<T, E>` with explicit error types ({} of {})",
+                result_explicit, rust_total
+            ))
+        } else {
+            None
+        };
+
+        if let Some(summary) = summary {
+            conventions.push(detected(
+                summary,
+                InstructionCategory::Architecture,
+                result_alias.max(result_explicit),
+            ));
+        }
+    }
+
+    if go_error >= MIN_EVIDENCE {
+        conventions.push(detected(
+            format!("Go functions return `error` as the last value ({} functions)", go_error),
+            InstructionCategory::Architecture,
+            go_error,
+        ));
+    }
+
+    Ok(conventions)
+}
+
+/// Check whether generic arguments (after the opening `<`) contain a top-level comma
+fn has_top_level_comma(args: &str) -> bool {
+    let mut depth = 0;
+    for c in args.chars() {
+        match c {
+            '<' | '(' | '[' => depth += 1,
+            ')' | ']' => depth -= 1,
+            '>' if depth == 0 => return false,
+            '>' => depth -= 1,
+            ',' if depth == 0 => return true,
+            _ => {}
+        }
+    }
+    false
+}
+
+/// Collect all file paths known to the graph
+fn collect_files(graph: &CodeGraph) -> HashSet<String> {
+    graph
+        .graph
+        .node_weights()
+        .filter_map(|node| {
+            node.data
+                .get("file")
+                .or_else(|| node.data.get("path"))
+                .and_then(|v| v.as_str())
+                .map(String::from)
+        })
+        .collect()
+}
+
+/// Test file layouts we recognise
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+enum TestLayout {
+    TestsDir,
+    GoSuffix,
+    JsSuffix,
+    PyPrefix,
+}
+
+impl TestLayout {
+    fn describe(&self) -> &'static str {
+        match self {
+            TestLayout::TestsDir => "in a top-level `tests/` directory",
+            TestLayout::GoSuffix => "next to sources as `*_test.go`",
+            TestLayout::JsSuffix => "next to sources as `*.test.*` / `*.spec.*`",
+            TestLayout::PyPrefix => "as `test_*.py` / `*_test.py` modules",
+        }
+    }
+}
+
+/// Classify a file path as a test file, returning its layout
+fn classify_test_file(path: &str) -> Option<TestLayout> {
+    let name = path.rsplit('/').next().unwrap_or(path);
+
+    if path.starts_with("tests/") || path.starts_with("test/") {
+        Some(TestLayout::TestsDir)
+    } else if name.ends_with("_test.go") {
+        Some(TestLayout::GoSuffix)
+    } else if name.contains(".test.") || name.contains(".spec.") {
+        Some(TestLayout::JsSuffix)
+    } else if name.ends_with(".py") && (name.starts_with("test_") || name.ends_with("_test.py")) {
+        Some(TestLayout::PyPrefix)
+    } else {
+        None
+    }
+}
+
+/// Detect where tests live
+fn detect_test_layout(graph: &CodeGraph, files: &HashSet<String>) -> Vec<Convention> {
+    let mut conventions = Vec::new();
+
+    let mut layouts: HashMap<TestLayout, usize> = HashMap::new();
+    for file in files {
+        if let Some(layout) = classify_test_file(file) {
+            *layouts.entry(layout).or_default() += 1;
+        }
+    }
+
+    if let Some((layout, count, _)) = dominant(&layouts) {
+        conventions.push(detected(
+            format!("Test files live {} ({} files)", layout.describe(), count),
+            InstructionCategory::Testing,
+            count,
+        ));
+    }
+
+    // Rust unit tests: `mod tests` blocks inside source files
+    let inline: HashSet<&str> = graph
+        .graph
+        .node_weights()
+        .filter(|node| node.kind == "module")
+        .filter(|node| node.data.get("name").and_then(|v| v.as_str()) == Some("tests"))
+        .filter_map(|node| node.data.get("file").and_then(|v| v.as_str()))
+        .filter(|file| classify_test_file(file).is_none())
+        .collect();
+
+    if inline.len() >= MIN_EVIDENCE {
+        conventions.push(detected(
+            format!(
+                "Unit tests are inline `#[cfg(test)] mod tests` blocks ({} files)",
+                inline.len()
+            ),
+            InstructionCategory::Testing,
+            inline.len(),
+        ));
+    }
+
+    conventions
+}
+
+/// Detect how directories are organized into modules
+fn detect_module_layout(files: &HashSet<String>) -> Vec<Convention> {
+    let mut conventions = Vec::new();
+
+    let count = |index: &str| {
+        files
+            .iter()
+            .filter(|f| f.rsplit('/').next() == Some(index) && f.contains('/'))
+            .count()
+    };
+
+    let mod_rs = count("mod.rs");
+    if mod_rs >= MIN_EVIDENCE {
+        conventions.push(detected(
+            format!(
+                "Rust modules with submodules use a `mod.rs` file in their directory ({} directories)",
+                mod_rs
+            ),
+            InstructionCategory::Architecture,
+            mod_rs,
+        ));
+    }
+
+    let init_py = count("__init__.py");
+    if init_py >= MIN_EVIDENCE {
+        conventions.push(detected(
+            format!(
+                "Python packages are directories with an `__init__.py` ({} packages)",
+                init_py
+            ),
+            InstructionCategory::Architecture,
+            init_py,
+        ));
+    }
+
+    let index_ts = count("index.ts") + count("index.js") + count("index.tsx");
+    if index_ts >= MIN_EVIDENCE {
+        conventions.push(detected(
+            format!(
+                "Directories expose their API through an `index` barrel file ({} directories)",
+                index_ts
+            ),
+            InstructionCategory::Architecture,
+            index_ts,
+        ));
+    }
+
+    conventions
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::config::{Config, GraphConfig, GraphMode};
     use crate::learning::Scope;
+    use tempfile::TempDir;
+
+    async fn setup_test_store() -> (Store, TempDir) {
+        let temp_dir = TempDir::new().unwrap();
+        let temp_path = temp_dir.path();
+
+        let config = Config {
+            project_root: temp_path.to_path_buf(),
+            cache_dir: temp_path.join("cache"),
+            codegraph_dir: temp_path.join(".codegraph"),
+            store_db_path: temp_path.join("cache/store.db"),
+            learning_db_path: temp_path.join(".codegraph/learning.db"),
+            settings: crate::config::ConfigFile::default().into(),
+        };
+
+        let store = Store::open(&config).await.unwrap();
+        (store, temp_dir)
+    }
 
     fn make_pattern(id: &str, intent: &str, paths: Vec<String>, tags: Vec<String>) -> Pattern {
         Pattern {
@@ -211,4 +633,82 @@ mod tests {
         let conventions = cluster_conventions(&patterns, 3);
         assert_eq!(conventions.len(), 1);
     }
+
+    fn add_symbol(graph: &mut CodeGraph, file: &str, name: &str, kind: &str, signature: &str) {
+        graph.add_node(
+            format!("{}::{}", file, name),
+            kind.to_string(),
+            serde_json::json!({"name": name, "file": file, "signature": signature}),
+        );
+    }
+
+    #[test]
+    fn test_classify_case() {
+        assert_eq!(classify_case("parse_file"), Some(Case::Snake));
+        assert_eq!(classify_case("parseFile"), Some(Case::Camel));
+        assert_eq!(classify_case("CodeGraph"), Some(Case::Pascal));
+        assert_eq!(classify_case("MIN_EVIDENCE"), Some(Case::ScreamingSnake));
+        assert_eq!(classify_case("parse"), None);
+    }
+
+    #[tokio::test]
+    async fn test_detect_conventions_from_graph() {
+        let (store, _temp) = setup_test_store().await;
+        let mut graph = CodeGraph::new();
+        for name in ["load_config", "parse_file", "open_store", "index_all"] {
+            add_symbol(
+                &mut graph,
+                &format!("src/{}/mod.rs", name),
+                name,
+                "function",
+                &format!("pub fn {}() -> Result<()> {{", name),
+            );
+        }
+        for file in ["src/a.rs", "src/b.rs", "src/c.rs"] {
+            add_symbol(&mut graph, file, "tests", "module", "mod tests {");
+        }
+
+        let conventions = detect_conventions(&graph, &store).await.unwrap();
+        let summaries: Vec<_> = conventions.iter().map(|c| c.summary.as_str()).collect();
+
+        assert!(summaries.contains(&"Functions in `.rs` files use snake_case (4 of 4)"));
+        assert!(summaries.iter().any(|s| s.contains("`Result<T>` alias")));
+        assert!(summaries.iter().any(|s| s.contains("inline `#[cfg(test)] mod tests`")));
+        assert!(summaries.iter().any(|s| s.contains("`mod.rs`")));
+        assert!(conventions.iter().all(|c| c.patterns.is_empty() && c.evidence >= MIN_EVIDENCE));
+    }
+
+    #[test]
+    fn test_mixed_naming_is_not_a_convention() {
+        let mut graph = CodeGraph::new();
+        for name in ["load_config", "parseFile", "open_store", "indexAll"] {
+            add_symbol(&mut graph, "src/lib.ts", name, "function", "");
+        }
+
+        assert!(detect_naming(&graph).is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_error_handling_in_slim_graph() {
+        let (store, _temp) = setup_test_store().await;
+        for name in ["load_config", "parse_file", "open_store"] {
+            let data = serde_json::json!({
+                "name": name,
+                "file": "src/lib.rs",
+                "signature": format!("pub fn {}() -> Result<(), Error> {{", name),
+            });
+            store.upsert_node(&format!("src/lib.rs::{}", name), "code", "function", &data).await.unwrap();
+        }
+        let settings = GraphConfig {
+            mode: GraphMode::Slim,
+            ..Default::default()
+        };
+        let graph = CodeGraph::load(&store, &settings).await.unwrap();
+        assert!(graph.get_node("src/lib.rs::parse_file").unwrap().data.get("signature").is_none());
+
+        // Signatures are read back from the store
+        let conventions = detect_error_handling(&graph, &store).await.unwrap();
+        assert_eq!(conventions.len(), 1);
+        assert!(conventions[0].summary.contains("`Result<T, E>`"), "{}", conventions[0].summary);
+    }
 }
@@ -1,34 +1,90 @@
 use anyhow::Result;
 use libsql::Connection;
+use std::collections::HashMap;
 use std::sync::Arc;
 
+use crate::learning::confidence::{effective_confidence, DEFAULT_HALF_LIFE_DAYS};
 use crate::learning::failures::{Failure, FailureStore, Severity};
 use crate::learning::patterns::{Pattern, PatternStore};
+use crate::learning::Scope;
+use crate::store::{CodeGraph, Store};
+
+use super::architecture::{analyze_architecture, ArchitectureOverview};
+use super::categories::{
+    InstructionCategory, InstructionFreshness, InstructionPriority, InstructionSource,
+    ProjectInstruction,
+};
+use super::conventions::{cluster_conventions, detect_conventions, Convention};
+use super::navigation::{generate_graph_navigation_hints, generate_navigation_hints, NavigationHint};
+
+/// Minimum pattern confidence used when no threshold is given
+pub const DEFAULT_CONFIDENCE_THRESHOLD: f32 = 0.7;
+
+/// Files and tags a distilled skill is limited to; empty lists mean everywhere
+#[derive(Debug, Clone, Default)]
+pub struct SkillScope {
+    pub paths: Vec<String>,
+    pub tags: Vec<String>,
+}
 
-use super::categories::{InstructionCategory, InstructionSource, ProjectInstruction};
-use super::conventions::{cluster_conventions, Convention};
-use super::navigation::{generate_navigation_hints, NavigationHint};
+impl SkillScope {
+    /// Whether this covers the whole project, so the result may be written to SKILL.md
+    pub fn is_project(&self) -> bool {
+        self.paths.is_empty() && self.tags.is_empty()
+    }
+}
 
+#[derive(Debug, Clone)]
 pub struct DistillResult {
     pub instructions: Vec<ProjectInstruction>,
     pub conventions: Vec<Convention>,
     pub navigation_hints: Vec<NavigationHint>,
+    pub architecture: ArchitectureOverview,
 }
 
-/// Distill project skill from patterns and failures
+/// Distill project skill from patterns, failures, and conventions detected in the code graph.
+///
+/// When `scope` names paths or tags, only knowledge whose scope covers at least one of
+/// those paths and shares a tag (if both sides have tags) is included (per-module skill generation).
 pub async fn distill_project_skill(
     pattern_store: &PatternStore,
     failure_store: &FailureStore,
     manual_store: &ManualInstructionStore,
+    store: &Store,
+    graph: &tokio::sync::RwLock<CodeGraph>,
     confidence_threshold: f32,
+    scope: &SkillScope,
 ) -> Result<DistillResult> {
+    let (paths, tags) = (scope.paths.as_slice(), scope.tags.as_slice());
     let mut instructions = Vec::new();
+    let priorities = manual_store.priorities().await?;
+    let pinned = |id: &str| priorities.get(id).is_some_and(|p| p.is_pinned());
+    let patterns = pattern_store.list_all().await?;
+    let failures = failure_store.list_all().await?;
+
+    // Analyze the graph up front; only convention detection awaits, to read
+    // signatures a slim graph leaves in the store.
+    // Pattern confidence is decayed against the graph so stale guidance drops out.
+    let now = chrono::Utc::now().timestamp();
+    let (detected_conventions, graph_hints, architecture, effective) = {
+        let graph = graph.read().await;
+        let effective: HashMap<&str, f32> = patterns
+            .iter()
+            .map(|p| (p.id.as_str(), effective_confidence(p, Some(&graph), now, DEFAULT_HALF_LIFE_DAYS)))
+            .collect();
+        (
+            detect_conventions(&graph, store).await?,
+            generate_graph_navigation_hints(&graph),
+            analyze_architecture(&graph),
+            effective,
+        )
+    };
 
     // Get high-confidence patterns
-    let patterns = pattern_store.list_all().await?;
     let high_conf_patterns: Vec<_> = patterns
         .iter()
-        .filter(|p| p.confidence >= confidence_threshold)
+        .filter(|p| effective[p.id.as_str()] >= confidence_threshold || pinned(&p.id))
+        .filter(|p| scope_covers_paths(&p.scope, paths, tags))
         .cloned()
         .collect();
 
@@ -42,16 +98,21 @@ pub async fn distill_project_skill(
             source: InstructionSource::Pattern {
                 id: pattern.id.clone(),
             },
-            confidence: Some(pattern.confidence),
+            confidence: Some(effective[pattern.id.as_str()]),
             scope: format_scope(&pattern.scope.include_paths),
+            priority: InstructionPriority::Normal,
+            freshness: Some(InstructionFreshness {
+                source_confidence: pattern.confidence,
+                last_validated: pattern.last_validated,
+            }),
         });
     }
 
     // Get critical and major failures
-    let failures = failure_store.list_all().await?;
     let important_failures: Vec<_> = failures
         .iter()
-        .filter(|f| matches!(f.severity, Severity::Critical | Severity::Major))
+        .filter(|f| matches!(f.severity, Severity::Critical | Severity::Major) || pinned(&f.id))
+        .filter(|f| scope_covers_paths(&f.scope, paths, tags))
         .cloned()
         .collect();
 
@@ -66,28 +127,38 @@ pub async fn distill_project_skill(
             },
             confidence: None,
             scope: format_scope(&failure.scope.include_paths),
+            priority: InstructionPriority::Normal,
+            freshness: None,
         });
     }
 
-    // Cluster patterns into conventions
-    let conventions = cluster_conventions(&high_conf_patterns, 3);
+    // Cluster patterns into conventions, then add those observed in the code itself
+    let mut conventions = cluster_conventions(&high_conf_patterns, 3);
+    conventions.extend(detected_conventions);
 
     // Add convention instructions
     for convention in &conventions {
         instructions.push(ProjectInstruction {
             id: format!("conv_{}", instructions.len()),
             instruction: convention.summary.clone(),
-            category: InstructionCategory::Architecture,
+            category: convention.category.clone(),
             source: InstructionSource::Convention {
                 pattern_ids: convention.patterns.clone(),
             },
             confidence: None,
             scope: convention.common_prefix.clone(),
+            priority: InstructionPriority::Normal,
+            freshness: None,
         });
     }
 
-    // Generate navigation hints
-    let navigation_hints = generate_navigation_hints(&high_conf_patterns);
+    // Generate navigation hints from learned patterns and from the graph structure
+    let mut navigation_hints = generate_navigation_hints(&high_conf_patterns);
+    navigation_hints.extend(
+        graph_hints
+            .into_iter()
+            .filter(|hint| hint_covers_paths(&hint.path, paths)),
+    );
 
     // Add navigation instructions
     for hint in &navigation_hints {
@@ -98,17 +169,40 @@ pub async fn distill_project_skill(
             source: InstructionSource::Manual { reason: None },
             confidence: None,
             scope: Some(hint.path.clone()),
+            priority: InstructionPriority::Normal,
+            freshness: None,
         });
     }
 
     // Add manual instructions
-    let manual = manual_store.list_all().await?;
+    let manual = manual_store.list_relevant(paths, tags).await?;
     instructions.extend(manual);
 
+    // Drop pins whose pattern, failure, or manual instruction has since been deleted
+    let live_ids: std::collections::HashSet<String> = patterns
+        .iter()
+        .map(|p| p.id.clone())
+        .chain(failures.iter().map(|f| f.id.clone()))
+        .chain(manual_store.list_all().await?.into_iter().map(|i| i.id))
+        .collect();
+    for id in priorities.keys().filter(|id| !live_ids.contains(*id)) {
+        manual_store
+            .set_priority(id, InstructionPriority::Normal)
+            .await?;
+    }
+
+    // Apply pins
+    for instruction in &mut instructions {
+        if let Some(priority) = priorities.get(&instruction.id) {
+            instruction.priority = *priority;
+        }
+    }
+
     Ok(DistillResult {
         instructions,
         conventions,
         navigation_hints,
+        architecture,
     })
 }
 
@@ -149,6 +243,26 @@ fn format_dont_instruction(failure: &Failure) -> String {
     format!("❌ {} — {}", failure.cause, failure.avoidance_rule)
 }
 
+/// Check whether a scope applies to any of the given paths.
+/// An empty path list means "everywhere", so only tags are checked.
+fn scope_covers_paths(scope: &Scope, paths: &[String], tags: &[String]) -> bool {
+    if paths.is_empty() {
+        scope.matches(None, &[], tags)
+    } else {
+        paths.iter().any(|p| scope.matches(Some(p), &[], tags))
+    }
+}
+
+/// Check whether a navigation hint is relevant to the given paths (same directory tree).
+/// A hint for a root-level file only covers other root-level paths.
+fn hint_covers_paths(hint_path: &str, paths: &[String]) -> bool {
+    let covers = |p: &String| match hint_path.rsplit_once('/') {
+        Some((dir, _)) => p.strip_prefix(dir).is_some_and(|rest| rest.is_empty() || rest.starts_with('/')),
+        None => !p.contains('/'),
+    };
+    paths.is_empty() || paths.iter().any(covers)
+}
+
 /// Format scope paths for display
 fn format_scope(paths: &[String]) -> Option<String> {
     if paths.is_empty() {
@@ -175,20 +289,23 @@ impl ManualInstructionStore {
         instruction: &str,
         category: InstructionCategory,
         reason: Option<&str>,
+        scope: &Scope,
     ) -> Result<String> {
         let id = uuid::Uuid::new_v4().to_string();
         let now = chrono::Utc::now().timestamp();
+        let scope_json = serde_json::to_string(scope)?;
 
         self.db
             .execute(
-                "INSERT INTO instructions (id, instruction, category, reason, created_at)
-                 VALUES (?1, ?2, ?3, ?4, ?5)",
+                "INSERT INTO instructions (id, instruction, category, reason, created_at, scope)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
                 libsql::params![
                     id.as_str(),
                     instruction,
                     category.to_str(),
                     reason.unwrap_or(""),
-                    now
+                    now,
+                    scope_json.as_str()
                 ],
             )
             .await?;
@@ -197,10 +314,35 @@ impl ManualInstructionStore {
     }
 
     pub async fn list_all(&self) -> Result<Vec<ProjectInstruction>> {
+        Ok(self
+            .list_with_scope()
+            .await?
+            .into_iter()
+            .map(|(inst, _)| inst)
+            .collect())
+    }
+
+    /// List instructions whose scope applies to any of the given paths/tags.
+    /// Unscoped instructions are always included.
+    pub async fn list_relevant(
+        &self,
+        paths: &[String],
+        tags: &[String],
+    ) -> Result<Vec<ProjectInstruction>> {
+        Ok(self
+            .list_with_scope()
+            .await?
+            .into_iter()
+            .filter(|(_, scope)| scope_covers_paths(scope, paths, tags))
+            .map(|(inst, _)| inst)
+            .collect())
+    }
+
+    async fn list_with_scope(&self) -> Result<Vec<(ProjectInstruction, Scope)>> {
         let mut rows = self
             .db
             .query(
-                "SELECT id, instruction, category, reason FROM instructions ORDER BY created_at",
+                "SELECT id, instruction, category, reason, scope FROM instructions ORDER BY created_at",
                 (),
             )
             .await?;
@@ -208,40 +350,179 @@ impl ManualInstructionStore {
         let mut instructions = Vec::new();
 
         while let Some(row) = rows.next().await? {
-            let reason: String = row.get(3)?;
-            instructions.push(ProjectInstruction {
-                id: row.get(0)?,
-                instruction: row.get(1)?,
-                category: InstructionCategory::from_str(&row.get::<String>(2)?),
-                source: InstructionSource::Manual {
-                    reason: if reason.is_empty() {
-                        None
-                    } else {
-                        Some(reason)
-                    },
-                },
-                confidence: None,
-                scope: None,
-            });
+            instructions.push(row_to_instruction(&row)?);
         }
 
         Ok(instructions)
     }
 
-    pub async fn delete(&self, id: &str) -> Result<()> {
+    /// Get a manual instruction by ID
+    pub async fn get(&self, id: &str) -> Result<Option<ProjectInstruction>> {
+        let mut rows = self
+            .db
+            .query(
+                "SELECT id, instruction, category, reason, scope FROM instructions WHERE id = ?1",
+                [id],
+            )
+            .await?;
+
+        if let Some(row) = rows.next().await? {
+            Ok(Some(row_to_instruction(&row)?.0))
+        } else {
+            Ok(None)
+        }
+    }
+
+    /// Update an existing instruction. Fields left as `None` keep their current value.
+    /// Returns false if no instruction with the given ID exists.
+    pub async fn update(
+        &self,
+        id: &str,
+        instruction: Option<&str>,
+        category: Option<InstructionCategory>,
+        reason: Option<&str>,
+    ) -> Result<bool> {
+        let Some(existing) = self.get(id).await? else {
+            return Ok(false);
+        };
+
+        let existing_reason = match &existing.source {
+            InstructionSource::Manual { reason } => reason.clone().unwrap_or_default(),
+            _ => String::new(),
+        };
+
         self.db
-            .execute("DELETE FROM instructions WHERE id = ?1", [id])
+            .execute(
+                "UPDATE instructions SET instruction = ?1, category = ?2, reason = ?3 WHERE id = ?4",
+                libsql::params![
+                    instruction.unwrap_or(&existing.instruction),
+                    category.unwrap_or(existing.category).to_str(),
+                    reason.unwrap_or(&existing_reason),
+                    id
+                ],
+            )
             .await?;
+
+        Ok(true)
+    }
+
+    /// Set the priority of a pattern, failure, or manual instruction by ID.
+    /// Setting `Normal` removes the pin.
+    pub async fn set_priority(&self, id: &str, priority: InstructionPriority) -> Result<()> {
+        if priority.is_pinned() {
+            self.db
+                .execute(
+                    "INSERT INTO instruction_priorities (id, priority, updated_at) VALUES (?1, ?2, ?3)
+                     ON CONFLICT(id) DO UPDATE SET priority = excluded.priority, updated_at = excluded.updated_at",
+                    libsql::params![id, priority.to_str(), chrono::Utc::now().timestamp()],
+                )
+                .await?;
+        } else {
+            self.db
+                .execute("DELETE FROM instruction_priorities WHERE id = ?1", [id])
+                .await?;
+        }
         Ok(())
     }
+
+    /// All pinned IDs and their priority
+    pub async fn priorities(&self) -> Result<HashMap<String, InstructionPriority>> {
+        let mut rows = self
+            .db
+            .query("SELECT id, priority FROM instruction_priorities", ())
+            .await?;
+
+        let mut priorities = HashMap::new();
+        while let Some(row) = rows.next().await? {
+            priorities.insert(
+                row.get::<String>(0)?,
+                row.get::<String>(1)?.parse().unwrap_or_default(),
+            );
+        }
+
+        Ok(priorities)
+    }
+
+    /// Delete an instruction. Returns false if no instruction with the given ID exists.
+    pub async fn delete(&self, id: &str) -> Result<bool> {
+        let deleted = self
+            .db
+            .execute("DELETE FROM instructions WHERE id = ?1", [id])
+            .await?;
+        self.set_priority(id, InstructionPriority::Normal).await?;
+        Ok(deleted > 0)
+    }
 }
 
-use crate::learning::Scope;
+/// Convert a row of `id, instruction, category, reason, scope` into an instruction
+fn row_to_instruction(row: &libsql::Row) -> Result<(ProjectInstruction, Scope)> {
+    let reason: String = row.get(3)?;
+    let scope: Scope = match row.get::<Option<String>>(4)? {
+        Some(json) => serde_json::from_str(&json)?,
+        None => Scope::default(),
+    };
+
+    let instruction = ProjectInstruction {
+        id: row.get(0)?,
+        instruction: row.get(1)?,
+        category: InstructionCategory::from_str(&row.get::<String>(2)?),
+        source: InstructionSource::Manual {
+            reason: if reason.is_empty() {
+                None
+            } else {
+                Some(reason)
+            },
+        },
+        confidence: None,
+        scope: format_scope(&scope.include_paths),
+        priority: InstructionPriority::Normal,
+        freshness: None,
+    };
+
+    Ok((instruction, scope))
+}
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    async fn setup_stores() -> (Store, PatternStore, FailureStore, ManualInstructionStore, tempfile::TempDir) {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let temp_path = temp_dir.path();
+
+        let config = crate::config::Config {
+            project_root: temp_path.to_path_buf(),
+            cache_dir: temp_path.join("cache"),
+            codegraph_dir: temp_path.join(".codegraph"),
+            store_db_path: temp_path.join("cache/store.db"),
+            learning_db_path: temp_path.join(".codegraph/learning.db"),
+            settings: crate::config::ConfigFile::default().into(),
+        };
+
+        let store = Store::open(&config).await.unwrap();
+        let db = Arc::new(store.learning_db.clone());
+        (
+            store,
+            PatternStore::new(db.clone()),
+            FailureStore::new(db.clone()),
+            ManualInstructionStore::new(db),
+            temp_dir,
+        )
+    }
+
+    #[test]
+    fn test_hint_covers_paths() {
+        let paths = |ps: &[&str]| ps.iter().map(|p| p.to_string()).collect::<Vec<_>>();
+        assert!(hint_covers_paths("src/a/x.rs", &[]));
+        assert!(hint_covers_paths("src/a/x.rs", &paths(&["src/a"])));
+        assert!(hint_covers_paths("src/a/x.rs", &paths(&["src/a/y.rs"])));
+        assert!(!hint_covers_paths("src/a/x.rs", &paths(&["src/ab/y.rs"])));
+        assert!(!hint_covers_paths("src/a/x.rs", &paths(&["src"])));
+
+        assert!(hint_covers_paths("main.rs", &paths(&["build.rs"])));
+        assert!(!hint_covers_paths("main.rs", &paths(&["src/lib.rs"])));
+    }
+
     #[test]
     fn test_infer_category() {
         let pattern = Pattern {
@@ -268,4 +549,138 @@ mod tests {
             InstructionCategory::Testing
         );
     }
+
+    #[tokio::test]
+    async fn test_manual_instruction_update_and_delete() {
+        let (_store, _, _, manual_store, _temp) = setup_stores().await;
+
+        let id = manual_store
+            .add(
+                "Run cargo fmt",
+                InstructionCategory::Style,
+                Some("CI checks it"),
+                &Scope::default(),
+            )
+            .await
+            .unwrap();
+
+        // Update only the text; category and reason are kept
+        let updated = manual_store
+            .update(&id, Some("Run cargo fmt before committing"), None, None)
+            .await
+            .unwrap();
+        assert!(updated);
+
+        let inst = manual_store.get(&id).await.unwrap().unwrap();
+        assert_eq!(inst.instruction, "Run cargo fmt before committing");
+        assert_eq!(inst.category, InstructionCategory::Style);
+        assert!(matches!(
+            inst.source,
+            InstructionSource::Manual { reason: Some(ref r) } if r == "CI checks it"
+        ));
+
+        // Unknown IDs are reported, not silently ignored
+        assert!(!manual_store.update("missing", Some("x"), None, None).await.unwrap());
+        assert!(!manual_store.delete("missing").await.unwrap());
+
+        assert!(manual_store.delete(&id).await.unwrap());
+        assert!(manual_store.get(&id).await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_manual_instruction_scoping() {
+        let (_store, _, _, manual_store, _temp) = setup_stores().await;
+
+        manual_store
+            .add("Use thiserror", InstructionCategory::Style, None, &Scope::default())
+            .await
+            .unwrap();
+        manual_store
+            .add(
+                "Parsers must be pure",
+                InstructionCategory::Architecture,
+                None,
+                &Scope {
+                    include_paths: vec!["src/code/**".to_string()],
+                    ..Scope::default()
+                },
+            )
+            .await
+            .unwrap();
+
+        let code = manual_store
+            .list_relevant(&["src/code/parser.rs".to_string()], &[])
+            .await
+            .unwrap();
+        assert_eq!(code.len(), 2);
+        assert!(code.iter().any(|i| i.scope.as_deref() == Some("src/code/**")));
+
+        let mcp = manual_store
+            .list_relevant(&["src/mcp/tools.rs".to_string()], &[])
+            .await
+            .unwrap();
+        assert_eq!(mcp.len(), 1);
+        assert_eq!(mcp[0].instruction, "Use thiserror");
+
+        // No paths in play: everything applies
+        assert_eq!(manual_store.list_relevant(&[], &[]).await.unwrap().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_distill_drops_stale_entries() {
+        let (store, pattern_store, failure_store, manual_store, _temp) = setup_stores().await;
+
+        let new_pattern = |intent: &str| crate::learning::patterns::NewPattern {
+            intent: intent.to_string(),
+            mechanism: None,
+            examples: vec![],
+            scope: Scope::default(),
+            confidence: 0.9,
+        };
+        let fresh = pattern_store.create(&new_pattern("Use async")).await.unwrap();
+        let old = pattern_store.create(&new_pattern("Use callbacks")).await.unwrap();
+
+        // A year old and never validated: decays well below the threshold
+        let year_ago = chrono::Utc::now().timestamp() - 365 * 86400;
+        store.learning_db.execute(
+            "UPDATE patterns SET created_at = ?1 WHERE id = ?2",
+            libsql::params![year_ago, old.id.clone()],
+        )
+        .await
+        .unwrap();
+
+        // Pin left behind by a pattern that has since been deleted
+        manual_store
+            .set_priority("deleted-pattern", InstructionPriority::High)
+            .await
+            .unwrap();
+
+        let graph = tokio::sync::RwLock::new(CodeGraph::new());
+        let result = distill_project_skill(
+            &pattern_store,
+            &failure_store,
+            &manual_store,
+            &store,
+            &graph,
+            DEFAULT_CONFIDENCE_THRESHOLD,
+            &SkillScope::default(),
+        )
+        .await
+        .unwrap();
+
+        let ids: Vec<_> = result.instructions.iter().map(|i| i.id.as_str()).collect();
+        assert!(ids.contains(&fresh.id.as_str()));
+        assert!(!ids.contains(&old.id.as_str()));
+
+        let instruction = result
+            .instructions
+            .iter()
+            .find(|i| i.id == fresh.id)
+            .unwrap();
+        let freshness = instruction.freshness.as_ref().unwrap();
+        assert_eq!(freshness.source_confidence, 0.9);
+        assert!(freshness.last_validated.is_none());
+
+        assert!(manual_store.priorities().await.unwrap().is_empty());
+    }
 }
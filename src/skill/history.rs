@@ -0,0 +1,188 @@
+use anyhow::Result;
+use std::collections::HashMap;
+use std::path::Path;
+
+/// Directory (under .codegraph/) holding previous SKILL.md versions
+pub const HISTORY_DIR: &str = "skill-history";
+
+/// Number of previous versions kept by default
+pub const DEFAULT_KEEP: usize = 5;
+
+/// Instruction-level differences between two SKILL.md renderings
+#[derive(Debug, Clone, Default)]
+pub struct SkillDiff {
+    pub added: Vec<String>,
+    pub removed: Vec<String>,
+    /// (old, new) pairs for instructions whose scope or confidence changed
+    pub changed: Vec<(String, String)>,
+}
+
+impl SkillDiff {
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.changed.is_empty()
+    }
+
+    /// Render the diff as a short human-readable summary
+    pub fn render(&self) -> String {
+        if self.is_empty() {
+            return "No instruction changes.".to_string();
+        }
+
+        let mut output = format!(
+            "Instruction changes: +{} -{} ~{}\n",
+            self.added.len(),
+            self.removed.len(),
+            self.changed.len()
+        );
+        for line in &self.added {
+            output.push_str(&format!("+ {}\n", line));
+        }
+        for line in &self.removed {
+            output.push_str(&format!("- {}\n", line));
+        }
+        for (old, new) in &self.changed {
+            output.push_str(&format!("~ {}\n  → {}\n", old, new));
+        }
+
+        output.trim_end().to_string()
+    }
+}
+
+/// Compare the instruction bullets of two SKILL.md files.
+/// Instructions are matched by their text; differing scope/confidence suffixes count as changes.
+pub fn diff_skill_md(old: &str, new: &str) -> SkillDiff {
+    let old_items = instruction_lines(old);
+    let new_items = instruction_lines(new);
+
+    let old_by_key: HashMap<&str, &str> = old_items.iter().map(|l| (instruction_key(l), *l)).collect();
+    let new_by_key: HashMap<&str, &str> = new_items.iter().map(|l| (instruction_key(l), *l)).collect();
+
+    let mut diff = SkillDiff::default();
+
+    for line in &new_items {
+        match old_by_key.get(instruction_key(line)) {
+            None => diff.added.push(line.to_string()),
+            Some(old_line) if old_line != line => {
+                diff.changed.push((old_line.to_string(), line.to_string()))
+            }
+            Some(_) => {}
+        }
+    }
+
+    for line in &old_items {
+        if !new_by_key.contains_key(instruction_key(line)) {
+            diff.removed.push(line.to_string());
+        }
+    }
+
+    diff
+}
+
+/// Extract instruction bullets ("- ...") from rendered markdown
+fn instruction_lines(markdown: &str) -> Vec<&str> {
+    markdown
+        .lines()
+        .filter_map(|l| l.strip_prefix("- "))
+        .map(str::trim)
+        .collect()
+}
+
+/// The instruction text without the rendered scope/confidence suffixes
+fn instruction_key(line: &str) -> &str {
+    super::render::parse_instruction_line(line).0
+}
+
+/// Save a previous SKILL.md version and prune history to the newest `keep` entries.
+/// Does nothing when `keep` is 0.
+pub fn save_history(codegraph_dir: &Path, markdown: &str, keep: usize) -> Result<()> {
+    if keep == 0 {
+        return Ok(());
+    }
+
+    let history_dir = codegraph_dir.join(HISTORY_DIR);
+    std::fs::create_dir_all(&history_dir)?;
+
+    // Millisecond names, with a counter for saves within the same millisecond
+    let timestamp = chrono::Utc::now().format("%Y%m%dT%H%M%S%3f").to_string();
+    let mut path = history_dir.join(format!("SKILL-{}.md", timestamp));
+    let mut n = 1;
+    while path.exists() {
+        path = history_dir.join(format!("SKILL-{}_{}.md", timestamp, n));
+        n += 1;
+    }
+    std::fs::write(path, markdown)?;
+
+    // Timestamped names sort chronologically
+    let mut versions: Vec<_> = std::fs::read_dir(&history_dir)?
+        .filter_map(|e| e.ok())
+        .map(|e| e.path())
+        .filter(|p| {
+            p.file_name()
+                .and_then(|n| n.to_str())
+                .map(|n| n.starts_with("SKILL-") && n.ends_with(".md"))
+                .unwrap_or(false)
+        })
+        .collect();
+    versions.sort();
+
+    let excess = versions.len().saturating_sub(keep);
+    for old in versions.into_iter().take(excess) {
+        std::fs::remove_file(old)?;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_diff_skill_md() {
+        let old = "# Project Skill\n\n## Testing\n\n- Always write tests — 80% confidence\n- Use fixtures\n";
+        let new = "# Project Skill\n\n## Testing\n\n- Always write tests — 90% confidence\n- Run cargo fmt (scope: `src/**`)\n";
+
+        let diff = diff_skill_md(old, new);
+        assert_eq!(diff.added, vec!["Run cargo fmt (scope: `src/**`)"]);
+        assert_eq!(diff.removed, vec!["Use fixtures"]);
+        assert_eq!(diff.changed.len(), 1);
+        assert_eq!(diff.changed[0].1, "Always write tests — 90% confidence");
+
+        assert!(diff_skill_md(new, new).is_empty());
+    }
+
+    #[test]
+    fn test_save_history_prunes() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let history_dir = temp_dir.path().join(HISTORY_DIR);
+        std::fs::create_dir_all(&history_dir).unwrap();
+        for i in 0..3 {
+            std::fs::write(history_dir.join(format!("SKILL-2020010{}T000000.md", i)), "old").unwrap();
+        }
+
+        save_history(temp_dir.path(), "current", 2).unwrap();
+
+        let mut names: Vec<_> = std::fs::read_dir(&history_dir)
+            .unwrap()
+            .map(|e| e.unwrap().file_name().into_string().unwrap())
+            .collect();
+        names.sort();
+        assert_eq!(names.len(), 2);
+        assert_eq!(names[0], "SKILL-20200102T000000.md");
+    }
+
+    #[test]
+    fn test_save_history_same_millisecond() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        for version in ["one", "two", "three"] {
+            save_history(temp_dir.path(), version, 5).unwrap();
+        }
+
+        let mut versions: Vec<_> = std::fs::read_dir(temp_dir.path().join(HISTORY_DIR))
+            .unwrap()
+            .map(|e| std::fs::read_to_string(e.unwrap().path()).unwrap())
+            .collect();
+        versions.sort();
+        assert_eq!(versions, vec!["one", "three", "two"]);
+    }
+}
@@ -0,0 +1,297 @@
+use anyhow::Result;
+use std::collections::{HashMap, HashSet};
+
+use crate::learning::Scope;
+
+use super::categories::{InstructionCategory, InstructionPriority};
+use super::distill::{DistillResult, ManualInstructionStore};
+use super::render::{category_from_title, parse_instruction_line};
+
+/// Heading used for pinned rules in the default layout
+const PINNED_HEADING: &str = "📌 Pinned Rules";
+
+/// An instruction bullet read back from SKILL.md
+#[derive(Debug, Clone, PartialEq)]
+pub struct ParsedInstruction {
+    pub text: String,
+    pub category: InstructionCategory,
+    pub scope: Option<String>,
+    pub pinned: bool,
+}
+
+/// Parse instruction bullets out of a (possibly hand-edited) SKILL.md.
+/// Only "- " bullets under "## " headings that name a category (or the pinned section) are read.
+pub fn parse_skill_md(markdown: &str) -> Vec<ParsedInstruction> {
+    let mut parsed = Vec::new();
+    let mut section: Option<(InstructionCategory, bool)> = None;
+
+    for line in markdown.lines() {
+        if let Some(title) = line.strip_prefix("## ") {
+            section = if title.trim() == PINNED_HEADING {
+                Some((InstructionCategory::Gotchas, true))
+            } else {
+                category_from_title(title).map(|c| (c, false))
+            };
+            continue;
+        }
+
+        // Top-level headings (split files) name their category too
+        if let Some(title) = line.strip_prefix("# ") {
+            section = category_from_title(title).map(|c| (c, false));
+            continue;
+        }
+
+        let (Some((category, pinned)), Some(bullet)) = (&section, line.strip_prefix("- ")) else {
+            continue;
+        };
+
+        let (text, scope) = parse_instruction_line(bullet);
+        if text.is_empty() {
+            continue;
+        }
+
+        parsed.push(ParsedInstruction {
+            text: text.to_string(),
+            category: category.clone(),
+            scope: scope.map(String::from),
+            pinned: *pinned,
+        });
+    }
+
+    parsed
+}
+
+/// What reconciling a SKILL.md against the store did (or would do, in a dry run)
+#[derive(Debug, Default)]
+pub struct ImportReport {
+    pub added: Vec<String>,
+    pub recategorized: Vec<String>,
+    pub removed: Vec<String>,
+    /// Generated instructions still present in the file
+    pub generated: usize,
+    /// Generated instructions the user deleted from the file (their sources still exist)
+    pub missing_generated: Vec<String>,
+    pub unchanged: usize,
+}
+
+impl ImportReport {
+    pub fn render(&self, dry_run: bool) -> String {
+        let mut output = format!(
+            "{}: {} added, {} recategorized, {} removed, {} unchanged manual, {} generated\n",
+            if dry_run { "Import preview" } else { "Imported SKILL.md" },
+            self.added.len(),
+            self.recategorized.len(),
+            self.removed.len(),
+            self.unchanged,
+            self.generated,
+        );
+        for text in &self.added {
+            output.push_str(&format!("+ {}\n", text));
+        }
+        for text in &self.recategorized {
+            output.push_str(&format!("~ {}\n", text));
+        }
+        for text in &self.removed {
+            output.push_str(&format!("- {}\n", text));
+        }
+        if !self.missing_generated.is_empty() {
+            output.push_str(&format!(
+                "\n{} generated instructions were deleted from the file but will return on the next distill. Delete their source pattern/failure to drop them:\n",
+                self.missing_generated.len()
+            ));
+            for text in &self.missing_generated {
+                output.push_str(&format!("  {}\n", text));
+            }
+        }
+        output.trim_end().to_string()
+    }
+}
+
+/// Reconcile parsed SKILL.md bullets into the manual instruction store.
+///
+/// Bullets matching generated content (patterns, failures, conventions, navigation) are
+/// left alone; anything else is a manual edit and becomes (or updates) a manual
+/// instruction. Manual instructions missing from the file are removed when `remove_missing`.
+pub async fn reconcile_skill(
+    parsed: &[ParsedInstruction],
+    current: &DistillResult,
+    manual_store: &ManualInstructionStore,
+    dry_run: bool,
+    remove_missing: bool,
+) -> Result<ImportReport> {
+    let mut report = ImportReport::default();
+
+    // Navigation hints also carry a manual source, so tell them apart by store ID
+    let manual = manual_store.list_all().await?;
+    let manual_ids: HashSet<&str> = manual.iter().map(|i| i.id.as_str()).collect();
+    let generated: HashMap<&str, &str> = current
+        .instructions
+        .iter()
+        .filter(|i| !manual_ids.contains(i.id.as_str()))
+        .map(|i| (i.instruction.as_str(), i.id.as_str()))
+        .collect();
+
+    let manual_by_text: HashMap<&str, _> = manual
+        .iter()
+        .map(|i| (i.instruction.as_str(), i))
+        .collect();
+
+    let mut seen = HashSet::new();
+
+    for item in parsed {
+        if !seen.insert(item.text.as_str()) {
+            continue;
+        }
+
+        if generated.contains_key(item.text.as_str()) {
+            report.generated += 1;
+            continue;
+        }
+
+        match manual_by_text.get(item.text.as_str()) {
+            Some(existing) if item.pinned || existing.category == item.category => {
+                report.unchanged += 1;
+            }
+            Some(existing) => {
+                if !dry_run {
+                    manual_store
+                        .update(&existing.id, None, Some(item.category.clone()), None)
+                        .await?;
+                }
+                report.recategorized.push(item.text.clone());
+            }
+            None => {
+                if !dry_run {
+                    let scope = Scope {
+                        include_paths: item
+                            .scope
+                            .as_deref()
+                            .map(|s| s.split(", ").map(String::from).collect())
+                            .unwrap_or_default(),
+                        ..Scope::default()
+                    };
+                    let id = manual_store
+                        .add(
+                            &item.text,
+                            item.category.clone(),
+                            Some("Imported from SKILL.md"),
+                            &scope,
+                        )
+                        .await?;
+                    if item.pinned {
+                        manual_store
+                            .set_priority(&id, InstructionPriority::Critical)
+                            .await?;
+                    }
+                }
+                report.added.push(item.text.clone());
+            }
+        }
+    }
+
+    for inst in &manual {
+        if !seen.contains(inst.instruction.as_str()) && remove_missing {
+            if !dry_run {
+                manual_store.delete(&inst.id).await?;
+            }
+            report.removed.push(inst.instruction.clone());
+        }
+    }
+
+    for text in generated.keys() {
+        if !seen.contains(text) {
+            report.missing_generated.push(text.to_string());
+        }
+    }
+    report.missing_generated.sort();
+
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::skill::architecture::ArchitectureOverview;
+    use crate::skill::categories::{InstructionSource, ProjectInstruction};
+    use std::sync::Arc;
+
+    #[test]
+    fn test_parse_skill_md() {
+        let markdown = "# Project Skill\n\n## 📌 Pinned Rules\n\n- Never push to main\n\n## Architecture Overview\n\n| Module | Files |\n\n## Testing\n\n- Use fixtures — a tip (scope: `tests/**`) — 85% confidence\n\n## ⚠️ Gotchas & Pitfalls\n\n- ❌ Stale cache — clear it\n";
+
+        let parsed = parse_skill_md(markdown);
+        assert_eq!(parsed.len(), 3);
+        assert!(parsed[0].pinned);
+        assert_eq!(parsed[1].text, "Use fixtures — a tip");
+        assert_eq!(parsed[1].category, InstructionCategory::Testing);
+        assert_eq!(parsed[1].scope.as_deref(), Some("tests/**"));
+        assert_eq!(parsed[2].text, "❌ Stale cache — clear it");
+        assert_eq!(parsed[2].category, InstructionCategory::Gotchas);
+    }
+
+    #[tokio::test]
+    async fn test_reconcile_skill() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let temp_path = temp_dir.path();
+
+        let config = crate::config::Config {
+            project_root: temp_path.to_path_buf(),
+            cache_dir: temp_path.join("cache"),
+            codegraph_dir: temp_path.join(".codegraph"),
+            store_db_path: temp_path.join("cache/store.db"),
+            learning_db_path: temp_path.join(".codegraph/learning.db"),
+            settings: crate::config::ConfigFile::default().into(),
+        };
+
+        let store = crate::store::Store::open(&config).await.unwrap();
+        let manual_store = ManualInstructionStore::new(Arc::new(store.learning_db));
+
+        manual_store
+            .add("Run cargo fmt", InstructionCategory::Style, None, &Scope::default())
+            .await
+            .unwrap();
+        manual_store
+            .add("Old advice", InstructionCategory::Style, None, &Scope::default())
+            .await
+            .unwrap();
+
+        let current = DistillResult {
+            instructions: vec![ProjectInstruction {
+                id: "p1".to_string(),
+                instruction: "Use async".to_string(),
+                category: InstructionCategory::Architecture,
+                source: InstructionSource::Pattern { id: "p1".to_string() },
+                confidence: Some(0.9),
+                scope: None,
+                priority: InstructionPriority::Normal,
+                freshness: None,
+            }],
+            conventions: vec![],
+            navigation_hints: vec![],
+            architecture: ArchitectureOverview::default(),
+        };
+
+        // User moved "Run cargo fmt" to Workflow, deleted "Old advice", added a new rule
+        let markdown = "## Architecture\n\n- Use async — 90% confidence\n\n## Workflow\n\n- Run cargo fmt\n- Squash before merge\n";
+        let parsed = parse_skill_md(markdown);
+
+        let preview = reconcile_skill(&parsed, &current, &manual_store, true, true)
+            .await
+            .unwrap();
+        assert_eq!(preview.added, vec!["Squash before merge"]);
+        assert_eq!(manual_store.list_all().await.unwrap().len(), 2);
+
+        let report = reconcile_skill(&parsed, &current, &manual_store, false, true)
+            .await
+            .unwrap();
+        assert_eq!(report.generated, 1);
+        assert_eq!(report.recategorized, vec!["Run cargo fmt"]);
+        assert_eq!(report.removed, vec!["Old advice"]);
+
+        let manual = manual_store.list_all().await.unwrap();
+        assert_eq!(manual.len(), 2);
+        assert!(manual
+            .iter()
+            .all(|i| i.category == InstructionCategory::Workflow));
+    }
+}
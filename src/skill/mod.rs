@@ -1,6 +1,9 @@
+pub mod architecture;
 pub mod categories;
 pub mod conventions;
 pub mod distill;
+pub mod history;
+pub mod import;
 pub mod navigation;
 pub mod render;
 
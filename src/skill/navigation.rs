@@ -1,5 +1,14 @@
 use crate::learning::patterns::Pattern;
-use std::collections::HashMap;
+use crate::store::CodeGraph;
+use petgraph::graph::NodeIndex;
+use petgraph::visit::{EdgeRef, IntoEdgeReferences};
+use std::collections::{HashMap, HashSet};
+
+/// How many of the most referenced files to surface
+const TOP_CENTRAL_FILES: usize = 5;
+
+/// Maximum length of a reported call chain
+const MAX_CHAIN_LENGTH: usize = 5;
 
 #[derive(Debug, Clone)]
 pub struct NavigationHint {
@@ -88,6 +97,215 @@ fn generate_hint_description(path: &str, intents: &[String]) -> String {
     }
 }
 
+// === Graph-based hints ===
+
+/// Generate navigation hints from the code graph: entry points, the most
+/// referenced files, a "start here" file per directory, and typical call chains.
+pub fn generate_graph_navigation_hints(graph: &CodeGraph) -> Vec<NavigationHint> {
+    let index = GraphIndex::build(graph);
+    let mut hints = Vec::new();
+
+    // Entry points
+    let mut entry_points: Vec<NodeIndex> = graph
+        .graph
+        .node_indices()
+        .filter(|&idx| {
+            let node = &graph.graph[idx];
+            node.kind == "function" && node.data.get("name").and_then(|v| v.as_str()) == Some("main")
+        })
+        .collect();
+    entry_points.sort_by_key(|&idx| node_file(graph, idx).unwrap_or_default().to_string());
+
+    for &idx in &entry_points {
+        if let Some(file) = node_file(graph, idx) {
+            hints.push(NavigationHint {
+                path: file.to_string(),
+                description: "the `main` entry point".to_string(),
+            });
+
+            let chain = index.call_chain(graph, idx);
+            if chain.len() >= 3 {
+                hints.push(NavigationHint {
+                    path: file.to_string(),
+                    description: format!(
+                        "the typical call chain {}",
+                        chain
+                            .iter()
+                            .map(|name| format!("`{}`", name))
+                            .collect::<Vec<_>>()
+                            .join(" → ")
+                    ),
+                });
+            }
+        }
+    }
+
+    // Highest-centrality files
+    let mut central: Vec<(&String, &HashSet<String>)> = index.fan_in.iter().collect();
+    central.sort_by(|a, b| b.1.len().cmp(&a.1.len()).then_with(|| a.0.cmp(b.0)));
+
+    for (file, referrers) in central.iter().take(TOP_CENTRAL_FILES) {
+        if referrers.len() < 2 {
+            break;
+        }
+        hints.push(NavigationHint {
+            path: file.to_string(),
+            description: format!(
+                "heavily shared code (referenced from {} other files)",
+                referrers.len()
+            ),
+        });
+    }
+
+    // "Start here" file per directory: the most referenced file in it
+    let mut by_dir: HashMap<&str, (&String, usize)> = HashMap::new();
+    for (file, referrers) in &central {
+        let Some((dir, _)) = file.rsplit_once('/') else {
+            continue;
+        };
+        let entry = by_dir.entry(dir).or_insert((file, 0));
+        if referrers.len() > entry.1 {
+            *entry = (file, referrers.len());
+        }
+    }
+
+    let mut dirs: Vec<_> = by_dir.into_iter().filter(|(_, (_, n))| *n > 0).collect();
+    dirs.sort_by(|a, b| a.0.cmp(b.0));
+
+    for (dir, (file, _)) in dirs {
+        if index.files_per_dir.get(dir).copied().unwrap_or(0) >= 2 {
+            hints.push(NavigationHint {
+                path: file.to_string(),
+                description: format!("the place to start when working in `{}/`", dir),
+            });
+        }
+    }
+
+    hints
+}
+
+/// Get the file a node belongs to
+pub(super) fn node_file(graph: &CodeGraph, idx: NodeIndex) -> Option<&str> {
+    let data = &graph.graph[idx].data;
+    data.get("file")
+        .or_else(|| data.get("path"))
+        .and_then(|v| v.as_str())
+}
+
+/// Lookup tables for resolving references by name across files
+pub(super) struct GraphIndex {
+    /// Symbol name -> defining node, if the name is unique
+    by_name: HashMap<String, Option<NodeIndex>>,
+    /// File -> set of other files referencing it
+    pub fan_in: HashMap<String, HashSet<String>>,
+    /// (from file, to file) -> number of resolved cross-file references
+    pub file_edges: HashMap<(String, String), usize>,
+    /// Symbol node -> number of incoming call edges
+    callers: HashMap<NodeIndex, usize>,
+    /// Directory -> number of indexed files in it
+    pub files_per_dir: HashMap<String, usize>,
+}
+
+impl GraphIndex {
+    pub fn build(graph: &CodeGraph) -> Self {
+        let mut by_name: HashMap<String, Option<NodeIndex>> = HashMap::new();
+        let mut files = HashSet::new();
+
+        for idx in graph.graph.node_indices() {
+            let node = &graph.graph[idx];
+            if matches!(node.kind.as_str(), "unresolved" | "file") {
+                continue;
+            }
+            if let Some(file) = node_file(graph, idx) {
+                files.insert(file.to_string());
+            }
+            if let Some(name) = node.data.get("name").and_then(|v| v.as_str()) {
+                by_name
+                    .entry(name.to_string())
+                    .and_modify(|e| *e = None)
+                    .or_insert(Some(idx));
+            }
+        }
+
+        let mut files_per_dir: HashMap<String, usize> = HashMap::new();
+        for file in &files {
+            if let Some((dir, _)) = file.rsplit_once('/') {
+                *files_per_dir.entry(dir.to_string()).or_default() += 1;
+            }
+        }
+
+        let mut index = Self {
+            by_name,
+            fan_in: HashMap::new(),
+            file_edges: HashMap::new(),
+            callers: HashMap::new(),
+            files_per_dir,
+        };
+
+        for edge in graph.graph.edge_references() {
+            let Some(target) = index.resolve(graph, edge.target()) else {
+                continue;
+            };
+
+            if edge.weight().kind == "calls" {
+                *index.callers.entry(target).or_default() += 1;
+            }
+
+            if let (Some(from), Some(to)) = (node_file(graph, edge.source()), node_file(graph, target)) {
+                if from != to {
+                    index
+                        .fan_in
+                        .entry(to.to_string())
+                        .or_default()
+                        .insert(from.to_string());
+                    *index
+                        .file_edges
+                        .entry((from.to_string(), to.to_string()))
+                        .or_default() += 1;
+                }
+            }
+        }
+
+        index
+    }
+
+    /// Resolve unresolved stubs to their unique definition by name
+    fn resolve(&self, graph: &CodeGraph, idx: NodeIndex) -> Option<NodeIndex> {
+        let node = &graph.graph[idx];
+        if node.kind != "unresolved" {
+            return Some(idx);
+        }
+        let name = node.data.get("name").and_then(|v| v.as_str())?;
+        self.by_name.get(name).copied().flatten()
+    }
+
+    /// Follow the most-called callee from `start` to sketch a typical call chain
+    fn call_chain(&self, graph: &CodeGraph, start: NodeIndex) -> Vec<String> {
+        let mut chain = Vec::new();
+        let mut visited = HashSet::new();
+        let mut current = Some(start);
+
+        while let Some(idx) = current {
+            if !visited.insert(idx) || chain.len() >= MAX_CHAIN_LENGTH {
+                break;
+            }
+            if let Some(name) = graph.graph[idx].data.get("name").and_then(|v| v.as_str()) {
+                chain.push(name.to_string());
+            }
+
+            current = graph
+                .graph
+                .edges(idx)
+                .filter(|e| e.weight().kind == "calls")
+                .filter_map(|e| self.resolve(graph, e.target()))
+                .filter(|t| !visited.contains(t))
+                .max_by_key(|t| (self.callers.get(t).copied().unwrap_or(0), std::cmp::Reverse(t.index())));
+        }
+
+        chain
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -147,4 +365,53 @@ mod tests {
         assert_eq!(hints[0].path, "src/db");
         assert!(hints[0].description.contains("database"));
     }
+
+    #[test]
+    fn test_generate_graph_hints() {
+        let mut graph = CodeGraph::new();
+        let symbols = [
+            ("src/main.rs", "main"),
+            ("src/server/mod.rs", "run"),
+            ("src/server/handler.rs", "handle"),
+            ("src/store/db.rs", "query"),
+            ("src/store/cache.rs", "cached"),
+        ];
+        for (file, name) in symbols {
+            graph.add_node(
+                format!("{}::{}", file, name),
+                "function".to_string(),
+                serde_json::json!({"name": name, "file": file}),
+            );
+        }
+        for name in ["run", "handle", "query"] {
+            graph.add_node(
+                format!("unresolved::{}", name),
+                "unresolved".to_string(),
+                serde_json::json!({"name": name}),
+            );
+        }
+        graph.add_edge("src/main.rs::main", "unresolved::run", "calls".to_string(), None);
+        graph.add_edge("src/server/mod.rs::run", "unresolved::handle", "calls".to_string(), None);
+        graph.add_edge("src/server/handler.rs::handle", "unresolved::query", "calls".to_string(), None);
+        graph.add_edge("src/store/cache.rs::cached", "unresolved::query", "calls".to_string(), None);
+
+        let hints = generate_graph_navigation_hints(&graph);
+        let describe = |path: &str| -> Vec<String> {
+            hints
+                .iter()
+                .filter(|h| h.path == path)
+                .map(|h| h.description.clone())
+                .collect()
+        };
+
+        let main_hints = describe("src/main.rs");
+        assert!(main_hints.contains(&"the `main` entry point".to_string()));
+        assert!(main_hints
+            .iter()
+            .any(|d| d.contains("`main` → `run` → `handle` → `query`")));
+
+        let db_hints = describe("src/store/db.rs");
+        assert!(db_hints.iter().any(|d| d.contains("referenced from 2 other files")));
+        assert!(db_hints.iter().any(|d| d.contains("`src/store/`")));
+    }
 }
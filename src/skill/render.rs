@@ -1,6 +1,224 @@
+use anyhow::{Context, Result};
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::path::Path;
+
+use super::architecture::ArchitectureOverview;
 use super::categories::{InstructionCategory, ProjectInstruction};
 use super::distill::DistillResult;
-use std::collections::HashMap;
+
+/// User-provided Handlebars template (under .codegraph/) that overrides the default layout
+pub const TEMPLATE_FILE: &str = "skill-template.md";
+
+/// Main skill file (under .codegraph/)
+pub const SKILL_FILE: &str = "SKILL.md";
+
+/// Learned instructions below this confidence are marked uncertain
+const UNCERTAIN_CONFIDENCE: f32 = 0.5;
+
+/// Learned instructions not validated for this many days are marked uncertain
+const STALE_AFTER_DAYS: i64 = 90;
+
+/// How far confidence may decay below the source pattern's before it is flagged
+const DECAY_MARGIN: f32 = 0.2;
+
+/// A rendered skill output file
+#[derive(Debug, Clone)]
+pub struct SkillFile {
+    pub name: String,
+    pub content: String,
+}
+
+/// Render the skill as SKILL.md plus one file per category listed in `split`
+/// (e.g. "gotchas" → GOTCHAS.md). Pinned instructions always stay in SKILL.md.
+/// SKILL.md is always the first file returned.
+pub fn render_skill_files(
+    result: &DistillResult,
+    codegraph_dir: &Path,
+    split: &[String],
+) -> Result<Vec<SkillFile>> {
+    // from_str falls back to gotchas, so only keep names that round-trip
+    let mut categories: Vec<InstructionCategory> = Vec::new();
+    for name in split {
+        let category = InstructionCategory::from_str(name);
+        if category.to_str().eq_ignore_ascii_case(name) && !categories.contains(&category) {
+            categories.push(category);
+        }
+    }
+    let split = categories;
+    let is_split = |i: &ProjectInstruction| !i.priority.is_pinned() && split.contains(&i.category);
+
+    let main = DistillResult {
+        instructions: result
+            .instructions
+            .iter()
+            .filter(|i| !is_split(i))
+            .cloned()
+            .collect(),
+        conventions: result.conventions.clone(),
+        navigation_hints: result.navigation_hints.clone(),
+        architecture: result.architecture.clone(),
+    };
+
+    let mut extra = Vec::new();
+    for category in &split {
+        let instructions: Vec<&ProjectInstruction> = result
+            .instructions
+            .iter()
+            .filter(|i| is_split(i) && &i.category == category)
+            .collect();
+        if !instructions.is_empty() {
+            extra.push(SkillFile {
+                name: format!("{}.md", category.to_str().to_uppercase()),
+                content: render_category_file(category, &instructions),
+            });
+        }
+    }
+
+    let mut content = render_project_skill(&main, codegraph_dir)?;
+    if !extra.is_empty() {
+        let names: Vec<_> = extra.iter().map(|f| format!("`{}`", f.name)).collect();
+        content.push_str(&format!("\n> See also: {}\n", names.join(", ")));
+    }
+
+    let mut files = vec![SkillFile {
+        name: SKILL_FILE.to_string(),
+        content,
+    }];
+    files.extend(extra);
+
+    Ok(files)
+}
+
+/// Render a standalone file for a single category
+fn render_category_file(category: &InstructionCategory, instructions: &[&ProjectInstruction]) -> String {
+    let mut output = format!("# {}\n\n", category_title(category));
+    output.push_str("> Auto-generated companion to SKILL.md. Hand edits can be imported with `import_skill`.\n\n");
+    for instruction in instructions {
+        output.push_str(&render_instruction(instruction));
+    }
+    output
+}
+
+/// Render SKILL.md, using `.codegraph/skill-template.md` when present
+pub fn render_project_skill(result: &DistillResult, codegraph_dir: &Path) -> Result<String> {
+    let template_path = codegraph_dir.join(TEMPLATE_FILE);
+    if !template_path.exists() {
+        return Ok(generate_project_skill_md(result));
+    }
+
+    let template = std::fs::read_to_string(&template_path)
+        .with_context(|| format!("Failed to read {}", template_path.display()))?;
+    render_with_template(result, &template)
+        .with_context(|| format!("Failed to render {}", template_path.display()))
+}
+
+/// Render the distill result through a Handlebars template.
+///
+/// Available data:
+/// - `pinned`: pinned instructions, highest priority first
+/// - `categories`: sections in display order, each `{ key, title, instructions }`
+/// - `sections.<key>`: the same sections by category key, for custom ordering
+/// - `navigation_hints`: `{ path, description }` entries
+/// - `architecture`: `{ modules, layers, flows }` summarizing the module dependency graph
+/// - `counts`: `{ patterns, failures, conventions, manual }`
+///
+/// Each instruction has `text`, `priority`, and optional `scope`, `confidence` (a percentage),
+/// and `uncertain` (why the guidance may be stale).
+pub fn render_with_template(result: &DistillResult, template: &str) -> Result<String> {
+    let mut handlebars = handlebars::Handlebars::new();
+    handlebars.set_strict_mode(false);
+    handlebars.register_escape_fn(handlebars::no_escape);
+    handlebars.register_template_string("skill", template)?;
+
+    Ok(handlebars.render("skill", &template_data(result))?)
+}
+
+/// Template data for a single instruction
+fn instruction_data(instruction: &ProjectInstruction) -> Value {
+    json!({
+        "text": instruction.instruction,
+        "scope": instruction.scope,
+        "confidence": instruction.confidence.map(|c| (c * 100.0).round() as u32),
+        "priority": instruction.priority.to_str(),
+        "uncertain": uncertainty(instruction, chrono::Utc::now().timestamp()),
+    })
+}
+
+/// Build the data exposed to skill templates
+fn template_data(result: &DistillResult) -> Value {
+    let mut by_category: HashMap<InstructionCategory, Vec<&ProjectInstruction>> = HashMap::new();
+    for instruction in result.instructions.iter().filter(|i| !i.priority.is_pinned()) {
+        by_category
+            .entry(instruction.category.clone())
+            .or_default()
+            .push(instruction);
+    }
+
+    let mut categories: Vec<_> = by_category.keys().cloned().collect();
+    categories.sort_by(|a, b| (a.display_order(), a.to_str()).cmp(&(b.display_order(), b.to_str())));
+
+    let sections: Vec<Value> = categories
+        .iter()
+        .map(|category| {
+            let instructions: Vec<Value> = by_category[category]
+                .iter()
+                .map(|i| instruction_data(i))
+                .collect();
+
+            json!({
+                "key": category.to_str(),
+                "title": category_title(category),
+                "instructions": instructions,
+            })
+        })
+        .collect();
+
+    let architecture = json!({
+        "modules": result.architecture.modules.iter().map(|m| json!({
+            "path": m.path,
+            "files": m.files,
+            "symbols": m.symbols,
+            "used_by": m.used_by,
+        })).collect::<Vec<_>>(),
+        "layers": result.architecture.layers,
+        "flows": result.architecture.flows.iter().map(|f| json!({
+            "from": f.from,
+            "to": f.to,
+            "references": f.references,
+        })).collect::<Vec<_>>(),
+    });
+
+    let pinned: Vec<Value> = pinned_instructions(&result.instructions)
+        .into_iter()
+        .map(instruction_data)
+        .collect();
+
+    let sections_by_key: serde_json::Map<String, Value> = sections
+        .iter()
+        .map(|s| (s["key"].as_str().unwrap_or_default().to_string(), s.clone()))
+        .collect();
+
+    let hints: Vec<Value> = result
+        .navigation_hints
+        .iter()
+        .map(|h| json!({ "path": h.path, "description": h.description }))
+        .collect();
+
+    json!({
+        "pinned": pinned,
+        "architecture": architecture,
+        "categories": sections,
+        "sections": sections_by_key,
+        "navigation_hints": hints,
+        "counts": {
+            "patterns": count_by_source(&result.instructions, "Pattern"),
+            "failures": count_by_source(&result.instructions, "Failure"),
+            "conventions": result.conventions.len(),
+            "manual": count_by_source(&result.instructions, "Manual"),
+        },
+    })
+}
 
 /// Generate SKILL.md content from distill result
 pub fn generate_project_skill_md(result: &DistillResult) -> String {
@@ -9,12 +227,27 @@ pub fn generate_project_skill_md(result: &DistillResult) -> String {
     // Header
     output.push_str("# Project Skill\n\n");
     output.push_str(
-        "> Auto-generated from learned patterns and failures. Do not edit manually.\n\n",
+        "> Auto-generated from learned patterns and failures. Hand edits can be imported with `import_skill`.\n\n",
     );
 
-    // Group instructions by category
+    // Pinned instructions come first, most important first
+    let pinned = pinned_instructions(&result.instructions);
+    if !pinned.is_empty() {
+        output.push_str("## 📌 Pinned Rules\n\n");
+        for instruction in &pinned {
+            output.push_str(&render_instruction(instruction));
+        }
+        output.push('\n');
+    }
+
+    // Orientation before instructions
+    if !result.architecture.is_empty() {
+        output.push_str(&render_architecture(&result.architecture));
+    }
+
+    // Group remaining instructions by category
     let mut by_category: HashMap<InstructionCategory, Vec<&ProjectInstruction>> = HashMap::new();
-    for instruction in &result.instructions {
+    for instruction in result.instructions.iter().filter(|i| !i.priority.is_pinned()) {
         by_category
             .entry(instruction.category.clone())
             .or_default()
@@ -23,7 +256,7 @@ pub fn generate_project_skill_md(result: &DistillResult) -> String {
 
     // Sort categories by display order
     let mut categories: Vec<_> = by_category.keys().cloned().collect();
-    categories.sort_by_key(|c| c.display_order());
+    categories.sort_by(|a, b| (a.display_order(), a.to_str()).cmp(&(b.display_order(), b.to_str())));
 
     // Render each category
     for category in categories {
@@ -32,20 +265,7 @@ pub fn generate_project_skill_md(result: &DistillResult) -> String {
         output.push_str(&format!("## {}\n\n", category_title(&category)));
 
         for instruction in instructions {
-            // Render instruction
-            output.push_str(&format!("- {}", instruction.instruction));
-
-            // Add scope if present
-            if let Some(scope) = &instruction.scope {
-                output.push_str(&format!(" (scope: `{}`)", scope));
-            }
-
-            // Add confidence if present
-            if let Some(conf) = instruction.confidence {
-                output.push_str(&format!(" — {:.0}% confidence", conf * 100.0));
-            }
-
-            output.push_str("\n");
+            output.push_str(&render_instruction(instruction));
         }
 
         output.push_str("\n");
@@ -63,8 +283,121 @@ pub fn generate_project_skill_md(result: &DistillResult) -> String {
     output
 }
 
+/// Render the architecture overview section
+fn render_architecture(overview: &ArchitectureOverview) -> String {
+    let mut output = String::from("## Architecture Overview\n\n");
+
+    output.push_str("| Module | Files | Symbols | Used by |\n|--------|-------|---------|---------|\n");
+    for module in &overview.modules {
+        output.push_str(&format!(
+            "| `{}` | {} | {} | {} modules |\n",
+            module.path, module.files, module.symbols, module.used_by
+        ));
+    }
+    output.push('\n');
+
+    if overview.layers.len() > 1 {
+        output.push_str("**Layers** (foundation first):\n\n");
+        for (i, layer) in overview.layers.iter().enumerate() {
+            let modules: Vec<_> = layer.iter().map(|m| format!("`{}`", m)).collect();
+            output.push_str(&format!("{}. {}\n", i + 1, modules.join(", ")));
+        }
+        output.push('\n');
+    }
+
+    if !overview.flows.is_empty() {
+        output.push_str("**Key dependencies:**\n\n| From | To | References |\n|------|----|------------|\n");
+        for flow in &overview.flows {
+            output.push_str(&format!(
+                "| `{}` | `{}` | {} |\n",
+                flow.from, flow.to, flow.references
+            ));
+        }
+        output.push('\n');
+    }
+
+    output
+}
+
+/// Render a single instruction as a markdown bullet
+fn render_instruction(instruction: &ProjectInstruction) -> String {
+    let mut line = format!("- {}", instruction.instruction);
+
+    // Add scope if present
+    if let Some(scope) = &instruction.scope {
+        line.push_str(&format!(" (scope: `{}`)", scope));
+    }
+
+    // Add confidence if present
+    if let Some(conf) = instruction.confidence {
+        line.push_str(&format!(" — {:.0}% confidence", conf * 100.0));
+    }
+
+    // Flag guidance whose source has gone stale
+    if let Some(reason) = uncertainty(instruction, chrono::Utc::now().timestamp()) {
+        line.push_str(&format!(" _(uncertain: {})_", reason));
+    }
+
+    line.push('\n');
+    line
+}
+
+/// Explain why a learned instruction should be treated with caution, if at all
+fn uncertainty(instruction: &ProjectInstruction, now: i64) -> Option<String> {
+    let confidence = instruction.confidence?;
+    let freshness = instruction.freshness.as_ref();
+
+    if let Some(days) = freshness
+        .and_then(|f| f.last_validated)
+        .map(|t| (now - t) / 86400)
+        .filter(|days| *days > STALE_AFTER_DAYS)
+    {
+        return Some(format!("last validated {} days ago", days));
+    }
+    if let Some(source) = freshness
+        .map(|f| f.source_confidence)
+        .filter(|source| source - confidence >= DECAY_MARGIN)
+    {
+        return Some(format!("decayed from {:.0}%", source * 100.0));
+    }
+    if confidence < UNCERTAIN_CONFIDENCE {
+        return Some("low confidence".to_string());
+    }
+    None
+}
+
+/// Pinned instructions, highest priority first
+fn pinned_instructions(instructions: &[ProjectInstruction]) -> Vec<&ProjectInstruction> {
+    let mut pinned: Vec<_> = instructions
+        .iter()
+        .filter(|i| i.priority.is_pinned())
+        .collect();
+    pinned.sort_by_key(|i| std::cmp::Reverse(i.priority));
+    pinned
+}
+
 /// Get category title for display
 fn category_title(category: &InstructionCategory) -> String {
+    let InstructionCategory::Custom(name) = category else {
+        return builtin_title(category).to_string();
+    };
+
+    // "api-design" -> "Api Design"
+    name.split(['-', '_', ' '])
+        .filter(|w| !w.is_empty())
+        .map(|w| {
+            let mut chars = w.chars();
+            match chars.next() {
+                Some(first) => first.to_uppercase().chain(chars).collect::<String>(),
+                None => String::new(),
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Title for a built-in category
+fn builtin_title(category: &InstructionCategory) -> &'static str {
     match category {
         InstructionCategory::Architecture => "Architecture",
         InstructionCategory::Testing => "Testing",
@@ -73,8 +406,54 @@ fn category_title(category: &InstructionCategory) -> String {
         InstructionCategory::Workflow => "Workflow",
         InstructionCategory::Tooling => "Tooling",
         InstructionCategory::Gotchas => "⚠️ Gotchas & Pitfalls",
+        InstructionCategory::Custom(_) => "",
+    }
+}
+
+/// Map a rendered section heading back to its category.
+/// Headings that aren't built-in categories are read as custom categories.
+pub(crate) fn category_from_title(title: &str) -> Option<InstructionCategory> {
+    let title = title.trim();
+    if title.is_empty() || GENERATED_SECTIONS.contains(&title) {
+        return None;
     }
-    .to_string()
+
+    InstructionCategory::BUILTIN
+        .into_iter()
+        .find(|c| builtin_title(c) == title || c.to_str().eq_ignore_ascii_case(title))
+        .or_else(|| {
+            let name = title.to_lowercase().split_whitespace().collect::<Vec<_>>().join("-");
+            Some(InstructionCategory::Custom(name))
+        })
+}
+
+/// Headings of generated sections that don't hold instructions
+const GENERATED_SECTIONS: &[&str] = &["Project Skill", "Architecture Overview"];
+
+/// Split a rendered instruction bullet (without the "- ") into its text and scope,
+/// dropping the confidence and uncertainty suffixes
+pub(crate) fn parse_instruction_line(line: &str) -> (&str, Option<&str>) {
+    let mut line = line.trim();
+
+    if let Some(rest) = line.strip_suffix(")_") {
+        if let Some((text, _)) = rest.rsplit_once(" _(uncertain: ") {
+            line = text;
+        }
+    }
+
+    if let Some((rest, suffix)) = line.rsplit_once(" — ") {
+        if suffix.ends_with("% confidence") {
+            line = rest;
+        }
+    }
+
+    if let Some(rest) = line.strip_suffix("`)") {
+        if let Some((text, scope)) = rest.rsplit_once(" (scope: `") {
+            return (text.trim(), Some(scope));
+        }
+    }
+
+    (line, None)
 }
 
 /// Count instructions by source type
@@ -88,7 +467,9 @@ fn count_by_source(instructions: &[ProjectInstruction], source_type: &str) -> us
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::skill::categories::{InstructionSource, ProjectInstruction};
+    use crate::skill::categories::{
+        InstructionFreshness, InstructionPriority, InstructionSource, ProjectInstruction,
+    };
 
     #[test]
     fn test_generate_skill_md() {
@@ -103,6 +484,8 @@ mod tests {
                     },
                     confidence: Some(0.9),
                     scope: None,
+                    priority: InstructionPriority::Normal,
+                    freshness: None,
                 },
                 ProjectInstruction {
                     id: "2".to_string(),
@@ -113,10 +496,13 @@ mod tests {
                     },
                     confidence: None,
                     scope: Some("src/**/*.js".to_string()),
+                    priority: InstructionPriority::Normal,
+                    freshness: None,
                 },
             ],
             conventions: vec![],
             navigation_hints: vec![],
+            architecture: ArchitectureOverview::default(),
         };
 
         let markdown = generate_project_skill_md(&result);
@@ -128,4 +514,238 @@ mod tests {
         assert!(markdown.contains("Don't use var"));
         assert!(markdown.contains("90% confidence"));
     }
+
+    #[test]
+    fn test_render_with_template() {
+        let result = DistillResult {
+            instructions: vec![
+                ProjectInstruction {
+                    id: "1".to_string(),
+                    instruction: "Always write tests".to_string(),
+                    category: InstructionCategory::Testing,
+                    source: InstructionSource::Pattern {
+                        id: "p1".to_string(),
+                    },
+                    confidence: Some(0.9),
+                    scope: None,
+                    priority: InstructionPriority::Normal,
+                    freshness: None,
+                },
+                ProjectInstruction {
+                    id: "2".to_string(),
+                    instruction: "Don't use var".to_string(),
+                    category: InstructionCategory::Gotchas,
+                    source: InstructionSource::Failure {
+                        id: "f1".to_string(),
+                    },
+                    confidence: None,
+                    scope: Some("src/**/*.js".to_string()),
+                    priority: InstructionPriority::Normal,
+                    freshness: None,
+                },
+            ],
+            conventions: vec![],
+            navigation_hints: vec![],
+            architecture: ArchitectureOverview::default(),
+        };
+
+        // Custom order: gotchas first, then everything else
+        let template = "# Team Rules\n{{#with sections.gotchas}}## Never\n{{#each instructions}}* {{text}}{{#if scope}} [{{scope}}]{{/if}}\n{{/each}}{{/with}}{{#each categories}}{{#if (ne key \"gotchas\")}}## {{title}}\n{{#each instructions}}* {{text}}{{#if confidence}} ({{confidence}}%){{/if}}\n{{/each}}{{/if}}{{/each}}";
+
+        let markdown = render_with_template(&result, template).unwrap();
+        assert_eq!(
+            markdown,
+            "# Team Rules\n## Never\n* Don't use var [src/**/*.js]\n## Testing\n* Always write tests (90%)\n"
+        );
+    }
+
+    #[test]
+    fn test_pinned_instructions_render_first() {
+        let instruction = |id: &str, text: &str, priority| ProjectInstruction {
+            id: id.to_string(),
+            instruction: text.to_string(),
+            category: InstructionCategory::Gotchas,
+            source: InstructionSource::Manual { reason: None },
+            confidence: None,
+            scope: None,
+            priority,
+            freshness: None,
+        };
+        let result = DistillResult {
+            instructions: vec![
+                instruction("1", "Prefer small PRs", InstructionPriority::Normal),
+                instruction("2", "Never commit secrets", InstructionPriority::High),
+                instruction("3", "Never force-push main", InstructionPriority::Critical),
+            ],
+            conventions: vec![],
+            navigation_hints: vec![],
+            architecture: ArchitectureOverview::default(),
+        };
+
+        let markdown = generate_project_skill_md(&result);
+        let pinned = markdown.find("## 📌 Pinned Rules").unwrap();
+        let critical = markdown.find("Never force-push main").unwrap();
+        let high = markdown.find("Never commit secrets").unwrap();
+        let gotchas = markdown.find("## ⚠️ Gotchas").unwrap();
+
+        assert!(pinned < critical && critical < high && high < gotchas);
+        assert_eq!(markdown.matches("Never commit secrets").count(), 1);
+    }
+
+    #[test]
+    fn test_architecture_section() {
+        use crate::skill::architecture::{ModuleFlow, ModuleSummary};
+
+        let result = DistillResult {
+            instructions: vec![],
+            conventions: vec![],
+            navigation_hints: vec![],
+            architecture: ArchitectureOverview {
+                modules: vec![ModuleSummary {
+                    path: "src/store".to_string(),
+                    files: 2,
+                    symbols: 10,
+                    used_by: 1,
+                }],
+                layers: vec![vec!["src/store".to_string()], vec!["src/mcp".to_string()]],
+                flows: vec![ModuleFlow {
+                    from: "src/mcp".to_string(),
+                    to: "src/store".to_string(),
+                    references: 7,
+                }],
+            },
+        };
+
+        let markdown = generate_project_skill_md(&result);
+        assert!(markdown.contains("## Architecture Overview"));
+        assert!(markdown.contains("| `src/store` | 2 | 10 | 1 modules |"));
+        assert!(markdown.contains("1. `src/store`\n2. `src/mcp`"));
+        assert!(markdown.contains("| `src/mcp` | `src/store` | 7 |"));
+    }
+
+    #[test]
+    fn test_render_split_files() {
+        let instruction = |text: &str, category, priority| ProjectInstruction {
+            id: text.to_string(),
+            instruction: text.to_string(),
+            category,
+            source: InstructionSource::Manual { reason: None },
+            confidence: None,
+            scope: None,
+            priority,
+            freshness: None,
+        };
+        let result = DistillResult {
+            instructions: vec![
+                instruction("Write tests", InstructionCategory::Testing, InstructionPriority::Normal),
+                instruction("Avoid unwrap", InstructionCategory::Gotchas, InstructionPriority::Normal),
+                instruction("Never push to main", InstructionCategory::Gotchas, InstructionPriority::Critical),
+            ],
+            conventions: vec![],
+            navigation_hints: vec![],
+            architecture: ArchitectureOverview::default(),
+        };
+
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let files = render_skill_files(
+            &result,
+            temp_dir.path(),
+            &["gotchas".to_string(), "bogus".to_string()],
+        )
+        .unwrap();
+
+        assert_eq!(files.len(), 2);
+        assert_eq!(files[0].name, SKILL_FILE);
+        assert_eq!(files[1].name, "GOTCHAS.md");
+
+        assert!(files[0].content.contains("Write tests"));
+        assert!(files[0].content.contains("Never push to main"));
+        assert!(!files[0].content.contains("Avoid unwrap"));
+        assert!(files[0].content.contains("See also: `GOTCHAS.md`"));
+        assert!(files[1].content.contains("- Avoid unwrap"));
+    }
+
+    #[test]
+    fn test_custom_category_section() {
+        let category = InstructionCategory::from_str("api-design");
+        assert_eq!(category, InstructionCategory::Custom("api-design".to_string()));
+
+        let result = DistillResult {
+            instructions: vec![ProjectInstruction {
+                id: "1".to_string(),
+                instruction: "Version every endpoint".to_string(),
+                category,
+                source: InstructionSource::Manual { reason: None },
+                confidence: None,
+                scope: None,
+                priority: InstructionPriority::Normal,
+                freshness: None,
+            }],
+            conventions: vec![],
+            navigation_hints: vec![],
+            architecture: ArchitectureOverview::default(),
+        };
+
+        let markdown = generate_project_skill_md(&result);
+        assert!(markdown.contains("## Api Design\n\n- Version every endpoint"));
+        assert_eq!(
+            category_from_title("Api Design"),
+            Some(InstructionCategory::Custom("api-design".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_uncertain_instructions() {
+        let now = chrono::Utc::now().timestamp();
+        let instruction = |confidence, freshness| ProjectInstruction {
+            id: "1".to_string(),
+            instruction: "Use async".to_string(),
+            category: InstructionCategory::Architecture,
+            source: InstructionSource::Pattern {
+                id: "p1".to_string(),
+            },
+            confidence: Some(confidence),
+            scope: Some("src/**".to_string()),
+            priority: InstructionPriority::Normal,
+            freshness,
+        };
+
+        let fresh = instruction(
+            0.85,
+            Some(InstructionFreshness {
+                source_confidence: 0.9,
+                last_validated: Some(now - 86400),
+            }),
+        );
+        assert_eq!(uncertainty(&fresh, now), None);
+
+        let stale = instruction(
+            0.85,
+            Some(InstructionFreshness {
+                source_confidence: 0.9,
+                last_validated: Some(now - 120 * 86400),
+            }),
+        );
+        assert_eq!(
+            uncertainty(&stale, now).as_deref(),
+            Some("last validated 120 days ago")
+        );
+
+        let decayed = instruction(
+            0.6,
+            Some(InstructionFreshness {
+                source_confidence: 0.9,
+                last_validated: None,
+            }),
+        );
+        assert_eq!(uncertainty(&decayed, now).as_deref(), Some("decayed from 90%"));
+        assert_eq!(uncertainty(&instruction(0.4, None), now).as_deref(), Some("low confidence"));
+
+        // The annotation doesn't leak into imported text
+        let line = render_instruction(&decayed);
+        assert!(line.contains("_(uncertain: decayed from 90%)_"));
+        let (text, scope) = parse_instruction_line(line.trim_start_matches("- "));
+        assert_eq!(text, "Use async");
+        assert_eq!(scope, Some("src/**"));
+    }
 }
@@ -1,12 +1,13 @@
 use anyhow::{Context, Result};
-use libsql::{Builder, Connection};
+use libsql::{Builder, Connection, Database};
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
-use std::path::Path;
-use tracing::debug;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use tracing::{debug, info, warn};
 
 use super::migrations::{apply_learning_migrations, apply_store_migrations};
-use crate::config::Config;
+use crate::config::{Config, RemoteLearningConfig};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Node {
@@ -41,9 +42,82 @@ pub struct FileMeta {
     pub indexed_at: Option<i64>,
 }
 
+/// Row counts across both databases
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct StoreStats {
+    pub files: u64,
+    /// Indexed symbols, excluding file, unresolved, and inferred (topic, table, ...) nodes
+    pub symbols: u64,
+    pub edges: u64,
+    pub connections: u64,
+    pub patterns: u64,
+    pub failures: u64,
+}
+
+/// Searchable text of one indexed symbol
+#[derive(Debug, Clone)]
+pub struct SymbolText<'a> {
+    pub id: &'a str,
+    pub file: &'a str,
+    pub kind: &'a str,
+    pub line: u32,
+    pub name: &'a str,
+    pub signature: &'a str,
+    pub docstring: &'a str,
+    pub body: &'a str,
+}
+
+/// A full-text search hit, best first
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CodeMatch {
+    pub id: String,
+    pub file: String,
+    pub kind: String,
+    pub line: u32,
+    pub name: String,
+    /// Matching text with the matched terms in «»
+    pub snippet: String,
+    /// Higher is better (negated bm25)
+    pub score: f64,
+}
+
+/// bm25 column weights for symbol_fts: a name match counts most, then the signature,
+/// the docstring, and finally the body
+const FTS_WEIGHTS: &str = "10.0, 5.0, 3.0, 1.0";
+
 pub struct Store {
     pub code_db: Connection,
     pub learning_db: Connection,
+    /// Embedded replica behind `learning_db` when [learning.remote] is configured
+    learning_replica: Option<Database>,
+    /// Held by each open transaction, and by writes to the code database made outside one;
+    /// SQLite transactions on one connection can't nest
+    tx_lock: tokio::sync::Mutex<()>,
+    /// Task that opened the current transaction (`None` inside it if opened outside any task)
+    tx_owner: std::sync::Mutex<Option<Option<tokio::task::Id>>>,
+}
+
+/// A transaction on the code database, see `Store::transaction`
+pub struct Transaction<'a> {
+    tx: libsql::Transaction,
+    // Dropped in order: the transaction ends before another task may write
+    _owner: TransactionOwner<'a>,
+    _lock: tokio::sync::MutexGuard<'a, ()>,
+}
+
+/// Clears `Store::tx_owner` when its transaction ends, committed or not
+struct TransactionOwner<'a>(&'a std::sync::Mutex<Option<Option<tokio::task::Id>>>);
+
+impl Drop for TransactionOwner<'_> {
+    fn drop(&mut self) {
+        *self.0.lock().unwrap_or_else(|e| e.into_inner()) = None;
+    }
+}
+
+impl Transaction<'_> {
+    pub async fn commit(self) -> Result<()> {
+        Ok(self.tx.commit().await?)
+    }
 }
 
 impl Store {
@@ -56,17 +130,130 @@ impl Store {
         let code_db = Self::open_database(&config.store_db_path).await?;
         apply_store_migrations(&code_db).await?;
 
-        debug!("Opening learning database: {:?}", config.learning_db_path);
-        let learning_db = Self::open_database(&config.learning_db_path).await?;
-        apply_learning_migrations(&learning_db).await?;
+        let remote = config.settings().learning.remote.clone();
+        let shared = if remote.is_enabled() {
+            let path = config.learning_replica_path();
+            debug!("Opening learning database replica of {}: {:?}", remote.url, path);
+            match Self::open_replica(&path, &remote).await {
+                Ok(shared) => Some(shared),
+                Err(e) => {
+                    warn!(
+                        "Couldn't open the shared learning database, recording to learning.db until it's reachable: {:#}",
+                        e
+                    );
+                    None
+                }
+            }
+        } else {
+            None
+        };
+        let (learning_db, learning_replica) = match shared {
+            Some((connection, replica)) => {
+                if config.learning_db_path.exists() {
+                    Self::merge_local_learnings(config, &connection).await;
+                }
+                (connection, Some(replica))
+            }
+            None => {
+                debug!("Opening learning database: {:?}", config.learning_db_path);
+                let local = Self::open_database(&config.learning_db_path).await?;
+                apply_learning_migrations(&local).await?;
+                (local, None)
+            }
+        };
 
         Ok(Self {
             code_db,
             learning_db,
+            learning_replica,
+            tx_lock: tokio::sync::Mutex::new(()),
+            tx_owner: std::sync::Mutex::new(None),
         })
     }
 
-    async fn open_database(path: &Path) -> Result<Connection> {
+    /// Open the embedded replica of the shared learning database, pull the latest changes, and
+    /// migrate it. The server must be reachable to migrate, since writes go to it; a failed
+    /// pull alone isn't fatal, since the replica answers reads as of its last sync.
+    async fn open_replica(path: &Path, remote: &RemoteLearningConfig) -> Result<(Connection, Database)> {
+        let auth_token = if remote.auth_token_env.is_empty() {
+            String::new()
+        } else {
+            std::env::var(&remote.auth_token_env)
+                .with_context(|| format!("[learning.remote] auth_token_env: ${} is not set", remote.auth_token_env))?
+        };
+        let mut builder = Builder::new_remote_replica(path, remote.url.clone(), auth_token);
+        if remote.sync_interval_secs > 0 {
+            builder = builder.sync_interval(Duration::from_secs(remote.sync_interval_secs));
+        }
+        let db = builder
+            .build()
+            .await
+            .with_context(|| format!("Failed to open a replica of {}", remote.url))?;
+        if let Err(e) = db.sync().await {
+            warn!("Couldn't sync learnings from {}, using the last synced copy: {}", remote.url, e);
+        }
+        let connection = db.connect()?;
+        apply_learning_migrations(&connection).await?;
+        Ok((connection, db))
+    }
+
+    /// Merge the learning.db kept before [learning.remote] was configured (or written while the
+    /// server was unreachable) into the shared database, then move it to learning-local.db so
+    /// it's merged only once. Earlier backups are kept by numbering the new one. If anything
+    /// fails it stays where it is, to be merged on the next start; merging it again doesn't
+    /// count anything twice.
+    async fn merge_local_learnings(config: &Config, shared: &Connection) {
+        let backup = Self::merged_backup_path(config);
+        let merged = async {
+            let local = Self::open_database(&config.learning_db_path).await?;
+            apply_learning_migrations(&local).await?;
+            let stats = crate::learning::sync::merge_learnings(&local, shared).await?;
+            drop(local);
+            for suffix in ["", "-wal", "-shm"] {
+                let mut from = config.learning_db_path.clone().into_os_string();
+                from.push(suffix);
+                let mut to = backup.clone().into_os_string();
+                to.push(suffix);
+                if Path::new(&from).exists() {
+                    std::fs::rename(&from, &to)?;
+                }
+            }
+            anyhow::Ok(stats)
+        };
+        match merged.await {
+            Ok(stats) => info!(
+                "Merged learning.db into the shared learning database ({} new, {} updated, {} folded); kept as {:?}",
+                stats.inserted,
+                stats.updated,
+                stats.folded,
+                backup
+            ),
+            Err(e) => warn!("Couldn't merge learning.db into the shared learning database: {:#}", e),
+        }
+    }
+
+    /// learning-local.db, or learning-local-2.db, -3, ... if earlier merges left it behind
+    fn merged_backup_path(config: &Config) -> PathBuf {
+        let first = config.merged_learning_db_path();
+        let mut path = first.clone();
+        let mut n = 2;
+        while path.exists() {
+            path = first.with_file_name(format!("learning-local-{}.db", n));
+            n += 1;
+        }
+        path
+    }
+
+    /// Pull the team's latest learnings into the local replica. Returns the number of WAL
+    /// frames pulled, or `None` if [learning.remote] isn't configured.
+    pub async fn sync_learning(&self) -> Result<Option<usize>> {
+        match &self.learning_replica {
+            Some(replica) => Ok(Some(replica.sync().await?.frames_synced())),
+            None => Ok(None),
+        }
+    }
+
+    pub async fn open_database(path: &Path) -> Result<Connection> {
         let db = Builder::new_local(path)
             .build()
             .await
@@ -76,6 +263,32 @@ impl Store {
         Ok(conn)
     }
 
+    /// Begin a transaction on the code database. Store methods share its connection, so writes
+    /// the opening task makes through them before `commit` land in it; dropping the transaction
+    /// uncommitted rolls them back, and SQLite discards it on the next open if the process dies
+    /// first. Other tasks' writes and transactions (say, a tool call's while the file watcher
+    /// indexes) wait until this one ends, so they are never committed or rolled back with it.
+    pub async fn transaction(&self) -> Result<Transaction<'_>> {
+        let lock = self.tx_lock.lock().await;
+        let tx = self.code_db.transaction().await?;
+        *self.tx_owner.lock().unwrap_or_else(|e| e.into_inner()) = Some(tokio::task::try_id());
+        Ok(Transaction {
+            tx,
+            _owner: TransactionOwner(&self.tx_owner),
+            _lock: lock,
+        })
+    }
+
+    /// Wait for another task's transaction to end before writing to the code database. Inside
+    /// the current task's own transaction there's nothing to wait for.
+    async fn write_lock(&self) -> Option<tokio::sync::MutexGuard<'_, ()>> {
+        let owner = *self.tx_owner.lock().unwrap_or_else(|e| e.into_inner());
+        if owner == Some(tokio::task::try_id()) {
+            return None;
+        }
+        Some(self.tx_lock.lock().await)
+    }
+
     // ===== Node CRUD =====
 
     pub async fn upsert_node(
@@ -85,6 +298,7 @@ impl Store {
         kind: &str,
         data: &Value,
     ) -> Result<()> {
+        let _write = self.write_lock().await;
         let data_str = serde_json::to_string(data)?;
 
         self.code_db
@@ -149,6 +363,7 @@ impl Store {
     }
 
     pub async fn delete_node(&self, id: &str) -> Result<()> {
+        let _write = self.write_lock().await;
         self.code_db
             .execute("DELETE FROM nodes WHERE id = ?1", [id])
             .await?;
@@ -181,6 +396,40 @@ impl Store {
         Ok(nodes)
     }
 
+    /// Code graph symbols defined in a file (IDs of the form `file::name`)
+    pub async fn get_file_nodes(&self, path: &str) -> Result<Vec<Node>> {
+        let prefix = format!("{}::", path);
+        let pattern = format!("{}%", prefix);
+        let mut rows = self
+            .code_db
+            .query(
+                "SELECT id, graph, kind, data, created_at, updated_at
+                 FROM nodes WHERE graph = 'code' AND id LIKE ?1",
+                [pattern.as_str()],
+            )
+            .await?;
+
+        let mut nodes = Vec::new();
+        while let Some(row) = rows.next().await? {
+            let id: String = row.get(0)?;
+            // `_` and `%` in the path are LIKE wildcards
+            if !id.starts_with(&prefix) {
+                continue;
+            }
+            let data_str: String = row.get(3)?;
+            nodes.push(Node {
+                id,
+                graph: row.get(1)?,
+                kind: row.get(2)?,
+                data: serde_json::from_str(&data_str)?,
+                created_at: row.get(4).ok(),
+                updated_at: row.get(5).ok(),
+            });
+        }
+
+        Ok(nodes)
+    }
+
     // ===== Edge CRUD =====
 
     pub async fn upsert_edge(
@@ -191,6 +440,7 @@ impl Store {
         graph: &str,
         data: Option<&Value>,
     ) -> Result<()> {
+        let _write = self.write_lock().await;
         let data_str = data.map(|d| serde_json::to_string(d)).transpose()?;
 
         self.code_db
@@ -259,6 +509,7 @@ impl Store {
     }
 
     pub async fn delete_edges_for(&self, node_id: &str) -> Result<()> {
+        let _write = self.write_lock().await;
         self.code_db
             .execute(
                 "DELETE FROM edges WHERE source = ?1 OR target = ?1",
@@ -292,6 +543,7 @@ impl Store {
     }
 
     pub async fn upsert_file_meta(&self, path: &str, mtime: i64, hash: &str) -> Result<()> {
+        let _write = self.write_lock().await;
         self.code_db
             .execute(
                 "INSERT INTO files (path, mtime, hash)
@@ -318,6 +570,7 @@ impl Store {
     }
 
     pub async fn remove_file_meta(&self, path: &str) -> Result<()> {
+        let _write = self.write_lock().await;
         self.code_db
             .execute("DELETE FROM files WHERE path = ?1", [path])
             .await?;
@@ -326,6 +579,7 @@ impl Store {
 
     /// Delete all nodes whose ID starts with a given prefix
     pub async fn delete_nodes_by_prefix(&self, prefix: &str) -> Result<u64> {
+        let _write = self.write_lock().await;
         let pattern = format!("{}%", prefix);
         let result = self
             .code_db
@@ -336,6 +590,7 @@ impl Store {
 
     /// Delete all edges where source or target starts with a given prefix
     pub async fn delete_edges_by_node_prefix(&self, prefix: &str) -> Result<u64> {
+        let _write = self.write_lock().await;
         let pattern = format!("{}%", prefix);
         let result = self
             .code_db
@@ -347,14 +602,15 @@ impl Store {
         Ok(result)
     }
 
-    /// Find all node IDs ending with `::suffix` (for cross-file resolution with ambiguity detection)
+    /// Find all node IDs ending with `::suffix` or `.suffix` (for cross-file resolution with ambiguity detection)
     pub async fn find_all_nodes_by_suffix(&self, suffix: &str) -> Result<Vec<String>> {
         let pattern = format!("%::{}", suffix);
+        let qualified = format!("%::%.{}", suffix);
         let mut rows = self
             .code_db
             .query(
-                "SELECT id FROM nodes WHERE id LIKE ?1 AND graph = 'code' AND kind != 'unresolved'",
-                [pattern.as_str()],
+                "SELECT id FROM nodes WHERE (id LIKE ?1 OR id LIKE ?2) AND graph = 'code' AND kind != 'unresolved'",
+                [pattern.as_str(), qualified.as_str()],
             )
             .await?;
 
@@ -365,6 +621,27 @@ impl Store {
         Ok(ids)
     }
 
+    /// Delete every edge of a kind, e.g. inferred `calls_api` edges before they are rebuilt
+    pub async fn delete_edges_by_kind(&self, kind: &str) -> Result<u64> {
+        let _write = self.write_lock().await;
+        let result = self.code_db.execute("DELETE FROM edges WHERE kind = ?1", [kind]).await?;
+        Ok(result)
+    }
+
+    /// Delete the `calls_api` edges from a node for one API path
+    pub async fn delete_api_edges(&self, source: &str, api_path: &str) -> Result<u64> {
+        let _write = self.write_lock().await;
+        let result = self
+            .code_db
+            .execute(
+                "DELETE FROM edges
+                 WHERE kind = 'calls_api' AND source = ?1 AND json_extract(data, '$.api') = ?2",
+                [source, api_path],
+            )
+            .await?;
+        Ok(result)
+    }
+
     /// Delete a specific edge
     pub async fn delete_edge(
         &self,
@@ -373,6 +650,7 @@ impl Store {
         kind: &str,
         graph: &str,
     ) -> Result<()> {
+        let _write = self.write_lock().await;
         self.code_db
             .execute(
                 "DELETE FROM edges WHERE source = ?1 AND target = ?2 AND kind = ?3 AND graph = ?4",
@@ -385,6 +663,7 @@ impl Store {
     /// Rewrite all edges pointing to `old_target` to point to `new_target` instead.
     /// Handles duplicates by deleting edges that would conflict with existing ones.
     pub async fn retarget_edges(&self, old_target: &str, new_target: &str) -> Result<u64> {
+        let _write = self.write_lock().await;
         // First, delete any edges from old_target that would conflict
         // (where an edge with the same source+kind+graph already points to new_target)
         self.code_db
@@ -436,8 +715,173 @@ impl Store {
         Ok(stubs)
     }
 
+    // ===== Full-text search =====
+
+    pub async fn insert_symbol_text(&self, symbol: &SymbolText<'_>) -> Result<()> {
+        let _write = self.write_lock().await;
+        self.code_db
+            .execute(
+                "INSERT INTO symbol_text (id, file, kind, line, name, signature, docstring, body)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+                libsql::params![
+                    symbol.id,
+                    symbol.file,
+                    symbol.kind,
+                    symbol.line,
+                    symbol.name,
+                    symbol.signature,
+                    symbol.docstring,
+                    symbol.body
+                ],
+            )
+            .await?;
+        Ok(())
+    }
+
+    pub async fn delete_symbol_text(&self, file: &str) -> Result<u64> {
+        let _write = self.write_lock().await;
+        let result = self
+            .code_db
+            .execute("DELETE FROM symbol_text WHERE file = ?1", [file])
+            .await?;
+        Ok(result)
+    }
+
+    /// Whether any symbol text has been indexed. Indexes built before full-text search existed
+    /// have none until the next full index.
+    pub async fn has_symbol_text(&self) -> Result<bool> {
+        Ok(Self::count(&self.code_db, "SELECT COUNT(*) FROM (SELECT 1 FROM symbol_text LIMIT 1)").await? > 0)
+    }
+
+    /// Rank symbols whose name, signature, docstring, or body contain every word of `query`.
+    /// Words are matched as FTS5 phrases, so punctuation in them (`foo.bar`, `a::b`) is safe.
+    pub async fn search_code(
+        &self,
+        query: &str,
+        kind: Option<&str>,
+        file_pattern: Option<&str>,
+        limit: usize,
+    ) -> Result<Vec<CodeMatch>> {
+        let Some(fts_query) = fts_query(query) else {
+            return Ok(Vec::new());
+        };
+
+        let sql = format!(
+            "SELECT t.id, t.file, t.kind, t.line, t.name,
+                    snippet(symbol_fts, -1, '«', '»', '…', 16),
+                    -bm25(symbol_fts, {}) AS score
+             FROM symbol_fts JOIN symbol_text t ON t.rowid = symbol_fts.rowid
+             WHERE symbol_fts MATCH ?1
+             AND (?2 = '' OR t.kind = ?2)
+             AND instr(t.file, ?3) > 0
+             ORDER BY score DESC
+             LIMIT ?4",
+            FTS_WEIGHTS
+        );
+        let mut rows = self
+            .code_db
+            .query(
+                &sql,
+                libsql::params![fts_query, kind.unwrap_or(""), file_pattern.unwrap_or(""), limit as i64],
+            )
+            .await?;
+
+        let mut matches = Vec::new();
+        while let Some(row) = rows.next().await? {
+            matches.push(CodeMatch {
+                id: row.get(0)?,
+                file: row.get(1)?,
+                kind: row.get(2)?,
+                line: row.get(3)?,
+                name: row.get(4)?,
+                snippet: row.get(5)?,
+                score: row.get(6)?,
+            });
+        }
+        Ok(matches)
+    }
+
+    // ===== Integrity =====
+
+    /// Code edges whose source or target node no longer exists
+    pub async fn dangling_edges(&self) -> Result<Vec<Edge>> {
+        let mut rows = self
+            .code_db
+            .query(
+                "SELECT source, target, kind, graph, data, created_at FROM edges e
+                 WHERE graph = 'code'
+                 AND (NOT EXISTS (SELECT 1 FROM nodes WHERE id = e.source)
+                      OR NOT EXISTS (SELECT 1 FROM nodes WHERE id = e.target))
+                 ORDER BY source, target",
+                (),
+            )
+            .await?;
+
+        let mut edges = Vec::new();
+        while let Some(row) = rows.next().await? {
+            let data_str: Option<String> = row.get(4).ok();
+            edges.push(Edge {
+                source: row.get(0)?,
+                target: row.get(1)?,
+                kind: row.get(2)?,
+                graph: row.get(3)?,
+                data: data_str.and_then(|s| serde_json::from_str(&s).ok()),
+                created_at: row.get(5).ok(),
+            });
+        }
+        Ok(edges)
+    }
+
+    /// Symbol nodes whose file is no longer indexed
+    pub async fn orphaned_nodes(&self) -> Result<Vec<String>> {
+        let mut rows = self
+            .code_db
+            .query(
+                "SELECT id FROM nodes
+                 WHERE graph = 'code' AND json_extract(data, '$.file') IS NOT NULL
+                 AND json_extract(data, '$.file') NOT IN (SELECT path FROM files)
+                 ORDER BY id",
+                (),
+            )
+            .await?;
+
+        let mut ids = Vec::new();
+        while let Some(row) = rows.next().await? {
+            ids.push(row.get(0)?);
+        }
+        Ok(ids)
+    }
+
+    // ===== Stats =====
+
+    /// Row counts for the code graph and learnings
+    pub async fn stats(&self) -> Result<StoreStats> {
+        Ok(StoreStats {
+            files: Self::count(&self.code_db, "SELECT COUNT(*) FROM files").await?,
+            symbols: Self::count(
+                &self.code_db,
+                "SELECT COUNT(*) FROM nodes WHERE graph = 'code'
+                 AND kind NOT IN ('file', 'unresolved', 'topic', 'channel', 'env_var', 'table')",
+            )
+            .await?,
+            edges: Self::count(&self.code_db, "SELECT COUNT(*) FROM edges WHERE graph = 'code'").await?,
+            connections: Self::count(&self.learning_db, "SELECT COUNT(*) FROM cross_language_edges").await?,
+            patterns: Self::count(&self.learning_db, "SELECT COUNT(*) FROM patterns").await?,
+            failures: Self::count(&self.learning_db, "SELECT COUNT(*) FROM failures").await?,
+        })
+    }
+
+    async fn count(conn: &Connection, sql: &str) -> Result<u64> {
+        let mut rows = conn.query(sql, ()).await?;
+        Ok(match rows.next().await? {
+            Some(row) => row.get::<i64>(0)? as u64,
+            None => 0,
+        })
+    }
+
     /// Delete all nodes and edges for a given graph type (e.g., "session")
     pub async fn delete_graph(&self, graph: &str) -> Result<()> {
+        let _write = self.write_lock().await;
         self.code_db
             .execute("DELETE FROM edges WHERE graph = ?1", [graph])
             .await?;
@@ -448,6 +892,16 @@ impl Store {
     }
 }
 
+/// Quote each whitespace-separated word as an FTS5 phrase (all must match), or `None` if
+/// there are no words
+fn fts_query(query: &str) -> Option<String> {
+    let phrases: Vec<String> = query
+        .split_whitespace()
+        .map(|word| format!("\"{}\"", word.replace('"', "\"\"")))
+        .collect();
+    (!phrases.is_empty()).then(|| phrases.join(" "))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -464,13 +918,45 @@ mod tests {
             codegraph_dir: temp_path.join(".codegraph"),
             store_db_path: temp_path.join("cache/store.db"),
             learning_db_path: temp_path.join(".codegraph/learning.db"),
-            settings: crate::config::ConfigFile::default(),
+            settings: crate::config::ConfigFile::default().into(),
         };
 
         let store = Store::open(&config).await.unwrap();
         (store, temp_dir)
     }
 
+    #[tokio::test]
+    async fn test_unreachable_remote_falls_back_to_local_learnings() {
+        let temp_dir = TempDir::new().unwrap();
+        let temp_path = temp_dir.path();
+        let mut settings = crate::config::ConfigFile::default();
+        settings.learning.remote.url = "http://127.0.0.1:1".to_string();
+        settings.learning.remote.auth_token_env = String::new();
+        settings.learning.remote.sync_interval_secs = 0;
+        let config = Config {
+            project_root: temp_path.to_path_buf(),
+            cache_dir: temp_path.join("cache"),
+            codegraph_dir: temp_path.join(".codegraph"),
+            store_db_path: temp_path.join("cache/store.db"),
+            learning_db_path: temp_path.join(".codegraph/learning.db"),
+            settings: settings.into(),
+        };
+
+        // Learnings go to learning.db, to be merged once the server is reachable again
+        let store = Store::open(&config).await.unwrap();
+        assert_eq!(store.sync_learning().await.unwrap(), None);
+        store.learning_db.query("SELECT COUNT(*) FROM patterns", ()).await.unwrap();
+        assert!(config.learning_db_path.exists());
+
+        // A later merge keeps the backup an earlier one left behind
+        assert_eq!(Store::merged_backup_path(&config), config.merged_learning_db_path());
+        std::fs::write(config.merged_learning_db_path(), "").unwrap();
+        assert_eq!(
+            Store::merged_backup_path(&config),
+            config.codegraph_dir.join("learning-local-2.db")
+        );
+    }
+
     #[tokio::test]
     async fn test_node_crud() {
         let (store, _temp) = setup_test_store().await;
@@ -537,6 +1023,80 @@ mod tests {
         assert_eq!(edges.len(), 0);
     }
 
+    #[tokio::test]
+    async fn test_stats() {
+        let (store, _temp) = setup_test_store().await;
+        store.upsert_node("file::a.rs", "code", "file", &json!({})).await.unwrap();
+        store.upsert_node("a.rs::run", "code", "function", &json!({})).await.unwrap();
+        store.upsert_node("topic::kafka:jobs", "code", "topic", &json!({})).await.unwrap();
+        store.upsert_edge("file::a.rs", "a.rs::run", "defines", "code", None).await.unwrap();
+
+        let stats = store.stats().await.unwrap();
+        assert_eq!((stats.symbols, stats.edges), (1, 1));
+        assert_eq!(stats.patterns, 0);
+    }
+
+    #[tokio::test]
+    async fn test_integrity_queries() {
+        let (store, _temp) = setup_test_store().await;
+        store.upsert_file_meta("a.rs", 1, "h").await.unwrap();
+        store.upsert_node("a.rs::run", "code", "function", &json!({"file": "a.rs"})).await.unwrap();
+        store.upsert_node("gone.rs::old", "code", "function", &json!({"file": "gone.rs"})).await.unwrap();
+        store.upsert_node("a.rs::stop", "code", "function", &json!({"file": "a.rs"})).await.unwrap();
+        store.upsert_edge("a.rs::run", "a.rs::stop", "calls", "code", None).await.unwrap();
+        // Databases written without foreign key enforcement can be left with dangling edges
+        store.code_db.execute("PRAGMA foreign_keys = OFF", ()).await.unwrap();
+        store.delete_node("a.rs::stop").await.unwrap();
+
+        let dangling = store.dangling_edges().await.unwrap();
+        assert_eq!(dangling.len(), 1);
+        assert_eq!(dangling[0].target, "a.rs::stop");
+        assert_eq!(store.orphaned_nodes().await.unwrap(), vec!["gone.rs::old".to_string()]);
+    }
+
+    #[tokio::test]
+    async fn test_search_code() {
+        let (store, _temp) = setup_test_store().await;
+        assert!(!store.has_symbol_text().await.unwrap());
+        let symbol = |id: &'static str, file: &'static str, name: &'static str, body: &'static str| SymbolText {
+            id,
+            file,
+            kind: "function",
+            line: 1,
+            name,
+            signature: "",
+            docstring: "",
+            body,
+        };
+        store
+            .insert_symbol_text(&symbol("a.rs::retry", "a.rs", "retry", "fn retry() { backoff.sleep(); }"))
+            .await
+            .unwrap();
+        store
+            .insert_symbol_text(&symbol("b.rs::backoff", "b.rs", "backoff", "fn backoff() { jitter(); }"))
+            .await
+            .unwrap();
+        assert!(store.has_symbol_text().await.unwrap());
+
+        // A name match outranks a body match
+        let matches = store.search_code("backoff", None, None, 10).await.unwrap();
+        let ids: Vec<&str> = matches.iter().map(|m| m.id.as_str()).collect();
+        assert_eq!(ids, vec!["b.rs::backoff", "a.rs::retry"]);
+        assert!(matches[1].snippet.contains("«backoff»"), "{}", matches[1].snippet);
+
+        // Every word must match; punctuation is not FTS syntax
+        let matches = store.search_code("backoff.sleep() retry", None, None, 10).await.unwrap();
+        assert_eq!(matches.len(), 1);
+        assert_eq!(matches[0].id, "a.rs::retry");
+        assert!(store.search_code("backoff", None, Some("b.rs"), 10).await.unwrap().len() == 1);
+        assert!(store.search_code("backoff", Some("class"), None, 10).await.unwrap().is_empty());
+        assert!(store.search_code("  ", None, None, 10).await.unwrap().is_empty());
+
+        store.delete_symbol_text("b.rs").await.unwrap();
+        let matches = store.search_code("jitter", None, None, 10).await.unwrap();
+        assert!(matches.is_empty());
+    }
+
     #[tokio::test]
     async fn test_file_meta() {
         let (store, _temp) = setup_test_store().await;
@@ -564,10 +1124,49 @@ mod tests {
         // List
         let files = store.list_indexed_files().await.unwrap();
         assert_eq!(files.len(), 1);
+        assert_eq!(store.stats().await.unwrap().files, 1);
 
         // Delete
         store.remove_file_meta("src/main.rs").await.unwrap();
         let meta = store.get_file_meta("src/main.rs").await.unwrap();
         assert!(meta.is_none());
     }
+
+    #[tokio::test]
+    async fn test_transaction_rolls_back_when_dropped() {
+        let (store, _temp) = setup_test_store().await;
+        {
+            let _tx = store.transaction().await.unwrap();
+            store.upsert_node("session::current", "session", "task", &json!({})).await.unwrap();
+            // Dropped without commit, like an update that failed halfway
+        }
+        assert!(store.get_node("session::current").await.unwrap().is_none());
+
+        let tx = store.transaction().await.unwrap();
+        store.upsert_node("session::current", "session", "task", &json!({})).await.unwrap();
+        tx.commit().await.unwrap();
+        assert!(store.get_node("session::current").await.unwrap().is_some());
+    }
+
+    #[tokio::test]
+    async fn test_other_task_writes_wait_for_transaction() {
+        let (store, _temp) = setup_test_store().await;
+        let store = std::sync::Arc::new(store);
+        let tx = store.transaction().await.unwrap();
+        store.upsert_node("file::a.rs", "code", "file", &json!({})).await.unwrap();
+
+        // A tool call writes while an indexing batch is open
+        let writer = tokio::spawn({
+            let store = store.clone();
+            async move { store.upsert_node("session::work", "session", "session", &json!({})).await.unwrap() }
+        });
+        tokio::task::yield_now().await;
+        assert!(!writer.is_finished());
+
+        // The batch fails; only its own write is rolled back
+        drop(tx);
+        writer.await.unwrap();
+        assert!(store.get_node("file::a.rs").await.unwrap().is_none());
+        assert!(store.get_node("session::work").await.unwrap().is_some());
+    }
 }
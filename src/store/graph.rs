@@ -1,16 +1,36 @@
 use anyhow::Result;
-use petgraph::graph::{DiGraph, NodeIndex};
-use serde_json::Value;
-use std::collections::HashMap;
-use tracing::debug;
+use petgraph::graph::NodeIndex;
+use petgraph::stable_graph::StableDiGraph;
+use petgraph::visit::{EdgeRef, IntoEdgeReferences};
+use serde_json::{json, Value};
+use std::collections::{HashMap, HashSet};
+use tokio::sync::{RwLock, RwLockReadGuard};
+use tracing::{debug, info};
 
 use super::db::Store;
+use crate::config::{GraphConfig, GraphMode};
+
+/// Node fields left out of a slim graph; they make up most of a symbol's data
+const HEAVY_FIELDS: &[&str] = &["signature", "docstring"];
+
+/// Rough in-memory cost of a node or edge beyond its JSON text (indices, id maps, allocations)
+const NODE_OVERHEAD_BYTES: u64 = 256;
+const EDGE_OVERHEAD_BYTES: u64 = 64;
+
+/// Parsed JSON takes several times the space of its text
+const JSON_EXPANSION: u64 = 4;
+
+/// PageRank damping factor: the chance of following an edge rather than jumping anywhere
+const RANK_DAMPING: f64 = 0.85;
+const RANK_ITERATIONS: usize = 20;
 
 #[derive(Debug, Clone)]
 pub struct NodeData {
     pub id: String,
     pub kind: String,
     pub data: Value,
+    /// PageRank centrality scaled so the most central node is 1.0; see `compute_ranks`
+    pub rank: f64,
 }
 
 #[derive(Debug, Clone)]
@@ -26,26 +46,183 @@ pub struct NeighborResult {
     pub distance: u32,
 }
 
+/// A route between two nodes: `nodes[i]` reaches `nodes[i + 1]` through an `edges[i]` edge
+#[derive(Debug, Clone)]
+pub struct GraphPath {
+    pub nodes: Vec<NodeData>,
+    pub edges: Vec<String>,
+}
+
+/// Files or directories that depend on each other in a loop, with the edges that close it
+#[derive(Debug, Clone)]
+pub struct Cycle {
+    pub members: Vec<String>,
+    pub edges: Vec<CycleEdge>,
+}
+
+/// A symbol-level edge between two members of a cycle
+#[derive(Debug, Clone)]
+pub struct CycleEdge {
+    pub from: String,
+    pub to: String,
+    pub source: String,
+    pub target: String,
+    pub kind: String,
+}
+
+/// What cycle detection treats as one unit
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Granularity {
+    File,
+    /// The directory a file is in
+    Module,
+}
+
+/// Output format of `CodeGraph::export`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExportFormat {
+    /// `{"nodes": [...], "edges": [...]}` with each node's data
+    Json,
+    /// Graphviz digraph, one cluster per file
+    Dot,
+    /// GraphML for Gephi, yEd, and similar tools
+    GraphMl,
+}
+
+impl std::str::FromStr for ExportFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "json" => Ok(Self::Json),
+            "dot" => Ok(Self::Dot),
+            "graphml" => Ok(Self::GraphMl),
+            _ => Err(format!("unknown export format '{}' (expected json, dot, or graphml)", s)),
+        }
+    }
+}
+
+/// The part of the graph `CodeGraph::export` writes. Empty lists keep everything.
+#[derive(Debug, Clone, Default)]
+pub struct ExportFilter {
+    /// Only nodes in files matching this glob
+    pub path: Option<glob::Pattern>,
+    pub kinds: Vec<String>,
+    pub edge_kinds: Vec<String>,
+    /// Keep `unresolved::` stubs that an exported edge points at
+    pub include_unresolved: bool,
+}
+
+/// Serialized graph with the number of nodes and edges it holds
+#[derive(Debug, Clone)]
+pub struct GraphExport {
+    pub text: String,
+    pub nodes: usize,
+    pub edges: usize,
+}
+
+/// A change made to the stored code graph, replayed to patch the in-memory graph
+/// without reloading it
+#[derive(Debug, Clone)]
+pub enum GraphChange {
+    /// All nodes of a file were deleted, along with every edge touching them
+    RemoveFile(String),
+    UpsertNode {
+        id: String,
+        kind: String,
+        data: Value,
+    },
+    UpsertEdge {
+        source: String,
+        target: String,
+        kind: String,
+        data: Option<Value>,
+    },
+    /// Edges into an `unresolved::` stub were moved to its definition and the stub deleted
+    Resolve { stub: String, target: String },
+}
+
 pub struct CodeGraph {
-    pub graph: DiGraph<NodeData, EdgeData>,
+    /// Stable indices, so nodes can be removed when patching without renumbering the rest
+    pub graph: StableDiGraph<NodeData, EdgeData>,
     id_to_index: HashMap<String, NodeIndex>,
     index_to_id: HashMap<NodeIndex, String>,
+    /// ID prefix before the first `::` (a file path for symbols) -> node IDs
+    by_prefix: HashMap<String, HashSet<String>>,
+    /// Nodes drop their heavy fields and edges their data; see `node_data`
+    slim: bool,
+    /// Patches since the last `compute_ranks`; see `read_ranked`
+    ranks_stale: bool,
 }
 
 impl CodeGraph {
     pub fn new() -> Self {
         Self {
-            graph: DiGraph::new(),
+            graph: StableDiGraph::new(),
             id_to_index: HashMap::new(),
             index_to_id: HashMap::new(),
+            by_prefix: HashMap::new(),
+            slim: false,
+            ranks_stale: false,
         }
     }
 
+    /// An empty graph that drops heavy node fields and edge data as they are added
+    pub fn new_slim() -> Self {
+        Self {
+            slim: true,
+            ..Self::new()
+        }
+    }
+
+    /// Load the code graph in the mode `settings` asks for; auto picks slim when the
+    /// estimated size of the full graph exceeds `slim_above_mb`. Slim keeps every node
+    /// and edge, so it only saves the share of memory taken by the dropped fields.
+    pub async fn load(store: &Store, settings: &GraphConfig) -> Result<Self> {
+        let slim = match settings.mode {
+            GraphMode::Full => false,
+            GraphMode::Slim => true,
+            GraphMode::Auto => {
+                let estimate = Self::estimate_size(store).await?;
+                let threshold = settings.slim_above_mb.saturating_mul(1024 * 1024);
+                if estimate > threshold {
+                    info!(
+                        "Full code graph would need ~{} MB (slim above {} MB); loading slim",
+                        estimate / (1024 * 1024),
+                        settings.slim_above_mb
+                    );
+                }
+                estimate > threshold
+            }
+        };
+        Self::load_into(store, if slim { Self::new_slim() } else { Self::new() }).await
+    }
+
+    /// Approximate bytes the full graph would take in memory
+    pub async fn estimate_size(store: &Store) -> Result<u64> {
+        let mut estimate = 0;
+        for (table, overhead) in [("nodes", NODE_OVERHEAD_BYTES), ("edges", EDGE_OVERHEAD_BYTES)] {
+            let query = format!(
+                "SELECT COUNT(*), COALESCE(SUM(LENGTH(data)), 0) FROM {} WHERE graph = 'code'",
+                table
+            );
+            let mut rows = store.code_db.query(&query, ()).await?;
+            if let Some(row) = rows.next().await? {
+                let count: i64 = row.get(0)?;
+                let bytes: i64 = row.get(1)?;
+                estimate += count as u64 * overhead + bytes as u64 * JSON_EXPANSION;
+            }
+        }
+        Ok(estimate)
+    }
+
     /// Load graph from store (code graph only)
     pub async fn load_from_store(store: &Store) -> Result<Self> {
-        debug!("Loading code graph from store");
+        Self::load_into(store, Self::new()).await
+    }
 
-        let mut code_graph = Self::new();
+    async fn load_into(store: &Store, mut code_graph: Self) -> Result<Self> {
+        debug!("Loading code graph from store (slim: {})", code_graph.slim);
 
         // Load all code nodes
         let nodes_query = "SELECT id, kind, data FROM nodes WHERE graph = 'code'";
@@ -74,6 +251,7 @@ impl CodeGraph {
             code_graph.add_edge(&source, &target, kind, data);
         }
 
+        code_graph.compute_ranks();
         debug!(
             "Loaded code graph: {} nodes, {} edges",
             code_graph.graph.node_count(),
@@ -83,14 +261,42 @@ impl CodeGraph {
         Ok(code_graph)
     }
 
+    /// Whether node data is partial; use `node_data` for fields like the signature
+    pub fn is_slim(&self) -> bool {
+        self.slim
+    }
+
+    /// The node's full data: what the graph holds, or in a slim graph, the stored node
+    pub async fn node_data(&self, store: &Store, node: &NodeData) -> Result<Value> {
+        if !self.slim {
+            return Ok(node.data.clone());
+        }
+        Ok(store.get_node(&node.id).await?.map_or_else(|| node.data.clone(), |stored| stored.data))
+    }
+
+    fn trim(&self, mut data: Value) -> Value {
+        if self.slim {
+            if let Some(fields) = data.as_object_mut() {
+                for field in HEAVY_FIELDS {
+                    fields.remove(*field);
+                }
+            }
+        }
+        data
+    }
+
     pub fn add_node(&mut self, id: String, kind: String, data: Value) {
         let node_data = NodeData {
             id: id.clone(),
             kind,
-            data,
+            data: self.trim(data),
+            rank: 0.0,
         };
 
         let index = self.graph.add_node(node_data);
+        if let Some((prefix, _)) = id.split_once("::") {
+            self.by_prefix.entry(prefix.to_string()).or_default().insert(id.clone());
+        }
         self.id_to_index.insert(id.clone(), index);
         self.index_to_id.insert(index, id);
     }
@@ -100,6 +306,7 @@ impl CodeGraph {
             self.id_to_index.get(source),
             self.id_to_index.get(target),
         ) {
+            let data = if self.slim { None } else { data };
             self.graph
                 .add_edge(source_idx, target_idx, EdgeData { kind, data });
         }
@@ -157,8 +364,8 @@ impl CodeGraph {
             results.push((node, score));
         }
 
-        // Sort by score (descending) and take limit
-        results.sort_by(|a, b| b.1.cmp(&a.1));
+        // Sort by score, then centrality (descending) and take limit
+        results.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| b.0.rank.total_cmp(&a.0.rank)));
         results
             .into_iter()
             .take(limit)
@@ -180,6 +387,17 @@ impl CodeGraph {
             .collect()
     }
 
+    /// Innermost symbol in `path` whose lines span `line`
+    pub fn symbol_at(&self, path: &str, line: usize) -> Option<&NodeData> {
+        let span = |node: &NodeData, key: &str| node.data.get(key).and_then(|v| v.as_u64()).map(|l| l as usize);
+        self.file_symbols(path)
+            .into_iter()
+            .filter_map(|node| Some((node, span(node, "line_start")?, span(node, "line_end")?)))
+            .filter(|(_, start, end)| (*start..=*end).contains(&line))
+            .min_by_key(|(_, start, end)| end - start)
+            .map(|(node, _, _)| node)
+    }
+
     /// Get neighbors of a node up to a certain depth
     pub fn neighbors(
         &self,
@@ -263,6 +481,85 @@ impl CodeGraph {
         results
     }
 
+    /// Shortest paths from one node to another along outgoing edges, at most `max_length` edges
+    /// long. Every path of the shortest length is returned, up to `limit`.
+    pub fn shortest_paths(
+        &self,
+        from: &str,
+        to: &str,
+        max_length: u32,
+        edge_filter: Option<&[&str]>,
+        limit: usize,
+    ) -> Vec<GraphPath> {
+        let (Some(&start), Some(&end)) = (self.id_to_index.get(from), self.id_to_index.get(to)) else {
+            return Vec::new();
+        };
+        if start == end {
+            return Vec::new();
+        }
+
+        // Breadth-first, level by level, recording every edge that reaches a node at its
+        // shortest distance so all equally short paths can be rebuilt
+        let mut distance = HashMap::from([(start, 0)]);
+        let mut predecessors: HashMap<NodeIndex, Vec<(NodeIndex, &str)>> = HashMap::new();
+        let mut frontier = vec![start];
+        let mut length = 0;
+        while !frontier.is_empty() && length < max_length && !distance.contains_key(&end) {
+            length += 1;
+            let mut next = Vec::new();
+            for &idx in &frontier {
+                for edge in self.graph.edges(idx) {
+                    let kind = edge.weight().kind.as_str();
+                    if edge_filter.is_some_and(|filter| !filter.contains(&kind)) {
+                        continue;
+                    }
+                    let target = edge.target();
+                    match distance.get(&target) {
+                        Some(&d) if d < length => continue,
+                        Some(_) => {}
+                        None => {
+                            distance.insert(target, length);
+                            next.push(target);
+                        }
+                    }
+                    let reached_by = predecessors.entry(target).or_default();
+                    if !reached_by.contains(&(idx, kind)) {
+                        reached_by.push((idx, kind));
+                    }
+                }
+            }
+            frontier = next;
+        }
+        if !distance.contains_key(&end) {
+            return Vec::new();
+        }
+
+        // Walk back from the end, branching at each node reached more than one way
+        let mut paths = Vec::new();
+        let mut partial = vec![(end, Vec::new(), Vec::new())];
+        while let Some((idx, mut nodes, mut edges)) = partial.pop() {
+            nodes.push(idx);
+            if idx == start {
+                nodes.reverse();
+                edges.reverse();
+                paths.push(GraphPath {
+                    nodes: nodes.iter().filter_map(|&i| self.graph.node_weight(i).cloned()).collect(),
+                    edges,
+                });
+                if paths.len() >= limit {
+                    break;
+                }
+                continue;
+            }
+            for &(prev, kind) in predecessors.get(&idx).into_iter().flatten() {
+                let mut edges = edges.clone();
+                edges.push(kind.to_string());
+                partial.push((prev, nodes.clone(), edges));
+            }
+        }
+        paths
+    }
+
     /// Get a node by ID
     pub fn get_node(&self, id: &str) -> Option<&NodeData> {
         self.id_to_index
@@ -270,10 +567,335 @@ impl CodeGraph {
             .and_then(|&idx| self.graph.node_weight(idx))
     }
 
-    /// Rebuild graph from store
-    pub async fn rebuild_from_store(&mut self, store: &Store) -> Result<()> {
-        *self = Self::load_from_store(store).await?;
-        Ok(())
+    /// Nodes with the given name, including the `unresolved::` stub holding references that
+    /// didn't resolve to a definition
+    pub fn nodes_named(&self, name: &str) -> Vec<&NodeData> {
+        self.graph
+            .node_weights()
+            .filter(|node| node.kind != "file" && node.data.get("name").and_then(|v| v.as_str()) == Some(name))
+            .collect()
+    }
+
+    /// Edges pointing at a node, each with the node it comes from
+    pub fn incoming(&self, id: &str) -> Vec<(&NodeData, &EdgeData)> {
+        let Some(&idx) = self.id_to_index.get(id) else {
+            return Vec::new();
+        };
+        self.graph
+            .edges_directed(idx, petgraph::Direction::Incoming)
+            .filter_map(|edge| self.graph.node_weight(edge.source()).map(|source| (source, edge.weight())))
+            .collect()
+    }
+
+    /// Symbols with no incoming edge of the given kinds (any kind if `None`) from another node,
+    /// by file and line. A name referenced through an `unresolved::` stub counts as referenced,
+    /// since the stub may stand for any definition with that name.
+    pub fn unreferenced(&self, edge_kinds: Option<&[&str]>) -> Vec<&NodeData> {
+        let referenced_by = |idx: NodeIndex| {
+            self.graph.edges_directed(idx, petgraph::Direction::Incoming).any(|edge| {
+                edge.source() != idx && edge_kinds.is_none_or(|kinds| kinds.contains(&edge.weight().kind.as_str()))
+            })
+        };
+        let stub_names: HashSet<&str> = self
+            .graph
+            .node_indices()
+            .filter(|&idx| self.graph[idx].kind == "unresolved" && referenced_by(idx))
+            .filter_map(|idx| self.graph[idx].data.get("name").and_then(|v| v.as_str()))
+            .collect();
+
+        let mut symbols: Vec<&NodeData> = self
+            .graph
+            .node_indices()
+            .filter(|&idx| {
+                let node = &self.graph[idx];
+                node.data.get("line_start").is_some()
+                    && !node.data.get("name").and_then(|v| v.as_str()).is_some_and(|n| stub_names.contains(n))
+                    && !referenced_by(idx)
+            })
+            .map(|idx| &self.graph[idx])
+            .collect();
+        let location = |node: &NodeData| {
+            let file = node.data.get("file").and_then(|v| v.as_str()).unwrap_or("").to_string();
+            (file, node.data.get("line_start").and_then(|v| v.as_u64()).unwrap_or(0))
+        };
+        symbols.sort_by_key(|node| location(node));
+        symbols
+    }
+
+    /// Dependency cycles between files or modules along edges of the given kinds that `keep`
+    /// accepts (given the source and target IDs), found as the strongly connected components
+    /// of the grouped graph. Largest cycles first.
+    pub fn cycles(
+        &self,
+        edge_kinds: &[&str],
+        granularity: Granularity,
+        mut keep: impl FnMut(&str, &str, &EdgeData) -> bool,
+    ) -> Vec<Cycle> {
+        let group_of = |idx: NodeIndex| {
+            let data = &self.graph[idx].data;
+            let file = data.get("file").or_else(|| data.get("path")).and_then(|v| v.as_str())?;
+            Some(match granularity {
+                Granularity::File => file.to_string(),
+                Granularity::Module => match std::path::Path::new(file).parent() {
+                    Some(dir) if !dir.as_os_str().is_empty() => dir.to_string_lossy().to_string(),
+                    _ => ".".to_string(),
+                },
+            })
+        };
+
+        let mut groups: petgraph::Graph<String, ()> = petgraph::Graph::new();
+        let mut group_index: HashMap<String, NodeIndex> = HashMap::new();
+        let mut edges: HashMap<(NodeIndex, NodeIndex), Vec<CycleEdge>> = HashMap::new();
+        for edge in self.graph.edge_references() {
+            if !edge_kinds.contains(&edge.weight().kind.as_str()) {
+                continue;
+            }
+            let (Some(from), Some(to)) = (group_of(edge.source()), group_of(edge.target())) else {
+                continue;
+            };
+            let (source, target) = (&self.graph[edge.source()].id, &self.graph[edge.target()].id);
+            if from == to || !keep(source, target, edge.weight()) {
+                continue;
+            }
+            let mut index_of = |group: &String| {
+                *group_index
+                    .entry(group.clone())
+                    .or_insert_with(|| groups.add_node(group.clone()))
+            };
+            let (a, b) = (index_of(&from), index_of(&to));
+            groups.update_edge(a, b, ());
+            edges.entry((a, b)).or_default().push(CycleEdge {
+                from,
+                to,
+                source: source.clone(),
+                target: target.clone(),
+                kind: edge.weight().kind.clone(),
+            });
+        }
+
+        let mut cycles: Vec<Cycle> = petgraph::algo::tarjan_scc(&groups)
+            .into_iter()
+            .filter(|component| component.len() > 1)
+            .map(|component| {
+                let mut members: Vec<String> = component.iter().map(|&idx| groups[idx].clone()).collect();
+                members.sort();
+                let mut cycle_edges: Vec<CycleEdge> = edges
+                    .iter()
+                    .filter(|((a, b), _)| component.contains(a) && component.contains(b))
+                    .flat_map(|(_, edges)| edges.iter().cloned())
+                    .collect();
+                cycle_edges.sort_by(|a, b| (&a.from, &a.to, &a.source).cmp(&(&b.from, &b.to, &b.source)));
+                Cycle {
+                    members,
+                    edges: cycle_edges,
+                }
+            })
+            .collect();
+        cycles.sort_by(|a, b| b.members.len().cmp(&a.members.len()).then_with(|| a.members.cmp(&b.members)));
+        cycles
+    }
+
+    /// Serialize the nodes and edges `filter` keeps, sorted by ID so exports diff cleanly.
+    /// Edges are kept only when both ends are.
+    pub async fn export(&self, store: &Store, format: ExportFormat, filter: &ExportFilter) -> Result<GraphExport> {
+        let keep = |node: &NodeData| {
+            if node.kind == "unresolved" {
+                return filter.include_unresolved;
+            }
+            let file = node.data.get("file").or_else(|| node.data.get("path")).and_then(|v| v.as_str());
+            (filter.kinds.is_empty() || filter.kinds.contains(&node.kind))
+                && filter.path.as_ref().is_none_or(|pattern| file.is_some_and(|file| pattern.matches(file)))
+        };
+        let mut edges: Vec<(&NodeData, &NodeData, &str)> = self
+            .graph
+            .edge_references()
+            .filter(|edge| filter.edge_kinds.is_empty() || filter.edge_kinds.contains(&edge.weight().kind))
+            .map(|edge| (&self.graph[edge.source()], &self.graph[edge.target()], edge.weight().kind.as_str()))
+            .filter(|(source, target, _)| keep(source) && keep(target))
+            .collect();
+        edges.sort_by(|a, b| (&a.0.id, &a.1.id, a.2).cmp(&(&b.0.id, &b.1.id, b.2)));
+        // A stub only matters as the target of an exported edge
+        let stub_targets: HashSet<&str> = edges.iter().map(|(_, target, _)| target.id.as_str()).collect();
+        let mut nodes: Vec<&NodeData> = self
+            .graph
+            .node_weights()
+            .filter(|node| keep(node) && (node.kind != "unresolved" || stub_targets.contains(node.id.as_str())))
+            .collect();
+        nodes.sort_by(|a, b| a.id.cmp(&b.id));
+
+        // JSON carries each node's full data; a slim graph reads it back for the exported nodes only
+        let mut stored: HashMap<String, Value> = HashMap::new();
+        if self.slim && format == ExportFormat::Json {
+            let ids: HashSet<&str> = nodes.iter().map(|node| node.id.as_str()).collect();
+            let mut rows = store.code_db.query("SELECT id, data FROM nodes WHERE graph = 'code'", ()).await?;
+            while let Some(row) = rows.next().await? {
+                let id: String = row.get(0)?;
+                if ids.contains(id.as_str()) {
+                    let data: String = row.get(1)?;
+                    stored.insert(id, serde_json::from_str(&data)?);
+                }
+            }
+        }
+
+        let text = match format {
+            ExportFormat::Json => export_json(&nodes, &edges, &stored),
+            ExportFormat::Dot => export_dot(&nodes, &edges),
+            ExportFormat::GraphMl => export_graphml(&nodes, &edges),
+        };
+        Ok(GraphExport {
+            text,
+            nodes: nodes.len(),
+            edges: edges.len(),
+        })
+    }
+
+    /// Score every node by PageRank over all edges, so symbols that much of the code depends on
+    /// (directly or through other central symbols) rank highest
+    pub fn compute_ranks(&mut self) {
+        let indices: Vec<NodeIndex> = self.graph.node_indices().collect();
+        if indices.is_empty() {
+            return;
+        }
+        let n = indices.len() as f64;
+        let position: HashMap<NodeIndex, usize> = indices.iter().enumerate().map(|(i, &idx)| (idx, i)).collect();
+        let out_degree: Vec<usize> = indices.iter().map(|&idx| self.graph.edges(idx).count()).collect();
+
+        let mut rank = vec![1.0 / n; indices.len()];
+        for _ in 0..RANK_ITERATIONS {
+            // Nodes without outgoing edges spread their rank over the whole graph
+            let dangling: f64 = rank.iter().zip(&out_degree).filter(|(_, &d)| d == 0).map(|(r, _)| r).sum();
+            let mut next = vec![(1.0 - RANK_DAMPING + RANK_DAMPING * dangling) / n; indices.len()];
+            for edge in self.graph.edge_references() {
+                let from = position[&edge.source()];
+                next[position[&edge.target()]] += RANK_DAMPING * rank[from] / out_degree[from] as f64;
+            }
+            rank = next;
+        }
+
+        let max = rank.iter().copied().fold(f64::MIN_POSITIVE, f64::max);
+        for (&idx, score) in indices.iter().zip(rank) {
+            self.graph[idx].rank = score / max;
+        }
+        self.ranks_stale = false;
+    }
+
+    /// Whether ranks predate the last patch
+    pub fn ranks_stale(&self) -> bool {
+        self.ranks_stale
+    }
+
+    /// Read the graph for a query that orders by rank, first recomputing ranks if a patch
+    /// left them stale. Patching only marks them, so saves in a row cost one recompute.
+    pub async fn read_ranked(lock: &RwLock<CodeGraph>) -> RwLockReadGuard<'_, CodeGraph> {
+        let graph = lock.read().await;
+        if !graph.ranks_stale {
+            return graph;
+        }
+        drop(graph);
+        let mut graph = lock.write().await;
+        if graph.ranks_stale {
+            graph.compute_ranks();
+        }
+        graph.downgrade()
+    }
+
+    /// Apply changes recorded during indexing, in order
+    pub fn apply(&mut self, changes: &[GraphChange]) {
+        for change in changes {
+            match change {
+                GraphChange::RemoveFile(path) => self.remove_file(path),
+                GraphChange::UpsertNode { id, kind, data } => self.upsert_node(id, kind, data),
+                GraphChange::UpsertEdge { source, target, kind, data } => {
+                    self.upsert_edge(source, target, kind, data.clone())
+                }
+                GraphChange::Resolve { stub, target } => self.resolve(stub, target),
+            }
+        }
+        if !changes.is_empty() {
+            self.ranks_stale = true;
+        }
+        debug!(
+            "Patched code graph with {} changes: {} nodes, {} edges",
+            changes.len(),
+            self.graph.node_count(),
+            self.graph.edge_count()
+        );
+    }
+
+    fn remove_node(&mut self, id: &str) {
+        if let Some(idx) = self.id_to_index.remove(id) {
+            self.index_to_id.remove(&idx);
+            self.graph.remove_node(idx);
+        }
+        if let Some((prefix, _)) = id.split_once("::") {
+            if let Some(ids) = self.by_prefix.get_mut(prefix) {
+                ids.remove(id);
+            }
+        }
+    }
+
+    /// Remove the file node and every `{path}::` symbol, like `Indexer::remove_file_nodes`
+    fn remove_file(&mut self, path: &str) {
+        for id in self.by_prefix.remove(path).unwrap_or_default() {
+            self.remove_node(&id);
+        }
+        self.remove_node(&format!("file::{}", path));
+    }
+
+    fn upsert_node(&mut self, id: &str, kind: &str, data: &Value) {
+        match self.id_to_index.get(id) {
+            Some(&idx) => {
+                let data = self.trim(data.clone());
+                let node = &mut self.graph[idx];
+                node.kind = kind.to_string();
+                node.data = data;
+            }
+            None => self.add_node(id.to_string(), kind.to_string(), data.clone()),
+        }
+    }
+
+    fn upsert_edge(&mut self, source: &str, target: &str, kind: &str, data: Option<Value>) {
+        let (Some(&source_idx), Some(&target_idx)) = (self.id_to_index.get(source), self.id_to_index.get(target))
+        else {
+            return;
+        };
+        let data = if self.slim { None } else { data };
+        let existing = self
+            .graph
+            .edges_connecting(source_idx, target_idx)
+            .find(|edge| edge.weight().kind == kind)
+            .map(|edge| edge.id());
+        match existing {
+            Some(edge) => self.graph[edge].data = data,
+            None => {
+                self.graph.add_edge(source_idx, target_idx, EdgeData { kind: kind.to_string(), data });
+            }
+        }
+    }
+
+    /// Move edges from `stub` to `target`, dropping any that `target` already has, like
+    /// `Store::retarget_edges`, then delete the stub
+    fn resolve(&mut self, stub: &str, target: &str) {
+        let Some(&stub_idx) = self.id_to_index.get(stub) else {
+            return;
+        };
+        if let Some(&target_idx) = self.id_to_index.get(target) {
+            let incoming: Vec<(NodeIndex, EdgeData)> = self
+                .graph
+                .edges_directed(stub_idx, petgraph::Direction::Incoming)
+                .map(|edge| (edge.source(), edge.weight().clone()))
+                .collect();
+            for (source_idx, edge) in incoming {
+                let duplicate = self
+                    .graph
+                    .edges_connecting(source_idx, target_idx)
+                    .any(|existing| existing.weight().kind == edge.kind);
+                if !duplicate {
+                    self.graph.add_edge(source_idx, target_idx, edge);
+                }
+            }
+        }
+        self.remove_node(stub);
     }
 }
 
@@ -290,10 +912,121 @@ impl Default for CodeGraph {
     }
 }
 
+fn node_str<'a>(node: &'a NodeData, field: &str) -> Option<&'a str> {
+    node.data.get(field).and_then(|v| v.as_str())
+}
+
+/// `stored` holds full data for nodes whose in-memory data was trimmed
+fn export_json(
+    nodes: &[&NodeData],
+    edges: &[(&NodeData, &NodeData, &str)],
+    stored: &HashMap<String, Value>,
+) -> String {
+    let nodes: Vec<Value> = nodes
+        .iter()
+        .map(|node| {
+            let data = stored.get(&node.id).unwrap_or(&node.data);
+            json!({ "id": node.id, "kind": node.kind, "rank": node.rank, "data": data })
+        })
+        .collect();
+    let edges: Vec<Value> = edges
+        .iter()
+        .map(|(source, target, kind)| json!({ "source": source.id, "target": target.id, "kind": kind }))
+        .collect();
+    let graph = json!({ "nodes": nodes, "edges": edges });
+    serde_json::to_string_pretty(&graph).unwrap_or_default() + "\n"
+}
+
+fn export_dot(nodes: &[&NodeData], edges: &[(&NodeData, &NodeData, &str)]) -> String {
+    let quote = |text: &str| format!("\"{}\"", text.replace('\\', "\\\\").replace('"', "\\\""));
+    let mut by_file: std::collections::BTreeMap<&str, Vec<&NodeData>> = Default::default();
+    for node in nodes {
+        let file = node_str(node, "file").or_else(|| node_str(node, "path"));
+        by_file.entry(file.unwrap_or("")).or_default().push(node);
+    }
+
+    let mut dot = String::from("digraph codegraph {\n  node [shape=box];\n");
+    for (i, (file, nodes)) in by_file.iter().enumerate() {
+        // Stubs belong to no file and stay outside the clusters
+        let indent = if file.is_empty() { "  " } else { "    " };
+        if !file.is_empty() {
+            dot.push_str(&format!("  subgraph cluster_{} {{\n    label={};\n", i, quote(file)));
+        }
+        for node in nodes {
+            let label = format!("{} [{}]", node_str(node, "name").unwrap_or(&node.id), node.kind);
+            dot.push_str(&format!("{}{} [label={}];\n", indent, quote(&node.id), quote(&label)));
+        }
+        if !file.is_empty() {
+            dot.push_str("  }\n");
+        }
+    }
+    for (source, target, kind) in edges {
+        dot.push_str(&format!("  {} -> {} [label={}];\n", quote(&source.id), quote(&target.id), quote(kind)));
+    }
+    dot.push_str("}\n");
+    dot
+}
+
+fn export_graphml(nodes: &[&NodeData], edges: &[(&NodeData, &NodeData, &str)]) -> String {
+    let escape = |text: &str| {
+        text.replace('&', "&amp;")
+            .replace('<', "&lt;")
+            .replace('>', "&gt;")
+            .replace('"', "&quot;")
+            .replace('\'', "&apos;")
+    };
+    let mut xml = String::from(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n\
+         <graphml xmlns=\"http://graphml.graphdrawing.org/xmlns\">\n\
+         \x20 <key id=\"kind\" for=\"node\" attr.name=\"kind\" attr.type=\"string\"/>\n\
+         \x20 <key id=\"name\" for=\"node\" attr.name=\"name\" attr.type=\"string\"/>\n\
+         \x20 <key id=\"file\" for=\"node\" attr.name=\"file\" attr.type=\"string\"/>\n\
+         \x20 <key id=\"line\" for=\"node\" attr.name=\"line\" attr.type=\"int\"/>\n\
+         \x20 <key id=\"rank\" for=\"node\" attr.name=\"rank\" attr.type=\"double\"/>\n\
+         \x20 <key id=\"edge_kind\" for=\"edge\" attr.name=\"kind\" attr.type=\"string\"/>\n\
+         \x20 <graph id=\"codegraph\" edgedefault=\"directed\">\n",
+    );
+    for node in nodes {
+        xml.push_str(&format!("    <node id=\"{}\">\n", escape(&node.id)));
+        let mut data = vec![("kind", node.kind.clone())];
+        data.extend(node_str(node, "name").map(|name| ("name", name.to_string())));
+        data.extend(node_str(node, "file").map(|file| ("file", file.to_string())));
+        data.extend(node.data.get("line_start").and_then(|v| v.as_u64()).map(|line| ("line", line.to_string())));
+        data.push(("rank", format!("{:.6}", node.rank)));
+        for (key, value) in data {
+            xml.push_str(&format!("      <data key=\"{}\">{}</data>\n", key, escape(&value)));
+        }
+        xml.push_str("    </node>\n");
+    }
+    for (source, target, kind) in edges {
+        xml.push_str(&format!(
+            "    <edge source=\"{}\" target=\"{}\"><data key=\"edge_kind\">{}</data></edge>\n",
+            escape(&source.id),
+            escape(&target.id),
+            escape(kind)
+        ));
+    }
+    xml.push_str("  </graph>\n</graphml>\n");
+    xml
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    async fn setup_store() -> (Store, tempfile::TempDir) {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let config = crate::config::Config {
+            project_root: temp_dir.path().to_path_buf(),
+            cache_dir: temp_dir.path().join("cache"),
+            codegraph_dir: temp_dir.path().join(".codegraph"),
+            store_db_path: temp_dir.path().join("cache/store.db"),
+            learning_db_path: temp_dir.path().join(".codegraph/learning.db"),
+            settings: crate::config::ConfigFile::default().into(),
+        };
+        (Store::open(&config).await.unwrap(), temp_dir)
+    }
+
     #[test]
     fn test_add_node() {
         let mut graph = CodeGraph::new();
@@ -325,6 +1058,20 @@ mod tests {
         assert_eq!(graph.graph.edge_count(), 1);
     }
 
+    #[test]
+    fn test_symbol_at() {
+        let mut graph = CodeGraph::new();
+        for (id, start, end) in [("src/a.rs::Parser", 1, 40), ("src/a.rs::Parser.parse", 10, 20)] {
+            let data = serde_json::json!({"name": id, "file": "src/a.rs", "line_start": start, "line_end": end});
+            graph.add_node(id.to_string(), "function".to_string(), data);
+        }
+
+        assert_eq!(graph.symbol_at("src/a.rs", 12).unwrap().id, "src/a.rs::Parser.parse");
+        assert_eq!(graph.symbol_at("src/a.rs", 30).unwrap().id, "src/a.rs::Parser");
+        assert!(graph.symbol_at("src/a.rs", 41).is_none());
+        assert!(graph.symbol_at("src/b.rs", 12).is_none());
+    }
+
     #[test]
     fn test_search() {
         let mut graph = CodeGraph::new();
@@ -343,4 +1090,324 @@ mod tests {
         assert_eq!(results.len(), 1);
         assert_eq!(results[0].id, "test::hello_world");
     }
+
+    #[test]
+    fn test_incoming_edges() {
+        let mut graph = CodeGraph::new();
+        for id in ["a.rs::run", "b.rs::main", "unresolved::run"] {
+            let name = id.rsplit("::").next().unwrap();
+            graph.add_node(id.to_string(), "function".to_string(), serde_json::json!({"name": name}));
+        }
+        let call = serde_json::json!({"file": "b.rs", "line": 4});
+        graph.add_edge("b.rs::main", "a.rs::run", "calls".to_string(), Some(call.clone()));
+
+        let named: Vec<&str> = graph.nodes_named("run").iter().map(|n| n.id.as_str()).collect();
+        assert_eq!(named.len(), 2);
+        assert!(named.contains(&"unresolved::run"));
+
+        let incoming = graph.incoming("a.rs::run");
+        assert_eq!(incoming.len(), 1);
+        assert_eq!(incoming[0].0.id, "b.rs::main");
+        assert_eq!(incoming[0].1.data, Some(call));
+        assert!(graph.incoming("b.rs::main").is_empty());
+        assert!(graph.incoming("missing").is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_compute_ranks() {
+        let mut graph = CodeGraph::new();
+        for id in ["b.rs::store_put", "a.rs::store_get", "c.rs::handler", "d.rs::cli", "e.rs::main"] {
+            let name = id.rsplit("::").next().unwrap();
+            graph.add_node(id.to_string(), "function".to_string(), serde_json::json!({"name": name}));
+        }
+        graph.apply(&[
+            GraphChange::UpsertEdge {
+                source: "c.rs::handler".to_string(),
+                target: "a.rs::store_get".to_string(),
+                kind: "calls".to_string(),
+                data: None,
+            },
+            GraphChange::UpsertEdge {
+                source: "d.rs::cli".to_string(),
+                target: "a.rs::store_get".to_string(),
+                kind: "calls".to_string(),
+                data: None,
+            },
+            GraphChange::UpsertEdge {
+                source: "e.rs::main".to_string(),
+                target: "c.rs::handler".to_string(),
+                kind: "calls".to_string(),
+                data: None,
+            },
+        ]);
+        assert!(graph.ranks_stale());
+        assert_eq!(graph.get_node("a.rs::store_get").unwrap().rank, 0.0);
+
+        let lock = RwLock::new(graph);
+        let graph = CodeGraph::read_ranked(&lock).await;
+        assert!(!graph.ranks_stale());
+        let rank = |id: &str| graph.get_node(id).unwrap().rank;
+        assert_eq!(rank("a.rs::store_get"), 1.0);
+        assert!(rank("c.rs::handler") > rank("d.rs::cli"));
+        assert!(rank("d.rs::cli") > 0.0);
+        assert_eq!(rank("d.rs::cli"), rank("b.rs::store_put"));
+
+        // Equally good name matches are ordered by centrality
+        let found: Vec<&str> = graph.search("store", None, None, 10).iter().map(|n| n.id.as_str()).collect();
+        assert_eq!(found, vec!["a.rs::store_get", "b.rs::store_put"]);
+    }
+
+    #[tokio::test]
+    async fn test_export() {
+        let (store, _temp) = setup_store().await;
+        let mut graph = CodeGraph::new();
+        graph.add_node("src/lib.rs".into(), "file".into(), json!({"path": "src/lib.rs", "name": "src/lib.rs"}));
+        for (id, kind) in [("src/lib.rs::run", "function"), ("src/lib.rs::Parser", "struct")] {
+            let name = id.rsplit("::").next().unwrap();
+            let file = id.split("::").next().unwrap();
+            graph.add_node(id.into(), kind.into(), json!({"name": name, "file": file, "line_start": 3}));
+        }
+        graph.add_node("tests/t.rs::t".into(), "function".into(), json!({"name": "t", "file": "tests/t.rs"}));
+        graph.add_node("unresolved::say<\"hi\">".into(), "unresolved".into(), json!({"name": "say<\"hi\">"}));
+        graph.add_node("unresolved::unused".into(), "unresolved".into(), json!({"name": "unused"}));
+        graph.add_edge("src/lib.rs", "src/lib.rs::Parser", "imports".into(), None);
+        graph.add_edge("src/lib.rs::run", "src/lib.rs::Parser", "calls".into(), None);
+        graph.add_edge("src/lib.rs::run", "unresolved::say<\"hi\">", "calls".into(), None);
+        graph.add_edge("tests/t.rs::t", "src/lib.rs::run", "calls".into(), None);
+
+        let export = graph.export(&store, ExportFormat::Json, &ExportFilter::default()).await.unwrap();
+        let exported: Value = serde_json::from_str(&export.text).unwrap();
+        let ids: Vec<&str> = exported["nodes"].as_array().unwrap().iter().filter_map(|n| n["id"].as_str()).collect();
+        assert_eq!(ids, vec!["src/lib.rs", "src/lib.rs::Parser", "src/lib.rs::run", "tests/t.rs::t"]);
+        assert_eq!((export.nodes, export.edges), (4, 3));
+        let import = json!({"source": "src/lib.rs", "target": "src/lib.rs::Parser", "kind": "imports"});
+        assert_eq!(exported["edges"][0], import);
+        assert_eq!(exported["nodes"][1]["data"]["line_start"], 3);
+
+        // Filters drop nodes, and edges with a dropped end; stubs come along only when referenced
+        let filter = ExportFilter {
+            path: Some(glob::Pattern::new("src/**").unwrap()),
+            kinds: vec!["function".into(), "struct".into()],
+            edge_kinds: vec!["calls".into()],
+            include_unresolved: true,
+        };
+        let export = graph.export(&store, ExportFormat::Dot, &filter).await.unwrap();
+        assert_eq!((export.nodes, export.edges), (3, 2));
+        assert_eq!(
+            export.text,
+            "digraph codegraph {\n  node [shape=box];\n\
+             \x20 \"unresolved::say<\\\"hi\\\">\" [label=\"say<\\\"hi\\\"> [unresolved]\"];\n\
+             \x20 subgraph cluster_1 {\n    label=\"src/lib.rs\";\n\
+             \x20   \"src/lib.rs::Parser\" [label=\"Parser [struct]\"];\n\
+             \x20   \"src/lib.rs::run\" [label=\"run [function]\"];\n  }\n\
+             \x20 \"src/lib.rs::run\" -> \"src/lib.rs::Parser\" [label=\"calls\"];\n\
+             \x20 \"src/lib.rs::run\" -> \"unresolved::say<\\\"hi\\\">\" [label=\"calls\"];\n}\n"
+        );
+
+        let export = graph.export(&store, ExportFormat::GraphMl, &filter).await.unwrap();
+        assert!(export.text.starts_with("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<graphml "), "{}", export.text);
+        assert!(export.text.contains("    <node id=\"unresolved::say&lt;&quot;hi&quot;&gt;\">\n"), "{}", export.text);
+        assert!(export.text.contains("      <data key=\"line\">3</data>\n"), "{}", export.text);
+        assert!(export.text.contains(
+            "    <edge source=\"src/lib.rs::run\" target=\"src/lib.rs::Parser\">\
+             <data key=\"edge_kind\">calls</data></edge>\n"
+        ));
+        assert!(export.text.ends_with("  </graph>\n</graphml>\n"));
+
+        assert_eq!("GraphML".parse::<ExportFormat>(), Ok(ExportFormat::GraphMl));
+        assert!("svg".parse::<ExportFormat>().is_err());
+    }
+
+    #[tokio::test]
+    async fn test_export_slim_reads_stored_data() {
+        let (store, _temp) = setup_store().await;
+        let data = json!({"name": "run", "file": "a.rs", "line_start": 3, "signature": "fn run()"});
+        store.upsert_node("a.rs::run", "code", "function", &data).await.unwrap();
+        store.upsert_node("b.rs::stop", "code", "function", &json!({"name": "stop", "file": "b.rs"})).await.unwrap();
+        let settings = GraphConfig {
+            mode: GraphMode::Slim,
+            ..Default::default()
+        };
+        let graph = CodeGraph::load(&store, &settings).await.unwrap();
+        assert!(graph.get_node("a.rs::run").unwrap().data.get("signature").is_none());
+
+        let export = graph.export(&store, ExportFormat::Json, &ExportFilter::default()).await.unwrap();
+        let exported: Value = serde_json::from_str(&export.text).unwrap();
+        assert_eq!(exported["nodes"][0]["data"], data);
+        assert_eq!(exported["nodes"][1]["data"]["name"], "stop");
+    }
+
+    #[test]
+    fn test_cycles() {
+        let mut graph = CodeGraph::new();
+        for id in ["app/a.py::run", "app/b.py::load", "app/c.py::save", "lib/d.py::util", "lib/e.py::log"] {
+            let (file, name) = id.split_once("::").unwrap();
+            graph.add_node(id.to_string(), "function".to_string(), serde_json::json!({"name": name, "file": file}));
+        }
+        graph.add_node("unresolved::x".to_string(), "unresolved".to_string(), serde_json::json!({"name": "x"}));
+        for (source, target, kind) in [
+            ("app/a.py::run", "app/b.py::load", "calls"),
+            ("app/b.py::load", "app/c.py::save", "calls"),
+            ("app/c.py::save", "app/a.py::run", "calls"),
+            ("app/c.py::save", "lib/d.py::util", "calls"),
+            ("lib/d.py::util", "lib/e.py::log", "calls"),
+            ("lib/e.py::log", "lib/d.py::util", "uses_type"),
+            ("lib/d.py::util", "app/b.py::load", "imports"),
+            ("lib/d.py::util", "unresolved::x", "calls"),
+        ] {
+            graph.add_edge(source, target, kind.to_string(), None);
+        }
+
+        let cycles = graph.cycles(&["calls"], Granularity::File, |_, _, _| true);
+        assert_eq!(cycles.len(), 1);
+        assert_eq!(cycles[0].members, vec!["app/a.py", "app/b.py", "app/c.py"]);
+        assert_eq!(cycles[0].edges.len(), 3);
+        assert_eq!(cycles[0].edges[0].source, "app/a.py::run");
+        assert_eq!(cycles[0].edges[0].target, "app/b.py::load");
+
+        let cycles = graph.cycles(&["calls", "uses_type"], Granularity::File, |_, _, _| true);
+        assert_eq!(cycles.len(), 2);
+        assert_eq!(cycles[1].members, vec!["lib/d.py", "lib/e.py"]);
+
+        // Grouped by directory, app calls into lib and lib imports from app
+        let cycles = graph.cycles(&["calls", "imports"], Granularity::Module, |_, _, _| true);
+        assert_eq!(cycles.len(), 1);
+        assert_eq!(cycles[0].members, vec!["app", "lib"]);
+        let crossing: Vec<(&str, &str)> =
+            cycles[0].edges.iter().map(|e| (e.source.as_str(), e.target.as_str())).collect();
+        assert_eq!(crossing, vec![("app/c.py::save", "lib/d.py::util"), ("lib/d.py::util", "app/b.py::load")]);
+
+        assert!(graph.cycles(&["imports"], Granularity::File, |_, _, _| true).is_empty());
+        assert!(graph.cycles(&["calls"], Granularity::Module, |_, _, _| true).is_empty());
+    }
+
+    #[test]
+    fn test_unreferenced() {
+        let mut graph = CodeGraph::new();
+        for (id, line) in [("b.py::used", 1), ("a.py::unused", 3), ("a.py::recursive", 1), ("a.py::save", 5)] {
+            let name = id.rsplit("::").next().unwrap();
+            let data = serde_json::json!({"name": name, "file": &id[..4], "line_start": line});
+            graph.add_node(id.to_string(), "function".to_string(), data);
+        }
+        graph.add_node("unresolved::save".to_string(), "unresolved".to_string(), serde_json::json!({"name": "save"}));
+        graph.add_edge("a.py::unused", "b.py::used", "calls".to_string(), None);
+        graph.add_edge("a.py::recursive", "a.py::recursive", "calls".to_string(), None);
+        graph.add_edge("b.py::used", "unresolved::save", "calls".to_string(), None);
+
+        let ids = |nodes: Vec<&NodeData>| nodes.iter().map(|n| n.id.clone()).collect::<Vec<_>>();
+        assert_eq!(ids(graph.unreferenced(Some(&["calls"]))), vec!["a.py::recursive", "a.py::unused"]);
+        assert_eq!(
+            ids(graph.unreferenced(Some(&["imports"]))),
+            vec!["a.py::recursive", "a.py::unused", "a.py::save", "b.py::used"]
+        );
+    }
+
+    #[test]
+    fn test_shortest_paths() {
+        let mut graph = CodeGraph::new();
+        for id in ["main", "load", "index", "parse", "upsert", "helper"] {
+            graph.add_node(id.to_string(), "function".to_string(), serde_json::json!({"name": id}));
+        }
+        for (source, target, kind) in [
+            ("main", "load", "calls"),
+            ("main", "index", "calls"),
+            ("load", "upsert", "calls"),
+            ("index", "upsert", "calls"),
+            ("index", "parse", "calls"),
+            ("parse", "helper", "uses_type"),
+            ("upsert", "helper", "calls"),
+        ] {
+            graph.add_edge(source, target, kind.to_string(), None);
+        }
+        let ids = |path: &GraphPath| path.nodes.iter().map(|n| n.id.clone()).collect::<Vec<_>>().join(" > ");
+
+        let mut paths: Vec<String> = graph.shortest_paths("main", "upsert", 5, None, 10).iter().map(ids).collect();
+        paths.sort();
+        assert_eq!(paths, vec!["main > index > upsert", "main > load > upsert"]);
+        assert_eq!(graph.shortest_paths("main", "upsert", 5, None, 1).len(), 1);
+
+        // Only the shortest length is returned, not longer detours
+        let paths = graph.shortest_paths("index", "helper", 5, None, 10);
+        assert_eq!(paths.len(), 2);
+        assert!(paths.iter().all(|p| p.edges.len() == 2));
+
+        let paths = graph.shortest_paths("index", "helper", 5, Some(&["calls"]), 10);
+        assert_eq!(paths.iter().map(ids).collect::<Vec<_>>(), vec!["index > upsert > helper"]);
+        assert_eq!(paths[0].edges, vec!["calls", "calls"]);
+
+        assert!(graph.shortest_paths("main", "helper", 2, None, 10).is_empty());
+        assert_eq!(graph.shortest_paths("main", "helper", 3, None, 10).len(), 3);
+        assert!(graph.shortest_paths("helper", "main", 5, None, 10).is_empty());
+        assert!(graph.shortest_paths("main", "missing", 5, None, 10).is_empty());
+    }
+
+    #[test]
+    fn test_slim_graph_drops_heavy_data() {
+        let mut graph = CodeGraph::new_slim();
+        let data = serde_json::json!({"name": "run", "file": "a.rs", "line_start": 3, "signature": "fn run()"});
+        graph.add_node("a.rs::run".to_string(), "function".to_string(), data.clone());
+        graph.apply(&[
+            GraphChange::UpsertNode {
+                id: "b.rs::stop".to_string(),
+                kind: "function".to_string(),
+                data: serde_json::json!({"name": "stop", "docstring": "Stops everything"}),
+            },
+            GraphChange::UpsertEdge {
+                source: "a.rs::run".to_string(),
+                target: "b.rs::stop".to_string(),
+                kind: "calls".to_string(),
+                data: Some(serde_json::json!({"file": "a.rs", "line": 4})),
+            },
+        ]);
+
+        let run = graph.get_node("a.rs::run").unwrap();
+        assert_eq!(run.data, serde_json::json!({"name": "run", "file": "a.rs", "line_start": 3}));
+        assert_eq!(graph.get_node("b.rs::stop").unwrap().data, serde_json::json!({"name": "stop"}));
+        assert!(graph.graph.edge_weights().all(|edge| edge.data.is_none()));
+        // Search and traversal only need what's kept
+        assert_eq!(graph.search("stop", None, None, 10).len(), 1);
+        assert_eq!(graph.neighbors("a.rs::run", 1, Direction::Outgoing, None).len(), 1);
+    }
+
+    #[test]
+    fn test_apply_changes() {
+        let mut graph = CodeGraph::new();
+        let node = |id: &str, kind: &str, name: &str| GraphChange::UpsertNode {
+            id: id.to_string(),
+            kind: kind.to_string(),
+            data: serde_json::json!({"name": name}),
+        };
+        let edge = |source: &str, target: &str| GraphChange::UpsertEdge {
+            source: source.to_string(),
+            target: target.to_string(),
+            kind: "calls".to_string(),
+            data: None,
+        };
+        graph.apply(&[
+            node("a.rs::run", "function", "run"),
+            node("unresolved::helper", "unresolved", "helper"),
+            edge("a.rs::run", "unresolved::helper"),
+            node("b.rs::helper", "function", "helper"),
+            node("b.rs::old", "function", "old"),
+            edge("a.rs::run", "b.rs::old"),
+        ]);
+        assert_eq!((graph.graph.node_count(), graph.graph.edge_count()), (4, 2));
+
+        // b.rs is reindexed without `old`, then the stub resolves to its `helper`
+        graph.apply(&[
+            GraphChange::RemoveFile("b.rs".to_string()),
+            node("b.rs::helper", "function", "helper"),
+            GraphChange::Resolve {
+                stub: "unresolved::helper".to_string(),
+                target: "b.rs::helper".to_string(),
+            },
+        ]);
+
+        assert!(graph.get_node("b.rs::old").is_none());
+        assert!(graph.get_node("unresolved::helper").is_none());
+        let callees = graph.neighbors("a.rs::run", 1, Direction::Outgoing, None);
+        assert_eq!(callees.len(), 1);
+        assert_eq!(callees[0].node.id, "b.rs::helper");
+        assert_eq!(graph.search("helper", None, None, 10).len(), 1);
+    }
 }
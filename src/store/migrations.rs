@@ -8,11 +8,19 @@ type Migration = (i32, &'static str);
 /// Store database migrations (store.db - code graph)
 const STORE_MIGRATIONS: &[Migration] = &[
     (1, include_str!("../../migrations/store_v1.sql")),
+    (2, include_str!("../../migrations/store_v2.sql")),
 ];
 
 /// Learning database migrations (learning.db - patterns, failures, etc.)
 const LEARNING_MIGRATIONS: &[Migration] = &[
     (1, include_str!("../../migrations/learning_v1.sql")),
+    (2, include_str!("../../migrations/learning_v2.sql")),
+    (3, include_str!("../../migrations/learning_v3.sql")),
+    (4, include_str!("../../migrations/learning_v4.sql")),
+    (5, include_str!("../../migrations/learning_v5.sql")),
+    (6, include_str!("../../migrations/learning_v6.sql")),
+    (7, include_str!("../../migrations/learning_v7.sql")),
+    (8, include_str!("../../migrations/learning_v8.sql")),
 ];
 
 /// Apply migrations to a database connection
@@ -85,6 +93,27 @@ pub async fn apply_migrations(
     Ok(())
 }
 
+/// Highest migration version recorded in a database (0 if none were applied)
+pub async fn schema_version(conn: &Connection) -> Result<i32> {
+    let mut rows = conn
+        .query(
+            "SELECT name FROM sqlite_master WHERE type = 'table' AND name = '_migrations'",
+            (),
+        )
+        .await?;
+    if rows.next().await?.is_none() {
+        return Ok(0);
+    }
+    let mut rows = conn.query("SELECT COALESCE(MAX(version), 0) FROM _migrations", ()).await?;
+    Ok(rows.next().await?.map(|row| row.get::<i32>(0).unwrap_or(0)).unwrap_or(0))
+}
+
+/// Schema versions this build migrates store.db and learning.db to
+pub fn latest_versions() -> (i32, i32) {
+    let latest = |migrations: &[Migration]| migrations.last().map_or(0, |(version, _)| *version);
+    (latest(STORE_MIGRATIONS), latest(LEARNING_MIGRATIONS))
+}
+
 /// Apply store database migrations
 pub async fn apply_store_migrations(conn: &Connection) -> Result<()> {
     apply_migrations(conn, STORE_MIGRATIONS, "store.db").await
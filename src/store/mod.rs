@@ -3,4 +3,4 @@ pub mod graph;
 pub mod migrations;
 
 pub use db::Store;
-pub use graph::{CodeGraph, Direction};
+pub use graph::{CodeGraph, Direction, Granularity};
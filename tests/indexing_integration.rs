@@ -1,7 +1,11 @@
+use codegraph::code::indexer::IndexProgress;
 use codegraph::code::Indexer;
 use codegraph::config::Config;
 use codegraph::store::{CodeGraph, Store};
-use std::sync::Arc;
+use petgraph::visit::{EdgeRef, IntoEdgeReferences};
+use std::collections::BTreeSet;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
 use tempfile::TempDir;
 
 async fn setup_indexer_with_files(
@@ -103,6 +107,50 @@ async fn test_incremental_indexing() {
     assert!(stats3.symbols_found > symbols1); // New symbol
 }
 
+/// Node IDs and (source, target, kind) edges, for comparing graphs
+fn graph_contents(graph: &CodeGraph) -> (BTreeSet<String>, BTreeSet<(String, String, String)>) {
+    let nodes = graph.graph.node_weights().map(|node| node.id.clone()).collect();
+    let edges = graph
+        .graph
+        .edge_references()
+        .map(|edge| {
+            (
+                graph.graph[edge.source()].id.clone(),
+                graph.graph[edge.target()].id.clone(),
+                edge.weight().kind.clone(),
+            )
+        })
+        .collect();
+    (nodes, edges)
+}
+
+#[tokio::test]
+async fn test_incremental_graph_patching() {
+    let files = vec![
+        ("src/main.rs", "fn main() { helper(); cleanup(); }"),
+        ("src/util.rs", "pub fn helper() {}\npub fn cleanup() {}"),
+        ("src/old.rs", "pub fn legacy() {}"),
+    ];
+    let (indexer, store, config, _temp) = setup_indexer_with_files(&files).await;
+    indexer.index_full().await.unwrap();
+    let mut graph = CodeGraph::load_from_store(&store).await.unwrap();
+
+    std::thread::sleep(std::time::Duration::from_millis(1100));
+    let root = &config.project_root;
+    std::fs::write(root.join("src/util.rs"), "pub fn helper() { extra(); }\npub fn extra() {}").unwrap();
+    std::fs::write(root.join("src/main.rs"), "fn main() { helper(); extra(); }").unwrap();
+    std::fs::remove_file(root.join("src/old.rs")).unwrap();
+
+    let stats = indexer.index_incremental().await.unwrap();
+    assert_eq!((stats.files_indexed, stats.files_removed), (2, 1));
+    graph.apply(&stats.changes);
+
+    let reloaded = CodeGraph::load_from_store(&store).await.unwrap();
+    assert_eq!(graph_contents(&graph), graph_contents(&reloaded));
+    assert!(graph.get_node("src/old.rs::legacy").is_none());
+    assert!(graph.get_node("src/util.rs::extra").is_some());
+}
+
 #[tokio::test]
 async fn test_cross_file_resolution() {
     let files = vec![
@@ -123,3 +171,246 @@ async fn test_cross_file_resolution() {
     // (may be 0 resolved if the parser doesn't generate the right unresolved:: stubs for this pattern)
     assert!(stats.unresolved_before >= 0);
 }
+
+#[tokio::test]
+async fn test_java_inheritance_edges() {
+    let files = vec![
+        ("src/User.java", "public class User {\n    public String name() { return \"\"; }\n}\n"),
+        ("src/Auditable.java", "public interface Auditable {\n    void audit();\n}\n"),
+        (
+            "src/Admin.java",
+            "public class Admin extends User implements Auditable {\n    public void audit() { name(); }\n}\n",
+        ),
+    ];
+    let (indexer, store, _config, _temp) = setup_indexer_with_files(&files).await;
+    let stats = indexer.index_full().await.unwrap();
+    assert_eq!(stats.files_indexed, 3);
+
+    let edges = store.get_edges_from("src/Admin.java::Admin").await.unwrap();
+    let has = |target: &str, kind: &str| edges.iter().any(|e| e.target == target && e.kind == kind);
+    assert!(has("src/User.java::User", "inherits"), "{:?}", edges);
+    assert!(has("src/Auditable.java::Auditable", "implements"), "{:?}", edges);
+
+    let edges = store.get_edges_from("src/Admin.java::audit").await.unwrap();
+    assert!(edges.iter().any(|e| e.target == "src/User.java::name" && e.kind == "calls"), "{:?}", edges);
+}
+
+#[tokio::test]
+async fn test_ruby_and_php_inheritance_edges() {
+    let files = vec![
+        ("app/models/application_record.rb", "class ApplicationRecord\n  def save\n  end\nend\n"),
+        ("app/models/post.rb", "class Post < ApplicationRecord\n  def publish\n    save\n  end\nend\n"),
+        ("src/Controller.php", "<?php\nabstract class Controller\n{\n}\n"),
+        ("src/PostController.php", "<?php\nclass PostController extends Controller\n{\n}\n"),
+    ];
+    let (indexer, store, _config, _temp) = setup_indexer_with_files(&files).await;
+    let stats = indexer.index_full().await.unwrap();
+    assert_eq!(stats.files_indexed, 4);
+
+    let edges = store.get_edges_from("app/models/post.rb::Post").await.unwrap();
+    assert!(
+        edges.iter().any(|e| e.target == "app/models/application_record.rb::ApplicationRecord" && e.kind == "inherits"),
+        "{:?}",
+        edges
+    );
+    let edges = store.get_edges_from("src/PostController.php::PostController").await.unwrap();
+    assert!(
+        edges.iter().any(|e| e.target == "src/Controller.php::Controller" && e.kind == "inherits"),
+        "{:?}",
+        edges
+    );
+}
+
+#[tokio::test]
+async fn test_csharp_namespace_qualified_ids() {
+    let files = vec![
+        (
+            "src/Commands/BaseCommand.cs",
+            "namespace App.Commands\n{\n    public abstract class BaseCommand\n    {\n        protected void Log() {}\n    }\n}\n",
+        ),
+        (
+            "src/Commands/CreateUser.cs",
+            "namespace App.Commands;\n\npublic class CreateUser : BaseCommand\n{\n    public void Execute() { Log(); }\n}\n",
+        ),
+        (
+            "src/Commands/DeleteUser.cs",
+            "namespace App.Commands;\n\npublic class DeleteUser : BaseCommand\n{\n    public void Execute() { Log(); }\n}\n",
+        ),
+    ];
+    let (indexer, store, _config, _temp) = setup_indexer_with_files(&files).await;
+    let stats = indexer.index_full().await.unwrap();
+    assert_eq!(stats.files_indexed, 3);
+
+    // Same-named methods get distinct IDs
+    for class in ["CreateUser", "DeleteUser"] {
+        let id = format!("src/Commands/{class}.cs::App.Commands.{class}.Execute");
+        assert!(store.get_node(&id).await.unwrap().is_some(), "missing {id}");
+
+        let edges = store.get_edges_from(&id).await.unwrap();
+        assert!(
+            edges.iter().any(|e| e.target == "src/Commands/BaseCommand.cs::App.Commands.BaseCommand.Log"),
+            "{:?}",
+            edges
+        );
+        let edges = store.get_edges_from(&format!("src/Commands/{class}.cs::App.Commands.{class}")).await.unwrap();
+        let base = "src/Commands/BaseCommand.cs::App.Commands.BaseCommand";
+        assert!(
+            edges.iter().any(|e| e.target == base && e.kind == "inherits"),
+            "{:?}",
+            edges
+        );
+    }
+}
+
+#[tokio::test]
+async fn test_parallel_indexing_many_files() {
+    // More files than one write batch, parsed out of order across workers
+    let sources: Vec<(String, String)> = (0..450)
+        .map(|i| {
+            let body = if i == 0 { "return 0".to_string() } else { format!("return step{}()", i - 1) };
+            (format!("steps/step{i}.py"), format!("def step{i}():\n    {body}\n"))
+        })
+        .collect();
+    let files: Vec<(&str, &str)> = sources.iter().map(|(p, c)| (p.as_str(), c.as_str())).collect();
+
+    let (indexer, store, _config, _temp) = setup_indexer_with_files(&files).await;
+    let stats = indexer.index_full().await.unwrap();
+    assert_eq!(stats.files_indexed, 450);
+    assert_eq!(stats.symbols_found, 450);
+    assert_eq!(store.stats().await.unwrap().symbols, 450);
+
+    // Every call resolves to the definition in the previous file, whichever was written first
+    for i in [1, 200, 449] {
+        let edges = store.get_edges_from(&format!("steps/step{i}.py::step{i}")).await.unwrap();
+        let expected = format!("steps/step{}.py::step{}", i - 1, i - 1);
+        assert!(edges.iter().any(|e| e.target == expected), "step{i} -> {:?}", edges);
+    }
+}
+
+#[tokio::test]
+async fn test_index_progress_and_cancellation() {
+    let sources: Vec<(String, String)> =
+        (0..20).map(|i| (format!("mods/mod{i}.py"), format!("def f{i}():\n    return {i}\n"))).collect();
+    let files: Vec<(&str, &str)> = sources.iter().map(|(p, c)| (p.as_str(), c.as_str())).collect();
+    let (indexer, store, _config, _temp) = setup_indexer_with_files(&files).await;
+
+    // Cancel as soon as the first progress report comes in, while the tree is being walked
+    let cancelled = AtomicBool::new(false);
+    let messages = Mutex::new(Vec::new());
+    let on_progress = |progress: &IndexProgress| {
+        messages.lock().unwrap().push(progress.message());
+        cancelled.store(true, Ordering::Relaxed);
+    };
+    let stats = indexer.index_with(true, &on_progress, &cancelled).await.unwrap();
+    assert!(stats.cancelled);
+    assert_eq!(stats.files_indexed, 0);
+    assert_eq!(*messages.lock().unwrap(), vec!["Scanned 1 files"]);
+    assert_eq!(store.stats().await.unwrap().symbols, 0);
+
+    // The next run picks up everything
+    let stats = indexer.index_incremental().await.unwrap();
+    assert!(!stats.cancelled);
+    assert_eq!(stats.files_indexed, 20);
+
+    let progress = IndexProgress {
+        files_scanned: 20,
+        files_to_index: Some(20),
+        files_indexed: 5,
+        eta: Some(std::time::Duration::from_millis(2500)),
+    };
+    assert_eq!(progress.message(), "Indexed 5/20 files, ~2s left");
+}
+
+#[tokio::test]
+async fn test_include_globs_limit_indexing() {
+    let files = vec![
+        ("services/auth/login.py", "def login(user):\n    return user\n"),
+        ("services/billing/charge.py", "def charge(amount):\n    return amount\n"),
+        ("libs/util.rs", "pub fn helper() {}"),
+        (
+            ".codegraph/config.toml",
+            "[indexing]\ninclude = [\"services/auth/**\", \"libs/**\"]\n",
+        ),
+    ];
+
+    let (indexer, store, _config, _temp) = setup_indexer_with_files(&files).await;
+
+    let stats = indexer.index_full().await.unwrap();
+    assert_eq!(stats.files_scanned, 2);
+
+    let indexed = store.list_indexed_files().await.unwrap();
+    assert!(indexed.contains(&"services/auth/login.py".to_string()));
+    assert!(indexed.contains(&"libs/util.rs".to_string()));
+    assert!(!indexed.iter().any(|f| f.starts_with("services/billing")));
+}
+
+#[tokio::test]
+async fn test_codegraphignore_excludes_files() {
+    let files = vec![
+        ("src/main.rs", "fn main() {}"),
+        ("src/generated/api.rs", "pub fn generated() {}"),
+        ("src/generated/models.rs", "pub struct Model;"),
+        ("scripts/build.py", "def build():\n    pass\n"),
+        ("scripts/deploy_test.py", "def test_deploy():\n    pass\n"),
+        (".codegraphignore", "# generated code\nsrc/generated/\n*_test.py\n"),
+    ];
+
+    let (indexer, store, _config, _temp) = setup_indexer_with_files(&files).await;
+
+    let stats = indexer.index_full().await.unwrap();
+    assert_eq!(stats.files_scanned, 2);
+
+    let mut indexed = store.list_indexed_files().await.unwrap();
+    indexed.sort();
+    assert_eq!(indexed, vec!["scripts/build.py", "src/main.rs"]);
+}
+
+#[tokio::test]
+async fn test_watcher_reindexes_changed_files() {
+    let files = vec![
+        (".codegraph/config.toml", "[indexing]\nwatch_debounce_ms = 50\n"),
+        ("src/lib.rs", "fn existing() {}\n"),
+        ("target/gen.rs", "fn generated() {}\n"),
+    ];
+    let (indexer, store, _config, temp) = setup_indexer_with_files(&files).await;
+    indexer.index_full().await.unwrap();
+
+    let graph = Arc::new(tokio::sync::RwLock::new(CodeGraph::load_from_store(&store).await.unwrap()));
+    let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel();
+    let _watcher = indexer
+        .watch(graph.clone(), move |stats| {
+            let tx = tx.clone();
+            async move {
+                let _ = tx.send(stats);
+            }
+        })
+        .unwrap();
+
+    // Batches can split a write across events, so wait until the graph shows the change
+    async fn wait_until(
+        rx: &mut tokio::sync::mpsc::UnboundedReceiver<codegraph::code::indexer::IndexStats>,
+        graph: &tokio::sync::RwLock<CodeGraph>,
+        done: impl Fn(&CodeGraph) -> bool,
+    ) {
+        tokio::time::timeout(std::time::Duration::from_secs(10), async {
+            while !done(&*graph.read().await) {
+                rx.recv().await.unwrap();
+            }
+        })
+        .await
+        .expect("watcher did not pick up the change");
+    }
+
+    std::fs::write(temp.path().join("target/other.rs"), "fn ignored() {}\n").unwrap();
+    std::fs::write(temp.path().join("src/new.rs"), "fn added() {}\n").unwrap();
+    wait_until(&mut rx, &graph, |g| g.get_node("src/new.rs::added").is_some()).await;
+    assert!(store.get_file_meta("src/new.rs").await.unwrap().is_some());
+
+    std::fs::remove_file(temp.path().join("src/lib.rs")).unwrap();
+    wait_until(&mut rx, &graph, |g| g.get_node("src/lib.rs::existing").is_none()).await;
+    assert!(store.get_file_meta("src/lib.rs").await.unwrap().is_none());
+
+    // Excluded directories are neither watched nor indexed
+    let indexed = store.list_indexed_files().await.unwrap();
+    assert!(indexed.iter().all(|f| !f.starts_with("target/")), "{:?}", indexed);
+}
@@ -18,7 +18,7 @@ async fn setup_learning_stores() -> (Arc<PatternStore>, Arc<FailureStore>, Arc<L
         codegraph_dir: temp_path.join(".codegraph"),
         store_db_path: temp_path.join("cache/store.db"),
         learning_db_path: temp_path.join(".codegraph/learning.db"),
-        settings: codegraph::config::ConfigFile::default(),
+        settings: codegraph::config::ConfigFile::default().into(),
     };
     config.ensure_dirs().unwrap();
 
@@ -11,7 +11,8 @@ use codegraph::session::SessionManager;
 use codegraph::skill::distill::ManualInstructionStore;
 use codegraph::store::{CodeGraph, Store};
 use serde_json::{json, Value};
-use std::sync::{Arc, RwLock};
+use std::sync::Arc;
+use tokio::sync::RwLock;
 use tempfile::TempDir;
 
 async fn setup_server_with_project() -> (Server, TempDir) {
@@ -64,7 +65,7 @@ pub fn multiply(a: i32, b: i32) -> i32 {
     let niche_store = Arc::new(NicheStore::new(Arc::new(store.learning_db.clone())));
     let manual_instruction_store =
         Arc::new(ManualInstructionStore::new(Arc::new(store.learning_db.clone())));
-    let cross_language_inferrer = Arc::new(CrossLanguageInferrer::new(store.clone()));
+    let cross_language_inferrer = Arc::new(CrossLanguageInferrer::new(store.clone(), config.clone()));
 
     let server = Server::with_dependencies(
         store,
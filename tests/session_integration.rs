@@ -1,7 +1,8 @@
 use codegraph::config::Config;
 use codegraph::session::{SessionManager, TaskStatus};
 use codegraph::store::{CodeGraph, Store};
-use std::sync::{Arc, RwLock};
+use std::sync::Arc;
+use tokio::sync::RwLock;
 use tempfile::TempDir;
 
 async fn setup_session() -> (Arc<SessionManager>, TempDir) {
@@ -14,7 +15,7 @@ async fn setup_session() -> (Arc<SessionManager>, TempDir) {
         codegraph_dir: temp_path.join(".codegraph"),
         store_db_path: temp_path.join("cache/store.db"),
         learning_db_path: temp_path.join(".codegraph/learning.db"),
-        settings: codegraph::config::ConfigFile::default(),
+        settings: codegraph::config::ConfigFile::default().into(),
     };
     config.ensure_dirs().unwrap();
 
@@ -34,6 +35,7 @@ async fn test_session_full_lifecycle() {
     // 1. Start session with items
     let session = manager
         .start_session(
+            None,
             "Implement user auth",
             &[
                 "Design API".to_string(),
@@ -120,7 +122,7 @@ async fn test_session_add_items_dynamically() {
     let (manager, _temp) = setup_session().await;
 
     manager
-        .start_session("Build feature", &["Step 1".to_string()])
+        .start_session(None, "Build feature", &["Step 1".to_string()])
         .await
         .unwrap();
 